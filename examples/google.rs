@@ -161,7 +161,7 @@ fn main() {
         let code_pair = url
             .query_pairs()
             .find(|pair| {
-                let &(ref key, _) = pair;
+                let (key, _) = pair;
                 key == "code"
             })
             .unwrap();
@@ -172,7 +172,7 @@ fn main() {
         let state_pair = url
             .query_pairs()
             .find(|pair| {
-                let &(ref key, _) = pair;
+                let (key, _) = pair;
                 key == "state"
             })
             .unwrap();
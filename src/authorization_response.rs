@@ -0,0 +1,606 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use oauth2::{AuthorizationCode, CsrfToken};
+use serde_json::{Map, Value};
+use thiserror::Error;
+use url::{form_urlencoded, Url};
+
+use crate::core::CoreAuthErrorResponseType;
+use crate::types::helpers::{deserialize_string_or_vec_opt, serde_utc_seconds_opt};
+use crate::verification::{AudiencesClaim, IssuerClaim};
+use crate::{
+    AdditionalClaims, Audience, ClaimsVerificationError, GenderClaim, IdToken, IssuerUrl,
+    JarmVerifier, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
+    JwsSigningAlgorithm,
+};
+
+///
+/// Successful authorization response returned by the OpenID Connect Provider, either by
+/// redirecting the user agent to the `redirect_uri` or (when requesting
+/// [`core::CoreResponseMode::FormPost`](crate::core::CoreResponseMode::FormPost)) by submitting
+/// an HTML form to it.
+///
+/// The returned `state` must be compared with the [`CsrfToken`] returned by
+/// [`AuthorizationRequest::url`](crate::AuthorizationRequest::url) before exchanging the `code`
+/// or trusting the `id_token`. Any ID token must be verified using
+/// [`IdToken::claims`](crate::IdToken::claims).
+///
+#[derive(Clone, Debug)]
+pub struct AuthorizationResponse<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    code: Option<AuthorizationCode>,
+    state: Option<CsrfToken>,
+    id_token: Option<IdToken<AC, GC, JE, JS, JT>>,
+}
+impl<AC, GC, JE, JS, JT> AuthorizationResponse<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Parses an authorization response from the URL to which the user agent was redirected.
+    ///
+    /// Parameters are read from the URL fragment if one is present (as is the default for the
+    /// implicit and hybrid flows), and from the query string otherwise.
+    ///
+    pub fn from_redirect_url(url: &Url) -> Result<Self, AuthorizationResponseError> {
+        match url.fragment() {
+            Some(fragment) if !fragment.is_empty() => Self::from_params(fragment.as_bytes()),
+            _ => Self::from_params(url.query().unwrap_or_default().as_bytes()),
+        }
+    }
+
+    ///
+    /// Parses an authorization response from the `application/x-www-form-urlencoded` body
+    /// submitted to the `redirect_uri` when using the form post response mode, as described in
+    /// [OAuth 2.0 Form Post Response Mode](
+    ///     https://openid.net/specs/oauth-v2-form-post-response-mode-1_0.html).
+    ///
+    pub fn from_form_post(body: &[u8]) -> Result<Self, AuthorizationResponseError> {
+        Self::from_params(body)
+    }
+
+    ///
+    /// Verifies and parses a JWT Secured Authorization Response Mode (JARM) response, which is
+    /// returned in the `response` parameter when requesting one of the JARM response modes
+    /// (e.g., [`core::CoreResponseMode::Jwt`](crate::core::CoreResponseMode::Jwt)).
+    ///
+    /// The response JWT's signature and its `iss`, `aud`, and `exp` claims are validated using
+    /// `verifier` (see [`Client::jarm_verifier`](crate::Client::jarm_verifier)) before the
+    /// contained authorization response parameters are parsed.
+    ///
+    pub fn from_jarm<JU, K>(
+        response: &str,
+        verifier: &JarmVerifier<JE, JS, JT, JU, K>,
+    ) -> Result<Self, AuthorizationResponseError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        let params = verifier
+            .verified_params(response)
+            .map_err(AuthorizationResponseError::Jarm)?
+            .into_iter()
+            .map(|(name, value)| match value {
+                Value::String(value) => (name, value),
+                other => (name, other.to_string()),
+            })
+            .collect();
+        Self::from_param_map(params)
+    }
+
+    fn from_params(encoded: &[u8]) -> Result<Self, AuthorizationResponseError> {
+        Self::from_param_map(parse_params(encoded)?)
+    }
+
+    fn from_param_map(
+        mut params: HashMap<String, String>,
+    ) -> Result<Self, AuthorizationResponseError> {
+        if let Some(error) = params.remove("error") {
+            return Err(AuthorizationResponseError::Provider(
+                AuthorizationErrorResponse {
+                    error: serde_plain::from_str(&error)
+                        .unwrap_or(CoreAuthErrorResponseType::Extension(error)),
+                    error_description: params.remove("error_description"),
+                    error_uri: params.remove("error_uri"),
+                    state: params.remove("state").map(CsrfToken::new),
+                },
+            ));
+        }
+
+        Ok(Self {
+            code: params.remove("code").map(AuthorizationCode::new),
+            state: params.remove("state").map(CsrfToken::new),
+            id_token: params
+                .remove("id_token")
+                .map(|id_token| id_token.parse())
+                .transpose()
+                .map_err(AuthorizationResponseError::InvalidIdToken)?,
+        })
+    }
+
+    ///
+    /// Returns the authorization code, if the requested response type includes `code`.
+    ///
+    pub fn code(&self) -> Option<&AuthorizationCode> {
+        self.code.as_ref()
+    }
+
+    ///
+    /// Returns the `state` parameter, which must match the [`CsrfToken`] sent in the
+    /// authorization request.
+    ///
+    pub fn state(&self) -> Option<&CsrfToken> {
+        self.state.as_ref()
+    }
+
+    ///
+    /// Returns the unverified ID token, if the requested response type includes `id_token`.
+    ///
+    pub fn id_token(&self) -> Option<&IdToken<AC, GC, JE, JS, JT>> {
+        self.id_token.as_ref()
+    }
+}
+
+///
+/// Error response returned by the OpenID Connect Provider's authorization endpoint, as described
+/// in [Section 3.1.2.6 of OpenID Connect Core 1.0](
+/// https://openid.net/specs/openid-connect-core-1_0.html#AuthError).
+///
+#[derive(Clone, Debug)]
+pub struct AuthorizationErrorResponse {
+    error: CoreAuthErrorResponseType,
+    error_description: Option<String>,
+    error_uri: Option<String>,
+    state: Option<CsrfToken>,
+}
+impl AuthorizationErrorResponse {
+    ///
+    /// Returns the error code returned in the `error` parameter. Codes not defined by any of the
+    /// supported specifications are returned as [`CoreAuthErrorResponseType::Extension`].
+    ///
+    pub fn error(&self) -> &CoreAuthErrorResponseType {
+        &self.error
+    }
+
+    ///
+    /// Returns the human-readable description returned in the `error_description` parameter.
+    ///
+    pub fn error_description(&self) -> Option<&String> {
+        self.error_description.as_ref()
+    }
+
+    ///
+    /// Returns the URI of a web page with information about the error, returned in the
+    /// `error_uri` parameter.
+    ///
+    pub fn error_uri(&self) -> Option<&String> {
+        self.error_uri.as_ref()
+    }
+
+    ///
+    /// Returns the `state` parameter, which should match the [`CsrfToken`] sent in the
+    /// authorization request.
+    ///
+    pub fn state(&self) -> Option<&CsrfToken> {
+        self.state.as_ref()
+    }
+
+    ///
+    /// Returns whether the request failed because it requires the end-user to interact with the
+    /// OpenID Connect Provider (e.g., after sending `prompt=none`), in which case the request
+    /// may be retried with an interactive prompt such as
+    /// [`core::CoreAuthPrompt::Login`](crate::core::CoreAuthPrompt::Login).
+    ///
+    pub fn requires_interaction(&self) -> bool {
+        matches!(
+            self.error,
+            CoreAuthErrorResponseType::AccountSelectionRequired
+                | CoreAuthErrorResponseType::ConsentRequired
+                | CoreAuthErrorResponseType::InteractionRequired
+                | CoreAuthErrorResponseType::LoginRequired
+        )
+    }
+}
+
+// Parameters must not be included more than once, as described in Section 3.1 of RFC 6749.
+fn parse_params(encoded: &[u8]) -> Result<HashMap<String, String>, AuthorizationResponseError> {
+    let mut params = HashMap::new();
+    for (name, value) in form_urlencoded::parse(encoded) {
+        if params
+            .insert(name.to_string(), value.into_owned())
+            .is_some()
+        {
+            return Err(AuthorizationResponseError::DuplicateParameter(
+                name.into_owned(),
+            ));
+        }
+    }
+    Ok(params)
+}
+
+// Claims contained in a JARM response JWT.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct JarmClaims {
+    #[serde(rename = "iss", skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<IssuerUrl>,
+    #[serde(
+        default,
+        rename = "aud",
+        deserialize_with = "deserialize_string_or_vec_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub audiences: Option<Vec<Audience>>,
+    #[serde(
+        default,
+        rename = "exp",
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    pub expiration: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub params: Map<String, Value>,
+}
+impl AudiencesClaim for JarmClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        self.audiences.as_ref()
+    }
+}
+impl IssuerClaim for JarmClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        self.issuer.as_ref()
+    }
+}
+
+///
+/// Error parsing an [`AuthorizationResponse`].
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum AuthorizationResponseError {
+    ///
+    /// The same parameter was included more than once.
+    ///
+    #[error("Duplicate parameter `{0}`")]
+    DuplicateParameter(String),
+    ///
+    /// The `id_token` parameter is not a valid JWT.
+    ///
+    #[error("Failed to parse ID token")]
+    InvalidIdToken(#[source] serde_json::Error),
+    ///
+    /// Failed to verify the JARM response JWT.
+    ///
+    #[error("Failed to verify JARM response")]
+    Jarm(#[source] ClaimsVerificationError),
+    ///
+    /// The OpenID Connect Provider returned an error response.
+    ///
+    #[error("Server returned error `{}`", .0.error().as_ref())]
+    Provider(AuthorizationErrorResponse),
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use oauth2::{AuthUrl, AuthorizationCode, ClientId, CsrfToken};
+    use serde_json::json;
+    use url::Url;
+
+    use crate::claim_sources::tests::claim_source_jwt;
+    use crate::core::{
+        CoreAuthErrorResponseType, CoreAuthorizationResponse, CoreClient, CoreJsonWebKey,
+    };
+    use crate::jwt::tests::TEST_RSA_PUB_KEY;
+    use crate::{
+        AuthorizationErrorResponse, AuthorizationResponseError, ClaimsVerificationError, IssuerUrl,
+        JsonWebKeySet,
+    };
+
+    const TEST_ID_TOKEN: &str = "eyJhbGciOiJSUzI1NiJ9.eyJpc3MiOiJodHRwczovL3NlcnZlci5leGFtcGxlLmNv\
+                                 bSIsImF1ZCI6WyJteV9jbGllbnQiXSwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1\
+                                 NDQ5Mjg1NDksInN1YiI6InN1YmplY3QifQ.c2lnbmF0dXJl";
+
+    #[test]
+    fn test_form_post_code_response() {
+        let body = format!(
+            "code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj&id_token={}",
+            TEST_ID_TOKEN
+        );
+        let response = CoreAuthorizationResponse::from_form_post(body.as_bytes()).unwrap();
+        assert_eq!(response.code().unwrap().secret(), "SplxlOBeZQQYbYS6WxSbIA");
+        assert_eq!(response.state().unwrap().secret(), "af0ifjsldkj");
+        assert_eq!(response.id_token().unwrap().to_string(), TEST_ID_TOKEN);
+
+        // Redirects produce the same response as form posts.
+        for redirect_url in [
+            format!("https://client.example.org/cb#{}", body),
+            format!("https://client.example.org/cb?{}", body),
+        ] {
+            let redirect_response =
+                CoreAuthorizationResponse::from_redirect_url(&Url::parse(&redirect_url).unwrap())
+                    .unwrap();
+            assert_eq!(
+                redirect_response.code().map(AuthorizationCode::secret),
+                response.code().map(AuthorizationCode::secret)
+            );
+            assert_eq!(
+                redirect_response.state().map(CsrfToken::secret),
+                response.state().map(CsrfToken::secret)
+            );
+            assert_eq!(redirect_response.id_token(), response.id_token());
+        }
+
+        match CoreAuthorizationResponse::from_form_post(
+            b"code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj&state=other",
+        ) {
+            Err(AuthorizationResponseError::DuplicateParameter(name)) => assert_eq!(name, "state"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(matches!(
+            CoreAuthorizationResponse::from_form_post(b"id_token=not-a-jwt"),
+            Err(AuthorizationResponseError::InvalidIdToken(_))
+        ));
+    }
+
+    fn error_response(encoded: &[u8]) -> AuthorizationErrorResponse {
+        match CoreAuthorizationResponse::from_form_post(encoded) {
+            Err(AuthorizationResponseError::Provider(error_response)) => error_response,
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_form_post_error_response() {
+        let error_response = error_response(
+            b"error=login_required&error_description=End-User+is+not+logged+in&\
+              error_uri=https%3A%2F%2Fserver.example.com%2Ferrors%2Flogin&state=af0ifjsldkj",
+        );
+        assert_eq!(
+            *error_response.error(),
+            CoreAuthErrorResponseType::LoginRequired
+        );
+        assert_eq!(
+            error_response.error_description(),
+            Some(&"End-User is not logged in".to_string())
+        );
+        assert_eq!(
+            error_response.error_uri(),
+            Some(&"https://server.example.com/errors/login".to_string())
+        );
+        assert_eq!(
+            error_response.state().map(CsrfToken::secret),
+            Some(&"af0ifjsldkj".to_string())
+        );
+        assert!(error_response.requires_interaction());
+    }
+
+    #[test]
+    fn test_authorization_error_codes() {
+        for (code, expected, requires_interaction) in [
+            (
+                "access_denied",
+                CoreAuthErrorResponseType::AccessDenied,
+                false,
+            ),
+            (
+                "account_selection_required",
+                CoreAuthErrorResponseType::AccountSelectionRequired,
+                true,
+            ),
+            (
+                "consent_required",
+                CoreAuthErrorResponseType::ConsentRequired,
+                true,
+            ),
+            (
+                "interaction_required",
+                CoreAuthErrorResponseType::InteractionRequired,
+                true,
+            ),
+            (
+                "invalid_request",
+                CoreAuthErrorResponseType::InvalidRequest,
+                false,
+            ),
+            (
+                "invalid_request_object",
+                CoreAuthErrorResponseType::InvalidRequestObject,
+                false,
+            ),
+            (
+                "invalid_request_uri",
+                CoreAuthErrorResponseType::InvalidRequestUri,
+                false,
+            ),
+            (
+                "invalid_scope",
+                CoreAuthErrorResponseType::InvalidScope,
+                false,
+            ),
+            (
+                "login_required",
+                CoreAuthErrorResponseType::LoginRequired,
+                true,
+            ),
+            (
+                "registration_not_supported",
+                CoreAuthErrorResponseType::RegistrationNotSupported,
+                false,
+            ),
+            (
+                "request_not_supported",
+                CoreAuthErrorResponseType::RequestNotSupported,
+                false,
+            ),
+            (
+                "request_uri_not_supported",
+                CoreAuthErrorResponseType::RequestUriNotSupported,
+                false,
+            ),
+            (
+                "server_error",
+                CoreAuthErrorResponseType::ServerError,
+                false,
+            ),
+            (
+                "temporarily_unavailable",
+                CoreAuthErrorResponseType::TemporarilyUnavailable,
+                false,
+            ),
+            (
+                "unauthorized_client",
+                CoreAuthErrorResponseType::UnauthorizedClient,
+                false,
+            ),
+            (
+                "unsupported_response_type",
+                CoreAuthErrorResponseType::UnsupportedResponseType,
+                false,
+            ),
+            (
+                "custom_error",
+                CoreAuthErrorResponseType::Extension("custom_error".to_string()),
+                false,
+            ),
+        ] {
+            let error_response = error_response(format!("error={}", code).as_bytes());
+            assert_eq!(*error_response.error(), expected);
+            assert_eq!(error_response.error().as_ref(), code);
+            assert_eq!(error_response.requires_interaction(), requires_interaction);
+            assert_eq!(error_response.error_description(), None);
+            assert!(error_response.state().is_none());
+        }
+    }
+
+    fn new_jarm_client() -> CoreClient {
+        CoreClient::new(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            None,
+            IssuerUrl::new("https://accounts.example.com".to_string()).unwrap(),
+            AuthUrl::new("https://accounts.example.com/authorize".to_string()).unwrap(),
+            None,
+            None,
+            JsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        )
+    }
+
+    #[test]
+    fn test_jarm_response() {
+        let client = new_jarm_client();
+        let verifier = client
+            .jarm_verifier()
+            .set_time_fn(|| Utc.timestamp_opt(1311281970, 0).single().unwrap());
+
+        let response = claim_source_jwt(
+            "https://accounts.example.com",
+            json!({
+                "aud": "s6BhdRkqt3",
+                "exp": 1311281980,
+                "code": "PyyFaux2o7Q0YfXBU32jhw.5FXSQpvr8akv9CeRDSd0QA",
+                "state": "S8NJ7uqk5fY4EjNvP_G_FtyJu6pUsvH9jsYni9dMAJw",
+            }),
+        );
+        let authorization_response =
+            CoreAuthorizationResponse::from_jarm(&response, &verifier).unwrap();
+        assert_eq!(
+            authorization_response.code().unwrap().secret(),
+            "PyyFaux2o7Q0YfXBU32jhw.5FXSQpvr8akv9CeRDSd0QA"
+        );
+        assert_eq!(
+            authorization_response.state().unwrap().secret(),
+            "S8NJ7uqk5fY4EjNvP_G_FtyJu6pUsvH9jsYni9dMAJw"
+        );
+        assert!(authorization_response.id_token().is_none());
+
+        // Error responses are also returned via JARM.
+        let response = claim_source_jwt(
+            "https://accounts.example.com",
+            json!({
+                "aud": "s6BhdRkqt3",
+                "exp": 1311281980,
+                "error": "access_denied",
+                "state": "S8NJ7uqk5fY4EjNvP_G_FtyJu6pUsvH9jsYni9dMAJw",
+            }),
+        );
+        match CoreAuthorizationResponse::from_jarm(&response, &verifier) {
+            Err(AuthorizationResponseError::Provider(error_response)) => {
+                assert_eq!(
+                    *error_response.error(),
+                    CoreAuthErrorResponseType::AccessDenied
+                );
+                assert_eq!(
+                    error_response.state().map(CsrfToken::secret),
+                    Some(&"S8NJ7uqk5fY4EjNvP_G_FtyJu6pUsvH9jsYni9dMAJw".to_string())
+                );
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_jarm_response_rejected() {
+        let client = new_jarm_client();
+        let verifier = client
+            .jarm_verifier()
+            .set_time_fn(|| Utc.timestamp_opt(1311281970, 0).single().unwrap());
+
+        // Expired by the default clock skew leeway.
+        let expired = claim_source_jwt(
+            "https://accounts.example.com",
+            json!({"aud": "s6BhdRkqt3", "exp": 1311281910, "code": "abc"}),
+        );
+        assert!(matches!(
+            CoreAuthorizationResponse::from_jarm(&expired, &verifier),
+            Err(AuthorizationResponseError::Jarm(
+                ClaimsVerificationError::Expired(_)
+            ))
+        ));
+
+        let missing_expiration = claim_source_jwt(
+            "https://accounts.example.com",
+            json!({"aud": "s6BhdRkqt3", "code": "abc"}),
+        );
+        assert!(matches!(
+            CoreAuthorizationResponse::from_jarm(&missing_expiration, &verifier),
+            Err(AuthorizationResponseError::Jarm(
+                ClaimsVerificationError::Expired(_)
+            ))
+        ));
+
+        let wrong_audience = claim_source_jwt(
+            "https://accounts.example.com",
+            json!({"aud": "other_client", "exp": 1311281980, "code": "abc"}),
+        );
+        assert!(matches!(
+            CoreAuthorizationResponse::from_jarm(&wrong_audience, &verifier),
+            Err(AuthorizationResponseError::Jarm(
+                ClaimsVerificationError::InvalidAudience(_)
+            ))
+        ));
+
+        let wrong_issuer = claim_source_jwt(
+            "https://attacker.example.com",
+            json!({"aud": "s6BhdRkqt3", "exp": 1311281980, "code": "abc"}),
+        );
+        assert!(matches!(
+            CoreAuthorizationResponse::from_jarm(&wrong_issuer, &verifier),
+            Err(AuthorizationResponseError::Jarm(
+                ClaimsVerificationError::InvalidIssuer(_)
+            ))
+        ));
+    }
+}
@@ -0,0 +1,656 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
+use http::method::Method;
+use http::status::StatusCode;
+use oauth2::{
+    AuthType, ClientId, ClientSecret, DeviceCodeErrorResponse, DeviceCodeErrorResponseType,
+    ErrorResponse, RequestTokenError, Scope, TokenType, TokenUrl,
+};
+use serde::de::DeserializeOwned;
+use url::{form_urlencoded, Url};
+
+use crate::http_utils::{
+    auth_basic, check_content_type, MIME_TYPE_FORM_URLENCODED, MIME_TYPE_JSON,
+};
+use crate::{
+    AdditionalClaims, AsyncHttpClient, AuthReqId, BackchannelAuthenticationUrl, GenderClaim,
+    HttpRequest, HttpResponse, IdToken, JsonWebKeyType, JweContentEncryptionAlgorithm,
+    JwsSigningAlgorithm, LoginHint, LoginHintToken, SyncHttpClient,
+};
+
+const CIBA_GRANT_TYPE: &str = "urn:openid:params:grant-type:ciba";
+
+///
+/// A request to the backchannel authentication endpoint, which initiates Client-Initiated
+/// Backchannel Authentication (CIBA) as described in
+/// [Section 7 of OpenID Connect CIBA Core 1.0](
+/// https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#auth_request).
+///
+/// Exactly one of [`set_login_hint`](Self::set_login_hint),
+/// [`set_login_hint_token`](Self::set_login_hint_token), or
+/// [`set_id_token_hint`](Self::set_id_token_hint) should be used to identify the end-user.
+///
+/// See [`Client::backchannel_authentication_request`](crate::Client::backchannel_authentication_request).
+///
+#[derive(Debug)]
+pub struct BackchannelAuthenticationRequest<'a, TE>
+where
+    TE: ErrorResponse,
+{
+    pub(crate) url: &'a BackchannelAuthenticationUrl,
+    pub(crate) client: EndpointClient<'a>,
+    pub(crate) scopes: Vec<Scope>,
+    pub(crate) params: Vec<(String, String)>,
+    pub(crate) _phantom: PhantomData<TE>,
+}
+impl<'a, TE> BackchannelAuthenticationRequest<'a, TE>
+where
+    TE: ErrorResponse + 'static,
+{
+    ///
+    /// Appends a new scope to the request.
+    ///
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        self.scopes.push(scope);
+        self
+    }
+
+    ///
+    /// Identifies the end-user to authenticate using a login identifier such as an email
+    /// address or phone number.
+    ///
+    pub fn set_login_hint(self, login_hint: &LoginHint) -> Self {
+        self.add_extra_param("login_hint", login_hint.secret().clone())
+    }
+
+    ///
+    /// Identifies the end-user to authenticate using a token whose format is specific to the
+    /// OpenID Connect Provider.
+    ///
+    pub fn set_login_hint_token(self, login_hint_token: &LoginHintToken) -> Self {
+        self.add_extra_param("login_hint_token", login_hint_token.secret().clone())
+    }
+
+    ///
+    /// Identifies the end-user to authenticate using an ID token previously issued by the
+    /// OpenID Connect Provider.
+    ///
+    pub fn set_id_token_hint<AC, GC, JE, JS, JT>(
+        self,
+        id_token_hint: &IdToken<AC, GC, JE, JS, JT>,
+    ) -> Self
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+    {
+        self.add_extra_param("id_token_hint", id_token_hint.to_string())
+    }
+
+    ///
+    /// Appends an extra param to the request (e.g., `binding_message` or `acr_values`).
+    ///
+    pub fn add_extra_param<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.params.push((name.into(), value.into()));
+        self
+    }
+
+    ///
+    /// Submits this request to the backchannel authentication endpoint using the specified
+    /// synchronous HTTP client.
+    ///
+    pub fn request<HC, RE>(
+        self,
+        http_client: HC,
+    ) -> Result<BackchannelAuthenticationResponse, RequestTokenError<RE, TE>>
+    where
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        http_client
+            .call(self.prepare_request()?)
+            .map_err(RequestTokenError::Request)
+            .and_then(endpoint_response)
+    }
+
+    ///
+    /// Submits this request to the backchannel authentication endpoint using the specified
+    /// asynchronous HTTP client.
+    ///
+    pub async fn request_async<C, F, RE>(
+        self,
+        http_client: C,
+    ) -> Result<BackchannelAuthenticationResponse, RequestTokenError<RE, TE>>
+    where
+        C: AsyncHttpClient<Error = RE, Future = F>,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        let http_request = self.prepare_request()?;
+        let http_response = http_client
+            .call(http_request)
+            .await
+            .map_err(RequestTokenError::Request)?;
+        endpoint_response(http_response)
+    }
+
+    fn prepare_request<RE>(&self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        let scope = self
+            .scopes
+            .iter()
+            .map(|scope| scope.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut params = vec![("scope".to_string(), scope)];
+        params.extend(self.params.iter().cloned());
+        self.client.prepare_request(self.url.url(), &params)
+    }
+}
+
+///
+/// Successful response from the backchannel authentication endpoint.
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BackchannelAuthenticationResponse {
+    auth_req_id: AuthReqId,
+    expires_in: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interval: Option<u64>,
+}
+impl BackchannelAuthenticationResponse {
+    ///
+    /// Instantiates a new backchannel authentication response.
+    ///
+    pub fn new(auth_req_id: AuthReqId, expires_in: Duration, interval: Option<Duration>) -> Self {
+        Self {
+            auth_req_id,
+            expires_in: expires_in.as_secs(),
+            interval: interval.map(|interval| interval.as_secs()),
+        }
+    }
+
+    ///
+    /// Returns the identifier of this authentication request, which is used to poll the token
+    /// endpoint.
+    ///
+    pub fn auth_req_id(&self) -> &AuthReqId {
+        &self.auth_req_id
+    }
+
+    ///
+    /// Returns the lifetime of the `auth_req_id`.
+    ///
+    pub fn expires_in(&self) -> Duration {
+        Duration::from_secs(self.expires_in)
+    }
+
+    ///
+    /// Returns the minimum amount of time to wait between polling requests to the token
+    /// endpoint, which defaults to 5 seconds if not specified by the provider.
+    ///
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval.unwrap_or(5))
+    }
+}
+
+///
+/// A request that polls the token endpoint until the end-user completes (or denies) a
+/// Client-Initiated Backchannel Authentication request, as described in
+/// [Section 10.1 of OpenID Connect CIBA Core 1.0](
+/// https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#token_request).
+///
+/// `authorization_pending` errors are retried after the polling interval, and `slow_down` errors
+/// increase the interval by 5 seconds. The returned ID token should be verified as usual using
+/// [`Client::id_token_verifier`](crate::Client::id_token_verifier); no nonce is sent in this flow.
+///
+/// See [`Client::exchange_backchannel_authentication`](crate::Client::exchange_backchannel_authentication).
+///
+#[derive(Debug)]
+pub struct BackchannelTokenRequest<'a, TR, TT> {
+    pub(crate) token_url: Option<&'a TokenUrl>,
+    pub(crate) client: EndpointClient<'a>,
+    pub(crate) response: &'a BackchannelAuthenticationResponse,
+    pub(crate) params: Vec<(String, String)>,
+    pub(crate) _phantom: PhantomData<(TR, TT)>,
+}
+impl<'a, TR, TT> BackchannelTokenRequest<'a, TR, TT>
+where
+    TR: oauth2::TokenResponse<TT> + DeserializeOwned,
+    TT: TokenType,
+{
+    ///
+    /// Polls the token endpoint using the specified synchronous HTTP client and sleep function
+    /// until a token is issued, an error is returned, or the request expires.
+    ///
+    /// If `timeout` is provided, polling stops after the shorter of `timeout` and the lifetime of
+    /// the `auth_req_id`.
+    ///
+    pub fn request<HC, S, RE>(
+        self,
+        http_client: HC,
+        sleep_fn: S,
+        timeout: Option<Duration>,
+    ) -> Result<TR, RequestTokenError<RE, DeviceCodeErrorResponse>>
+    where
+        HC: SyncHttpClient<Error = RE>,
+        S: Fn(Duration),
+        RE: std::error::Error + 'static,
+    {
+        let deadline = self.deadline(timeout);
+        let mut interval = self.response.interval();
+        loop {
+            if Utc::now() > deadline {
+                return Err(Self::expired());
+            }
+            let http_response = http_client.call(self.prepare_request()?);
+            match Self::poll_result(http_response, interval) {
+                PollResult::Continue(new_interval) => interval = new_interval,
+                PollResult::Done(result) => return result,
+            }
+            sleep_fn(interval);
+        }
+    }
+
+    ///
+    /// Polls the token endpoint using the specified asynchronous HTTP client and sleep function
+    /// until a token is issued, an error is returned, or the request expires.
+    ///
+    pub async fn request_async<C, F, S, SF, RE>(
+        self,
+        http_client: C,
+        sleep_fn: S,
+        timeout: Option<Duration>,
+    ) -> Result<TR, RequestTokenError<RE, DeviceCodeErrorResponse>>
+    where
+        C: AsyncHttpClient<Error = RE, Future = F>,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        S: Fn(Duration) -> SF,
+        SF: Future<Output = ()>,
+        RE: std::error::Error + 'static,
+    {
+        let deadline = self.deadline(timeout);
+        let mut interval = self.response.interval();
+        loop {
+            if Utc::now() > deadline {
+                return Err(Self::expired());
+            }
+            let http_response = http_client.call(self.prepare_request()?).await;
+            match Self::poll_result(http_response, interval) {
+                PollResult::Continue(new_interval) => interval = new_interval,
+                PollResult::Done(result) => return result,
+            }
+            sleep_fn(interval).await;
+        }
+    }
+
+    fn deadline(&self, timeout: Option<Duration>) -> DateTime<Utc> {
+        let expires_in = timeout
+            .map(|timeout| std::cmp::min(timeout, self.response.expires_in()))
+            .unwrap_or_else(|| self.response.expires_in());
+        let now = Utc::now();
+        chrono::Duration::from_std(expires_in)
+            .ok()
+            .and_then(|expires_in| now.checked_add_signed(expires_in))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    fn expired<RE>() -> RequestTokenError<RE, DeviceCodeErrorResponse>
+    where
+        RE: std::error::Error + 'static,
+    {
+        RequestTokenError::ServerResponse(DeviceCodeErrorResponse::new(
+            DeviceCodeErrorResponseType::ExpiredToken,
+            Some("The backchannel authentication request has expired.".to_string()),
+            None,
+        ))
+    }
+
+    fn prepare_request<RE>(
+        &self,
+    ) -> Result<HttpRequest, RequestTokenError<RE, DeviceCodeErrorResponse>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        let token_url = self
+            .token_url
+            .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?;
+        let mut params = vec![
+            ("grant_type".to_string(), CIBA_GRANT_TYPE.to_string()),
+            (
+                "auth_req_id".to_string(),
+                self.response.auth_req_id().secret().clone(),
+            ),
+        ];
+        params.extend(self.params.iter().cloned());
+        self.client.prepare_request(token_url.url(), &params)
+    }
+
+    fn poll_result<RE>(
+        http_response: Result<HttpResponse, RE>,
+        interval: Duration,
+    ) -> PollResult<TR, RE>
+    where
+        RE: std::error::Error + 'static,
+    {
+        let http_response = match http_response {
+            Ok(http_response) => http_response,
+            Err(err) => return PollResult::Done(Err(RequestTokenError::Request(err))),
+        };
+        match endpoint_response::<RE, TR, DeviceCodeErrorResponse>(http_response) {
+            Err(RequestTokenError::ServerResponse(err)) => match err.error() {
+                DeviceCodeErrorResponseType::AuthorizationPending => PollResult::Continue(interval),
+                DeviceCodeErrorResponseType::SlowDown => {
+                    PollResult::Continue(interval + Duration::from_secs(5))
+                }
+                _ => PollResult::Done(Err(RequestTokenError::ServerResponse(err))),
+            },
+            result => PollResult::Done(result),
+        }
+    }
+}
+
+enum PollResult<TR, RE>
+where
+    RE: std::error::Error + 'static,
+{
+    Continue(Duration),
+    Done(Result<TR, RequestTokenError<RE, DeviceCodeErrorResponse>>),
+}
+
+// Client credentials used for authenticating requests to the backchannel authentication and
+// token endpoints.
+#[derive(Debug)]
+pub(crate) struct EndpointClient<'a> {
+    pub(crate) client_id: &'a ClientId,
+    pub(crate) client_secret: Option<&'a ClientSecret>,
+    pub(crate) auth_type: &'a AuthType,
+    // Client assertion parameters (e.g., for private_key_jwt authentication), or the error that
+    // occurred while signing the assertion, which is reported when the request is submitted.
+    pub(crate) auth_params: Result<Vec<(String, String)>, String>,
+}
+impl<'a> EndpointClient<'a> {
+    fn prepare_request<RE, TE>(
+        &self,
+        url: &Url,
+        params: &[(String, String)],
+    ) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: std::error::Error + 'static,
+        TE: ErrorResponse,
+    {
+        let auth_params = self.auth_params.as_ref().map_err(|err| {
+            RequestTokenError::Other(format!("Failed to sign client assertion: {}", err))
+        })?;
+
+        let mut headers = HeaderMap::new();
+        headers.append(ACCEPT, HeaderValue::from_static(MIME_TYPE_JSON));
+        headers.append(
+            CONTENT_TYPE,
+            HeaderValue::from_static(MIME_TYPE_FORM_URLENCODED),
+        );
+
+        let mut body = form_urlencoded::Serializer::new(String::new());
+        body.extend_pairs(params.iter());
+        body.extend_pairs(auth_params.iter());
+
+        match (self.client_secret, self.auth_type) {
+            // HTTP Basic authentication is the default (see RFC 6749 Section 2.3.1).
+            (Some(client_secret), AuthType::BasicAuth) => {
+                let (auth_header, auth_value) = auth_basic(self.client_id, client_secret);
+                headers.append(auth_header, auth_value);
+            }
+            (Some(client_secret), _) => {
+                body.append_pair("client_id", self.client_id);
+                body.append_pair("client_secret", client_secret.secret());
+            }
+            (None, _) => {
+                body.append_pair("client_id", self.client_id);
+            }
+        }
+
+        Ok(HttpRequest {
+            url: url.clone(),
+            method: Method::POST,
+            headers,
+            body: body.finish().into_bytes(),
+        })
+    }
+}
+
+fn endpoint_response<RE, T, TE>(http_response: HttpResponse) -> Result<T, RequestTokenError<RE, TE>>
+where
+    RE: std::error::Error + 'static,
+    T: DeserializeOwned,
+    TE: ErrorResponse,
+{
+    check_content_type(&http_response.headers, MIME_TYPE_JSON).map_err(|err_msg| {
+        RequestTokenError::Other(format!(
+            "{} (HTTP status code {})",
+            err_msg, http_response.status_code
+        ))
+    })?;
+
+    if http_response.status_code != StatusCode::OK {
+        let reason = http_response.body.as_slice();
+        if reason.is_empty() {
+            return Err(RequestTokenError::Other(
+                "Server returned empty error response".to_string(),
+            ));
+        }
+        return Err(serde_path_to_error::deserialize::<_, TE>(
+            &mut serde_json::Deserializer::from_slice(reason),
+        )
+        .map(RequestTokenError::ServerResponse)
+        .unwrap_or_else(|err| RequestTokenError::Parse(err, reason.to_vec())));
+    }
+
+    serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(
+        &http_response.body,
+    ))
+    .map_err(|err| RequestTokenError::Parse(err, http_response.body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::time::Duration;
+
+    use http::header::AUTHORIZATION;
+    use http::status::StatusCode;
+    use oauth2::{ClientId, ClientSecret, DeviceCodeErrorResponseType, RequestTokenError};
+    use url::form_urlencoded;
+
+    use crate::core::{CoreClient, CoreProviderMetadata};
+    use crate::tests::mock_json_response;
+    use crate::{
+        AuthReqId, BackchannelAuthenticationResponse, HttpRequest, LoginHint, OAuth2TokenResponse,
+        TokenResponse,
+    };
+
+    fn new_client() -> CoreClient {
+        let provider_metadata = serde_json::from_str::<CoreProviderMetadata>(
+            r#"{
+                "issuer": "https://server.example.com",
+                "authorization_endpoint": "https://server.example.com/authorize",
+                "token_endpoint": "https://server.example.com/token",
+                "jwks_uri": "https://server.example.com/jwks.json",
+                "backchannel_authentication_endpoint": "https://server.example.com/bc-authorize",
+                "response_types_supported": ["code"],
+                "subject_types_supported": ["public"],
+                "id_token_signing_alg_values_supported": ["RS256"]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            provider_metadata
+                .backchannel_authentication_endpoint()
+                .map(|url| url.as_str()),
+            Some("https://server.example.com/bc-authorize")
+        );
+        CoreClient::from_provider_metadata(
+            provider_metadata,
+            ClientId::new("s6BhdRkqt3".to_string()),
+            Some(ClientSecret::new("secret".to_string())),
+        )
+    }
+
+    fn form_params(request: &HttpRequest) -> Vec<(String, String)> {
+        form_urlencoded::parse(&request.body).into_owned().collect()
+    }
+
+    #[test]
+    fn test_backchannel_authentication_request() {
+        let client = new_client();
+        let response = client
+            .backchannel_authentication_request()
+            .unwrap()
+            .set_login_hint(&LoginHint::new("user@example.com".to_string()))
+            .add_extra_param("binding_message", "W4SCT")
+            .request(|request: HttpRequest| {
+                assert_eq!(
+                    request.url.as_str(),
+                    "https://server.example.com/bc-authorize"
+                );
+                assert_eq!(
+                    request.headers.get(AUTHORIZATION).unwrap(),
+                    "Basic czZCaGRSa3F0MzpzZWNyZXQ="
+                );
+                assert_eq!(
+                    form_params(&request),
+                    vec![
+                        ("scope".to_string(), "openid".to_string()),
+                        ("login_hint".to_string(), "user@example.com".to_string()),
+                        ("binding_message".to_string(), "W4SCT".to_string()),
+                    ]
+                );
+                mock_json_response(
+                    StatusCode::OK,
+                    r#"{
+                        "auth_req_id": "1c266114-a1be-4252-8ad1-04986c5b9ac1",
+                        "expires_in": 120,
+                        "interval": 2
+                    }"#,
+                )
+            })
+            .unwrap();
+        assert_eq!(
+            response.auth_req_id().secret(),
+            "1c266114-a1be-4252-8ad1-04986c5b9ac1"
+        );
+        assert_eq!(response.expires_in(), Duration::from_secs(120));
+        assert_eq!(response.interval(), Duration::from_secs(2));
+
+        // The interval defaults to 5 seconds.
+        let response: BackchannelAuthenticationResponse =
+            serde_json::from_str(r#"{"auth_req_id": "abc", "expires_in": 60}"#).unwrap();
+        assert_eq!(response.interval(), Duration::from_secs(5));
+
+        match client
+            .backchannel_authentication_request()
+            .unwrap()
+            .request(|_| {
+                mock_json_response(StatusCode::BAD_REQUEST, r#"{"error": "unknown_user_id"}"#)
+            }) {
+            Err(RequestTokenError::ServerResponse(err)) => {
+                assert_eq!(err.error().as_ref(), "unknown_user_id")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_backchannel_token_polling() {
+        let client = new_client();
+        let response = BackchannelAuthenticationResponse::new(
+            AuthReqId::new("1c266114-a1be-4252-8ad1-04986c5b9ac1".to_string()),
+            Duration::from_secs(120),
+            Some(Duration::from_secs(2)),
+        );
+
+        let responses = RefCell::new(vec![
+            mock_json_response(
+                StatusCode::BAD_REQUEST,
+                r#"{"error": "authorization_pending"}"#,
+            ),
+            mock_json_response(StatusCode::BAD_REQUEST, r#"{"error": "slow_down"}"#),
+            mock_json_response(
+                StatusCode::OK,
+                "{\
+                    \"access_token\": \"G5kXH2wHvUra0sHlDy1iTkDJgsgUO1bN\",\
+                    \"token_type\": \"Bearer\",\
+                    \"expires_in\": 3600,\
+                    \"id_token\": \"eyJhbGciOiJSUzI1NiJ9.eyJpc3MiOiJodHRwczovL3NlcnZlci5leGFtcGxl\
+                    LmNvbSIsImF1ZCI6WyJzNkJoZFJrcXQzIl0sImV4cCI6MTU0NDkzMjE0OSwiaWF0IjoxNTQ0OTI4\
+                    NTQ5LCJzdWIiOiJzdWJqZWN0In0.c2lnbmF0dXJl\"\
+                }",
+            ),
+        ]);
+        let sleeps = RefCell::new(Vec::new());
+        let token_response = client
+            .exchange_backchannel_authentication(&response)
+            .request(
+                |request: HttpRequest| {
+                    assert_eq!(request.url.as_str(), "https://server.example.com/token");
+                    assert_eq!(
+                        form_params(&request),
+                        vec![
+                            (
+                                "grant_type".to_string(),
+                                "urn:openid:params:grant-type:ciba".to_string()
+                            ),
+                            (
+                                "auth_req_id".to_string(),
+                                "1c266114-a1be-4252-8ad1-04986c5b9ac1".to_string()
+                            ),
+                        ]
+                    );
+                    responses.borrow_mut().remove(0)
+                },
+                |interval| sleeps.borrow_mut().push(interval),
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            token_response.access_token().secret(),
+            "G5kXH2wHvUra0sHlDy1iTkDJgsgUO1bN"
+        );
+        assert!(token_response.id_token().is_some());
+        // The interval is increased by 5 seconds after a `slow_down` error.
+        assert_eq!(
+            *sleeps.borrow(),
+            vec![Duration::from_secs(2), Duration::from_secs(7)]
+        );
+
+        // Other errors (e.g., the user denying the request) stop polling.
+        let attempts = Cell::new(0);
+        match client
+            .exchange_backchannel_authentication(&response)
+            .request(
+                |_| {
+                    attempts.set(attempts.get() + 1);
+                    mock_json_response(StatusCode::BAD_REQUEST, r#"{"error": "access_denied"}"#)
+                },
+                |_| {},
+                None,
+            ) {
+            Err(RequestTokenError::ServerResponse(err)) => {
+                assert_eq!(*err.error(), DeviceCodeErrorResponseType::AccessDenied)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(attempts.get(), 1);
+    }
+}
@@ -0,0 +1,722 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+
+use http::header::{HeaderValue, ACCEPT};
+use http::method::Method;
+use http::status::StatusCode;
+use oauth2::AccessToken;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::http_utils::{auth_bearer, MIME_TYPE_JWT};
+use crate::types::helpers::deserialize_string_or_vec_opt;
+use crate::verification::{AudiencesClaim, IssuerClaim};
+use crate::{
+    AsyncHttpClient, Audience, ClaimSourceEndpointUrl, ClaimSourceVerifier,
+    ClaimsVerificationError, HttpRequest, HttpResponse, IssuerUrl, JsonWebKey, JsonWebKeyType,
+    JsonWebKeyUse, JweContentEncryptionAlgorithm, JwsSigningAlgorithm, SyncHttpClient,
+};
+
+const CLAIM_NAMES: &str = "_claim_names";
+const CLAIM_SOURCES: &str = "_claim_sources";
+
+///
+/// Source of aggregated or distributed claims, as described in
+/// [Section 5.6.2 of OpenID Connect Core 1.0](
+/// https://openid.net/specs/openid-connect-core-1_0.html#AggregatedDistributedClaims).
+///
+/// Claim sources are referenced by the `_claim_names` member of a set of claims (see
+/// [`IdTokenClaims::claim_names`](crate::IdTokenClaims::claim_names) and
+/// [`UserInfoClaims::claim_names`](crate::UserInfoClaims::claim_names)).
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum ClaimSource {
+    ///
+    /// Aggregated claims, which are contained in a JWT signed by the claims provider.
+    ///
+    Aggregated {
+        ///
+        /// JWT containing the claims.
+        ///
+        #[serde(rename = "JWT")]
+        jwt: String,
+    },
+    ///
+    /// Distributed claims, which are retrieved as a JWT from the claims provider's endpoint.
+    ///
+    Distributed {
+        ///
+        /// Claims provider endpoint from which the claims are retrieved.
+        ///
+        endpoint: ClaimSourceEndpointUrl,
+        ///
+        /// Access token to send to the claims provider endpoint, if any.
+        ///
+        #[serde(skip_serializing_if = "Option::is_none")]
+        access_token: Option<AccessToken>,
+    },
+}
+
+impl PartialEq for ClaimSource {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ClaimSource::Aggregated { jwt }, ClaimSource::Aggregated { jwt: other_jwt }) => {
+                jwt == other_jwt
+            }
+            (
+                ClaimSource::Distributed {
+                    endpoint,
+                    access_token,
+                },
+                ClaimSource::Distributed {
+                    endpoint: other_endpoint,
+                    access_token: other_access_token,
+                },
+            ) => {
+                endpoint == other_endpoint
+                    && access_token.as_ref().map(AccessToken::secret)
+                        == other_access_token.as_ref().map(AccessToken::secret)
+            }
+            _ => false,
+        }
+    }
+}
+
+///
+/// Error resolving aggregated or distributed claims.
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ClaimSourceError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    ///
+    /// Failed to verify the claims returned by the claim source with the given name.
+    ///
+    #[error("Failed to verify claims from claim source `{0}`")]
+    ClaimsVerification(String, #[source] ClaimsVerificationError),
+    ///
+    /// Failed to merge the resolved claims into the original set of claims.
+    ///
+    #[error("Failed to merge resolved claims")]
+    Merge(#[source] serde_json::Error),
+    ///
+    /// An error occurred while sending the request or receiving the response (e.g., network
+    /// connectivity failed).
+    ///
+    #[error("Request failed")]
+    Request(#[source] RE),
+    ///
+    /// Server returned an invalid response.
+    ///
+    #[error("Server returned invalid response: {2}")]
+    Response(StatusCode, Vec<u8>, String),
+    ///
+    /// An unexpected error occurred.
+    ///
+    #[error("Other error: {0}")]
+    Other(String),
+}
+
+// Claims contained in a claim source JWT.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ClaimSourceJwtClaims {
+    #[serde(rename = "iss", skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<IssuerUrl>,
+    #[serde(
+        default,
+        rename = "aud",
+        deserialize_with = "deserialize_string_or_vec_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub audiences: Option<Vec<Audience>>,
+    #[serde(flatten)]
+    pub claims: Map<String, Value>,
+}
+impl AudiencesClaim for ClaimSourceJwtClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        self.audiences.as_ref()
+    }
+}
+impl IssuerClaim for ClaimSourceJwtClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        self.issuer.as_ref()
+    }
+}
+
+// Returns the name, endpoint, and access token of each distributed claim source.
+fn distributed_sources(
+    claim_sources: Option<&HashMap<String, ClaimSource>>,
+) -> Vec<(&String, &ClaimSourceEndpointUrl, Option<&AccessToken>)> {
+    let mut sources = claim_sources
+        .into_iter()
+        .flatten()
+        .filter_map(|(source_name, source)| match source {
+            ClaimSource::Distributed {
+                endpoint,
+                access_token,
+            } => Some((source_name, endpoint, access_token.as_ref())),
+            ClaimSource::Aggregated { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    // Fetch the sources in a deterministic order.
+    sources.sort_by_key(|(source_name, _, _)| *source_name);
+    sources
+}
+
+fn distributed_claims_request(
+    endpoint: &ClaimSourceEndpointUrl,
+    access_token: Option<&AccessToken>,
+) -> HttpRequest {
+    HttpRequest {
+        url: endpoint.url().clone(),
+        method: Method::GET,
+        headers: vec![(ACCEPT, HeaderValue::from_static(MIME_TYPE_JWT))]
+            .into_iter()
+            .chain(access_token.map(auth_bearer))
+            .collect(),
+        body: Vec::new(),
+    }
+}
+
+fn distributed_claims_response<JE, JS, JT, JU, K, RE>(
+    source_name: &str,
+    verifier: &ClaimSourceVerifier<JE, JS, JT, JU, K>,
+    http_response: HttpResponse,
+) -> Result<Map<String, Value>, ClaimSourceError<RE>>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RE: std::error::Error + 'static,
+{
+    if http_response.status_code != StatusCode::OK {
+        return Err(ClaimSourceError::Response(
+            http_response.status_code,
+            http_response.body,
+            format!(
+                "unexpected HTTP status code from claim source `{}`",
+                source_name
+            ),
+        ));
+    }
+
+    let jwt = String::from_utf8(http_response.body).map_err(|_| {
+        ClaimSourceError::Other(format!(
+            "response from claim source `{}` is not a valid JWT",
+            source_name
+        ))
+    })?;
+    verifier
+        .verified_claims(jwt.trim())
+        .map_err(|err| ClaimSourceError::ClaimsVerification(source_name.to_string(), err))
+}
+
+///
+/// Merges the claims resolved from each claim source into `claims` and removes the resolved
+/// claim names and sources.
+///
+/// Only claims listed in `_claim_names` as originating from the corresponding source are merged.
+///
+pub(crate) fn merge_resolved_claims<T>(
+    claims: &T,
+    resolved_claims: HashMap<String, Map<String, Value>>,
+) -> Result<T, serde_json::Error>
+where
+    T: DeserializeOwned + Serialize,
+{
+    let mut claims_value = serde_json::to_value(claims)?;
+    let claims_map = match claims_value {
+        Value::Object(ref mut claims_map) => claims_map,
+        _ => return serde_json::from_value(claims_value),
+    };
+
+    if let Some(Value::Object(claim_names)) = claims_map.remove(CLAIM_NAMES) {
+        let mut unresolved_names = Map::new();
+        for (claim_name, source_name) in claim_names {
+            let resolved_value = source_name
+                .as_str()
+                .and_then(|source_name| resolved_claims.get(source_name))
+                .map(|source_claims| source_claims.get(&claim_name).cloned());
+            match resolved_value {
+                Some(Some(value)) => {
+                    claims_map.insert(claim_name, value);
+                }
+                // The claim source was resolved but doesn't contain the claim.
+                Some(None) => {}
+                None => {
+                    unresolved_names.insert(claim_name, source_name);
+                }
+            }
+        }
+        if !unresolved_names.is_empty() {
+            claims_map.insert(CLAIM_NAMES.to_string(), Value::Object(unresolved_names));
+        }
+    }
+
+    if let Some(Value::Object(mut claim_sources)) = claims_map.remove(CLAIM_SOURCES) {
+        claim_sources.retain(|source_name, _| !resolved_claims.contains_key(source_name));
+        if !claim_sources.is_empty() {
+            claims_map.insert(CLAIM_SOURCES.to_string(), Value::Object(claim_sources));
+        }
+    }
+
+    serde_json::from_value(claims_value)
+}
+
+pub(crate) fn resolve_aggregated_claims<T, JE, JS, JT, JU, K>(
+    claims: &T,
+    claim_sources: Option<&HashMap<String, ClaimSource>>,
+    verifier: &ClaimSourceVerifier<JE, JS, JT, JU, K>,
+) -> Result<T, ClaimSourceError<Infallible>>
+where
+    T: DeserializeOwned + Serialize,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    let resolved_claims = claim_sources
+        .into_iter()
+        .flatten()
+        .filter_map(|(source_name, source)| match source {
+            ClaimSource::Aggregated { jwt } => Some((source_name, jwt)),
+            ClaimSource::Distributed { .. } => None,
+        })
+        .map(|(source_name, jwt)| {
+            verifier
+                .verified_claims(jwt)
+                .map(|source_claims| (source_name.clone(), source_claims))
+                .map_err(|err| ClaimSourceError::ClaimsVerification(source_name.clone(), err))
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    merge_resolved_claims(claims, resolved_claims).map_err(ClaimSourceError::Merge)
+}
+
+pub(crate) fn resolve_distributed_claims<T, JE, JS, JT, JU, K, HC, RE>(
+    claims: &T,
+    claim_sources: Option<&HashMap<String, ClaimSource>>,
+    verifier: &ClaimSourceVerifier<JE, JS, JT, JU, K>,
+    http_client: HC,
+) -> Result<T, ClaimSourceError<RE>>
+where
+    T: DeserializeOwned + Serialize,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    HC: SyncHttpClient<Error = RE>,
+    RE: std::error::Error + 'static,
+{
+    let mut resolved_claims = HashMap::new();
+    for (source_name, endpoint, access_token) in distributed_sources(claim_sources) {
+        let http_response = http_client
+            .call(distributed_claims_request(endpoint, access_token))
+            .map_err(ClaimSourceError::Request)?;
+        resolved_claims.insert(
+            source_name.clone(),
+            distributed_claims_response(source_name, verifier, http_response)?,
+        );
+    }
+    merge_resolved_claims(claims, resolved_claims).map_err(ClaimSourceError::Merge)
+}
+
+pub(crate) async fn resolve_distributed_claims_async<T, JE, JS, JT, JU, K, F, HC, RE>(
+    claims: &T,
+    claim_sources: Option<&HashMap<String, ClaimSource>>,
+    verifier: &ClaimSourceVerifier<'_, JE, JS, JT, JU, K>,
+    http_client: HC,
+) -> Result<T, ClaimSourceError<RE>>
+where
+    T: DeserializeOwned + Serialize,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    F: Future<Output = Result<HttpResponse, RE>>,
+    HC: AsyncHttpClient<Error = RE, Future = F>,
+    RE: std::error::Error + 'static,
+{
+    let mut resolved_claims = HashMap::new();
+    for (source_name, endpoint, access_token) in distributed_sources(claim_sources) {
+        let http_response = http_client
+            .call(distributed_claims_request(endpoint, access_token))
+            .await
+            .map_err(ClaimSourceError::Request)?;
+        resolved_claims.insert(
+            source_name.clone(),
+            distributed_claims_response(source_name, verifier, http_response)?,
+        );
+    }
+    merge_resolved_claims(claims, resolved_claims).map_err(ClaimSourceError::Merge)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::cell::RefCell;
+
+    use http::header::{HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+    use http::status::StatusCode;
+    use serde::{Deserialize, Serialize};
+
+    use super::{ClaimSource, ClaimSourceError, ClaimSourceJwtClaims};
+
+    use crate::core::{
+        CoreClaimSourceVerifier, CoreGenderClaim, CoreJsonWebKey, CoreJsonWebKeySet,
+        CoreJweContentEncryptionAlgorithm, CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey,
+    };
+    use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
+    use crate::jwt::{JsonWebToken, JsonWebTokenJsonPayloadSerde};
+    use crate::{
+        AdditionalClaims, ClaimsVerificationError, EndUserPhoneNumber, HttpRequest, HttpResponse,
+        IssuerUrl, JsonWebKeyId, UserInfoClaims,
+    };
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct TestClaims {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payment_info: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        credit_score: Option<u32>,
+    }
+    impl AdditionalClaims for TestClaims {}
+
+    type TestUserInfoClaims = UserInfoClaims<TestClaims, CoreGenderClaim>;
+
+    // Based on the example in Section 5.6.2.2 of OpenID Connect Core 1.0.
+    const TEST_USER_INFO: &str = r#"{
+        "sub": "248289761001",
+        "name": "Jane Doe",
+        "email": "janedoe@example.com",
+        "_claim_names": {
+            "payment_info": "src1",
+            "phone_number": "src2",
+            "credit_score": "src2"
+        },
+        "_claim_sources": {
+            "src1": {
+                "endpoint": "https://bank.example.com/claim_source"
+            },
+            "src2": {
+                "endpoint": "https://creditagency.example.com/claims_here",
+                "access_token": "ksj3n283dke"
+            },
+            "src3": {
+                "JWT": "jwt_header.claims.signature"
+            }
+        }
+    }"#;
+
+    pub fn claim_source_jwt(issuer: &str, claims: serde_json::Value) -> String {
+        let jwt = JsonWebToken::<
+            CoreJweContentEncryptionAlgorithm,
+            CoreJwsSigningAlgorithm,
+            _,
+            _,
+            JsonWebTokenJsonPayloadSerde,
+        >::new(
+            ClaimSourceJwtClaims {
+                issuer: Some(IssuerUrl::new(issuer.to_string()).unwrap()),
+                audiences: None,
+                claims: serde_json::from_value(claims).unwrap(),
+            },
+            &CoreRsaPrivateSigningKey::from_pem(
+                TEST_RSA_PRIV_KEY,
+                Some(JsonWebKeyId::new(
+                    "bilbo.baggins@hobbiton.example".to_string(),
+                )),
+            )
+            .unwrap(),
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+        serde_json::to_value(jwt)
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn jwt_response(jwt: String) -> Result<HttpResponse, std::io::Error> {
+        Ok(HttpResponse {
+            status_code: StatusCode::OK,
+            headers: vec![(CONTENT_TYPE, HeaderValue::from_static("application/jwt"))]
+                .into_iter()
+                .collect(),
+            body: jwt.into_bytes(),
+        })
+    }
+
+    fn verifier() -> CoreClaimSourceVerifier<'static> {
+        let keys = CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+            TEST_RSA_PUB_KEY,
+        )
+        .unwrap()]);
+        CoreClaimSourceVerifier::new()
+            .add_trusted_issuer(
+                IssuerUrl::new("https://creditagency.example.com".to_string()).unwrap(),
+                keys.clone(),
+            )
+            .add_trusted_issuer(
+                IssuerUrl::new("https://bank.example.com".to_string()).unwrap(),
+                keys,
+            )
+    }
+
+    #[test]
+    fn test_claim_sources_serde() {
+        let claims =
+            TestUserInfoClaims::from_json::<std::io::Error>(TEST_USER_INFO.as_bytes(), None)
+                .unwrap();
+
+        let claim_names = claims.claim_names().unwrap();
+        assert_eq!(claim_names.len(), 3);
+        assert_eq!(claim_names["payment_info"], "src1");
+        assert_eq!(claim_names["phone_number"], "src2");
+
+        let claim_sources = claims.claim_sources().unwrap();
+        assert_eq!(claim_sources.len(), 3);
+        match claim_sources["src1"] {
+            ClaimSource::Distributed {
+                ref endpoint,
+                access_token: None,
+            } => assert_eq!(endpoint.as_str(), "https://bank.example.com/claim_source"),
+            ref other => panic!("unexpected claim source: {:?}", other),
+        }
+        match claim_sources["src2"] {
+            ClaimSource::Distributed {
+                ref endpoint,
+                access_token: Some(ref access_token),
+            } => {
+                assert_eq!(
+                    endpoint.as_str(),
+                    "https://creditagency.example.com/claims_here"
+                );
+                assert_eq!(access_token.secret(), "ksj3n283dke");
+            }
+            ref other => panic!("unexpected claim source: {:?}", other),
+        }
+        match claim_sources["src3"] {
+            ClaimSource::Aggregated { ref jwt } => assert_eq!(jwt, "jwt_header.claims.signature"),
+            ref other => panic!("unexpected claim source: {:?}", other),
+        }
+
+        // The pointers round-trip through serialization.
+        let serialized = serde_json::to_value(&claims).unwrap();
+        assert_eq!(
+            serialized["_claim_sources"]["src2"]["access_token"],
+            "ksj3n283dke"
+        );
+        assert_eq!(
+            serialized["_claim_sources"]["src3"]["JWT"],
+            "jwt_header.claims.signature"
+        );
+    }
+
+    #[test]
+    fn test_resolve_distributed_claims() {
+        let claims =
+            TestUserInfoClaims::from_json::<std::io::Error>(TEST_USER_INFO.as_bytes(), None)
+                .unwrap();
+        let requests = RefCell::new(Vec::new());
+
+        let resolved = claims
+            .resolve_distributed_claims(&verifier(), |request: HttpRequest| {
+                assert_eq!(
+                    request.headers.get(ACCEPT).unwrap(),
+                    HeaderValue::from_static("application/jwt")
+                );
+                requests.borrow_mut().push((
+                    request.url.to_string(),
+                    request
+                        .headers
+                        .get(AUTHORIZATION)
+                        .map(|value| value.to_str().unwrap().to_string()),
+                ));
+                match request.url.as_str() {
+                    "https://bank.example.com/claim_source" => jwt_response(claim_source_jwt(
+                        "https://bank.example.com",
+                        serde_json::json!({
+                            "payment_info": "Some_Card",
+                            "email": "overridden@example.com"
+                        }),
+                    )),
+                    "https://creditagency.example.com/claims_here" => {
+                        jwt_response(claim_source_jwt(
+                            "https://creditagency.example.com",
+                            serde_json::json!({
+                                "phone_number": "+1 (310) 123-4567",
+                                "credit_score": 650
+                            }),
+                        ))
+                    }
+                    other => panic!("unexpected URL: {}", other),
+                }
+            })
+            .unwrap();
+
+        assert_eq!(
+            requests.into_inner(),
+            vec![
+                ("https://bank.example.com/claim_source".to_string(), None),
+                (
+                    "https://creditagency.example.com/claims_here".to_string(),
+                    Some("Bearer ksj3n283dke".to_string())
+                ),
+            ]
+        );
+
+        // Claims from the sources are merged into the typed claims.
+        assert_eq!(
+            resolved.phone_number(),
+            Some(&EndUserPhoneNumber::new("+1 (310) 123-4567".to_string()))
+        );
+        // Claims not listed in _claim_names aren't merged.
+        assert_eq!(resolved.email().unwrap().as_str(), "janedoe@example.com");
+        // Resolved pointers are removed, while the aggregated claim source is left untouched.
+        assert_eq!(resolved.claim_names(), None);
+        let claim_sources = resolved.claim_sources().unwrap();
+        assert_eq!(claim_sources.len(), 1);
+        assert!(matches!(
+            claim_sources["src3"],
+            ClaimSource::Aggregated { .. }
+        ));
+        assert_eq!(
+            resolved.additional_claims().payment_info.as_deref(),
+            Some("Some_Card")
+        );
+        assert_eq!(resolved.additional_claims().credit_score, Some(650));
+    }
+
+    #[test]
+    fn test_resolve_distributed_claims_errors() {
+        let claims =
+            TestUserInfoClaims::from_json::<std::io::Error>(TEST_USER_INFO.as_bytes(), None)
+                .unwrap();
+
+        // Claims signed by an issuer that isn't trusted by the verifier are rejected.
+        match claims.resolve_distributed_claims(&verifier(), |_| {
+            jwt_response(claim_source_jwt(
+                "https://evil.example.com",
+                serde_json::json!({ "credit_score": 850 }),
+            ))
+        }) {
+            Err(ClaimSourceError::ClaimsVerification(
+                source_name,
+                ClaimsVerificationError::InvalidIssuer(_),
+            )) => assert_eq!(source_name, "src1"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match claims.resolve_distributed_claims(&verifier(), |_| {
+            Ok::<_, std::io::Error>(HttpResponse {
+                status_code: StatusCode::UNAUTHORIZED,
+                headers: Default::default(),
+                body: vec![],
+            })
+        }) {
+            Err(ClaimSourceError::Response(StatusCode::UNAUTHORIZED, _, _)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    fn aggregated_user_info(jwt: &str) -> TestUserInfoClaims {
+        TestUserInfoClaims::from_json::<std::io::Error>(
+            serde_json::json!({
+                "sub": "248289761001",
+                "_claim_names": {
+                    "payment_info": "src1",
+                    "credit_score": "src2"
+                },
+                "_claim_sources": {
+                    "src1": { "JWT": jwt },
+                    "src2": { "endpoint": "https://creditagency.example.com/claims_here" }
+                }
+            })
+            .to_string()
+            .as_bytes(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_aggregated_claims() {
+        let claims = aggregated_user_info(&claim_source_jwt(
+            "https://bank.example.com",
+            serde_json::json!({
+                "payment_info": "Some_Card",
+                "credit_score": 850
+            }),
+        ));
+
+        let resolved = claims.resolve_aggregated_claims(&verifier()).unwrap();
+        assert_eq!(
+            resolved.additional_claims().payment_info.as_deref(),
+            Some("Some_Card")
+        );
+        // The credit score is attributed to a different claim source, so the aggregated value
+        // isn't merged.
+        assert_eq!(resolved.additional_claims().credit_score, None);
+
+        // Only the distributed claim source remains.
+        assert_eq!(
+            resolved
+                .claim_names()
+                .unwrap()
+                .get("credit_score")
+                .map(String::as_str),
+            Some("src2")
+        );
+        let claim_sources = resolved.claim_sources().unwrap();
+        assert_eq!(claim_sources.len(), 1);
+        assert!(matches!(
+            claim_sources["src2"],
+            ClaimSource::Distributed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_resolve_aggregated_claims_untrusted_signer() {
+        // Signed by an issuer that isn't trusted by the verifier.
+        let claims = aggregated_user_info(&claim_source_jwt(
+            "https://evil.example.com",
+            serde_json::json!({ "payment_info": "Stolen_Card" }),
+        ));
+        match claims.resolve_aggregated_claims(&verifier()) {
+            Err(ClaimSourceError::ClaimsVerification(
+                source_name,
+                ClaimsVerificationError::InvalidIssuer(_),
+            )) => assert_eq!(source_name, "src1"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Claiming to be a trusted issuer, but not signed by any of that issuer's keys.
+        let claims = aggregated_user_info(&claim_source_jwt(
+            "https://bank.example.com",
+            serde_json::json!({ "payment_info": "Stolen_Card" }),
+        ));
+        let verifier = CoreClaimSourceVerifier::new().add_trusted_issuer(
+            IssuerUrl::new("https://bank.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![]),
+        );
+        match claims.resolve_aggregated_claims(&verifier) {
+            Err(ClaimSourceError::ClaimsVerification(
+                source_name,
+                ClaimsVerificationError::SignatureVerification(_),
+            )) => assert_eq!(source_name, "src1"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
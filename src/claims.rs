@@ -6,16 +6,19 @@ use chrono::{DateTime, Utc};
 use serde::de::{Deserialize, DeserializeOwned, Deserializer, MapAccess, Visitor};
 use serde::ser::SerializeMap;
 use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
 
-use crate::helpers::FlattenFilter;
-use crate::types::helpers::{split_language_tag_key, timestamp_to_utc, utc_to_seconds};
+use crate::helpers::{FilteredFlatten, FlattenFilter};
+use crate::types::helpers::{
+    split_language_tag_key, timestamp_to_utc, utc_to_seconds, ObjectOrString,
+};
 use crate::types::{Boolean, LocalizedClaim, Timestamp};
 use crate::{
-    AddressCountry, AddressLocality, AddressPostalCode, AddressRegion, EndUserBirthday,
-    EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName,
-    EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone,
-    EndUserUsername, EndUserWebsiteUrl, FormattedAddress, LanguageTag, StreetAddress,
-    SubjectIdentifier,
+    AddressCountry, AddressLocality, AddressPostalCode, AddressRegion, ClaimsVerificationError,
+    EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName,
+    EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl,
+    EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, FormattedAddress, LanguageTag, Seconds,
+    StreetAddress, SubjectIdentifier,
 };
 
 ///
@@ -32,6 +35,112 @@ pub trait AdditionalClaims: Debug + DeserializeOwned + Serialize + 'static {}
 pub struct EmptyAdditionalClaims {}
 impl AdditionalClaims for EmptyAdditionalClaims {}
 
+///
+/// Group and role membership claims.
+///
+/// These claims are not defined by OpenID Connect Core, but they are commonly issued by identity
+/// providers such as Keycloak, Azure AD, and Okta. This struct may be used directly as the
+/// additional claims type, or combined with other claims via `#[serde(flatten)]`.
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct GroupsClaims {
+    ///
+    /// Groups of which the End-User is a member.
+    ///
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<String>>,
+    ///
+    /// Roles assigned to the End-User.
+    ///
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roles: Option<Vec<String>>,
+    ///
+    /// Entitlements granted to the End-User.
+    ///
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entitlements: Option<Vec<String>>,
+}
+impl AdditionalClaims for GroupsClaims {}
+
+///
+/// Catch-all additional claims that preserve every claim not recognized as one of the
+/// [`StandardClaims`] (or, for ID tokens and logout tokens, one of the other registered claims).
+///
+/// Using this type as the additional claims type ensures that provider-specific claims survive a
+/// deserialization and serialization round trip, which is useful for gateways that must forward
+/// claims faithfully. Unlike [`EmptyAdditionalClaims`], no claims are discarded.
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct PreservedClaims(pub serde_json::Map<String, serde_json::Value>);
+impl PreservedClaims {
+    ///
+    /// Returns the value of the given claim, if present.
+    ///
+    pub fn get(&self, name: &str) -> Option<&serde_json::Value> {
+        self.0.get(name)
+    }
+}
+impl AdditionalClaims for PreservedClaims {}
+
+///
+/// Standard Claims merged with every other claim, which is captured as arbitrary JSON.
+///
+/// This is a middle ground between [`EmptyAdditionalClaims`], which discards any claims other
+/// than the [`StandardClaims`], and a dedicated [`AdditionalClaims`] struct declaring each
+/// expected claim. Standard Claims are parsed into their typed fields (see
+/// [`MergedClaims::standard_claims`]), while all remaining claims are available through
+/// [`MergedClaims::additional`]. To capture the remaining claims of an ID token or UserInfo
+/// response, use [`PreservedClaims`] as the additional claims type instead.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MergedClaims<GC>
+where
+    GC: GenderClaim,
+{
+    #[serde(bound = "GC: GenderClaim")]
+    #[serde(flatten)]
+    standard_claims: StandardClaims<GC>,
+
+    #[serde(bound = "GC: GenderClaim")]
+    #[serde(flatten)]
+    additional_claims: FilteredFlatten<StandardClaims<GC>, Map<String, Value>>,
+}
+impl<GC> MergedClaims<GC>
+where
+    GC: GenderClaim,
+{
+    ///
+    /// Initializes merged claims from the given Standard Claims and additional claims.
+    ///
+    pub fn new(standard_claims: StandardClaims<GC>, additional_claims: Map<String, Value>) -> Self {
+        Self {
+            standard_claims,
+            additional_claims: additional_claims.into(),
+        }
+    }
+
+    ///
+    /// Returns the Standard Claims.
+    ///
+    pub fn standard_claims(&self) -> &StandardClaims<GC> {
+        &self.standard_claims
+    }
+
+    ///
+    /// Returns all claims other than the Standard Claims.
+    ///
+    pub fn additional_claims(&self) -> &Map<String, Value> {
+        self.additional_claims.as_ref()
+    }
+
+    ///
+    /// Returns the value of the given claim, if present and not one of the Standard Claims.
+    ///
+    pub fn additional(&self, key: &str) -> Option<&Value> {
+        self.additional_claims.as_ref().get(key)
+    }
+}
+
 ///
 /// Address claims.
 ///
@@ -112,6 +221,7 @@ where
     pub(crate) phone_number_verified: Option<bool>,
     pub(crate) address: Option<AddressClaim>,
     pub(crate) updated_at: Option<DateTime<Utc>>,
+    pub(crate) updated_at_seconds: Option<Seconds>,
 }
 impl<GC> StandardClaims<GC>
 where
@@ -145,6 +255,7 @@ where
             phone_number_verified: None,
             address: None,
             updated_at: None,
+            updated_at_seconds: None,
         }
     }
 
@@ -186,9 +297,162 @@ where
             set_phone_number -> phone_number[Option<EndUserPhoneNumber>],
             set_phone_number_verified -> phone_number_verified[Option<bool>],
             set_address -> address[Option<AddressClaim>],
-            set_updated_at -> updated_at[Option<DateTime<Utc>>],
         }
     ];
+
+    ///
+    /// Returns the `updated_at` claim.
+    ///
+    /// If the claim value is out of the range supported by [`DateTime`], this method returns
+    /// `None`, while [`updated_at_seconds`](Self::updated_at_seconds) still returns the value
+    /// received from the provider.
+    ///
+    pub fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at
+    }
+
+    ///
+    /// Returns the `updated_at` claim as the number of seconds since the Unix epoch, exactly as
+    /// received from the OpenID Connect Provider.
+    ///
+    /// This preserves fractional values, as well as those that can't be represented as a
+    /// [`DateTime`]. For claims set via [`set_updated_at`](Self::set_updated_at), this returns
+    /// the corresponding whole number of seconds.
+    ///
+    pub fn updated_at_seconds(&self) -> Option<Seconds> {
+        self.updated_at_seconds.clone()
+    }
+
+    ///
+    /// Sets the `updated_at` claim.
+    ///
+    pub fn set_updated_at(mut self, updated_at: Option<DateTime<Utc>>) -> Self {
+        self.updated_at_seconds = updated_at
+            .as_ref()
+            .and_then(|utc| utc_to_seconds(utc).seconds());
+        self.updated_at = updated_at;
+        self
+    }
+
+    ///
+    /// Returns the JSON names of the claims present in this set of Standard Claims.
+    ///
+    /// The Subject (`sub`) claim is always present. Localized claims are considered present if
+    /// they contain an entry for at least one locale (or the default locale).
+    ///
+    pub fn present_fields(&self) -> impl Iterator<Item = &'static str> {
+        fn localized<T>(claim: &Option<LocalizedClaim<T>>) -> bool {
+            claim
+                .as_ref()
+                .map(|claim| claim.iter().next().is_some())
+                .unwrap_or(false)
+        }
+
+        vec![
+            ("sub", true),
+            ("name", localized(&self.name)),
+            ("given_name", localized(&self.given_name)),
+            ("family_name", localized(&self.family_name)),
+            ("middle_name", localized(&self.middle_name)),
+            ("nickname", localized(&self.nickname)),
+            ("preferred_username", self.preferred_username.is_some()),
+            ("profile", localized(&self.profile)),
+            ("picture", localized(&self.picture)),
+            ("website", localized(&self.website)),
+            ("email", self.email.is_some()),
+            ("email_verified", self.email_verified.is_some()),
+            ("gender", self.gender.is_some()),
+            ("birthday", self.birthday.is_some()),
+            ("birthdate", self.birthdate.is_some()),
+            ("zoneinfo", self.zoneinfo.is_some()),
+            ("locale", self.locale.is_some()),
+            ("phone_number", self.phone_number.is_some()),
+            (
+                "phone_number_verified",
+                self.phone_number_verified.is_some(),
+            ),
+            ("address", self.address.is_some()),
+            (
+                "updated_at",
+                self.updated_at.is_some() || self.updated_at_seconds.is_some(),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, present)| if present { Some(name) } else { None })
+    }
+
+    ///
+    /// Merges another set of Standard Claims into this one.
+    ///
+    /// This is typically used to enrich the claims returned in an ID token with those returned by
+    /// the UserInfo endpoint. Each claim present in `other` overwrites the corresponding claim in
+    /// `self`, while claims absent from `other` are left untouched. Localized claims are merged
+    /// per locale, so that (for example) a German `name` does not replace an English `name`.
+    ///
+    /// Returns [`ClaimsVerificationError::InvalidSubject`] if the two sets of claims do not refer
+    /// to the same Subject (`sub`), as required by
+    /// [Section 5.3.2](https://openid.net/specs/openid-connect-core-1_0.html#UserInfoResponse)
+    /// of OpenID Connect Core.
+    ///
+    pub fn merge(self, other: StandardClaims<GC>) -> Result<Self, ClaimsVerificationError> {
+        if self.sub != other.sub {
+            return Err(ClaimsVerificationError::InvalidSubject(format!(
+                "expected `{}` (found `{}`)",
+                self.sub.as_str(),
+                other.sub.as_str()
+            )));
+        }
+
+        let (updated_at, updated_at_seconds) =
+            if other.updated_at.is_some() || other.updated_at_seconds.is_some() {
+                (other.updated_at, other.updated_at_seconds)
+            } else {
+                (self.updated_at, self.updated_at_seconds)
+            };
+
+        Ok(Self {
+            sub: self.sub,
+            name: merge_localized(self.name, other.name),
+            given_name: merge_localized(self.given_name, other.given_name),
+            family_name: merge_localized(self.family_name, other.family_name),
+            middle_name: merge_localized(self.middle_name, other.middle_name),
+            nickname: merge_localized(self.nickname, other.nickname),
+            preferred_username: other.preferred_username.or(self.preferred_username),
+            profile: merge_localized(self.profile, other.profile),
+            picture: merge_localized(self.picture, other.picture),
+            website: merge_localized(self.website, other.website),
+            email: other.email.or(self.email),
+            email_verified: other.email_verified.or(self.email_verified),
+            gender: other.gender.or(self.gender),
+            birthday: other.birthday.or(self.birthday),
+            birthdate: other.birthdate.or(self.birthdate),
+            zoneinfo: other.zoneinfo.or(self.zoneinfo),
+            locale: other.locale.or(self.locale),
+            phone_number: other.phone_number.or(self.phone_number),
+            phone_number_verified: other.phone_number_verified.or(self.phone_number_verified),
+            address: other.address.or(self.address),
+            updated_at,
+            updated_at_seconds,
+        })
+    }
+}
+
+fn merge_localized<T>(
+    base: Option<LocalizedClaim<T>>,
+    other: Option<LocalizedClaim<T>>,
+) -> Option<LocalizedClaim<T>>
+where
+    T: 'static,
+{
+    match (base, other) {
+        (Some(mut base), Some(other)) => {
+            for (locale, value) in other {
+                base.insert(locale, value);
+            }
+            Some(base)
+        }
+        (base, other) => other.or(base),
+    }
 }
 impl<GC> FlattenFilter for StandardClaims<GC>
 where
@@ -270,8 +534,8 @@ where
                         [Option(locale)]
                         [Option(phone_number)]
                         [Option(Boolean(phone_number_verified))]
-                        [Option(address)]
-                        [Option(DateTime(Seconds(updated_at)))]
+                        [Option(ObjectOrString(address))]
+                        [Option(DateTime(Seconds(updated_at, updated_at_seconds)))]
                     }
                 }
             }
@@ -310,8 +574,569 @@ where
                 [Option(phone_number)]
                 [Option(phone_number_verified)]
                 [Option(address)]
-                [Option(DateTime(Seconds(updated_at)))]
+                [Option(DateTime(Seconds(updated_at, updated_at_seconds)))]
+            }
+        }
+    }
+}
+
+///
+/// JSON type of a claim value.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClaimValueType {
+    ///
+    /// JSON boolean.
+    ///
+    Boolean,
+    ///
+    /// JSON number.
+    ///
+    Number,
+    ///
+    /// JSON object.
+    ///
+    Object,
+    ///
+    /// JSON string.
+    ///
+    String,
+}
+
+///
+/// Metadata describing one of the [`StandardClaims`], as serialized to JSON.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StandardClaimMetadata {
+    name: &'static str,
+    localized: bool,
+    value_type: ClaimValueType,
+}
+impl StandardClaimMetadata {
+    const fn new(name: &'static str, localized: bool, value_type: ClaimValueType) -> Self {
+        Self {
+            name,
+            localized,
+            value_type,
+        }
+    }
+
+    ///
+    /// Returns the JSON name of the claim.
+    ///
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    ///
+    /// Returns whether the claim may be [localized](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#ClaimsLanguagesAndScripts), in which
+    /// case it may also be serialized as `<name>#<language tag>`.
+    ///
+    pub fn is_localized(&self) -> bool {
+        self.localized
+    }
+
+    ///
+    /// Returns the JSON type of the claim value.
+    ///
+    pub fn value_type(&self) -> ClaimValueType {
+        self.value_type
+    }
+}
+
+const STANDARD_CLAIMS_METADATA: &[StandardClaimMetadata] = &[
+    StandardClaimMetadata::new("sub", false, ClaimValueType::String),
+    StandardClaimMetadata::new("name", true, ClaimValueType::String),
+    StandardClaimMetadata::new("given_name", true, ClaimValueType::String),
+    StandardClaimMetadata::new("family_name", true, ClaimValueType::String),
+    StandardClaimMetadata::new("middle_name", true, ClaimValueType::String),
+    StandardClaimMetadata::new("nickname", true, ClaimValueType::String),
+    StandardClaimMetadata::new("preferred_username", false, ClaimValueType::String),
+    StandardClaimMetadata::new("profile", true, ClaimValueType::String),
+    StandardClaimMetadata::new("picture", true, ClaimValueType::String),
+    StandardClaimMetadata::new("website", true, ClaimValueType::String),
+    StandardClaimMetadata::new("email", false, ClaimValueType::String),
+    StandardClaimMetadata::new("email_verified", false, ClaimValueType::Boolean),
+    StandardClaimMetadata::new("gender", false, ClaimValueType::String),
+    StandardClaimMetadata::new("birthday", false, ClaimValueType::String),
+    StandardClaimMetadata::new("birthdate", false, ClaimValueType::String),
+    StandardClaimMetadata::new("zoneinfo", false, ClaimValueType::String),
+    StandardClaimMetadata::new("locale", false, ClaimValueType::String),
+    StandardClaimMetadata::new("phone_number", false, ClaimValueType::String),
+    StandardClaimMetadata::new("phone_number_verified", false, ClaimValueType::Boolean),
+    StandardClaimMetadata::new("address", false, ClaimValueType::Object),
+    StandardClaimMetadata::new("updated_at", false, ClaimValueType::Number),
+];
+
+///
+/// Returns metadata describing each of the [`StandardClaims`] in the order in which they are
+/// serialized.
+///
+/// This is intended for tooling that documents the token format or generates JSON schemas.
+///
+pub fn standard_claims_metadata() -> &'static [StandardClaimMetadata] {
+    STANDARD_CLAIMS_METADATA
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::core::CoreGenderClaim;
+    use crate::{
+        standard_claims_metadata, AdditionalClaims, ClaimValueType, ClaimsVerificationError,
+        EndUserEmail, EndUserFamilyName, EndUserName, GroupsClaims, IdTokenClaims, LanguageTag,
+        LocalizedClaim, MergedClaims, PreservedClaims, StandardClaims, SubjectIdentifier,
+        UserInfoClaims,
+    };
+
+    #[test]
+    fn test_preserved_claims_round_trip() {
+        let id_token_json = serde_json::json!({
+            "iss": "https://server.example.com",
+            "sub": "24400320",
+            "aud": ["s6BhdRkqt3"],
+            "exp": 1311281970,
+            "iat": 1311280970,
+            "name": "Jane Doe",
+            "name#ja-Kana-JP": "ジェーン ドウ",
+            "tfa_method": "u2f",
+            "https://example.com/tenant": {"id": 42, "regions": ["eu", "us"]}
+        });
+        let claims = serde_json::from_value::<IdTokenClaims<PreservedClaims, CoreGenderClaim>>(
+            id_token_json.clone(),
+        )
+        .expect("failed to deserialize");
+        assert_eq!(
+            claims.additional_claims().0.keys().collect::<Vec<_>>(),
+            vec!["https://example.com/tenant", "tfa_method"]
+        );
+        assert_eq!(
+            claims.additional_claims().get("tfa_method"),
+            Some(&serde_json::json!("u2f"))
+        );
+        assert_eq!(
+            serde_json::to_value(&claims).expect("failed to serialize"),
+            id_token_json
+        );
+
+        let user_info_json = serde_json::json!({
+            "sub": "24400320",
+            "email": "janedoe@example.com",
+            "department": "engineering"
+        });
+        let user_info_claims = UserInfoClaims::<PreservedClaims, CoreGenderClaim>::from_json::<
+            std::io::Error,
+        >(user_info_json.to_string().as_bytes(), None)
+        .expect("failed to deserialize");
+        assert_eq!(
+            serde_json::to_value(&user_info_claims).expect("failed to serialize"),
+            user_info_json
+        );
+    }
+
+    #[test]
+    fn test_merged_claims() {
+        let claims_json = serde_json::json!({
+            "sub": "24400320",
+            "name": "Jane Doe",
+            "name#ja-Kana-JP": "ジェーン ドウ",
+            "email": "janedoe@example.com",
+            "email_verified": true,
+            "updated_at": 1311280970,
+            "tfa_method": "u2f",
+            "https://example.com/tenant": {"id": 42}
+        });
+        let claims =
+            serde_json::from_value::<MergedClaims<CoreGenderClaim>>(claims_json.clone()).unwrap();
+
+        let standard_claims = claims.standard_claims();
+        assert_eq!(**standard_claims.subject(), "24400320");
+        assert_eq!(
+            standard_claims
+                .name()
+                .and_then(|name| name.get(Some(&LanguageTag::new("ja-Kana-JP".to_string()))))
+                .map(|name| name.as_str()),
+            Some("ジェーン ドウ")
+        );
+        assert_eq!(
+            standard_claims.email(),
+            Some(&EndUserEmail::new("janedoe@example.com".to_string()))
+        );
+        assert_eq!(standard_claims.email_verified(), Some(true));
+        assert_eq!(
+            standard_claims.updated_at(),
+            Utc.timestamp_opt(1311280970, 0).single()
+        );
+
+        // Only the claims that aren't Standard Claims are captured as JSON.
+        assert_eq!(claims.additional_claims().len(), 2);
+        assert_eq!(
+            claims.additional("tfa_method"),
+            Some(&serde_json::json!("u2f"))
+        );
+        assert_eq!(
+            claims.additional("https://example.com/tenant"),
+            Some(&serde_json::json!({"id": 42}))
+        );
+        assert_eq!(claims.additional("email"), None);
+        assert_eq!(claims.additional("name#ja-Kana-JP"), None);
+        assert_eq!(claims.additional("missing"), None);
+
+        assert_eq!(serde_json::to_value(&claims).unwrap(), claims_json);
+
+        let mut additional_claims = serde_json::Map::new();
+        additional_claims.insert("tfa_method".to_string(), serde_json::json!("u2f"));
+        let claims = MergedClaims::<CoreGenderClaim>::new(
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+            additional_claims,
+        );
+        assert_eq!(
+            serde_json::to_value(&claims).unwrap(),
+            serde_json::json!({"sub": "24400320", "tfa_method": "u2f"})
+        );
+    }
+
+    #[test]
+    fn test_standard_claims_metadata() {
+        let claims = serde_json::from_str::<StandardClaims<CoreGenderClaim>>(
+            "{
+                \"sub\": \"24400320\",
+                \"name\": \"Jane Doe\",
+                \"name#ja-Kana-JP\": \"ジェーン ドウ\",
+                \"given_name\": \"Jane\",
+                \"family_name\": \"Doe\",
+                \"middle_name\": \"Middle\",
+                \"nickname\": \"JD\",
+                \"preferred_username\": \"j.doe\",
+                \"profile\": \"https://example.com/janedoe\",
+                \"picture\": \"https://example.com/janedoe/me.jpg\",
+                \"website\": \"https://example.com\",
+                \"email\": \"janedoe@example.com\",
+                \"email_verified\": true,
+                \"gender\": \"female\",
+                \"birthday\": \"1970-01-01\",
+                \"birthdate\": \"1970-01-01\",
+                \"zoneinfo\": \"America/Los_Angeles\",
+                \"locale\": \"en-US\",
+                \"phone_number\": \"+1 (310) 123-4567\",
+                \"phone_number_verified\": false,
+                \"address\": {\"country\": \"US\"},
+                \"updated_at\": 1311280970
+            }",
+        )
+        .expect("failed to deserialize");
+        let serialized = match serde_json::to_value(&claims).expect("failed to serialize") {
+            serde_json::Value::Object(map) => map,
+            other => panic!("unexpected JSON value: {:?}", other),
+        };
+
+        let metadata = standard_claims_metadata();
+        assert_eq!(
+            metadata
+                .iter()
+                .map(|claim| claim.name())
+                .collect::<BTreeSet<_>>(),
+            serialized
+                .keys()
+                .filter(|key| !key.contains('#'))
+                .map(String::as_str)
+                .collect::<BTreeSet<_>>()
+        );
+        assert_eq!(metadata.len(), serialized.len() - 1);
+        for (key, value) in serialized.iter() {
+            let (name, language_tag) = match key.split_once('#') {
+                Some((name, language_tag)) => (name, Some(language_tag)),
+                None => (key.as_str(), None),
+            };
+            let claim = metadata
+                .iter()
+                .find(|claim| claim.name() == name)
+                .unwrap_or_else(|| panic!("missing metadata for `{}`", key));
+            assert!(language_tag.is_none() || claim.is_localized());
+            let value_type = match value {
+                serde_json::Value::Bool(_) => ClaimValueType::Boolean,
+                serde_json::Value::Number(_) => ClaimValueType::Number,
+                serde_json::Value::Object(_) => ClaimValueType::Object,
+                serde_json::Value::String(_) => ClaimValueType::String,
+                other => panic!("unexpected JSON value: {:?}", other),
+            };
+            assert_eq!(claim.value_type(), value_type, "{}", key);
+        }
+        assert!(serialized.contains_key("name#ja-Kana-JP"));
+    }
+
+    #[test]
+    fn test_standard_claims_language_tag_case() {
+        let claims = serde_json::from_str::<StandardClaims<CoreGenderClaim>>(
+            "{
+                \"sub\": \"24400320\",
+                \"name#EN-us\": \"Jane Doe\",
+                \"family_name#zh-hant-tw\": \"杜\"
+            }",
+        )
+        .expect("failed to deserialize");
+        let name = claims.name().unwrap();
+        let family_name = claims.family_name().unwrap();
+
+        #[cfg(not(feature = "preserve-language-tag-case"))]
+        {
+            assert_eq!(
+                name.get(Some(&LanguageTag::new("en-US".to_string()))),
+                Some(&EndUserName::new("Jane Doe".to_string()))
+            );
+            assert_eq!(
+                family_name.languages().collect::<Vec<_>>(),
+                vec![Some(&LanguageTag::new("zh-Hant-TW".to_string()))]
+            );
+            assert_eq!(
+                serde_json::to_value(&claims).expect("failed to serialize"),
+                serde_json::json!({
+                    "sub": "24400320",
+                    "name#en-US": "Jane Doe",
+                    "family_name#zh-Hant-TW": "杜",
+                })
+            );
+        }
+
+        #[cfg(feature = "preserve-language-tag-case")]
+        {
+            assert_eq!(
+                name.get(Some(&LanguageTag::new("EN-us".to_string()))),
+                Some(&EndUserName::new("Jane Doe".to_string()))
+            );
+            assert_eq!(name.get(Some(&LanguageTag::new("en-US".to_string()))), None);
+            assert_eq!(
+                family_name.languages().collect::<Vec<_>>(),
+                vec![Some(&LanguageTag::new("zh-hant-tw".to_string()))]
+            );
+            assert_eq!(
+                serde_json::to_value(&claims).expect("failed to serialize"),
+                serde_json::json!({
+                    "sub": "24400320",
+                    "name#EN-us": "Jane Doe",
+                    "family_name#zh-hant-tw": "杜",
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_standard_claims_merge() {
+        let sub = SubjectIdentifier::new("subject".to_string());
+        let id_token_claims = StandardClaims::<CoreGenderClaim>::new(sub.clone())
+            .set_name(Some(
+                vec![
+                    (None, EndUserName::new("Jane".to_string())),
+                    (
+                        Some(LanguageTag::new("en".to_string())),
+                        EndUserName::new("Jane (en)".to_string()),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+            ))
+            .set_email(Some(EndUserEmail::new("old@example.com".to_string())))
+            .set_email_verified(Some(false));
+        let user_info_claims = StandardClaims::<CoreGenderClaim>::new(sub.clone())
+            .set_name(Some(
+                vec![(
+                    Some(LanguageTag::new("de".to_string())),
+                    EndUserName::new("Jana".to_string()),
+                )]
+                .into_iter()
+                .collect::<LocalizedClaim<_>>(),
+            ))
+            .set_email(Some(EndUserEmail::new("new@example.com".to_string())));
+
+        let merged = id_token_claims.merge(user_info_claims).unwrap();
+        assert_eq!(merged.subject(), &sub);
+
+        // Localized claims are merged per language.
+        let name = merged.name().unwrap();
+        assert_eq!(name.get(None).unwrap().as_str(), "Jane");
+        assert_eq!(
+            name.get(Some(&LanguageTag::new("en".to_string())))
+                .unwrap()
+                .as_str(),
+            "Jane (en)"
+        );
+        assert_eq!(
+            name.get(Some(&LanguageTag::new("de".to_string())))
+                .unwrap()
+                .as_str(),
+            "Jana"
+        );
+
+        // Scalar claims present in both are overwritten, while absent ones are left untouched.
+        assert_eq!(merged.email().unwrap().as_str(), "new@example.com");
+        assert_eq!(merged.email_verified(), Some(false));
+        assert_eq!(merged.given_name(), None);
+    }
+
+    #[test]
+    fn test_standard_claims_merge_conflicting_subject() {
+        let claims =
+            StandardClaims::<CoreGenderClaim>::new(SubjectIdentifier::new("a".to_string()));
+        let other = StandardClaims::<CoreGenderClaim>::new(SubjectIdentifier::new("b".to_string()))
+            .set_email(Some(EndUserEmail::new("b@example.com".to_string())));
+
+        match claims.merge(other) {
+            Err(ClaimsVerificationError::InvalidSubject(msg)) => {
+                assert_eq!(msg, "expected `a` (found `b`)")
             }
+            other => panic!("unexpected result: {:?}", other),
         }
     }
+
+    #[test]
+    fn test_groups_claims_serde() {
+        let claims: GroupsClaims = serde_json::from_str("{}").unwrap();
+        assert_eq!(claims, GroupsClaims::default());
+        assert_eq!(serde_json::to_string(&claims).unwrap(), "{}");
+
+        let json = "{\"groups\":[\"admins\",\"users\"],\"roles\":[\"editor\"]}";
+        let claims: GroupsClaims = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            claims.groups,
+            Some(vec!["admins".to_string(), "users".to_string()])
+        );
+        assert_eq!(claims.roles, Some(vec!["editor".to_string()]));
+        assert_eq!(claims.entitlements, None);
+        assert_eq!(serde_json::to_string(&claims).unwrap(), json);
+    }
+
+    #[test]
+    fn test_groups_claims_flatten() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct CustomClaims {
+            tenant: String,
+            #[serde(flatten)]
+            groups: GroupsClaims,
+        }
+        impl AdditionalClaims for CustomClaims {}
+
+        let json = "{\"tenant\":\"acme\",\"entitlements\":[\"billing\"]}";
+        let claims: CustomClaims = serde_json::from_str(json).unwrap();
+        assert_eq!(claims.tenant, "acme");
+        assert_eq!(
+            claims.groups.entitlements,
+            Some(vec!["billing".to_string()])
+        );
+        assert_eq!(serde_json::to_string(&claims).unwrap(), json);
+    }
+
+    #[test]
+    fn test_standard_claims_updated_at_seconds() {
+        let claims: StandardClaims<CoreGenderClaim> =
+            serde_json::from_str("{\"sub\":\"subject\",\"updated_at\":1311283970.5}").unwrap();
+        assert_eq!(
+            claims.updated_at().map(|updated_at| updated_at.timestamp()),
+            Some(1311283970)
+        );
+        assert_eq!(
+            claims.updated_at_seconds().unwrap().to_string(),
+            "1311283970.5"
+        );
+        // The raw value is serialized as received.
+        assert_eq!(
+            serde_json::to_string(&claims).unwrap(),
+            "{\"sub\":\"subject\",\"updated_at\":1311283970.5}"
+        );
+
+        // Values beyond the range supported by chrono are retained as raw seconds.
+        let claims: StandardClaims<CoreGenderClaim> =
+            serde_json::from_str("{\"sub\":\"subject\",\"updated_at\":1e20}").unwrap();
+        assert_eq!(claims.updated_at(), None);
+        assert_eq!(claims.updated_at_seconds().unwrap().as_f64(), Some(1e20));
+
+        let claims = claims.set_updated_at(Some(Utc.timestamp_opt(1000, 0).single().unwrap()));
+        assert_eq!(claims.updated_at_seconds().unwrap().as_i64(), Some(1000));
+        assert_eq!(
+            serde_json::to_string(&claims).unwrap(),
+            "{\"sub\":\"subject\",\"updated_at\":1000}"
+        );
+
+        let claims = claims.set_updated_at(None);
+        assert_eq!(claims.updated_at_seconds(), None);
+        assert_eq!(
+            serde_json::to_string(&claims).unwrap(),
+            "{\"sub\":\"subject\"}"
+        );
+    }
+
+    #[test]
+    fn test_standard_claims_address() {
+        let nested: StandardClaims<CoreGenderClaim> = serde_json::from_str(
+            "{\"sub\":\"subject\",\"address\":{\"locality\":\"Los Angeles\",\"country\":\"US\"}}",
+        )
+        .unwrap();
+        let address = nested.address().unwrap();
+        assert_eq!(address.locality.as_ref().unwrap().as_str(), "Los Angeles");
+        assert_eq!(address.country.as_ref().unwrap().as_str(), "US");
+
+        let stringified: StandardClaims<CoreGenderClaim> = serde_json::from_str(
+            "{\"sub\":\"subject\",\
+            \"address\":\"{\\\"locality\\\":\\\"Los Angeles\\\",\\\"country\\\":\\\"US\\\"}\"}",
+        )
+        .unwrap();
+        assert_eq!(stringified, nested);
+        // Addresses are always serialized as nested objects.
+        assert_eq!(
+            serde_json::to_string(&stringified).unwrap(),
+            "{\"sub\":\"subject\",\"address\":{\"locality\":\"Los Angeles\",\"country\":\"US\"}}",
+        );
+
+        serde_json::from_str::<StandardClaims<CoreGenderClaim>>(
+            "{\"sub\":\"subject\",\"address\":\"1234 Hollywood Blvd.\"}",
+        )
+        .expect_err("malformed address string should fail to deserialize");
+        serde_json::from_str::<StandardClaims<CoreGenderClaim>>(
+            "{\"sub\":\"subject\",\"address\":\"{\\\"locality\\\":1}\"}",
+        )
+        .expect_err("address string with invalid field should fail to deserialize");
+    }
+
+    #[test]
+    fn test_standard_claims_present_fields() {
+        let claims =
+            StandardClaims::<CoreGenderClaim>::new(SubjectIdentifier::new("subject".to_string()));
+        assert_eq!(claims.present_fields().collect::<Vec<_>>(), vec!["sub"]);
+
+        let claims = claims
+            .set_name(Some(LocalizedClaim::new()))
+            .set_family_name(Some(
+                vec![(
+                    Some(LanguageTag::new("de".to_string())),
+                    EndUserFamilyName::new("Doe".to_string()),
+                )]
+                .into_iter()
+                .collect(),
+            ))
+            .set_email_verified(Some(false))
+            .set_updated_at(Some(Utc.timestamp_opt(1000, 0).single().unwrap()));
+        let present = claims.present_fields().collect::<Vec<_>>();
+        // Empty localized claims are not reported.
+        assert_eq!(
+            present,
+            vec!["sub", "family_name", "email_verified", "updated_at"]
+        );
+
+        // The names match the serialized JSON keys (ignoring language tags).
+        let serialized = serde_json::to_value(&claims).unwrap();
+        let mut keys = serialized
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|key| key.split('#').next().unwrap())
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        let mut sorted_present = present;
+        sorted_present.sort_unstable();
+        assert_eq!(keys, sorted_present);
+    }
 }
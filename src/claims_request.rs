@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+///
+/// Individual claims requested via the `claims` authorization request parameter, as described in
+/// [Section 5.5 of OpenID Connect Core 1.0](
+/// https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter).
+///
+/// Each member maps a claim name to either `None` (which serializes as `null` and requests the
+/// claim in the default manner) or a [`ClaimRequestValue`] describing additional constraints.
+///
+/// See [`AuthorizationRequest::set_claims`](crate::AuthorizationRequest::set_claims).
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ClaimsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    userinfo: Option<HashMap<String, Option<ClaimRequestValue>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id_token: Option<HashMap<String, Option<ClaimRequestValue>>>,
+}
+impl ClaimsRequest {
+    ///
+    /// Initializes an empty claims request.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    field_getters_setters![
+        pub self [self] ["claims request member"] {
+            set_userinfo -> userinfo[Option<HashMap<String, Option<ClaimRequestValue>>>],
+            set_id_token -> id_token[Option<HashMap<String, Option<ClaimRequestValue>>>],
+        }
+    ];
+
+    ///
+    /// Requests that the given claim be returned from the UserInfo endpoint.
+    ///
+    pub fn add_userinfo_claim<N>(mut self, name: N, value: Option<ClaimRequestValue>) -> Self
+    where
+        N: Into<String>,
+    {
+        self.userinfo
+            .get_or_insert_with(HashMap::new)
+            .insert(name.into(), value);
+        self
+    }
+
+    ///
+    /// Requests that the given claim be returned in the ID token.
+    ///
+    pub fn add_id_token_claim<N>(mut self, name: N, value: Option<ClaimRequestValue>) -> Self
+    where
+        N: Into<String>,
+    {
+        self.id_token
+            .get_or_insert_with(HashMap::new)
+            .insert(name.into(), value);
+        self
+    }
+}
+
+///
+/// Constraints on an individual claim requested via a [`ClaimsRequest`].
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ClaimRequestValue {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    essential: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    values: Option<Vec<Value>>,
+}
+impl ClaimRequestValue {
+    ///
+    /// Initializes a claim request with no constraints.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    field_getters_setters![
+        pub self [self] ["claim request member"] {
+            set_essential -> essential[Option<bool>],
+            set_value -> value[Option<Value>],
+            set_values -> values[Option<Vec<Value>>],
+        }
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ClaimRequestValue, ClaimsRequest};
+
+    // Example from Section 5.5 of OpenID Connect Core 1.0.
+    const TEST_CLAIMS_REQUEST: &str = r#"{
+        "userinfo": {
+            "given_name": {"essential": true},
+            "nickname": null,
+            "email": {"essential": true},
+            "email_verified": {"essential": true},
+            "picture": null,
+            "http://example.info/claims/groups": null
+        },
+        "id_token": {
+            "auth_time": {"essential": true},
+            "acr": {"values": ["urn:mace:incommon:iap:silver"]},
+            "sub": {"value": "248289761001"}
+        }
+    }"#;
+
+    #[test]
+    fn test_claims_request_serde() {
+        let claims_request: ClaimsRequest = serde_json::from_str(TEST_CLAIMS_REQUEST).unwrap();
+
+        let expected = ClaimsRequest::new()
+            .add_userinfo_claim(
+                "given_name",
+                Some(ClaimRequestValue::new().set_essential(Some(true))),
+            )
+            .add_userinfo_claim("nickname", None)
+            .add_userinfo_claim(
+                "email",
+                Some(ClaimRequestValue::new().set_essential(Some(true))),
+            )
+            .add_userinfo_claim(
+                "email_verified",
+                Some(ClaimRequestValue::new().set_essential(Some(true))),
+            )
+            .add_userinfo_claim("picture", None)
+            .add_userinfo_claim("http://example.info/claims/groups", None)
+            .add_id_token_claim(
+                "auth_time",
+                Some(ClaimRequestValue::new().set_essential(Some(true))),
+            )
+            .add_id_token_claim(
+                "acr",
+                Some(
+                    ClaimRequestValue::new()
+                        .set_values(Some(vec![json!("urn:mace:incommon:iap:silver")])),
+                ),
+            )
+            .add_id_token_claim(
+                "sub",
+                Some(ClaimRequestValue::new().set_value(Some(json!("248289761001")))),
+            );
+        assert_eq!(claims_request, expected);
+
+        let id_token = claims_request.id_token().unwrap();
+        assert_eq!(
+            id_token["auth_time"].as_ref().unwrap().essential(),
+            Some(true)
+        );
+        assert_eq!(
+            id_token["sub"].as_ref().unwrap().value(),
+            Some(&json!("248289761001"))
+        );
+        assert_eq!(id_token["sub"].as_ref().unwrap().essential(), None);
+        assert!(claims_request.userinfo().unwrap()["nickname"].is_none());
+
+        // Serializing produces the exact JSON defined by the spec, including `null` members.
+        assert_eq!(
+            serde_json::to_value(&claims_request).unwrap(),
+            serde_json::from_str::<serde_json::Value>(TEST_CLAIMS_REQUEST).unwrap()
+        );
+        assert_eq!(
+            serde_json::from_value::<ClaimsRequest>(serde_json::to_value(&claims_request).unwrap())
+                .unwrap(),
+            claims_request
+        );
+    }
+
+    #[test]
+    fn test_claims_request_omits_empty_sections() {
+        let claims_request = ClaimsRequest::new().add_id_token_claim("email", None);
+        assert_eq!(
+            serde_json::to_string(&claims_request).unwrap(),
+            r#"{"id_token":{"email":null}}"#
+        );
+        assert_eq!(serde_json::to_string(&ClaimsRequest::new()).unwrap(), "{}");
+    }
+}
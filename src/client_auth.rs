@@ -0,0 +1,106 @@
+use std::borrow::Cow;
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use oauth2::ClientId;
+
+use crate::jwt::{JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
+use crate::types::helpers::serde_utc_seconds;
+use crate::{
+    Audience, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken, JsonWebTokenId,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, PrivateSigningKey,
+};
+
+pub(crate) const CLIENT_ASSERTION_TYPE_JWT_BEARER: &str =
+    "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+// Client assertions are generated immediately before each request, so they only need to remain
+// valid for long enough to tolerate modest clock skew between the client and the provider.
+const CLIENT_ASSERTION_LIFETIME: Duration = Duration::from_secs(60);
+
+// Claims of a JWT used for client authentication, as described in Section 9 of OpenID Connect
+// Core 1.0 and Section 3 of RFC 7523.
+#[derive(Debug, Deserialize, Serialize)]
+struct ClientAssertionClaims {
+    iss: ClientId,
+    sub: ClientId,
+    aud: Audience,
+    jti: JsonWebTokenId,
+    #[serde(with = "serde_utc_seconds")]
+    exp: DateTime<Utc>,
+    #[serde(with = "serde_utc_seconds")]
+    iat: DateTime<Utc>,
+}
+
+// Signs client assertions for the `private_key_jwt` client authentication method.
+#[derive(Clone)]
+pub(crate) struct ClientAssertionSigner {
+    #[allow(clippy::type_complexity)]
+    sign_fn: Arc<dyn Fn(&ClientId, &str) -> Result<String, JsonWebTokenError> + Send + Sync>,
+}
+impl ClientAssertionSigner {
+    pub fn new<JE, JS, JT, JU, K, S>(signing_key: S, alg: JS) -> Self
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT> + Send + Sync,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K> + Send + Sync + 'static,
+    {
+        Self {
+            sign_fn: Arc::new(move |client_id, audience| {
+                let issue_time = Utc::now();
+                let claims = ClientAssertionClaims {
+                    iss: client_id.clone(),
+                    sub: client_id.clone(),
+                    aud: Audience::new(audience.to_string()),
+                    jti: JsonWebTokenId::new_random(),
+                    exp: issue_time
+                        + chrono::Duration::from_std(CLIENT_ASSERTION_LIFETIME)
+                            .expect("valid duration"),
+                    iat: issue_time,
+                };
+                JsonWebToken::<JE, JS, JT, _, JsonWebTokenJsonPayloadSerde>::new(
+                    claims,
+                    &signing_key,
+                    &alg,
+                )
+                .map(|jwt| {
+                    serde_json::to_value(jwt)
+                        .ok()
+                        .and_then(|value| value.as_str().map(ToOwned::to_owned))
+                        .expect("JWT serializer did not produce a str")
+                })
+            }),
+        }
+    }
+
+    // Returns the request parameters authenticating the client to the given audience (typically
+    // the token endpoint URL).
+    pub fn params(
+        &self,
+        client_id: &ClientId,
+        audience: &str,
+    ) -> Result<Vec<(Cow<'static, str>, Cow<'static, str>)>, JsonWebTokenError> {
+        let client_assertion = (self.sign_fn)(client_id, audience)?;
+        Ok(vec![
+            (
+                Cow::Borrowed("client_assertion_type"),
+                Cow::Borrowed(CLIENT_ASSERTION_TYPE_JWT_BEARER),
+            ),
+            (
+                Cow::Borrowed("client_assertion"),
+                Cow::Owned(client_assertion),
+            ),
+        ])
+    }
+}
+impl Debug for ClientAssertionSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatterResult {
+        f.debug_struct("ClientAssertionSigner")
+            .finish_non_exhaustive()
+    }
+}
@@ -1,7 +1,10 @@
 use crate::types::Base64UrlEncodedBytes;
-use crate::{JsonWebKey, SignatureVerificationError};
+use crate::{DecryptionError, JsonWebKey, SignatureVerificationError};
 
-use super::{jwk::CoreJsonCurveType, CoreJsonWebKey, CoreJsonWebKeyType};
+use super::{
+    jwk::CoreJsonCurveType, CoreJsonWebKey, CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm,
+    CoreJweKeyManagementAlgorithm,
+};
 
 use std::ops::Deref;
 
@@ -96,7 +99,6 @@ pub fn verify_rsa_signature(
 /// According to https://briansmith.org/rustdoc/ring/signature/index.html#ecdsa__fixed-details-fixed-length-pkcs11-style-ecdsa-signatures,
 /// to recover the X and Y coordinates from an octet string, the Octet-String-To-Elliptic-Curve-Point Conversion
 /// is used (Section 2.3.4 of https://www.secg.org/sec1-v2.pdf).
-
 pub fn verify_ec_signature(
     key: &CoreJsonWebKey,
     msg: &[u8],
@@ -137,6 +139,20 @@ pub fn verify_ec_signature(
                     SignatureVerificationError::CryptoError("EC Signature was wrong".to_string())
                 })
         }
+        CoreJsonCurveType::Secp256k1 => {
+            let public_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(&pk)
+                .map_err(|e| SignatureVerificationError::InvalidKey(e.to_string()))?;
+            public_key
+                .verify(
+                    msg,
+                    &k256::ecdsa::Signature::from_slice(signature).map_err(|_| {
+                        SignatureVerificationError::CryptoError("Invalid signature".to_string())
+                    })?,
+                )
+                .map_err(|_| {
+                    SignatureVerificationError::CryptoError("EC Signature was wrong".to_string())
+                })
+        }
         CoreJsonCurveType::P521 => Err(SignatureVerificationError::UnsupportedAlg(
             "P521".to_string(),
         )),
@@ -177,6 +193,90 @@ pub fn verify_ed_signature(
     }
 }
 
+// Unwraps a Content Encryption Key (CEK) using AES Key Wrap.
+// See https://tools.ietf.org/html/rfc7518#section-4.4.
+pub fn unwrap_aes_key(
+    alg: &CoreJweKeyManagementAlgorithm,
+    kek: &[u8],
+    encrypted_key: &[u8],
+) -> Result<Vec<u8>, DecryptionError> {
+    let invalid_key =
+        |_| DecryptionError::InvalidKey("invalid AES key wrap key length".to_string());
+    match *alg {
+        CoreJweKeyManagementAlgorithm::AesKeyWrap128 => aes_kw::KekAes128::try_from(kek)
+            .map_err(invalid_key)?
+            .unwrap_vec(encrypted_key),
+        CoreJweKeyManagementAlgorithm::AesKeyWrap256 => aes_kw::KekAes256::try_from(kek)
+            .map_err(invalid_key)?
+            .unwrap_vec(encrypted_key),
+        ref other => {
+            return Err(DecryptionError::UnsupportedAlg(
+                serde_plain::to_string(other).unwrap_or_else(|err| {
+                    panic!(
+                        "key management alg {:?} failed to serialize to a string: {}",
+                        other, err
+                    )
+                }),
+            ))
+        }
+    }
+    // An integrity check failure indicates the wrong key or a tampered encrypted key.
+    .map_err(|_| DecryptionError::CryptoError)
+}
+
+pub fn decrypt_aes_gcm(
+    enc: &CoreJweContentEncryptionAlgorithm,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptionError> {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+
+    // AES-GCM uses a 96-bit IV and a 128-bit authentication tag.
+    // See https://tools.ietf.org/html/rfc7518#section-5.3.
+    let nonce: aes_gcm::Nonce<_> = <[u8; 12]>::try_from(iv)
+        .map_err(|_| {
+            DecryptionError::Other(format!(
+                "invalid AES-GCM initialization vector length {} (expected 12)",
+                iv.len()
+            ))
+        })?
+        .into();
+    if tag.len() != 16 {
+        return Err(DecryptionError::Other(format!(
+            "invalid AES-GCM authentication tag length {} (expected 16)",
+            tag.len()
+        )));
+    }
+
+    let mut msg = ciphertext.to_vec();
+    msg.extend_from_slice(tag);
+    let payload = Payload { msg: &msg, aad };
+
+    let invalid_key = |_| DecryptionError::InvalidKey("invalid AES-GCM key length".to_string());
+    match *enc {
+        CoreJweContentEncryptionAlgorithm::Aes128Gcm => aes_gcm::Aes128Gcm::new_from_slice(cek)
+            .map_err(invalid_key)?
+            .decrypt(&nonce, payload),
+        CoreJweContentEncryptionAlgorithm::Aes256Gcm => aes_gcm::Aes256Gcm::new_from_slice(cek)
+            .map_err(invalid_key)?
+            .decrypt(&nonce, payload),
+        ref other => {
+            return Err(DecryptionError::UnsupportedAlg(
+                serde_plain::to_string(other).unwrap_or_else(|err| {
+                    panic!(
+                        "encryption alg {:?} failed to serialize to a string: {}",
+                        other, err
+                    )
+                }),
+            ))
+        }
+    }
+    .map_err(|_| DecryptionError::CryptoError)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,20 +1,22 @@
 use ed25519_dalek::pkcs8::DecodePrivateKey;
 use ed25519_dalek::Signer;
+use oauth2::ClientSecret;
 use rsa::pkcs1::DecodeRsaPrivateKey;
 use sha2::Digest;
 
-use super::{crypto, CoreJwsSigningAlgorithm};
+use super::{
+    crypto, CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm,
+    CoreJwsSigningAlgorithm,
+};
 use crate::types::{check_key_compatibility, Base64UrlEncodedBytes};
 use crate::types::{helpers::deserialize_option_or_none, JsonCurveType};
 use crate::{
-    JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse, PrivateSigningKey,
-    SignatureVerificationError, SigningError,
+    DecryptionError, JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse, PrivateDecryptionKey,
+    PrivateSigningKey, SignatureVerificationError, SigningError,
 };
 
 #[cfg(feature = "jwk-alg")]
-use crate::{
-    core::CoreJweContentEncryptionAlgorithm, jwt::JsonWebTokenAlgorithm, types::JsonWebKeyAlgorithm,
-};
+use crate::{jwt::JsonWebTokenAlgorithm, types::JsonWebKeyAlgorithm};
 
 // Other than the 'kty' (key type) parameter, which must be present in all JWKs, Section 4 of RFC
 // 7517 states that "member names used for representing key parameters for different keys types
@@ -33,6 +35,14 @@ pub struct CoreJsonWebKey {
     pub(crate) use_: Option<CoreJsonWebKeyUse>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) kid: Option<JsonWebKeyId>,
+    /// The operations for which this key is intended to be used
+    /// (https://www.rfc-editor.org/rfc/rfc7517#section-4.3).
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) key_ops: Option<Vec<String>>,
 
     /// The algorithm intended to be used with this key (https://www.rfc-editor.org/rfc/rfc7517#section-4.4)
     /// It can either be an algorithm intended for use with JWS or JWE, or something different.
@@ -117,6 +127,7 @@ impl CoreJsonWebKey {
             x: None,
             y: None,
             d: None,
+            key_ops: None,
             #[cfg(feature = "jwk-alg")]
             alg: None,
         }
@@ -143,6 +154,7 @@ impl CoreJsonWebKey {
             x: Some(Base64UrlEncodedBytes::new(x)),
             y: Some(Base64UrlEncodedBytes::new(y)),
             d: None,
+            key_ops: None,
             #[cfg(feature = "jwk-alg")]
             alg: None,
         }
@@ -165,6 +177,7 @@ impl CoreJsonWebKey {
             x: Some(Base64UrlEncodedBytes::new(x)),
             y: None,
             d: None,
+            key_ops: None,
             #[cfg(feature = "jwk-alg")]
             alg: None,
         }
@@ -181,6 +194,12 @@ impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse>
     fn key_use(&self) -> Option<&CoreJsonWebKeyUse> {
         self.use_.as_ref()
     }
+    fn allows_signature_verification(&self) -> bool {
+        match self.key_ops {
+            Some(ref key_ops) => key_ops.iter().any(|key_op| key_op == "verify"),
+            None => true,
+        }
+    }
 
     fn new_symmetric(key: Vec<u8>) -> Self {
         Self {
@@ -194,6 +213,7 @@ impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse>
             x: None,
             y: None,
             d: None,
+            key_ops: None,
             #[cfg(feature = "jwk-alg")]
             alg: None,
         }
@@ -207,8 +227,7 @@ impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse>
     ) -> Result<(), SignatureVerificationError> {
         use hmac::Mac;
 
-        check_key_compatibility(self, signature_alg)
-            .map_err(|e| SignatureVerificationError::InvalidKey(e.to_owned()))?;
+        check_key_compatibility(self, signature_alg)?;
 
         match *signature_alg {
             CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256 => {
@@ -352,9 +371,22 @@ impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse>
                     ))
                 }
             }
+            CoreJwsSigningAlgorithm::EcdsaSecp256k1Sha256 => {
+                if matches!(self.crv, Some(CoreJsonCurveType::Secp256k1)) {
+                    crypto::verify_ec_signature(self, message, signature)
+                } else {
+                    Err(SignatureVerificationError::InvalidKey(
+                        "Key uses different CRV than JWT".to_string(),
+                    ))
+                }
+            }
             CoreJwsSigningAlgorithm::EdDsaEd25519 => {
                 if matches!(self.crv, Some(CoreJsonCurveType::Ed25519)) {
                     crypto::verify_ed_signature(self, message, signature)
+                } else if matches!(self.crv, Some(CoreJsonCurveType::Ed448)) {
+                    Err(SignatureVerificationError::UnsupportedAlg(
+                        "EdDSA with Ed448 curve".to_string(),
+                    ))
                 } else {
                     Err(SignatureVerificationError::InvalidKey(
                         "Key uses different CRV than JWT".to_string(),
@@ -372,6 +404,19 @@ impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse>
         }
     }
 
+    fn supports_signing_alg(&self, signature_alg: &CoreJwsSigningAlgorithm) -> bool {
+        // ECDSA algorithms are each bound to a single curve, so an EC key for one curve must not
+        // be selected for another (e.g., a secp256k1 key for ES256).
+        let required_crv = match *signature_alg {
+            CoreJwsSigningAlgorithm::EcdsaP256Sha256 => CoreJsonCurveType::P256,
+            CoreJwsSigningAlgorithm::EcdsaP384Sha384 => CoreJsonCurveType::P384,
+            CoreJwsSigningAlgorithm::EcdsaP521Sha512 => CoreJsonCurveType::P521,
+            CoreJwsSigningAlgorithm::EcdsaSecp256k1Sha256 => CoreJsonCurveType::Secp256k1,
+            _ => return true,
+        };
+        self.crv.as_ref() == Some(&required_crv)
+    }
+
     #[cfg(feature = "jwk-alg")]
     fn signing_alg(&self) -> JsonWebKeyAlgorithm<&CoreJwsSigningAlgorithm> {
         match self.alg {
@@ -427,21 +472,21 @@ impl
                     .map_err(|e| SigningError::Other(format!("Could not create key: {}", e)))?;
                 mac.update(message);
                 let result = mac.finalize();
-                Ok(result.into_bytes().as_slice().to_vec())
+                Ok(result.into_bytes().to_vec())
             }
             CoreJwsSigningAlgorithm::HmacSha384 => {
                 let mut mac = hmac::Hmac::<sha2::Sha384>::new_from_slice(&self.secret)
                     .map_err(|e| SigningError::Other(format!("Could not create key: {}", e)))?;
                 mac.update(message);
                 let result = mac.finalize();
-                Ok(result.into_bytes().as_slice().to_vec())
+                Ok(result.into_bytes().to_vec())
             }
             CoreJwsSigningAlgorithm::HmacSha512 => {
                 let mut mac = hmac::Hmac::<sha2::Sha512>::new_from_slice(&self.secret)
                     .map_err(|e| SigningError::Other(format!("Could not create key: {}", e)))?;
                 mac.update(message);
                 let result = mac.finalize();
-                Ok(result.into_bytes().as_slice().to_vec())
+                Ok(result.into_bytes().to_vec())
             }
             ref other => Err(SigningError::UnsupportedAlg(
                 serde_plain::to_string(other).unwrap_or_else(|err| {
@@ -543,6 +588,7 @@ impl
                 y: None,
                 d: None,
                 k: None,
+                key_ops: None,
                 #[cfg(feature = "jwk-alg")]
                 alg: None,
             },
@@ -702,12 +748,227 @@ impl
             x: None,
             y: None,
             d: None,
+            key_ops: None,
             #[cfg(feature = "jwk-alg")]
             alg: None,
         }
     }
 }
 
+///
+/// RSA private key for decrypting JSON Web Encryption (JWE) payloads.
+///
+/// This key supports the `RSA-OAEP` and `RSA-OAEP-256` key management algorithms with the
+/// `A128GCM` and `A256GCM` content encryption algorithms.
+///
+pub struct CoreRsaPrivateDecryptionKey {
+    key_pair: rsa::RsaPrivateKey,
+}
+impl CoreRsaPrivateDecryptionKey {
+    ///
+    /// Initializes a decryption key from an RSA private key (in PKCS#1 PEM format).
+    ///
+    pub fn from_pem(pem: &str) -> Result<Self, String> {
+        let key_pair = rsa::RsaPrivateKey::from_pkcs1_pem(pem).map_err(|err| err.to_string())?;
+        Ok(Self { key_pair })
+    }
+}
+impl
+    PrivateDecryptionKey<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJweKeyManagementAlgorithm,
+        CoreJsonWebKeyType,
+    > for CoreRsaPrivateDecryptionKey
+{
+    fn decrypt_key(
+        &self,
+        alg: &CoreJweKeyManagementAlgorithm,
+        enc: &CoreJweContentEncryptionAlgorithm,
+        encrypted_key: &[u8],
+    ) -> Result<Vec<u8>, DecryptionError> {
+        let cek = match *alg {
+            CoreJweKeyManagementAlgorithm::RsaOaep => self
+                .key_pair
+                .decrypt(rsa::Oaep::new::<sha1::Sha1>(), encrypted_key),
+            CoreJweKeyManagementAlgorithm::RsaOaepSha256 => self
+                .key_pair
+                .decrypt(rsa::Oaep::new::<sha2::Sha256>(), encrypted_key),
+            ref other => {
+                return Err(DecryptionError::UnsupportedAlg(
+                    serde_plain::to_string(other).unwrap_or_else(|err| {
+                        panic!(
+                            "key management alg {:?} failed to serialize to a string: {}",
+                            other, err
+                        )
+                    }),
+                ))
+            }
+        }
+        .map_err(|_| DecryptionError::CryptoError)?;
+
+        if cek.len() != cek_len(enc).unwrap_or(cek.len()) {
+            return Err(DecryptionError::CryptoError);
+        }
+        Ok(cek)
+    }
+
+    fn decrypt_content(
+        &self,
+        enc: &CoreJweContentEncryptionAlgorithm,
+        cek: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<Vec<u8>, DecryptionError> {
+        crypto::decrypt_aes_gcm(enc, cek, iv, aad, ciphertext, tag)
+    }
+}
+
+// Returns the Content Encryption Key (CEK) length required by the given content encryption
+// algorithm, or `None` if the algorithm is unsupported (in which case it's rejected by
+// decrypt_content()).
+fn cek_len(enc: &CoreJweContentEncryptionAlgorithm) -> Option<usize> {
+    match *enc {
+        CoreJweContentEncryptionAlgorithm::Aes128Gcm => Some(16),
+        CoreJweContentEncryptionAlgorithm::Aes256Gcm => Some(32),
+        _ => None,
+    }
+}
+
+///
+/// Symmetric key for decrypting JSON Web Encryption (JWE) payloads.
+///
+/// This key supports the `dir` (direct encryption), `A128KW`, and `A256KW` (AES Key Wrap) key
+/// management algorithms with the `A128GCM` and `A256GCM` content encryption algorithms.
+///
+pub struct CoreSymmetricDecryptionKey {
+    key: Vec<u8>,
+    is_client_secret: bool,
+}
+impl CoreSymmetricDecryptionKey {
+    ///
+    /// Initializes a decryption key from raw key bytes.
+    ///
+    /// The key must be exactly the length required by the key management algorithm (i.e., 16
+    /// bytes for `A128KW` and 32 bytes for `A256KW`), or, for direct encryption, by the content
+    /// encryption algorithm (i.e., 16 bytes for `A128GCM` and 32 bytes for `A256GCM`).
+    ///
+    pub fn new(key: &[u8]) -> Self {
+        Self {
+            key: key.to_vec(),
+            is_client_secret: false,
+        }
+    }
+
+    ///
+    /// Initializes a decryption key from the client secret.
+    ///
+    /// As described in [Section 10.2 of OpenID Connect Core 1.0](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#Encryption), the symmetric key is
+    /// derived from the left-most bits of the SHA-256 hash of the client secret, truncated to the
+    /// key length required by the algorithm.
+    ///
+    pub fn from_client_secret(client_secret: &ClientSecret) -> Self {
+        Self {
+            key: client_secret.secret().as_bytes().to_vec(),
+            is_client_secret: true,
+        }
+    }
+
+    fn key(&self, len: usize) -> Result<Vec<u8>, DecryptionError> {
+        if self.is_client_secret {
+            // Keys of up to 256 bits are derived using SHA-256, which is sufficient for all of the
+            // supported algorithms.
+            let hash = sha2::Sha256::digest(&self.key);
+            hash.get(..len).map(<[u8]>::to_vec).ok_or_else(|| {
+                DecryptionError::InvalidKey(format!(
+                    "cannot derive {}-bit key from client secret",
+                    len * 8
+                ))
+            })
+        } else if self.key.len() == len {
+            Ok(self.key.clone())
+        } else {
+            Err(DecryptionError::InvalidKey(format!(
+                "invalid symmetric key length {} (expected {})",
+                self.key.len(),
+                len
+            )))
+        }
+    }
+}
+impl
+    PrivateDecryptionKey<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJweKeyManagementAlgorithm,
+        CoreJsonWebKeyType,
+    > for CoreSymmetricDecryptionKey
+{
+    fn decrypt_key(
+        &self,
+        alg: &CoreJweKeyManagementAlgorithm,
+        enc: &CoreJweContentEncryptionAlgorithm,
+        encrypted_key: &[u8],
+    ) -> Result<Vec<u8>, DecryptionError> {
+        let unsupported_enc = || {
+            DecryptionError::UnsupportedAlg(serde_plain::to_string(enc).unwrap_or_else(|err| {
+                panic!(
+                    "encryption alg {:?} failed to serialize to a string: {}",
+                    enc, err
+                )
+            }))
+        };
+        let cek_len = cek_len(enc).ok_or_else(unsupported_enc)?;
+
+        let cek = match *alg {
+            // With direct encryption, the JWE Encrypted Key must be empty. See
+            // https://tools.ietf.org/html/rfc7516#section-5.2.
+            CoreJweKeyManagementAlgorithm::Direct => {
+                if !encrypted_key.is_empty() {
+                    return Err(DecryptionError::Other(
+                        "JWE encrypted key must be empty when using direct encryption".to_string(),
+                    ));
+                }
+                self.key(cek_len)?
+            }
+            CoreJweKeyManagementAlgorithm::AesKeyWrap128 => {
+                crypto::unwrap_aes_key(alg, &self.key(16)?, encrypted_key)?
+            }
+            CoreJweKeyManagementAlgorithm::AesKeyWrap256 => {
+                crypto::unwrap_aes_key(alg, &self.key(32)?, encrypted_key)?
+            }
+            ref other => {
+                return Err(DecryptionError::UnsupportedAlg(
+                    serde_plain::to_string(other).unwrap_or_else(|err| {
+                        panic!(
+                            "key management alg {:?} failed to serialize to a string: {}",
+                            other, err
+                        )
+                    }),
+                ))
+            }
+        };
+
+        if cek.len() != cek_len {
+            return Err(DecryptionError::CryptoError);
+        }
+        Ok(cek)
+    }
+
+    fn decrypt_content(
+        &self,
+        enc: &CoreJweContentEncryptionAlgorithm,
+        cek: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<Vec<u8>, DecryptionError> {
+        crypto::decrypt_aes_gcm(enc, cek, iv, aad, ciphertext, tag)
+    }
+}
+
 ///
 /// Type of JSON Web Key.
 ///
@@ -761,10 +1022,20 @@ pub enum CoreJsonCurveType {
     #[serde(rename = "P-521")]
     P521,
     ///
+    /// secp256k1 Curve
+    ///
+    #[serde(rename = "secp256k1")]
+    Secp256k1,
+    ///
     /// Ed25519 Curve
     ///
     #[serde(rename = "Ed25519")]
     Ed25519,
+    ///
+    /// Ed448 Curve (currently not supported)
+    ///
+    #[serde(rename = "Ed448")]
+    Ed448,
 }
 impl JsonCurveType for CoreJsonWebKeyType {}
 
@@ -1058,6 +1329,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_eddsa_alg_names() {
+        assert_eq!(
+            serde_json::from_str::<CoreJwsSigningAlgorithm>("\"EdDSA\"").unwrap(),
+            CoreJwsSigningAlgorithm::EdDsaEd25519
+        );
+        assert_eq!(
+            serde_json::from_str::<CoreJwsSigningAlgorithm>("\"Ed25519\"").unwrap(),
+            CoreJwsSigningAlgorithm::EdDsaEd25519
+        );
+
+        // Known-answer test using the example from RFC 8037, Appendix A.4.
+        let key: CoreJsonWebKey = serde_json::from_str(
+            "{
+            \"kty\": \"OKP\",
+            \"crv\": \"Ed25519\",
+            \"x\": \"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo\"
+        }",
+        )
+        .expect("deserialization failed");
+        verify_signature(
+            &key,
+            &CoreJwsSigningAlgorithm::EdDsaEd25519,
+            "eyJhbGciOiJFZERTQSJ9.RXhhbXBsZSBvZiBFZDI1NTE5IHNpZ25pbmc",
+            "hgyY0il_MGCjP0JzlnLWG1PPOt7-09PGcvMg3AIbQR6dWbhijcNR4ki4iylGjg5BhVsPt9g7sVvpAr_MuM0KAg",
+        );
+    }
+
+    #[test]
+    fn test_eddsa_ed448_unsupported() {
+        let key: CoreJsonWebKey = serde_json::from_str(
+            "{
+            \"kty\": \"OKP\",
+            \"use\": \"sig\",
+            \"alg\": \"EdDSA\",
+            \"crv\": \"Ed448\",
+            \"x\": \"X9dEm1m0Yf0s54fsYWrUah2hNCSFpw4fig6nXYDpZ3jt8SR2m0bHBhvWeD3x5Q9s0foavq_oJWGA\"
+        }",
+        )
+        .expect("deserialization failed");
+        assert_eq!(key.crv, Some(CoreJsonCurveType::Ed448));
+
+        match key.verify_signature(
+            &CoreJwsSigningAlgorithm::EdDsaEd25519,
+            "eyJhbGciOiJFZERTQSJ9.eyJpc3MiOiJqb2UifQ".as_bytes(),
+            &[0; 114],
+        ) {
+            Err(SignatureVerificationError::UnsupportedAlg(alg)) => {
+                assert_eq!(alg, "EdDSA with Ed448 curve")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_eddsa_verification() {
         let key_ed25519: CoreJsonWebKey =
@@ -1103,7 +1428,7 @@ mod tests {
         {
             let error_msg = "key type does not match signature algorithm".to_string();
             match err {
-                SignatureVerificationError::InvalidKey(msg) => {
+                SignatureVerificationError::KeyTypeMismatch(msg) => {
                     if msg != error_msg {
                         panic!("The error should be about key type")
                     }
@@ -1200,6 +1525,104 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ecdsa_secp256k1_verification() {
+        let key_secp256k1: CoreJsonWebKey = serde_json::from_str(
+            "{
+            \"kty\": \"EC\",
+            \"crv\": \"secp256k1\",
+            \"x\": \"4d6LL0tdeZ-Q0NSLiE9BN_RfG12B6D2tqPPooE3qXBA\",
+            \"y\": \"gxus5tnuzZdG9D-IcmyH1ULPlC3KMLobVxPd2lEoSpw\"
+        }",
+        )
+        .expect("deserialization failed");
+        assert_eq!(key_secp256k1.crv, Some(CoreJsonCurveType::Secp256k1));
+        assert_eq!(
+            serde_json::from_str::<CoreJwsSigningAlgorithm>("\"ES256K\"").unwrap(),
+            CoreJwsSigningAlgorithm::EcdsaSecp256k1Sha256
+        );
+
+        let signing_input = "eyJhbGciOiJFUzI1NksifQ.eyJpc3MiOiJqb2UiLCJleHAiOjEzMDA4MTkzODB9";
+        let signature_secp256k1 =
+            "CakDthdGrWFajgyRVdtggLBBJfSkb2aY62AE39fyPhFkx8N4_ziG5jJry4YzaMBKAG\
+                                   gM9vGCHspHJVyeRptA4A";
+        verify_signature(
+            &key_secp256k1,
+            &CoreJwsSigningAlgorithm::EcdsaSecp256k1Sha256,
+            signing_input,
+            signature_secp256k1,
+        );
+
+        // A P-256 signature must not verify against the secp256k1 key.
+        verify_invalid_signature(
+            &key_secp256k1,
+            &CoreJwsSigningAlgorithm::EcdsaSecp256k1Sha256,
+            signing_input,
+            "EnKCtAHhzhqxV2GTr1VEurse2kQ7oHpFoVqM66sYGlmahDRGSlfrVAsGCzdLv66OS2Qf1zt6OPHX-5ZAkMgzlA",
+        );
+
+        match key_secp256k1
+            .verify_signature(
+                &CoreJwsSigningAlgorithm::EcdsaP256Sha256,
+                signing_input.as_bytes(),
+                signature_secp256k1.as_bytes(),
+            )
+            .expect_err("verification should fail")
+        {
+            SignatureVerificationError::InvalidKey(msg) => {
+                assert_eq!(msg, "Key uses different CRV than JWT")
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let key_p256: CoreJsonWebKey =
+            serde_json::from_str(TEST_EC_PUB_KEY_P256).expect("deserialization failed");
+        match key_p256
+            .verify_signature(
+                &CoreJwsSigningAlgorithm::EcdsaSecp256k1Sha256,
+                signing_input.as_bytes(),
+                signature_secp256k1.as_bytes(),
+            )
+            .expect_err("verification should fail")
+        {
+            SignatureVerificationError::InvalidKey(msg) => {
+                assert_eq!(msg, "Key uses different CRV than JWT")
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ecdsa_key_selection_by_curve() {
+        let key_secp256k1: CoreJsonWebKey = serde_json::from_str(
+            "{
+            \"kty\": \"EC\",
+            \"crv\": \"secp256k1\",
+            \"x\": \"4d6LL0tdeZ-Q0NSLiE9BN_RfG12B6D2tqPPooE3qXBA\",
+            \"y\": \"gxus5tnuzZdG9D-IcmyH1ULPlC3KMLobVxPd2lEoSpw\"
+        }",
+        )
+        .expect("deserialization failed");
+        let key_p256: CoreJsonWebKey =
+            serde_json::from_str(TEST_EC_PUB_KEY_P256).expect("deserialization failed");
+
+        // Neither key has a matching `kid`, so selection relies solely on the curve.
+        let jwks = CoreJsonWebKeySet::new(vec![key_secp256k1.clone(), key_p256.clone()]);
+        assert_eq!(
+            jwks.filter_keys(&None, &CoreJwsSigningAlgorithm::EcdsaP256Sha256),
+            vec![&key_p256]
+        );
+        assert_eq!(
+            jwks.filter_keys(&None, &CoreJwsSigningAlgorithm::EcdsaSecp256k1Sha256),
+            vec![&key_secp256k1]
+        );
+
+        let jwks = CoreJsonWebKeySet::new(vec![key_secp256k1]);
+        assert!(jwks
+            .filter_keys(&None, &CoreJwsSigningAlgorithm::EcdsaP256Sha256)
+            .is_empty());
+    }
+
     #[test]
     fn test_rsa_pkcs1_verification() {
         let key: CoreJsonWebKey =
@@ -1258,7 +1681,7 @@ mod tests {
             )
             .expect_err("signature verification should fail")
         {
-            SignatureVerificationError::InvalidKey(_) => {}
+            SignatureVerificationError::KeyTypeMismatch(_) => {}
             other => panic!("unexpected error: {:?}", other),
         }
 
@@ -1367,6 +1790,270 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rsa_pss_verification_tampered() {
+        let key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PUB_KEY).expect("deserialization failed");
+        let pss_signing_input =
+            "eyJhbGciOiJQUzM4NCIsImtpZCI6ImJpbGJvLmJhZ2dpbnNAaG9iYml0b24uZXhhbXBsZSJ9.\
+             SXTigJlzIGEgZGFuZ2Vyb3VzIGJ1c2luZXNzLCBGcm9kbywgZ29pbmcgb3V0IH\
+             lvdXIgZG9vci4gWW91IHN0ZXAgb250byB0aGUgcm9hZCwgYW5kIGlmIHlvdSBk\
+             b24ndCBrZWVwIHlvdXIgZmVldCwgdGhlcmXigJlzIG5vIGtub3dpbmcgd2hlcm\
+             UgeW91IG1pZ2h0IGJlIHN3ZXB0IG9mZiB0by4";
+        // Same as the valid PS256 signature above, with the first character changed.
+        let tampered_signature =
+            "Z62we_hs07d0qJ2cT_QpbrodwDhPK9rEpNX2b3GqLHFM18YtDlPCr40Xf_yLIosIrt\
+             mMP4NgDSCkn2qOcRJBD8zrHumER4JIkGZbRIwU8gYms8xKX2HaveK9vrOjbHoWLjOU\
+             nyNpprYUFGdRZ6oebT61bqU2CZrJG_GcqR87W8FOn7kqrCPI7B8oNHgliMke49hOpz\
+             mluL20BKN5Mb3O42nwgmiONZK0Pjm2GTIAYRUvNQ741aCWVJ3rnWvo99qWhe86ap_H\
+             v40SUSaMwJig5AqC-wHIzYaYU0PlQbi83Dgw7Zft9kL2dGB0vMWY_h2HDgZU0teAcK\
+             SkhyH8ZDRyYQ";
+
+        verify_invalid_signature(
+            &key,
+            &CoreJwsSigningAlgorithm::RsaSsaPssSha256,
+            pss_signing_input,
+            tampered_signature,
+        );
+        // A valid PSS signature must not verify as PKCS#1 v1.5.
+        verify_invalid_signature(
+            &key,
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            pss_signing_input,
+            "Y62we_hs07d0qJ2cT_QpbrodwDhPK9rEpNX2b3GqLHFM18YtDlPCr40Xf_yLIosIrt\
+             mMP4NgDSCkn2qOcRJBD8zrHumER4JIkGZbRIwU8gYms8xKX2HaveK9vrOjbHoWLjOU\
+             nyNpprYUFGdRZ6oebT61bqU2CZrJG_GcqR87W8FOn7kqrCPI7B8oNHgliMke49hOpz\
+             mluL20BKN5Mb3O42nwgmiONZK0Pjm2GTIAYRUvNQ741aCWVJ3rnWvo99qWhe86ap_H\
+             v40SUSaMwJig5AqC-wHIzYaYU0PlQbi83Dgw7Zft9kL2dGB0vMWY_h2HDgZU0teAcK\
+             SkhyH8ZDRyYQ",
+        );
+    }
+
+    #[test]
+    fn test_rsa_key_selection() {
+        // RSA keys without an `alg` are usable for both RSASSA-PKCS1-v1_5 and RSASSA-PSS.
+        let jwks = CoreJsonWebKeySet::new(vec![
+            serde_json::from_str(TEST_RSA_PUB_KEY).expect("deserialization failed")
+        ]);
+        let kid = Some(JsonWebKeyId::new(
+            "bilbo.baggins@hobbiton.example".to_string(),
+        ));
+        for alg in [
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            CoreJwsSigningAlgorithm::RsaSsaPssSha256,
+            CoreJwsSigningAlgorithm::RsaSsaPssSha512,
+        ] {
+            assert_eq!(jwks.filter_keys(&kid, &alg).len(), 1, "{:?}", alg);
+        }
+        assert!(jwks
+            .filter_keys(&kid, &CoreJwsSigningAlgorithm::EcdsaP256Sha256)
+            .is_empty());
+
+        // Keys with an `alg` are restricted to that algorithm.
+        #[cfg(feature = "jwk-alg")]
+        {
+            let mut key: CoreJsonWebKey =
+                serde_json::from_str(TEST_RSA_PUB_KEY).expect("deserialization failed");
+            key.alg = Some(JsonWebTokenAlgorithm::Signature(
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                std::marker::PhantomData,
+            ));
+            let jwks = CoreJsonWebKeySet::new(vec![key]);
+            assert_eq!(
+                jwks.filter_keys(&kid, &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256)
+                    .len(),
+                1
+            );
+            assert!(jwks
+                .filter_keys(&kid, &CoreJwsSigningAlgorithm::RsaSsaPssSha256)
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_jwks_large_key_set_selection() {
+        let key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PUB_KEY).expect("deserialization failed");
+        let jwks = CoreJsonWebKeySet::new(
+            (0..1000)
+                .map(|i| CoreJsonWebKey {
+                    kid: Some(JsonWebKeyId::new(format!("key-{}", i))),
+                    ..key.clone()
+                })
+                .collect(),
+        );
+
+        let kid = JsonWebKeyId::new("key-637".to_string());
+        let selected = jwks.key_by_id(&kid).expect("key should be found");
+        assert!(std::ptr::eq(selected, &jwks.keys()[637]));
+        let filtered = jwks.filter_keys(
+            &Some(kid.clone()),
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        );
+        assert_eq!(filtered.len(), 1);
+        assert!(std::ptr::eq(filtered[0], &jwks.keys()[637]));
+        assert!(jwks
+            .filter_keys(
+                &Some(kid.clone()),
+                &CoreJwsSigningAlgorithm::EcdsaP256Sha256
+            )
+            .is_empty());
+
+        let unknown_kid = JsonWebKeyId::new("key-1000".to_string());
+        assert!(jwks.key_by_id(&unknown_kid).is_none());
+        assert!(jwks
+            .filter_keys(
+                &Some(unknown_kid),
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256
+            )
+            .is_empty());
+
+        // The index is rebuilt when the key set is deserialized or cloned.
+        let deserialized = serde_json::from_str::<CoreJsonWebKeySet>(
+            &serde_json::to_string(&jwks).expect("serialization failed"),
+        )
+        .expect("deserialization failed");
+        assert_eq!(deserialized, jwks);
+        assert_eq!(deserialized.key_by_id(&kid), Some(&jwks.keys()[637]));
+        assert_eq!(jwks.clone().key_by_id(&kid), Some(&jwks.keys()[637]));
+    }
+
+    #[test]
+    fn test_jwks_no_kid_selection() {
+        let rsa_key: CoreJsonWebKey = CoreJsonWebKey {
+            kid: None,
+            ..serde_json::from_str(TEST_RSA_PUB_KEY).expect("deserialization failed")
+        };
+        let rsa_enc_key = CoreJsonWebKey {
+            use_: Some(CoreJsonWebKeyUse::Encryption),
+            ..rsa_key.clone()
+        };
+        let ec_key: CoreJsonWebKey = CoreJsonWebKey {
+            kid: None,
+            ..serde_json::from_str(TEST_EC_PUB_KEY_P256).expect("deserialization failed")
+        };
+        let jwks =
+            CoreJsonWebKeySet::new(vec![rsa_enc_key.clone(), ec_key.clone(), rsa_key.clone()]);
+
+        // Without a `kid`, keys are selected by their type and permitted use.
+        assert_eq!(
+            jwks.filter_keys(&None, &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256),
+            vec![&rsa_key]
+        );
+        assert_eq!(
+            jwks.filter_keys(&None, &CoreJwsSigningAlgorithm::EcdsaP256Sha256),
+            vec![&ec_key]
+        );
+
+        // Keys without a `kid` never match a JWT that specifies one.
+        assert!(jwks
+            .filter_keys(
+                &Some(JsonWebKeyId::new(
+                    "bilbo.baggins@hobbiton.example".to_string()
+                )),
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256
+            )
+            .is_empty());
+        assert!(jwks
+            .key_by_id(&JsonWebKeyId::new(
+                "bilbo.baggins@hobbiton.example".to_string()
+            ))
+            .is_none());
+    }
+
+    #[test]
+    fn test_jwks_key_use_and_ops_selection() {
+        let kid = Some(JsonWebKeyId::new(
+            "bilbo.baggins@hobbiton.example".to_string(),
+        ));
+        let sig_key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PUB_KEY).expect("deserialization failed");
+        let enc_key = CoreJsonWebKey {
+            use_: Some(CoreJsonWebKeyUse::Encryption),
+            ..sig_key.clone()
+        };
+        let ops_enc_key: CoreJsonWebKey = serde_json::from_value({
+            let mut value = serde_json::to_value(&sig_key).unwrap();
+            value["key_ops"] = serde_json::json!(["encrypt", "wrapKey"]);
+            value.as_object_mut().unwrap().remove("use");
+            value
+        })
+        .expect("deserialization failed");
+        assert_eq!(
+            ops_enc_key.key_ops,
+            Some(vec!["encrypt".to_string(), "wrapKey".to_string()])
+        );
+        let ops_sig_key = CoreJsonWebKey {
+            key_ops: Some(vec!["verify".to_string()]),
+            ..ops_enc_key.clone()
+        };
+
+        let jwks = CoreJsonWebKeySet::new(vec![
+            enc_key.clone(),
+            ops_enc_key.clone(),
+            ops_sig_key.clone(),
+            sig_key.clone(),
+        ]);
+        assert_eq!(
+            jwks.filter_keys(&kid, &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256),
+            vec![&ops_sig_key, &sig_key]
+        );
+        assert_eq!(
+            jwks.filter_keys(&None, &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256),
+            vec![&ops_sig_key, &sig_key]
+        );
+
+        let jwks = CoreJsonWebKeySet::new(vec![enc_key.clone(), ops_enc_key.clone()]);
+        assert!(jwks
+            .filter_keys(&kid, &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256)
+            .is_empty());
+
+        for key in [enc_key, ops_enc_key] {
+            match key
+                .verify_signature(
+                    &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                    b"message",
+                    &Vec::new(),
+                )
+                .expect_err("signature verification should fail")
+            {
+                SignatureVerificationError::InvalidKey(_) => {}
+                other => panic!("unexpected error: {:?}", other),
+            }
+        }
+
+        // A key restricting `alg` is only selected for that algorithm.
+        #[cfg(feature = "jwk-alg")]
+        {
+            let ps256_key = CoreJsonWebKey {
+                alg: Some(JsonWebTokenAlgorithm::Signature(
+                    CoreJwsSigningAlgorithm::RsaSsaPssSha256,
+                    std::marker::PhantomData,
+                )),
+                ..sig_key.clone()
+            };
+            let rs256_key = CoreJsonWebKey {
+                alg: Some(JsonWebTokenAlgorithm::Signature(
+                    CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                    std::marker::PhantomData,
+                )),
+                ..sig_key
+            };
+            let jwks = CoreJsonWebKeySet::new(vec![ps256_key.clone(), rs256_key.clone()]);
+            assert_eq!(
+                jwks.filter_keys(&kid, &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256),
+                vec![&rs256_key]
+            );
+            assert_eq!(
+                jwks.filter_keys(&None, &CoreJwsSigningAlgorithm::RsaSsaPssSha256),
+                vec![&ps256_key]
+            );
+            assert!(jwks
+                .filter_keys(&kid, &CoreJwsSigningAlgorithm::RsaSsaPssSha384)
+                .is_empty());
+        }
+    }
+
     #[test]
     fn test_hmac_sha256_verification() {
         // the original spec example also has alg=HS256, which was removed to test other signing algorithms
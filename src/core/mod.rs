@@ -10,30 +10,35 @@ pub use oauth2::basic::{
 use oauth2::devicecode::{DeviceAuthorizationResponse, EmptyExtraDeviceAuthorizationFields};
 pub use oauth2::StandardRevocableToken as CoreRevocableToken;
 use oauth2::{
-    EmptyExtraTokenFields, ErrorResponseType, ResponseType as OAuth2ResponseType,
-    StandardErrorResponse, StandardTokenIntrospectionResponse, StandardTokenResponse,
+    EmptyExtraTokenFields, ErrorResponse, ErrorResponseType, ResponseType as OAuth2ResponseType,
+    RevocableToken, StandardErrorResponse, StandardTokenIntrospectionResponse,
+    StandardTokenResponse, TokenIntrospectionResponse, TokenType,
 };
 
 use serde::{Deserialize, Serialize};
 
+use crate::client_auth::ClientAssertionSigner;
 use crate::registration::{
-    ClientMetadata, ClientRegistrationRequest, ClientRegistrationResponse,
+    ClientManagement, ClientMetadata, ClientRegistrationRequest, ClientRegistrationResponse,
     EmptyAdditionalClientMetadata, EmptyAdditionalClientRegistrationResponse,
     RegisterErrorResponseType,
 };
 use crate::{
-    ApplicationType, AuthDisplay, AuthPrompt, ClaimName, ClaimType, Client, ClientAuthMethod,
-    EmptyAdditionalClaims, EmptyAdditionalProviderMetadata, GenderClaim, GrantType, IdToken,
-    IdTokenClaims, IdTokenFields, IdTokenVerifier, JsonWebKeySet, JweContentEncryptionAlgorithm,
-    JweKeyManagementAlgorithm, JwsSigningAlgorithm, ProviderMetadata, ResponseMode, ResponseType,
-    SubjectIdentifierType, UserInfoClaims, UserInfoJsonWebToken, UserInfoVerifier,
+    AdditionalClaims, ApplicationType, AuthDisplay, AuthPrompt, AuthorizationResponse, ClaimName,
+    ClaimSourceVerifier, ClaimType, Client, ClientAuthMethod, EmptyAdditionalClaims,
+    EmptyAdditionalProviderMetadata, GenderClaim, GrantType, IdToken, IdTokenClaims, IdTokenFields,
+    IdTokenVerifier, JarmVerifier, JsonWebKeySet, JsonWebTokenError, JweContentEncryptionAlgorithm,
+    JweKeyManagementAlgorithm, JwksCache, JwsSigningAlgorithm, LogoutToken, LogoutTokenVerifier,
+    MultiIssuerVerifier, ProviderMetadata, RequestObject, RequestObjectVerifier, ResponseMode,
+    ResponseType, SignedMetadataVerifier, SigningError, SubjectIdentifierType, TokenResponse,
+    UserInfoClaims, UserInfoJsonWebToken, UserInfoVerifier,
 };
 
 use super::AuthenticationFlow;
 
 pub use self::jwk::{
     CoreEdDsaPrivateSigningKey, CoreHmacKey, CoreJsonWebKey, CoreJsonWebKeyType, CoreJsonWebKeyUse,
-    CoreRsaPrivateSigningKey,
+    CoreRsaPrivateDecryptionKey, CoreRsaPrivateSigningKey, CoreSymmetricDecryptionKey,
 };
 
 mod crypto;
@@ -78,6 +83,100 @@ pub type CoreClient = Client<
     CoreRevocableToken,
     CoreRevocationErrorResponse,
 >;
+impl<AC, AD, GC, JE, P, TE, TR, TT, TIR, RT, TRE>
+    Client<
+        AC,
+        AD,
+        GC,
+        JE,
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+        CoreJsonWebKeyUse,
+        CoreJsonWebKey,
+        P,
+        TE,
+        TR,
+        TT,
+        TIR,
+        RT,
+        TRE,
+    >
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<CoreJsonWebKeyType> + 'static,
+    P: AuthPrompt,
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<AC, GC, JE, CoreJwsSigningAlgorithm, CoreJsonWebKeyType, TT>,
+    TT: TokenType + 'static,
+    TIR: TokenIntrospectionResponse<TT>,
+    RT: RevocableToken,
+    TRE: ErrorResponse + 'static,
+{
+    ///
+    /// Configures the client to authenticate using the `client_secret_jwt` method described in
+    /// [Section 9 of OpenID Connect Core 1.0](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#ClientAuthentication).
+    ///
+    /// This method behaves like [`set_private_key_jwt_auth`](Client::set_private_key_jwt_auth),
+    /// except that each `client_assertion` is signed with the client secret using the specified
+    /// HMAC algorithm (typically [`CoreJwsSigningAlgorithm::HmacSha256`]) rather than with a
+    /// private key. The client secret itself is never sent to the provider.
+    ///
+    /// As required by [Section 3.2 of RFC 7518](https://tools.ietf.org/html/rfc7518#section-3.2),
+    /// the client secret must be at least as long as the hash output: 32 bytes for `HS256`, 48
+    /// bytes for `HS384`, and 64 bytes for `HS512`. An error is returned if the client has no
+    /// secret, the secret is too short, or `alg` is not an HMAC algorithm.
+    ///
+    pub fn set_client_secret_jwt_auth(
+        self,
+        alg: CoreJwsSigningAlgorithm,
+    ) -> Result<Self, JsonWebTokenError> {
+        let alg_name = serde_plain::to_string(&alg).unwrap_or_else(|err| {
+            panic!(
+                "signature alg {:?} failed to serialize to a string: {}",
+                alg, err
+            )
+        });
+        let min_secret_len = match alg {
+            CoreJwsSigningAlgorithm::HmacSha256 => 32,
+            CoreJwsSigningAlgorithm::HmacSha384 => 48,
+            CoreJwsSigningAlgorithm::HmacSha512 => 64,
+            _ => {
+                return Err(JsonWebTokenError::SigningError(
+                    SigningError::UnsupportedAlg(alg_name),
+                ))
+            }
+        };
+        let secret = self
+            .client_secret
+            .as_ref()
+            .ok_or_else(|| {
+                JsonWebTokenError::SigningError(SigningError::Other(
+                    "client_secret_jwt authentication requires a client secret".to_string(),
+                ))
+            })?
+            .secret()
+            .as_bytes()
+            .to_vec();
+        if secret.len() < min_secret_len {
+            return Err(JsonWebTokenError::SigningError(SigningError::Other(
+                format!(
+                    "client secret must be at least {} bytes long for {}",
+                    min_secret_len, alg_name
+                ),
+            )));
+        }
+
+        self.set_client_assertion_signer(
+            ClientAssertionSigner::new::<JE, _, _, _, CoreJsonWebKey, _>(
+                CoreHmacKey::new(secret),
+                alg,
+            ),
+        )
+    }
+}
 
 ///
 /// OpenID Connect Core client metadata.
@@ -117,6 +216,26 @@ pub type CoreClientRegistrationRequest = ClientRegistrationRequest<
     CoreSubjectIdentifierType,
 >;
 
+///
+/// OpenID Connect Core client configuration endpoint operations.
+///
+pub type CoreClientManagement = ClientManagement<
+    EmptyAdditionalClientMetadata,
+    EmptyAdditionalClientRegistrationResponse,
+    CoreApplicationType,
+    CoreClientAuthMethod,
+    CoreRegisterErrorResponseType,
+    CoreGrantType,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJweKeyManagementAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreResponseType,
+    CoreSubjectIdentifierType,
+>;
+
 ///
 /// OpenID Connect Core client registration response.
 ///
@@ -136,6 +255,17 @@ pub type CoreClientRegistrationResponse = ClientRegistrationResponse<
     CoreSubjectIdentifierType,
 >;
 
+///
+/// OpenID Connect Core authorization response.
+///
+pub type CoreAuthorizationResponse = AuthorizationResponse<
+    EmptyAdditionalClaims,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
 ///
 /// OpenID Connect Core ID token.
 ///
@@ -147,6 +277,20 @@ pub type CoreIdToken = IdToken<
     CoreJsonWebKeyType,
 >;
 
+///
+/// OpenID Connect Core ID token builder.
+///
+/// This type is only available when the `test-utils` feature is enabled.
+///
+#[cfg(feature = "test-utils")]
+pub type CoreIdTokenBuilder = crate::IdTokenBuilder<
+    EmptyAdditionalClaims,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
 ///
 /// OpenID Connect Core ID token claims.
 ///
@@ -175,6 +319,87 @@ pub type CoreIdTokenVerifier<'a> = IdTokenVerifier<
     CoreJsonWebKey,
 >;
 
+///
+/// OpenID Connect Core multi-issuer ID token verifier.
+///
+pub type CoreMultiIssuerVerifier<'a> = MultiIssuerVerifier<
+    'a,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+>;
+
+///
+/// OpenID Connect Back-Channel Logout token.
+///
+pub type CoreLogoutToken =
+    LogoutToken<CoreJweContentEncryptionAlgorithm, CoreJwsSigningAlgorithm, CoreJsonWebKeyType>;
+
+///
+/// OpenID Connect JWT Secured Authorization Response Mode (JARM) response verifier.
+///
+pub type CoreJarmVerifier<'a> = JarmVerifier<
+    'a,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+>;
+
+///
+/// OpenID Connect Back-Channel Logout token verifier.
+///
+pub type CoreLogoutTokenVerifier<'a> = LogoutTokenVerifier<
+    'a,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+>;
+
+///
+/// OpenID Connect Core request object.
+///
+pub type CoreRequestObject =
+    RequestObject<CoreJweContentEncryptionAlgorithm, CoreJwsSigningAlgorithm, CoreJsonWebKeyType>;
+
+///
+/// OpenID Connect Core request object verifier.
+///
+pub type CoreRequestObjectVerifier<'a> = RequestObjectVerifier<
+    'a,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+>;
+
+///
+/// OpenID Connect Core aggregated and distributed claims verifier.
+///
+pub type CoreClaimSourceVerifier<'a> = ClaimSourceVerifier<
+    'a,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+>;
+
+///
+/// OpenID Connect Core signed provider metadata verifier.
+///
+pub type CoreSignedMetadataVerifier<'a> = SignedMetadataVerifier<
+    'a,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+>;
+
 ///
 /// OpenID Connect Core token response.
 ///
@@ -186,6 +411,12 @@ pub type CoreTokenResponse = StandardTokenResponse<CoreIdTokenFields, CoreTokenT
 pub type CoreJsonWebKeySet =
     JsonWebKeySet<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreJsonWebKey>;
 
+///
+/// OpenID Connect Core JSON Web Key Set cache.
+///
+pub type CoreJwksCache<RE> =
+    JwksCache<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreJsonWebKey, RE>;
+
 ///
 /// OpenID Connect Core provider metadata.
 ///
@@ -841,6 +1072,12 @@ pub enum CoreJwsSigningAlgorithm {
     #[serde(rename = "ES512")]
     EcdsaP521Sha512,
     ///
+    /// ECDSA using secp256k1 and SHA-256, as defined in
+    /// [RFC 8812](https://tools.ietf.org/html/rfc8812#section-3.2).
+    ///
+    #[serde(rename = "ES256K")]
+    EcdsaSecp256k1Sha256,
+    ///
     /// RSA SSA-PSS using SHA-256 and MGF1 with SHA-256.
     ///
     #[serde(rename = "PS256")]
@@ -856,9 +1093,14 @@ pub enum CoreJwsSigningAlgorithm {
     #[serde(rename = "PS512")]
     RsaSsaPssSha512,
     ///
-    /// EdDSA signature using Ed25519 curve
+    /// EdDSA signature using Ed25519 curve.
     ///
-    #[serde(rename = "Ed25519")]
+    /// Both the standard `EdDSA` algorithm name defined in
+    /// [RFC 8037](https://tools.ietf.org/html/rfc8037#section-3.1) and the legacy `Ed25519` name
+    /// are accepted when deserializing. The curve used by the key must be `Ed25519`; `Ed448` is not
+    /// currently supported.
+    ///
+    #[serde(rename = "Ed25519", alias = "EdDSA")]
     EdDsaEd25519,
     ///
     /// No digital signature or MAC performed.
@@ -888,7 +1130,10 @@ impl JwsSigningAlgorithm<CoreJsonWebKeyType> for CoreJwsSigningAlgorithm {
             | CoreJwsSigningAlgorithm::RsaSsaPssSha512 => Some(CoreJsonWebKeyType::RSA),
             CoreJwsSigningAlgorithm::EcdsaP256Sha256
             | CoreJwsSigningAlgorithm::EcdsaP384Sha384
-            | CoreJwsSigningAlgorithm::EcdsaP521Sha512 => Some(CoreJsonWebKeyType::EllipticCurve),
+            | CoreJwsSigningAlgorithm::EcdsaP521Sha512
+            | CoreJwsSigningAlgorithm::EcdsaSecp256k1Sha256 => {
+                Some(CoreJsonWebKeyType::EllipticCurve)
+            }
             CoreJwsSigningAlgorithm::EdDsaEd25519 => Some(CoreJsonWebKeyType::OctetKeyPair),
             CoreJwsSigningAlgorithm::None => None,
         }
@@ -906,7 +1151,8 @@ impl JwsSigningAlgorithm<CoreJsonWebKeyType> for CoreJwsSigningAlgorithm {
             CoreJwsSigningAlgorithm::HmacSha256
             | CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256
             | CoreJwsSigningAlgorithm::RsaSsaPssSha256
-            | CoreJwsSigningAlgorithm::EcdsaP256Sha256 => {
+            | CoreJwsSigningAlgorithm::EcdsaP256Sha256
+            | CoreJwsSigningAlgorithm::EcdsaSecp256k1Sha256 => {
                 let mut hasher = Sha256::new();
                 hasher.update(bytes);
                 hasher.finalize().to_vec()
@@ -1072,7 +1318,7 @@ impl AsRef<str> for CoreAuthErrorResponseType {
             CoreAuthErrorResponseType::ConsentRequired => "consent_required",
             CoreAuthErrorResponseType::InteractionRequired => "interaction_required",
             CoreAuthErrorResponseType::InvalidRequest => "invalid_request",
-            CoreAuthErrorResponseType::InvalidRequestObject => "invalid_request_obbject",
+            CoreAuthErrorResponseType::InvalidRequestObject => "invalid_request_object",
             CoreAuthErrorResponseType::InvalidRequestUri => "invalid_request_uri",
             CoreAuthErrorResponseType::InvalidScope => "invalid_scope",
             CoreAuthErrorResponseType::LoginRequired => "login_required",
@@ -1104,6 +1350,14 @@ pub enum CoreRegisterErrorResponseType {
     ///
     InvalidRedirectUri,
     ///
+    /// The software statement presented is invalid.
+    ///
+    InvalidSoftwareStatement,
+    ///
+    /// The software statement presented is not approved for use by this authorization server.
+    ///
+    UnapprovedSoftwareStatement,
+    ///
     /// An extension not defined by any of the supported specifications.
     ///
     Extension(String),
@@ -1115,6 +1369,10 @@ impl CoreRegisterErrorResponseType {
         match s {
             "invalid_client_metadata" => CoreRegisterErrorResponseType::InvalidClientMetadata,
             "invalid_redirect_uri" => CoreRegisterErrorResponseType::InvalidRedirectUri,
+            "invalid_software_statement" => CoreRegisterErrorResponseType::InvalidSoftwareStatement,
+            "unapproved_software_statement" => {
+                CoreRegisterErrorResponseType::UnapprovedSoftwareStatement
+            }
             ext => CoreRegisterErrorResponseType::Extension(ext.to_string()),
         }
     }
@@ -1124,6 +1382,10 @@ impl AsRef<str> for CoreRegisterErrorResponseType {
         match *self {
             CoreRegisterErrorResponseType::InvalidClientMetadata => "invalid_client_metadata",
             CoreRegisterErrorResponseType::InvalidRedirectUri => "invalid_redirect_uri",
+            CoreRegisterErrorResponseType::InvalidSoftwareStatement => "invalid_software_statement",
+            CoreRegisterErrorResponseType::UnapprovedSoftwareStatement => {
+                "unapproved_software_statement"
+            }
             CoreRegisterErrorResponseType::Extension(ref ext) => ext.as_str(),
         }
     }
@@ -1183,6 +1445,24 @@ pub enum CoreResponseMode {
     ///
     FormPost,
     ///
+    /// JWT Secured Authorization Response Mode (JARM) using the default encoding of the
+    /// requested response type, as described in
+    /// [Section 2.3.4 of JARM](https://openid.net/specs/oauth-v2-jarm.html#name-response-mode-jwt).
+    ///
+    Jwt,
+    ///
+    /// JARM response encoded as the `response` query parameter of the `redirect_uri`.
+    ///
+    QueryJwt,
+    ///
+    /// JARM response encoded as the `response` fragment parameter of the `redirect_uri`.
+    ///
+    FragmentJwt,
+    ///
+    /// JARM response encoded as the `response` form value transmitted via the HTTP `POST` method.
+    ///
+    FormPostJwt,
+    ///
     /// An extension not defined by any of the supported specifications.
     ///
     Extension(String),
@@ -1195,6 +1475,10 @@ impl CoreResponseMode {
             "query" => CoreResponseMode::Query,
             "fragment" => CoreResponseMode::Fragment,
             "form_post" => CoreResponseMode::FormPost,
+            "jwt" => CoreResponseMode::Jwt,
+            "query.jwt" => CoreResponseMode::QueryJwt,
+            "fragment.jwt" => CoreResponseMode::FragmentJwt,
+            "form_post.jwt" => CoreResponseMode::FormPostJwt,
             ext => CoreResponseMode::Extension(ext.to_string()),
         }
     }
@@ -1205,6 +1489,10 @@ impl AsRef<str> for CoreResponseMode {
             CoreResponseMode::Query => "query",
             CoreResponseMode::Fragment => "fragment",
             CoreResponseMode::FormPost => "form_post",
+            CoreResponseMode::Jwt => "jwt",
+            CoreResponseMode::QueryJwt => "query.jwt",
+            CoreResponseMode::FragmentJwt => "fragment.jwt",
+            CoreResponseMode::FormPostJwt => "form_post.jwt",
             CoreResponseMode::Extension(ref ext) => ext.as_str(),
         }
     }
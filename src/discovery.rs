@@ -1,25 +1,34 @@
+use std::convert::Infallible;
 use std::fmt::Debug;
 use std::future::Future;
 use std::marker::PhantomData;
+use std::task::Poll;
 
-use http::header::{HeaderValue, ACCEPT};
+use http::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
 use http::method::Method;
 use http::status::StatusCode;
-use oauth2::{AuthUrl, Scope, TokenUrl};
+use oauth2::{AuthUrl, DeviceAuthorizationUrl, IntrospectionUrl, RevocationUrl, Scope, TokenUrl};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::{Map, Value};
 use serde_with::{serde_as, skip_serializing_none, VecSkipError};
 use thiserror::Error;
 
-use super::http_utils::{check_content_type, MIME_TYPE_JSON};
+use super::http_utils::{
+    check_content_type, content_type_has_essence, MIME_TYPE_JSON, MIME_TYPE_JWT,
+};
 use super::types::{
-    AuthDisplay, AuthenticationContextClass, ClaimName, ClaimType, ClientAuthMethod, GrantType,
-    IssuerUrl, JsonWebKey, JsonWebKeySet, JsonWebKeySetUrl, JsonWebKeyType, JsonWebKeyUse,
-    JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag,
-    OpPolicyUrl, OpTosUrl, RegistrationUrl, ResponseMode, ResponseType, ResponseTypes,
+    AuthDisplay, AuthenticationContextClass, BackchannelAuthenticationUrl, CheckSessionIframeUrl,
+    ClaimName, ClaimType, ClientAuthMethod, GrantType, IssuerUrl, JsonWebKey, JsonWebKeySet,
+    JsonWebKeySetUrl, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag, OpPolicyUrl, OpTosUrl,
+    PushedAuthorizationRequestUrl, RegistrationUrl, ResponseMode, ResponseType, ResponseTypes,
     ServiceDocUrl, SubjectIdentifierType,
 };
-use super::{HttpRequest, HttpResponse, UserInfoUrl, CONFIG_URL_SUFFIX};
+use super::{
+    AsyncHttpClient, ClaimsVerificationError, ClassifyTimeout, HttpRequest, HttpResponse,
+    HttpTimeout, SignedMetadataVerifier, SyncHttpClient, UserInfoUrl,
+};
 
 ///
 /// Trait for adding extra fields to [`ProviderMetadata`].
@@ -153,6 +162,20 @@ where
     require_request_uri_registration: Option<bool>,
     op_policy_uri: Option<OpPolicyUrl>,
     op_tos_uri: Option<OpTosUrl>,
+    // Defined by OAuth 2.0 Authorization Server Metadata (RFC 8414).
+    introspection_endpoint: Option<IntrospectionUrl>,
+    revocation_endpoint: Option<RevocationUrl>,
+    // Defined by OAuth 2.0 Device Authorization Grant (RFC 8628).
+    device_authorization_endpoint: Option<DeviceAuthorizationUrl>,
+    // Defined by OAuth 2.0 Pushed Authorization Requests (RFC 9126).
+    pushed_authorization_request_endpoint: Option<PushedAuthorizationRequestUrl>,
+    require_pushed_authorization_requests: Option<bool>,
+    // Defined by OpenID Connect Client-Initiated Backchannel Authentication Flow (CIBA).
+    backchannel_authentication_endpoint: Option<BackchannelAuthenticationUrl>,
+    // Defined by OpenID Connect Session Management 1.0.
+    check_session_iframe: Option<CheckSessionIframeUrl>,
+    // Defined by OAuth 2.0 Authorization Server Metadata (RFC 8414).
+    signed_metadata: Option<String>,
 
     #[serde(bound(deserialize = "A: AdditionalProviderMetadata"), flatten)]
     additional_metadata: A,
@@ -228,6 +251,14 @@ where
             require_request_uri_registration: None,
             op_policy_uri: None,
             op_tos_uri: None,
+            introspection_endpoint: None,
+            revocation_endpoint: None,
+            device_authorization_endpoint: None,
+            pushed_authorization_request_endpoint: None,
+            require_pushed_authorization_requests: None,
+            backchannel_authentication_endpoint: None,
+            check_session_iframe: None,
+            signed_metadata: None,
             additional_metadata,
             _phantom_jt: PhantomData,
         }
@@ -283,6 +314,18 @@ where
             set_require_request_uri_registration -> require_request_uri_registration[Option<bool>],
             set_op_policy_uri -> op_policy_uri[Option<OpPolicyUrl>],
             set_op_tos_uri -> op_tos_uri[Option<OpTosUrl>],
+            set_introspection_endpoint -> introspection_endpoint[Option<IntrospectionUrl>],
+            set_revocation_endpoint -> revocation_endpoint[Option<RevocationUrl>],
+            set_device_authorization_endpoint ->
+                device_authorization_endpoint[Option<DeviceAuthorizationUrl>],
+            set_pushed_authorization_request_endpoint ->
+                pushed_authorization_request_endpoint[Option<PushedAuthorizationRequestUrl>],
+            set_require_pushed_authorization_requests ->
+                require_pushed_authorization_requests[Option<bool>],
+            set_backchannel_authentication_endpoint ->
+                backchannel_authentication_endpoint[Option<BackchannelAuthenticationUrl>],
+            set_check_session_iframe -> check_session_iframe[Option<CheckSessionIframeUrl>],
+            set_signed_metadata -> signed_metadata[Option<String>],
         }
     ];
 
@@ -295,17 +338,18 @@ where
         http_client: HC,
     ) -> Result<Self, DiscoveryError<RE>>
     where
-        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        HC: SyncHttpClient<Error = RE>,
         RE: std::error::Error + 'static,
     {
         let discovery_url = issuer_url
-            .join(CONFIG_URL_SUFFIX)
+            .discovery_url()
             .map_err(DiscoveryError::UrlParse)?;
 
-        http_client(Self::discovery_request(discovery_url.clone()))
+        http_client
+            .call(Self::discovery_request(discovery_url.clone(), false))
             .map_err(DiscoveryError::Request)
             .and_then(|http_response| {
-                Self::discovery_response(issuer_url, &discovery_url, http_response)
+                Self::discovery_response(issuer_url, &discovery_url, http_response, None)
             })
             .and_then(|provider_metadata| {
                 JsonWebKeySet::fetch(provider_metadata.jwks_uri(), http_client).map(|jwks| Self {
@@ -325,18 +369,19 @@ where
     ) -> Result<Self, DiscoveryError<RE>>
     where
         F: Future<Output = Result<HttpResponse, RE>>,
-        HC: Fn(HttpRequest) -> F,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
         RE: std::error::Error + 'static,
     {
         let discovery_url = issuer_url
-            .join(CONFIG_URL_SUFFIX)
+            .discovery_url()
             .map_err(DiscoveryError::UrlParse)?;
 
-        let provider_metadata = http_client(Self::discovery_request(discovery_url.clone()))
+        let provider_metadata = http_client
+            .call(Self::discovery_request(discovery_url.clone(), false))
             .await
             .map_err(DiscoveryError::Request)
             .and_then(|http_response| {
-                Self::discovery_response(&issuer_url, &discovery_url, http_response)
+                Self::discovery_response(&issuer_url, &discovery_url, http_response, None)
             })?;
 
         JsonWebKeySet::fetch_async(provider_metadata.jwks_uri(), http_client)
@@ -347,13 +392,233 @@ where
             })
     }
 
-    fn discovery_request(discovery_url: url::Url) -> HttpRequest {
+    ///
+    /// Asynchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider, giving up once `timeout` completes.
+    ///
+    /// This crate does not depend on any particular async runtime, so the deadline is supplied by
+    /// the caller as a future that completes when the timeout elapses (e.g.,
+    /// `tokio::time::sleep(Duration::from_secs(10))`). If it completes before discovery finishes,
+    /// the in-flight HTTP request is dropped and [`DiscoveryError::Timeout`] is returned. The
+    /// deadline covers the discovery document and JSON Web Key Set requests combined.
+    ///
+    /// Connect and read timeouts configured on the HTTP client itself (e.g., via
+    /// `reqwest::ClientBuilder::connect_timeout` and `reqwest::ClientBuilder::timeout`) are
+    /// classified using [`ClassifyTimeout`] and returned as [`DiscoveryError::ConnectTimeout`] and
+    /// [`DiscoveryError::ReadTimeout`], respectively. Other transport errors are returned as
+    /// [`DiscoveryError::Request`].
+    ///
+    pub async fn discover_async_with_timeout<F, HC, RE, T>(
+        issuer_url: IssuerUrl,
+        http_client: HC,
+        timeout: T,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
+        RE: ClassifyTimeout,
+        T: Future<Output = ()>,
+    {
+        let mut discovery = Box::pin(Self::discover_async(issuer_url, http_client));
+        let mut timeout = Box::pin(timeout);
+        std::future::poll_fn(|cx| {
+            if let Poll::Ready(result) = discovery.as_mut().poll(cx) {
+                Poll::Ready(result.map_err(|err| match err {
+                    DiscoveryError::Request(err) => match err.timeout() {
+                        Some(HttpTimeout::Connect) => DiscoveryError::ConnectTimeout(err),
+                        Some(HttpTimeout::Read) => DiscoveryError::ReadTimeout(err),
+                        None => DiscoveryError::Request(err),
+                    },
+                    err => err,
+                }))
+            } else if timeout.as_mut().poll(cx).is_ready() {
+                Poll::Ready(Err(DiscoveryError::Timeout))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    ///
+    /// Fetches the OpenID Connect Discovery document and associated JSON Web Key Set from the
+    /// OpenID Connect Provider, requiring the metadata to be signed by the trust anchor configured
+    /// in `signed_metadata_verifier`.
+    ///
+    /// The discovery document may either be served as a signed JWT (`application/jwt`), or as JSON
+    /// containing a `signed_metadata` value. See
+    /// [`verify_signed_metadata`](Self::verify_signed_metadata) for how signed and unsigned values
+    /// are combined.
+    ///
+    pub fn discover_signed<HC, RE>(
+        issuer_url: &IssuerUrl,
+        signed_metadata_verifier: &SignedMetadataVerifier<JE, JS, JT, JU, K>,
+        http_client: HC,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        let discovery_url = issuer_url
+            .discovery_url()
+            .map_err(DiscoveryError::UrlParse)?;
+
+        http_client
+            .call(Self::discovery_request(discovery_url.clone(), true))
+            .map_err(DiscoveryError::Request)
+            .and_then(|http_response| {
+                Self::discovery_response(
+                    issuer_url,
+                    &discovery_url,
+                    http_response,
+                    Some(signed_metadata_verifier),
+                )
+            })
+            .and_then(|provider_metadata| {
+                JsonWebKeySet::fetch(provider_metadata.jwks_uri(), http_client).map(|jwks| Self {
+                    jwks,
+                    ..provider_metadata
+                })
+            })
+    }
+
+    ///
+    /// Asynchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider, requiring the metadata to be signed by the trust anchor
+    /// configured in `signed_metadata_verifier`.
+    ///
+    /// See [`discover_signed`](Self::discover_signed).
+    ///
+    pub async fn discover_signed_async<F, HC, RE>(
+        issuer_url: IssuerUrl,
+        signed_metadata_verifier: &SignedMetadataVerifier<'_, JE, JS, JT, JU, K>,
+        http_client: HC,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
+        RE: std::error::Error + 'static,
+    {
+        let discovery_url = issuer_url
+            .discovery_url()
+            .map_err(DiscoveryError::UrlParse)?;
+
+        let provider_metadata = http_client
+            .call(Self::discovery_request(discovery_url.clone(), true))
+            .await
+            .map_err(DiscoveryError::Request)
+            .and_then(|http_response| {
+                Self::discovery_response(
+                    &issuer_url,
+                    &discovery_url,
+                    http_response,
+                    Some(signed_metadata_verifier),
+                )
+            })?;
+
+        JsonWebKeySet::fetch_async(provider_metadata.jwks_uri(), http_client)
+            .await
+            .map(|jwks| Self {
+                jwks,
+                ..provider_metadata
+            })
+    }
+
+    ///
+    /// Parses provider metadata from the JWT form of a discovery document (i.e., one served as
+    /// `application/jwt`) after verifying its signature.
+    ///
+    pub fn from_signed_metadata(
+        jwt: &str,
+        signed_metadata_verifier: &SignedMetadataVerifier<JE, JS, JT, JU, K>,
+    ) -> Result<Self, DiscoveryError<Infallible>> {
+        Self::merge_signed_metadata(Map::new(), jwt, signed_metadata_verifier)
+    }
+
+    ///
+    /// Verifies the `signed_metadata` value and returns the provider metadata with the signed
+    /// values applied, as described in
+    /// [Section 2.1 of RFC 8414](https://datatracker.ietf.org/doc/html/rfc8414#section-2.1).
+    ///
+    /// Values contained in the signed metadata take precedence over the corresponding unsigned
+    /// values. An error is returned if no `signed_metadata` value is present or if it fails
+    /// verification.
+    ///
+    pub fn verify_signed_metadata(
+        self,
+        signed_metadata_verifier: &SignedMetadataVerifier<JE, JS, JT, JU, K>,
+    ) -> Result<Self, DiscoveryError<Infallible>> {
+        self.verify_signed_metadata_impl(signed_metadata_verifier)
+    }
+
+    fn verify_signed_metadata_impl<RE>(
+        self,
+        signed_metadata_verifier: &SignedMetadataVerifier<JE, JS, JT, JU, K>,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        let signed_metadata = self.signed_metadata.as_ref().ok_or_else(|| {
+            DiscoveryError::Validation("no signed_metadata value is present".to_string())
+        })?;
+        let unsigned_metadata = match serde_json::to_value(&self) {
+            Ok(Value::Object(unsigned_metadata)) => unsigned_metadata,
+            Ok(other) => {
+                return Err(DiscoveryError::Other(format!(
+                    "provider metadata serialized to unexpected JSON value: {}",
+                    other
+                )))
+            }
+            Err(err) => {
+                return Err(DiscoveryError::Other(format!(
+                    "failed to serialize provider metadata: {}",
+                    err
+                )))
+            }
+        };
+
+        Self::merge_signed_metadata(unsigned_metadata, signed_metadata, signed_metadata_verifier)
+            .map(|provider_metadata| Self {
+                jwks: self.jwks,
+                ..provider_metadata
+            })
+    }
+
+    fn merge_signed_metadata<RE>(
+        mut unsigned_metadata: Map<String, Value>,
+        jwt: &str,
+        signed_metadata_verifier: &SignedMetadataVerifier<JE, JS, JT, JU, K>,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        let signed_metadata = signed_metadata_verifier
+            .verified_metadata(jwt)
+            .map_err(DiscoveryError::SignedMetadata)?;
+        unsigned_metadata.extend(signed_metadata);
+
+        serde_path_to_error::deserialize::<_, Self>(Value::Object(unsigned_metadata))
+            .map_err(DiscoveryError::Parse)
+            .map(|provider_metadata| Self {
+                signed_metadata: Some(jwt.to_string()),
+                ..provider_metadata
+            })
+    }
+
+    fn discovery_request(discovery_url: url::Url, accept_jwt: bool) -> HttpRequest {
         HttpRequest {
             url: discovery_url,
             method: Method::GET,
-            headers: vec![(ACCEPT, HeaderValue::from_static(MIME_TYPE_JSON))]
-                .into_iter()
-                .collect(),
+            headers: vec![(
+                ACCEPT,
+                HeaderValue::from_static(if accept_jwt {
+                    "application/json, application/jwt"
+                } else {
+                    MIME_TYPE_JSON
+                }),
+            )]
+            .into_iter()
+            .collect(),
             body: Vec::new(),
         }
     }
@@ -362,6 +627,7 @@ where
         issuer_url: &IssuerUrl,
         discovery_url: &url::Url,
         discovery_response: HttpResponse,
+        signed_metadata_verifier: Option<&SignedMetadataVerifier<JE, JS, JT, JU, K>>,
     ) -> Result<Self, DiscoveryError<RE>>
     where
         RE: std::error::Error + 'static,
@@ -377,18 +643,43 @@ where
             ));
         }
 
-        check_content_type(&discovery_response.headers, MIME_TYPE_JSON).map_err(|err_msg| {
-            DiscoveryError::Response(
-                discovery_response.status_code,
-                discovery_response.body.clone(),
-                err_msg,
-            )
-        })?;
+        let provider_metadata = match signed_metadata_verifier {
+            Some(signed_metadata_verifier)
+                if discovery_response
+                    .headers
+                    .get(CONTENT_TYPE)
+                    .map_or(false, |content_type| {
+                        content_type_has_essence(content_type, MIME_TYPE_JWT)
+                    }) =>
+            {
+                let jwt = String::from_utf8(discovery_response.body).map_err(|err| {
+                    DiscoveryError::Other(format!("signed metadata is not valid UTF-8: {}", err))
+                })?;
+                Self::merge_signed_metadata(Map::new(), jwt.trim(), signed_metadata_verifier)?
+            }
+            _ => {
+                check_content_type(&discovery_response.headers, MIME_TYPE_JSON).map_err(
+                    |err_msg| {
+                        DiscoveryError::Response(
+                            discovery_response.status_code,
+                            discovery_response.body.clone(),
+                            err_msg,
+                        )
+                    },
+                )?;
 
-        let provider_metadata = serde_path_to_error::deserialize::<_, Self>(
-            &mut serde_json::Deserializer::from_slice(&discovery_response.body),
-        )
-        .map_err(DiscoveryError::Parse)?;
+                let provider_metadata = serde_path_to_error::deserialize::<_, Self>(
+                    &mut serde_json::Deserializer::from_slice(&discovery_response.body),
+                )
+                .map_err(DiscoveryError::Parse)?;
+
+                if let Some(signed_metadata_verifier) = signed_metadata_verifier {
+                    provider_metadata.verify_signed_metadata_impl(signed_metadata_verifier)?
+                } else {
+                    provider_metadata
+                }
+            }
+        };
 
         if provider_metadata.issuer() != issuer_url {
             Err(DiscoveryError::Validation(format!(
@@ -401,6 +692,69 @@ where
         }
     }
 
+    ///
+    /// Checks that the provider metadata satisfies the requirements of
+    /// [Section 3 of OpenID Connect Discovery 1.0](
+    /// https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata).
+    ///
+    /// Fields that are always REQUIRED (e.g., `jwks_uri`) are enforced when deserializing the
+    /// metadata, while this method checks the remaining constraints so that misconfigured providers
+    /// can be rejected at configuration time rather than surfacing later during verification.
+    /// Every problem found is returned, rather than only the first.
+    ///
+    pub fn validate(&self) -> Result<(), Vec<ProviderMetadataValidationError>> {
+        let mut errors = Vec::new();
+
+        let issuer = self.issuer.url();
+        if issuer.scheme() != "https" || issuer.query().is_some() || issuer.fragment().is_some() {
+            errors.push(ProviderMetadataValidationError::InvalidIssuer(
+                self.issuer.to_string(),
+            ));
+        }
+
+        if self.response_types_supported.is_empty() {
+            errors.push(ProviderMetadataValidationError::MissingField(
+                "response_types_supported",
+            ));
+        }
+        if self.subject_types_supported.is_empty() {
+            errors.push(ProviderMetadataValidationError::MissingField(
+                "subject_types_supported",
+            ));
+        }
+
+        // The token endpoint is only optional for providers that exclusively support the Implicit
+        // Flow.
+        if self.token_endpoint.is_none()
+            && self
+                .response_types_supported
+                .iter()
+                .any(|response_types| response_types.iter().any(|rt| rt.as_ref() == "code"))
+        {
+            errors.push(ProviderMetadataValidationError::MissingField(
+                "token_endpoint",
+            ));
+        }
+
+        if self.id_token_signing_alg_values_supported.is_empty() {
+            errors.push(ProviderMetadataValidationError::MissingField(
+                "id_token_signing_alg_values_supported",
+            ));
+        }
+        if !self
+            .id_token_signing_alg_values_supported
+            .contains(&JS::rsa_sha_256())
+        {
+            errors.push(ProviderMetadataValidationError::Rs256NotSupported);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     ///
     /// Returns additional provider metadata fields.
     ///
@@ -451,15 +805,70 @@ where
     #[error("Failed to parse URL")]
     UrlParse(#[source] url::ParseError),
     ///
+    /// The WebFinger response for the given resource did not contain an issuer link.
+    ///
+    #[error("No issuer found for resource `{0}`")]
+    IssuerNotFound(String),
+    ///
+    /// The caller-supplied deadline elapsed before discovery completed.
+    ///
+    #[error("Discovery timed out")]
+    Timeout,
+    ///
+    /// The HTTP client timed out while connecting to the provider.
+    ///
+    #[error("Timed out connecting to provider")]
+    ConnectTimeout(#[source] RE),
+    ///
+    /// The HTTP client timed out while waiting for the provider's response.
+    ///
+    #[error("Timed out reading provider response")]
+    ReadTimeout(#[source] RE),
+    ///
+    /// Failed to verify signed provider metadata.
+    ///
+    #[error("Failed to verify signed metadata")]
+    SignedMetadata(#[source] ClaimsVerificationError),
+    ///
     /// Failed to validate provider metadata.
     ///
     #[error("Validation error: {0}")]
     Validation(String),
 }
 
+///
+/// Problem found while validating provider metadata with [`ProviderMetadata::validate`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProviderMetadataValidationError {
+    ///
+    /// The issuer is not an `https` URL without query or fragment components.
+    ///
+    #[error("Invalid issuer URL `{0}`")]
+    InvalidIssuer(String),
+    ///
+    /// A REQUIRED field is missing or empty.
+    ///
+    #[error("Missing required field `{0}`")]
+    MissingField(&'static str),
+    ///
+    /// The `RS256` signing algorithm is not listed in `id_token_signing_alg_values_supported`.
+    ///
+    #[error("RS256 is not a supported ID token signing algorithm")]
+    Rs256NotSupported,
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    use http::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
+    use http::status::StatusCode;
     use oauth2::{AuthUrl, Scope, TokenUrl};
+    use thiserror::Error;
 
     use crate::core::{
         CoreAuthDisplay, CoreClaimName, CoreClaimType, CoreClientAuthMethod, CoreGrantType,
@@ -471,6 +880,14 @@ mod tests {
         AuthenticationContextClass, IssuerUrl, JsonWebKeySetUrl, LanguageTag, OpPolicyUrl,
         OpTosUrl, RegistrationUrl, ResponseTypes, ServiceDocUrl, UserInfoUrl,
     };
+    use crate::claim_sources::tests::claim_source_jwt;
+    use crate::core::{CoreJsonWebKeySet, CoreSignedMetadataVerifier};
+    use crate::http_utils::MIME_TYPE_JSON;
+    use crate::jwt::tests::{TEST_EC_PUB_KEY_P256, TEST_RSA_PUB_KEY};
+    use crate::{
+        ClaimsVerificationError, ClassifyTimeout, DiscoveryError, HttpRequest, HttpResponse,
+        HttpTimeout, ProviderMetadataValidationError,
+    };
 
     #[test]
     fn test_discovery_deserialization() {
@@ -928,7 +1345,7 @@ mod tests {
         );
         assert_eq!(
             Some(
-                &vec![
+                &[
                     "email",
                     "phone",
                     "profile",
@@ -1622,4 +2039,430 @@ mod tests {
             serde_json::from_str(&serialized_json).unwrap();
         assert_eq!(provider_metadata, redeserialized_metadata);
     }
+
+    fn discovery_response(issuer: &str) -> Result<HttpResponse, std::io::Error> {
+        Ok(HttpResponse {
+            status_code: StatusCode::OK,
+            headers: vec![(CONTENT_TYPE, HeaderValue::from_static(MIME_TYPE_JSON))]
+                .into_iter()
+                .collect(),
+            body: format!(
+                "{{\
+                    \"issuer\":\"{}\",\
+                    \"authorization_endpoint\":\"https://example.com/authorize\",\
+                    \"jwks_uri\":\"https://example.com/jwks.json\",\
+                    \"response_types_supported\":[\"code\"],\
+                    \"subject_types_supported\":[\"public\"],\
+                    \"id_token_signing_alg_values_supported\":[\"RS256\"]\
+                }}",
+                issuer
+            )
+            .into_bytes(),
+        })
+    }
+
+    // Minimal executor for driving futures in tests without depending on an async runtime.
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        struct NoopWaker;
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn test_discovery_issuer_mismatch() {
+        let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+
+        match CoreProviderMetadata::discover(&issuer_url, |request: HttpRequest| {
+            assert_eq!(
+                request.url.as_str(),
+                "https://example.com/.well-known/openid-configuration"
+            );
+            discovery_response("https://attacker.example.com")
+        }) {
+            Err(DiscoveryError::Validation(msg)) => assert_eq!(
+                msg,
+                "unexpected issuer URI `https://attacker.example.com` \
+                 (expected `https://example.com`)"
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The issuer must match exactly, including any trailing slash.
+        match block_on(CoreProviderMetadata::discover_async(
+            issuer_url,
+            |_| async { discovery_response("https://example.com/") },
+        )) {
+            Err(DiscoveryError::Validation(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_discovery_timeout() {
+        let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+
+        // The mock server never responds, so the deadline always wins.
+        match block_on(CoreProviderMetadata::discover_async_with_timeout(
+            issuer_url.clone(),
+            |_| std::future::pending::<Result<HttpResponse, std::io::Error>>(),
+            std::future::ready(()),
+        )) {
+            Err(DiscoveryError::Timeout) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Errors that occur before the deadline are returned unchanged.
+        match block_on(CoreProviderMetadata::discover_async_with_timeout(
+            issuer_url,
+            |_| async { discovery_response("https://attacker.example.com") },
+            std::future::pending(),
+        )) {
+            Err(DiscoveryError::Validation(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[derive(Debug, Error)]
+    #[error("mock transport error")]
+    struct MockTransportError(Option<HttpTimeout>);
+    impl ClassifyTimeout for MockTransportError {
+        fn timeout(&self) -> Option<HttpTimeout> {
+            self.0
+        }
+    }
+
+    // Mock server that stalls until the HTTP client's own timeout fires: the first poll of the
+    // response future is pending, and the next one fails with the given timeout.
+    fn stalled_response(
+        timeout: HttpTimeout,
+    ) -> impl Future<Output = Result<HttpResponse, MockTransportError>> {
+        let mut stalled = false;
+        std::future::poll_fn(move |cx| {
+            if stalled {
+                Poll::Ready(Err(MockTransportError(Some(timeout))))
+            } else {
+                stalled = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+    }
+
+    #[test]
+    fn test_discovery_connect_timeout() {
+        let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+
+        match block_on(CoreProviderMetadata::discover_async_with_timeout(
+            issuer_url,
+            |request: HttpRequest| {
+                assert_eq!(
+                    request.url.as_str(),
+                    "https://example.com/.well-known/openid-configuration"
+                );
+                stalled_response(HttpTimeout::Connect)
+            },
+            std::future::pending(),
+        )) {
+            Err(DiscoveryError::ConnectTimeout(MockTransportError(Some(HttpTimeout::Connect)))) => {
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_discovery_read_timeout() {
+        let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+
+        // The discovery document is served, but the JWKS endpoint stalls.
+        let result = block_on(CoreProviderMetadata::discover_async_with_timeout(
+            issuer_url,
+            |request: HttpRequest| {
+                let is_discovery =
+                    request.url.as_str() == "https://example.com/.well-known/openid-configuration";
+                async move {
+                    if is_discovery {
+                        discovery_response("https://example.com")
+                            .map_err(|_| MockTransportError(None))
+                    } else {
+                        stalled_response(HttpTimeout::Read).await
+                    }
+                }
+            },
+            std::future::pending(),
+        ));
+        match result {
+            Err(DiscoveryError::ReadTimeout(MockTransportError(Some(HttpTimeout::Read)))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Transport errors other than timeouts are still returned as request errors.
+        match block_on(CoreProviderMetadata::discover_async_with_timeout(
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            |_| async { Err(MockTransportError(None)) },
+            std::future::pending(),
+        )) {
+            Err(DiscoveryError::Request(MockTransportError(None))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid_json = "{\
+            \"issuer\":\"https://example.com\",\
+            \"authorization_endpoint\":\"https://example.com/authorize\",\
+            \"token_endpoint\":\"https://example.com/token\",\
+            \"jwks_uri\":\"https://example.com/jwks.json\",\
+            \"response_types_supported\":[\"code\",\"id_token\"],\
+            \"subject_types_supported\":[\"public\"],\
+            \"id_token_signing_alg_values_supported\":[\"RS256\",\"ES256\"]\
+        }";
+        let provider_metadata: CoreProviderMetadata = serde_json::from_str(valid_json).unwrap();
+        assert_eq!(provider_metadata.validate(), Ok(()));
+
+        // Missing REQUIRED URLs are rejected when parsing the metadata.
+        let mut missing_jwks_uri: serde_json::Value = serde_json::from_str(valid_json).unwrap();
+        missing_jwks_uri.as_object_mut().unwrap().remove("jwks_uri");
+        let err = serde_json::from_value::<CoreProviderMetadata>(missing_jwks_uri).unwrap_err();
+        assert!(err.to_string().contains("jwks_uri"), "{}", err);
+
+        assert_eq!(
+            provider_metadata
+                .clone()
+                .set_id_token_signing_alg_values_supported(vec![
+                    CoreJwsSigningAlgorithm::EcdsaP256Sha256
+                ])
+                .validate(),
+            Err(vec![ProviderMetadataValidationError::Rs256NotSupported])
+        );
+
+        assert_eq!(
+            provider_metadata
+                .clone()
+                .set_issuer(IssuerUrl::new("http://example.com?tenant=1".to_string()).unwrap())
+                .set_token_endpoint(None)
+                .set_subject_types_supported(vec![])
+                .set_id_token_signing_alg_values_supported(vec![])
+                .validate(),
+            Err(vec![
+                ProviderMetadataValidationError::InvalidIssuer(
+                    "http://example.com?tenant=1".to_string()
+                ),
+                ProviderMetadataValidationError::MissingField("subject_types_supported"),
+                ProviderMetadataValidationError::MissingField("token_endpoint"),
+                ProviderMetadataValidationError::MissingField(
+                    "id_token_signing_alg_values_supported"
+                ),
+                ProviderMetadataValidationError::Rs256NotSupported,
+            ])
+        );
+
+        // The token endpoint is optional for Implicit Flow-only providers.
+        assert_eq!(
+            provider_metadata
+                .set_token_endpoint(None)
+                .set_response_types_supported(vec![ResponseTypes::new(vec![
+                    CoreResponseType::IdToken
+                ])])
+                .validate(),
+            Ok(())
+        );
+    }
+
+    fn signed_metadata_verifier(
+        trust_anchor: &str,
+        key: &str,
+    ) -> CoreSignedMetadataVerifier<'static> {
+        CoreSignedMetadataVerifier::new(
+            IssuerUrl::new(trust_anchor.to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![serde_json::from_str(key).unwrap()]),
+        )
+    }
+
+    #[test]
+    fn test_verify_signed_metadata() {
+        let signed_metadata = claim_source_jwt(
+            "https://federation.example.com",
+            serde_json::json!({
+                "issuer": "https://example.com",
+                "token_endpoint": "https://example.com/signed/token",
+                "service_documentation": "https://example.com/signed/docs",
+                "iat": 1544928549,
+            }),
+        );
+        let provider_metadata: CoreProviderMetadata = serde_json::from_str(&format!(
+            "{{\
+                \"issuer\":\"https://example.com\",\
+                \"authorization_endpoint\":\"https://example.com/authorize\",\
+                \"token_endpoint\":\"https://example.com/unsigned/token\",\
+                \"jwks_uri\":\"https://example.com/jwks.json\",\
+                \"response_types_supported\":[\"code\"],\
+                \"subject_types_supported\":[\"public\"],\
+                \"id_token_signing_alg_values_supported\":[\"RS256\"],\
+                \"signed_metadata\":\"{}\"\
+            }}",
+            signed_metadata
+        ))
+        .unwrap();
+
+        let verified = provider_metadata
+            .clone()
+            .verify_signed_metadata(&signed_metadata_verifier(
+                "https://federation.example.com",
+                TEST_RSA_PUB_KEY,
+            ))
+            .unwrap();
+        // Signed values override unsigned ones, while unsigned values are otherwise retained.
+        assert_eq!(
+            verified.token_endpoint().unwrap().as_str(),
+            "https://example.com/signed/token"
+        );
+        assert_eq!(
+            verified.service_documentation().unwrap().as_str(),
+            "https://example.com/signed/docs"
+        );
+        assert_eq!(
+            verified.authorization_endpoint().as_str(),
+            "https://example.com/authorize"
+        );
+        assert_eq!(verified.signed_metadata(), Some(&signed_metadata));
+
+        // Signed by an unknown key.
+        match provider_metadata
+            .clone()
+            .verify_signed_metadata(&signed_metadata_verifier(
+                "https://federation.example.com",
+                TEST_EC_PUB_KEY_P256,
+            )) {
+            Err(DiscoveryError::SignedMetadata(
+                ClaimsVerificationError::SignatureVerification(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Tampered signature.
+        let mut tampered = signed_metadata.clone();
+        tampered.truncate(tampered.len() - 4);
+        tampered.push_str("AAAA");
+        match provider_metadata
+            .clone()
+            .set_signed_metadata(Some(tampered))
+            .verify_signed_metadata(&signed_metadata_verifier(
+                "https://federation.example.com",
+                TEST_RSA_PUB_KEY,
+            )) {
+            Err(DiscoveryError::SignedMetadata(
+                ClaimsVerificationError::SignatureVerification(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Signed by a party other than the trust anchor.
+        match provider_metadata
+            .clone()
+            .verify_signed_metadata(&signed_metadata_verifier(
+                "https://other-federation.example.com",
+                TEST_RSA_PUB_KEY,
+            )) {
+            Err(DiscoveryError::SignedMetadata(ClaimsVerificationError::InvalidIssuer(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match provider_metadata
+            .set_signed_metadata(None)
+            .verify_signed_metadata(&signed_metadata_verifier(
+                "https://federation.example.com",
+                TEST_RSA_PUB_KEY,
+            )) {
+            Err(DiscoveryError::Validation(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_discover_signed() {
+        let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let verifier = signed_metadata_verifier("https://federation.example.com", TEST_RSA_PUB_KEY);
+        let new_http_client = |signed_issuer: &'static str| {
+            move |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                match request.url.as_str() {
+                    "https://example.com/.well-known/openid-configuration" => {
+                        assert_eq!(
+                            request.headers.get(ACCEPT).unwrap(),
+                            "application/json, application/jwt"
+                        );
+                        Ok(HttpResponse {
+                            status_code: StatusCode::OK,
+                            headers: vec![(
+                                CONTENT_TYPE,
+                                HeaderValue::from_static("application/jwt"),
+                            )]
+                            .into_iter()
+                            .collect(),
+                            body: claim_source_jwt(
+                                "https://federation.example.com",
+                                serde_json::json!({
+                                    "issuer": signed_issuer,
+                                    "authorization_endpoint": "https://example.com/authorize",
+                                    "jwks_uri": "https://example.com/jwks.json",
+                                    "response_types_supported": ["code"],
+                                    "subject_types_supported": ["public"],
+                                    "id_token_signing_alg_values_supported": ["RS256"],
+                                }),
+                            )
+                            .into_bytes(),
+                        })
+                    }
+                    "https://example.com/jwks.json" => Ok(HttpResponse {
+                        status_code: StatusCode::OK,
+                        headers: vec![(CONTENT_TYPE, HeaderValue::from_static(MIME_TYPE_JSON))]
+                            .into_iter()
+                            .collect(),
+                        body: format!("{{\"keys\":[{}]}}", TEST_RSA_PUB_KEY).into_bytes(),
+                    }),
+                    other => panic!("unexpected request to {}", other),
+                }
+            }
+        };
+
+        let provider_metadata = CoreProviderMetadata::discover_signed(
+            &issuer_url,
+            &verifier,
+            new_http_client("https://example.com"),
+        )
+        .unwrap();
+        assert_eq!(provider_metadata.issuer(), &issuer_url);
+        assert_eq!(provider_metadata.jwks().keys().len(), 1);
+        assert!(provider_metadata.signed_metadata().is_some());
+
+        // The signed issuer must still match the requested issuer.
+        match CoreProviderMetadata::discover_signed(
+            &issuer_url,
+            &verifier,
+            new_http_client("https://attacker.example.com"),
+        ) {
+            Err(DiscoveryError::Validation(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Unsigned metadata is rejected.
+        match CoreProviderMetadata::discover_signed(&issuer_url, &verifier, |_| {
+            discovery_response("https://example.com")
+        }) {
+            Err(DiscoveryError::Validation(msg)) => {
+                assert_eq!(msg, "no signed_metadata value is present")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,646 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use http::header::{HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use http::method::Method;
+use http::status::StatusCode;
+use oauth2::AccessToken;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use url::Url;
+
+use crate::http_utils::DPOP;
+use crate::jwt::sign_compact_jws;
+use crate::types::helpers::serde_utc_seconds;
+use crate::{
+    HttpRequest, HttpResponse, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebTokenError,
+    JsonWebTokenId, JwsSigningAlgorithm, PrivateSigningKey, SigningError,
+};
+
+const DPOP_HEADER: &str = "dpop";
+const DPOP_NONCE_HEADER: &str = "dpop-nonce";
+const DPOP_JWT_TYPE: &str = "dpop+jwt";
+const USE_DPOP_NONCE_ERROR: &str = "use_dpop_nonce";
+
+#[derive(Serialize)]
+struct DPoPProofHeader<'a> {
+    typ: &'static str,
+    alg: &'a str,
+    jwk: &'a Value,
+}
+
+#[derive(Serialize)]
+struct DPoPProofClaims<'a> {
+    jti: JsonWebTokenId,
+    htm: &'a str,
+    htu: String,
+    #[serde(with = "serde_utc_seconds")]
+    iat: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ath: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nonce: Option<&'a str>,
+}
+
+///
+/// A client key pair used for generating DPoP proofs
+/// ([RFC 9449](https://tools.ietf.org/html/rfc9449)).
+///
+/// DPoP binds access (and refresh) tokens to this key pair so that a stolen token can't be used
+/// without the private key. Requests are authenticated by including a `DPoP` header containing a
+/// proof JWT signed by the private key, whose header contains the corresponding public key.
+///
+/// The simplest way to send DPoP proofs is to wrap the HTTP client passed to a request's
+/// `request` (or `request_async`) method using [`http_client`](Self::http_client) (or
+/// [`async_http_client`](Self::async_http_client)):
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "reqwest")]
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use openidconnect::core::{CoreClient, CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey};
+/// use openidconnect::reqwest::http_client;
+/// use openidconnect::{AuthorizationCode, DPoPKey};
+///
+/// # let client: CoreClient = unimplemented!();
+/// # let private_key_pem = "";
+/// let dpop_key = DPoPKey::new(
+///     CoreRsaPrivateSigningKey::from_pem(private_key_pem, None)?,
+///     CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+/// )?;
+///
+/// let token_response = client
+///     .exchange_code(AuthorizationCode::new("code".to_string()))
+///     .request(dpop_key.http_client(http_client))?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The wrapped HTTP client adds a proof to each request, binding it to the access token sent in
+/// any `Authorization: DPoP <access token>` request header. If the server responds with a
+/// `use_dpop_nonce` error and a `DPoP-Nonce` header, the request is retried once with a proof
+/// containing the nonce, which is also remembered (per origin) for subsequent requests. Failures
+/// to sign a proof are reported as [`DPoPHttpClientError::Proof`] errors.
+///
+#[derive(Clone)]
+pub struct DPoPKey {
+    alg: String,
+    jwk: Value,
+    jwk_thumbprint: String,
+    sign_fn: Arc<dyn Fn(&[u8]) -> Result<Vec<u8>, SigningError> + Send + Sync>,
+    nonces: Arc<Mutex<HashMap<String, String>>>,
+}
+impl DPoPKey {
+    ///
+    /// Initializes a DPoP key from the given private key and asymmetric signature algorithm.
+    ///
+    /// Returns an error if `alg` uses a shared secret, if the public key cannot be represented as
+    /// an RSA, EC, or OKP JSON Web Key, or if `signing_key` doesn't support `alg`.
+    ///
+    pub fn new<JS, JT, JU, K, S>(signing_key: S, alg: JS) -> Result<Self, JsonWebTokenError>
+    where
+        JS: JwsSigningAlgorithm<JT> + Send + Sync + 'static,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K> + Send + Sync + 'static,
+    {
+        let alg_name = serde_json::to_value(&alg)
+            .map_err(JsonWebTokenError::SerializationError)?
+            .as_str()
+            .expect("signature alg must serialize to a string")
+            .to_string();
+        if alg.uses_shared_secret() {
+            return Err(JsonWebTokenError::SigningError(
+                SigningError::UnsupportedAlg(alg_name),
+            ));
+        }
+        let jwk = serde_json::to_value(signing_key.as_verification_key())
+            .map_err(JsonWebTokenError::SerializationError)?;
+        let jwk_thumbprint = jwk_thumbprint(&jwk)
+            .map_err(|err| JsonWebTokenError::SigningError(SigningError::Other(err)))?;
+        let sign_fn = move |message: &[u8]| signing_key.sign(&alg, message);
+        // Ensure that the key supports the algorithm before any proofs are needed.
+        sign_fn(b"").map_err(JsonWebTokenError::SigningError)?;
+
+        Ok(Self {
+            alg: alg_name,
+            jwk,
+            jwk_thumbprint,
+            sign_fn: Arc::new(sign_fn),
+            nonces: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    ///
+    /// Returns the base64url-encoded SHA-256 JWK thumbprint
+    /// ([RFC 7638](https://tools.ietf.org/html/rfc7638)) of the public key.
+    ///
+    /// This is the value of the `jkt` confirmation claim of DPoP-bound access tokens, and of the
+    /// `dpop_jkt` authorization request parameter.
+    ///
+    pub fn jwk_thumbprint(&self) -> &str {
+        &self.jwk_thumbprint
+    }
+
+    ///
+    /// Generates a DPoP proof for a request with the given HTTP method and URL.
+    ///
+    /// The `htu` claim contains the URL without its query and fragment. If an access token is
+    /// provided, the proof is bound to it via the `ath` claim, as required for requests to
+    /// protected resources. The `nonce` should be the most recent `DPoP-Nonce` value provided by
+    /// the server, if any.
+    ///
+    pub fn proof(
+        &self,
+        method: &Method,
+        url: &Url,
+        access_token: Option<&AccessToken>,
+        nonce: Option<&str>,
+    ) -> Result<String, JsonWebTokenError> {
+        let header = DPoPProofHeader {
+            typ: DPOP_JWT_TYPE,
+            alg: &self.alg,
+            jwk: &self.jwk,
+        };
+        let claims = DPoPProofClaims {
+            jti: JsonWebTokenId::new_random(),
+            htm: method.as_str(),
+            htu: htu(url),
+            iat: Utc::now(),
+            ath: access_token.map(|access_token| {
+                base64::encode_config(
+                    Sha256::digest(access_token.secret().as_bytes()),
+                    base64::URL_SAFE_NO_PAD,
+                )
+            }),
+            nonce,
+        };
+
+        sign_compact_jws(&header, &claims, |signing_input| {
+            (self.sign_fn)(signing_input)
+        })
+    }
+
+    ///
+    /// Wraps a synchronous HTTP client so that each request includes a DPoP proof.
+    ///
+    /// See the [type-level documentation](Self) for details.
+    ///
+    pub fn http_client<'c, C, RE>(
+        &'c self,
+        http_client: C,
+    ) -> impl Fn(HttpRequest) -> Result<HttpResponse, DPoPHttpClientError<RE>> + 'c
+    where
+        C: Fn(HttpRequest) -> Result<HttpResponse, RE> + 'c,
+        RE: std::error::Error + 'static,
+    {
+        move |request| {
+            let send = |request| {
+                http_client(
+                    self.add_proof(request)
+                        .map_err(DPoPHttpClientError::Proof)?,
+                )
+                .map_err(DPoPHttpClientError::Request)
+            };
+            let response = send(request.clone())?;
+            if self.update_nonce(&request.url, &response) {
+                let response = send(request.clone())?;
+                self.update_nonce(&request.url, &response);
+                Ok(response)
+            } else {
+                Ok(response)
+            }
+        }
+    }
+
+    ///
+    /// Wraps an asynchronous HTTP client so that each request includes a DPoP proof.
+    ///
+    /// See the [type-level documentation](Self) for details.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn async_http_client<'c, C, F, RE>(
+        &'c self,
+        http_client: C,
+    ) -> impl Fn(
+        HttpRequest,
+    )
+        -> Pin<Box<dyn Future<Output = Result<HttpResponse, DPoPHttpClientError<RE>>> + 'c>>
+           + 'c
+    where
+        C: Fn(HttpRequest) -> F + 'c,
+        F: Future<Output = Result<HttpResponse, RE>> + 'c,
+        RE: std::error::Error + 'static,
+    {
+        let http_client = Arc::new(http_client);
+        move |request| {
+            let http_client = http_client.clone();
+            Box::pin(async move {
+                let send = |request| {
+                    let http_request = self.add_proof(request);
+                    let http_client = &http_client;
+                    async move {
+                        http_client(http_request.map_err(DPoPHttpClientError::Proof)?)
+                            .await
+                            .map_err(DPoPHttpClientError::Request)
+                    }
+                };
+                let response = send(request.clone()).await?;
+                if self.update_nonce(&request.url, &response) {
+                    let response = send(request.clone()).await?;
+                    self.update_nonce(&request.url, &response);
+                    Ok(response)
+                } else {
+                    Ok(response)
+                }
+            })
+        }
+    }
+
+    pub(crate) fn add_proof(
+        &self,
+        mut request: HttpRequest,
+    ) -> Result<HttpRequest, JsonWebTokenError> {
+        let access_token = request
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix(DPOP))
+            .and_then(|value| value.strip_prefix(' '))
+            .map(|access_token| AccessToken::new(access_token.to_string()));
+        let nonce = self
+            .nonces
+            .lock()
+            .expect("DPoP nonce lock poisoned")
+            .get(&request.url.origin().ascii_serialization())
+            .cloned();
+
+        let proof = self.proof(
+            &request.method,
+            &request.url,
+            access_token.as_ref(),
+            nonce.as_deref(),
+        )?;
+        request.headers.insert(
+            DPOP_HEADER,
+            HeaderValue::from_str(&proof).expect("invalid DPoP proof"),
+        );
+        Ok(request)
+    }
+
+    // Remembers any nonce provided by the server, and returns whether the request should be
+    // retried because the server rejected a proof that lacked the current nonce.
+    pub(crate) fn update_nonce(&self, url: &Url, response: &HttpResponse) -> bool {
+        let nonce = match response
+            .headers
+            .get(DPOP_NONCE_HEADER)
+            .and_then(|nonce| nonce.to_str().ok())
+        {
+            Some(nonce) => nonce.to_string(),
+            None => return false,
+        };
+        let previous_nonce = self
+            .nonces
+            .lock()
+            .expect("DPoP nonce lock poisoned")
+            .insert(url.origin().ascii_serialization(), nonce.clone());
+
+        // Avoid retrying indefinitely if the server keeps rejecting the same nonce.
+        is_use_dpop_nonce_error(response) && previous_nonce.as_deref() != Some(nonce.as_str())
+    }
+}
+impl Debug for DPoPKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatterResult {
+        f.debug_struct("DPoPKey")
+            .field("alg", &self.alg)
+            .field("jwk", &self.jwk)
+            .finish_non_exhaustive()
+    }
+}
+
+// Per Section 4.2 of RFC 9449, the `htu` claim excludes the query and fragment.
+///
+/// Error returned by HTTP clients wrapped using [`DPoPKey::http_client`] or
+/// [`DPoPKey::async_http_client`].
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum DPoPHttpClientError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    ///
+    /// Failed to sign the DPoP proof.
+    ///
+    #[error("Failed to sign DPoP proof")]
+    Proof(#[source] JsonWebTokenError),
+    ///
+    /// An error occurred while sending the request or receiving the response (e.g., network
+    /// connectivity failed).
+    ///
+    #[error("Request failed")]
+    Request(#[source] RE),
+}
+
+fn htu(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_query(None);
+    url.set_fragment(None);
+    url.to_string()
+}
+
+// Authorization servers return a `use_dpop_nonce` error in a JSON body (Section 8 of RFC 9449),
+// while resource servers return it in the `WWW-Authenticate` header (Section 9).
+fn is_use_dpop_nonce_error(response: &HttpResponse) -> bool {
+    match response.status_code {
+        StatusCode::BAD_REQUEST => serde_json::from_slice::<Value>(&response.body)
+            .ok()
+            .and_then(|body| body.get("error").cloned())
+            .map_or(false, |error| error == USE_DPOP_NONCE_ERROR),
+        StatusCode::UNAUTHORIZED => response
+            .headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| value.contains(USE_DPOP_NONCE_ERROR)),
+        _ => false,
+    }
+}
+
+// Computes the JWK thumbprint from the required members of the public key, serialized in
+// lexicographic order without whitespace (see Section 3 of RFC 7638).
+fn jwk_thumbprint(jwk: &Value) -> Result<String, String> {
+    let members: &[&str] = match jwk.get("kty").and_then(Value::as_str) {
+        Some("RSA") => &["e", "kty", "n"],
+        Some("EC") => &["crv", "kty", "x", "y"],
+        Some("OKP") => &["crv", "kty", "x"],
+        other => return Err(format!("unsupported DPoP key type: {:?}", other)),
+    };
+    let required_members = members
+        .iter()
+        .map(|member| {
+            jwk.get(*member)
+                .map(|value| (*member, value))
+                .ok_or_else(|| format!("DPoP key is missing `{}` member", member))
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    let canonical_jwk = serde_json::to_vec(&required_members).map_err(|err| err.to_string())?;
+    Ok(base64::encode_config(
+        Sha256::digest(canonical_jwk),
+        base64::URL_SAFE_NO_PAD,
+    ))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::cell::RefCell;
+
+    use http::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+    use http::method::Method;
+    use http::status::StatusCode;
+    use oauth2::{AccessToken, AuthUrl, AuthorizationCode, ClientId, TokenUrl};
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha256};
+    use url::Url;
+
+    use super::{htu, jwk_thumbprint, DPoPHttpClientError, DPoPKey};
+    use crate::core::{
+        CoreClient, CoreHmacKey, CoreJsonWebKey, CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey,
+    };
+    use crate::jwt::tests::TEST_RSA_PRIV_KEY;
+    use crate::tests::FailingSigningKey;
+    use crate::{
+        HttpRequest, HttpResponse, IssuerUrl, JsonWebKey, JsonWebKeySet, JsonWebTokenError,
+        OAuth2TokenResponse, SigningError,
+    };
+
+    pub fn new_dpop_key() -> DPoPKey {
+        DPoPKey::new(
+            CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap(),
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap()
+    }
+
+    // Verifies the proof's signature using the public key in its header, and returns the decoded
+    // header and claims.
+    pub fn decode_proof(proof: &str) -> (Value, Value) {
+        let parts = proof.split('.').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 3);
+        let decode_part = |part: &str| {
+            serde_json::from_slice::<Value>(
+                &base64::decode_config(part, base64::URL_SAFE_NO_PAD).unwrap(),
+            )
+            .unwrap()
+        };
+        let header = decode_part(parts[0]);
+        serde_json::from_value::<CoreJsonWebKey>(header["jwk"].clone())
+            .unwrap()
+            .verify_signature(
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                format!("{}.{}", parts[0], parts[1]).as_bytes(),
+                &base64::decode_config(parts[2], base64::URL_SAFE_NO_PAD).unwrap(),
+            )
+            .expect("invalid DPoP proof signature");
+        (header, decode_part(parts[1]))
+    }
+
+    #[test]
+    fn test_jwk_thumbprint() {
+        // Example from Section 3.1 of RFC 7638.
+        let jwk = json!({
+            "kty": "RSA",
+            "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6\
+                  tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-\
+                  65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNL\
+                  yrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJ\
+                  zKnqDKgw",
+            "e": "AQAB",
+            "alg": "RS256",
+            "kid": "2011-04-29"
+        });
+        assert_eq!(
+            jwk_thumbprint(&jwk).unwrap(),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+        assert!(jwk_thumbprint(&json!({"kty": "oct", "k": "secret"})).is_err());
+    }
+
+    #[test]
+    fn test_dpop_key_requires_asymmetric_alg() {
+        assert!(matches!(
+            DPoPKey::new(
+                CoreHmacKey::new("0123456789abcdef0123456789abcdef"),
+                CoreJwsSigningAlgorithm::HmacSha256,
+            ),
+            Err(JsonWebTokenError::SigningError(
+                SigningError::UnsupportedAlg(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_dpop_proof_signing_failure() {
+        // The first signature is used for validating the key when creating the DPoPKey.
+        let dpop_key = DPoPKey::new(
+            FailingSigningKey {
+                key: CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap(),
+                remaining: 1.into(),
+            },
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+
+        let result = dpop_key.http_client(|_| -> Result<HttpResponse, std::io::Error> {
+            panic!("request should not be sent")
+        })(HttpRequest {
+            url: Url::parse("https://example/token").unwrap(),
+            method: Method::POST,
+            headers: HeaderMap::new(),
+            body: vec![],
+        });
+        assert!(matches!(
+            result,
+            Err(DPoPHttpClientError::Proof(JsonWebTokenError::SigningError(
+                SigningError::Other(_)
+            )))
+        ));
+    }
+
+    #[test]
+    fn test_dpop_proof() {
+        let dpop_key = new_dpop_key();
+        let url = Url::parse("https://example/resource?query=1#fragment").unwrap();
+
+        let (header, claims) =
+            decode_proof(&dpop_key.proof(&Method::POST, &url, None, None).unwrap());
+        assert_eq!(header["typ"], "dpop+jwt");
+        assert_eq!(header["alg"], "RS256");
+        assert_eq!(header["jwk"]["kty"], "RSA");
+        assert!(header["jwk"].get("d").is_none());
+        assert_eq!(
+            dpop_key.jwk_thumbprint(),
+            jwk_thumbprint(&header["jwk"]).unwrap()
+        );
+        assert_eq!(claims["htm"], "POST");
+        assert_eq!(claims["htu"], "https://example/resource");
+        assert!(!claims["jti"].as_str().unwrap().is_empty());
+        assert!((claims["iat"].as_i64().unwrap() - chrono::Utc::now().timestamp()).abs() <= 5);
+        assert!(claims.get("ath").is_none());
+        assert!(claims.get("nonce").is_none());
+
+        let access_token = AccessToken::new("access token".to_string());
+        let (_, claims) = decode_proof(
+            &dpop_key
+                .proof(&Method::GET, &url, Some(&access_token), Some("abc"))
+                .unwrap(),
+        );
+        assert_eq!(claims["htm"], "GET");
+        assert_eq!(
+            claims["ath"],
+            base64::encode_config(Sha256::digest(b"access token"), base64::URL_SAFE_NO_PAD)
+        );
+        assert_eq!(claims["nonce"], "abc");
+    }
+
+    #[test]
+    fn test_htu() {
+        assert_eq!(
+            htu(&Url::parse("https://example:443/token?a=b&c=d#frag").unwrap()),
+            "https://example/token"
+        );
+        assert_eq!(
+            htu(&Url::parse("https://example:8443/a/b/?x").unwrap()),
+            "https://example:8443/a/b/"
+        );
+    }
+
+    #[test]
+    fn test_dpop_nonce_retry() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            None,
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            None,
+            JsonWebKeySet::default(),
+        );
+        let dpop_key = new_dpop_key();
+
+        let json_headers = |nonce: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            headers.insert("DPoP-Nonce", HeaderValue::from_static(nonce));
+            headers
+        };
+        let nonces = RefCell::new(Vec::new());
+        let token_response = client
+            .exchange_code(AuthorizationCode::new("code".to_string()))
+            .request(dpop_key.http_client(|request: HttpRequest| {
+                let (_, claims) = decode_proof(request.headers["DPoP"].to_str().unwrap());
+                assert_eq!(claims["htm"], "POST");
+                assert_eq!(claims["htu"], "https://example/token");
+                nonces.borrow_mut().push(claims["nonce"].clone());
+                Ok::<_, std::io::Error>(if claims.get("nonce").is_none() {
+                    HttpResponse {
+                        status_code: StatusCode::BAD_REQUEST,
+                        headers: json_headers("nonce1"),
+                        body: b"{\"error\": \"use_dpop_nonce\"}".to_vec(),
+                    }
+                } else {
+                    HttpResponse {
+                        status_code: StatusCode::OK,
+                        headers: json_headers("nonce2"),
+                        body: b"{\"access_token\": \"token\", \"token_type\": \"DPoP\"}".to_vec(),
+                    }
+                })
+            }))
+            .unwrap();
+        assert_eq!(token_response.access_token().secret(), "token");
+        assert_eq!(*nonces.borrow(), vec![Value::Null, json!("nonce1")]);
+
+        // Later requests to the same origin use the most recent nonce, and proofs bind the access
+        // token sent in a DPoP authorization header. A server rejecting the same nonce again
+        // doesn't cause an infinite loop.
+        let requests = RefCell::new(0);
+        let response = dpop_key.http_client(|request: HttpRequest| {
+            *requests.borrow_mut() += 1;
+            let (_, claims) = decode_proof(request.headers["DPoP"].to_str().unwrap());
+            assert_eq!(claims["htm"], "GET");
+            assert_eq!(claims["htu"], "https://example/userinfo");
+            assert_eq!(claims["nonce"], "nonce2");
+            assert_eq!(
+                claims["ath"],
+                base64::encode_config(Sha256::digest(b"token"), base64::URL_SAFE_NO_PAD)
+            );
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                WWW_AUTHENTICATE,
+                HeaderValue::from_static("DPoP error=\"use_dpop_nonce\""),
+            );
+            headers.insert("DPoP-Nonce", HeaderValue::from_static("nonce2"));
+            Ok::<_, std::io::Error>(HttpResponse {
+                status_code: StatusCode::UNAUTHORIZED,
+                headers,
+                body: vec![],
+            })
+        })(HttpRequest {
+            url: Url::parse("https://example/userinfo").unwrap(),
+            method: Method::GET,
+            headers: vec![(AUTHORIZATION, HeaderValue::from_static("DPoP token"))]
+                .into_iter()
+                .collect(),
+            body: vec![],
+        })
+        .unwrap();
+        assert_eq!(response.status_code, StatusCode::UNAUTHORIZED);
+        assert_eq!(*requests.borrow(), 1);
+    }
+}
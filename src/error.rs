@@ -0,0 +1,455 @@
+use std::fmt::Display;
+
+use http::status::StatusCode;
+use oauth2::{ErrorResponseType, RequestTokenError, StandardErrorResponse};
+use thiserror::Error;
+
+use crate::{ClaimsVerificationError, DiscoveryError, SignatureVerificationError, UserInfoError};
+
+///
+/// Error returned by an OpenID Connect request, classified by the kind of failure.
+///
+/// The errors returned by individual requests (e.g., [`DiscoveryError`], [`UserInfoError`], or
+/// [`RequestTokenError`]) convert into this type using `?` or `map_err(OpenIdConnectError::from)`.
+/// This makes it possible to decide how to handle a failure without matching on each request's
+/// error type: [`Transport`](Self::Transport) and [`Timeout`](Self::Timeout) errors are typically
+/// transient, while [`Deserialization`](Self::Deserialization) and
+/// [`Validation`](Self::Validation) errors indicate a misbehaving provider or a misconfigured
+/// client and are unlikely to succeed if retried. Use [`is_transient`](Self::is_transient) to
+/// determine whether a request may be retried.
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum OpenIdConnectError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    ///
+    /// An error occurred while sending the request or receiving the response (e.g., network
+    /// connectivity failed).
+    ///
+    #[error("Failed to send request or receive response; check network connectivity")]
+    Transport(#[source] RE),
+    ///
+    /// The request did not complete before the caller-supplied deadline elapsed.
+    ///
+    #[error("Request timed out")]
+    Timeout,
+    ///
+    /// The server's response could not be parsed (e.g., it was not valid JSON, had an unexpected
+    /// `Content-Type`, or was missing required fields).
+    ///
+    #[error("Failed to parse server response: {message}")]
+    Deserialization {
+        /// Description of the parse failure, including the path to the offending field, if known.
+        message: String,
+        /// Raw response body, if available.
+        body: Vec<u8>,
+    },
+    ///
+    /// The server returned an error response, such as an OAuth 2.0 error (see
+    /// [Section 5.2 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-5.2)) or an
+    /// unsuccessful HTTP status code.
+    ///
+    #[error("{}", protocol_error_message(*.status_code, .error, .error_description))]
+    Protocol {
+        /// HTTP status code of the response, if known.
+        status_code: Option<StatusCode>,
+        /// OAuth 2.0 error code (e.g., `invalid_grant`), if the response included one.
+        error: Option<String>,
+        /// Human-readable description of the error, if the response included one.
+        error_description: Option<String>,
+        /// Raw response body.
+        body: Vec<u8>,
+    },
+    ///
+    /// The server's response was parsed successfully but failed validation (e.g., an invalid
+    /// signature or claim).
+    ///
+    #[error("Failed to validate server response: {0}")]
+    Validation(#[source] ClaimsVerificationError),
+    ///
+    /// An unexpected error occurred.
+    ///
+    #[error("Other error: {0}")]
+    Other(String),
+}
+impl<RE> OpenIdConnectError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    ///
+    /// Returns whether the failure is likely transient, in which case the request may succeed if
+    /// retried.
+    ///
+    /// Transport errors, timeouts, `5xx` and `429 Too Many Requests` responses, and
+    /// `temporarily_unavailable` OAuth 2.0 errors are considered transient.
+    ///
+    pub fn is_transient(&self) -> bool {
+        match self {
+            OpenIdConnectError::Transport(_) | OpenIdConnectError::Timeout => true,
+            OpenIdConnectError::Protocol {
+                status_code, error, ..
+            } => {
+                status_code.map_or(false, |status_code| {
+                    status_code.is_server_error() || status_code == StatusCode::TOO_MANY_REQUESTS
+                }) || error.as_deref() == Some("temporarily_unavailable")
+            }
+            _ => false,
+        }
+    }
+
+    // Classifies an HTTP response that the request rejected as invalid. A successful status code
+    // means the response body or headers couldn't be parsed, while other status codes indicate an
+    // error response, which may include an OAuth 2.0 error body.
+    fn from_response(status_code: StatusCode, body: Vec<u8>, message: String) -> Self {
+        #[derive(Deserialize)]
+        struct ErrorBody {
+            error: String,
+            error_description: Option<String>,
+        }
+
+        if status_code.is_success() {
+            return OpenIdConnectError::Deserialization { message, body };
+        }
+
+        let (error, error_description) = match serde_json::from_slice::<ErrorBody>(&body) {
+            Ok(error_body) => (Some(error_body.error), error_body.error_description),
+            Err(_) => (None, None),
+        };
+        OpenIdConnectError::Protocol {
+            status_code: Some(status_code),
+            error,
+            error_description,
+            body,
+        }
+    }
+}
+
+fn protocol_error_message(
+    status_code: Option<StatusCode>,
+    error: &Option<String>,
+    error_description: &Option<String>,
+) -> String {
+    let mut message = "Server returned error response".to_string();
+    if let Some(status_code) = status_code {
+        message.push_str(&format!(" with HTTP status code {}", status_code));
+    }
+    if let Some(error) = error {
+        message.push_str(&format!(": {}", error));
+        if let Some(error_description) = error_description {
+            message.push_str(&format!(" ({})", error_description));
+        }
+    }
+    message
+}
+
+impl<RE, T> From<RequestTokenError<RE, StandardErrorResponse<T>>> for OpenIdConnectError<RE>
+where
+    RE: std::error::Error + 'static,
+    T: AsRef<str> + ErrorResponseType + Display + 'static,
+{
+    fn from(err: RequestTokenError<RE, StandardErrorResponse<T>>) -> Self {
+        match err {
+            RequestTokenError::ServerResponse(err) => OpenIdConnectError::Protocol {
+                status_code: None,
+                error: Some(err.error().as_ref().to_string()),
+                error_description: err.error_description().cloned(),
+                body: serde_json::to_vec(&err).unwrap_or_default(),
+            },
+            RequestTokenError::Request(err) => OpenIdConnectError::Transport(err),
+            RequestTokenError::Parse(err, body) => OpenIdConnectError::Deserialization {
+                message: err.to_string(),
+                body,
+            },
+            RequestTokenError::Other(message) => OpenIdConnectError::Other(message),
+        }
+    }
+}
+impl<RE> From<DiscoveryError<RE>> for OpenIdConnectError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    fn from(err: DiscoveryError<RE>) -> Self {
+        match err {
+            DiscoveryError::Request(err)
+            | DiscoveryError::ConnectTimeout(err)
+            | DiscoveryError::ReadTimeout(err) => OpenIdConnectError::Transport(err),
+            DiscoveryError::Timeout => OpenIdConnectError::Timeout,
+            DiscoveryError::Parse(err) => OpenIdConnectError::Deserialization {
+                message: err.to_string(),
+                body: Vec::new(),
+            },
+            DiscoveryError::Response(status_code, body, message) => {
+                Self::from_response(status_code, body, message)
+            }
+            DiscoveryError::SignedMetadata(err) => OpenIdConnectError::Validation(err),
+            DiscoveryError::Validation(message) => {
+                OpenIdConnectError::Validation(ClaimsVerificationError::Other(message))
+            }
+            err @ (DiscoveryError::UrlParse(_)
+            | DiscoveryError::IssuerNotFound(_)
+            | DiscoveryError::Other(_)) => OpenIdConnectError::Other(err.to_string()),
+        }
+    }
+}
+impl<RE> From<UserInfoError<RE>> for OpenIdConnectError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    fn from(err: UserInfoError<RE>) -> Self {
+        match err {
+            UserInfoError::Request(err) => OpenIdConnectError::Transport(err),
+            UserInfoError::Parse(err) => OpenIdConnectError::Deserialization {
+                message: err.to_string(),
+                body: Vec::new(),
+            },
+            UserInfoError::Response(status_code, body, message) => {
+                Self::from_response(status_code, body, message)
+            }
+            UserInfoError::ClaimsVerification(err) => OpenIdConnectError::Validation(err),
+            UserInfoError::Other(message) => OpenIdConnectError::Other(message),
+        }
+    }
+}
+impl<RE> From<ClaimsVerificationError> for OpenIdConnectError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    fn from(err: ClaimsVerificationError) -> Self {
+        OpenIdConnectError::Validation(err)
+    }
+}
+impl<RE> From<SignatureVerificationError> for OpenIdConnectError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    fn from(err: SignatureVerificationError) -> Self {
+        OpenIdConnectError::Validation(ClaimsVerificationError::SignatureVerification(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use http::header::{HeaderValue, CONTENT_TYPE};
+    use http::status::StatusCode;
+    use oauth2::AuthorizationCode;
+
+    use crate::core::{CoreClient, CoreGenderClaim, CoreProviderMetadata, CoreTokenResponse};
+    use crate::tests::mock_json_response;
+    use crate::{
+        AccessToken, ClaimsVerificationError, DiscoveryError, EmptyAdditionalClaims, HttpRequest,
+        HttpResponse, IssuerUrl, Nonce, OpenIdConnectError, SignatureVerificationError,
+        TokenResponse, UserInfoUrl,
+    };
+
+    fn new_client() -> CoreClient {
+        let mut client = crate::tests::new_client();
+        client.userinfo_endpoint =
+            Some(UserInfoUrl::new("https://example/userinfo".to_string()).unwrap());
+        client
+    }
+
+    fn html_response(status_code: StatusCode, body: &str) -> Result<HttpResponse, std::io::Error> {
+        Ok(HttpResponse {
+            status_code,
+            headers: vec![(CONTENT_TYPE, HeaderValue::from_static("text/html"))]
+                .into_iter()
+                .collect(),
+            body: body.as_bytes().to_vec(),
+        })
+    }
+
+    fn user_info(
+        http_response: Result<HttpResponse, std::io::Error>,
+    ) -> OpenIdConnectError<std::io::Error> {
+        let http_response = RefCell::new(Some(http_response));
+        new_client()
+            .user_info(AccessToken::new("access_token".to_string()), None)
+            .unwrap()
+            .request::<EmptyAdditionalClaims, CoreGenderClaim, _, _>(|_: HttpRequest| {
+                http_response.borrow_mut().take().unwrap()
+            })
+            .map_err(OpenIdConnectError::from)
+            .expect_err("user info request should fail")
+    }
+
+    fn exchange_code(
+        http_response: Result<HttpResponse, std::io::Error>,
+    ) -> OpenIdConnectError<std::io::Error> {
+        let mut http_response = Some(http_response);
+        new_client()
+            .exchange_code(AuthorizationCode::new("code".to_string()))
+            .request(|_| http_response.take().unwrap())
+            .map_err(OpenIdConnectError::from)
+            .expect_err("token request should fail")
+    }
+
+    #[test]
+    fn test_transport_error() {
+        let err = exchange_code(Err(std::io::Error::new(
+            std::io::ErrorKind::ConnectionRefused,
+            "connection refused",
+        )));
+        assert!(
+            matches!(err, OpenIdConnectError::Transport(ref err) if err.kind() == std::io::ErrorKind::ConnectionRefused)
+        );
+        assert!(err.is_transient());
+        assert_eq!(
+            err.to_string(),
+            "Failed to send request or receive response; check network connectivity"
+        );
+
+        let err =
+            OpenIdConnectError::<std::io::Error>::from(DiscoveryError::<std::io::Error>::Timeout);
+        assert!(matches!(err, OpenIdConnectError::Timeout));
+        assert!(err.is_transient());
+    }
+
+    #[test]
+    fn test_deserialization_error() {
+        let err = exchange_code(mock_json_response(
+            StatusCode::OK,
+            "{\"access_token\": \"12/34\"}",
+        ));
+        match err {
+            OpenIdConnectError::Deserialization {
+                ref message,
+                ref body,
+            } => {
+                assert!(message.contains("token_type"), "{}", message);
+                assert_eq!(body, b"{\"access_token\": \"12/34\"}");
+            }
+            ref other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(!err.is_transient());
+
+        // A successful response with the wrong content type also fails to parse.
+        let err = user_info(html_response(StatusCode::OK, "<html></html>"));
+        match err {
+            OpenIdConnectError::Deserialization { ref body, .. } => {
+                assert_eq!(body, b"<html></html>")
+            }
+            ref other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err
+            .to_string()
+            .starts_with("Failed to parse server response: "));
+    }
+
+    #[test]
+    fn test_protocol_error() {
+        let err = exchange_code(mock_json_response(
+            StatusCode::BAD_REQUEST,
+            "{\"error\": \"invalid_grant\", \"error_description\": \"code expired\"}",
+        ));
+        match err {
+            OpenIdConnectError::Protocol {
+                status_code,
+                ref error,
+                ref error_description,
+                ..
+            } => {
+                assert_eq!(status_code, None);
+                assert_eq!(error.as_deref(), Some("invalid_grant"));
+                assert_eq!(error_description.as_deref(), Some("code expired"));
+            }
+            ref other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(!err.is_transient());
+        assert_eq!(
+            err.to_string(),
+            "Server returned error response: invalid_grant (code expired)"
+        );
+
+        let err = exchange_code(mock_json_response(
+            StatusCode::BAD_REQUEST,
+            "{\"error\": \"temporarily_unavailable\"}",
+        ));
+        assert!(err.is_transient());
+
+        let err = OpenIdConnectError::from(
+            CoreProviderMetadata::discover(
+                &IssuerUrl::new("https://example".to_string()).unwrap(),
+                |_| html_response(StatusCode::SERVICE_UNAVAILABLE, "Unavailable"),
+            )
+            .unwrap_err(),
+        );
+        match err {
+            OpenIdConnectError::Protocol {
+                status_code,
+                ref error,
+                ref body,
+                ..
+            } => {
+                assert_eq!(status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+                assert_eq!(*error, None);
+                assert_eq!(body, b"Unavailable");
+            }
+            ref other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err.is_transient());
+        assert_eq!(
+            err.to_string(),
+            "Server returned error response with HTTP status code 503 Service Unavailable"
+        );
+
+        // Non-2xx responses with an OAuth 2.0 error body expose the error code.
+        let err = user_info(mock_json_response(
+            StatusCode::FORBIDDEN,
+            "{\"error\": \"insufficient_scope\"}",
+        ));
+        match err {
+            OpenIdConnectError::Protocol {
+                status_code,
+                ref error,
+                ..
+            } => {
+                assert_eq!(status_code, Some(StatusCode::FORBIDDEN));
+                assert_eq!(error.as_deref(), Some("insufficient_scope"));
+            }
+            ref other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(!err.is_transient());
+    }
+
+    #[test]
+    fn test_validation_error() {
+        let token_response: CoreTokenResponse = serde_json::from_str(
+            "{\"access_token\": \"12/34\", \"token_type\": \"bearer\", \"id_token\": \
+             \"eyJhbGciOiJSUzI1NiJ9.eyJpc3MiOiJodHRwczovL2V4YW1wbGUiLCJhdWQiOiJhYWEiLCJzdWIiO\
+             iJzdWJqZWN0IiwiZXhwIjoxLCJpYXQiOjF9.c2lnbmF0dXJl\"}",
+        )
+        .unwrap();
+        let client = new_client();
+        let err = OpenIdConnectError::<std::io::Error>::from(
+            token_response
+                .id_token()
+                .unwrap()
+                .claims(
+                    &client.id_token_verifier(),
+                    &Nonce::new("nonce".to_string()),
+                )
+                .unwrap_err(),
+        );
+        assert!(matches!(
+            err,
+            OpenIdConnectError::Validation(ClaimsVerificationError::SignatureVerification(_))
+        ));
+        assert!(!err.is_transient());
+        assert!(err
+            .to_string()
+            .starts_with("Failed to validate server response: "));
+
+        let err =
+            OpenIdConnectError::<std::io::Error>::from(SignatureVerificationError::NoMatchingKey);
+        assert!(matches!(
+            err,
+            OpenIdConnectError::Validation(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey
+            ))
+        ));
+    }
+}
@@ -7,6 +7,8 @@ use std::cmp::PartialEq;
 use std::fmt::{Debug, Formatter, Result as FormatterResult};
 use std::marker::PhantomData;
 
+use subtle::ConstantTimeEq;
+
 pub(crate) trait FlattenFilter {
     fn should_include(field_name: &str) -> bool;
 }
@@ -182,3 +184,11 @@ where
         Debug::fmt(&self.inner, f)
     }
 }
+
+/// Compares two strings in constant time (with respect to their contents) to avoid leaking
+/// information about secret or security-sensitive values (e.g., nonces and token hashes) through
+/// timing side channels. Strings of different lengths compare unequal without examining their
+/// contents.
+pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
@@ -0,0 +1,646 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http::header::{HeaderMap, RETRY_AFTER};
+use http::status::StatusCode;
+
+use crate::{HttpRequest, HttpResponse};
+
+///
+/// Synchronous HTTP client.
+///
+/// This trait is implemented for any `Fn(HttpRequest) -> Result<HttpResponse, RE>`, including
+/// the feature-gated backends ([`reqwest::http_client`](crate::reqwest::http_client),
+/// `curl::http_client`, and `ureq::http_client`), as well as the corresponding
+/// `ReqwestHttpClient`, `CurlHttpClient`, and `UreqHttpClient` types. Custom transports may
+/// implement it directly. Request builders defined by this crate accept any implementation, while
+/// those provided by the `oauth2` crate (e.g.,
+/// [`CodeTokenRequest::request`](crate::CodeTokenRequest::request)) require wrapping it using
+/// [`http_client_fn`].
+///
+pub trait SyncHttpClient {
+    ///
+    /// Error returned by the underlying transport.
+    ///
+    type Error: std::error::Error + 'static;
+
+    ///
+    /// Sends the HTTP request and returns the response.
+    ///
+    fn call(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error>;
+}
+impl<C, RE> SyncHttpClient for C
+where
+    C: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+    RE: std::error::Error + 'static,
+{
+    type Error = RE;
+
+    fn call(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+        self(request)
+    }
+}
+
+///
+/// Asynchronous HTTP client.
+///
+/// This trait is implemented for any `Fn(HttpRequest) -> F` where `F` resolves to a
+/// `Result<HttpResponse, RE>`, including the feature-gated
+/// [`reqwest::async_http_client`](crate::reqwest::async_http_client) backend, as well as the
+/// `ReqwestHttpClient` type. Custom transports (e.g., hyper or a WASM `fetch` shim) may implement
+/// it directly. Transports whose futures borrow the client should implement this trait for a
+/// reference to the client type (e.g., `&'a MyHttpClient`). Request builders defined by this
+/// crate accept any implementation, while those provided by the `oauth2` crate (e.g.,
+/// [`CodeTokenRequest::request_async`](crate::CodeTokenRequest::request_async)) require wrapping
+/// it using [`async_http_client_fn`].
+///
+pub trait AsyncHttpClient {
+    ///
+    /// Error returned by the underlying transport.
+    ///
+    type Error: std::error::Error + 'static;
+
+    ///
+    /// Future resolving to the HTTP response.
+    ///
+    type Future: Future<Output = Result<HttpResponse, Self::Error>>;
+
+    ///
+    /// Sends the HTTP request and returns a future resolving to the response.
+    ///
+    fn call(&self, request: HttpRequest) -> Self::Future;
+}
+impl<C, F, RE> AsyncHttpClient for C
+where
+    C: Fn(HttpRequest) -> F,
+    F: Future<Output = Result<HttpResponse, RE>>,
+    RE: std::error::Error + 'static,
+{
+    type Error = RE;
+    type Future = F;
+
+    fn call(&self, request: HttpRequest) -> Self::Future {
+        self(request)
+    }
+}
+
+///
+/// Phase of an HTTP request that exceeded a timeout configured on the HTTP client.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HttpTimeout {
+    ///
+    /// The connection to the server could not be established in time.
+    ///
+    Connect,
+    ///
+    /// The connection was established, but the server did not send the response in time.
+    ///
+    Read,
+}
+
+///
+/// HTTP client error that reports whether the request timed out, and in which phase.
+///
+/// Used by [`ProviderMetadata::discover_async_with_timeout`](crate::ProviderMetadata::discover_async_with_timeout)
+/// to distinguish connect and read timeouts. This trait is implemented for the `reqwest` backend
+/// errors and for [`std::io::Error`] (which never reports a timeout). Custom transports may
+/// implement it for their own error types; the default implementation never reports a timeout.
+///
+pub trait ClassifyTimeout: std::error::Error + 'static {
+    ///
+    /// Returns the phase in which the request timed out, or `None` if the error is not a timeout.
+    ///
+    fn timeout(&self) -> Option<HttpTimeout> {
+        None
+    }
+}
+// I/O errors do not identify the phase that timed out, so they are never classified.
+impl ClassifyTimeout for std::io::Error {}
+#[cfg(feature = "reqwest")]
+impl ClassifyTimeout for reqwest_::Error {
+    fn timeout(&self) -> Option<HttpTimeout> {
+        if !self.is_timeout() {
+            None
+        } else if self.is_connect() {
+            Some(HttpTimeout::Connect)
+        } else {
+            Some(HttpTimeout::Read)
+        }
+    }
+}
+#[cfg(feature = "reqwest")]
+impl<T> ClassifyTimeout for crate::reqwest::Error<T>
+where
+    T: ClassifyTimeout,
+{
+    fn timeout(&self) -> Option<HttpTimeout> {
+        match self {
+            crate::reqwest::Error::Reqwest(err) => err.timeout(),
+            crate::reqwest::Error::Io(err) => err.timeout(),
+            _ => None,
+        }
+    }
+}
+
+///
+/// Adapts a [`SyncHttpClient`] into the closure form accepted by the synchronous `request`
+/// methods of the request builders (e.g., [`CodeTokenRequest::request`](crate::CodeTokenRequest::request)).
+///
+pub fn http_client_fn<C>(
+    http_client: &C,
+) -> impl Fn(HttpRequest) -> Result<HttpResponse, C::Error> + '_
+where
+    C: SyncHttpClient,
+{
+    move |request| http_client.call(request)
+}
+
+///
+/// Adapts an [`AsyncHttpClient`] into the closure form accepted by the asynchronous
+/// `request_async` methods of the request builders (e.g.,
+/// [`CodeTokenRequest::request_async`](crate::CodeTokenRequest::request_async)).
+///
+pub fn async_http_client_fn<C>(http_client: &C) -> impl Fn(HttpRequest) -> C::Future + '_
+where
+    C: AsyncHttpClient,
+{
+    move |request| http_client.call(request)
+}
+
+///
+/// [`SyncHttpClient`] and [`AsyncHttpClient`] backed by [`reqwest`](crate::reqwest).
+///
+/// The synchronous client uses [`reqwest::http_client`](crate::reqwest::http_client), and the
+/// asynchronous client uses [`reqwest::async_http_client`](crate::reqwest::async_http_client).
+///
+#[cfg(feature = "reqwest")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReqwestHttpClient;
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+impl SyncHttpClient for ReqwestHttpClient {
+    type Error = crate::reqwest::HttpClientError;
+
+    fn call(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+        crate::reqwest::http_client(request)
+    }
+}
+#[cfg(feature = "reqwest")]
+impl AsyncHttpClient for ReqwestHttpClient {
+    type Error = crate::reqwest::AsyncHttpClientError;
+    #[cfg(not(target_arch = "wasm32"))]
+    type Future = Pin<Box<dyn Future<Output = Result<HttpResponse, Self::Error>> + Send>>;
+    #[cfg(target_arch = "wasm32")]
+    type Future = Pin<Box<dyn Future<Output = Result<HttpResponse, Self::Error>>>>;
+
+    fn call(&self, request: HttpRequest) -> Self::Future {
+        Box::pin(crate::reqwest::async_http_client(request))
+    }
+}
+
+///
+/// [`SyncHttpClient`] backed by [`curl::http_client`](crate::curl::http_client).
+///
+#[cfg(all(feature = "curl", not(target_arch = "wasm32")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CurlHttpClient;
+#[cfg(all(feature = "curl", not(target_arch = "wasm32")))]
+impl SyncHttpClient for CurlHttpClient {
+    type Error = crate::curl::Error;
+
+    fn call(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+        crate::curl::http_client(request)
+    }
+}
+
+///
+/// [`SyncHttpClient`] backed by [`ureq::http_client`](crate::ureq::http_client).
+///
+#[cfg(feature = "ureq")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UreqHttpClient;
+#[cfg(feature = "ureq")]
+impl SyncHttpClient for UreqHttpClient {
+    type Error = crate::ureq::Error;
+
+    fn call(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+        crate::ureq::http_client(request)
+    }
+}
+
+///
+/// Status code and headers of an unsuccessful HTTP response.
+///
+/// Errors such as [`RequestTokenError::ServerResponse`](crate::RequestTokenError::ServerResponse)
+/// only contain the parsed error body. An [`HttpErrorRecorder`] may be used to retain the
+/// corresponding status code and headers (e.g., `Retry-After` on a `429 Too Many Requests` or
+/// `503 Service Unavailable` response).
+///
+#[derive(Clone, Debug)]
+pub struct HttpErrorResponse {
+    status_code: StatusCode,
+    headers: HeaderMap,
+}
+impl HttpErrorResponse {
+    ///
+    /// Returns the HTTP status code.
+    ///
+    pub fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    ///
+    /// Returns the HTTP response headers.
+    ///
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    ///
+    /// Returns how long to wait before retrying, as indicated by the `Retry-After` header.
+    ///
+    /// Both the delay-seconds and HTTP-date forms are supported. A date in the past results in
+    /// a zero delay. Returns `None` if the header is absent or invalid.
+    ///
+    /// See <https://www.rfc-editor.org/rfc/rfc9110#section-10.2.3>
+    ///
+    pub fn retry_after(&self) -> Option<Duration> {
+        let value = self.headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+        let date = DateTime::parse_from_rfc2822(value).ok()?;
+        Some(
+            (date.with_timezone(&Utc) - Utc::now())
+                .to_std()
+                .unwrap_or(Duration::ZERO),
+        )
+    }
+}
+
+///
+/// Records the status code and headers of unsuccessful HTTP responses.
+///
+/// The recorder wraps an HTTP client passed to any request builder. After a failed request,
+/// [`last_error_response`](Self::last_error_response) returns the details of the response to the
+/// most recent HTTP request if it had a non-`2xx` status code. The recorded response is cleared
+/// when each HTTP request starts, so a request that succeeds (e.g., after being retried) or fails
+/// without a response doesn't leave a stale error response behind. Since clones of a recorder
+/// share the same recorded response, a separate recorder should be used for each concurrent
+/// request:
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "reqwest")]
+/// # fn err_wrapper(client: openidconnect::core::CoreClient) -> Result<(), anyhow::Error> {
+/// use openidconnect::reqwest::http_client;
+/// use openidconnect::{AuthorizationCode, HttpErrorRecorder};
+///
+/// let recorder = HttpErrorRecorder::new();
+/// let token_result = client
+///     .exchange_code(AuthorizationCode::new("some authorization code".to_string()))
+///     .request(recorder.http_client(http_client));
+/// if token_result.is_err() {
+///     if let Some(retry_after) = recorder.last_error_response().and_then(|r| r.retry_after()) {
+///         println!("retrying in {:?}", retry_after);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Clone, Debug, Default)]
+pub struct HttpErrorRecorder {
+    last_error_response: Arc<Mutex<Option<HttpErrorResponse>>>,
+}
+impl HttpErrorRecorder {
+    ///
+    /// Creates a new recorder.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Returns the details of the response to the most recent HTTP request, if it was
+    /// unsuccessful.
+    ///
+    pub fn last_error_response(&self) -> Option<HttpErrorResponse> {
+        self.last_error_response
+            .lock()
+            .expect("recorder lock poisoned")
+            .clone()
+    }
+
+    ///
+    /// Wraps a synchronous HTTP client so that unsuccessful responses are recorded.
+    ///
+    pub fn http_client<HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> impl Fn(HttpRequest) -> Result<HttpResponse, RE>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let recorder = self.clone();
+        move |request| {
+            recorder.clear();
+            let result = http_client(request);
+            recorder.record(&result);
+            result
+        }
+    }
+
+    ///
+    /// Wraps an asynchronous HTTP client so that unsuccessful responses are recorded.
+    ///
+    pub fn async_http_client<'c, C, F, RE>(
+        &'c self,
+        http_client: &'c C,
+    ) -> impl Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<HttpResponse, RE>> + 'c>> + 'c
+    where
+        C: Fn(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>> + 'c,
+        RE: std::error::Error + 'static,
+    {
+        move |request| {
+            Box::pin(async move {
+                self.clear();
+                let result = http_client(request).await;
+                self.record(&result);
+                result
+            })
+        }
+    }
+
+    fn clear(&self) {
+        *self
+            .last_error_response
+            .lock()
+            .expect("recorder lock poisoned") = None;
+    }
+
+    fn record<RE>(&self, result: &Result<HttpResponse, RE>) {
+        if let Ok(response) = result {
+            if !response.status_code.is_success() {
+                *self
+                    .last_error_response
+                    .lock()
+                    .expect("recorder lock poisoned") = Some(HttpErrorResponse {
+                    status_code: response.status_code,
+                    headers: response.headers.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::future::Ready;
+
+    use oauth2::{AuthUrl, AuthorizationCode, ClientId, ClientSecret, TokenUrl};
+
+    use crate::core::{CoreClient, CoreErrorResponseType, CoreUserInfoClaims};
+    use crate::discovery::tests::block_on;
+    use crate::tests::mock_json_response;
+    use crate::{
+        async_http_client_fn, http_client_fn, AccessToken, AsyncHttpClient, HttpErrorRecorder,
+        HttpRequest, HttpResponse, IssuerUrl, JsonWebKeySet, OAuth2TokenResponse,
+        RequestTokenError, SyncHttpClient, UserInfoUrl,
+    };
+
+    // Trivial transport serving canned JSON responses keyed by request path.
+    struct InMemoryHttpClient {
+        responses: HashMap<&'static str, &'static str>,
+        requests: RefCell<Vec<String>>,
+    }
+    impl InMemoryHttpClient {
+        fn respond(&self, request: HttpRequest) -> Result<HttpResponse, std::io::Error> {
+            self.requests
+                .borrow_mut()
+                .push(request.url.path().to_string());
+            let body = self.responses.get(request.url.path()).ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::NotFound, request.url.to_string())
+            })?;
+            mock_json_response(http::StatusCode::OK, body)
+        }
+    }
+    impl SyncHttpClient for InMemoryHttpClient {
+        type Error = std::io::Error;
+
+        fn call(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+            self.respond(request)
+        }
+    }
+    impl AsyncHttpClient for InMemoryHttpClient {
+        type Error = std::io::Error;
+        type Future = Ready<Result<HttpResponse, Self::Error>>;
+
+        fn call(&self, request: HttpRequest) -> Self::Future {
+            std::future::ready(self.respond(request))
+        }
+    }
+
+    #[test]
+    fn test_in_memory_http_client() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            Some(UserInfoUrl::new("https://example/userinfo".to_string()).unwrap()),
+            JsonWebKeySet::default(),
+        );
+        let new_http_client = || InMemoryHttpClient {
+            responses: vec![
+                (
+                    "/token",
+                    "{\"access_token\": \"12/34\", \"token_type\": \"bearer\"}",
+                ),
+                ("/userinfo", "{\"sub\": \"subject\"}"),
+            ]
+            .into_iter()
+            .collect(),
+            requests: RefCell::new(Vec::new()),
+        };
+        let http_client = new_http_client();
+
+        let token_response = client
+            .exchange_code(AuthorizationCode::new("ccc".to_string()))
+            .request(http_client_fn(&http_client))
+            .unwrap();
+        assert_eq!(token_response.access_token().secret(), "12/34");
+
+        let token_response = block_on(
+            client
+                .exchange_code(AuthorizationCode::new("ccc".to_string()))
+                .request_async(async_http_client_fn(&http_client)),
+        )
+        .unwrap();
+        assert_eq!(token_response.access_token().secret(), "12/34");
+        assert_eq!(*http_client.requests.borrow(), vec!["/token", "/token"]);
+
+        // Request builders defined by this crate accept the transport directly.
+        let access_token = AccessToken::new("12/34".to_string());
+        let user_info: CoreUserInfoClaims = client
+            .user_info(access_token.clone(), None)
+            .unwrap()
+            .request(new_http_client())
+            .unwrap();
+        assert_eq!(user_info.subject().as_str(), "subject");
+
+        let user_info: CoreUserInfoClaims = block_on(
+            client
+                .user_info(access_token, None)
+                .unwrap()
+                .request_async(new_http_client()),
+        )
+        .unwrap();
+        assert_eq!(user_info.subject().as_str(), "subject");
+    }
+
+    #[test]
+    fn test_http_error_recorder() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            None,
+            JsonWebKeySet::default(),
+        );
+        let rate_limited = |retry_after: &'static str| {
+            move |_| {
+                Ok::<_, std::io::Error>(HttpResponse {
+                    status_code: http::StatusCode::TOO_MANY_REQUESTS,
+                    headers: vec![
+                        (
+                            http::header::CONTENT_TYPE,
+                            http::HeaderValue::from_static("application/json"),
+                        ),
+                        (
+                            http::header::RETRY_AFTER,
+                            http::HeaderValue::from_static(retry_after),
+                        ),
+                    ]
+                    .into_iter()
+                    .collect(),
+                    body: b"{\"error\": \"temporarily_unavailable\"}".to_vec(),
+                })
+            }
+        };
+
+        let recorder = HttpErrorRecorder::new();
+        assert!(recorder.last_error_response().is_none());
+        match client
+            .exchange_code(AuthorizationCode::new("ccc".to_string()))
+            .request(recorder.http_client(rate_limited("120")))
+        {
+            Err(RequestTokenError::ServerResponse(err)) => assert_eq!(
+                *err.error(),
+                CoreErrorResponseType::Extension("temporarily_unavailable".to_string())
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let error_response = recorder.last_error_response().unwrap();
+        assert_eq!(
+            error_response.status_code(),
+            http::StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(error_response.headers()[http::header::RETRY_AFTER], "120");
+        assert_eq!(
+            error_response.retry_after(),
+            Some(std::time::Duration::from_secs(120))
+        );
+
+        // HTTP-date form (in the past, so no delay is needed).
+        let async_http_client = rate_limited("Wed, 21 Oct 2015 07:28:00 GMT");
+        let async_http_client = |request| std::future::ready(async_http_client(request));
+        assert!(block_on(
+            client
+                .exchange_code(AuthorizationCode::new("ccc".to_string()))
+                .request_async(recorder.async_http_client(&async_http_client)),
+        )
+        .is_err());
+        assert_eq!(
+            recorder.last_error_response().unwrap().retry_after(),
+            Some(std::time::Duration::ZERO)
+        );
+
+        // A successful request (e.g., a retry) clears the previously recorded error response, as
+        // does a request that fails without receiving a response.
+        let responses = RefCell::new(vec![
+            mock_json_response(
+                http::StatusCode::OK,
+                "{\"access_token\": \"12/34\", \"token_type\": \"bearer\"}",
+            ),
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "connection reset",
+            )),
+            mock_json_response(
+                http::StatusCode::TOO_MANY_REQUESTS,
+                "{\"error\": \"temporarily_unavailable\"}",
+            ),
+        ]);
+        let flaky_http_client = |_| responses.borrow_mut().pop().unwrap();
+        let recorded_status_codes = RefCell::new(Vec::new());
+        let token_response = client
+            .exchange_code(AuthorizationCode::new("ccc".to_string()))
+            .request(|request: HttpRequest| {
+                let http_client = recorder.http_client(&flaky_http_client);
+                let mut result = http_client(request.clone());
+                while !matches!(result, Ok(ref response) if response.status_code.is_success()) {
+                    recorded_status_codes.borrow_mut().push(
+                        recorder
+                            .last_error_response()
+                            .map(|error_response| error_response.status_code()),
+                    );
+                    result = http_client(request.clone());
+                }
+                result
+            })
+            .unwrap();
+        assert_eq!(token_response.access_token().secret(), "12/34");
+        assert_eq!(
+            *recorded_status_codes.borrow(),
+            vec![Some(http::StatusCode::TOO_MANY_REQUESTS), None]
+        );
+        assert!(recorder.last_error_response().is_none());
+    }
+
+    #[test]
+    fn test_feature_gated_http_clients() {
+        #[allow(dead_code)]
+        fn assert_sync_http_client<C: SyncHttpClient>(_: C) {}
+        #[allow(dead_code)]
+        fn assert_async_http_client<C: AsyncHttpClient>(_: C) {}
+
+        #[cfg(feature = "reqwest")]
+        {
+            assert_sync_http_client(crate::reqwest::http_client);
+            assert_async_http_client(crate::reqwest::async_http_client);
+            assert_sync_http_client(crate::ReqwestHttpClient);
+            assert_async_http_client(crate::ReqwestHttpClient);
+        }
+        #[cfg(feature = "curl")]
+        {
+            assert_sync_http_client(crate::curl::http_client);
+            assert_sync_http_client(crate::CurlHttpClient);
+        }
+        #[cfg(feature = "ureq")]
+        {
+            assert_sync_http_client(crate::ureq::http_client);
+            assert_sync_http_client(crate::UreqHttpClient);
+        }
+    }
+}
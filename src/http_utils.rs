@@ -1,11 +1,15 @@
 use http::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
-use oauth2::AccessToken;
+use oauth2::{AccessToken, ClientId, ClientSecret};
+use url::form_urlencoded;
 
+pub const MIME_TYPE_FORM_URLENCODED: &str = "application/x-www-form-urlencoded";
 pub const MIME_TYPE_JSON: &str = "application/json";
 pub const MIME_TYPE_JWKS: &str = "application/jwk-set+json";
 pub const MIME_TYPE_JWT: &str = "application/jwt";
 
+pub const BASIC: &str = "Basic";
 pub const BEARER: &str = "Bearer";
+pub const DPOP: &str = "DPoP";
 
 // The [essence](https://mimesniff.spec.whatwg.org/#mime-type-essence) is the <type>/<subtype>
 // representation.
@@ -48,3 +52,26 @@ pub fn auth_bearer(access_token: &AccessToken) -> (HeaderName, HeaderValue) {
             .expect("invalid access token"),
     )
 }
+
+pub fn auth_dpop(access_token: &AccessToken) -> (HeaderName, HeaderValue) {
+    (
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("{} {}", DPOP, access_token.secret()))
+            .expect("invalid access token"),
+    )
+}
+
+// Per Section 2.3.1 of RFC 6749, the client ID and secret are form-urlencoded prior to being
+// base64-encoded.
+pub fn auth_basic(client_id: &ClientId, client_secret: &ClientSecret) -> (HeaderName, HeaderValue) {
+    let credentials = format!(
+        "{}:{}",
+        form_urlencoded::byte_serialize(client_id.as_bytes()).collect::<String>(),
+        form_urlencoded::byte_serialize(client_secret.secret().as_bytes()).collect::<String>(),
+    );
+    (
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("{} {}", BASIC, base64::encode(credentials)))
+            .expect("invalid client credentials"),
+    )
+}
@@ -1,27 +1,33 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::fmt::Debug;
+use std::future::Future;
 use std::marker::PhantomData;
 use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
 use oauth2::ClientId;
-use serde::Serialize;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 
-use crate::helpers::FilteredFlatten;
+use crate::claim_sources;
+use crate::helpers::{constant_time_eq, FilteredFlatten};
 use crate::jwt::JsonWebTokenAccess;
-use crate::jwt::{JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
+use crate::jwt::{is_compact_jwe, JsonWebTokenError, JsonWebTokenJsonPayloadSerde, JwsHeader};
 use crate::types::helpers::{deserialize_string_or_vec, serde_utc_seconds, serde_utc_seconds_opt};
 use crate::types::LocalizedClaim;
 use crate::{
-    AccessToken, AccessTokenHash, AdditionalClaims, AddressClaim, Audience, AudiencesClaim,
-    AuthenticationContextClass, AuthenticationMethodReference, AuthorizationCode,
-    AuthorizationCodeHash, ClaimsVerificationError, EndUserBirthday, EndUserEmail,
-    EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname,
-    EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername,
-    EndUserWebsiteUrl, ExtraTokenFields, GenderClaim, IdTokenVerifier, IssuerClaim, IssuerUrl,
-    JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken, JsonWebTokenAlgorithm,
-    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, LanguageTag, Nonce, NonceVerifier,
-    PrivateSigningKey, SigningError, StandardClaims, SubjectIdentifier,
+    AccessToken, AccessTokenHash, AdditionalClaims, AddressClaim, AsyncHttpClient, Audience,
+    AudiencesClaim, AuthenticationContextClass, AuthenticationMethodReference, AuthorizationCode,
+    AuthorizationCodeHash, ClaimSource, ClaimSourceError, ClaimSourceVerifier,
+    ClaimsVerificationError, EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName,
+    EndUserMiddleName, EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl,
+    EndUserProfileUrl, EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, ExtraTokenFields,
+    GenderClaim, HttpResponse, IdTokenVerifier, IssuerClaim, IssuerUrl, JsonWebKey, JsonWebKeyType,
+    JsonWebKeyUse, JsonWebToken, JsonWebTokenAlgorithm, JweContentEncryptionAlgorithm,
+    JwsSigningAlgorithm, LanguageTag, Nonce, NonceVerifier, PrivateSigningKey, Seconds,
+    SessionIdentifier, SigningError, StandardClaims, SubjectIdentifier, SyncHttpClient,
 };
 
 // This wrapper layer exists instead of directly verifying the JWT and returning the claims so that
@@ -30,6 +36,10 @@ use crate::{
 ///
 /// OpenID Connect ID token.
 ///
+/// ID tokens are usually signed JWTs, but they may also be encrypted using JSON Web Encryption
+/// (JWE), in which case they must be decrypted (see
+/// [`IdTokenVerifier::set_decryption_key`]) before their claims can be accessed.
+///
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct IdToken<
     AC: AdditionalClaims,
@@ -37,10 +47,64 @@ pub struct IdToken<
     JE: JweContentEncryptionAlgorithm<JT>,
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
->(
-    #[serde(bound = "AC: AdditionalClaims")]
-    JsonWebToken<JE, JS, JT, IdTokenClaims<AC, GC>, JsonWebTokenJsonPayloadSerde>,
-);
+>(#[serde(bound = "AC: AdditionalClaims")] IdTokenJwt<AC, GC, JE, JS, JT>);
+
+// Signed (JWS) or encrypted (JWE) representation of an ID token. Encrypted ID tokens are kept in
+// their serialized form since their JOSE header and claims can't be parsed until decrypted. Signed ID
+// tokens are by far the most common, so we avoid boxing them.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq)]
+enum IdTokenJwt<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    Signed(JsonWebToken<JE, JS, JT, IdTokenClaims<AC, GC>, JsonWebTokenJsonPayloadSerde>),
+    Encrypted(String),
+}
+impl<'de, AC, GC, JE, JS, JT> Deserialize<'de> for IdTokenJwt<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw_token = String::deserialize(deserializer)?;
+        if is_compact_jwe(&raw_token) {
+            Ok(IdTokenJwt::Encrypted(raw_token))
+        } else {
+            serde_json::from_value(Value::String(raw_token))
+                .map(IdTokenJwt::Signed)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+impl<AC, GC, JE, JS, JT> Serialize for IdTokenJwt<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    fn serialize<SE>(&self, serializer: SE) -> Result<SE::Ok, SE::Error>
+    where
+        SE: Serializer,
+    {
+        match self {
+            IdTokenJwt::Signed(jwt) => jwt.serialize(serializer),
+            IdTokenJwt::Encrypted(raw_token) => serializer.serialize_str(raw_token),
+        }
+    }
+}
 
 impl<AC, GC, JE, JS, JT> FromStr for IdToken<AC, GC, JE, JS, JT>
 where
@@ -106,12 +170,15 @@ where
             signing_key,
             &alg,
         )
-        .map(Self)
+        .map(|jwt| Self(IdTokenJwt::Signed(jwt)))
     }
 
     ///
     /// Verifies and returns a reference to the ID token claims.
     ///
+    /// This function returns an error if the ID token is encrypted. Encrypted ID tokens must first
+    /// be decrypted using [`IdToken::decrypt`], or verified using [`IdToken::into_claims`].
+    ///
     pub fn claims<'a, JU, K, N>(
         &'a self,
         verifier: &IdTokenVerifier<JS, JT, JU, K>,
@@ -122,12 +189,117 @@ where
         K: JsonWebKey<JS, JT, JU>,
         N: NonceVerifier,
     {
-        verifier.verified_claims(&self.0, nonce_verifier)
+        match self.0 {
+            IdTokenJwt::Signed(ref jwt) => verifier.verified_claims(jwt, nonce_verifier),
+            IdTokenJwt::Encrypted(_) => Err(ClaimsVerificationError::Unsupported(
+                "encrypted ID tokens must be decrypted using `IdToken::decrypt` or verified \
+                 using `IdToken::into_claims`"
+                    .to_string(),
+            )),
+        }
+    }
+
+    ///
+    /// Verifies and returns a reference to the ID token claims, additionally verifying the
+    /// access token hash (`at_hash`) claim against the given `access_token`.
+    ///
+    /// As described in [Section 3.2.2.9 of OpenID Connect Core 1.0](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#ImplicitTokenValidation), the hash is
+    /// computed using the hash algorithm of the ID token's signing algorithm (e.g., SHA-256 for
+    /// `RS256`), and its left-most half is compared to the `at_hash` claim. ID tokens without an
+    /// `at_hash` claim are accepted, since the claim is optional when the access token is returned
+    /// from the token endpoint.
+    ///
+    /// Returns [`ClaimsVerificationError::InvalidAccessTokenHash`] if the hash does not match.
+    ///
+    pub fn claims_with_access_token<'a, JU, K, N>(
+        &'a self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        nonce_verifier: N,
+        access_token: &AccessToken,
+    ) -> Result<&'a IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        N: NonceVerifier,
+    {
+        let claims = self.claims(verifier, nonce_verifier)?;
+        if let Some(expected_hash) = claims.access_token_hash() {
+            let alg = self.verified_signing_alg()?;
+            let actual_hash = AccessTokenHash::from_token(access_token, &alg)
+                .map_err(|err| ClaimsVerificationError::Unsupported(err.to_string()))?;
+            if !constant_time_eq(&actual_hash, expected_hash) {
+                return Err(ClaimsVerificationError::InvalidAccessTokenHash(
+                    "access token hash does not match the access token".to_string(),
+                ));
+            }
+        }
+        Ok(claims)
+    }
+
+    ///
+    /// Verifies and returns a reference to the ID token claims, additionally verifying the
+    /// authorization code hash (`c_hash`) claim against the given authorization `code`.
+    ///
+    /// This is intended for the hybrid flow, in which the authorization code is returned from the
+    /// authorization endpoint alongside the ID token, as described in
+    /// [Section 3.3.2.10 of OpenID Connect Core 1.0](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#CodeValidation). The hash is computed
+    /// in the same manner as the access token hash (see
+    /// [`claims_with_access_token`](Self::claims_with_access_token)). ID tokens without a `c_hash`
+    /// claim are accepted.
+    ///
+    /// If the ID token contains a `c_hash` claim but no `code` is supplied, this method returns an
+    /// error unless the verifier is configured to ignore the claim in this case (see
+    /// [`IdTokenVerifier::require_code_for_code_hash`]).
+    ///
+    /// Returns [`ClaimsVerificationError::InvalidCodeHash`] if the hash does not match.
+    ///
+    pub fn claims_with_code<'a, JU, K, N>(
+        &'a self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        nonce_verifier: N,
+        code: Option<&AuthorizationCode>,
+    ) -> Result<&'a IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        N: NonceVerifier,
+    {
+        let claims = self.claims(verifier, nonce_verifier)?;
+        match (claims.code_hash(), code) {
+            (Some(expected_hash), Some(code)) => {
+                let alg = self.verified_signing_alg()?;
+                let actual_hash = AuthorizationCodeHash::from_code(code, &alg)
+                    .map_err(|err| ClaimsVerificationError::Unsupported(err.to_string()))?;
+                if !constant_time_eq(&actual_hash, expected_hash) {
+                    return Err(ClaimsVerificationError::InvalidCodeHash(
+                        "authorization code hash does not match the authorization code".to_string(),
+                    ));
+                }
+            }
+            (Some(_), None) if verifier.code_hash_requires_code() => {
+                return Err(ClaimsVerificationError::InvalidCodeHash(
+                    "no authorization code supplied to verify the authorization code hash"
+                        .to_string(),
+                ));
+            }
+            _ => {}
+        }
+        Ok(claims)
     }
 
+    // Returns the signing algorithm of an ID token whose claims have already been verified.
+    fn verified_signing_alg(&self) -> Result<JS, ClaimsVerificationError> {
+        self.signing_alg()
+            .map_err(|err| ClaimsVerificationError::Unsupported(err.to_string()))
+    }
     ///
     /// Verifies and returns the ID token claims.
     ///
+    /// Encrypted ID tokens are decrypted using the verifier's decryption key (see
+    /// [`IdTokenVerifier::set_decryption_key`]) before the nested JWT is verified.
+    ///
     pub fn into_claims<JU, K, N>(
         self,
         verifier: &IdTokenVerifier<JS, JT, JU, K>,
@@ -138,7 +310,98 @@ where
         K: JsonWebKey<JS, JT, JU>,
         N: NonceVerifier,
     {
-        verifier.verified_claims_owned(self.0, nonce_verifier)
+        let jwt = match self.0 {
+            IdTokenJwt::Signed(jwt) => jwt,
+            IdTokenJwt::Encrypted(ref raw_token) => Self::decrypt_nested(raw_token, verifier)?,
+        };
+        verifier.verified_claims_owned(jwt, nonce_verifier)
+    }
+
+    ///
+    /// Decrypts an ID token encrypted using JSON Web Encryption (JWE) and returns the nested
+    /// (signed) ID token, which has not yet been verified.
+    ///
+    /// The ID token is decrypted using the verifier's decryption key (see
+    /// [`IdTokenVerifier::set_decryption_key`]). Unencrypted ID tokens are returned unchanged.
+    ///
+    pub fn decrypt<JU, K>(
+        self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+    ) -> Result<Self, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        match self.0 {
+            IdTokenJwt::Signed(_) => Ok(self),
+            IdTokenJwt::Encrypted(ref raw_token) => Ok(Self(IdTokenJwt::Signed(
+                Self::decrypt_nested(raw_token, verifier)?,
+            ))),
+        }
+    }
+
+    ///
+    /// Returns true if this ID token is encrypted using JSON Web Encryption (JWE).
+    ///
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self.0, IdTokenJwt::Encrypted(_))
+    }
+
+    fn decrypt_nested<JU, K>(
+        raw_token: &str,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+    ) -> Result<
+        JsonWebToken<JE, JS, JT, IdTokenClaims<AC, GC>, JsonWebTokenJsonPayloadSerde>,
+        ClaimsVerificationError,
+    >
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        let nested_jwt = verifier.decrypt(raw_token)?;
+        serde_json::from_value(Value::String(nested_jwt)).map_err(|err| {
+            ClaimsVerificationError::Other(format!("failed to parse nested JWT: {}", err))
+        })
+    }
+
+    ///
+    /// Returns the JOSE header of this ID token **without verifying its signature**.
+    ///
+    /// The returned header is untrusted and must not be used for making security decisions. It's
+    /// intended for diagnosing issues such as a key ID (`kid`) that doesn't match any key in the
+    /// provider's JSON Web Key Set. Returns an error if the ID token is JWE-encrypted.
+    ///
+    pub fn unverified_header(&self) -> Result<JwsHeader, JsonWebTokenError> {
+        match self.0 {
+            IdTokenJwt::Signed(ref jwt) => jwt.unverified_raw_header(),
+            IdTokenJwt::Encrypted(_) => Err(JsonWebTokenError::InvalidHeader(
+                "JWE-encrypted ID token must be decrypted before its JWS header can be accessed"
+                    .to_string(),
+            )),
+        }
+    }
+
+    ///
+    /// Returns the claims of this ID token **without verifying them or the token's signature**.
+    ///
+    /// # Security Warning
+    ///
+    /// The returned claims are untrusted: anyone can construct an ID token containing arbitrary
+    /// claims. They may only be used to select how to verify the ID token (e.g., routing by the
+    /// `iss` claim to the verifier of the corresponding provider in a multi-tenant deployment),
+    /// after which the claims must be verified using [`IdToken::claims`] or a similar method.
+    ///
+    /// This function returns an error if the ID token is encrypted.
+    ///
+    pub fn unverified_claims(&self) -> Result<&IdTokenClaims<AC, GC>, ClaimsVerificationError> {
+        match self.0 {
+            IdTokenJwt::Signed(ref jwt) => Ok(jwt.unverified_payload_ref()),
+            IdTokenJwt::Encrypted(_) => Err(ClaimsVerificationError::Unsupported(
+                "encrypted ID tokens must be decrypted using `IdToken::decrypt` before their \
+                 claims can be accessed"
+                    .to_string(),
+            )),
+        }
     }
 
     ///
@@ -148,7 +411,15 @@ where
     /// (JWE).
     ///
     pub fn signing_alg(&self) -> Result<JS, SigningError> {
-        match self.0.unverified_header().alg {
+        let jwt = match self.0 {
+            IdTokenJwt::Signed(ref jwt) => jwt,
+            IdTokenJwt::Encrypted(_) => {
+                return Err(SigningError::UnsupportedAlg(
+                    "JWE-encrypted ID token".to_string(),
+                ))
+            }
+        };
+        match jwt.unverified_header().alg {
             JsonWebTokenAlgorithm::Signature(ref signing_alg, _) => Ok(signing_alg.clone()),
             JsonWebTokenAlgorithm::Encryption(ref other) => Err(SigningError::UnsupportedAlg(
                 serde_plain::to_string(other).unwrap_or_else(|err| {
@@ -162,6 +433,104 @@ where
         }
     }
 }
+
+///
+/// Builder for signed ID tokens, intended for testing Relying Party code without a live OpenID
+/// Connect Provider.
+///
+/// The `at_hash`, `c_hash`, and `nonce` claims are only set when the corresponding access token,
+/// authorization code, or nonce is provided to the builder. Otherwise, they are unchanged from the
+/// values specified in the claims (which may be used to construct intentionally invalid ID
+/// tokens).
+///
+/// This type is only available when the `test-utils` feature is enabled.
+///
+#[cfg(feature = "test-utils")]
+#[derive(Clone, Debug)]
+pub struct IdTokenBuilder<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    claims: IdTokenClaims<AC, GC>,
+    alg: JS,
+    access_token: Option<AccessToken>,
+    code: Option<AuthorizationCode>,
+    _phantom: PhantomData<(JE, JT)>,
+}
+#[cfg(feature = "test-utils")]
+impl<AC, GC, JE, JS, JT> IdTokenBuilder<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Initializes a builder for an ID token with the specified claims, to be signed using the
+    /// given algorithm.
+    ///
+    pub fn new(claims: IdTokenClaims<AC, GC>, alg: JS) -> Self {
+        Self {
+            claims,
+            alg,
+            access_token: None,
+            code: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Sets the `nonce` claim.
+    ///
+    pub fn set_nonce(mut self, nonce: Nonce) -> Self {
+        self.claims = self.claims.set_nonce(Some(nonce));
+        self
+    }
+
+    ///
+    /// Sets the access token from which to compute the `at_hash` claim.
+    ///
+    pub fn set_access_token(mut self, access_token: AccessToken) -> Self {
+        self.access_token = Some(access_token);
+        self
+    }
+
+    ///
+    /// Sets the authorization code from which to compute the `c_hash` claim.
+    ///
+    pub fn set_code(mut self, code: AuthorizationCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    ///
+    /// Signs the ID token using the given signing key.
+    ///
+    pub fn sign<JU, K, S>(
+        self,
+        signing_key: &S,
+    ) -> Result<IdToken<AC, GC, JE, JS, JT>, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        IdToken::new(
+            self.claims,
+            signing_key,
+            self.alg,
+            self.access_token.as_ref(),
+            self.code.as_ref(),
+        )
+    }
+}
+
+#[allow(unknown_lints, clippy::to_string_trait_impl)]
 impl<AC, GC, JE, JS, JT> ToString for IdToken<AC, GC, JE, JS, JT>
 where
     AC: AdditionalClaims,
@@ -182,6 +551,15 @@ where
     }
 }
 
+///
+/// Authorized party (`azp`) claim of an ID token, which identifies the client to which the ID
+/// token was issued.
+///
+/// See [Section 2](https://openid.net/specs/openid-connect-core-1_0.html#IDToken) of the OpenID
+/// Connect Core specification.
+///
+pub type AuthorizedParty = ClientId;
+
 ///
 /// OpenID Connect ID token claims.
 ///
@@ -219,11 +597,25 @@ where
     #[serde(rename = "amr", skip_serializing_if = "Option::is_none")]
     auth_method_refs: Option<Vec<AuthenticationMethodReference>>,
     #[serde(rename = "azp", skip_serializing_if = "Option::is_none")]
-    authorized_party: Option<ClientId>,
+    authorized_party: Option<AuthorizedParty>,
     #[serde(rename = "at_hash", skip_serializing_if = "Option::is_none")]
     access_token_hash: Option<AccessTokenHash>,
     #[serde(rename = "c_hash", skip_serializing_if = "Option::is_none")]
     code_hash: Option<AuthorizationCodeHash>,
+    #[serde(rename = "sid", skip_serializing_if = "Option::is_none")]
+    session_id: Option<SessionIdentifier>,
+    #[serde(
+        default,
+        rename = "_claim_names",
+        skip_serializing_if = "Option::is_none"
+    )]
+    claim_names: Option<HashMap<String, String>>,
+    #[serde(
+        default,
+        rename = "_claim_sources",
+        skip_serializing_if = "Option::is_none"
+    )]
+    claim_sources: Option<HashMap<String, ClaimSource>>,
 
     #[serde(bound = "GC: GenderClaim")]
     #[serde(flatten)]
@@ -261,6 +653,9 @@ where
             authorized_party: None,
             access_token_hash: None,
             code_hash: None,
+            session_id: None,
+            claim_names: None,
+            claim_sources: None,
             standard_claims,
             additional_claims: additional_claims.into(),
         }
@@ -276,12 +671,133 @@ where
             set_nonce -> nonce[Option<Nonce>],
             set_auth_context_ref -> auth_context_ref[Option<AuthenticationContextClass>] ["acr"],
             set_auth_method_refs -> auth_method_refs[Option<Vec<AuthenticationMethodReference>>] ["amr"],
-            set_authorized_party -> authorized_party[Option<ClientId>] ["azp"],
+            set_authorized_party -> authorized_party[Option<AuthorizedParty>] ["azp"],
             set_access_token_hash -> access_token_hash[Option<AccessTokenHash>] ["at_hash"],
             set_code_hash -> code_hash[Option<AuthorizationCodeHash>] ["c_hash"],
+            set_session_id -> session_id[Option<SessionIdentifier>] ["sid"],
+            set_claim_names -> claim_names[Option<HashMap<String, String>>] ["_claim_names"],
+            set_claim_sources -> claim_sources[Option<HashMap<String, ClaimSource>>] ["_claim_sources"],
         }
     ];
 
+    ///
+    /// Verifies the [aggregated claims](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#AggregatedExample) embedded in these
+    /// claims and returns a copy of these claims with the aggregated claims merged in.
+    ///
+    /// Each aggregated claim source's JWT must be signed by one of the claims providers trusted by
+    /// `verifier`, which may differ from the issuer of these claims. Only the claims that
+    /// `_claim_names` attributes to each source are merged, and the resolved entries are removed
+    /// from `_claim_names` and `_claim_sources`. Distributed claim sources are left untouched (see
+    /// [`resolve_distributed_claims`](Self::resolve_distributed_claims)).
+    ///
+    pub fn resolve_aggregated_claims<JE, JS, JT, JU, K>(
+        &self,
+        verifier: &ClaimSourceVerifier<JE, JS, JT, JU, K>,
+    ) -> Result<Self, ClaimSourceError<Infallible>>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        claim_sources::resolve_aggregated_claims(self, self.claim_sources.as_ref(), verifier)
+    }
+
+    ///
+    /// Fetches the [distributed claims](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#DistributedExample) referenced by
+    /// these claims and returns a copy of these claims with the distributed claims merged in.
+    ///
+    /// Each distributed claim source's endpoint is requested (using the synchronous
+    /// `http_client`) with the source's access token, if any, and the returned JWT is verified by
+    /// `verifier`. Only the claims that `_claim_names` attributes to each source are merged, and
+    /// the resolved entries are removed from `_claim_names` and `_claim_sources`. Aggregated claim
+    /// sources are left untouched (see
+    /// [`resolve_aggregated_claims`](Self::resolve_aggregated_claims)).
+    ///
+    pub fn resolve_distributed_claims<JE, JS, JT, JU, K, HC, RE>(
+        &self,
+        verifier: &ClaimSourceVerifier<JE, JS, JT, JU, K>,
+        http_client: HC,
+    ) -> Result<Self, ClaimSourceError<RE>>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        claim_sources::resolve_distributed_claims(
+            self,
+            self.claim_sources.as_ref(),
+            verifier,
+            http_client,
+        )
+    }
+
+    ///
+    /// Asynchronously fetches the distributed claims referenced by these claims and returns a copy
+    /// of these claims with the distributed claims merged in.
+    ///
+    /// See [`resolve_distributed_claims`](Self::resolve_distributed_claims) for details.
+    ///
+    pub async fn resolve_distributed_claims_async<JE, JS, JT, JU, K, F, HC, RE>(
+        &self,
+        verifier: &ClaimSourceVerifier<'_, JE, JS, JT, JU, K>,
+        http_client: HC,
+    ) -> Result<Self, ClaimSourceError<RE>>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
+        RE: std::error::Error + 'static,
+    {
+        claim_sources::resolve_distributed_claims_async(
+            self,
+            self.claim_sources.as_ref(),
+            verifier,
+            http_client,
+        )
+        .await
+    }
+
+    ///
+    /// Returns the `acr` claim (authentication context class reference).
+    ///
+    /// This is equivalent to [`auth_context_ref`](Self::auth_context_ref).
+    ///
+    pub fn acr(&self) -> Option<&AuthenticationContextClass> {
+        self.auth_context_ref.as_ref()
+    }
+
+    ///
+    /// Returns the `amr` claim (authentication method references).
+    ///
+    /// This is equivalent to [`auth_method_refs`](Self::auth_method_refs).
+    ///
+    pub fn amr(&self) -> Option<&[AuthenticationMethodReference]> {
+        self.auth_method_refs.as_deref()
+    }
+
+    ///
+    /// Returns whether the `amr` claim includes the given authentication method (e.g.,
+    /// [`AuthenticationMethodReference::MFA`]).
+    ///
+    /// Returns `false` if the `amr` claim is absent.
+    ///
+    pub fn amr_contains(&self, method: &str) -> bool {
+        self.amr()
+            .map_or(false, |amr| amr.iter().any(|amr| amr.as_str() == method))
+    }
+
     ///
     /// Returns the `sub` claim.
     ///
@@ -319,10 +835,34 @@ where
             set_phone_number -> phone_number[Option<EndUserPhoneNumber>],
             set_phone_number_verified -> phone_number_verified[Option<bool>],
             set_address -> address[Option<AddressClaim>],
-            set_updated_at -> updated_at[Option<DateTime<Utc>>],
         }
     ];
 
+    ///
+    /// Returns the `updated_at` claim.
+    ///
+    pub fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.standard_claims.updated_at()
+    }
+
+    ///
+    /// Returns the `updated_at` claim as the number of seconds since the Unix epoch, exactly as
+    /// received from the OpenID Connect Provider.
+    ///
+    /// See [`StandardClaims::updated_at_seconds`] for details.
+    ///
+    pub fn updated_at_seconds(&self) -> Option<Seconds> {
+        self.standard_claims.updated_at_seconds()
+    }
+
+    ///
+    /// Sets the `updated_at` claim.
+    ///
+    pub fn set_updated_at(mut self, updated_at: Option<DateTime<Utc>>) -> Self {
+        self.standard_claims = self.standard_claims.set_updated_at(updated_at);
+        self
+    }
+
     ///
     /// Returns additional ID token claims.
     ///
@@ -345,7 +885,7 @@ where
         Some(IdTokenClaims::audiences(self))
     }
 }
-impl<'a, AC, GC> AudiencesClaim for &'a IdTokenClaims<AC, GC>
+impl<AC, GC> AudiencesClaim for &IdTokenClaims<AC, GC>
 where
     AC: AdditionalClaims,
     GC: GenderClaim,
@@ -363,7 +903,7 @@ where
         Some(IdTokenClaims::issuer(self))
     }
 }
-impl<'a, AC, GC> IssuerClaim for &'a IdTokenClaims<AC, GC>
+impl<AC, GC> IssuerClaim for &IdTokenClaims<AC, GC>
 where
     AC: AdditionalClaims,
     GC: GenderClaim,
@@ -444,22 +984,33 @@ mod tests {
 
     use chrono::{TimeZone, Utc};
     use oauth2::basic::BasicTokenType;
-    use oauth2::{ClientId, TokenResponse};
+    use oauth2::{AccessToken, AuthorizationCode, ClientId, TokenResponse};
     use url::Url;
 
     use crate::claims::{AdditionalClaims, EmptyAdditionalClaims, StandardClaims};
-    use crate::core::{CoreGenderClaim, CoreIdToken, CoreIdTokenClaims, CoreTokenResponse};
-    use crate::jwt::JsonWebTokenAccess;
+    use crate::core::{
+        CoreGenderClaim, CoreIdToken, CoreIdTokenClaims, CoreIdTokenVerifier, CoreJsonWebKeySet,
+        CoreTokenResponse,
+    };
+    use crate::jwt::{JsonWebTokenAccess, JsonWebTokenError, JwsHeader};
     use crate::{
         AccessTokenHash, AddressClaim, AddressCountry, AddressLocality, AddressPostalCode,
         AddressRegion, Audience, AuthenticationContextClass, AuthenticationMethodReference,
-        AuthorizationCodeHash, EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName,
-        EndUserMiddleName, EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl,
-        EndUserProfileUrl, EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, FormattedAddress,
-        IssuerUrl, LanguageTag, Nonce, StreetAddress, SubjectIdentifier,
+        AuthorizationCodeHash, ClaimsVerificationError, EndUserBirthday, EndUserEmail,
+        EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname,
+        EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername,
+        EndUserWebsiteUrl, FormattedAddress, IssuerUrl, JsonWebKeyId, LanguageTag, Nonce, Seconds,
+        SessionIdentifier, StreetAddress, SubjectIdentifier,
     };
 
-    use super::{AudiencesClaim, IdTokenClaims, IssuerClaim};
+    use super::{AudiencesClaim, AuthorizedParty, IdTokenClaims, IdTokenJwt, IssuerClaim};
+
+    fn unverified_claims(id_token: &CoreIdToken) -> &CoreIdTokenClaims {
+        match id_token.0 {
+            IdTokenJwt::Signed(ref jwt) => jwt.unverified_payload_ref(),
+            IdTokenJwt::Encrypted(_) => panic!("expected a signed ID token"),
+        }
+    }
 
     #[test]
     fn test_id_token() {
@@ -474,7 +1025,7 @@ mod tests {
         // `serde::Deserialize` implementation is tested within the `FromStr` implementation
         let id_token = CoreIdToken::from_str(ID_TOKEN).expect("failed to parse id_token");
 
-        let claims = id_token.0.unverified_payload_ref();
+        let claims = unverified_claims(&id_token);
 
         assert_eq!(
             *claims.issuer().url(),
@@ -509,6 +1060,256 @@ mod tests {
         assert_eq!(de, format!("\"{}\"", ID_TOKEN));
     }
 
+    // Returns an ID token with the given header and claims and an invalid signature, for use with
+    // an insecure verifier.
+    fn id_token_with_alg(alg: &str, additional_claims: &str) -> CoreIdToken {
+        id_token_with_header(&format!("{{\"alg\":\"{}\"}}", alg), additional_claims)
+    }
+
+    fn id_token_with_header(header: &str, additional_claims: &str) -> CoreIdToken {
+        let encode = |json: String| base64::encode_config(json, base64::URL_SAFE_NO_PAD);
+        CoreIdToken::from_str(&format!(
+            "{}.{}.aW52YWxpZF9zaWduYXR1cmU",
+            encode(header.to_string()),
+            encode(format!(
+                "{{\"iss\":\"https://server.example.com\",\"aud\":[\"s6BhdRkqt3\"],\
+                 \"exp\":4102444800,\"iat\":1311280970,\"sub\":\"24400320\"{}}}",
+                additional_claims
+            )),
+        ))
+        .expect("failed to parse id_token")
+    }
+
+    #[test]
+    fn test_unverified_header() {
+        let id_token = id_token_with_header(
+            r#"{"alg":"RS256","kid":"bilbo.baggins@hobbiton.example","typ":"JWT"}"#,
+            "",
+        );
+        let header = id_token.unverified_header().unwrap();
+        assert_eq!(header.alg(), "RS256");
+        assert_eq!(
+            header.kid(),
+            Some(&JsonWebKeyId::new(
+                "bilbo.baggins@hobbiton.example".to_string()
+            ))
+        );
+        assert_eq!(header.typ(), Some("JWT"));
+        assert_eq!(header.cty(), None);
+        assert_eq!(header.crit(), None);
+        assert!(header.other_fields().is_empty());
+
+        // Critical and unrecognized header parameters are returned as-is, even though such ID
+        // tokens fail verification.
+        let id_token = id_token_with_header(
+            r#"{"alg":"ES256","crit":["http://example.com/ext"],"http://example.com/ext":true}"#,
+            "",
+        );
+        let header = id_token.unverified_header().unwrap();
+        assert_eq!(header.alg(), "ES256");
+        assert_eq!(header.kid(), None);
+        assert_eq!(
+            header.crit(),
+            Some(&vec!["http://example.com/ext".to_string()])
+        );
+        assert_eq!(
+            header.other_fields().get("http://example.com/ext"),
+            Some(&serde_json::Value::Bool(true))
+        );
+
+        match JwsHeader::from_compact_jws("eyJ*bGciOiJSUzI1NiJ9.e30.c2ln") {
+            Err(JsonWebTokenError::InvalidHeader(msg)) => {
+                assert!(msg.contains("base64url"), "unexpected message: {}", msg)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match JwsHeader::from_compact_jws("eyJhbGciOiJSUzI1NiJ9.e30") {
+            Err(JsonWebTokenError::InvalidHeader(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            JwsHeader::from_compact_jws("eyJhbGciOiJSUzI1NiJ9.e30.c2ln")
+                .unwrap()
+                .alg(),
+            "RS256"
+        );
+    }
+
+    #[test]
+    fn test_unverified_claims() {
+        // The signature is invalid, but isn't consulted.
+        let id_token = id_token_with_alg("RS256", "");
+        let claims = id_token.unverified_claims().unwrap();
+        assert_eq!(
+            *claims.issuer(),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap()
+        );
+        assert_eq!(
+            *claims.audiences(),
+            vec![Audience::new("s6BhdRkqt3".to_string())]
+        );
+        assert_eq!(**claims.subject(), "24400320");
+
+        // Verification still fails due to the invalid signature.
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![]),
+        );
+        match id_token.claims(&verifier, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::SignatureVerification(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_access_token_hash() {
+        let verifier = CoreIdTokenVerifier::new_insecure_without_verification();
+        // Access token from Appendix A.3 of OpenID Connect Core 1.0.
+        let access_token =
+            AccessToken::new("jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y".to_string());
+        let tampered_access_token =
+            AccessToken::new("jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Z".to_string());
+
+        for (alg, at_hash) in [
+            // SHA-256, truncated to 128 bits.
+            ("RS256", "77QmUPtjPfzWtF2AnpK9RQ"),
+            // SHA-512, truncated to 256 bits.
+            ("ES512", "q7nS86GgvvFaZkzALLWqJYaJIKw2wCDAVfCAsm5CrBM"),
+        ] {
+            let id_token = id_token_with_alg(alg, &format!(",\"at_hash\":\"{}\"", at_hash));
+            let claims = id_token
+                .claims_with_access_token(&verifier, |_: Option<&Nonce>| Ok(()), &access_token)
+                .unwrap();
+            assert_eq!(
+                claims.access_token_hash(),
+                Some(&AccessTokenHash::new(at_hash.to_string()))
+            );
+
+            match id_token.claims_with_access_token(
+                &verifier,
+                |_: Option<&Nonce>| Ok(()),
+                &tampered_access_token,
+            ) {
+                Err(ClaimsVerificationError::InvalidAccessTokenHash(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        // The RS256 hash doesn't match when the token is signed using ES512.
+        let id_token = id_token_with_alg("ES512", ",\"at_hash\":\"77QmUPtjPfzWtF2AnpK9RQ\"");
+        assert!(matches!(
+            id_token.claims_with_access_token(&verifier, |_: Option<&Nonce>| Ok(()), &access_token),
+            Err(ClaimsVerificationError::InvalidAccessTokenHash(_))
+        ));
+
+        // The at_hash claim is optional.
+        id_token_with_alg("RS256", "")
+            .claims_with_access_token(&verifier, |_: Option<&Nonce>| Ok(()), &access_token)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_code_hash() {
+        let verifier = CoreIdTokenVerifier::new_insecure_without_verification();
+        // Authorization code and c_hash from Appendix A.4 of OpenID Connect Core 1.0.
+        let code = AuthorizationCode::new(
+            "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvk".to_string(),
+        );
+        let tampered_code = AuthorizationCode::new(
+            "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvl".to_string(),
+        );
+        let id_token = id_token_with_alg("RS256", ",\"c_hash\":\"LDktKdoQak3Pk0cnXxCltA\"");
+
+        let claims = id_token
+            .claims_with_code(&verifier, |_: Option<&Nonce>| Ok(()), Some(&code))
+            .unwrap();
+        assert_eq!(
+            claims.code_hash(),
+            Some(&AuthorizationCodeHash::new(
+                "LDktKdoQak3Pk0cnXxCltA".to_string()
+            ))
+        );
+
+        match id_token.claims_with_code(&verifier, |_: Option<&Nonce>| Ok(()), Some(&tampered_code))
+        {
+            Err(ClaimsVerificationError::InvalidCodeHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The c_hash claim is present, but no code is supplied.
+        match id_token.claims_with_code(&verifier, |_: Option<&Nonce>| Ok(()), None) {
+            Err(ClaimsVerificationError::InvalidCodeHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        id_token
+            .claims_with_code(
+                &CoreIdTokenVerifier::new_insecure_without_verification()
+                    .require_code_for_code_hash(false),
+                |_: Option<&Nonce>| Ok(()),
+                None,
+            )
+            .unwrap();
+
+        // The c_hash claim is optional.
+        id_token_with_alg("RS256", "")
+            .claims_with_code(&verifier, |_: Option<&Nonce>| Ok(()), Some(&code))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hash_comparison() {
+        let verifier = CoreIdTokenVerifier::new_insecure_without_verification();
+        let access_token =
+            AccessToken::new("jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y".to_string());
+        let code = AuthorizationCode::new(
+            "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvk".to_string(),
+        );
+
+        // Hashes must match exactly: prefixes, extensions, and case changes are all rejected.
+        for (at_hash, valid) in [
+            ("77QmUPtjPfzWtF2AnpK9RQ", true),
+            ("77QmUPtjPfzWtF2AnpK9R", false),
+            ("77QmUPtjPfzWtF2AnpK9RQA", false),
+            ("77qmUPtjPfzWtF2AnpK9RQ", false),
+            ("", false),
+        ] {
+            let result = id_token_with_alg("RS256", &format!(",\"at_hash\":\"{}\"", at_hash))
+                .claims_with_access_token(&verifier, |_: Option<&Nonce>| Ok(()), &access_token)
+                .map(|_| ());
+            match result {
+                Ok(()) if valid => {}
+                Err(ClaimsVerificationError::InvalidAccessTokenHash(_)) if !valid => {}
+                other => panic!("unexpected result for {:?}: {:?}", at_hash, other),
+            }
+        }
+        for (c_hash, valid) in [
+            ("LDktKdoQak3Pk0cnXxCltA", true),
+            ("LDktKdoQak3Pk0cnXxClt", false),
+            ("LDktKdoQak3Pk0cnXxCltB", false),
+        ] {
+            let result = id_token_with_alg("RS256", &format!(",\"c_hash\":\"{}\"", c_hash))
+                .claims_with_code(&verifier, |_: Option<&Nonce>| Ok(()), Some(&code))
+                .map(|_| ());
+            match result {
+                Ok(()) if valid => {}
+                Err(ClaimsVerificationError::InvalidCodeHash(_)) if !valid => {}
+                other => panic!("unexpected result for {:?}: {:?}", c_hash, other),
+            }
+        }
+
+        let id_token = id_token_with_alg("RS256", ",\"nonce\":\"the_nonce\"");
+        id_token
+            .claims(&verifier, &Nonce::new("the_nonce".to_string()))
+            .unwrap();
+        for nonce in ["the_nonc", "the_nonce2", "THE_NONCE", ""] {
+            match id_token.claims(&verifier, &Nonce::new(nonce.to_string())) {
+                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+                other => panic!("unexpected result for {:?}: {:?}", nonce, other),
+            }
+        }
+    }
+
     #[test]
     fn test_oauth2_response() {
         let response_str = "{\
@@ -525,7 +1326,7 @@ mod tests {
         assert_eq!(*response.token_type(), BasicTokenType::Bearer);
 
         let id_token = response.extra_fields().id_token();
-        let claims = id_token.unwrap().0.unverified_payload_ref();
+        let claims = unverified_claims(id_token.unwrap());
 
         assert_eq!(
             *claims.issuer().url(),
@@ -607,6 +1408,7 @@ mod tests {
         assert_eq!(claims.authorized_party(), None);
         assert_eq!(claims.access_token_hash(), None);
         assert_eq!(claims.code_hash(), None);
+        assert_eq!(claims.session_id(), None);
         assert_eq!(*claims.additional_claims(), EmptyAdditionalClaims {});
         assert_eq!(claims.subject(), new_claims.subject());
         assert_eq!(claims.name(), None);
@@ -831,6 +1633,7 @@ mod tests {
                         .single()
                         .expect("valid timestamp"),
                 ),
+                updated_at_seconds: Some(Seconds::new(1311283970.into())),
             },
             EmptyAdditionalClaims {},
         )
@@ -1046,6 +1849,28 @@ mod tests {
              \"sub\":\"24400320\"\
              }",
         );
+        assert_eq!(multi_aud_claims.authorized_party(), None);
+
+        let azp_claims = serde_json::from_str::<CoreIdTokenClaims>(
+            "{
+                    \"iss\": \"https://server.example.com\",
+                    \"sub\": \"24400320\",
+                    \"aud\": [\"s6BhdRkqt3\", \"aud2\"],
+                    \"azp\": \"s6BhdRkqt3\",
+                    \"exp\": 1311281970,
+                    \"iat\": 1311280970
+                }",
+        )
+        .expect("failed to deserialize");
+        let audiences: &[Audience] = azp_claims.audiences();
+        assert_eq!(audiences.len(), 2);
+        assert_eq!(
+            azp_claims.authorized_party(),
+            Some(&AuthorizedParty::new("s6BhdRkqt3".to_string()))
+        );
+        assert!(audiences
+            .iter()
+            .any(|aud| **aud == **azp_claims.authorized_party().unwrap()));
     }
 
     #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -1115,6 +1940,86 @@ mod tests {
         assert_eq!(claims.additional_claims().0["tfa_method"], "u2f");
     }
 
+    #[test]
+    fn test_amr_claim() {
+        let claims_json = serde_json::json!({
+            "iss": "https://server.example.com",
+            "aud": ["s6BhdRkqt3"],
+            "exp": 1311281970,
+            "iat": 1311280970,
+            "amr": ["pwd", "hwk", "mfa"],
+            "sub": "24400320"
+        });
+        let claims = serde_json::from_value::<CoreIdTokenClaims>(claims_json.clone())
+            .expect("failed to deserialize");
+        assert_eq!(
+            claims.amr(),
+            Some(
+                &[
+                    AuthenticationMethodReference::new(
+                        AuthenticationMethodReference::PWD.to_string()
+                    ),
+                    AuthenticationMethodReference::new(
+                        AuthenticationMethodReference::HWK.to_string()
+                    ),
+                    AuthenticationMethodReference::new(
+                        AuthenticationMethodReference::MFA.to_string()
+                    ),
+                ][..]
+            )
+        );
+        assert_eq!(serde_json::to_value(&claims).unwrap(), claims_json);
+
+        assert!(claims.amr_contains(AuthenticationMethodReference::MFA));
+        assert!(claims.amr_contains(AuthenticationMethodReference::HWK));
+        assert!(!claims.amr_contains(AuthenticationMethodReference::OTP));
+        assert!(!claims.amr_contains("MFA"));
+
+        let claims = claims.set_auth_method_refs(None);
+        assert_eq!(claims.amr(), None);
+        assert!(!claims.amr_contains(AuthenticationMethodReference::PWD));
+    }
+
+    #[test]
+    fn test_session_id_claim_serde() {
+        let claims = serde_json::from_str::<IdTokenClaims<AllOtherClaims, CoreGenderClaim>>(
+            "{
+                \"iss\": \"https://server.example.com\",
+                \"sub\": \"24400320\",
+                \"aud\": [\"s6BhdRkqt3\"],
+                \"exp\": 1311281970,
+                \"iat\": 1311280970,
+                \"sid\": \"08a5019c-17e1-4977-8f42-65a12843ea02\"
+            }",
+        )
+        .expect("failed to deserialize");
+        assert_eq!(
+            claims.session_id(),
+            Some(&SessionIdentifier::new(
+                "08a5019c-17e1-4977-8f42-65a12843ea02".to_string()
+            ))
+        );
+        assert_eq!(**claims.subject(), "24400320");
+        // The sid claim isn't duplicated in the additional claims.
+        assert!(claims.additional_claims().0.is_empty());
+
+        let serialized_claims = serde_json::to_value(&claims).expect("failed to serialize");
+        assert_eq!(
+            serialized_claims["sid"],
+            "08a5019c-17e1-4977-8f42-65a12843ea02"
+        );
+
+        let claims = claims.set_session_id(None);
+        assert_eq!(claims.session_id(), None);
+        let serialized_claims = serde_json::to_value(&claims).expect("failed to serialize");
+        assert!(serialized_claims.get("sid").is_none());
+        let claims_round_trip = serde_json::from_value::<
+            IdTokenClaims<AllOtherClaims, CoreGenderClaim>,
+        >(serialized_claims)
+        .expect("failed to deserialize");
+        assert_eq!(claims_round_trip.session_id(), None);
+    }
+
     #[test]
     fn test_audiences_claim() {
         let claims = serde_json::from_str::<CoreIdTokenClaims>(
@@ -1160,4 +2065,89 @@ mod tests {
         verify_issuer(&claims);
         verify_issuer(&&claims);
     }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_id_token_builder() {
+        use crate::core::{CoreIdTokenBuilder, CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey};
+        use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
+
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let nonce = Nonce::new("the_nonce".to_string());
+        let access_token = AccessToken::new("the_access_token".to_string());
+        let code = AuthorizationCode::new("the_code".to_string());
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(
+            TEST_RSA_PRIV_KEY,
+            Some(JsonWebKeyId::new(
+                "bilbo.baggins@hobbiton.example".to_string(),
+            )),
+        )
+        .unwrap();
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            client_id.clone(),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![serde_json::from_str(TEST_RSA_PUB_KEY).unwrap()]),
+        );
+        let claims = CoreIdTokenClaims::new(
+            issuer,
+            vec![Audience::new(client_id.to_string())],
+            Utc::now() + chrono::Duration::hours(1),
+            Utc::now(),
+            StandardClaims::new(SubjectIdentifier::new("subject".to_string())),
+            EmptyAdditionalClaims {},
+        );
+
+        let id_token = CoreIdTokenBuilder::new(
+            claims.clone(),
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .set_nonce(nonce.clone())
+        .set_access_token(access_token.clone())
+        .set_code(code.clone())
+        .sign(&signing_key)
+        .unwrap();
+        // Round-trip through the serialized form, as an RP would receive it.
+        let id_token = CoreIdToken::from_str(&id_token.to_string()).unwrap();
+        let verified_claims = id_token
+            .claims_with_access_token(&verifier, &nonce, &access_token)
+            .unwrap();
+        assert_eq!(verified_claims.nonce(), Some(&nonce));
+        assert_eq!(**verified_claims.subject(), "subject");
+        id_token
+            .claims_with_code(&verifier, &nonce, Some(&code))
+            .unwrap();
+        match id_token.claims(&verifier, &Nonce::new("other_nonce".to_string())) {
+            Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Claims are left unchanged when no access token, code, or nonce is provided.
+        let id_token = CoreIdTokenBuilder::new(
+            claims
+                .clone()
+                .set_access_token_hash(Some(AccessTokenHash::new("wrong_hash".to_string()))),
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .sign(&signing_key)
+        .unwrap();
+        assert_eq!(unverified_claims(&id_token).nonce(), None);
+        assert_eq!(unverified_claims(&id_token).code_hash(), None);
+        match id_token.claims_with_access_token(
+            &verifier,
+            |_: Option<&Nonce>| Ok(()),
+            &access_token,
+        ) {
+            Err(ClaimsVerificationError::InvalidAccessTokenHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Signing fails when the key doesn't support the algorithm.
+        match CoreIdTokenBuilder::new(claims, CoreJwsSigningAlgorithm::EcdsaP256Sha256)
+            .sign(&signing_key)
+        {
+            Err(JsonWebTokenError::SigningError(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
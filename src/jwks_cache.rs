@@ -0,0 +1,649 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http::header::{HeaderMap, CACHE_CONTROL};
+
+use crate::{
+    DiscoveryError, HttpRequest, HttpResponse, JsonWebKey, JsonWebKeySet, JsonWebKeySetUrl,
+    JsonWebKeyType, JsonWebKeyUse, JwsSigningAlgorithm,
+};
+
+const DEFAULT_TTL: Duration = Duration::from_secs(300);
+const DEFAULT_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+///
+/// Source of signature verification keys that may change over time (e.g., due to key rotation).
+///
+/// This trait is intentionally private so that verifiers only depend on [`JwksCache`].
+///
+pub(crate) trait JwksSource<JS, JT, JU, K>: Debug + Send + Sync
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Returns the current key set, fetching it first if the cached copy has expired.
+    ///
+    fn current_keys(&self) -> Result<Arc<JsonWebKeySet<JS, JT, JU, K>>, String>;
+
+    ///
+    /// Called when `stale_keys` contain no key matching a JWT. Returns a newer key set, or `None`
+    /// if no newer key set is available (e.g., because the keys were refreshed too recently).
+    ///
+    fn refresh_stale_keys(
+        &self,
+        stale_keys: &Arc<JsonWebKeySet<JS, JT, JU, K>>,
+    ) -> Result<Option<Arc<JsonWebKeySet<JS, JT, JU, K>>>, String>;
+}
+
+struct CachedKeys<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    keys: Arc<JsonWebKeySet<JS, JT, JU, K>>,
+    fetched_at: DateTime<Utc>,
+    // `None` if the keys never expire.
+    expires_at: Option<DateTime<Utc>>,
+}
+
+///
+/// Cache of a remote [JSON Web Key Set](https://tools.ietf.org/html/rfc7517#section-5) (JWKS).
+///
+/// The cached keys are refetched from the JWKS URL once they expire, which happens after the
+/// `max-age` specified by the `Cache-Control` header of the JWKS response, or after the
+/// [default TTL](Self::set_default_ttl) if the response doesn't specify a `max-age`. To support
+/// [rotation of signing keys](
+/// http://openid.net/specs/openid-connect-core-1_0.html#RotateSigKeys), the keys are also
+/// refetched whenever a JWT is signed by an unknown key, at most once per
+/// [minimum refresh interval](Self::set_min_refresh_interval).
+///
+/// A cache may be passed to [`Client::set_jwks_cache`](crate::Client::set_jwks_cache) or to
+/// verifiers such as [`IdTokenVerifier::set_jwks_cache`](crate::IdTokenVerifier::set_jwks_cache).
+/// Clones of a cache share the same cached keys.
+///
+/// Since claims verification is synchronous, the cache requires a blocking `http_client`
+/// (e.g., [`crate::reqwest::http_client`]).
+///
+pub struct JwksCache<JS, JT, JU, K, RE>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RE: std::error::Error + 'static,
+{
+    jwks_url: JsonWebKeySetUrl,
+    #[allow(clippy::type_complexity)]
+    http_client: Arc<dyn Fn(HttpRequest) -> Result<HttpResponse, RE> + Send + Sync>,
+    default_ttl: Duration,
+    min_refresh_interval: Duration,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+    state: Arc<RwLock<Option<CachedKeys<JS, JT, JU, K>>>>,
+    // Serializes fetches so that concurrent verifications don't all refetch the keys at once.
+    refresh_lock: Arc<Mutex<()>>,
+}
+impl<JS, JT, JU, K, RE> JwksCache<JS, JT, JU, K, RE>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RE: std::error::Error + 'static,
+{
+    ///
+    /// Initializes an empty cache of the JSON Web Key Set at `jwks_url`.
+    ///
+    /// The keys are fetched using `http_client` the first time they are needed.
+    ///
+    pub fn new<HC>(jwks_url: JsonWebKeySetUrl, http_client: HC) -> Self
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE> + Send + Sync + 'static,
+    {
+        Self {
+            jwks_url,
+            http_client: Arc::new(http_client),
+            default_ttl: DEFAULT_TTL,
+            min_refresh_interval: DEFAULT_MIN_REFRESH_INTERVAL,
+            time_fn: Arc::new(Utc::now),
+            state: Arc::new(RwLock::new(None)),
+            refresh_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    ///
+    /// Sets how long fetched keys are cached if the JWKS response doesn't include a
+    /// `Cache-Control: max-age` directive. Defaults to 5 minutes.
+    ///
+    pub fn set_default_ttl(mut self, default_ttl: Duration) -> Self {
+        self.default_ttl = default_ttl;
+        self
+    }
+
+    ///
+    /// Sets the minimum time between refetches triggered by a JWT signed with an unknown key.
+    /// Defaults to 30 seconds.
+    ///
+    /// This limit prevents callers from forcing a refetch on every verification by submitting
+    /// JWTs with arbitrary key IDs. It does not apply to refetches of expired keys.
+    ///
+    pub fn set_min_refresh_interval(mut self, min_refresh_interval: Duration) -> Self {
+        self.min_refresh_interval = min_refresh_interval;
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for determining whether the cached keys have expired. The default
+    /// is [`Utc::now`].
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    ///
+    /// Returns the URL of the cached JSON Web Key Set.
+    ///
+    pub fn jwks_url(&self) -> &JsonWebKeySetUrl {
+        &self.jwks_url
+    }
+
+    ///
+    /// Returns the time at which the cached keys expire, or `None` if no keys have been fetched
+    /// yet or the cached keys never expire.
+    ///
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.read_state()
+            .as_ref()
+            .and_then(|cached| cached.expires_at)
+    }
+
+    ///
+    /// Returns the cached keys, fetching them first if none are cached or the cached keys have
+    /// expired.
+    ///
+    pub fn keys(&self) -> Result<JsonWebKeySet<JS, JT, JU, K>, DiscoveryError<RE>> {
+        self.cached_keys().map(|keys| (*keys).clone())
+    }
+
+    ///
+    /// Fetches the keys from the JWKS URL, replacing any cached keys.
+    ///
+    pub fn refresh(&self) -> Result<(), DiscoveryError<RE>> {
+        let _guard = self.lock_refresh();
+        self.fetch().map(|_| ())
+    }
+
+    fn read_state(&self) -> std::sync::RwLockReadGuard<'_, Option<CachedKeys<JS, JT, JU, K>>> {
+        self.state.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn lock_refresh(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.refresh_lock
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    // Returns the cached keys if they haven't expired as of `now`.
+    fn fresh_keys(&self, now: DateTime<Utc>) -> Option<Arc<JsonWebKeySet<JS, JT, JU, K>>> {
+        self.read_state()
+            .as_ref()
+            .filter(|cached| {
+                cached
+                    .expires_at
+                    .map(|expires_at| now < expires_at)
+                    .unwrap_or(true)
+            })
+            .map(|cached| cached.keys.clone())
+    }
+
+    fn cached_keys(&self) -> Result<Arc<JsonWebKeySet<JS, JT, JU, K>>, DiscoveryError<RE>> {
+        if let Some(keys) = self.fresh_keys((*self.time_fn)()) {
+            return Ok(keys);
+        }
+
+        let _guard = self.lock_refresh();
+        // Another thread may have fetched the keys while we were waiting for the lock.
+        if let Some(keys) = self.fresh_keys((*self.time_fn)()) {
+            return Ok(keys);
+        }
+        self.fetch()
+    }
+
+    // Must be called while holding the refresh lock.
+    fn fetch(&self) -> Result<Arc<JsonWebKeySet<JS, JT, JU, K>>, DiscoveryError<RE>> {
+        let http_response = (*self.http_client)(JsonWebKeySet::<JS, JT, JU, K>::fetch_request(
+            &self.jwks_url,
+        ))
+        .map_err(DiscoveryError::Request)?;
+        let ttl = cache_control_max_age(&http_response.headers).unwrap_or(self.default_ttl);
+        let keys = Arc::new(JsonWebKeySet::fetch_response(http_response)?);
+
+        let fetched_at = (*self.time_fn)();
+        let expires_at = chrono::Duration::from_std(ttl)
+            .ok()
+            .and_then(|ttl| fetched_at.checked_add_signed(ttl));
+        *self.state.write().unwrap_or_else(|err| err.into_inner()) = Some(CachedKeys {
+            keys: keys.clone(),
+            fetched_at,
+            expires_at,
+        });
+        Ok(keys)
+    }
+}
+impl<JS, JT, JU, K, RE> JwksSource<JS, JT, JU, K> for JwksCache<JS, JT, JU, K, RE>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RE: std::error::Error + 'static,
+    JsonWebKeySet<JS, JT, JU, K>: Send + Sync,
+{
+    fn current_keys(&self) -> Result<Arc<JsonWebKeySet<JS, JT, JU, K>>, String> {
+        self.cached_keys().map_err(|err| jwks_error_message(&err))
+    }
+
+    fn refresh_stale_keys(
+        &self,
+        stale_keys: &Arc<JsonWebKeySet<JS, JT, JU, K>>,
+    ) -> Result<Option<Arc<JsonWebKeySet<JS, JT, JU, K>>>, String> {
+        let _guard = self.lock_refresh();
+        if let Some(cached) = self.read_state().as_ref() {
+            // Another thread already refreshed the keys.
+            if !Arc::ptr_eq(&cached.keys, stale_keys) {
+                return Ok(Some(cached.keys.clone()));
+            }
+            let refresh_allowed_at = chrono::Duration::from_std(self.min_refresh_interval)
+                .ok()
+                .and_then(|interval| cached.fetched_at.checked_add_signed(interval));
+            match refresh_allowed_at {
+                Some(refresh_allowed_at) if (*self.time_fn)() >= refresh_allowed_at => {}
+                _ => return Ok(None),
+            }
+        }
+        self.fetch()
+            .map(Some)
+            .map_err(|err| jwks_error_message(&err))
+    }
+}
+impl<JS, JT, JU, K, RE> Clone for JwksCache<JS, JT, JU, K, RE>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RE: std::error::Error + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            jwks_url: self.jwks_url.clone(),
+            http_client: self.http_client.clone(),
+            default_ttl: self.default_ttl,
+            min_refresh_interval: self.min_refresh_interval,
+            time_fn: self.time_fn.clone(),
+            state: self.state.clone(),
+            refresh_lock: self.refresh_lock.clone(),
+        }
+    }
+}
+impl<JS, JT, JU, K, RE> Debug for JwksCache<JS, JT, JU, K, RE>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RE: std::error::Error + 'static,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatterResult {
+        f.debug_struct("JwksCache")
+            .field("jwks_url", &self.jwks_url)
+            .field("default_ttl", &self.default_ttl)
+            .field("min_refresh_interval", &self.min_refresh_interval)
+            .field("expires_at", &self.expires_at())
+            .finish()
+    }
+}
+
+fn jwks_error_message<RE>(err: &DiscoveryError<RE>) -> String
+where
+    RE: std::error::Error + 'static,
+{
+    let mut message = format!("failed to fetch JWKS: {}", err);
+    let mut source = std::error::Error::source(err);
+    while let Some(err) = source {
+        message.push_str(&format!(": {}", err));
+        source = err.source();
+    }
+    message
+}
+
+///
+/// Returns the lifetime specified by the `Cache-Control` response header, if any.
+///
+/// The `no-cache` and `no-store` directives take precedence over `max-age` and result in a zero
+/// lifetime.
+///
+fn cache_control_max_age(headers: &HeaderMap) -> Option<Duration> {
+    let directives = headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect::<Vec<_>>();
+
+    if directives.iter().any(|directive| {
+        directive.eq_ignore_ascii_case("no-cache") || directive.eq_ignore_ascii_case("no-store")
+    }) {
+        return Some(Duration::from_secs(0));
+    }
+
+    directives.iter().find_map(|directive| {
+        let (name, value) = directive.split_once('=')?;
+        if name.trim().eq_ignore_ascii_case("max-age") {
+            value
+                .trim()
+                .trim_matches('"')
+                .parse::<u64>()
+                .ok()
+                .map(Duration::from_secs)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use http::header::{HeaderMap, HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+    use http::status::StatusCode;
+    use oauth2::ClientId;
+
+    use super::{cache_control_max_age, JwksCache};
+    use crate::core::{
+        CoreIdToken, CoreIdTokenClaims, CoreIdTokenVerifier, CoreJsonWebKey, CoreJsonWebKeySet,
+        CoreJwksCache, CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey,
+    };
+    use crate::jwt::tests::TEST_RSA_PRIV_KEY;
+    use crate::verification::SignatureVerificationError;
+    use crate::{
+        Audience, ClaimsVerificationError, EmptyAdditionalClaims, HttpResponse, IssuerUrl,
+        JsonWebKey, JsonWebKeyId, JsonWebKeySetUrl, Nonce, PrivateSigningKey, StandardClaims,
+        SubjectIdentifier,
+    };
+
+    const TEST_CLIENT_ID: &str = "my_client";
+    const TEST_ISSUER: &str = "https://example.com";
+
+    fn signing_key(kid: &str) -> CoreRsaPrivateSigningKey {
+        CoreRsaPrivateSigningKey::from_pem(
+            TEST_RSA_PRIV_KEY,
+            Some(JsonWebKeyId::new(kid.to_string())),
+        )
+        .unwrap()
+    }
+
+    fn jwks_json(kids: &[&str]) -> String {
+        serde_json::to_string(&CoreJsonWebKeySet::new(
+            kids.iter()
+                .map(|kid| signing_key(kid).as_verification_key())
+                .collect::<Vec<CoreJsonWebKey>>(),
+        ))
+        .unwrap()
+    }
+
+    fn id_token(kid: &str) -> CoreIdToken {
+        CoreIdToken::new(
+            CoreIdTokenClaims::new(
+                IssuerUrl::new(TEST_ISSUER.to_string()).unwrap(),
+                vec![Audience::new(TEST_CLIENT_ID.to_string())],
+                Utc::now() + chrono::Duration::hours(1),
+                Utc::now(),
+                StandardClaims::new(SubjectIdentifier::new("subject".to_string())),
+                EmptyAdditionalClaims {},
+            ),
+            &signing_key(kid),
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn skip_nonce(_: Option<&Nonce>) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn verifier(cache: CoreJwksCache<std::io::Error>) -> CoreIdTokenVerifier<'static> {
+        CoreIdTokenVerifier::new_public_client(
+            ClientId::new(TEST_CLIENT_ID.to_string()),
+            IssuerUrl::new(TEST_ISSUER.to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .set_jwks_cache(cache)
+    }
+
+    // Mock JWKS endpoint that serves `responses` in order, repeating the last one.
+    struct MockJwksEndpoint {
+        responses: Vec<(String, Option<&'static str>)>,
+        requests: AtomicUsize,
+    }
+    impl MockJwksEndpoint {
+        fn new(responses: Vec<(String, Option<&'static str>)>) -> Arc<Self> {
+            Arc::new(Self {
+                responses,
+                requests: AtomicUsize::new(0),
+            })
+        }
+
+        fn cache(self: &Arc<Self>, now: &Arc<AtomicI64>) -> CoreJwksCache<std::io::Error> {
+            let endpoint = self.clone();
+            let now = now.clone();
+            JwksCache::new(
+                JsonWebKeySetUrl::new("https://example.com/jwks".to_string()).unwrap(),
+                move |request| {
+                    assert_eq!(request.url.as_str(), "https://example.com/jwks");
+                    let index = endpoint.requests.fetch_add(1, Ordering::SeqCst);
+                    let (body, cache_control) = endpoint
+                        .responses
+                        .get(index)
+                        .or_else(|| endpoint.responses.last())
+                        .unwrap()
+                        .clone();
+                    let mut headers = HeaderMap::new();
+                    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                    if let Some(cache_control) = cache_control {
+                        headers.insert(CACHE_CONTROL, HeaderValue::from_static(cache_control));
+                    }
+                    Ok(HttpResponse {
+                        status_code: StatusCode::OK,
+                        headers,
+                        body: body.into_bytes(),
+                    })
+                },
+            )
+            .set_time_fn(move || {
+                Utc.timestamp_opt(now.load(Ordering::SeqCst), 0)
+                    .single()
+                    .unwrap()
+            })
+        }
+
+        fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn test_cache_control_max_age() {
+        let headers = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CACHE_CONTROL, HeaderValue::from_static(value));
+            headers
+        };
+        assert_eq!(cache_control_max_age(&HeaderMap::new()), None);
+        assert_eq!(
+            cache_control_max_age(&headers("public, max-age=3600")),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            cache_control_max_age(&headers("Max-Age=\"60\"")),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            cache_control_max_age(&headers("max-age=60, no-cache")),
+            Some(Duration::from_secs(0))
+        );
+        assert_eq!(cache_control_max_age(&headers("max-age=bogus")), None);
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let now = Arc::new(AtomicI64::new(1_000_000));
+        let endpoint = MockJwksEndpoint::new(vec![
+            (jwks_json(&["key-1"]), Some("public, max-age=60")),
+            (jwks_json(&["key-2"]), None),
+        ]);
+        let cache = endpoint
+            .cache(&now)
+            .set_default_ttl(Duration::from_secs(120));
+
+        assert_eq!(cache.expires_at(), None);
+        let keys = cache.keys().unwrap();
+        assert_eq!(**keys.keys()[0].key_id().unwrap(), "key-1");
+        assert_eq!(cache.expires_at(), Utc.timestamp_opt(1_000_060, 0).single());
+        assert_eq!(endpoint.requests(), 1);
+
+        // Still fresh.
+        now.store(1_000_059, Ordering::SeqCst);
+        cache.keys().unwrap();
+        assert_eq!(endpoint.requests(), 1);
+
+        // The max-age has elapsed, so the keys are refetched. The second response has no
+        // Cache-Control header, so the default TTL applies.
+        now.store(1_000_060, Ordering::SeqCst);
+        let keys = cache.keys().unwrap();
+        assert_eq!(**keys.keys()[0].key_id().unwrap(), "key-2");
+        assert_eq!(endpoint.requests(), 2);
+        assert_eq!(cache.expires_at(), Utc.timestamp_opt(1_000_180, 0).single());
+
+        // Clones share the cached keys.
+        cache.clone().keys().unwrap();
+        assert_eq!(endpoint.requests(), 2);
+
+        cache.refresh().unwrap();
+        assert_eq!(endpoint.requests(), 3);
+    }
+
+    #[test]
+    fn test_refetch_on_unknown_kid() {
+        let now = Arc::new(AtomicI64::new(1_000_000));
+        let endpoint = MockJwksEndpoint::new(vec![
+            (jwks_json(&["key-1"]), Some("max-age=3600")),
+            (jwks_json(&["key-2"]), Some("max-age=3600")),
+        ]);
+        let verifier = verifier(endpoint.cache(&now));
+
+        id_token("key-1").claims(&verifier, skip_nonce).unwrap();
+        assert_eq!(endpoint.requests(), 1);
+
+        // A token signed by an unknown key doesn't trigger a refetch within the minimum refresh
+        // interval.
+        match id_token("key-2").claims(&verifier, skip_nonce) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey,
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(endpoint.requests(), 1);
+
+        // Once the minimum refresh interval elapses, the unknown key triggers a refetch even
+        // though the cached keys haven't expired.
+        now.store(1_000_030, Ordering::SeqCst);
+        id_token("key-2").claims(&verifier, skip_nonce).unwrap();
+        assert_eq!(endpoint.requests(), 2);
+
+        // Subsequent tokens use the refetched keys.
+        id_token("key-2").claims(&verifier, skip_nonce).unwrap();
+        assert_eq!(endpoint.requests(), 2);
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let now = Arc::new(AtomicI64::new(1_000_000));
+        // The provider publishes its new key alongside the old one while tokens signed by the old
+        // key may still be in use.
+        let endpoint = MockJwksEndpoint::new(vec![
+            (jwks_json(&["old-key"]), Some("max-age=3600")),
+            (jwks_json(&["old-key", "new-key"]), Some("max-age=3600")),
+            (jwks_json(&["new-key"]), Some("max-age=3600")),
+        ]);
+        let cache = endpoint
+            .cache(&now)
+            .set_min_refresh_interval(Duration::from_secs(0));
+        let verifier = verifier(cache.clone());
+
+        let old_token = id_token("old-key");
+        old_token.claims(&verifier, skip_nonce).unwrap();
+
+        let new_token = id_token("new-key");
+        new_token.claims(&verifier, skip_nonce).unwrap();
+        assert_eq!(endpoint.requests(), 2);
+
+        // The previously-issued token still validates after the rotation.
+        old_token.claims(&verifier, skip_nonce).unwrap();
+        assert_eq!(endpoint.requests(), 2);
+
+        // Once the old key is no longer published, tokens signed by it are rejected.
+        now.store(1_003_600, Ordering::SeqCst);
+        new_token.claims(&verifier, skip_nonce).unwrap();
+        assert_eq!(endpoint.requests(), 3);
+        match old_token.claims(&verifier, skip_nonce) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey,
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // The unknown key triggered another (unsuccessful) refetch.
+        assert_eq!(endpoint.requests(), 4);
+    }
+
+    #[test]
+    fn test_fetch_error() {
+        let cache = CoreJwksCache::new(
+            JsonWebKeySetUrl::new("https://example.com/jwks".to_string()).unwrap(),
+            |_| {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "connection refused",
+                ))
+            },
+        );
+        match id_token("key-1").claims(&verifier(cache), skip_nonce) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::Other(message),
+            )) => assert_eq!(
+                message,
+                "failed to fetch JWKS: Request failed: connection refused"
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
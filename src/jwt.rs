@@ -5,11 +5,13 @@ use std::str;
 
 use serde::de::{DeserializeOwned, Error as _, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
 use thiserror::Error;
 
 use super::{
-    JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
-    JwsSigningAlgorithm, PrivateSigningKey, SignatureVerificationError, SigningError,
+    DecryptionError, JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse,
+    JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm,
+    PrivateDecryptionKey, PrivateSigningKey, SignatureVerificationError, SigningError,
 };
 
 new_type![
@@ -125,6 +127,121 @@ where
     _phantom_jt: PhantomData<JT>,
 }
 
+// JOSE header of a JSON Web Encryption (JWE) compact serialization.
+// See https://tools.ietf.org/html/rfc7516#section-4.1.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct JsonWebEncryptionHeader<JE, JK, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JT: JsonWebKeyType,
+{
+    #[serde(bound = "JK: JweKeyManagementAlgorithm")]
+    pub alg: JK,
+    #[serde(bound = "JE: JweContentEncryptionAlgorithm<JT>, JT: JsonWebKeyType")]
+    pub enc: JE,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crit: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cty: Option<JsonWebTokenContentType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<JsonWebKeyId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typ: Option<JsonWebTokenType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip: Option<String>,
+    #[serde(skip)]
+    _phantom_jt: PhantomData<JT>,
+}
+
+// Signs the given header and payload using the JWS compact serialization. This is used for JWTs
+// whose header contains fields other than those supported by `JsonWebTokenHeader` (e.g., an
+// embedded public key).
+pub fn sign_compact_jws<H, P, F>(
+    header: &H,
+    payload: &P,
+    sign_fn: F,
+) -> Result<String, JsonWebTokenError>
+where
+    H: Serialize,
+    P: Serialize,
+    F: FnOnce(&[u8]) -> Result<Vec<u8>, SigningError>,
+{
+    let encode_part = |part: &[u8]| base64::encode_config(part, base64::URL_SAFE_NO_PAD);
+    let signing_input = format!(
+        "{}.{}",
+        encode_part(&serde_json::to_vec(header).map_err(JsonWebTokenError::SerializationError)?),
+        encode_part(&serde_json::to_vec(payload).map_err(JsonWebTokenError::SerializationError)?)
+    );
+    let signature = sign_fn(signing_input.as_bytes()).map_err(JsonWebTokenError::SigningError)?;
+    Ok(format!("{}.{}", signing_input, encode_part(&signature)))
+}
+
+// Returns true if the given token uses the five-part JWE compact serialization rather than the
+// three-part JWS compact serialization.
+pub fn is_compact_jwe(token: &str) -> bool {
+    token.split('.').count() == 5
+}
+
+// Decrypts a JWE compact serialization and returns the plaintext (e.g., a nested JWS).
+// See https://tools.ietf.org/html/rfc7516#section-5.2.
+pub fn decrypt_compact_jwe<JE, JK, JT, D>(jwe: &str, key: &D) -> Result<Vec<u8>, DecryptionError>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JT: JsonWebKeyType,
+    D: PrivateDecryptionKey<JE, JK, JT>,
+{
+    let parts = jwe.split('.').collect::<Vec<_>>();
+    if parts.len() != 5 {
+        return Err(DecryptionError::Other(format!(
+            "Invalid JSON web encryption: found {} parts (expected 5)",
+            parts.len()
+        )));
+    }
+
+    let decode_part = |part: &str, name: &str| {
+        base64::decode_config(part, crate::core::base64_url_safe_no_pad()).map_err(|err| {
+            DecryptionError::Other(format!("Invalid base64url {} encoding: {:?}", name, err))
+        })
+    };
+
+    let header_json = decode_part(parts[0], "header")?;
+    let header: JsonWebEncryptionHeader<JE, JK, JT> = serde_json::from_slice(&header_json)
+        .map_err(|err| DecryptionError::Other(format!("Failed to parse header JSON: {:?}", err)))?;
+
+    // We don't understand any critical header fields, so we must reject any JWE that includes
+    // them. See https://tools.ietf.org/html/rfc7516#section-4.1.13.
+    if header.crit.is_some() {
+        return Err(DecryptionError::Other(
+            "critical JWE header fields are unsupported".to_string(),
+        ));
+    }
+    if let Some(ref zip) = header.zip {
+        return Err(DecryptionError::UnsupportedAlg(format!(
+            "JWE compression algorithm `{}`",
+            zip
+        )));
+    }
+
+    let encrypted_key = decode_part(parts[1], "encrypted key")?;
+    let iv = decode_part(parts[2], "initialization vector")?;
+    let ciphertext = decode_part(parts[3], "ciphertext")?;
+    let tag = decode_part(parts[4], "authentication tag")?;
+
+    let cek = key.decrypt_key(&header.alg, &header.enc, &encrypted_key)?;
+    // The Additional Authenticated Data is the ASCII representation of the encoded protected
+    // header.
+    key.decrypt_content(
+        &header.enc,
+        &cek,
+        &iv,
+        parts[0].as_bytes(),
+        &ciphertext,
+        &tag,
+    )
+}
+
 pub trait JsonWebTokenPayloadSerde<P>: Debug
 where
     P: Debug + DeserializeOwned + Serialize,
@@ -145,7 +262,7 @@ where
     }
 
     fn serialize(payload: &P) -> Result<String, serde_json::Error> {
-        serde_json::to_string(payload).map_err(Into::into)
+        serde_json::to_string(payload)
     }
 }
 
@@ -190,6 +307,108 @@ pub enum JsonWebTokenError {
     ///
     #[error("Failed to sign JWT")]
     SigningError(#[source] SigningError),
+    ///
+    /// Failed to parse the JWT's JOSE header.
+    ///
+    #[error("Invalid JWT header: {0}")]
+    InvalidHeader(String),
+}
+
+///
+/// Unverified [JOSE header](https://tools.ietf.org/html/rfc7515#section-4) of a JSON Web
+/// Signature (JWS).
+///
+/// The header is parsed without validating the JWT's signature, so its contents are untrusted
+/// and must not be used for making security decisions. It's primarily useful for diagnosing key
+/// selection issues (e.g., a `kid` that doesn't match any key in the provider's JSON Web Key
+/// Set).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct JwsHeader {
+    alg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crit: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kid: Option<JsonWebKeyId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+    #[serde(flatten)]
+    other_fields: Map<String, Value>,
+}
+impl JwsHeader {
+    ///
+    /// Decodes and parses the JOSE header of a JWS compact serialization (e.g., a signed JWT)
+    /// without validating its signature.
+    ///
+    pub fn from_compact_jws(jws: &str) -> Result<Self, JsonWebTokenError> {
+        let parts = jws.split('.').collect::<Vec<_>>();
+        if parts.len() != 3 {
+            return Err(JsonWebTokenError::InvalidHeader(format!(
+                "found {} parts in JWS compact serialization (expected 3)",
+                parts.len()
+            )));
+        }
+        Self::from_encoded(parts[0])
+    }
+
+    fn from_encoded(encoded_header: &str) -> Result<Self, JsonWebTokenError> {
+        let header_json =
+            base64::decode_config(encoded_header, crate::core::base64_url_safe_no_pad()).map_err(
+                |err| {
+                    JsonWebTokenError::InvalidHeader(format!(
+                        "invalid base64url header encoding: {:?}",
+                        err
+                    ))
+                },
+            )?;
+        serde_json::from_slice(&header_json).map_err(|err| {
+            JsonWebTokenError::InvalidHeader(format!("failed to parse header JSON: {}", err))
+        })
+    }
+
+    ///
+    /// Returns the `alg` header parameter (signature algorithm), exactly as received.
+    ///
+    pub fn alg(&self) -> &str {
+        &self.alg
+    }
+
+    ///
+    /// Returns the `crit` header parameter (critical extension header parameters).
+    ///
+    pub fn crit(&self) -> Option<&Vec<String>> {
+        self.crit.as_ref()
+    }
+
+    ///
+    /// Returns the `cty` header parameter (content type).
+    ///
+    pub fn cty(&self) -> Option<&str> {
+        self.cty.as_deref()
+    }
+
+    ///
+    /// Returns the `kid` header parameter (key ID).
+    ///
+    pub fn kid(&self) -> Option<&JsonWebKeyId> {
+        self.kid.as_ref()
+    }
+
+    ///
+    /// Returns the `typ` header parameter (media type).
+    ///
+    pub fn typ(&self) -> Option<&str> {
+        self.typ.as_deref()
+    }
+
+    ///
+    /// Returns any other header parameters.
+    ///
+    pub fn other_fields(&self) -> &Map<String, Value> {
+        &self.other_fields
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -252,6 +471,17 @@ where
             _phantom: PhantomData,
         })
     }
+
+    // Parses the raw JOSE header as received, including any parameters not modeled by
+    // `JsonWebTokenHeader`.
+    pub fn unverified_raw_header(&self) -> Result<JwsHeader, JsonWebTokenError> {
+        let encoded_header = self
+            .signing_input
+            .split('.')
+            .next()
+            .expect("split always returns at least one element");
+        JwsHeader::from_encoded(encoded_header)
+    }
 }
 // Owned JWT.
 impl<JE, JS, JT, P, S> JsonWebTokenAccess<JE, JS, JT, P> for JsonWebToken<JE, JS, JT, P, S>
@@ -555,6 +785,102 @@ pub mod tests {
          1Vre3XB9HH8MYBVB6UIexaAq4xSeoemRKTBesZro7OKjKT8/GmiO\n\
          -----END RSA PRIVATE KEY-----";
 
+    // Encrypts the given plaintext as a JWE compact serialization using RSA-OAEP-256 (or RSA-OAEP)
+    // and the given AES-GCM content encryption algorithm, using the public half of
+    // TEST_RSA_PRIV_KEY.
+    pub fn encrypt_compact_jwe(plaintext: &str, alg: &str, enc: &str) -> String {
+        use rsa::pkcs1::DecodeRsaPrivateKey;
+
+        let public_key = rsa::RsaPrivateKey::from_pkcs1_pem(TEST_RSA_PRIV_KEY)
+            .unwrap()
+            .to_public_key();
+        let mut cek = vec![0; if enc == "A128GCM" { 16 } else { 32 }];
+        rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut cek);
+
+        let mut rng = rand::thread_rng();
+        let encrypted_key = match alg {
+            "RSA-OAEP" => public_key.encrypt(&mut rng, rsa::Oaep::new::<sha1::Sha1>(), &cek),
+            "RSA-OAEP-256" => public_key.encrypt(&mut rng, rsa::Oaep::new::<sha2::Sha256>(), &cek),
+            _ => panic!("unsupported alg {}", alg),
+        }
+        .unwrap();
+
+        encrypt_compact_jwe_content(plaintext, alg, enc, &cek, &encrypted_key)
+    }
+
+    // Encrypts the given plaintext as a JWE compact serialization using the given symmetric key
+    // with direct encryption (`dir`) or AES Key Wrap (`A128KW` or `A256KW`), and the given AES-GCM
+    // content encryption algorithm.
+    pub fn encrypt_compact_jwe_symmetric(
+        plaintext: &str,
+        alg: &str,
+        enc: &str,
+        key: &[u8],
+    ) -> String {
+        let (cek, encrypted_key) = if alg == "dir" {
+            (key.to_vec(), Vec::new())
+        } else {
+            let mut cek = vec![0; if enc == "A128GCM" { 16 } else { 32 }];
+            rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut cek);
+            let encrypted_key = match alg {
+                "A128KW" => aes_kw::KekAes128::try_from(key).unwrap().wrap_vec(&cek),
+                "A256KW" => aes_kw::KekAes256::try_from(key).unwrap().wrap_vec(&cek),
+                _ => panic!("unsupported alg {}", alg),
+            }
+            .unwrap();
+            (cek, encrypted_key)
+        };
+
+        encrypt_compact_jwe_content(plaintext, alg, enc, &cek, &encrypted_key)
+    }
+
+    fn encrypt_compact_jwe_content(
+        plaintext: &str,
+        alg: &str,
+        enc: &str,
+        cek: &[u8],
+        encrypted_key: &[u8],
+    ) -> String {
+        use aes_gcm::aead::{Aead, KeyInit, Payload};
+        use rand::RngCore;
+
+        let mut iv = [0; 12];
+        rand::thread_rng().fill_bytes(&mut iv);
+
+        let header = base64::encode_config(
+            format!(
+                "{{\"alg\":\"{}\",\"enc\":\"{}\",\"cty\":\"JWT\"}}",
+                alg, enc
+            ),
+            base64::URL_SAFE_NO_PAD,
+        );
+        let payload = Payload {
+            msg: plaintext.as_bytes(),
+            aad: header.as_bytes(),
+        };
+        let nonce = iv.into();
+        let mut ciphertext = if enc == "A128GCM" {
+            aes_gcm::Aes128Gcm::new_from_slice(cek)
+                .unwrap()
+                .encrypt(&nonce, payload)
+        } else {
+            aes_gcm::Aes256Gcm::new_from_slice(cek)
+                .unwrap()
+                .encrypt(&nonce, payload)
+        }
+        .unwrap();
+        let tag = ciphertext.split_off(ciphertext.len() - 16);
+
+        [
+            header,
+            base64::encode_config(encrypted_key, base64::URL_SAFE_NO_PAD),
+            base64::encode_config(iv, base64::URL_SAFE_NO_PAD),
+            base64::encode_config(ciphertext, base64::URL_SAFE_NO_PAD),
+            base64::encode_config(tag, base64::URL_SAFE_NO_PAD),
+        ]
+        .join(".")
+    }
+
     #[test]
     fn test_jwt_algorithm_deserialization() {
         assert_eq!(
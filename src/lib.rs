@@ -70,6 +70,12 @@
 //!      RE: std::error::Error + 'static
 //!    ```
 //!
+//!    Alternatively, transports may implement the [`SyncHttpClient`] or [`AsyncHttpClient`]
+//!    trait. Request builders defined by this crate (e.g., [`UserInfoRequest`] and
+//!    [`ProviderMetadata::discover`]) accept any implementation of these traits, while those
+//!    provided by the [`oauth2`] crate (e.g., [`CodeTokenRequest`]) accept them via
+//!    [`http_client_fn`] or [`async_http_client_fn`], respectively.
+//!
 //! # OpenID Connect Relying Party (Client) Interface
 //!
 //! The [`Client`] struct provides the OpenID Connect Relying Party interface. The most common
@@ -577,11 +583,14 @@ extern crate pretty_assertions;
 extern crate serde_derive;
 
 use oauth2::ResponseType as OAuth2ResponseType;
+use thiserror::Error;
 use url::Url;
 
 use std::borrow::Cow;
+use std::future::Future;
 use std::marker::PhantomData;
 use std::str;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 pub use oauth2::{
@@ -617,33 +626,81 @@ pub use oauth2::reqwest;
 #[cfg(feature = "ureq")]
 pub use oauth2::ureq;
 
+pub use authorization_response::{
+    AuthorizationErrorResponse, AuthorizationResponse, AuthorizationResponseError,
+};
+pub use ciba::{
+    BackchannelAuthenticationRequest, BackchannelAuthenticationResponse, BackchannelTokenRequest,
+};
+pub use claim_sources::{ClaimSource, ClaimSourceError};
 pub use claims::{
-    AdditionalClaims, AddressClaim, EmptyAdditionalClaims, GenderClaim, StandardClaims,
+    standard_claims_metadata, AdditionalClaims, AddressClaim, ClaimValueType,
+    EmptyAdditionalClaims, GenderClaim, GroupsClaims, MergedClaims, PreservedClaims,
+    StandardClaimMetadata, StandardClaims,
 };
+pub use claims_request::{ClaimRequestValue, ClaimsRequest};
+use client_auth::ClientAssertionSigner;
 pub use discovery::{
     AdditionalProviderMetadata, DiscoveryError, EmptyAdditionalProviderMetadata, ProviderMetadata,
+    ProviderMetadataValidationError,
+};
+pub use dpop::{DPoPHttpClientError, DPoPKey};
+pub use error::OpenIdConnectError;
+#[cfg(all(feature = "curl", not(target_arch = "wasm32")))]
+pub use http_client::CurlHttpClient;
+#[cfg(feature = "reqwest")]
+pub use http_client::ReqwestHttpClient;
+#[cfg(feature = "ureq")]
+pub use http_client::UreqHttpClient;
+pub use http_client::{
+    async_http_client_fn, http_client_fn, AsyncHttpClient, ClassifyTimeout, HttpErrorRecorder,
+    HttpErrorResponse, HttpTimeout, SyncHttpClient,
 };
+#[cfg(feature = "test-utils")]
+pub use id_token::IdTokenBuilder;
 pub use id_token::IdTokenFields;
-pub use id_token::{IdToken, IdTokenClaims};
-pub use jwt::JsonWebTokenError;
+pub use id_token::{AuthorizedParty, IdToken, IdTokenClaims};
+pub use jwks_cache::JwksCache;
+use jwks_cache::JwksSource;
 use jwt::{JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenHeader};
-pub use logout::{LogoutProviderMetadata, LogoutRequest, ProviderMetadataWithLogout};
+pub use jwt::{JsonWebTokenError, JwsHeader};
+pub use logout::{
+    FrontChannelLogoutError, FrontChannelLogoutRequest, LogoutProviderMetadata, LogoutRequest,
+    LogoutToken, LogoutTokenClaims, ProviderMetadataWithLogout,
+};
+pub use par::{PushedAuthorizationRequest, PushedAuthorizationResponse};
+pub use refresh_token::{RefreshTokenError, RefreshTokenUpdate};
+pub use request_object::{
+    RequestObject, RequestObjectClaims, RequestObjectError, RequestObjectFetchError,
+};
+pub use resource_indicator::{ResourceIndicator, ResourceIndicatorError, ResourceIndicatorRequest};
+pub use retry_policy::RetryPolicy;
+pub use scopes::{scopes_for_claims, Scopes, ScopesError};
+pub use session::compute_session_state;
 // Flatten the module hierarchy involving types. They're only separated to improve code
 // organization.
+pub use token_manager::{TokenManager, TokenManagerError};
+#[cfg(feature = "chrono-tz")]
+pub use types::TimezoneParseError;
 pub use types::{
-    AccessTokenHash, AddressCountry, AddressLocality, AddressPostalCode, AddressRegion,
-    ApplicationType, Audience, AuthDisplay, AuthPrompt, AuthenticationContextClass,
-    AuthenticationMethodReference, AuthorizationCodeHash, ClaimName, ClaimType, ClientAuthMethod,
-    ClientConfigUrl, ClientContactEmail, ClientName, ClientUrl, EndSessionUrl, EndUserBirthday,
-    EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName,
-    EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone,
-    EndUserUsername, EndUserWebsiteUrl, FormattedAddress, GrantType, InitiateLoginUrl, IssuerUrl,
-    JsonWebKey, JsonWebKeyAlgorithm, JsonWebKeyId, JsonWebKeySet, JsonWebKeySetUrl, JsonWebKeyType,
-    JsonWebKeyUse, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm,
-    LanguageTag, LocalizedClaim, LoginHint, LogoUrl, LogoutHint, Nonce, OpPolicyUrl, OpTosUrl,
-    PolicyUrl, PostLogoutRedirectUrl, PrivateSigningKey, RegistrationAccessToken, RegistrationUrl,
-    RequestUrl, ResponseMode, ResponseType, ResponseTypes, SectorIdentifierUrl, ServiceDocUrl,
-    SigningError, StreetAddress, SubjectIdentifier, SubjectIdentifierType, ToSUrl,
+    compute_access_token_hash, compute_code_hash, AccessTokenHash, AddressCountry, AddressLocality,
+    AddressPostalCode, AddressRegion, ApplicationType, Audience, AuthDisplay, AuthPrompt,
+    AuthReqId, AuthenticationContextClass, AuthenticationMethodReference, AuthorizationCodeHash,
+    BackchannelAuthenticationUrl, BirthdayParseError, CheckSessionIframeUrl, ClaimName,
+    ClaimSourceEndpointUrl, ClaimType, ClientAuthMethod, ClientConfigUrl, ClientContactEmail,
+    ClientName, ClientUrl, DecryptionError, EndSessionUrl, EndUserBirthday, EndUserEmail,
+    EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname,
+    EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername,
+    EndUserWebsiteUrl, FormattedAddress, GrantType, InitiateLoginUrl, IssuerUrl, JsonWebKey,
+    JsonWebKeyAlgorithm, JsonWebKeyId, JsonWebKeySet, JsonWebKeySetUrl, JsonWebKeyType,
+    JsonWebKeyUse, JsonWebTokenId, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm,
+    JwsSigningAlgorithm, LanguageTag, LocalizedClaim, LoginHint, LoginHintToken, LogoUrl,
+    LogoutHint, Nonce, OpPolicyUrl, OpTosUrl, PhoneNumberParseError, PolicyUrl,
+    PostLogoutRedirectUrl, PrivateDecryptionKey, PrivateSigningKey, PushedAuthorizationRequestUri,
+    PushedAuthorizationRequestUrl, RegistrationAccessToken, RegistrationUrl, RequestUrl,
+    ResponseMode, ResponseType, ResponseTypes, Seconds, SectorIdentifierUrl, ServiceDocUrl,
+    SessionIdentifier, SigningError, SoftwareStatement, StreetAddress, SubjectIdentifier,
+    SubjectIdentifierType, ToSUrl,
 };
 
 pub use user_info::{
@@ -651,9 +708,11 @@ pub use user_info::{
 };
 use verification::{AudiencesClaim, IssuerClaim};
 pub use verification::{
-    ClaimsVerificationError, IdTokenVerifier, NonceVerifier, SignatureVerificationError,
-    UserInfoVerifier,
+    ClaimSourceVerifier, ClaimsVerificationError, IdTokenVerifier, IssuerMatch, JarmVerifier,
+    LogoutTokenVerifier, MultiIssuerVerifier, NonceCheck, NonceVerifier, RequestObjectVerifier,
+    SignatureVerificationError, SignedMetadataVerifier, SignedNonceVerifier, UserInfoVerifier,
 };
+pub use webfinger::IssuerDiscovery;
 
 // Defined first since other modules need the macros, and definition order is significant for
 // macros. This module is private.
@@ -668,14 +727,32 @@ pub mod registration;
 
 // Private modules since we may move types between different modules; these are exported publicly
 // via the pub use above.
+mod authorization_response;
+mod ciba;
+mod claim_sources;
 mod claims;
+mod claims_request;
+mod client_auth;
 mod discovery;
+mod dpop;
+mod error;
 mod helpers;
+mod http_client;
 mod id_token;
+mod jwks_cache;
 mod logout;
+mod par;
+mod refresh_token;
+mod request_object;
+mod resource_indicator;
+mod retry_policy;
+mod scopes;
+mod session;
+mod token_manager;
 pub(crate) mod types;
 mod user_info;
 mod verification;
+mod webfinger;
 
 // Private module for HTTP(S) utilities.
 mod http_utils;
@@ -808,8 +885,20 @@ where
     client_secret: Option<ClientSecret>,
     issuer: IssuerUrl,
     userinfo_endpoint: Option<UserInfoUrl>,
-    jwks: JsonWebKeySet<JS, JT, JU, K>,
+    pushed_authorization_request_url: Option<PushedAuthorizationRequestUrl>,
+    backchannel_authentication_url: Option<BackchannelAuthenticationUrl>,
+    client_assertion_signer: Option<ClientAssertionSigner>,
+    // Shared between clones so that refreshed keys are visible to all of them. Refreshing swaps in
+    // a new `Arc` rather than mutating the keys in place, so verifiers share a snapshot without
+    // copying it.
+    jwks: Arc<RwLock<Arc<JsonWebKeySet<JS, JT, JU, K>>>>,
+    jwks_uri: Option<JsonWebKeySetUrl>,
+    jwks_cache: Option<Arc<dyn JwksSource<JS, JT, JU, K>>>,
     id_token_signing_algs: Option<Vec<JS>>,
+    request_object_signing_algs: Option<Vec<JS>>,
+    request_parameter_supported: Option<bool>,
+    request_uri_parameter_supported: Option<bool>,
+    response_modes_supported: Option<Vec<String>>,
     use_openid_scope: bool,
     _phantom: PhantomData<(AC, AD, GC, JE, P)>,
 }
@@ -855,8 +944,17 @@ where
             client_secret,
             issuer,
             userinfo_endpoint,
-            jwks,
+            pushed_authorization_request_url: None,
+            backchannel_authentication_url: None,
+            client_assertion_signer: None,
+            jwks: Arc::new(RwLock::new(Arc::new(jwks))),
+            jwks_uri: None,
+            jwks_cache: None,
             id_token_signing_algs: None,
+            request_object_signing_algs: None,
+            request_parameter_supported: None,
+            request_uri_parameter_supported: None,
+            response_modes_supported: None,
             use_openid_scope: true,
             _phantom: PhantomData,
         }
@@ -884,23 +982,53 @@ where
         RS: ResponseType,
         S: SubjectIdentifierType,
     {
+        let mut oauth2_client = oauth2::Client::new(
+            client_id.clone(),
+            client_secret.clone(),
+            provider_metadata.authorization_endpoint().clone(),
+            provider_metadata.token_endpoint().cloned(),
+        );
+        if let Some(introspection_endpoint) = provider_metadata.introspection_endpoint() {
+            oauth2_client = oauth2_client.set_introspection_uri(introspection_endpoint.clone());
+        }
+        if let Some(revocation_endpoint) = provider_metadata.revocation_endpoint() {
+            oauth2_client = oauth2_client.set_revocation_uri(revocation_endpoint.clone());
+        }
+        if let Some(device_authorization_endpoint) =
+            provider_metadata.device_authorization_endpoint()
+        {
+            oauth2_client =
+                oauth2_client.set_device_authorization_url(device_authorization_endpoint.clone());
+        }
         Client {
-            oauth2_client: oauth2::Client::new(
-                client_id.clone(),
-                client_secret.clone(),
-                provider_metadata.authorization_endpoint().clone(),
-                provider_metadata.token_endpoint().cloned(),
-            ),
+            oauth2_client,
             client_id,
             client_secret,
             issuer: provider_metadata.issuer().clone(),
             userinfo_endpoint: provider_metadata.userinfo_endpoint().cloned(),
-            jwks: provider_metadata.jwks().to_owned(),
+            pushed_authorization_request_url: provider_metadata
+                .pushed_authorization_request_endpoint()
+                .cloned(),
+            backchannel_authentication_url: provider_metadata
+                .backchannel_authentication_endpoint()
+                .cloned(),
+            client_assertion_signer: None,
+            jwks: Arc::new(RwLock::new(Arc::new(provider_metadata.jwks().to_owned()))),
+            jwks_uri: Some(provider_metadata.jwks_uri().clone()),
+            jwks_cache: None,
             id_token_signing_algs: Some(
                 provider_metadata
                     .id_token_signing_alg_values_supported()
                     .to_owned(),
             ),
+            request_object_signing_algs: provider_metadata
+                .request_object_signing_alg_values_supported()
+                .cloned(),
+            request_parameter_supported: provider_metadata.request_parameter_supported(),
+            request_uri_parameter_supported: provider_metadata.request_uri_parameter_supported(),
+            response_modes_supported: provider_metadata
+                .response_modes_supported()
+                .map(|response_modes| response_modes.iter().map(response_mode_to_string).collect()),
             use_openid_scope: true,
             _phantom: PhantomData,
         }
@@ -916,11 +1044,90 @@ where
     /// [`Client::new`]), [`AuthType::RequestBody`] is used regardless of the `auth_type` passed to
     /// this function.
     ///
+    /// JWT-based client authentication is configured separately using
+    /// [`set_private_key_jwt_auth`](Self::set_private_key_jwt_auth) or, for clients using the core
+    /// types, `set_client_secret_jwt_auth`.
+    ///
     pub fn set_auth_type(mut self, auth_type: AuthType) -> Self {
         self.oauth2_client = self.oauth2_client.set_auth_type(auth_type);
         self
     }
 
+    ///
+    /// Configures the client to authenticate using the `private_key_jwt` method described in
+    /// [Section 9 of OpenID Connect Core 1.0](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#ClientAuthentication).
+    ///
+    /// Instead of sending a client secret, each request to the token, introspection, revocation,
+    /// device authorization, and pushed authorization request endpoints includes a
+    /// `client_assertion` JWT signed by `signing_key` using `alg`. The assertion's `iss` and `sub`
+    /// claims are the client ID, its `aud` claim is the token endpoint URL (or the issuer URL if
+    /// no token endpoint is configured, as well as for pushed authorization requests), and it
+    /// expires shortly after being issued. The client secret (if any) is no longer sent to these
+    /// endpoints, but it continues to be used for verifying HMAC-signed ID tokens.
+    ///
+    /// This function should be called after any calls to [`set_auth_type`](Self::set_auth_type),
+    /// which is overridden to [`AuthType::RequestBody`].
+    ///
+    /// Returns an error if `signing_key` cannot produce signatures using `alg`. If signing a client
+    /// assertion fails later, pushed authorization and backchannel authentication requests return
+    /// a [`RequestTokenError::Other`] error when submitted. Other requests are sent without the
+    /// assertion, and the provider rejects them with an `invalid_client` error.
+    ///
+    pub fn set_private_key_jwt_auth<S>(
+        self,
+        signing_key: S,
+        alg: JS,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JE: 'static,
+        JS: Send + Sync + 'static,
+        JT: 'static,
+        JU: 'static,
+        K: 'static,
+        S: PrivateSigningKey<JS, JT, JU, K> + Send + Sync + 'static,
+    {
+        self.set_client_assertion_signer(ClientAssertionSigner::new::<JE, JS, JT, JU, K, S>(
+            signing_key,
+            alg,
+        ))
+    }
+
+    // Authenticates all subsequent requests using JWT assertions produced by the given signer
+    // instead of the client secret.
+    pub(crate) fn set_client_assertion_signer(
+        mut self,
+        signer: ClientAssertionSigner,
+    ) -> Result<Self, JsonWebTokenError> {
+        // Sign a throwaway assertion so that incompatible keys and algorithms are reported here
+        // rather than when building each request.
+        signer.params(&self.client_id, self.issuer.as_str())?;
+
+        let mut oauth2_client = oauth2::Client::new(
+            self.client_id.clone(),
+            None,
+            self.oauth2_client.auth_url().clone(),
+            self.oauth2_client.token_url().cloned(),
+        )
+        .set_auth_type(AuthType::RequestBody);
+        if let Some(redirect_url) = self.oauth2_client.redirect_url() {
+            oauth2_client = oauth2_client.set_redirect_uri(redirect_url.clone());
+        }
+        if let Some(introspection_url) = self.oauth2_client.introspection_url() {
+            oauth2_client = oauth2_client.set_introspection_uri(introspection_url.clone());
+        }
+        if let Some(revocation_url) = self.oauth2_client.revocation_url() {
+            oauth2_client = oauth2_client.set_revocation_uri(revocation_url.clone());
+        }
+        if let Some(device_authorization_url) = self.oauth2_client.device_authorization_url() {
+            oauth2_client =
+                oauth2_client.set_device_authorization_url(device_authorization_url.clone());
+        }
+        self.oauth2_client = oauth2_client;
+        self.client_assertion_signer = Some(signer);
+        Ok(self)
+    }
+
     ///
     /// Sets the the redirect URL used by the authorization endpoint.
     ///
@@ -948,6 +1155,34 @@ where
         self
     }
 
+    ///
+    /// Sets the pushed authorization request URL for contacting the pushed authorization request
+    /// endpoint ([RFC 9126](https://tools.ietf.org/html/rfc9126)).
+    ///
+    /// See: [`push_authorization_request()`](Self::push_authorization_request())
+    ///
+    pub fn set_pushed_authorization_request_uri(
+        mut self,
+        pushed_authorization_request_url: PushedAuthorizationRequestUrl,
+    ) -> Self {
+        self.pushed_authorization_request_url = Some(pushed_authorization_request_url);
+        self
+    }
+
+    ///
+    /// Sets the backchannel authentication URL for initiating Client-Initiated Backchannel
+    /// Authentication (CIBA).
+    ///
+    /// See: [`backchannel_authentication_request()`](Self::backchannel_authentication_request())
+    ///
+    pub fn set_backchannel_authentication_uri(
+        mut self,
+        backchannel_authentication_url: BackchannelAuthenticationUrl,
+    ) -> Self {
+        self.backchannel_authentication_url = Some(backchannel_authentication_url);
+        self
+    }
+
     ///
     /// Sets the device authorization URL for contacting the device authorization endpoint ([RFC 8628](https://tools.ietf.org/html/rfc8628)).
     ///
@@ -961,6 +1196,112 @@ where
         self
     }
 
+    ///
+    /// Sets the URL of the provider's JSON Web Key Set, which is fetched by
+    /// [`refresh_jwks`](Self::refresh_jwks) and [`refresh_jwks_async`](Self::refresh_jwks_async).
+    ///
+    /// This URL is set automatically by [`from_provider_metadata`](Self::from_provider_metadata).
+    ///
+    pub fn set_jwks_uri(mut self, jwks_uri: JsonWebKeySetUrl) -> Self {
+        self.jwks_uri = Some(jwks_uri);
+        self
+    }
+
+    ///
+    /// Returns the URL of the provider's JSON Web Key Set, if set.
+    ///
+    pub fn jwks_uri(&self) -> Option<&JsonWebKeySetUrl> {
+        self.jwks_uri.as_ref()
+    }
+
+    ///
+    /// Fetches the provider's JSON Web Key Set from its [`jwks_uri`](Self::jwks_uri) and replaces
+    /// the keys used for verifying ID tokens, logout tokens, and signed user info responses.
+    ///
+    /// This is useful for proactively picking up rotated signing keys (e.g., on a schedule or in
+    /// response to a webhook). The keys are replaced atomically and are shared by all clones of
+    /// this client, while verifiers previously returned by this client keep using the keys they
+    /// were created with. Clients configured with [`set_jwks_cache`](Self::set_jwks_cache) use
+    /// the cached keys instead; see [`JwksCache::refresh`].
+    ///
+    /// Returns the key IDs (`kid`) of the fetched keys, in order, with `None` for any key without
+    /// a key ID.
+    ///
+    pub fn refresh_jwks<HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> Result<Vec<Option<JsonWebKeyId>>, DiscoveryError<RE>>
+    where
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        let jwks = JsonWebKeySet::fetch(self.jwks_uri_or_err()?, http_client)?;
+        Ok(self.replace_jwks(jwks))
+    }
+
+    ///
+    /// Asynchronously fetches the provider's JSON Web Key Set from its
+    /// [`jwks_uri`](Self::jwks_uri) and replaces the keys used for verification.
+    ///
+    /// See [`refresh_jwks`](Self::refresh_jwks) for details.
+    ///
+    pub async fn refresh_jwks_async<F, HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> Result<Vec<Option<JsonWebKeyId>>, DiscoveryError<RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
+        RE: std::error::Error + 'static,
+    {
+        let jwks = JsonWebKeySet::fetch_async(self.jwks_uri_or_err()?, http_client).await?;
+        Ok(self.replace_jwks(jwks))
+    }
+
+    fn jwks_uri_or_err<RE>(&self) -> Result<&JsonWebKeySetUrl, DiscoveryError<RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        self.jwks_uri
+            .as_ref()
+            .ok_or_else(|| DiscoveryError::Other("no JWKS URL (jwks_uri) is set".to_string()))
+    }
+
+    fn replace_jwks(&self, jwks: JsonWebKeySet<JS, JT, JU, K>) -> Vec<Option<JsonWebKeyId>> {
+        let key_ids = jwks
+            .keys()
+            .iter()
+            .map(|key| key.key_id().cloned())
+            .collect();
+        *self.jwks.write().unwrap_or_else(|err| err.into_inner()) = Arc::new(jwks);
+        key_ids
+    }
+
+    // Returns the current keys, shared with any clones of this client and the verifiers created
+    // from them. A later refresh replaces the shared `Arc` and leaves this snapshot unchanged.
+    fn jwks_snapshot(&self) -> Arc<JsonWebKeySet<JS, JT, JU, K>> {
+        self.jwks
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    ///
+    /// Verifies ID tokens, logout tokens, and signed user info responses using the keys in the
+    /// given [`JwksCache`] instead of the JSON Web Key Set this client was initialized with.
+    ///
+    /// This allows a long-running client to keep verifying tokens across rotations of the
+    /// provider's signing keys.
+    ///
+    pub fn set_jwks_cache<RE>(mut self, jwks_cache: JwksCache<JS, JT, JU, K, RE>) -> Self
+    where
+        RE: std::error::Error + 'static,
+        JsonWebKeySet<JS, JT, JU, K>: Send + Sync,
+    {
+        self.jwks_cache = Some(Arc::new(jwks_cache));
+        self
+    }
+
     ///
     /// Enables the `openid` scope to be requested automatically.
     ///
@@ -983,22 +1324,66 @@ where
     ///
     /// Returns an ID token verifier for use with the [`IdToken::claims`] method.
     ///
-    pub fn id_token_verifier(&self) -> IdTokenVerifier<JS, JT, JU, K> {
+    pub fn id_token_verifier(&self) -> IdTokenVerifier<'_, JS, JT, JU, K> {
+        // The keys are set below so that the verifier shares this client's snapshot of the JWKS
+        // instead of copying it.
         let verifier = if let Some(ref client_secret) = self.client_secret {
             IdTokenVerifier::new_confidential_client(
                 self.client_id.clone(),
                 client_secret.clone(),
                 self.issuer.clone(),
-                self.jwks.clone(),
+                JsonWebKeySet::default(),
             )
         } else {
             IdTokenVerifier::new_public_client(
                 self.client_id.clone(),
                 self.issuer.clone(),
-                self.jwks.clone(),
+                JsonWebKeySet::default(),
             )
         };
 
+        let verifier = if let Some(ref jwks_cache) = self.jwks_cache {
+            verifier.set_jwks_source(jwks_cache.clone())
+        } else {
+            verifier.set_signature_keys(self.jwks_snapshot())
+        };
+
+        if let Some(id_token_signing_algs) = self.id_token_signing_algs.clone() {
+            verifier.set_allowed_algs(id_token_signing_algs)
+        } else {
+            verifier
+        }
+    }
+
+    ///
+    /// Returns a verifier for logout tokens received by this client's back-channel logout URI,
+    /// for use with the [`LogoutToken::claims`] method.
+    ///
+    /// The verifier accepts the same signature algorithms as the
+    /// [ID token verifier](Self::id_token_verifier).
+    ///
+    pub fn logout_token_verifier(&self) -> LogoutTokenVerifier<'_, JS, JT, JU, K> {
+        let verifier = if let Some(ref client_secret) = self.client_secret {
+            LogoutTokenVerifier::new_confidential_client(
+                self.client_id.clone(),
+                client_secret.clone(),
+                self.issuer.clone(),
+                JsonWebKeySet::default(),
+            )
+        } else {
+            LogoutTokenVerifier::new_public_client(
+                self.client_id.clone(),
+                self.issuer.clone(),
+                JsonWebKeySet::default(),
+            )
+        };
+
+        let verifier = if let Some(ref jwks_cache) = self.jwks_cache {
+            verifier.set_jwks_source(jwks_cache.clone())
+        } else {
+            verifier.set_signature_keys(self.jwks_snapshot())
+        };
+
         if let Some(id_token_signing_algs) = self.id_token_signing_algs.clone() {
             verifier.set_allowed_algs(id_token_signing_algs)
         } else {
@@ -1006,12 +1391,44 @@ where
         }
     }
 
+    ///
+    /// Returns a verifier for JWT Secured Authorization Response Mode (JARM) responses, for use
+    /// with the [`AuthorizationResponse::from_jarm`] method.
+    ///
+    /// By default, only `RS256` signatures are accepted. Use [`JarmVerifier::set_allowed_algs`]
+    /// to accept the algorithms listed in the provider's
+    /// `authorization_signing_alg_values_supported` metadata.
+    ///
+    pub fn jarm_verifier(&self) -> JarmVerifier<'_, JE, JS, JT, JU, K> {
+        let verifier = if let Some(ref client_secret) = self.client_secret {
+            JarmVerifier::new_confidential_client(
+                self.client_id.clone(),
+                client_secret.clone(),
+                self.issuer.clone(),
+                JsonWebKeySet::default(),
+            )
+        } else {
+            JarmVerifier::new_public_client(
+                self.client_id.clone(),
+                self.issuer.clone(),
+                JsonWebKeySet::default(),
+            )
+        };
+
+        if let Some(ref jwks_cache) = self.jwks_cache {
+            verifier.set_jwks_source(jwks_cache.clone())
+        } else {
+            verifier.set_signature_keys(self.jwks_snapshot())
+        }
+    }
+
     ///
     /// Generates an authorization URL for a new authorization request.
     ///
-    /// NOTE: [Passing authorization request parameters as a JSON Web Token
+    /// NOTE: To pass the authorization request parameters [as a JSON Web Token
     /// ](https://openid.net/specs/openid-connect-core-1_0.html#JWTRequests)
-    /// instead of URL query parameters is not currently supported. The
+    /// instead of URL query parameters, use
+    /// [`authorization_url_with_request`](Self::authorization_url_with_request). The
     /// [`claims` parameter](https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter)
     /// is also not directly supported, although the [`AuthorizationRequest::add_extra_param`]
     /// method can be used to add custom parameters, including `claims`.
@@ -1041,7 +1458,7 @@ where
         authentication_flow: AuthenticationFlow<RS>,
         state_fn: SF,
         nonce_fn: NF,
-    ) -> AuthorizationRequest<AD, P, RS>
+    ) -> AuthorizationRequest<'_, AD, P, RS>
     where
         NF: FnOnce() -> Nonce + 'static,
         RS: ResponseType,
@@ -1051,6 +1468,7 @@ where
             inner: self.oauth2_client.authorize_url(state_fn),
             acr_values: Vec::new(),
             authentication_flow,
+            claims: None,
             claims_locales: Vec::new(),
             display: None,
             id_token_hint: None,
@@ -1058,6 +1476,10 @@ where
             max_age: None,
             nonce: nonce_fn(),
             prompts: Vec::new(),
+            resources: Vec::new(),
+            response_mode: None,
+            response_modes_supported: self.response_modes_supported.as_ref(),
+            scopes: Vec::new(),
             ui_locales: Vec::new(),
         };
         if self.use_openid_scope {
@@ -1075,29 +1497,49 @@ where
     ///
     /// See <https://tools.ietf.org/html/rfc6749#section-4.1.3>
     ///
-    pub fn exchange_code(&self, code: AuthorizationCode) -> CodeTokenRequest<TE, TR, TT> {
-        self.oauth2_client.exchange_code(code)
+    pub fn exchange_code(&self, code: AuthorizationCode) -> CodeTokenRequest<'_, TE, TR, TT> {
+        self.oauth2_client_assertion_params().into_iter().fold(
+            self.oauth2_client.exchange_code(code),
+            |request, (name, value)| request.add_extra_param(name, value),
+        )
     }
 
     ///
     /// Creates a request builder for device authorization.
     ///
-    /// See <https://tools.ietf.org/html/rfc8628#section-3.4>
+    /// Requires that [`set_device_authorization_uri()`](Self::set_device_authorization_uri())
+    /// have already been called to set the device authorization endpoint URL, or that the client
+    /// was initialized from provider metadata containing a `device_authorization_endpoint`. The
+    /// `openid` scope is requested unless [`disable_openid_scope()`](Self::disable_openid_scope())
+    /// was called.
+    ///
+    /// See <https://tools.ietf.org/html/rfc8628#section-3.1>
     ///
     pub fn exchange_device_code(
         &self,
-    ) -> Result<DeviceAuthorizationRequest<TE>, ConfigurationError> {
-        let request = self.oauth2_client.exchange_device_code();
+    ) -> Result<DeviceAuthorizationRequest<'_, TE>, ConfigurationError> {
+        let request = self.oauth2_client_assertion_params().into_iter().fold(
+            self.oauth2_client.exchange_device_code()?,
+            |request, (name, value)| request.add_extra_param(name, value),
+        );
         if self.use_openid_scope {
-            Ok(request?.add_scope(Scope::new(OPENID_SCOPE.to_string())))
+            Ok(request.add_scope(Scope::new(OPENID_SCOPE.to_string())))
         } else {
-            request
+            Ok(request)
         }
     }
 
     ///
     /// Creates a request builder for exchanging a device code for an access token.
     ///
+    /// The returned request polls the token endpoint until the user completes the authorization,
+    /// waiting for the interval specified by the device authorization response between attempts.
+    /// `authorization_pending` errors cause polling to continue at the same interval, while
+    /// `slow_down` errors increase the interval by 5 seconds. The resulting token response
+    /// contains an ID token, which should be verified using
+    /// [`id_token_verifier()`](Self::id_token_verifier()). Since the device flow doesn't
+    /// support sending a nonce, [`NonceCheck::Absent`] should be used as the nonce verifier.
+    ///
     /// See <https://tools.ietf.org/html/rfc8628#section-3.4>
     ///
     pub fn exchange_device_access_token<'a, 'b, 'c, EF>(
@@ -1108,13 +1550,22 @@ where
         'a: 'b,
         EF: ExtraDeviceAuthorizationFields,
     {
-        self.oauth2_client
-            .exchange_device_access_token(auth_response)
+        self.oauth2_client_assertion_params().into_iter().fold(
+            self.oauth2_client
+                .exchange_device_access_token(auth_response),
+            |request, (name, value)| request.add_extra_param(name, value),
+        )
     }
 
     ///
     /// Creates a request builder for exchanging a refresh token for an access token.
     ///
+    /// If the authorization server rotates refresh tokens, the new refresh token is returned by
+    /// [`OAuth2TokenResponse::refresh_token`], and the previous one should no longer be used.
+    /// [`RefreshTokenUpdate`] determines which refresh token to persist, and
+    /// [`RefreshTokenError`] distinguishes `invalid_grant` errors caused by a refresh token that
+    /// was already used or rotated out.
+    ///
     /// See <https://tools.ietf.org/html/rfc6749#section-6>
     ///
     pub fn exchange_refresh_token<'a, 'b>(
@@ -1124,7 +1575,10 @@ where
     where
         'a: 'b,
     {
-        self.oauth2_client.exchange_refresh_token(refresh_token)
+        self.oauth2_client_assertion_params().into_iter().fold(
+            self.oauth2_client.exchange_refresh_token(refresh_token),
+            |request, (name, value)| request.add_extra_param(name, value),
+        )
     }
 
     ///
@@ -1140,7 +1594,10 @@ where
     where
         'a: 'b,
     {
-        self.oauth2_client.exchange_password(username, password)
+        self.oauth2_client_assertion_params().into_iter().fold(
+            self.oauth2_client.exchange_password(username, password),
+            |request, (name, value)| request.add_extra_param(name, value),
+        )
     }
 
     ///
@@ -1154,7 +1611,10 @@ where
     where
         'a: 'b,
     {
-        self.oauth2_client.exchange_client_credentials()
+        self.oauth2_client_assertion_params().into_iter().fold(
+            self.oauth2_client.exchange_client_credentials(),
+            |request, (name, value)| request.add_extra_param(name, value),
+        )
     }
 
     ///
@@ -1175,51 +1635,375 @@ where
         &self,
         access_token: AccessToken,
         expected_subject: Option<SubjectIdentifier>,
-    ) -> Result<UserInfoRequest<JE, JS, JT, JU, K>, ConfigurationError> {
+    ) -> Result<UserInfoRequest<'_, JE, JS, JT, JU, K>, ConfigurationError> {
         Ok(UserInfoRequest {
-            url: self
-                .userinfo_endpoint
-                .as_ref()
-                .ok_or(ConfigurationError::MissingUrl("userinfo"))?,
+            url: Cow::Borrowed(
+                self.userinfo_endpoint
+                    .as_ref()
+                    .ok_or(ConfigurationError::MissingUrl("userinfo"))?,
+            ),
             access_token,
             require_signed_response: false,
-            signed_response_verifier: UserInfoVerifier::new(
-                self.client_id.clone(),
-                self.issuer.clone(),
-                self.jwks.clone(),
-                expected_subject,
-            ),
+            signed_response_verifier: {
+                let verifier = UserInfoVerifier::new(
+                    self.client_id.clone(),
+                    self.issuer.clone(),
+                    JsonWebKeySet::default(),
+                    expected_subject,
+                );
+                if let Some(ref jwks_cache) = self.jwks_cache {
+                    verifier.set_jwks_source(jwks_cache.clone())
+                } else {
+                    verifier.set_signature_keys(self.jwks_snapshot())
+                }
+            },
+            dpop_key: None,
+        })
+    }
+
+    ///
+    /// Creates a request for pushing the parameters of an authorization request directly to the
+    /// OpenID Connect Provider ([RFC 9126](https://tools.ietf.org/html/rfc9126)).
+    ///
+    /// Instead of redirecting the user to the URL returned by
+    /// [`AuthorizationRequest::url`], the authorization request parameters are POSTed (along with
+    /// the client's credentials) to the pushed authorization request endpoint. The user is then
+    /// redirected to the URL returned by [`PushedAuthorizationResponse::authorization_url`] (see
+    /// [`pushed_authorization_url()`](Self::pushed_authorization_url())). The returned CSRF state
+    /// and nonce must be retained for validating the authorization response, as with
+    /// [`AuthorizationRequest::url`].
+    ///
+    /// Requires that [`set_pushed_authorization_request_uri()`](
+    /// Self::set_pushed_authorization_request_uri()) have already been called, or that the client
+    /// was initialized from provider metadata containing a
+    /// `pushed_authorization_request_endpoint`.
+    ///
+    pub fn push_authorization_request<'a, RS>(
+        &'a self,
+        authorization_request: AuthorizationRequest<'_, AD, P, RS>,
+    ) -> Result<(PushedAuthorizationRequest<'a, TE>, CsrfToken, Nonce), ConfigurationError>
+    where
+        RS: ResponseType,
+    {
+        let url = self.pushed_authorization_request_url.as_ref().ok_or(
+            ConfigurationError::MissingUrl("pushed authorization request"),
+        )?;
+        let (authorize_url, csrf_state, nonce) = authorization_request.url();
+        let mut params: Vec<(String, String)> = authorize_url.query_pairs().into_owned().collect();
+        // RFC 9126 requires the provider to accept its issuer identifier as the audience of client
+        // assertions sent to the pushed authorization request endpoint.
+        // Signing failures are reported when the request is submitted.
+        let client_assertion_error = match self.client_assertion_params(self.issuer.as_str()) {
+            Ok(client_assertion_params) => {
+                params.extend(
+                    client_assertion_params
+                        .into_iter()
+                        .map(|(name, value)| (name.into_owned(), value.into_owned())),
+                );
+                None
+            }
+            Err(err) => Some(err.to_string()),
+        };
+        Ok((
+            PushedAuthorizationRequest {
+                url,
+                client_id: &self.client_id,
+                client_secret: if self.client_assertion_signer.is_some() {
+                    None
+                } else {
+                    self.client_secret.as_ref()
+                },
+                auth_type: self.oauth2_client.auth_type(),
+                params,
+                client_assertion_error,
+                _phantom: PhantomData,
+            },
+            csrf_state,
+            nonce,
+        ))
+    }
+
+    ///
+    /// Creates a request builder for initiating Client-Initiated Backchannel Authentication
+    /// (CIBA) of the end-user identified by a login hint, login hint token, or ID token hint.
+    ///
+    /// The `openid` scope is included automatically unless disabled via
+    /// [`disable_openid_scope`](Self::disable_openid_scope). Once the provider returns an
+    /// `auth_req_id`, use
+    /// [`exchange_backchannel_authentication()`](Self::exchange_backchannel_authentication()) to
+    /// poll the token endpoint.
+    ///
+    /// Requires that [`set_backchannel_authentication_uri()`](
+    /// Self::set_backchannel_authentication_uri()) have already been called, or that the client
+    /// was initialized from provider metadata containing a `backchannel_authentication_endpoint`.
+    ///
+    pub fn backchannel_authentication_request(
+        &self,
+    ) -> Result<BackchannelAuthenticationRequest<'_, TE>, ConfigurationError> {
+        let url = self
+            .backchannel_authentication_url
+            .as_ref()
+            .ok_or(ConfigurationError::MissingUrl("backchannel authentication"))?;
+        Ok(BackchannelAuthenticationRequest {
+            url,
+            // CIBA requires the provider to accept its issuer identifier as the audience of client
+            // assertions sent to the backchannel authentication endpoint.
+            client: self.endpoint_client(self.client_assertion_params(self.issuer.as_str())),
+            scopes: if self.use_openid_scope {
+                vec![Scope::new(OPENID_SCOPE.to_string())]
+            } else {
+                Vec::new()
+            },
+            params: Vec::new(),
+            _phantom: PhantomData,
         })
     }
 
+    ///
+    /// Creates a request builder that polls the token endpoint for the result of a
+    /// Client-Initiated Backchannel Authentication (CIBA) request using the poll token delivery
+    /// mode.
+    ///
+    pub fn exchange_backchannel_authentication<'a>(
+        &'a self,
+        backchannel_authentication_response: &'a BackchannelAuthenticationResponse,
+    ) -> BackchannelTokenRequest<'a, TR, TT> {
+        BackchannelTokenRequest {
+            token_url: self.oauth2_client.token_url(),
+            client: self.endpoint_client(self.token_client_assertion_params()),
+            response: backchannel_authentication_response,
+            params: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn endpoint_client(
+        &self,
+        client_assertion_params: Result<
+            Vec<(Cow<'static, str>, Cow<'static, str>)>,
+            JsonWebTokenError,
+        >,
+    ) -> ciba::EndpointClient<'_> {
+        ciba::EndpointClient {
+            client_id: &self.client_id,
+            client_secret: if self.client_assertion_signer.is_some() {
+                None
+            } else {
+                self.client_secret.as_ref()
+            },
+            auth_type: self.oauth2_client.auth_type(),
+            auth_params: client_assertion_params
+                .map(|params| {
+                    params
+                        .into_iter()
+                        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+                        .collect()
+                })
+                .map_err(|err| err.to_string()),
+        }
+    }
+
+    ///
+    /// Returns the authorization URL to which the user should be redirected after a successful
+    /// [pushed authorization request](Self::push_authorization_request()).
+    ///
+    /// The URL only contains the `client_id` and `request_uri` parameters.
+    ///
+    pub fn pushed_authorization_url(&self, response: &PushedAuthorizationResponse) -> Url {
+        response.authorization_url(self.oauth2_client.auth_url(), &self.client_id)
+    }
+
+    ///
+    /// Returns an authorization URL that passes the authorization request parameters by value
+    /// inside a signed request object, as described in
+    /// [Section 6.1 of OpenID Connect Core 1.0](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#RequestObject).
+    ///
+    /// All of the parameters of `authorization_request` are included as claims of a JWT signed
+    /// by `signing_key` using `alg`, along with `iss` (the client ID), `aud` (the issuer), `jti`,
+    /// `iat`, and `exp` claims. The returned URL contains this JWT in the `request` parameter,
+    /// while the `response_type`, `client_id`, and `scope` parameters are duplicated as query
+    /// parameters as required by the specification. The returned CSRF state and nonce must be
+    /// retained for validating the authorization response, as with [`AuthorizationRequest::url`].
+    ///
+    /// If the client was initialized from provider metadata, an error is returned if the
+    /// metadata sets `request_parameter_supported` to `false` or doesn't list `alg` in
+    /// `request_object_signing_alg_values_supported`.
+    ///
+    pub fn authorization_url_with_request<RS, S>(
+        &self,
+        authorization_request: AuthorizationRequest<'_, AD, P, RS>,
+        signing_key: &S,
+        alg: &JS,
+    ) -> Result<(Url, CsrfToken, Nonce), RequestObjectError>
+    where
+        RS: ResponseType,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        if self.request_parameter_supported == Some(false) {
+            return Err(RequestObjectError::UnsupportedParameter("request"));
+        }
+
+        let (params, request_object, csrf_state, nonce) =
+            self.sign_request_object(authorization_request, signing_key, alg)?;
+        Ok((
+            request_object::request_object_url(
+                self.oauth2_client.auth_url(),
+                &params,
+                ("request", &request_object),
+            ),
+            csrf_state,
+            nonce,
+        ))
+    }
+
+    ///
+    /// Returns an authorization URL that passes the authorization request parameters by
+    /// reference using the `request_uri` parameter, as described in
+    /// [Section 6.2 of OpenID Connect Core 1.0](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#RequestUriParameter).
+    ///
+    /// This method returns the URL along with a signed request object constructed as described
+    /// in [`authorization_url_with_request`](Self::authorization_url_with_request). The client
+    /// must serve the request object at `request_uri` (typically with the
+    /// `application/oauth-authz-req+jwt` content type) so that the provider can retrieve it. Some
+    /// providers require `request_uri` to be pre-registered (see
+    /// [`ClientMetadata::request_uris`](registration::ClientMetadata::request_uris)).
+    ///
+    /// If the client was initialized from provider metadata, an error is returned if the
+    /// metadata sets `request_uri_parameter_supported` to `false` or doesn't list `alg` in
+    /// `request_object_signing_alg_values_supported`.
+    ///
+    pub fn authorization_url_with_request_uri<RS, S>(
+        &self,
+        authorization_request: AuthorizationRequest<'_, AD, P, RS>,
+        request_uri: &RequestUrl,
+        signing_key: &S,
+        alg: &JS,
+    ) -> Result<(Url, String, CsrfToken, Nonce), RequestObjectError>
+    where
+        RS: ResponseType,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        // Section 3 of OpenID Connect Discovery 1.0 specifies that the default value is true.
+        if self.request_uri_parameter_supported == Some(false) {
+            return Err(RequestObjectError::UnsupportedParameter("request_uri"));
+        }
+
+        let (params, request_object, csrf_state, nonce) =
+            self.sign_request_object(authorization_request, signing_key, alg)?;
+        Ok((
+            request_object::request_object_url(
+                self.oauth2_client.auth_url(),
+                &params,
+                ("request_uri", request_uri.as_str()),
+            ),
+            request_object,
+            csrf_state,
+            nonce,
+        ))
+    }
+
+    // Returns the authorization request's parameters along with a request object containing them.
+    #[allow(clippy::type_complexity)]
+    fn sign_request_object<RS, S>(
+        &self,
+        authorization_request: AuthorizationRequest<'_, AD, P, RS>,
+        signing_key: &S,
+        alg: &JS,
+    ) -> Result<(Vec<(String, String)>, String, CsrfToken, Nonce), RequestObjectError>
+    where
+        RS: ResponseType,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        let (authorize_url, csrf_state, nonce) = authorization_request.url();
+        let params: Vec<(String, String)> = authorize_url.query_pairs().into_owned().collect();
+        let request_object = request_object::sign_request_object(
+            &params,
+            &self.client_id,
+            &self.issuer,
+            signing_key,
+            alg,
+            self.request_object_signing_algs.as_ref(),
+        )?;
+        Ok((params, request_object, csrf_state, nonce))
+    }
+
     ///
     /// Creates a request builder for obtaining metadata about a previously received token.
     ///
+    /// Requires that [`set_introspection_uri()`](Self::set_introspection_uri()) have already been
+    /// called to set the introspection endpoint URL, or that the client was initialized from
+    /// provider metadata containing an `introspection_endpoint`. The token type hint may be set
+    /// using [`IntrospectionRequest::set_token_type_hint`].
+    ///
     /// See <https://tools.ietf.org/html/rfc7662>
     ///
     pub fn introspect<'a>(
         &'a self,
         token: &'a AccessToken,
     ) -> Result<IntrospectionRequest<'a, TE, TIR, TT>, ConfigurationError> {
-        self.oauth2_client.introspect(token)
+        Ok(self.oauth2_client_assertion_params().into_iter().fold(
+            self.oauth2_client.introspect(token)?,
+            |request, (name, value)| request.add_extra_param(name, value),
+        ))
     }
 
     ///
     /// Creates a request builder for revoking a previously received token.
     ///
     /// Requires that [`set_revocation_uri()`](Self::set_revocation_uri()) have already been called to set the
-    /// revocation endpoint URL.
+    /// revocation endpoint URL, or that the client was initialized from provider metadata
+    /// containing a `revocation_endpoint`.
     ///
     /// Attempting to submit the generated request without calling [`set_revocation_uri()`](Self::set_revocation_uri())
-    /// first will result in an error.
+    /// first will result in an error. The token type hint is derived from the variant of the
+    /// revocable token (e.g., [`CoreRevocableToken`](core::CoreRevocableToken)). A `200 OK` response
+    /// is treated as success regardless of its (typically empty) body, and an
+    /// `unsupported_token_type` error is reported as
+    /// [`RevocationErrorResponseType::UnsupportedTokenType`].
     ///
     /// See <https://tools.ietf.org/html/rfc7009>
     ///
     pub fn revoke_token(
         &self,
         token: RT,
-    ) -> Result<RevocationRequest<RT, TRE>, ConfigurationError> {
-        self.oauth2_client.revoke_token(token)
+    ) -> Result<RevocationRequest<'_, RT, TRE>, ConfigurationError> {
+        Ok(self.oauth2_client_assertion_params().into_iter().fold(
+            self.oauth2_client.revoke_token(token)?,
+            |request, (name, value)| request.add_extra_param(name, value),
+        ))
+    }
+
+    // Returns the `private_key_jwt` client authentication parameters (if configured) for requests
+    // built using the oauth2 crate, which provides no way to report request construction errors.
+    // If the client assertion can't be signed, it's omitted, and the provider rejects the request
+    // with an `invalid_client` error since no other client credentials are sent.
+    fn oauth2_client_assertion_params(&self) -> Vec<(Cow<'static, str>, Cow<'static, str>)> {
+        self.token_client_assertion_params().unwrap_or_default()
+    }
+
+    // Returns the `private_key_jwt` client authentication parameters (if configured) for requests
+    // whose assertion audience is the token endpoint.
+    fn token_client_assertion_params(
+        &self,
+    ) -> Result<Vec<(Cow<'static, str>, Cow<'static, str>)>, JsonWebTokenError> {
+        let audience = self
+            .oauth2_client
+            .token_url()
+            .map(|token_url| token_url.as_str())
+            .unwrap_or_else(|| self.issuer.as_str());
+        self.client_assertion_params(audience)
+    }
+
+    fn client_assertion_params(
+        &self,
+        audience: &str,
+    ) -> Result<Vec<(Cow<'static, str>, Cow<'static, str>)>, JsonWebTokenError> {
+        self.client_assertion_signer
+            .as_ref()
+            .map(|signer| signer.params(&self.client_id, audience))
+            .unwrap_or_else(|| Ok(Vec::new()))
     }
 }
 
@@ -1235,6 +2019,7 @@ where
     inner: oauth2::AuthorizationRequest<'a>,
     acr_values: Vec<AuthenticationContextClass>,
     authentication_flow: AuthenticationFlow<RT>,
+    claims: Option<ClaimsRequest>,
     claims_locales: Vec<LanguageTag>,
     display: Option<AD>,
     id_token_hint: Option<String>,
@@ -1242,6 +2027,10 @@ where
     max_age: Option<Duration>,
     nonce: Nonce,
     prompts: Vec<P>,
+    resources: Vec<ResourceIndicator>,
+    response_mode: Option<String>,
+    response_modes_supported: Option<&'a Vec<String>>,
+    scopes: Vec<Scope>,
     ui_locales: Vec<LanguageTag>,
 }
 impl<'a, AD, P, RT> AuthorizationRequest<'a, AD, P, RT>
@@ -1253,20 +2042,28 @@ where
     ///
     /// Appends a new scope to the authorization URL.
     ///
+    /// Scopes that have already been added (including the `openid` scope added by
+    /// [`Client::authorize_url`]) are ignored.
+    ///
     pub fn add_scope(mut self, scope: Scope) -> Self {
-        self.inner = self.inner.add_scope(scope);
+        if !self.scopes.contains(&scope) {
+            self.scopes.push(scope);
+        }
         self
     }
 
     ///
     /// Appends a collection of scopes to the authorization URL.
     ///
-    pub fn add_scopes<I>(mut self, scopes: I) -> Self
+    /// See [`Scopes`] for a helper that includes the standard OpenID Connect scopes.
+    ///
+    pub fn add_scopes<I>(self, scopes: I) -> Self
     where
         I: IntoIterator<Item = Scope>,
     {
-        self.inner = self.inner.add_scopes(scopes);
-        self
+        scopes
+            .into_iter()
+            .fold(self, |request, scope| request.add_scope(scope))
     }
 
     ///
@@ -1318,6 +2115,18 @@ where
         self
     }
 
+    ///
+    /// Replaces the requested Authentication Context Class Reference values.
+    ///
+    /// ACR values should be provided in order of preference. The same values may be passed to
+    /// [`IdTokenVerifier::require_acr`] to ensure that the provider satisfied one of them. An
+    /// empty list omits the `acr_values` parameter entirely.
+    ///
+    pub fn set_acr_values(mut self, acr_values: Vec<AuthenticationContextClass>) -> Self {
+        self.acr_values = acr_values;
+        self
+    }
+
     ///
     /// Requests the preferred languages for claims returned by the OpenID Connect Provider.
     ///
@@ -1328,12 +2137,36 @@ where
         self
     }
 
-    // TODO: support 'claims' parameter
-    // https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter
-
     ///
-    /// Specifies how the OpenID Connect Provider displays the authentication and consent user
-    /// interfaces to the end user.
+    /// Requests a collection of preferred languages for claims returned by the OpenID Connect
+    /// Provider.
+    ///
+    /// Languages should be provided in order of preference.
+    ///
+    pub fn add_claims_locales<I>(mut self, claims_locales: I) -> Self
+    where
+        I: IntoIterator<Item = LanguageTag>,
+    {
+        self.claims_locales.extend(claims_locales);
+        self
+    }
+
+    ///
+    /// Requests individual claims to be returned in the ID token and/or from the UserInfo
+    /// endpoint via the
+    /// [`claims` parameter](https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter).
+    ///
+    /// Providers advertise support for this parameter via the `claims_parameter_supported`
+    /// provider metadata value.
+    ///
+    pub fn set_claims(mut self, claims: ClaimsRequest) -> Self {
+        self.claims = Some(claims);
+        self
+    }
+
+    ///
+    /// Specifies how the OpenID Connect Provider displays the authentication and consent user
+    /// interfaces to the end user.
     ///
     pub fn set_display(mut self, display: AD) -> Self {
         self.display = Some(display);
@@ -1377,19 +2210,95 @@ where
     /// Sets a maximum amount of time since the user has last authenticated with the OpenID
     /// Connect Provider.
     ///
-    /// If more time has elapsed, the provider forces the user to re-authenticate.
+    /// If more time has elapsed, the provider forces the user to re-authenticate. The value is
+    /// sent as a whole number of seconds, with any fractional second rounded down.
+    ///
+    /// The same value should be passed to [`IdTokenVerifier::require_auth_time`] when verifying
+    /// the resulting ID token, since the provider must then return the `auth_time` claim.
     ///
     pub fn set_max_age(mut self, max_age: Duration) -> Self {
         self.max_age = Some(max_age);
         self
     }
 
+    ///
+    /// Returns the maximum authentication age set via [`AuthorizationRequest::set_max_age`], if
+    /// any.
+    ///
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
     ///
     /// Specifies what level of authentication and consent prompts the OpenID Connect Provider
     /// should present to the user.
     ///
+    /// The `prompt` parameter is a set, so adding a prompt that was already requested has no
+    /// effect. Note that [`core::CoreAuthPrompt::None`] must not be combined with any other
+    /// prompt, which [`AuthorizationRequest::try_url`] enforces.
+    ///
     pub fn add_prompt(mut self, prompt: P) -> Self {
-        self.prompts.push(prompt);
+        if !self
+            .prompts
+            .iter()
+            .any(|existing| existing.as_ref() == prompt.as_ref())
+        {
+            self.prompts.push(prompt);
+        }
+        self
+    }
+
+    ///
+    /// Specifies the mechanism the OpenID Connect Provider should use for returning the
+    /// authorization response parameters (e.g., [`core::CoreResponseMode::FormPost`] or one of
+    /// the JWT Secured Authorization Response Mode variants such as
+    /// [`core::CoreResponseMode::Jwt`]).
+    ///
+    /// If the client was initialized from provider metadata that includes
+    /// `response_modes_supported`, an error is returned if `response_mode` is not listed there.
+    ///
+    pub fn set_response_mode<RM>(
+        mut self,
+        response_mode: &RM,
+    ) -> Result<Self, AuthorizationRequestError>
+    where
+        RM: ResponseMode,
+    {
+        let response_mode = response_mode_to_string(response_mode);
+        if let Some(response_modes_supported) = self.response_modes_supported {
+            if !response_modes_supported.contains(&response_mode) {
+                return Err(AuthorizationRequestError::UnsupportedResponseMode(
+                    response_mode,
+                ));
+            }
+        }
+        self.response_mode = Some(response_mode);
+        Ok(self)
+    }
+
+    ///
+    /// Requests an access token intended for use at the given protected resource via the
+    /// [RFC 8707](https://tools.ietf.org/html/rfc8707) `resource` parameter.
+    ///
+    /// This method may be called multiple times to request access to several resources. The same
+    /// resources should typically also be requested from the token endpoint using
+    /// [`ResourceIndicatorRequest::add_resource`].
+    ///
+    pub fn add_resource(mut self, resource: ResourceIndicator) -> Self {
+        self.resources.push(resource);
+        self
+    }
+
+    ///
+    /// Requests an access token intended for use at each of the given protected resources.
+    ///
+    /// See [`add_resource`](Self::add_resource).
+    ///
+    pub fn add_resources<I>(mut self, resources: I) -> Self
+    where
+        I: IntoIterator<Item = ResourceIndicator>,
+    {
+        self.resources.extend(resources);
         self
     }
 
@@ -1404,6 +2313,20 @@ where
         self
     }
 
+    ///
+    /// Requests a collection of preferred languages for the user interface presented by the
+    /// OpenID Connect Provider.
+    ///
+    /// Languages should be provided in order of preference.
+    ///
+    pub fn add_ui_locales<I>(mut self, ui_locales: I) -> Self
+    where
+        I: IntoIterator<Item = LanguageTag>,
+    {
+        self.ui_locales.extend(ui_locales);
+        self
+    }
+
     ///
     /// Overrides the `redirect_url` to the one specified.
     ///
@@ -1412,17 +2335,39 @@ where
         self
     }
 
+    ///
+    /// Returns the full authorization URL and CSRF state for this authorization
+    /// request, after validating the combination of requested parameters.
+    ///
+    /// Unlike [`AuthorizationRequest::url`], this method returns an error if
+    /// [`core::CoreAuthPrompt::None`] is combined with any other prompt, as required by
+    /// [Section 3.1.2.1](https://openid.net/specs/openid-connect-core-1_0.html#AuthRequest) of
+    /// OpenID Connect Core 1.0.
+    ///
+    pub fn try_url(self) -> Result<(Url, CsrfToken, Nonce), AuthorizationRequestError> {
+        if self.prompts.len() > 1 && self.prompts.iter().any(|prompt| prompt.as_ref() == "none") {
+            return Err(AuthorizationRequestError::InvalidPrompt(join_vec(
+                &self.prompts,
+            )));
+        }
+        Ok(self.url())
+    }
+
     ///
     /// Returns the full authorization URL and CSRF state for this authorization
     /// request.
     ///
+    /// This method does not validate the combination of requested parameters. Use
+    /// [`AuthorizationRequest::try_url`] to reject invalid combinations before redirecting the
+    /// user.
+    ///
     pub fn url(self) -> (Url, CsrfToken, Nonce) {
         let response_type = match self.authentication_flow {
             AuthenticationFlow::AuthorizationCode => core::CoreResponseType::Code.to_oauth2(),
             AuthenticationFlow::Implicit(include_token) => {
                 if include_token {
                     OAuth2ResponseType::new(
-                        vec![
+                        [
                             core::CoreResponseType::IdToken,
                             core::CoreResponseType::Token,
                         ]
@@ -1445,6 +2390,7 @@ where
         };
         let (mut inner, nonce) = (
             self.inner
+                .add_scopes(self.scopes)
                 .set_response_type(&response_type)
                 .add_extra_param("nonce", self.nonce.secret().clone()),
             self.nonce,
@@ -1452,6 +2398,12 @@ where
         if !self.acr_values.is_empty() {
             inner = inner.add_extra_param("acr_values", join_vec(&self.acr_values));
         }
+        if let Some(ref claims) = self.claims {
+            inner = inner.add_extra_param(
+                "claims",
+                serde_json::to_string(claims).expect("failed to serialize claims request"),
+            );
+        }
         if !self.claims_locales.is_empty() {
             inner = inner.add_extra_param("claims_locales", join_vec(&self.claims_locales));
         }
@@ -1470,6 +2422,12 @@ where
         if !self.prompts.is_empty() {
             inner = inner.add_extra_param("prompt", join_vec(&self.prompts));
         }
+        for resource in self.resources {
+            inner = inner.add_extra_param("resource", String::from(resource));
+        }
+        if let Some(response_mode) = self.response_mode {
+            inner = inner.add_extra_param("response_mode", response_mode);
+        }
         if !self.ui_locales.is_empty() {
             inner = inner.add_extra_param("ui_locales", join_vec(&self.ui_locales));
         }
@@ -1479,6 +2437,25 @@ where
     }
 }
 
+///
+/// Error building an [`AuthorizationRequest`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuthorizationRequestError {
+    ///
+    /// The `none` prompt was combined with other prompts.
+    ///
+    #[error("Prompt `none` must not be combined with other prompts: `{0}`")]
+    InvalidPrompt(String),
+    ///
+    /// The requested response mode is not listed in the provider's `response_modes_supported`
+    /// metadata.
+    ///
+    #[error("Unsupported response mode: `{0}`")]
+    UnsupportedResponseMode(String),
+}
+
 ///
 /// Extends the base OAuth2 token response with an ID token.
 ///
@@ -1498,6 +2475,19 @@ where
     /// to allow for interoperability with authorization servers that only support OAuth2.
     ///
     fn id_token(&self) -> Option<&IdToken<AC, GC, JE, JS, JT>>;
+
+    ///
+    /// Returns the scopes granted by the authorization server, parsed from the space-delimited
+    /// `scope` field of the token response.
+    ///
+    /// The authorization server may grant fewer scopes than were requested. If the `scope` field
+    /// is omitted, this method returns `None`, in which case the granted scopes are identical to
+    /// the requested scopes, as specified in
+    /// [Section 5.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-5.1).
+    ///
+    fn granted_scopes(&self) -> Option<&[Scope]> {
+        self.scopes().map(Vec::as_slice)
+    }
 }
 
 impl<AC, EF, GC, JE, JS, JT, TT> TokenResponse<AC, GC, JE, JS, JT, TT>
@@ -1527,22 +2517,56 @@ where
         .join(" ")
 }
 
+fn response_mode_to_string<RM>(response_mode: &RM) -> String
+where
+    RM: ResponseMode,
+{
+    serde_plain::to_string(response_mode).unwrap_or_else(|err| {
+        panic!(
+            "response mode {:?} failed to serialize to a string: {}",
+            response_mode, err
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::borrow::Cow;
+    use std::cell::RefCell;
     use std::time::Duration;
 
-    use oauth2::{AuthUrl, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope, TokenUrl};
+    use chrono::Utc;
+
+    use oauth2::{
+        AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, DeviceAuthorizationUrl,
+        RedirectUrl, Scope, TokenUrl,
+    };
 
     use crate::core::CoreAuthenticationFlow;
-    use crate::core::{CoreAuthDisplay, CoreAuthPrompt, CoreClient, CoreIdToken, CoreResponseType};
+    use crate::core::{
+        CoreAuthDisplay, CoreAuthPrompt, CoreClient, CoreDeviceAuthorizationResponse,
+        CoreErrorResponseType, CoreGenderClaim, CoreIdToken, CoreIdTokenClaims, CoreJsonWebKey,
+        CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreJwsSigningAlgorithm, CoreProviderMetadata,
+        CoreResponseMode, CoreResponseType, CoreRevocableToken, CoreRsaPrivateSigningKey,
+        CoreSubjectIdentifierType,
+    };
+    use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
     use crate::IssuerUrl;
     use crate::{
-        AuthenticationContextClass, AuthenticationFlow, JsonWebKeySet, LanguageTag, LoginHint,
-        Nonce,
+        AccessToken, Audience, AuthenticationContextClass, AuthenticationFlow,
+        AuthorizationRequestError, BackchannelAuthenticationUrl, ClaimRequestValue, ClaimsRequest,
+        ConfigurationError, EmptyAdditionalClaims, EmptyAdditionalProviderMetadata, HttpRequest,
+        HttpResponse, IntrospectionUrl, JsonWebKeySet, JsonWebKeySetUrl, JsonWebTokenError,
+        LanguageTag, LoginHint, Nonce, OAuth2TokenResponse, PushedAuthorizationRequestUrl,
+        RefreshToken, RequestTokenError, ResponseTypes, RevocationErrorResponseType, RevocationUrl,
+        StandardClaims, SubjectIdentifier, TokenIntrospectionResponse, TokenResponse, UserInfoUrl,
+    };
+    use crate::{
+        ClaimsVerificationError, DiscoveryError, JsonWebKey, JsonWebKeyId, PrivateSigningKey,
+        SignatureVerificationError, SigningError,
     };
 
-    fn new_client() -> CoreClient {
+    pub(crate) fn new_client() -> CoreClient {
         color_backtrace::install();
         CoreClient::new(
             ClientId::new("aaa".to_string()),
@@ -1765,4 +2789,1128 @@ mod tests {
             authorize_url.to_string()
         );
     }
+
+    #[test]
+    fn test_authorize_url_claims() {
+        let client = new_client();
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .set_claims(ClaimsRequest::new().add_id_token_claim(
+                "email",
+                Some(ClaimRequestValue::new().set_essential(Some(true))),
+            ))
+            .url();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+             scope=openid&nonce=NONCE456&\
+             claims=%7B%22id_token%22%3A%7B%22email%22%3A%7B%22essential%22%3Atrue%7D%7D%7D",
+            authorize_url.to_string()
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_prompts() {
+        let client = new_client();
+        let new_request = || {
+            client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+        };
+
+        let (authorize_url, _, _) = new_request()
+            .add_prompt(CoreAuthPrompt::SelectAccount)
+            .add_prompt(CoreAuthPrompt::Consent)
+            .add_prompt(CoreAuthPrompt::SelectAccount)
+            .set_login_hint(LoginHint::new("user+tag@example.com".to_string()))
+            .try_url()
+            .unwrap();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+             scope=openid&nonce=NONCE456&login_hint=user%2Btag%40example.com&\
+             prompt=select_account+consent",
+            authorize_url.to_string()
+        );
+
+        let (authorize_url, _, _) = new_request()
+            .add_prompt(CoreAuthPrompt::None)
+            .try_url()
+            .unwrap();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+             scope=openid&nonce=NONCE456&prompt=none",
+            authorize_url.to_string()
+        );
+
+        assert_eq!(
+            new_request()
+                .add_prompt(CoreAuthPrompt::None)
+                .add_prompt(CoreAuthPrompt::Login)
+                .try_url()
+                .unwrap_err(),
+            AuthorizationRequestError::InvalidPrompt("none login".to_string())
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_max_age() {
+        let client = new_client();
+        let new_request = || {
+            client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+        };
+
+        let request = new_request().set_max_age(Duration::from_secs(5 * 60));
+        let max_age = request.max_age().unwrap();
+        let (authorize_url, _, _) = request.url();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+             scope=openid&nonce=NONCE456&max_age=300",
+            authorize_url.to_string()
+        );
+        // The same value can be reused to require a recent auth_time claim.
+        let _verifier = client.id_token_verifier().require_auth_time(max_age);
+
+        let (authorize_url, _, _) = new_request()
+            .set_max_age(Duration::from_millis(300_999))
+            .url();
+        assert!(authorize_url.to_string().ends_with("&max_age=300"));
+
+        let (authorize_url, _, _) = new_request().set_max_age(Duration::from_millis(999)).url();
+        assert!(authorize_url.to_string().ends_with("&max_age=0"));
+    }
+
+    #[test]
+    fn test_authorize_url_acr_values() {
+        let client = new_client();
+        let new_request = || {
+            client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+        };
+
+        let (authorize_url, _, _) = new_request()
+            .add_auth_context_value(AuthenticationContextClass::new("replaced".to_string()))
+            .set_acr_values(vec![
+                AuthenticationContextClass::new("urn:mace:incommon:iap:silver".to_string()),
+                AuthenticationContextClass::new("urn:mace:incommon:iap:bronze".to_string()),
+            ])
+            .url();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+             scope=openid&nonce=NONCE456&\
+             acr_values=urn%3Amace%3Aincommon%3Aiap%3Asilver+urn%3Amace%3Aincommon%3Aiap%3Abronze",
+            authorize_url.to_string()
+        );
+
+        let (authorize_url, _, _) = new_request().set_acr_values(vec![]).url();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+             scope=openid&nonce=NONCE456",
+            authorize_url.to_string()
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_response_mode() {
+        let client = new_client();
+        for (response_mode, expected) in [
+            (CoreResponseMode::Query, "query"),
+            (CoreResponseMode::Fragment, "fragment"),
+            (CoreResponseMode::FormPost, "form_post"),
+            (CoreResponseMode::Jwt, "jwt"),
+            (CoreResponseMode::QueryJwt, "query.jwt"),
+            (CoreResponseMode::FragmentJwt, "fragment.jwt"),
+            (CoreResponseMode::FormPostJwt, "form_post.jwt"),
+        ] {
+            assert_eq!(
+                serde_json::to_string(&response_mode).unwrap(),
+                format!("\"{}\"", expected)
+            );
+            assert_eq!(
+                serde_json::from_str::<CoreResponseMode>(&format!("\"{}\"", expected)).unwrap(),
+                response_mode
+            );
+
+            let (authorize_url, _, _) = client
+                .authorize_url(
+                    CoreAuthenticationFlow::AuthorizationCode,
+                    || CsrfToken::new("CSRF123".to_string()),
+                    || Nonce::new("NONCE456".to_string()),
+                )
+                .set_response_mode(&response_mode)
+                .unwrap()
+                .url();
+            assert_eq!(
+                format!(
+                    "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+                     scope=openid&nonce=NONCE456&response_mode={}",
+                    expected
+                ),
+                authorize_url.to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_authorize_url_unsupported_response_mode() {
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+        .set_response_modes_supported(Some(vec![
+            CoreResponseMode::Query,
+            CoreResponseMode::FormPost,
+        ]));
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata,
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        let new_request = || {
+            client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+        };
+
+        let (authorize_url, _, _) = new_request()
+            .set_response_mode(&CoreResponseMode::FormPost)
+            .unwrap()
+            .url();
+        assert!(authorize_url
+            .to_string()
+            .ends_with("&response_mode=form_post"));
+
+        assert_eq!(
+            new_request()
+                .set_response_mode(&CoreResponseMode::Jwt)
+                .err()
+                .unwrap(),
+            AuthorizationRequestError::UnsupportedResponseMode("jwt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_locales() {
+        let client = new_client();
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_claims_locale(LanguageTag::new("de".to_string()))
+            .add_claims_locales(vec![
+                LanguageTag::new("en-GB".to_string()),
+                LanguageTag::new("en".to_string()),
+            ])
+            .add_ui_locales(vec![
+                LanguageTag::new("sr-Latn".to_string()),
+                LanguageTag::new("sr".to_string()),
+            ])
+            .add_ui_locale(LanguageTag::new("en".to_string()))
+            .url();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+             scope=openid&nonce=NONCE456&claims_locales=de+en-GB+en&ui_locales=sr-Latn+sr+en",
+            authorize_url.to_string()
+        );
+    }
+
+    pub(crate) fn mock_json_response(
+        status_code: http::StatusCode,
+        body: &str,
+    ) -> Result<HttpResponse, std::io::Error> {
+        Ok(HttpResponse {
+            status_code,
+            headers: vec![(
+                http::header::CONTENT_TYPE,
+                http::HeaderValue::from_static("application/json"),
+            )]
+            .into_iter()
+            .collect(),
+            body: body.as_bytes().to_vec(),
+        })
+    }
+
+    #[test]
+    fn test_introspection_endpoint_from_provider_metadata() {
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        );
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata.clone(),
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        let token = AccessToken::new("token".to_string());
+        assert!(matches!(
+            client.introspect(&token),
+            Err(ConfigurationError::MissingUrl("introspection"))
+        ));
+
+        let provider_metadata = provider_metadata.set_introspection_endpoint(Some(
+            IntrospectionUrl::new("https://example/introspect".to_string()).unwrap(),
+        ));
+        let serialized = serde_json::to_value(&provider_metadata).unwrap();
+        assert_eq!(
+            serialized["introspection_endpoint"],
+            "https://example/introspect"
+        );
+        assert_eq!(
+            serde_json::from_value::<CoreProviderMetadata>(serialized).unwrap(),
+            provider_metadata
+        );
+
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata,
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        client
+            .introspect(&token)
+            .expect("introspection endpoint should be configured");
+    }
+
+    #[test]
+    fn test_introspection() {
+        let client = new_client().set_introspection_uri(
+            IntrospectionUrl::new("https://example/introspect".to_string()).unwrap(),
+        );
+        let token = AccessToken::new("access_token_123".to_string());
+
+        let response = client
+            .introspect(&token)
+            .unwrap()
+            .set_token_type_hint("access_token")
+            .request(|request| {
+                assert_eq!(request.url.as_str(), "https://example/introspect");
+                assert_eq!(request.method, http::Method::POST);
+                assert_eq!(
+                    String::from_utf8(request.body).unwrap(),
+                    "token=access_token_123&token_type_hint=access_token"
+                );
+                mock_json_response(
+                    http::StatusCode::OK,
+                    "{\
+                        \"active\": true,\
+                        \"scope\": \"openid email\",\
+                        \"client_id\": \"aaa\",\
+                        \"sub\": \"subject\",\
+                        \"exp\": 1700000000\
+                    }",
+                )
+            })
+            .unwrap();
+        assert!(response.active());
+        assert_eq!(
+            response.scopes(),
+            Some(&vec![
+                Scope::new("openid".to_string()),
+                Scope::new("email".to_string())
+            ])
+        );
+        assert_eq!(
+            response.client_id(),
+            Some(&ClientId::new("aaa".to_string()))
+        );
+        assert_eq!(response.sub(), Some("subject"));
+        assert_eq!(response.exp().unwrap().timestamp(), 1700000000);
+
+        let response = client
+            .introspect(&token)
+            .unwrap()
+            .request(|_| mock_json_response(http::StatusCode::OK, "{\"active\": false}"))
+            .unwrap();
+        assert!(!response.active());
+        assert_eq!(response.scopes(), None);
+        assert_eq!(response.sub(), None);
+
+        match client.introspect(&token).unwrap().request(|_| {
+            mock_json_response(
+                http::StatusCode::UNAUTHORIZED,
+                "{\"error\": \"invalid_client\", \"error_description\": \"bad secret\"}",
+            )
+        }) {
+            Err(RequestTokenError::ServerResponse(err)) => {
+                assert_eq!(*err.error(), CoreErrorResponseType::InvalidClient);
+                assert_eq!(err.error_description(), Some(&"bad secret".to_string()));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_revocation() {
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+        .set_revocation_endpoint(Some(
+            RevocationUrl::new("https://example/revoke".to_string()).unwrap(),
+        ));
+        assert_eq!(
+            serde_json::to_value(&provider_metadata).unwrap()["revocation_endpoint"],
+            "https://example/revoke"
+        );
+        assert!(matches!(
+            new_client().revoke_token(AccessToken::new("token".to_string()).into()),
+            Err(ConfigurationError::MissingUrl("revocation"))
+        ));
+
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata,
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+        );
+
+        client
+            .revoke_token(CoreRevocableToken::RefreshToken(RefreshToken::new(
+                "refresh token/123".to_string(),
+            )))
+            .unwrap()
+            .request(|request| {
+                assert_eq!(request.url.as_str(), "https://example/revoke");
+                assert_eq!(request.method, http::Method::POST);
+                assert_eq!(
+                    request.headers.get(http::header::CONTENT_TYPE).unwrap(),
+                    "application/x-www-form-urlencoded"
+                );
+                assert_eq!(
+                    request.headers.get(http::header::AUTHORIZATION).unwrap(),
+                    "Basic YWFhOmJiYg=="
+                );
+                assert_eq!(
+                    String::from_utf8(request.body).unwrap(),
+                    "token=refresh+token%2F123&token_type_hint=refresh_token"
+                );
+                Ok::<_, std::io::Error>(HttpResponse {
+                    status_code: http::StatusCode::OK,
+                    headers: http::HeaderMap::new(),
+                    body: vec![],
+                })
+            })
+            .expect("revocation should succeed");
+
+        match client
+            .revoke_token(AccessToken::new("access_token_123".to_string()).into())
+            .unwrap()
+            .request(|request| {
+                assert_eq!(
+                    String::from_utf8(request.body).unwrap(),
+                    "token=access_token_123&token_type_hint=access_token"
+                );
+                mock_json_response(
+                    http::StatusCode::BAD_REQUEST,
+                    "{\"error\": \"unsupported_token_type\"}",
+                )
+            }) {
+            Err(RequestTokenError::ServerResponse(err)) => {
+                assert_eq!(
+                    *err.error(),
+                    RevocationErrorResponseType::UnsupportedTokenType
+                );
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_device_authorization_flow() {
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+        .set_token_endpoint(Some(
+            TokenUrl::new("https://example/token".to_string()).unwrap(),
+        ))
+        .set_device_authorization_endpoint(Some(
+            DeviceAuthorizationUrl::new("https://example/device".to_string()).unwrap(),
+        ))
+        .set_jwks(JsonWebKeySet::new(vec![serde_json::from_str::<
+            CoreJsonWebKey,
+        >(TEST_RSA_PUB_KEY)
+        .unwrap()]));
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata,
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+
+        let details: CoreDeviceAuthorizationResponse = client
+            .exchange_device_code()
+            .unwrap()
+            .request(|request| {
+                assert_eq!(request.url.as_str(), "https://example/device");
+                assert_eq!(
+                    String::from_utf8(request.body).unwrap(),
+                    "scope=openid&client_id=aaa"
+                );
+                mock_json_response(
+                    http::StatusCode::OK,
+                    "{\
+                        \"device_code\": \"GmRhmhcxhwAzkoEqiMEg_DnyEysNkuNhszIySk9eS\",\
+                        \"user_code\": \"WDJB-MJHT\",\
+                        \"verification_uri\": \"https://example/device\",\
+                        \"verification_uri_complete\": \"https://example/device?user_code=WDJB-MJHT\",\
+                        \"expires_in\": 1800,\
+                        \"interval\": 5\
+                    }",
+                )
+            })
+            .unwrap();
+        assert_eq!(
+            details.device_code().secret(),
+            "GmRhmhcxhwAzkoEqiMEg_DnyEysNkuNhszIySk9eS"
+        );
+        assert_eq!(details.user_code().secret(), "WDJB-MJHT");
+        assert_eq!(
+            details.verification_uri().as_str(),
+            "https://example/device"
+        );
+        assert_eq!(
+            details
+                .verification_uri_complete()
+                .map(|uri| uri.secret().as_str()),
+            Some("https://example/device?user_code=WDJB-MJHT")
+        );
+        assert_eq!(details.expires_in(), Duration::from_secs(1800));
+        assert_eq!(details.interval(), Duration::from_secs(5));
+
+        let id_token = CoreIdToken::new(
+            CoreIdTokenClaims::new(
+                IssuerUrl::new("https://example".to_string()).unwrap(),
+                vec![Audience::new("aaa".to_string())],
+                Utc::now() + chrono::Duration::hours(1),
+                Utc::now(),
+                StandardClaims::new(SubjectIdentifier::new("subject".to_string())),
+                Default::default(),
+            ),
+            &CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap(),
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            None,
+            None,
+        )
+        .unwrap();
+        let token_response = format!(
+            "{{\"access_token\": \"access_token_123\", \"token_type\": \"bearer\", \
+             \"id_token\": \"{}\"}}",
+            id_token.to_string()
+        );
+
+        let poll_responses = RefCell::new(vec![
+            (http::StatusCode::OK, token_response.as_str()),
+            (http::StatusCode::BAD_REQUEST, "{\"error\": \"slow_down\"}"),
+            (
+                http::StatusCode::BAD_REQUEST,
+                "{\"error\": \"authorization_pending\"}",
+            ),
+        ]);
+        let sleeps = RefCell::new(Vec::new());
+        let response = client
+            .exchange_device_access_token(&details)
+            .request(
+                |request| {
+                    assert_eq!(request.url.as_str(), "https://example/token");
+                    assert_eq!(
+                        String::from_utf8(request.body).unwrap(),
+                        "grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Adevice_code\
+                         &device_code=GmRhmhcxhwAzkoEqiMEg_DnyEysNkuNhszIySk9eS&client_id=aaa"
+                    );
+                    let (status_code, body) = poll_responses.borrow_mut().pop().unwrap();
+                    mock_json_response(status_code, body)
+                },
+                |interval| sleeps.borrow_mut().push(interval),
+                None,
+            )
+            .unwrap();
+        assert!(poll_responses.borrow().is_empty());
+        // The poll interval is increased by 5 seconds after a `slow_down` error.
+        assert_eq!(
+            *sleeps.borrow(),
+            vec![Duration::from_secs(5), Duration::from_secs(10)]
+        );
+        assert_eq!(response.access_token().secret(), "access_token_123");
+
+        let claims = response
+            .id_token()
+            .expect("missing ID token")
+            .claims(&client.id_token_verifier(), |nonce: Option<&Nonce>| {
+                nonce.map_or(Ok(()), |_| Err("unexpected nonce".to_string()))
+            })
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "subject");
+    }
+
+    // Checks that the given form-encoded request body authenticates the client "aaa" using a JWT
+    // assertion signed with the given key and algorithm, and returns the assertion's claims.
+    fn check_client_assertion(
+        body: &[u8],
+        verification_key: &CoreJsonWebKey,
+        alg: CoreJwsSigningAlgorithm,
+        expected_audience: &str,
+    ) -> serde_json::Value {
+        let params = url::form_urlencoded::parse(body)
+            .into_owned()
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(params.get("client_id").map(String::as_str), Some("aaa"));
+        assert!(!params.contains_key("client_secret"));
+        assert_eq!(
+            params.get("client_assertion_type").map(String::as_str),
+            Some("urn:ietf:params:oauth:client-assertion-type:jwt-bearer")
+        );
+
+        let assertion = params.get("client_assertion").expect("missing assertion");
+        let parts = assertion.split('.').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 3);
+        let header: serde_json::Value = serde_json::from_slice(
+            &base64::decode_config(parts[0], base64::URL_SAFE_NO_PAD).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(header["alg"], serde_plain::to_string(&alg).unwrap());
+        verification_key
+            .verify_signature(
+                &alg,
+                format!("{}.{}", parts[0], parts[1]).as_bytes(),
+                &base64::decode_config(parts[2], base64::URL_SAFE_NO_PAD).unwrap(),
+            )
+            .expect("invalid assertion signature");
+
+        let claims: serde_json::Value = serde_json::from_slice(
+            &base64::decode_config(parts[1], base64::URL_SAFE_NO_PAD).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(claims["iss"], "aaa");
+        assert_eq!(claims["sub"], "aaa");
+        assert_eq!(claims["aud"], expected_audience);
+        assert!(!claims["jti"].as_str().unwrap().is_empty());
+        let now = Utc::now().timestamp();
+        let iat = claims["iat"].as_i64().unwrap();
+        let exp = claims["exp"].as_i64().unwrap();
+        assert!(iat <= now && now < exp && exp <= iat + 60);
+        claims
+    }
+
+    #[test]
+    fn test_private_key_jwt_auth() {
+        let signing_key = || CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        assert!(matches!(
+            new_client()
+                .set_private_key_jwt_auth(signing_key(), CoreJwsSigningAlgorithm::EcdsaP256Sha256),
+            Err(JsonWebTokenError::SigningError(_))
+        ));
+
+        let client = new_client()
+            .set_introspection_uri(
+                IntrospectionUrl::new("https://example/introspect".to_string()).unwrap(),
+            )
+            .set_revocation_uri(RevocationUrl::new("https://example/revoke".to_string()).unwrap())
+            .set_pushed_authorization_request_uri(
+                PushedAuthorizationRequestUrl::new("https://example/par".to_string()).unwrap(),
+            )
+            .set_private_key_jwt_auth(signing_key(), CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256)
+            .unwrap();
+
+        let rsa_pub_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY).unwrap();
+        let check_client_assertion = |body: &[u8], expected_audience: &str| {
+            check_client_assertion(
+                body,
+                &rsa_pub_key,
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                expected_audience,
+            )
+        };
+        let jtis = RefCell::new(Vec::new());
+        client
+            .exchange_code(AuthorizationCode::new("code".to_string()))
+            .request(|request| {
+                assert_eq!(request.url.as_str(), "https://example/token");
+                assert!(request.headers.get(http::header::AUTHORIZATION).is_none());
+                let claims = check_client_assertion(&request.body, "https://example/token");
+                jtis.borrow_mut()
+                    .push(claims["jti"].as_str().unwrap().to_string());
+                mock_json_response(
+                    http::StatusCode::OK,
+                    "{\"access_token\": \"12/34\", \"token_type\": \"bearer\"}",
+                )
+            })
+            .unwrap();
+
+        client
+            .introspect(&AccessToken::new("access_token_123".to_string()))
+            .unwrap()
+            .request(|request| {
+                assert_eq!(request.url.as_str(), "https://example/introspect");
+                assert!(request.headers.get(http::header::AUTHORIZATION).is_none());
+                let claims = check_client_assertion(&request.body, "https://example/token");
+                jtis.borrow_mut()
+                    .push(claims["jti"].as_str().unwrap().to_string());
+                mock_json_response(http::StatusCode::OK, "{\"active\": false}")
+            })
+            .unwrap();
+
+        client
+            .revoke_token(AccessToken::new("access_token_123".to_string()).into())
+            .unwrap()
+            .request(|request| {
+                assert_eq!(request.url.as_str(), "https://example/revoke");
+                assert!(request.headers.get(http::header::AUTHORIZATION).is_none());
+                let claims = check_client_assertion(&request.body, "https://example/token");
+                jtis.borrow_mut()
+                    .push(claims["jti"].as_str().unwrap().to_string());
+                Ok::<_, std::io::Error>(HttpResponse {
+                    status_code: http::StatusCode::OK,
+                    headers: http::HeaderMap::new(),
+                    body: vec![],
+                })
+            })
+            .unwrap();
+
+        let (request, _, _) = client
+            .push_authorization_request(client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                CsrfToken::new_random,
+                Nonce::new_random,
+            ))
+            .unwrap();
+        request
+            .request(|request: HttpRequest| {
+                assert!(request.headers.get(http::header::AUTHORIZATION).is_none());
+                let claims = check_client_assertion(&request.body, "https://example");
+                jtis.borrow_mut()
+                    .push(claims["jti"].as_str().unwrap().to_string());
+                mock_json_response(
+                    http::StatusCode::CREATED,
+                    "{\"request_uri\": \"urn:example:abc\", \"expires_in\": 60}",
+                )
+            })
+            .unwrap();
+
+        // Each assertion must have a unique JWT ID.
+        let mut jtis = jtis.into_inner();
+        jtis.sort();
+        jtis.dedup();
+        assert_eq!(jtis.len(), 4);
+    }
+
+    // Signing key that produces valid signatures for the first `remaining` messages and fails
+    // for all subsequent ones.
+    pub(crate) struct FailingSigningKey {
+        pub(crate) key: CoreRsaPrivateSigningKey,
+        pub(crate) remaining: std::sync::atomic::AtomicUsize,
+    }
+    impl
+        PrivateSigningKey<
+            CoreJwsSigningAlgorithm,
+            CoreJsonWebKeyType,
+            CoreJsonWebKeyUse,
+            CoreJsonWebKey,
+        > for FailingSigningKey
+    {
+        fn sign(
+            &self,
+            signature_alg: &CoreJwsSigningAlgorithm,
+            message: &[u8],
+        ) -> Result<Vec<u8>, SigningError> {
+            self.remaining
+                .fetch_update(
+                    std::sync::atomic::Ordering::SeqCst,
+                    std::sync::atomic::Ordering::SeqCst,
+                    |remaining| remaining.checked_sub(1),
+                )
+                .map_err(|_| SigningError::Other("signing key unavailable".to_string()))?;
+            self.key.sign(signature_alg, message)
+        }
+
+        fn as_verification_key(&self) -> CoreJsonWebKey {
+            self.key.as_verification_key()
+        }
+    }
+
+    #[test]
+    fn test_private_key_jwt_auth_signing_failure() {
+        // The first signature is used for validating the key when configuring the client.
+        let signing_key = FailingSigningKey {
+            key: CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap(),
+            remaining: 1.into(),
+        };
+        let client = new_client()
+            .set_pushed_authorization_request_uri(
+                PushedAuthorizationRequestUrl::new("https://example/par".to_string()).unwrap(),
+            )
+            .set_backchannel_authentication_uri(
+                BackchannelAuthenticationUrl::new("https://example/bc-authorize".to_string())
+                    .unwrap(),
+            )
+            .set_private_key_jwt_auth(signing_key, CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256)
+            .unwrap();
+
+        let (request, _, _) = client
+            .push_authorization_request(client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                CsrfToken::new_random,
+                Nonce::new_random,
+            ))
+            .unwrap();
+        match request.request(|_| -> Result<HttpResponse, std::io::Error> {
+            panic!("request should not be sent")
+        }) {
+            Err(RequestTokenError::Other(msg)) => {
+                assert_eq!(msg, "Failed to sign client assertion: Failed to sign JWT")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match client
+            .backchannel_authentication_request()
+            .unwrap()
+            .set_login_hint(&LoginHint::new("user@example.com".to_string()))
+            .request(|_| -> Result<HttpResponse, std::io::Error> {
+                panic!("request should not be sent")
+            }) {
+            Err(RequestTokenError::Other(msg)) => {
+                assert_eq!(msg, "Failed to sign client assertion: Failed to sign JWT")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Requests built using the oauth2 crate are sent without any client credentials, which
+        // the provider rejects.
+        match client
+            .exchange_code(AuthorizationCode::new("code".to_string()))
+            .request(|request| {
+                let body = String::from_utf8(request.body).unwrap();
+                assert!(!body.contains("client_assertion"));
+                assert!(!body.contains("client_secret"));
+                assert!(request.headers.get(http::header::AUTHORIZATION).is_none());
+                mock_json_response(
+                    http::StatusCode::UNAUTHORIZED,
+                    "{\"error\": \"invalid_client\"}",
+                )
+            }) {
+            Err(RequestTokenError::ServerResponse(err)) => {
+                assert_eq!(err.error(), &CoreErrorResponseType::InvalidClient)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_client_secret_jwt_auth() {
+        let secret = "0123456789abcdef0123456789abcdef";
+        let new_client = |secret: Option<&str>| {
+            CoreClient::new(
+                ClientId::new("aaa".to_string()),
+                secret.map(|secret| ClientSecret::new(secret.to_string())),
+                IssuerUrl::new("https://example".to_string()).unwrap(),
+                AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+                Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+                None,
+                JsonWebKeySet::default(),
+            )
+        };
+
+        assert!(matches!(
+            new_client(None).set_client_secret_jwt_auth(CoreJwsSigningAlgorithm::HmacSha256),
+            Err(JsonWebTokenError::SigningError(SigningError::Other(_)))
+        ));
+        // HS256 requires a secret of at least 256 bits.
+        assert!(matches!(
+            new_client(Some(&secret[1..]))
+                .set_client_secret_jwt_auth(CoreJwsSigningAlgorithm::HmacSha256),
+            Err(JsonWebTokenError::SigningError(SigningError::Other(_)))
+        ));
+        assert!(matches!(
+            new_client(Some(secret))
+                .set_client_secret_jwt_auth(CoreJwsSigningAlgorithm::HmacSha384),
+            Err(JsonWebTokenError::SigningError(SigningError::Other(_)))
+        ));
+        assert!(matches!(
+            new_client(Some(secret))
+                .set_client_secret_jwt_auth(CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256),
+            Err(JsonWebTokenError::SigningError(
+                SigningError::UnsupportedAlg(_)
+            ))
+        ));
+
+        let client = new_client(Some(secret))
+            .set_client_secret_jwt_auth(CoreJwsSigningAlgorithm::HmacSha256)
+            .unwrap();
+        let hmac_key = CoreJsonWebKey::new_symmetric(secret.as_bytes().to_vec());
+
+        let jtis = RefCell::new(Vec::new());
+        for _ in 0..2 {
+            client
+                .exchange_refresh_token(&RefreshToken::new("refresh_token".to_string()))
+                .request(|request| {
+                    assert!(request.headers.get(http::header::AUTHORIZATION).is_none());
+                    assert!(!String::from_utf8(request.body.clone())
+                        .unwrap()
+                        .contains(secret));
+                    let claims = check_client_assertion(
+                        &request.body,
+                        &hmac_key,
+                        CoreJwsSigningAlgorithm::HmacSha256,
+                        "https://example/token",
+                    );
+                    jtis.borrow_mut()
+                        .push(claims["jti"].as_str().unwrap().to_string());
+                    mock_json_response(
+                        http::StatusCode::OK,
+                        "{\"access_token\": \"12/34\", \"token_type\": \"bearer\"}",
+                    )
+                })
+                .unwrap();
+        }
+
+        // Providers detect replayed assertions using the `jti` claim, so each request must use a
+        // fresh value.
+        let jtis = jtis.into_inner();
+        assert_eq!(jtis.len(), 2);
+        assert_ne!(jtis[0], jtis[1]);
+    }
+
+    #[test]
+    fn test_refresh_jwks() {
+        fn signing_key(kid: &str) -> CoreRsaPrivateSigningKey {
+            CoreRsaPrivateSigningKey::from_pem(
+                TEST_RSA_PRIV_KEY,
+                Some(JsonWebKeyId::new(kid.to_string())),
+            )
+            .unwrap()
+        }
+        fn jwks_json(kids: &[&str]) -> String {
+            serde_json::to_string(&JsonWebKeySet::new(
+                kids.iter()
+                    .map(|kid| signing_key(kid).as_verification_key())
+                    .collect::<Vec<CoreJsonWebKey>>(),
+            ))
+            .unwrap()
+        }
+        fn id_token(kid: &str) -> CoreIdToken {
+            CoreIdToken::new(
+                CoreIdTokenClaims::new(
+                    IssuerUrl::new("https://example".to_string()).unwrap(),
+                    vec![Audience::new("aaa".to_string())],
+                    Utc::now() + chrono::Duration::hours(1),
+                    Utc::now(),
+                    StandardClaims::new(SubjectIdentifier::new("subject".to_string())),
+                    Default::default(),
+                ),
+                &signing_key(kid),
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                None,
+                None,
+            )
+            .unwrap()
+        }
+        fn verify(client: &CoreClient, kid: &str) -> Result<(), ClaimsVerificationError> {
+            id_token(kid)
+                .claims(&client.id_token_verifier(), |_: Option<&Nonce>| {
+                    Ok::<_, String>(())
+                })
+                .map(|_| ())
+        }
+        let no_matching_key = Err(ClaimsVerificationError::SignatureVerification(
+            SignatureVerificationError::NoMatchingKey,
+        ));
+
+        let client = new_client()
+            .set_jwks_uri(JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap());
+        let client_clone = client.clone();
+        assert_eq!(verify(&client, "key-1"), no_matching_key);
+
+        let key_ids = client
+            .refresh_jwks(|request: HttpRequest| {
+                assert_eq!(request.url.as_str(), "https://example/jwks");
+                mock_json_response(http::StatusCode::OK, &jwks_json(&["key-1"]))
+            })
+            .unwrap();
+        assert_eq!(key_ids, vec![Some(JsonWebKeyId::new("key-1".to_string()))]);
+        verify(&client, "key-1").unwrap();
+        assert_eq!(verify(&client, "key-2"), no_matching_key);
+
+        // The refreshed keys are shared with clones of the client.
+        let key_ids = client_clone
+            .refresh_jwks(|_| {
+                mock_json_response(http::StatusCode::OK, &jwks_json(&["key-1", "key-2"]))
+            })
+            .unwrap();
+        assert_eq!(
+            key_ids,
+            vec![
+                Some(JsonWebKeyId::new("key-1".to_string())),
+                Some(JsonWebKeyId::new("key-2".to_string())),
+            ]
+        );
+        for client in [&client, &client_clone] {
+            verify(client, "key-1").unwrap();
+            verify(client, "key-2").unwrap();
+        }
+
+        // A failed refresh keeps the current keys.
+        assert!(matches!(
+            client.refresh_jwks(|_| mock_json_response(http::StatusCode::NOT_FOUND, "{}")),
+            Err(DiscoveryError::Response(http::StatusCode::NOT_FOUND, _, _))
+        ));
+        verify(&client, "key-2").unwrap();
+
+        assert!(matches!(
+            new_client().refresh_jwks(|_| -> Result<HttpResponse, std::io::Error> {
+                panic!("no request should be sent")
+            }),
+            Err(DiscoveryError::Other(_))
+        ));
+    }
+
+    // Runs the blocking and async variants of a request against the same mock server. Since both
+    // results must have the same type, this also checks at compile time that each flow exposes
+    // both variants with identical success and error types.
+    fn assert_blocking_async_parity<T, E, F>(blocking: Result<T, E>, non_blocking: F)
+    where
+        F: std::future::Future<Output = Result<T, E>>,
+        T: std::fmt::Debug,
+        E: std::fmt::Debug,
+    {
+        assert_eq!(
+            format!("{:?}", blocking),
+            format!("{:?}", crate::discovery::tests::block_on(non_blocking))
+        );
+    }
+
+    fn mock_endpoints(request: HttpRequest) -> Result<HttpResponse, std::io::Error> {
+        match request.url.path() {
+            "/token" => mock_json_response(
+                http::StatusCode::OK,
+                "{\"access_token\": \"12/34\", \"token_type\": \"bearer\"}",
+            ),
+            "/userinfo" => mock_json_response(http::StatusCode::OK, "{\"sub\": \"subject\"}"),
+            "/introspect" => mock_json_response(http::StatusCode::OK, "{\"active\": false}"),
+            "/revoke" => mock_json_response(http::StatusCode::OK, ""),
+            _ => mock_json_response(http::StatusCode::NOT_FOUND, "{}"),
+        }
+    }
+
+    #[test]
+    fn test_blocking_async_parity() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            Some(UserInfoUrl::new("https://example/userinfo".to_string()).unwrap()),
+            JsonWebKeySet::default(),
+        )
+        .set_introspection_uri(
+            IntrospectionUrl::new("https://example/introspect".to_string()).unwrap(),
+        )
+        .set_revocation_uri(RevocationUrl::new("https://example/revoke".to_string()).unwrap());
+        let async_endpoints = |request| async move { mock_endpoints(request) };
+        let code = || AuthorizationCode::new("ccc".to_string());
+        let access_token = AccessToken::new("12/34".to_string());
+
+        assert_blocking_async_parity(
+            client.exchange_code(code()).request(mock_endpoints),
+            client.exchange_code(code()).request_async(async_endpoints),
+        );
+        assert_blocking_async_parity(
+            client
+                .user_info(access_token.clone(), None)
+                .unwrap()
+                .request::<EmptyAdditionalClaims, CoreGenderClaim, _, _>(mock_endpoints),
+            client
+                .user_info(access_token.clone(), None)
+                .unwrap()
+                .request_async::<EmptyAdditionalClaims, CoreGenderClaim, _, _, _>(async_endpoints),
+        );
+        assert_blocking_async_parity(
+            client
+                .introspect(&access_token)
+                .unwrap()
+                .request(mock_endpoints),
+            client
+                .introspect(&access_token)
+                .unwrap()
+                .request_async(async_endpoints),
+        );
+        assert_blocking_async_parity(
+            client
+                .revoke_token(CoreRevocableToken::from(access_token.clone()))
+                .unwrap()
+                .request(mock_endpoints),
+            client
+                .revoke_token(CoreRevocableToken::from(access_token.clone()))
+                .unwrap()
+                .request_async(async_endpoints),
+        );
+        let issuer_url = IssuerUrl::new("https://example".to_string()).unwrap();
+        assert_blocking_async_parity(
+            CoreProviderMetadata::discover(&issuer_url, mock_endpoints),
+            CoreProviderMetadata::discover_async(issuer_url.clone(), async_endpoints),
+        );
+    }
+
+    #[test]
+    fn test_granted_scopes() {
+        let token_response = |body: &'static str| {
+            new_client()
+                .exchange_code(AuthorizationCode::new("ccc".to_string()))
+                .request(|_| mock_json_response(http::StatusCode::OK, body))
+                .unwrap()
+        };
+
+        // Narrowed scopes.
+        let narrowed = token_response(
+            "{\"access_token\": \"12/34\", \"token_type\": \"bearer\", \"scope\": \"openid email\"}",
+        );
+        assert_eq!(
+            narrowed.granted_scopes(),
+            Some(
+                &[
+                    Scope::new("openid".to_string()),
+                    Scope::new("email".to_string())
+                ][..]
+            )
+        );
+
+        // Omitted scopes (i.e., identical to the requested scopes).
+        let omitted = token_response("{\"access_token\": \"12/34\", \"token_type\": \"bearer\"}");
+        assert_eq!(omitted.granted_scopes(), None);
+    }
 }
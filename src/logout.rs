@@ -1,5 +1,11 @@
+use std::fmt::{Display, Formatter, Result as FormatterResult};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
 use oauth2::{ClientId, CsrfToken};
+use serde_json::{Map, Value};
 use serde_with::skip_serializing_none;
+use thiserror::Error;
 use url::Url;
 
 use crate::{
@@ -10,15 +16,21 @@ use crate::{
         CoreSubjectIdentifierType,
     },
     join_vec,
+    jwt::{JsonWebTokenError, JsonWebTokenJsonPayloadSerde},
+    types::helpers::{deserialize_string_or_vec, serde_utc_seconds, serde_utc_seconds_opt},
     types::{LogoutHint, PostLogoutRedirectUrl},
-    AdditionalClaims, AdditionalProviderMetadata, EmptyAdditionalProviderMetadata, EndSessionUrl,
-    GenderClaim, IdToken, JsonWebKeyType, JweContentEncryptionAlgorithm, JwsSigningAlgorithm,
-    LanguageTag, ProviderMetadata,
+    AdditionalClaims, AdditionalProviderMetadata, Audience, AudiencesClaim,
+    ClaimsVerificationError, EmptyAdditionalProviderMetadata, EndSessionUrl, GenderClaim, IdToken,
+    IssuerClaim, IssuerUrl, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken,
+    JsonWebTokenId, JweContentEncryptionAlgorithm, JwsSigningAlgorithm, LanguageTag,
+    LogoutTokenVerifier, PrivateSigningKey, ProviderMetadata, SessionIdentifier, SubjectIdentifier,
 };
 
 ///
 /// Additional metadata for providers implementing [OpenID Connect RP-Initiated
-/// Logout 1.0](https://openid.net/specs/openid-connect-rpinitiated-1_0.html).
+/// Logout 1.0](https://openid.net/specs/openid-connect-rpinitiated-1_0.html) and
+/// [OpenID Connect Front-Channel Logout 1.0](
+/// https://openid.net/specs/openid-connect-frontchannel-1_0.html).
 ///
 #[non_exhaustive]
 #[skip_serializing_none]
@@ -32,6 +44,17 @@ where
     /// Logout 1.0](https://openid.net/specs/openid-connect-rpinitiated-1_0.html).
     ///
     pub end_session_endpoint: Option<EndSessionUrl>,
+    ///
+    /// Whether the provider supports front-channel logout, as described in [OpenID Connect
+    /// Front-Channel Logout 1.0](
+    /// https://openid.net/specs/openid-connect-frontchannel-1_0.html#OPLogout).
+    ///
+    pub frontchannel_logout_supported: Option<bool>,
+    ///
+    /// Whether the provider can pass the `iss` and `sid` query parameters to the client's
+    /// front-channel logout URI.
+    ///
+    pub frontchannel_logout_session_supported: Option<bool>,
     #[serde(bound = "A: AdditionalProviderMetadata", flatten)]
     ///
     /// A field for an additional struct implementing AdditionalProviderMetadata.
@@ -40,6 +63,19 @@ where
 }
 impl<A> AdditionalProviderMetadata for LogoutProviderMetadata<A> where A: AdditionalProviderMetadata {}
 
+impl<A> LogoutProviderMetadata<A>
+where
+    A: AdditionalProviderMetadata,
+{
+    ///
+    /// Returns a new [`LogoutRequest`] targeting the provider's `end_session_endpoint`, or `None`
+    /// if the provider did not advertise one.
+    ///
+    pub fn logout_request(&self) -> Option<LogoutRequest> {
+        self.end_session_endpoint.clone().map(LogoutRequest::from)
+    }
+}
+
 ///
 /// Provider metadata returned by [OpenID Connect Discovery](
 /// https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata)
@@ -166,6 +202,8 @@ impl LogoutRequest {
     /// Returns the full logout URL. In order to logout, a GET request should be made to this URL
     /// by the client's browser.
     ///
+    /// Parameters are URL-encoded, and any parameters that have not been set are omitted.
+    ///
     pub fn http_get_url(self) -> Url {
         let mut url = self.end_session_endpoint.url().to_owned();
         {
@@ -198,21 +236,346 @@ impl LogoutRequest {
     }
 }
 
+///
+/// Front-channel logout request made by the End-User's browser to the client's front-channel
+/// logout URI, as described in [OpenID Connect Front-Channel Logout 1.0](
+/// https://openid.net/specs/openid-connect-frontchannel-1_0.html#RPLogout).
+///
+/// When the client registers with `frontchannel_logout_session_required`, the OpenID Connect
+/// Provider includes the `iss` and `sid` query parameters, which should be compared against the
+/// issuer and session ID (`sid` claim) of the client's current session using
+/// [`FrontChannelLogoutRequest::verify`] before logging the End-User out.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrontChannelLogoutRequest {
+    issuer: Option<IssuerUrl>,
+    session_id: Option<SessionIdentifier>,
+}
+impl FrontChannelLogoutRequest {
+    ///
+    /// Parses a front-channel logout request from the URL requested by the End-User's browser.
+    ///
+    pub fn from_url(url: &Url) -> Result<Self, FrontChannelLogoutError> {
+        let mut issuer = None;
+        let mut session_id = None;
+        for (name, value) in url.query_pairs() {
+            let param = match name.as_ref() {
+                "iss" => &mut issuer,
+                "sid" => &mut session_id,
+                _ => continue,
+            };
+            if param.replace(value.into_owned()).is_some() {
+                return Err(FrontChannelLogoutError::DuplicateParameter(
+                    name.into_owned(),
+                ));
+            }
+        }
+
+        Ok(Self {
+            issuer: issuer
+                .map(IssuerUrl::new)
+                .transpose()
+                .map_err(FrontChannelLogoutError::InvalidIssuerUrl)?,
+            session_id: session_id.map(SessionIdentifier::new),
+        })
+    }
+
+    ///
+    /// Returns the issuer passed in the `iss` query parameter, if any.
+    ///
+    pub fn issuer(&self) -> Option<&IssuerUrl> {
+        self.issuer.as_ref()
+    }
+
+    ///
+    /// Returns the session ID passed in the `sid` query parameter, if any.
+    ///
+    pub fn session_id(&self) -> Option<&SessionIdentifier> {
+        self.session_id.as_ref()
+    }
+
+    ///
+    /// Verifies that the `iss` and `sid` query parameters are present and match the issuer and
+    /// session ID of the client's current session, returning the matching session ID.
+    ///
+    pub fn verify(
+        &self,
+        expected_issuer: &IssuerUrl,
+        expected_session_id: &SessionIdentifier,
+    ) -> Result<&SessionIdentifier, FrontChannelLogoutError> {
+        let issuer = self
+            .issuer
+            .as_ref()
+            .ok_or(FrontChannelLogoutError::MissingParameter("iss"))?;
+        let session_id = self
+            .session_id
+            .as_ref()
+            .ok_or(FrontChannelLogoutError::MissingParameter("sid"))?;
+
+        if issuer != expected_issuer {
+            return Err(FrontChannelLogoutError::InvalidIssuer(format!(
+                "expected `{}` (found `{}`)",
+                **expected_issuer, **issuer
+            )));
+        }
+        if session_id != expected_session_id {
+            return Err(FrontChannelLogoutError::InvalidSessionId);
+        }
+        Ok(session_id)
+    }
+}
+
+///
+/// Error parsing or verifying a [`FrontChannelLogoutRequest`].
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum FrontChannelLogoutError {
+    ///
+    /// A query parameter was included more than once.
+    ///
+    #[error("Duplicate parameter `{0}`")]
+    DuplicateParameter(String),
+    ///
+    /// The `iss` query parameter is not a valid URL.
+    ///
+    #[error("Invalid issuer URL")]
+    InvalidIssuerUrl(#[source] url::ParseError),
+    ///
+    /// The `iss` query parameter does not match the expected issuer.
+    ///
+    #[error("Invalid issuer: {0}")]
+    InvalidIssuer(String),
+    ///
+    /// The `sid` query parameter does not match the expected session ID.
+    ///
+    #[error("Session ID does not match the current session")]
+    InvalidSessionId,
+    ///
+    /// A required query parameter is missing.
+    ///
+    #[error("Missing required parameter `{0}`")]
+    MissingParameter(&'static str),
+}
+
+///
+/// Logout token sent by an OpenID Connect Provider to the client's back-channel logout URI, as
+/// described in [OpenID Connect Back-Channel Logout 1.0](
+/// https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct LogoutToken<
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+>(#[serde(bound = "")] JsonWebToken<JE, JS, JT, LogoutTokenClaims, JsonWebTokenJsonPayloadSerde>);
+
+impl<JE, JS, JT> FromStr for LogoutToken<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    type Err = serde_json::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(Value::String(s.to_string()))
+    }
+}
+
+impl<JE, JS, JT> LogoutToken<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Initializes a logout token with the specified claims, signed using the given signing key
+    /// and algorithm.
+    ///
+    pub fn new<JU, K, S>(
+        claims: LogoutTokenClaims,
+        signing_key: &S,
+        alg: JS,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        JsonWebToken::new(claims, signing_key, &alg).map(Self)
+    }
+
+    ///
+    /// Verifies and returns a reference to the logout token claims.
+    ///
+    /// In addition to the signature, issuer, audience, and timestamps, this verifies that the
+    /// token contains a `sub` and/or `sid` claim and a back-channel logout event, and that it
+    /// does not contain a `nonce` claim. Detecting replayed tokens (e.g., using the `jti` claim) is
+    /// left to the caller.
+    ///
+    pub fn claims<'a, JU, K>(
+        &'a self,
+        verifier: &LogoutTokenVerifier<JS, JT, JU, K>,
+    ) -> Result<&'a LogoutTokenClaims, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        verifier.verified_claims(&self.0)
+    }
+}
+impl<JE, JS, JT> Display for LogoutToken<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatterResult {
+        f.write_str(
+            serde_json::to_value(self)
+                // This should never arise, since we're just asking serde_json to serialize the
+                // signing input concatenated with the signature, both of which are precomputed.
+                .expect("logout token serialization failed")
+                .as_str()
+                // This should also never arise, since our JsonWebToken serializer always calls
+                // serialize_str
+                .expect("logout token serializer did not produce a str"),
+        )
+    }
+}
+
+///
+/// Back-channel logout token claims.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct LogoutTokenClaims {
+    #[serde(rename = "iss")]
+    issuer: IssuerUrl,
+    #[serde(
+        default,
+        rename = "aud",
+        deserialize_with = "deserialize_string_or_vec"
+    )]
+    audiences: Vec<Audience>,
+    #[serde(rename = "iat", with = "serde_utc_seconds")]
+    issue_time: DateTime<Utc>,
+    #[serde(
+        default,
+        rename = "exp",
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    expiration: Option<DateTime<Utc>>,
+    jti: JsonWebTokenId,
+    #[serde(rename = "sub", skip_serializing_if = "Option::is_none")]
+    subject: Option<SubjectIdentifier>,
+    #[serde(rename = "sid", skip_serializing_if = "Option::is_none")]
+    session_id: Option<SessionIdentifier>,
+    // Missing events are rejected by the verifier rather than during deserialization so that
+    // callers receive a descriptive error.
+    #[serde(default)]
+    events: Map<String, Value>,
+    // Logout tokens are prohibited from containing a nonce. We only keep track of it so that the
+    // verifier can reject such tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nonce: Option<Value>,
+}
+impl LogoutTokenClaims {
+    ///
+    /// Member name of the `events` claim identifying a back-channel logout token.
+    ///
+    pub const BACKCHANNEL_LOGOUT_EVENT: &'static str =
+        "http://schemas.openid.net/event/backchannel-logout";
+
+    ///
+    /// Initializes new logout token claims containing the back-channel logout event.
+    ///
+    /// At least one of the `sub` and `sid` claims must be set (see
+    /// [`LogoutTokenClaims::set_subject`] and [`LogoutTokenClaims::set_session_id`]) for the
+    /// resulting logout token to be valid.
+    ///
+    pub fn new(
+        issuer: IssuerUrl,
+        audiences: Vec<Audience>,
+        issue_time: DateTime<Utc>,
+        jti: JsonWebTokenId,
+    ) -> Self {
+        let mut events = Map::new();
+        events.insert(
+            Self::BACKCHANNEL_LOGOUT_EVENT.to_string(),
+            Value::Object(Map::new()),
+        );
+        Self {
+            issuer,
+            audiences,
+            issue_time,
+            expiration: None,
+            jti,
+            subject: None,
+            session_id: None,
+            events,
+            nonce: None,
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["claim"] {
+            set_issuer -> issuer[IssuerUrl] ["iss"],
+            set_audiences -> audiences[Vec<Audience>] ["aud"],
+            set_issue_time -> issue_time[DateTime<Utc>] ["iat"],
+            set_expiration -> expiration[Option<DateTime<Utc>>] ["exp"],
+            set_jti -> jti[JsonWebTokenId],
+            set_subject -> subject[Option<SubjectIdentifier>] ["sub"],
+            set_session_id -> session_id[Option<SessionIdentifier>] ["sid"],
+            set_events -> events[Map<String, Value>],
+        }
+    ];
+
+    pub(crate) fn has_nonce(&self) -> bool {
+        self.nonce.is_some()
+    }
+}
+impl AudiencesClaim for LogoutTokenClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(LogoutTokenClaims::audiences(self))
+    }
+}
+impl AudiencesClaim for &LogoutTokenClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(LogoutTokenClaims::audiences(self))
+    }
+}
+impl IssuerClaim for LogoutTokenClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(LogoutTokenClaims::issuer(self))
+    }
+}
+impl IssuerClaim for &LogoutTokenClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(LogoutTokenClaims::issuer(self))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
+    use chrono::{TimeZone, Utc};
     use oauth2::{AuthUrl, ClientId, CsrfToken};
     use url::Url;
 
+    use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
     use crate::{
         core::{
-            CoreGenderClaim, CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm,
-            CoreJwsSigningAlgorithm,
+            CoreGenderClaim, CoreJsonWebKey, CoreJsonWebKeySet, CoreJsonWebKeyType,
+            CoreJweContentEncryptionAlgorithm, CoreJwsSigningAlgorithm, CoreLogoutToken,
+            CoreLogoutTokenVerifier, CoreRsaPrivateSigningKey,
         },
         types::{LogoutHint, PostLogoutRedirectUrl},
-        EmptyAdditionalClaims, EndSessionUrl, IdToken, IssuerUrl, JsonWebKeySetUrl, LanguageTag,
-        LogoutProviderMetadata, LogoutRequest, ProviderMetadataWithLogout,
+        Audience, ClaimsVerificationError, EmptyAdditionalClaims, EmptyAdditionalProviderMetadata,
+        EndSessionUrl, FrontChannelLogoutError, FrontChannelLogoutRequest, IdToken, IssuerUrl,
+        JsonWebKeySetUrl, JsonWebTokenId, LanguageTag, LogoutProviderMetadata, LogoutRequest,
+        LogoutTokenClaims, ProviderMetadataWithLogout, SessionIdentifier, SubjectIdentifier,
     };
 
     #[test]
@@ -255,6 +618,8 @@ mod tests {
                     "https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/end_session"
                         .to_string()
                 ).unwrap()),
+                frontchannel_logout_supported: None,
+                frontchannel_logout_session_supported: None,
                 additional_metadata: Default::default(),
             },
         );
@@ -272,6 +637,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_frontchannel_logout_metadata() {
+        let provider_metadata: ProviderMetadataWithLogout = serde_json::from_str(
+            "{\
+            \"issuer\":\"https://server.example.com\",\
+            \"authorization_endpoint\":\"https://server.example.com/authorize\",\
+            \"jwks_uri\":\"https://server.example.com/jwks.json\",\
+            \"response_types_supported\":[],\
+            \"subject_types_supported\":[],\
+            \"id_token_signing_alg_values_supported\": [],\
+            \"frontchannel_logout_supported\":true,\
+            \"frontchannel_logout_session_supported\":true}",
+        )
+        .unwrap();
+        let logout_metadata = provider_metadata.additional_metadata();
+        assert_eq!(logout_metadata.end_session_endpoint, None);
+        assert_eq!(logout_metadata.frontchannel_logout_supported, Some(true));
+        assert_eq!(
+            logout_metadata.frontchannel_logout_session_supported,
+            Some(true)
+        );
+
+        let serialized = serde_json::to_value(&provider_metadata).unwrap();
+        assert_eq!(serialized["frontchannel_logout_supported"], true);
+        assert_eq!(serialized["frontchannel_logout_session_supported"], true);
+        assert!(serialized.get("end_session_endpoint").is_none());
+    }
+
+    #[test]
+    fn test_frontchannel_logout_request() {
+        let issuer = IssuerUrl::new("https://server.example.com".to_string()).unwrap();
+        let session_id = SessionIdentifier::new("08a5019c-17e1-4977-8f42-65a12843ea02".to_string());
+
+        let request = FrontChannelLogoutRequest::from_url(
+            &Url::parse(
+                "https://rp.example.org/frontchannel_logout\
+                 ?iss=https%3A%2F%2Fserver.example.com\
+                 &sid=08a5019c-17e1-4977-8f42-65a12843ea02",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(request.issuer(), Some(&issuer));
+        assert_eq!(request.session_id(), Some(&session_id));
+        assert_eq!(request.verify(&issuer, &session_id).unwrap(), &session_id);
+
+        match request.verify(
+            &IssuerUrl::new("https://other.example.com".to_string()).unwrap(),
+            &session_id,
+        ) {
+            Err(FrontChannelLogoutError::InvalidIssuer(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match request.verify(
+            &issuer,
+            &SessionIdentifier::new("other_session".to_string()),
+        ) {
+            Err(FrontChannelLogoutError::InvalidSessionId) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The parameters are omitted unless the client requires them.
+        let request = FrontChannelLogoutRequest::from_url(
+            &Url::parse("https://rp.example.org/frontchannel_logout").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(request.issuer(), None);
+        assert_eq!(request.session_id(), None);
+        match request.verify(&issuer, &session_id) {
+            Err(FrontChannelLogoutError::MissingParameter("iss")) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match FrontChannelLogoutRequest::from_url(
+            &Url::parse("https://rp.example.org/frontchannel_logout?iss=a&iss=b").unwrap(),
+        ) {
+            Err(FrontChannelLogoutError::DuplicateParameter(name)) => assert_eq!(name, "iss"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match FrontChannelLogoutRequest::from_url(
+            &Url::parse("https://rp.example.org/frontchannel_logout?iss=not_a_url&sid=x").unwrap(),
+        ) {
+            Err(FrontChannelLogoutError::InvalidIssuerUrl(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_logout_request_with_no_parameters() {
         let endpoint = EndSessionUrl::new(
@@ -337,4 +789,201 @@ mod tests {
             logout_url
         );
     }
+
+    #[test]
+    fn test_logout_request_from_provider_metadata() {
+        let metadata: LogoutProviderMetadata<EmptyAdditionalProviderMetadata> =
+            serde_json::from_str("{}").unwrap();
+        assert!(metadata.logout_request().is_none());
+
+        let metadata: LogoutProviderMetadata<EmptyAdditionalProviderMetadata> =
+            serde_json::from_str(
+                "{\"end_session_endpoint\":\"https://example/logout?tenant=a%20b\"}",
+            )
+            .unwrap();
+
+        let logout_url = metadata
+            .logout_request()
+            .unwrap()
+            .set_post_logout_redirect_uri(
+                PostLogoutRedirectUrl::new("https://localhost:8000/done?x=1&y=2".to_string())
+                    .unwrap(),
+            )
+            .set_state(CsrfToken::new("a b&c=d".to_string()))
+            .add_ui_locale(LanguageTag::new("fr-CA".to_string()))
+            .http_get_url();
+
+        assert_eq!(
+            logout_url.as_str(),
+            "https://example/logout?tenant=a%20b\
+             &post_logout_redirect_uri=https%3A%2F%2Flocalhost%3A8000%2Fdone%3Fx%3D1%26y%3D2\
+             &state=a+b%26c%3Dd&ui_locales=fr-CA"
+        );
+    }
+
+    fn sign_logout_token(claims: LogoutTokenClaims) -> CoreLogoutToken {
+        let rsa_priv_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        CoreLogoutToken::new(
+            claims,
+            &rsa_priv_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap()
+    }
+
+    fn logout_token_verifier<'a>() -> CoreLogoutTokenVerifier<'a> {
+        let rsa_pub_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY).unwrap();
+        CoreLogoutTokenVerifier::new_public_client(
+            ClientId::new("my_client".to_string()),
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![rsa_pub_key]),
+        )
+        .set_time_fn(|| Utc.timestamp_opt(1544928600, 0).single().unwrap())
+    }
+
+    fn logout_token_claims_json(extra: &str) -> String {
+        format!(
+            "{{\
+                \"iss\": \"https://example.com\",\
+                \"aud\": \"my_client\",\
+                \"iat\": 1544928549,\
+                \"jti\": \"bWJq\"\
+                {}\
+            }}",
+            extra
+        )
+    }
+
+    #[test]
+    fn test_logout_token() {
+        let claims = LogoutTokenClaims::new(
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            vec![Audience::new("my_client".to_string())],
+            Utc.timestamp_opt(1544928549, 0).single().unwrap(),
+            JsonWebTokenId::new("bWJq".to_string()),
+        )
+        .set_expiration(Some(Utc.timestamp_opt(1544928669, 0).single().unwrap()))
+        .set_subject(Some(SubjectIdentifier::new("248289761001".to_string())))
+        .set_session_id(Some(SessionIdentifier::new(
+            "08a5019c-17e1-4977-8f42-65a12843ea02".to_string(),
+        )));
+        assert_eq!(
+            serde_json::to_value(&claims).unwrap(),
+            serde_json::json!({
+                "iss": "https://example.com",
+                "aud": ["my_client"],
+                "iat": 1544928549,
+                "exp": 1544928669,
+                "jti": "bWJq",
+                "sub": "248289761001",
+                "sid": "08a5019c-17e1-4977-8f42-65a12843ea02",
+                "events": {
+                    "http://schemas.openid.net/event/backchannel-logout": {}
+                }
+            })
+        );
+
+        let logout_token =
+            CoreLogoutToken::from_str(&sign_logout_token(claims.clone()).to_string()).unwrap();
+        let verifier = logout_token_verifier();
+        let verified_claims = logout_token.claims(&verifier).unwrap();
+        assert_eq!(verified_claims, &claims);
+        assert_eq!(verified_claims.jti().as_str(), "bWJq");
+        assert_eq!(
+            verified_claims.session_id().map(|sid| sid.as_str()),
+            Some("08a5019c-17e1-4977-8f42-65a12843ea02")
+        );
+
+        // Only a `sid` claim is also acceptable.
+        let sid_only: LogoutTokenClaims = serde_json::from_str(&logout_token_claims_json(
+            ",\"sid\": \"the_sid\",\
+             \"events\": {\"http://schemas.openid.net/event/backchannel-logout\": {}}",
+        ))
+        .unwrap();
+        assert_eq!(sid_only.subject(), None);
+        sign_logout_token(sid_only).claims(&verifier).unwrap();
+
+        // Expired (beyond the default clock skew leeway).
+        let expired_verifier = logout_token_verifier()
+            .set_time_fn(|| Utc.timestamp_opt(1544928729, 0).single().unwrap());
+        match logout_token.claims(&expired_verifier) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Wrong audience.
+        let other_client_verifier = CoreLogoutTokenVerifier::new_public_client(
+            ClientId::new("other_client".to_string()),
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        );
+        match logout_token.claims(&other_client_verifier) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_logout_token_forbidden_nonce() {
+        let claims: LogoutTokenClaims = serde_json::from_str(&logout_token_claims_json(
+            ",\"sub\": \"248289761001\",\
+             \"nonce\": \"the_nonce\",\
+             \"events\": {\"http://schemas.openid.net/event/backchannel-logout\": {}}",
+        ))
+        .unwrap();
+
+        assert_eq!(
+            sign_logout_token(claims).claims(&logout_token_verifier()),
+            Err(ClaimsVerificationError::InvalidNonce(
+                "logout token must not contain a nonce".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_logout_token_invalid_events() {
+        let verifier = logout_token_verifier();
+
+        let missing_events: LogoutTokenClaims =
+            serde_json::from_str(&logout_token_claims_json(",\"sub\": \"248289761001\"")).unwrap();
+        assert_eq!(
+            sign_logout_token(missing_events).claims(&verifier),
+            Err(ClaimsVerificationError::InvalidEvents(
+                "missing `http://schemas.openid.net/event/backchannel-logout` event".to_string()
+            ))
+        );
+
+        let other_event: LogoutTokenClaims = serde_json::from_str(&logout_token_claims_json(
+            ",\"sub\": \"248289761001\",\
+             \"events\": {\"https://example.com/other-event\": {}}",
+        ))
+        .unwrap();
+        match sign_logout_token(other_event).claims(&verifier) {
+            Err(ClaimsVerificationError::InvalidEvents(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let non_object_event: LogoutTokenClaims = serde_json::from_str(&logout_token_claims_json(
+            ",\"sub\": \"248289761001\",\
+             \"events\": {\"http://schemas.openid.net/event/backchannel-logout\": true}",
+        ))
+        .unwrap();
+        match sign_logout_token(non_object_event).claims(&verifier) {
+            Err(ClaimsVerificationError::InvalidEvents(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Neither `sub` nor `sid`.
+        let no_subject: LogoutTokenClaims = serde_json::from_str(&logout_token_claims_json(
+            ",\"events\": {\"http://schemas.openid.net/event/backchannel-logout\": {}}",
+        ))
+        .unwrap();
+        match sign_logout_token(no_subject).claims(&verifier) {
+            Err(ClaimsVerificationError::InvalidSubject(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
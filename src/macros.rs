@@ -399,14 +399,21 @@ macro_rules! new_url_type {
 
 macro_rules! deserialize_fields {
     (@field_str Option(Seconds($field:ident))) => { stringify![$field] };
+    (@field_str Option(DateTime(Seconds($field:ident, $raw:ident)))) => { stringify![$field] };
     (@field_str Option(DateTime(Seconds($field:ident)))) => { stringify![$field] };
     (@field_str Option(Boolean($field:ident))) => { stringify![$field] };
+    (@field_str Option(ObjectOrString($field:ident))) => { stringify![$field] };
     (@field_str Option($field:ident)) => { stringify![$field] };
     (@field_str LanguageTag($field:ident)) => { stringify![$field] };
     (@field_str $field:ident) => { stringify![$field] };
     (@let_none Option(Seconds($field:ident))) => { let mut $field = None; };
+    (@let_none Option(DateTime(Seconds($field:ident, $raw:ident)))) => {
+        let mut $field = None;
+        let mut $raw = None;
+    };
     (@let_none Option(DateTime(Seconds($field:ident)))) => { let mut $field = None; };
     (@let_none Option(Boolean($field:ident))) => { let mut $field = None; };
+    (@let_none Option(ObjectOrString($field:ident))) => { let mut $field = None; };
     (@let_none Option($field:ident)) => { let mut $field = None; };
     (@let_none LanguageTag($field:ident)) => { let mut $field = None; };
     (@let_none $field:ident) => { let mut $field = None; };
@@ -426,6 +433,41 @@ macro_rules! deserialize_fields {
         let seconds = $map.next_value::<Option<u64>>()?;
         $field = seconds.map(Duration::from_secs);
     };
+    // Like Option(DateTime(Seconds(...))), but also retains the raw number of seconds. Values
+    // that can't be converted to a UTC datetime yield `None` for the datetime rather than an
+    // error, provided that the raw number of seconds is available.
+    (@case $map:ident $key:ident $language_tag_opt:ident
+     Option(DateTime(Seconds($field:ident, $raw:ident)))) => {
+        if $field.is_some() || $raw.is_some() {
+            return Err(serde::de::Error::duplicate_field(stringify!($field)));
+        } else if let Some(language_tag) = $language_tag_opt {
+            return Err(
+                serde::de::Error::custom(
+                    format!(
+                        concat!("unexpected language tag `{}` for key `", stringify!($field), "`"),
+                        language_tag.as_ref()
+                    )
+                )
+            );
+        }
+        if let Some(sec) = $map.next_value::<Option<Timestamp>>()? {
+            $raw = sec.seconds();
+            $field = match timestamp_to_utc(&sec) {
+                Ok(utc) => Some(utc),
+                Err(_) if $raw.is_some() => None,
+                Err(_) => return Err(serde::de::Error::custom(
+                    format!(
+                        concat!(
+                            "failed to parse `{}` as UTC datetime (in seconds) for key `",
+                            stringify!($field),
+                            "`"
+                        ),
+                        sec,
+                    )
+                )),
+            };
+        }
+    };
     (@case $map:ident $key:ident $language_tag_opt:ident
      Option(DateTime(Seconds($field:ident)))) => {
         if $field.is_some() {
@@ -470,6 +512,23 @@ macro_rules! deserialize_fields {
         let boolean = $map.next_value::<Option<Boolean>>()?;
         $field = boolean.map(|b| b.0);
     };
+    (@case $map:ident $key:ident $language_tag_opt:ident
+     Option(ObjectOrString($field:ident))) => {
+        if $field.is_some() {
+            return Err(serde::de::Error::duplicate_field(stringify!($field)));
+        } else if let Some(language_tag) = $language_tag_opt {
+            return Err(
+                serde::de::Error::custom(
+                    format!(
+                        concat!("unexpected language tag `{}` for key `", stringify!($field), "`"),
+                        language_tag.as_ref()
+                    )
+                )
+            );
+        }
+        let value = $map.next_value::<Option<ObjectOrString<_>>>()?;
+        $field = value.map(|v| v.0);
+    };
     (@case $map:ident $key:ident $language_tag_opt:ident Option($field:ident)) => {
         if $field.is_some() {
             return Err(serde::de::Error::duplicate_field(stringify!($field)));
@@ -524,6 +583,16 @@ macro_rules! deserialize_fields {
             }
         ]
     };
+    (@struct_recurs [$($struct_type:tt)+] {
+        $($name:ident: $e:expr),* =>
+            [Option(DateTime(Seconds($field_new:ident, $raw_new:ident)))] $([$($entry:tt)+])*
+    }) => {
+        deserialize_fields![
+            @struct_recurs [$($struct_type)+] {
+                $($name: $e,)* $field_new: $field_new, $raw_new: $raw_new => $([$($entry)+])*
+            }
+        ]
+    };
     (@struct_recurs [$($struct_type:tt)+] {
         $($name:ident: $e:expr),* => [Option(DateTime(Seconds($field_new:ident)))] $([$($entry:tt)+])*
     }) => {
@@ -542,6 +611,15 @@ macro_rules! deserialize_fields {
             }
         ]
     };
+    (@struct_recurs [$($struct_type:tt)+] {
+        $($name:ident: $e:expr),* => [Option(ObjectOrString($field_new:ident))] $([$($entry:tt)+])*
+    }) => {
+        deserialize_fields![
+            @struct_recurs [$($struct_type)+] {
+                $($name: $e,)* $field_new: $field_new => $([$($entry)+])*
+            }
+        ]
+    };
     (@struct_recurs [$($struct_type:tt)+] {
         $($name:ident: $e:expr),* => [Option($field_new:ident)] $([$($entry:tt)+])*
     }) => {
@@ -614,6 +692,13 @@ macro_rules! serialize_fields {
             $map.serialize_entry(stringify!($field), &$field.as_secs())?;
         }
     };
+    (@case $self:ident $map:ident Option(DateTime(Seconds($field:ident, $raw:ident)))) => {
+        if let Some(ref $raw) = $self.$raw {
+            $map.serialize_entry(stringify!($field), $raw)?;
+        } else if let Some(ref $field) = $self.$field {
+            $map.serialize_entry(stringify!($field), &utc_to_seconds(&$field))?;
+        }
+    };
     (@case $self:ident $map:ident Option(DateTime(Seconds($field:ident)))) => {
         if let Some(ref $field) = $self.$field {
             $map.serialize_entry(stringify!($field), &utc_to_seconds(&$field))?;
@@ -0,0 +1,402 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use http::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
+use http::method::Method;
+use http::status::StatusCode;
+use oauth2::{AuthType, AuthUrl, ClientId, ClientSecret, ErrorResponse, RequestTokenError};
+use url::{form_urlencoded, Url};
+
+use crate::http_utils::{
+    auth_basic, check_content_type, MIME_TYPE_FORM_URLENCODED, MIME_TYPE_JSON,
+};
+use crate::{
+    AsyncHttpClient, HttpRequest, HttpResponse, PushedAuthorizationRequestUri,
+    PushedAuthorizationRequestUrl, SyncHttpClient,
+};
+
+///
+/// A request to the pushed authorization request endpoint
+/// ([RFC 9126](https://tools.ietf.org/html/rfc9126)).
+///
+/// See [`Client::push_authorization_request`](crate::Client::push_authorization_request).
+///
+#[derive(Debug)]
+pub struct PushedAuthorizationRequest<'a, TE>
+where
+    TE: ErrorResponse,
+{
+    pub(crate) url: &'a PushedAuthorizationRequestUrl,
+    pub(crate) client_id: &'a ClientId,
+    pub(crate) client_secret: Option<&'a ClientSecret>,
+    pub(crate) auth_type: &'a AuthType,
+    pub(crate) params: Vec<(String, String)>,
+    // Error that occurred while signing the client assertion (e.g., for private_key_jwt
+    // authentication), which is reported when the request is submitted.
+    pub(crate) client_assertion_error: Option<String>,
+    pub(crate) _phantom: PhantomData<TE>,
+}
+impl<'a, TE> PushedAuthorizationRequest<'a, TE>
+where
+    TE: ErrorResponse + 'static,
+{
+    ///
+    /// Returns the authorization request parameters that will be pushed to the provider.
+    ///
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    ///
+    /// Submits this request to the pushed authorization request endpoint using the specified
+    /// synchronous HTTP client.
+    ///
+    pub fn request<HC, RE>(
+        self,
+        http_client: HC,
+    ) -> Result<PushedAuthorizationResponse, RequestTokenError<RE, TE>>
+    where
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        http_client
+            .call(self.prepare_request()?)
+            .map_err(RequestTokenError::Request)
+            .and_then(Self::pushed_authorization_response)
+    }
+
+    ///
+    /// Submits this request to the pushed authorization request endpoint using the specified
+    /// asynchronous HTTP client.
+    ///
+    pub async fn request_async<C, F, RE>(
+        self,
+        http_client: C,
+    ) -> Result<PushedAuthorizationResponse, RequestTokenError<RE, TE>>
+    where
+        C: AsyncHttpClient<Error = RE, Future = F>,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        let http_request = self.prepare_request()?;
+        let http_response = http_client
+            .call(http_request)
+            .await
+            .map_err(RequestTokenError::Request)?;
+        Self::pushed_authorization_response(http_response)
+    }
+
+    fn prepare_request<RE>(&self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        if let Some(ref err) = self.client_assertion_error {
+            return Err(RequestTokenError::Other(format!(
+                "Failed to sign client assertion: {}",
+                err
+            )));
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.append(ACCEPT, HeaderValue::from_static(MIME_TYPE_JSON));
+        headers.append(
+            CONTENT_TYPE,
+            HeaderValue::from_static(MIME_TYPE_FORM_URLENCODED),
+        );
+
+        let mut body = form_urlencoded::Serializer::new(String::new());
+        body.extend_pairs(self.params.iter());
+
+        // The client_id is always included among the authorization request parameters, so only
+        // the secret needs to be added for request body authentication.
+        if let Some(client_secret) = self.client_secret {
+            match self.auth_type {
+                AuthType::RequestBody => {
+                    body.append_pair("client_secret", client_secret.secret());
+                }
+                // HTTP Basic authentication is the default (see RFC 6749 Section 2.3.1).
+                _ => {
+                    let (auth_header, auth_value) = auth_basic(self.client_id, client_secret);
+                    headers.append(auth_header, auth_value);
+                }
+            }
+        }
+
+        Ok(HttpRequest {
+            url: self.url.url().clone(),
+            method: Method::POST,
+            headers,
+            body: body.finish().into_bytes(),
+        })
+    }
+
+    fn pushed_authorization_response<RE>(
+        http_response: HttpResponse,
+    ) -> Result<PushedAuthorizationResponse, RequestTokenError<RE, TE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        check_content_type(&http_response.headers, MIME_TYPE_JSON).map_err(|err_msg| {
+            RequestTokenError::Other(format!(
+                "{} (HTTP status code {})",
+                err_msg, http_response.status_code
+            ))
+        })?;
+
+        // RFC 9126 specifies a 201 Created status for successful responses, but we also accept 200
+        // OK for interoperability.
+        if http_response.status_code != StatusCode::CREATED
+            && http_response.status_code != StatusCode::OK
+        {
+            let reason = http_response.body.as_slice();
+            if reason.is_empty() {
+                return Err(RequestTokenError::Other(
+                    "Server returned empty error response".to_string(),
+                ));
+            }
+            return Err(serde_path_to_error::deserialize::<_, TE>(
+                &mut serde_json::Deserializer::from_slice(reason),
+            )
+            .map(RequestTokenError::ServerResponse)
+            .unwrap_or_else(|err| RequestTokenError::Parse(err, reason.to_vec())));
+        }
+
+        serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(
+            &http_response.body,
+        ))
+        .map_err(|err| RequestTokenError::Parse(err, http_response.body))
+    }
+}
+
+///
+/// Successful response from the pushed authorization request endpoint.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct PushedAuthorizationResponse {
+    request_uri: PushedAuthorizationRequestUri,
+    expires_in: u64,
+}
+impl PushedAuthorizationResponse {
+    ///
+    /// Instantiates a new pushed authorization response.
+    ///
+    pub fn new(request_uri: PushedAuthorizationRequestUri, expires_in: Duration) -> Self {
+        Self {
+            request_uri,
+            expires_in: expires_in.as_secs(),
+        }
+    }
+
+    ///
+    /// Returns the reference to the pushed authorization request, which should be passed to the
+    /// authorization endpoint as the `request_uri` parameter.
+    ///
+    pub fn request_uri(&self) -> &PushedAuthorizationRequestUri {
+        &self.request_uri
+    }
+
+    ///
+    /// Returns the lifetime of the `request_uri`.
+    ///
+    pub fn expires_in(&self) -> Duration {
+        Duration::from_secs(self.expires_in)
+    }
+
+    ///
+    /// Returns the URL to which the user should be redirected in order to complete the pushed
+    /// authorization request.
+    ///
+    /// Per [Section 4 of RFC 9126](https://tools.ietf.org/html/rfc9126#section-4), the URL only
+    /// includes the `client_id` and `request_uri` parameters.
+    ///
+    pub fn authorization_url(&self, auth_url: &AuthUrl, client_id: &ClientId) -> Url {
+        let mut url = auth_url.url().clone();
+        url.query_pairs_mut()
+            .append_pair("client_id", client_id)
+            .append_pair("request_uri", &self.request_uri);
+        url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::header::{AUTHORIZATION, CONTENT_TYPE};
+    use http::{Method, StatusCode};
+    use oauth2::{AuthType, AuthUrl, ClientId, CsrfToken, RedirectUrl, RequestTokenError, Scope};
+
+    use crate::core::{
+        CoreAuthenticationFlow, CoreClient, CoreErrorResponseType, CoreJwsSigningAlgorithm,
+        CoreProviderMetadata, CoreResponseType, CoreSubjectIdentifierType,
+    };
+    use crate::tests::mock_json_response;
+    use crate::{
+        ConfigurationError, EmptyAdditionalProviderMetadata, HttpRequest, IssuerUrl,
+        JsonWebKeySetUrl, Nonce, PushedAuthorizationRequestUri, PushedAuthorizationRequestUrl,
+        PushedAuthorizationResponse, ResponseTypes,
+    };
+
+    fn new_client() -> CoreClient {
+        crate::tests::new_client()
+            .set_redirect_uri(RedirectUrl::new("http://localhost:8888/".to_string()).unwrap())
+            .set_pushed_authorization_request_uri(
+                PushedAuthorizationRequestUrl::new("https://example/par".to_string()).unwrap(),
+            )
+    }
+
+    #[test]
+    fn test_push_authorization_request() {
+        let client = new_client();
+        let (request, csrf_state, nonce) = client
+            .push_authorization_request(
+                client
+                    .authorize_url(
+                        CoreAuthenticationFlow::AuthorizationCode,
+                        || CsrfToken::new("CSRF123".to_string()),
+                        || Nonce::new("NONCE456".to_string()),
+                    )
+                    .add_scope(Scope::new("email".to_string())),
+            )
+            .unwrap();
+        assert_eq!(csrf_state.secret(), "CSRF123");
+        assert_eq!(nonce.secret(), "NONCE456");
+
+        let response = request
+            .request(|request: HttpRequest| {
+                assert_eq!(request.url.as_str(), "https://example/par");
+                assert_eq!(request.method, Method::POST);
+                assert_eq!(
+                    request.headers.get(CONTENT_TYPE).unwrap(),
+                    "application/x-www-form-urlencoded"
+                );
+                assert_eq!(
+                    request.headers.get(AUTHORIZATION).unwrap(),
+                    "Basic YWFhOmJiYg=="
+                );
+                assert_eq!(
+                    String::from_utf8(request.body).unwrap(),
+                    "response_type=code&client_id=aaa&state=CSRF123\
+                     &redirect_uri=http%3A%2F%2Flocalhost%3A8888%2F&scope=openid+email\
+                     &nonce=NONCE456"
+                );
+                mock_json_response(
+                    StatusCode::CREATED,
+                    "{\
+                        \"request_uri\": \"urn:ietf:params:oauth:request_uri:6esc_11ACC5bwc014ltc14eY22c\",\
+                        \"expires_in\": 60\
+                    }",
+                )
+            })
+            .unwrap();
+        assert_eq!(
+            response,
+            PushedAuthorizationResponse::new(
+                PushedAuthorizationRequestUri::new(
+                    "urn:ietf:params:oauth:request_uri:6esc_11ACC5bwc014ltc14eY22c".to_string()
+                ),
+                Duration::from_secs(60),
+            )
+        );
+        assert_eq!(response.expires_in(), Duration::from_secs(60));
+
+        assert_eq!(
+            client.pushed_authorization_url(&response).as_str(),
+            "https://example/authorize?client_id=aaa\
+             &request_uri=urn%3Aietf%3Aparams%3Aoauth%3Arequest_uri%3A6esc_11ACC5bwc014ltc14eY22c"
+        );
+    }
+
+    #[test]
+    fn test_push_authorization_request_body_auth_error() {
+        let client = new_client().set_auth_type(AuthType::RequestBody);
+        let (request, _, _) = client
+            .push_authorization_request(client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            ))
+            .unwrap();
+
+        match request.request(|request: HttpRequest| {
+            assert!(request.headers.get(AUTHORIZATION).is_none());
+            assert!(String::from_utf8(request.body)
+                .unwrap()
+                .ends_with("&nonce=NONCE456&client_secret=bbb"));
+            mock_json_response(
+                StatusCode::BAD_REQUEST,
+                "{\"error\": \"invalid_request\", \"error_description\": \"bad redirect_uri\"}",
+            )
+        }) {
+            Err(RequestTokenError::ServerResponse(err)) => {
+                assert_eq!(*err.error(), CoreErrorResponseType::InvalidRequest);
+                assert_eq!(
+                    err.error_description(),
+                    Some(&"bad redirect_uri".to_string())
+                );
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pushed_authorization_request_endpoint_from_provider_metadata() {
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        );
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata.clone(),
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        assert!(matches!(
+            client.push_authorization_request(client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                CsrfToken::new_random,
+                Nonce::new_random,
+            )),
+            Err(ConfigurationError::MissingUrl(
+                "pushed authorization request"
+            ))
+        ));
+
+        let provider_metadata: CoreProviderMetadata = serde_json::from_value(
+            serde_json::to_value(
+                provider_metadata
+                    .set_pushed_authorization_request_endpoint(Some(
+                        PushedAuthorizationRequestUrl::new("https://example/par".to_string())
+                            .unwrap(),
+                    ))
+                    .set_require_pushed_authorization_requests(Some(true)),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            provider_metadata.require_pushed_authorization_requests(),
+            Some(true)
+        );
+
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata,
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        let (request, _, _) = client
+            .push_authorization_request(client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                CsrfToken::new_random,
+                Nonce::new_random,
+            ))
+            .unwrap();
+        assert_eq!(request.url.as_str(), "https://example/par");
+        assert!(request.client_secret.is_none());
+    }
+}
@@ -0,0 +1,207 @@
+use std::fmt::Display;
+
+use oauth2::{
+    ErrorResponseType, RefreshToken, RequestTokenError, StandardErrorResponse,
+    TokenResponse as OAuth2TokenResponse, TokenType,
+};
+use thiserror::Error;
+
+const INVALID_GRANT: &str = "invalid_grant";
+
+///
+/// Refresh token to persist after a refresh token grant.
+///
+/// Many authorization servers rotate refresh tokens, returning a new refresh token in each
+/// refresh token response and invalidating the previous one. Others omit the `refresh_token`
+/// field, in which case the previous refresh token remains valid (see
+/// [Section 6 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-6)). This type determines
+/// which of the two refresh tokens should be stored for the next refresh.
+///
+#[derive(Clone, Debug)]
+pub enum RefreshTokenUpdate {
+    ///
+    /// The authorization server issued a new refresh token, which replaces the previous one.
+    ///
+    Rotated(RefreshToken),
+    ///
+    /// The authorization server did not issue a new refresh token, so the previous refresh token
+    /// should continue to be used.
+    ///
+    Unchanged(RefreshToken),
+}
+impl RefreshTokenUpdate {
+    ///
+    /// Determines the refresh token to persist, given the refresh token that was sent in the
+    /// request and the resulting token response.
+    ///
+    pub fn new<TR, TT>(previous: RefreshToken, token_response: &TR) -> Self
+    where
+        TR: OAuth2TokenResponse<TT>,
+        TT: TokenType,
+    {
+        match token_response.refresh_token() {
+            Some(refresh_token) if refresh_token.secret() != previous.secret() => {
+                RefreshTokenUpdate::Rotated(refresh_token.clone())
+            }
+            _ => RefreshTokenUpdate::Unchanged(previous),
+        }
+    }
+
+    ///
+    /// Returns whether the refresh token was rotated.
+    ///
+    pub fn is_rotated(&self) -> bool {
+        matches!(self, RefreshTokenUpdate::Rotated(_))
+    }
+
+    ///
+    /// Returns the refresh token to persist.
+    ///
+    pub fn refresh_token(&self) -> &RefreshToken {
+        match self {
+            RefreshTokenUpdate::Rotated(refresh_token)
+            | RefreshTokenUpdate::Unchanged(refresh_token) => refresh_token,
+        }
+    }
+
+    ///
+    /// Consumes this value and returns the refresh token to persist.
+    ///
+    pub fn into_refresh_token(self) -> RefreshToken {
+        match self {
+            RefreshTokenUpdate::Rotated(refresh_token)
+            | RefreshTokenUpdate::Unchanged(refresh_token) => refresh_token,
+        }
+    }
+}
+
+///
+/// Error returned by a refresh token grant.
+///
+/// Converting a [`RequestTokenError`] into this type (e.g., using `?` or
+/// `map_err(RefreshTokenError::from)`) distinguishes `invalid_grant` errors, which indicate that
+/// the refresh token is no longer valid. With refresh token rotation, this typically means that
+/// the refresh token was already used (i.e., it was replayed or rotated out), and the End-User
+/// must reauthenticate.
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RefreshTokenError<RE, T>
+where
+    RE: std::error::Error + 'static,
+    T: ErrorResponseType + Display + 'static,
+{
+    ///
+    /// The refresh token is invalid, expired, revoked, or was already rotated out.
+    ///
+    #[error("Refresh token is no longer valid")]
+    InvalidGrant(StandardErrorResponse<T>),
+    ///
+    /// Any other error returned by the token request.
+    ///
+    #[error(transparent)]
+    Request(RequestTokenError<RE, StandardErrorResponse<T>>),
+}
+impl<RE, T> From<RequestTokenError<RE, StandardErrorResponse<T>>> for RefreshTokenError<RE, T>
+where
+    RE: std::error::Error + 'static,
+    T: AsRef<str> + ErrorResponseType + Display + 'static,
+{
+    fn from(err: RequestTokenError<RE, StandardErrorResponse<T>>) -> Self {
+        match err {
+            RequestTokenError::ServerResponse(err) if err.error().as_ref() == INVALID_GRANT => {
+                RefreshTokenError::InvalidGrant(err)
+            }
+            err => RefreshTokenError::Request(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::RefreshToken;
+    use url::form_urlencoded;
+
+    use crate::core::{CoreErrorResponseType, CoreTokenResponse};
+    use crate::tests::{mock_json_response, new_client};
+    use crate::{OAuth2TokenResponse, RefreshTokenError, RefreshTokenUpdate, RequestTokenError};
+
+    fn refresh(
+        refresh_token: &RefreshToken,
+        status_code: http::StatusCode,
+        body: &'static str,
+    ) -> Result<CoreTokenResponse, RefreshTokenError<std::io::Error, CoreErrorResponseType>> {
+        Ok(new_client()
+            .exchange_refresh_token(refresh_token)
+            .request(|request| {
+                assert!(form_urlencoded::parse(&request.body)
+                    .any(|(name, value)| name == "refresh_token" && value == "old_token"));
+                mock_json_response(status_code, body)
+            })?)
+    }
+
+    #[test]
+    fn test_refresh_token_rotated() {
+        let old_token = RefreshToken::new("old_token".to_string());
+
+        let rotated = refresh(
+            &old_token,
+            http::StatusCode::OK,
+            "{\"access_token\": \"12/34\", \"token_type\": \"bearer\", \
+             \"refresh_token\": \"new_token\"}",
+        )
+        .unwrap();
+        assert_eq!(rotated.refresh_token().unwrap().secret(), "new_token");
+        let update = RefreshTokenUpdate::new(old_token.clone(), &rotated);
+        assert!(update.is_rotated());
+        assert_eq!(update.refresh_token().secret(), "new_token");
+
+        let unchanged = refresh(
+            &old_token,
+            http::StatusCode::OK,
+            "{\"access_token\": \"12/34\", \"token_type\": \"bearer\"}",
+        )
+        .unwrap();
+        let update = RefreshTokenUpdate::new(old_token.clone(), &unchanged);
+        assert!(!update.is_rotated());
+        assert_eq!(update.into_refresh_token().secret(), "old_token");
+
+        // Some servers echo back the same refresh token.
+        let echoed = refresh(
+            &old_token,
+            http::StatusCode::OK,
+            "{\"access_token\": \"12/34\", \"token_type\": \"bearer\", \
+             \"refresh_token\": \"old_token\"}",
+        )
+        .unwrap();
+        assert!(!RefreshTokenUpdate::new(old_token, &echoed).is_rotated());
+    }
+
+    #[test]
+    fn test_refresh_token_invalid_grant() {
+        let old_token = RefreshToken::new("old_token".to_string());
+
+        match refresh(
+            &old_token,
+            http::StatusCode::BAD_REQUEST,
+            "{\"error\": \"invalid_grant\", \"error_description\": \"token reused\"}",
+        ) {
+            Err(RefreshTokenError::InvalidGrant(err)) => {
+                assert_eq!(*err.error(), CoreErrorResponseType::InvalidGrant);
+                assert_eq!(err.error_description().unwrap(), "token reused");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match refresh(
+            &old_token,
+            http::StatusCode::BAD_REQUEST,
+            "{\"error\": \"invalid_client\"}",
+        ) {
+            Err(RefreshTokenError::Request(RequestTokenError::ServerResponse(err))) => {
+                assert_eq!(*err.error(), CoreErrorResponseType::InvalidClient)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -4,27 +4,29 @@ use std::marker::PhantomData;
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
-use http::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
+use http::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE, WWW_AUTHENTICATE};
 use http::method::Method;
 use http::status::StatusCode;
 use serde::de::{Deserialize, DeserializeOwned, Deserializer, MapAccess, Visitor};
 use serde::ser::SerializeMap;
 use serde::{Serialize, Serializer};
+use serde_json::Value;
 use thiserror::Error;
 
 use super::http_utils::{auth_bearer, check_content_type, MIME_TYPE_JSON};
+use super::jwt::{sign_compact_jws, JsonWebTokenError};
 use super::types::helpers::{serde_utc_seconds_opt, split_language_tag_key};
 use super::types::{
     ApplicationType, AuthenticationContextClass, ClientAuthMethod, ClientConfigUrl,
-    ClientContactEmail, ClientName, ClientUrl, GrantType, InitiateLoginUrl, JsonWebKeySetUrl,
-    JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm,
-    JwsSigningAlgorithm, LocalizedClaim, LogoUrl, PolicyUrl, RegistrationAccessToken,
-    RegistrationUrl, RequestUrl, ResponseType, ResponseTypes, SectorIdentifierUrl,
-    SubjectIdentifierType, ToSUrl,
+    ClientContactEmail, ClientName, ClientUrl, GrantType, InitiateLoginUrl, IssuerUrl,
+    JsonWebKeyId, JsonWebKeySetUrl, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, LocalizedClaim, LogoUrl, PolicyUrl,
+    PrivateSigningKey, RegistrationAccessToken, RegistrationUrl, RequestUrl, ResponseType,
+    ResponseTypes, SectorIdentifierUrl, SoftwareStatement, SubjectIdentifierType, ToSUrl,
 };
 use super::{
-    AccessToken, ClientId, ClientSecret, ErrorResponseType, HttpRequest, HttpResponse, JsonWebKey,
-    JsonWebKeySet, RedirectUrl, StandardErrorResponse,
+    AccessToken, AsyncHttpClient, ClientId, ClientSecret, ErrorResponseType, HttpRequest,
+    HttpResponse, JsonWebKey, JsonWebKeySet, RedirectUrl, StandardErrorResponse, SyncHttpClient,
 };
 
 ///
@@ -171,6 +173,54 @@ where
     pub fn additional_metadata_mut(&mut self) -> &mut A {
         &mut self.additional_metadata
     }
+
+    ///
+    /// Signs a software statement asserting this client metadata, as described in
+    /// [Section 2.3 of RFC 7591](https://datatracker.ietf.org/doc/html/rfc7591#section-2.3).
+    ///
+    /// The statement contains each of the client metadata values along with the `iss` claim
+    /// identifying the party vouching for the client software (e.g., a federation operator) and
+    /// the `iat` claim.
+    ///
+    pub fn sign_software_statement<SK>(
+        &self,
+        issuer: &IssuerUrl,
+        signing_key: &SK,
+        alg: &JS,
+    ) -> Result<SoftwareStatement, JsonWebTokenError>
+    where
+        SK: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        let claims = SoftwareStatementClaims {
+            iss: issuer,
+            iat: Utc::now().timestamp(),
+            metadata: self,
+        };
+
+        let header = SoftwareStatementHeader {
+            alg,
+            kid: signing_key.as_verification_key().key_id().cloned(),
+        };
+        sign_compact_jws(&header, &claims, |signing_input| {
+            signing_key.sign(alg, signing_input)
+        })
+        .map(SoftwareStatement::new)
+    }
+}
+
+#[derive(Serialize)]
+struct SoftwareStatementClaims<'a, M> {
+    iss: &'a IssuerUrl,
+    iat: i64,
+    #[serde(flatten)]
+    metadata: &'a M,
+}
+
+#[derive(Serialize)]
+struct SoftwareStatementHeader<'a, JS> {
+    alg: &'a JS,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kid: Option<JsonWebKeyId>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -422,6 +472,7 @@ where
 {
     client_metadata: ClientMetadata<AC, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
     initial_access_token: Option<AccessToken>,
+    software_statement: Option<SoftwareStatement>,
     _phantom: PhantomData<(AR, ET)>,
 }
 impl<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>
@@ -449,6 +500,7 @@ where
         Self {
             client_metadata: ClientMetadata::new(redirect_uris, additional_metadata),
             initial_access_token: None,
+            software_statement: None,
             _phantom: PhantomData,
         }
     }
@@ -466,12 +518,14 @@ where
         ClientRegistrationError<ET, RE>,
     >
     where
-        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        HC: SyncHttpClient<Error = RE>,
         RE: std::error::Error + 'static,
     {
         self.prepare_registration(registration_endpoint)
             .and_then(|http_request| {
-                http_client(http_request).map_err(ClientRegistrationError::Request)
+                http_client
+                    .call(http_request)
+                    .map_err(ClientRegistrationError::Request)
             })
             .and_then(Self::register_response)
     }
@@ -490,11 +544,12 @@ where
     >
     where
         F: Future<Output = Result<HttpResponse, RE>>,
-        HC: FnOnce(HttpRequest) -> F,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
         RE: std::error::Error + 'static,
     {
         let http_request = self.prepare_registration(registration_endpoint)?;
-        let http_response = http_client(http_request)
+        let http_response = http_client
+            .call(http_request)
             .await
             .map_err(ClientRegistrationError::Request)?;
         Self::register_response(http_response)
@@ -507,9 +562,18 @@ where
     where
         RE: std::error::Error + 'static,
     {
-        let request_json = serde_json::to_string(self.client_metadata())
-            .map_err(ClientRegistrationError::Serialize)?
-            .into_bytes();
+        let mut request_json = serde_json::to_value(self.client_metadata())
+            .map_err(ClientRegistrationError::Serialize)?;
+        if let (Some(software_statement), Value::Object(request_json)) =
+            (self.software_statement(), &mut request_json)
+        {
+            request_json.insert(
+                "software_statement".to_string(),
+                Value::String(software_statement.to_string()),
+            );
+        }
+        let request_json =
+            serde_json::to_vec(&request_json).map_err(ClientRegistrationError::Serialize)?;
 
         let auth_header_opt = self.initial_access_token().map(auth_bearer);
 
@@ -544,7 +608,20 @@ where
         // Spec says that a successful response SHOULD use 201 Created, and a registration error
         // condition returns (no "SHOULD") 400 Bad Request. For now, only accept these two status
         // codes. We may need to relax the success status to improve interoperability.
-        if http_response.status_code != StatusCode::CREATED
+        Self::registration_response(http_response, StatusCode::CREATED)
+    }
+
+    fn registration_response<RE>(
+        http_response: HttpResponse,
+        success_status_code: StatusCode,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        RE: std::error::Error + 'static,
+    {
+        if http_response.status_code != success_status_code
             && http_response.status_code != StatusCode::BAD_REQUEST
         {
             return Err(ClientRegistrationError::Response(
@@ -570,10 +647,18 @@ where
         })?;
 
         if http_response.status_code == StatusCode::BAD_REQUEST {
+            // Error responses that don't follow Section 3.2.2 of RFC 7591 are returned along with
+            // the raw response body so that callers can still inspect them.
             let response_error: StandardErrorResponse<ET> = serde_path_to_error::deserialize(
                 &mut serde_json::Deserializer::from_str(&response_body),
             )
-            .map_err(ClientRegistrationError::Parse)?;
+            .map_err(|err| {
+                ClientRegistrationError::Response(
+                    http_response.status_code,
+                    response_body.clone().into_bytes(),
+                    format!("failed to parse error response: {}", err),
+                )
+            })?;
             return Err(ClientRegistrationError::ServerResponse(response_error));
         }
 
@@ -602,6 +687,24 @@ where
         self
     }
 
+    ///
+    /// Returns the software statement associated with this registration request.
+    ///
+    pub fn software_statement(&self) -> Option<&SoftwareStatement> {
+        self.software_statement.as_ref()
+    }
+    ///
+    /// Sets the software statement for this request, which is sent verbatim to the registration
+    /// endpoint.
+    ///
+    /// A software statement may be signed using
+    /// [`ClientMetadata::sign_software_statement`].
+    ///
+    pub fn set_software_statement(mut self, software_statement: Option<SoftwareStatement>) -> Self {
+        self.software_statement = software_statement;
+        self
+    }
+
     field_getters_setters![
         pub self [self.client_metadata.standard_metadata] ["client metadata value"] {
             set_redirect_uris -> redirect_uris[Vec<RedirectUrl>],
@@ -828,6 +931,33 @@ where
         &mut self.client_metadata.additional_metadata
     }
 
+    ///
+    /// Returns the names of the client metadata fields in `asserted` (e.g., the metadata signed in
+    /// a software statement or sent in the registration request) whose values the server
+    /// replaced or omitted in this response.
+    ///
+    /// Per [Section 3.2.1 of RFC 7591](
+    /// https://datatracker.ietf.org/doc/html/rfc7591#section-3.2.1), the server may substitute
+    /// its own values for any requested metadata, so clients should check that any values they
+    /// rely upon were accepted. Field names are returned in sorted order.
+    ///
+    pub fn overridden_metadata(
+        &self,
+        asserted: &ClientMetadata<AC, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+    ) -> Result<Vec<String>, serde_json::Error> {
+        let registered = serde_json::to_value(&self.client_metadata)?;
+        let mut overridden = match serde_json::to_value(asserted)? {
+            Value::Object(asserted) => asserted
+                .into_iter()
+                .filter(|(name, value)| registered.get(name) != Some(value))
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            _ => Vec::new(),
+        };
+        overridden.sort();
+        Ok(overridden)
+    }
+
     ///
     /// Returns additional response fields.
     ///
@@ -842,7 +972,357 @@ where
     }
 }
 
-// TODO: implement client configuration endpoint request (Section 4)
+///
+/// Client configuration endpoint operations, as described in
+/// [RFC 7592](https://datatracker.ietf.org/doc/html/rfc7592).
+///
+/// These operations read, update, and delete a previously registered client using the
+/// `registration_client_uri` and `registration_access_token` returned in the
+/// [`ClientRegistrationResponse`].
+///
+#[derive(Clone, Debug)]
+pub struct ClientManagement<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>
+where
+    AC: AdditionalClientMetadata,
+    AR: AdditionalClientRegistrationResponse,
+    AT: ApplicationType,
+    CA: ClientAuthMethod,
+    ET: RegisterErrorResponseType,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RT: ResponseType,
+    S: SubjectIdentifierType,
+{
+    registration_client_uri: ClientConfigUrl,
+    registration_access_token: RegistrationAccessToken,
+    #[allow(clippy::type_complexity)]
+    _phantom: PhantomData<(AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S)>,
+}
+impl<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>
+    ClientManagement<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>
+where
+    AC: AdditionalClientMetadata,
+    AR: AdditionalClientRegistrationResponse,
+    AT: ApplicationType,
+    CA: ClientAuthMethod,
+    ET: RegisterErrorResponseType + Send + Sync,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RT: ResponseType,
+    S: SubjectIdentifierType + Send + Sync,
+{
+    ///
+    /// Instantiates client configuration endpoint operations for the client with the given
+    /// configuration endpoint and registration access token.
+    ///
+    pub fn new(
+        registration_client_uri: ClientConfigUrl,
+        registration_access_token: RegistrationAccessToken,
+    ) -> Self {
+        Self {
+            registration_client_uri,
+            registration_access_token,
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Instantiates client configuration endpoint operations from a registration response, or
+    /// returns `None` if the response lacks a `registration_client_uri` or
+    /// `registration_access_token`.
+    ///
+    pub fn from_registration_response(
+        registration_response: &ClientRegistrationResponse<
+            AC,
+            AR,
+            AT,
+            CA,
+            G,
+            JE,
+            JK,
+            JS,
+            JT,
+            JU,
+            K,
+            RT,
+            S,
+        >,
+    ) -> Option<Self> {
+        Some(Self::new(
+            registration_response.registration_client_uri()?.clone(),
+            registration_response.registration_access_token()?.clone(),
+        ))
+    }
+
+    ///
+    /// Returns the client configuration endpoint URL.
+    ///
+    pub fn registration_client_uri(&self) -> &ClientConfigUrl {
+        &self.registration_client_uri
+    }
+
+    ///
+    /// Returns the registration access token.
+    ///
+    pub fn registration_access_token(&self) -> &RegistrationAccessToken {
+        &self.registration_access_token
+    }
+
+    ///
+    /// Reads the current client configuration using the specified synchronous HTTP client.
+    ///
+    pub fn read<HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        http_client
+            .call(self.prepare_request(Method::GET, Vec::new()))
+            .map_err(ClientRegistrationError::Request)
+            .and_then(Self::configuration_response)
+    }
+
+    ///
+    /// Reads the current client configuration using the specified asynchronous HTTP client.
+    ///
+    pub async fn read_async<F, HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
+        RE: std::error::Error + 'static,
+    {
+        let http_response = http_client
+            .call(self.prepare_request(Method::GET, Vec::new()))
+            .await
+            .map_err(ClientRegistrationError::Request)?;
+        Self::configuration_response(http_response)
+    }
+
+    ///
+    /// Replaces the client configuration with the client metadata contained in `registration`
+    /// using the specified synchronous HTTP client.
+    ///
+    /// The full client metadata is sent along with the `client_id` and `client_secret` (if any),
+    /// as required by [Section 2.2 of RFC 7592](
+    /// https://datatracker.ietf.org/doc/html/rfc7592#section-2.2). Any metadata omitted from
+    /// `registration` may be reset to its default value by the server. Typically, `registration`
+    /// is the response returned by [`read`](Self::read) or by the initial registration, modified
+    /// as needed. The refreshed registration response is returned on success.
+    ///
+    pub fn update<HC, RE>(
+        &self,
+        registration: &ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        http_client: HC,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        self.prepare_update(registration)
+            .and_then(|http_request| {
+                http_client
+                    .call(http_request)
+                    .map_err(ClientRegistrationError::Request)
+            })
+            .and_then(Self::configuration_response)
+    }
+
+    ///
+    /// Replaces the client configuration with the client metadata contained in `registration`
+    /// using the specified asynchronous HTTP client.
+    ///
+    /// See [`update`](Self::update).
+    ///
+    pub async fn update_async<F, HC, RE>(
+        &self,
+        registration: &ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        http_client: HC,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
+        RE: std::error::Error + 'static,
+    {
+        let http_request = self.prepare_update(registration)?;
+        let http_response = http_client
+            .call(http_request)
+            .await
+            .map_err(ClientRegistrationError::Request)?;
+        Self::configuration_response(http_response)
+    }
+
+    ///
+    /// Deletes the client registration using the specified synchronous HTTP client.
+    ///
+    pub fn delete<HC, RE>(&self, http_client: HC) -> Result<(), ClientRegistrationError<ET, RE>>
+    where
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        http_client
+            .call(self.prepare_request(Method::DELETE, Vec::new()))
+            .map_err(ClientRegistrationError::Request)
+            .and_then(Self::delete_response)
+    }
+
+    ///
+    /// Deletes the client registration using the specified asynchronous HTTP client.
+    ///
+    pub async fn delete_async<F, HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> Result<(), ClientRegistrationError<ET, RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
+        RE: std::error::Error + 'static,
+    {
+        let http_response = http_client
+            .call(self.prepare_request(Method::DELETE, Vec::new()))
+            .await
+            .map_err(ClientRegistrationError::Request)?;
+        Self::delete_response(http_response)
+    }
+
+    fn prepare_request(&self, method: Method, body: Vec<u8>) -> HttpRequest {
+        let (auth_header, auth_value) = auth_bearer(&AccessToken::new(
+            self.registration_access_token.secret().to_string(),
+        ));
+
+        let mut headers = HeaderMap::new();
+        headers.append(ACCEPT, HeaderValue::from_static(MIME_TYPE_JSON));
+        if !body.is_empty() {
+            headers.append(CONTENT_TYPE, HeaderValue::from_static(MIME_TYPE_JSON));
+        }
+        headers.append(auth_header, auth_value);
+
+        HttpRequest {
+            url: self.registration_client_uri.url().clone(),
+            method,
+            headers,
+            body,
+        }
+    }
+
+    fn prepare_update<RE>(
+        &self,
+        registration: &ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+    ) -> Result<HttpRequest, ClientRegistrationError<ET, RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        // The request must not include the registration_access_token, registration_client_uri,
+        // client_secret_expires_at, or client_id_issued_at fields, so only the client metadata is
+        // serialized along with the client credentials.
+        let mut request_json = match serde_json::to_value(&registration.client_metadata)
+            .map_err(ClientRegistrationError::Serialize)?
+        {
+            serde_json::Value::Object(request_json) => request_json,
+            other => {
+                return Err(ClientRegistrationError::Other(format!(
+                    "client metadata serialized to unexpected JSON value: {}",
+                    other
+                )))
+            }
+        };
+        request_json.insert(
+            "client_id".to_string(),
+            serde_json::Value::String(registration.client_id.to_string()),
+        );
+        if let Some(client_secret) = registration.client_secret.as_ref() {
+            request_json.insert(
+                "client_secret".to_string(),
+                serde_json::Value::String(client_secret.secret().to_string()),
+            );
+        }
+
+        let request_body =
+            serde_json::to_vec(&request_json).map_err(ClientRegistrationError::Serialize)?;
+        Ok(self.prepare_request(Method::PUT, request_body))
+    }
+
+    fn check_unauthorized<RE>(
+        http_response: &HttpResponse,
+    ) -> Result<(), ClientRegistrationError<ET, RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        // Per Section 2 of RFC 7592, an invalid registration access token (including one
+        // belonging to a client that no longer exists) results in a 401 Unauthorized response.
+        if http_response.status_code == StatusCode::UNAUTHORIZED {
+            Err(ClientRegistrationError::InvalidToken(
+                http_response
+                    .headers
+                    .get(WWW_AUTHENTICATE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn configuration_response<RE>(
+        http_response: HttpResponse,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        RE: std::error::Error + 'static,
+    {
+        Self::check_unauthorized(&http_response)?;
+        ClientRegistrationRequest::<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>::registration_response(
+            http_response,
+            StatusCode::OK,
+        )
+    }
+
+    fn delete_response<RE>(
+        http_response: HttpResponse,
+    ) -> Result<(), ClientRegistrationError<ET, RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        Self::check_unauthorized(&http_response)?;
+        if http_response.status_code != StatusCode::NO_CONTENT {
+            return Err(ClientRegistrationError::Response(
+                http_response.status_code,
+                http_response.body,
+                "unexpected HTTP status code".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
 
 ///
 /// Trait representing an error returned by the dynamic client registration endpoint.
@@ -890,6 +1370,12 @@ where
     ///
     #[error("Server returned error")]
     ServerResponse(StandardErrorResponse<T>),
+    ///
+    /// The registration access token is invalid or the client no longer exists. Contains the
+    /// `WWW-Authenticate` response header, if any.
+    ///
+    #[error("Registration access token is invalid")]
+    InvalidToken(Option<String>),
 }
 
 #[cfg(test)]
@@ -897,22 +1383,31 @@ mod tests {
     use std::time::Duration;
 
     use chrono::{TimeZone, Utc};
+    use http::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+    use http::method::Method;
+    use http::status::StatusCode;
     use itertools::sorted;
     use oauth2::{ClientId, RedirectUrl};
 
     use crate::core::{
-        CoreApplicationType, CoreClientAuthMethod, CoreClientMetadata,
-        CoreClientRegistrationResponse, CoreGrantType, CoreJweContentEncryptionAlgorithm,
-        CoreJweKeyManagementAlgorithm, CoreJwsSigningAlgorithm, CoreResponseType,
-        CoreSubjectIdentifierType,
+        CoreApplicationType, CoreClientAuthMethod, CoreClientManagement, CoreClientMetadata,
+        CoreClientRegistrationRequest, CoreClientRegistrationResponse, CoreGrantType,
+        CoreJsonWebKey, CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm,
+        CoreJwsSigningAlgorithm, CoreRegisterErrorResponseType, CoreResponseType,
+        CoreRsaPrivateSigningKey, CoreSubjectIdentifierType,
     };
-    use crate::jwt::tests::TEST_RSA_PUB_KEY;
+    use crate::http_utils::MIME_TYPE_JSON;
+    use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
     use crate::{
         AuthenticationContextClass, ClientConfigUrl, ClientContactEmail, ClientName, ClientUrl,
-        JsonWebKeySet, JsonWebKeySetUrl, LanguageTag, LogoUrl, PolicyUrl, RequestUrl,
-        ResponseTypes, SectorIdentifierUrl, ToSUrl,
+        IssuerUrl, JsonWebKey, JsonWebKeyId, JsonWebKeySet, JsonWebKeySetUrl, LanguageTag, LogoUrl,
+        PolicyUrl, RegistrationAccessToken, RegistrationUrl, RequestUrl, ResponseTypes,
+        SectorIdentifierUrl, ToSUrl,
     };
 
+    use super::ClientRegistrationError;
+    use crate::{HttpRequest, HttpResponse};
+
     #[test]
     fn test_metadata_serialization() {
         // `jwks_uri` and `jwks` aren't supposed to be used together, but this test is just for
@@ -1538,4 +2033,409 @@ mod tests {
             deserialized.additional_response,
         );
     }
+
+    fn client_configuration_response(
+        status_code: StatusCode,
+        redirect_uri: &str,
+    ) -> Result<HttpResponse, std::io::Error> {
+        Ok(HttpResponse {
+            status_code,
+            headers: vec![(CONTENT_TYPE, HeaderValue::from_static(MIME_TYPE_JSON))]
+                .into_iter()
+                .collect(),
+            body: format!(
+                "{{
+                    \"client_id\": \"abcdefgh\",
+                    \"client_secret\": \"shhhh\",
+                    \"registration_access_token\": \"use_me_to_update_registration\",
+                    \"registration_client_uri\": \"https://example-provider.com/register/abcdefgh\",
+                    \"client_secret_expires_at\": 1526545306,
+                    \"redirect_uris\": [\"{}\"],
+                    \"client_name\": \"Example\"
+                }}",
+                redirect_uri
+            )
+            .into_bytes(),
+        })
+    }
+
+    fn client_management() -> CoreClientManagement {
+        CoreClientManagement::new(
+            ClientConfigUrl::new("https://example-provider.com/register/abcdefgh".to_string())
+                .unwrap(),
+            RegistrationAccessToken::new("use_me_to_update_registration".to_string()),
+        )
+    }
+
+    fn assert_management_request(request: &HttpRequest, method: Method) {
+        assert_eq!(request.method, method);
+        assert_eq!(
+            request.url.as_str(),
+            "https://example-provider.com/register/abcdefgh"
+        );
+        assert_eq!(
+            request.headers.get(AUTHORIZATION).unwrap(),
+            "Bearer use_me_to_update_registration"
+        );
+    }
+
+    #[test]
+    fn test_client_management_read() {
+        let registration = client_management()
+            .read(|request| {
+                assert_management_request(&request, Method::GET);
+                assert!(request.body.is_empty());
+                client_configuration_response(StatusCode::OK, "https://example.com/redirect-1")
+            })
+            .unwrap();
+        assert_eq!(registration.client_id().as_str(), "abcdefgh");
+        assert_eq!(
+            registration
+                .client_name()
+                .unwrap()
+                .get(None)
+                .unwrap()
+                .as_str(),
+            "Example"
+        );
+        assert_eq!(
+            *registration.redirect_uris(),
+            vec![RedirectUrl::new("https://example.com/redirect-1".to_string()).unwrap()]
+        );
+
+        // The response to a read can be used to manage the client again.
+        let management = CoreClientManagement::from_registration_response(&registration).unwrap();
+        assert_eq!(
+            management.registration_client_uri(),
+            client_management().registration_client_uri()
+        );
+        assert_eq!(
+            management.registration_access_token().secret(),
+            "use_me_to_update_registration"
+        );
+
+        match client_management().read(|_| {
+            Ok::<_, std::io::Error>(HttpResponse {
+                status_code: StatusCode::UNAUTHORIZED,
+                headers: vec![(
+                    WWW_AUTHENTICATE,
+                    HeaderValue::from_static("Bearer error=\"invalid_token\""),
+                )]
+                .into_iter()
+                .collect(),
+                body: Vec::new(),
+            })
+        }) {
+            Err(ClientRegistrationError::InvalidToken(Some(www_authenticate))) => {
+                assert_eq!(www_authenticate, "Bearer error=\"invalid_token\"")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_client_management_update() {
+        let registration: CoreClientRegistrationResponse = serde_json::from_slice(
+            &client_configuration_response(StatusCode::OK, "https://example.com/redirect-1")
+                .unwrap()
+                .body,
+        )
+        .unwrap();
+
+        let updated = client_management()
+            .update(
+                &registration.set_redirect_uris(vec![RedirectUrl::new(
+                    "https://example.com/redirect-2".to_string(),
+                )
+                .unwrap()]),
+                |request| {
+                    assert_management_request(&request, Method::PUT);
+                    assert_eq!(request.headers.get(CONTENT_TYPE).unwrap(), MIME_TYPE_JSON);
+                    assert_eq!(
+                        serde_json::from_slice::<serde_json::Value>(&request.body).unwrap(),
+                        serde_json::json!({
+                            "client_id": "abcdefgh",
+                            "client_secret": "shhhh",
+                            "redirect_uris": ["https://example.com/redirect-2"],
+                            "client_name": "Example",
+                        })
+                    );
+                    client_configuration_response(StatusCode::OK, "https://example.com/redirect-2")
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            *updated.redirect_uris(),
+            vec![RedirectUrl::new("https://example.com/redirect-2".to_string()).unwrap()]
+        );
+
+        match client_management().update(&updated, |_| {
+            Ok::<_, std::io::Error>(HttpResponse {
+                status_code: StatusCode::BAD_REQUEST,
+                headers: vec![(CONTENT_TYPE, HeaderValue::from_static(MIME_TYPE_JSON))]
+                    .into_iter()
+                    .collect(),
+                body: b"{\"error\": \"invalid_redirect_uri\"}".to_vec(),
+            })
+        }) {
+            Err(ClientRegistrationError::ServerResponse(err)) => assert_eq!(
+                *err.error(),
+                CoreRegisterErrorResponseType::InvalidRedirectUri
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_client_management_delete() {
+        client_management()
+            .delete(|request| {
+                assert_management_request(&request, Method::DELETE);
+                Ok::<_, std::io::Error>(HttpResponse {
+                    status_code: StatusCode::NO_CONTENT,
+                    headers: HeaderMap::new(),
+                    body: Vec::new(),
+                })
+            })
+            .unwrap();
+
+        match client_management().delete(|_| {
+            Ok::<_, std::io::Error>(HttpResponse {
+                status_code: StatusCode::UNAUTHORIZED,
+                headers: HeaderMap::new(),
+                body: Vec::new(),
+            })
+        }) {
+            Err(ClientRegistrationError::InvalidToken(None)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match client_management().delete(|_| {
+            Ok::<_, std::io::Error>(HttpResponse {
+                status_code: StatusCode::FORBIDDEN,
+                headers: HeaderMap::new(),
+                body: Vec::new(),
+            })
+        }) {
+            Err(ClientRegistrationError::Response(StatusCode::FORBIDDEN, _, _)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_register_error_response() {
+        let register = |body: String| {
+            CoreClientRegistrationRequest::new(
+                vec![RedirectUrl::new("https://example.com/redirect".to_string()).unwrap()],
+                Default::default(),
+            )
+            .register(
+                &RegistrationUrl::new("https://example-provider.com/register".to_string()).unwrap(),
+                |_| {
+                    Ok::<_, std::io::Error>(HttpResponse {
+                        status_code: StatusCode::BAD_REQUEST,
+                        headers: vec![(CONTENT_TYPE, HeaderValue::from_static(MIME_TYPE_JSON))]
+                            .into_iter()
+                            .collect(),
+                        body: body.clone().into_bytes(),
+                    })
+                },
+            )
+        };
+
+        for (code, expected) in [
+            (
+                "invalid_redirect_uri",
+                CoreRegisterErrorResponseType::InvalidRedirectUri,
+            ),
+            (
+                "invalid_client_metadata",
+                CoreRegisterErrorResponseType::InvalidClientMetadata,
+            ),
+            (
+                "invalid_software_statement",
+                CoreRegisterErrorResponseType::InvalidSoftwareStatement,
+            ),
+            (
+                "unapproved_software_statement",
+                CoreRegisterErrorResponseType::UnapprovedSoftwareStatement,
+            ),
+            (
+                "invalid_tenant",
+                CoreRegisterErrorResponseType::Extension("invalid_tenant".to_string()),
+            ),
+        ] {
+            let body = format!(
+                "{{\"error\": \"{}\", \"error_description\": \"Rejected\"}}",
+                code
+            );
+            match register(body) {
+                Err(ClientRegistrationError::ServerResponse(err)) => {
+                    assert_eq!(*err.error(), expected);
+                    assert_eq!(err.error().as_ref(), code);
+                    assert_eq!(err.error_description().unwrap(), "Rejected");
+                }
+                other => panic!("unexpected result for {}: {:?}", code, other),
+            }
+        }
+
+        // Responses that aren't valid error responses preserve the raw body.
+        match register("{\"message\": \"bad things happened\"}".to_string()) {
+            Err(ClientRegistrationError::Response(StatusCode::BAD_REQUEST, body, _)) => {
+                assert_eq!(body, b"{\"message\": \"bad things happened\"}")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_software_statement() {
+        let software_metadata = CoreClientMetadata::new(
+            vec![RedirectUrl::new("https://example.com/redirect".to_string()).unwrap()],
+            Default::default(),
+        )
+        .set_client_name(Some(
+            vec![(None, ClientName::new("Example".to_string()))]
+                .into_iter()
+                .collect(),
+        ));
+        let software_statement = software_metadata
+            .sign_software_statement(
+                &IssuerUrl::new("https://federation.example.com".to_string()).unwrap(),
+                &CoreRsaPrivateSigningKey::from_pem(
+                    TEST_RSA_PRIV_KEY,
+                    Some(JsonWebKeyId::new("federation-key".to_string())),
+                )
+                .unwrap(),
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            )
+            .unwrap();
+
+        // The signed statement decodes to the expected claims.
+        let parts = software_statement.split('.').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 3);
+        let decode_part =
+            |part: &str| base64::decode_config(part, base64::URL_SAFE_NO_PAD).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&decode_part(parts[0])).unwrap(),
+            serde_json::json!({"alg": "RS256", "kid": "federation-key"})
+        );
+        let mut claims =
+            serde_json::from_slice::<serde_json::Value>(&decode_part(parts[1])).unwrap();
+        assert!(claims["iat"].is_i64());
+        claims.as_object_mut().unwrap().remove("iat");
+        assert_eq!(
+            claims,
+            serde_json::json!({
+                "iss": "https://federation.example.com",
+                "redirect_uris": ["https://example.com/redirect"],
+                "client_name": "Example",
+            })
+        );
+        serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .unwrap()
+            .verify_signature(
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                format!("{}.{}", parts[0], parts[1]).as_bytes(),
+                &decode_part(parts[2]),
+            )
+            .unwrap();
+
+        // The statement is sent verbatim alongside the client metadata.
+        let registration_response = CoreClientRegistrationRequest::new(
+            vec![RedirectUrl::new("https://example.com/redirect".to_string()).unwrap()],
+            Default::default(),
+        )
+        .set_software_statement(Some(software_statement.clone()))
+        .register(
+            &RegistrationUrl::new("https://example-provider.com/register".to_string()).unwrap(),
+            |request: HttpRequest| {
+                assert_eq!(
+                    serde_json::from_slice::<serde_json::Value>(&request.body).unwrap(),
+                    serde_json::json!({
+                        "redirect_uris": ["https://example.com/redirect"],
+                        "software_statement": software_statement.as_str(),
+                    })
+                );
+                Ok::<_, std::io::Error>(HttpResponse {
+                    status_code: StatusCode::CREATED,
+                    headers: vec![(CONTENT_TYPE, HeaderValue::from_static(MIME_TYPE_JSON))]
+                        .into_iter()
+                        .collect(),
+                    body: b"{
+                        \"client_id\": \"abcdefgh\",
+                        \"redirect_uris\": [\"https://example.com/redirect\"],
+                        \"client_name\": \"Example (approved)\"
+                    }"
+                    .to_vec(),
+                })
+            },
+        )
+        .unwrap();
+
+        // The provider replaced the client name asserted by the software statement.
+        assert_eq!(
+            registration_response
+                .overridden_metadata(&software_metadata)
+                .unwrap(),
+            vec!["client_name".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_sector_identifier_uri_round_trip() {
+        let sector_identifier_uri =
+            SectorIdentifierUrl::new("https://example.com/sector.json".to_string()).unwrap();
+        let registration_response = CoreClientRegistrationRequest::new(
+            vec![RedirectUrl::new("https://example.com/redirect".to_string()).unwrap()],
+            Default::default(),
+        )
+        .set_sector_identifier_uri(Some(sector_identifier_uri.clone()))
+        .set_subject_type(Some(CoreSubjectIdentifierType::Pairwise))
+        .register(
+            &RegistrationUrl::new("https://example-provider.com/register".to_string()).unwrap(),
+            |request: HttpRequest| {
+                let request_json =
+                    serde_json::from_slice::<serde_json::Value>(&request.body).unwrap();
+                assert_eq!(
+                    request_json,
+                    serde_json::json!({
+                        "redirect_uris": ["https://example.com/redirect"],
+                        "sector_identifier_uri": "https://example.com/sector.json",
+                        "subject_type": "pairwise",
+                    })
+                );
+
+                let mut response_json = request_json;
+                response_json["client_id"] = "abcdefgh".into();
+                Ok::<_, std::io::Error>(HttpResponse {
+                    status_code: StatusCode::CREATED,
+                    headers: vec![(CONTENT_TYPE, HeaderValue::from_static(MIME_TYPE_JSON))]
+                        .into_iter()
+                        .collect(),
+                    body: serde_json::to_vec(&response_json).unwrap(),
+                })
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            registration_response.sector_identifier_uri(),
+            Some(&sector_identifier_uri)
+        );
+        assert_eq!(
+            registration_response.subject_type(),
+            Some(&CoreSubjectIdentifierType::Pairwise)
+        );
+
+        // Both fields are omitted when absent.
+        let serialized = serde_json::to_value(
+            registration_response
+                .set_sector_identifier_uri(None)
+                .set_subject_type(None),
+        )
+        .unwrap();
+        assert_eq!(serialized.get("sector_identifier_uri"), None);
+        assert_eq!(serialized.get("subject_type"), None);
+    }
 }
@@ -0,0 +1,695 @@
+use std::fmt::{Display, Formatter, Result as FormatterResult};
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http::header::{HeaderValue, ACCEPT};
+use http::method::Method;
+use http::status::StatusCode;
+use oauth2::{AuthUrl, ClientId};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+use url::Url;
+
+use crate::jwt::{sign_compact_jws, JsonWebTokenJsonPayloadSerde};
+use crate::types::helpers::{deserialize_string_or_vec, serde_utc_seconds_opt};
+use crate::verification::{AudiencesClaim, IssuerClaim};
+use crate::{
+    AsyncHttpClient, Audience, ClaimsVerificationError, HttpRequest, HttpResponse, IssuerUrl,
+    JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse, JsonWebToken, JsonWebTokenError,
+    JsonWebTokenId, JweContentEncryptionAlgorithm, JwsSigningAlgorithm, PrivateSigningKey,
+    RequestObjectVerifier, RequestUrl, SyncHttpClient,
+};
+
+// See Section 4 of RFC 9101.
+pub(crate) const REQUEST_OBJECT_JWT_TYPE: &str = "oauth-authz-req+jwt";
+const MIME_TYPE_REQUEST_OBJECT: &str = "application/oauth-authz-req+jwt, application/jwt";
+
+// Request objects are generated immediately before redirecting the user to the provider, so they
+// only need to remain valid for long enough for the user-agent to follow the redirect.
+const REQUEST_OBJECT_LIFETIME: Duration = Duration::from_secs(300);
+
+// Parameters that Section 6.1 of OpenID Connect Core 1.0 requires to be passed using the OAuth 2.0
+// request syntax (i.e., as query parameters) in addition to inside the request object.
+const REQUIRED_QUERY_PARAMS: &[&str] = &["response_type", "client_id", "scope"];
+
+///
+/// Error creating an authorization request that passes its parameters as a request object.
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RequestObjectError {
+    ///
+    /// The provider's metadata indicates that it does not support the given authorization request
+    /// parameter (`request` or `request_uri`).
+    ///
+    #[error("Provider does not support the `{0}` parameter")]
+    UnsupportedParameter(&'static str),
+    ///
+    /// The provider's metadata does not list the signature algorithm in
+    /// `request_object_signing_alg_values_supported`.
+    ///
+    #[error("Request object signature algorithm not supported by provider: {0}")]
+    UnsupportedAlg(String),
+    ///
+    /// Failed to sign the request object.
+    ///
+    #[error("Failed to sign request object")]
+    Signing(#[source] JsonWebTokenError),
+}
+
+#[derive(Serialize)]
+struct RequestObjectHeader<'a, JS> {
+    alg: &'a JS,
+    typ: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kid: Option<JsonWebKeyId>,
+}
+
+// Signs a request object containing the given authorization request parameters, along with the
+// `iss` and `aud` claims recommended by Section 6.1 of OpenID Connect Core 1.0.
+pub(crate) fn sign_request_object<JS, JT, JU, K, S>(
+    params: &[(String, String)],
+    client_id: &ClientId,
+    issuer: &IssuerUrl,
+    signing_key: &S,
+    alg: &JS,
+    supported_algs: Option<&Vec<JS>>,
+) -> Result<String, RequestObjectError>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    S: PrivateSigningKey<JS, JT, JU, K>,
+{
+    if let Some(supported_algs) = supported_algs {
+        if !supported_algs.contains(alg) {
+            return Err(RequestObjectError::UnsupportedAlg(
+                serde_plain::to_string(alg).unwrap_or_else(|err| {
+                    panic!(
+                        "signature alg {:?} failed to serialize to a string: {}",
+                        alg, err
+                    )
+                }),
+            ));
+        }
+    }
+
+    let mut claims = params
+        .iter()
+        .map(|(name, value)| {
+            let value = match name.as_str() {
+                // These parameters are represented as JSON values rather than strings within a
+                // request object.
+                "max_age" => value.parse::<u64>().map(Value::from).ok(),
+                "claims" => serde_json::from_str::<Value>(value).ok(),
+                _ => None,
+            }
+            .unwrap_or_else(|| Value::String(value.clone()));
+            (name.clone(), value)
+        })
+        .collect::<Map<_, _>>();
+    let issue_time = Utc::now();
+    let expiration =
+        issue_time + chrono::Duration::from_std(REQUEST_OBJECT_LIFETIME).expect("valid duration");
+    claims.insert("iss".to_string(), Value::String(client_id.to_string()));
+    claims.insert("aud".to_string(), Value::String(issuer.to_string()));
+    claims.insert(
+        "jti".to_string(),
+        Value::String(JsonWebTokenId::new_random().to_string()),
+    );
+    claims.insert("iat".to_string(), issue_time.timestamp().into());
+    claims.insert("exp".to_string(), expiration.timestamp().into());
+
+    let header = RequestObjectHeader {
+        alg,
+        typ: REQUEST_OBJECT_JWT_TYPE,
+        kid: signing_key.as_verification_key().key_id().cloned(),
+    };
+    sign_compact_jws(&header, &claims, |signing_input| {
+        signing_key.sign(alg, signing_input)
+    })
+    .map_err(RequestObjectError::Signing)
+}
+
+// Returns an authorization URL containing the given request object parameter (`request` or
+// `request_uri`), along with the parameters that must be duplicated outside of the request object.
+pub(crate) fn request_object_url(
+    auth_url: &AuthUrl,
+    params: &[(String, String)],
+    request_param: (&str, &str),
+) -> Url {
+    let mut url = auth_url.url().clone();
+    url.query_pairs_mut()
+        .extend_pairs(
+            params
+                .iter()
+                .filter(|(name, _)| REQUIRED_QUERY_PARAMS.contains(&name.as_str())),
+        )
+        .append_pair(request_param.0, request_param.1);
+    url
+}
+
+///
+/// Error fetching a request object from a `request_uri`.
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RequestObjectFetchError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    ///
+    /// An unexpected error occurred.
+    ///
+    #[error("Other error: {0}")]
+    Other(String),
+    ///
+    /// Failed to parse the request object.
+    ///
+    #[error("Failed to parse request object")]
+    Parse(#[source] serde_json::Error),
+    ///
+    /// An error occurred while sending the request or receiving the response (e.g., network
+    /// connectivity failed).
+    ///
+    #[error("Request failed")]
+    Request(#[source] RE),
+    ///
+    /// Server returned an invalid response.
+    ///
+    #[error("Server returned invalid response: {2}")]
+    Response(StatusCode, Vec<u8>, String),
+}
+
+///
+/// Request object containing authorization request parameters, as described in
+/// [Section 6 of OpenID Connect Core 1.0](
+/// https://openid.net/specs/openid-connect-core-1_0.html#JWTRequests).
+///
+/// Relying parties that receive authorization requests (e.g., proxies) may use this type to
+/// fetch and verify request objects passed by reference using the `request_uri` parameter.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct RequestObject<
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+>(
+    #[serde(bound = "")]
+    JsonWebToken<JE, JS, JT, RequestObjectClaims, JsonWebTokenJsonPayloadSerde>,
+);
+
+impl<JE, JS, JT> FromStr for RequestObject<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    type Err = serde_json::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(Value::String(s.to_string()))
+    }
+}
+
+impl<JE, JS, JT> RequestObject<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Fetches the request object referenced by the given `request_uri`.
+    ///
+    /// The request object must be verified using [`RequestObject::claims`] before its parameters
+    /// are trusted.
+    ///
+    pub fn fetch<HC, RE>(
+        request_uri: &RequestUrl,
+        http_client: HC,
+    ) -> Result<Self, RequestObjectFetchError<RE>>
+    where
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        http_client
+            .call(Self::fetch_request(request_uri))
+            .map_err(RequestObjectFetchError::Request)
+            .and_then(|http_response| Self::fetch_response(request_uri, http_response))
+    }
+
+    ///
+    /// Asynchronously fetches the request object referenced by the given `request_uri`.
+    ///
+    /// The request object must be verified using [`RequestObject::claims`] before its parameters
+    /// are trusted.
+    ///
+    pub async fn fetch_async<F, HC, RE>(
+        request_uri: &RequestUrl,
+        http_client: HC,
+    ) -> Result<Self, RequestObjectFetchError<RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
+        RE: std::error::Error + 'static,
+    {
+        http_client
+            .call(Self::fetch_request(request_uri))
+            .await
+            .map_err(RequestObjectFetchError::Request)
+            .and_then(|http_response| Self::fetch_response(request_uri, http_response))
+    }
+
+    fn fetch_request(request_uri: &RequestUrl) -> HttpRequest {
+        HttpRequest {
+            url: request_uri.url().clone(),
+            method: Method::GET,
+            headers: vec![(ACCEPT, HeaderValue::from_static(MIME_TYPE_REQUEST_OBJECT))]
+                .into_iter()
+                .collect(),
+            body: Vec::new(),
+        }
+    }
+
+    fn fetch_response<RE>(
+        request_uri: &RequestUrl,
+        http_response: HttpResponse,
+    ) -> Result<Self, RequestObjectFetchError<RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        if http_response.status_code != StatusCode::OK {
+            return Err(RequestObjectFetchError::Response(
+                http_response.status_code,
+                http_response.body,
+                format!(
+                    "HTTP status code {} at {}",
+                    http_response.status_code,
+                    request_uri.as_str()
+                ),
+            ));
+        }
+
+        let request_object = String::from_utf8(http_response.body).map_err(|err| {
+            RequestObjectFetchError::Other(format!("request object is not valid UTF-8: {}", err))
+        })?;
+        Self::from_str(request_object.trim()).map_err(RequestObjectFetchError::Parse)
+    }
+
+    ///
+    /// Verifies and returns a reference to the request object claims.
+    ///
+    /// In addition to the signature, this verifies that the `aud` claim contains the receiving
+    /// party's issuer identifier, and that the `iss` and `client_id` claims (if present) match the
+    /// requesting client's ID. Verifying that the query parameters of the authorization request
+    /// are consistent with the request object (e.g., `client_id` and `response_type`) is left to
+    /// the caller.
+    ///
+    pub fn claims<'a, JU, K>(
+        &'a self,
+        verifier: &RequestObjectVerifier<JS, JT, JU, K>,
+    ) -> Result<&'a RequestObjectClaims, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        verifier.verified_claims(&self.0)
+    }
+}
+impl<JE, JS, JT> Display for RequestObject<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatterResult {
+        f.write_str(
+            serde_json::to_value(self)
+                // This should never arise, since we're just asking serde_json to serialize the
+                // signing input concatenated with the signature, both of which are precomputed.
+                .expect("request object serialization failed")
+                .as_str()
+                // This should also never arise, since our JsonWebToken serializer always calls
+                // serialize_str
+                .expect("request object serializer did not produce a str"),
+        )
+    }
+}
+
+///
+/// Request object claims.
+///
+/// Besides the standard JWT claims, each authorization request parameter is represented as a
+/// claim (see [`RequestObjectClaims::parameters`]).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct RequestObjectClaims {
+    #[serde(rename = "iss", skip_serializing_if = "Option::is_none")]
+    issuer: Option<ClientId>,
+    #[serde(
+        default,
+        rename = "aud",
+        deserialize_with = "deserialize_string_or_vec"
+    )]
+    audiences: Vec<Audience>,
+    #[serde(
+        default,
+        rename = "exp",
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    expiration: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_id: Option<ClientId>,
+    #[serde(flatten)]
+    parameters: Map<String, Value>,
+}
+impl RequestObjectClaims {
+    field_getters_setters![
+        pub self [self] ["claim"] {
+            set_issuer -> issuer[Option<ClientId>] ["iss"],
+            set_audiences -> audiences[Vec<Audience>] ["aud"],
+            set_expiration -> expiration[Option<DateTime<Utc>>] ["exp"],
+            set_client_id -> client_id[Option<ClientId>],
+            set_parameters -> parameters[Map<String, Value>],
+        }
+    ];
+}
+impl AudiencesClaim for RequestObjectClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(RequestObjectClaims::audiences(self))
+    }
+}
+impl AudiencesClaim for &RequestObjectClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(RequestObjectClaims::audiences(self))
+    }
+}
+// The issuer of a request object is a client ID rather than an issuer URL, so it's verified
+// separately by the RequestObjectVerifier.
+impl IssuerClaim for RequestObjectClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        None
+    }
+}
+impl IssuerClaim for &RequestObjectClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+    use http::method::Method;
+    use http::status::StatusCode;
+    use oauth2::{AuthUrl, ClientId, CsrfToken, Scope};
+    use serde_json::Value;
+
+    use super::{RequestObjectError, RequestObjectFetchError};
+    use crate::core::{
+        CoreAuthenticationFlow, CoreClient, CoreJsonWebKey, CoreJwsSigningAlgorithm,
+        CoreProviderMetadata, CoreRequestObject, CoreRequestObjectVerifier, CoreResponseType,
+        CoreRsaPrivateSigningKey, CoreSubjectIdentifierType,
+    };
+    use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
+    use crate::{
+        ClaimsVerificationError, EmptyAdditionalProviderMetadata, HttpRequest, HttpResponse,
+        IssuerUrl, JsonWebKey, JsonWebKeyId, JsonWebKeySet, JsonWebKeySetUrl, Nonce, RequestUrl,
+        ResponseTypes,
+    };
+
+    fn new_provider_metadata() -> CoreProviderMetadata {
+        CoreProviderMetadata::new(
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+    }
+
+    fn new_signing_key() -> CoreRsaPrivateSigningKey {
+        CoreRsaPrivateSigningKey::from_pem(
+            TEST_RSA_PRIV_KEY,
+            Some(JsonWebKeyId::new(
+                "bilbo.baggins@hobbiton.example".to_string(),
+            )),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_authorization_url_with_request() {
+        let client = CoreClient::from_provider_metadata(
+            new_provider_metadata()
+                .set_request_parameter_supported(Some(true))
+                .set_request_object_signing_alg_values_supported(Some(vec![
+                    CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                ])),
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+
+        let (url, csrf_state, nonce) = client
+            .authorization_url_with_request(
+                client
+                    .authorize_url(
+                        CoreAuthenticationFlow::AuthorizationCode,
+                        || CsrfToken::new("CSRF123".to_string()),
+                        || Nonce::new("NONCE456".to_string()),
+                    )
+                    .add_scope(Scope::new("email".to_string()))
+                    .set_max_age(std::time::Duration::from_secs(1800)),
+                &new_signing_key(),
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            )
+            .unwrap();
+        assert_eq!(csrf_state.secret(), "CSRF123");
+        assert_eq!(nonce.secret(), "NONCE456");
+
+        let query = url.query_pairs().into_owned().collect::<Vec<_>>();
+        assert_eq!(
+            query
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["response_type", "client_id", "scope", "request"]
+        );
+        assert_eq!(
+            url.as_str().split('?').next(),
+            Some("https://example/authorize")
+        );
+        assert_eq!(query[0].1, "code");
+        assert_eq!(query[1].1, "aaa");
+        assert_eq!(query[2].1, "openid email");
+
+        let parts = query[3].1.split('.').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 3);
+        let decode_part = |part: &str| {
+            serde_json::from_slice::<Value>(
+                &base64::decode_config(part, base64::URL_SAFE_NO_PAD).unwrap(),
+            )
+            .unwrap()
+        };
+        let header = decode_part(parts[0]);
+        assert_eq!(header["alg"], "RS256");
+        assert_eq!(header["typ"], "oauth-authz-req+jwt");
+        assert_eq!(header["kid"], "bilbo.baggins@hobbiton.example");
+        serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .unwrap()
+            .verify_signature(
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                format!("{}.{}", parts[0], parts[1]).as_bytes(),
+                &base64::decode_config(parts[2], base64::URL_SAFE_NO_PAD).unwrap(),
+            )
+            .expect("invalid request object signature");
+
+        let claims = decode_part(parts[1]);
+        assert_eq!(claims["iss"], "aaa");
+        assert_eq!(claims["aud"], "https://example");
+        assert_eq!(claims["response_type"], "code");
+        assert_eq!(claims["client_id"], "aaa");
+        assert_eq!(claims["scope"], "openid email");
+        assert_eq!(claims["state"], "CSRF123");
+        assert_eq!(claims["nonce"], "NONCE456");
+        assert_eq!(claims["max_age"], 1800);
+        assert!(claims["jti"].is_string());
+        let iat = claims["iat"].as_i64().unwrap();
+        assert!((iat - Utc::now().timestamp()).abs() <= 5);
+        assert_eq!(claims["exp"].as_i64().unwrap(), iat + 300);
+    }
+
+    #[test]
+    fn test_authorization_url_with_request_unsupported() {
+        let authorization_request = |client: &CoreClient| {
+            client.authorization_url_with_request(
+                client.authorize_url(
+                    CoreAuthenticationFlow::AuthorizationCode,
+                    CsrfToken::new_random,
+                    Nonce::new_random,
+                ),
+                &new_signing_key(),
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            )
+        };
+
+        let client = CoreClient::from_provider_metadata(
+            new_provider_metadata().set_request_parameter_supported(Some(false)),
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        assert!(matches!(
+            authorization_request(&client),
+            Err(RequestObjectError::UnsupportedParameter("request"))
+        ));
+
+        let client = CoreClient::from_provider_metadata(
+            new_provider_metadata().set_request_object_signing_alg_values_supported(Some(vec![
+                CoreJwsSigningAlgorithm::EcdsaP256Sha256,
+            ])),
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        assert!(matches!(
+            authorization_request(&client),
+            Err(RequestObjectError::UnsupportedAlg(alg)) if alg == "RS256"
+        ));
+    }
+
+    #[test]
+    fn test_authorization_url_with_request_uri() {
+        let request_uri =
+            RequestUrl::new("https://client.example/request/abc".to_string()).unwrap();
+        let client = CoreClient::from_provider_metadata(
+            new_provider_metadata(),
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        let (url, request_object, _, _) = client
+            .authorization_url_with_request_uri(
+                client.authorize_url(
+                    CoreAuthenticationFlow::AuthorizationCode,
+                    || CsrfToken::new("CSRF123".to_string()),
+                    Nonce::new_random,
+                ),
+                &request_uri,
+                &new_signing_key(),
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            )
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://example/authorize?response_type=code&client_id=aaa&scope=openid&\
+             request_uri=https%3A%2F%2Fclient.example%2Frequest%2Fabc"
+        );
+
+        // The provider fetches and verifies the hosted request object.
+        let verifier = CoreRequestObjectVerifier::new(
+            ClientId::new("aaa".to_string()),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            JsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        );
+        let fetched = CoreRequestObject::fetch(&request_uri, |request: HttpRequest| {
+            assert_eq!(request.url.as_str(), "https://client.example/request/abc");
+            assert_eq!(request.method, Method::GET);
+            Ok::<_, std::io::Error>(HttpResponse {
+                status_code: StatusCode::OK,
+                headers: vec![(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("application/oauth-authz-req+jwt"),
+                )]
+                .into_iter()
+                .collect(),
+                body: request_object.clone().into_bytes(),
+            })
+        })
+        .unwrap();
+        assert_eq!(fetched.to_string(), request_object);
+        let claims = fetched.claims(&verifier).unwrap();
+        assert_eq!(claims.issuer().map(|iss| iss.as_str()), Some("aaa"));
+        assert_eq!(claims.client_id().map(|id| id.as_str()), Some("aaa"));
+        assert_eq!(claims.parameters()["state"], "CSRF123");
+        assert_eq!(claims.parameters()["response_type"], "code");
+
+        // Request objects from other clients, for other audiences, or that have expired are
+        // rejected.
+        let other_client_verifier = CoreRequestObjectVerifier::new(
+            ClientId::new("bbb".to_string()),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            JsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        );
+        assert!(matches!(
+            fetched.claims(&other_client_verifier),
+            Err(ClaimsVerificationError::InvalidIssuer(_))
+        ));
+        let other_audience_verifier = CoreRequestObjectVerifier::new(
+            ClientId::new("aaa".to_string()),
+            IssuerUrl::new("https://other.example".to_string()).unwrap(),
+            JsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        );
+        assert!(matches!(
+            fetched.claims(&other_audience_verifier),
+            Err(ClaimsVerificationError::InvalidAudience(_))
+        ));
+        let expired_verifier = verifier
+            .clone()
+            .set_time_fn(|| Utc::now() + chrono::Duration::seconds(600));
+        assert!(matches!(
+            fetched.claims(&expired_verifier),
+            Err(ClaimsVerificationError::Expired(_))
+        ));
+
+        assert!(matches!(
+            CoreRequestObject::fetch(&request_uri, |_| {
+                Ok::<_, std::io::Error>(HttpResponse {
+                    status_code: StatusCode::NOT_FOUND,
+                    headers: HeaderMap::new(),
+                    body: vec![],
+                })
+            }),
+            Err(RequestObjectFetchError::Response(
+                StatusCode::NOT_FOUND,
+                _,
+                _
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_authorization_url_with_request_uri_unsupported() {
+        let client = CoreClient::from_provider_metadata(
+            new_provider_metadata().set_request_uri_parameter_supported(Some(false)),
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        assert!(matches!(
+            client.authorization_url_with_request_uri(
+                client.authorize_url(
+                    CoreAuthenticationFlow::AuthorizationCode,
+                    CsrfToken::new_random,
+                    Nonce::new_random,
+                ),
+                &RequestUrl::new("https://client.example/request/abc".to_string()).unwrap(),
+                &new_signing_key(),
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            ),
+            Err(RequestObjectError::UnsupportedParameter("request_uri"))
+        ));
+    }
+}
@@ -0,0 +1,217 @@
+use std::ops::Deref;
+
+use oauth2::{
+    ClientCredentialsTokenRequest, CodeTokenRequest, ErrorResponse, PasswordTokenRequest,
+    RefreshTokenRequest, TokenResponse, TokenType,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::Url;
+
+///
+/// Resource indicator identifying a protected resource at which the requested access token is
+/// intended to be used, as described in [RFC 8707](https://tools.ietf.org/html/rfc8707).
+///
+/// Section 2 of RFC 8707 requires resource indicators to be absolute URIs without a fragment
+/// component, which [`ResourceIndicator::new`] enforces.
+///
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ResourceIndicator(String);
+impl ResourceIndicator {
+    ///
+    /// Creates a new resource indicator, returning an error if `uri` is not an absolute URI or
+    /// contains a fragment component.
+    ///
+    pub fn new(uri: String) -> Result<Self, ResourceIndicatorError> {
+        if Url::parse(&uri)
+            .map_err(ResourceIndicatorError::Parse)?
+            .fragment()
+            .is_some()
+        {
+            return Err(ResourceIndicatorError::Fragment(uri));
+        }
+        Ok(ResourceIndicator(uri))
+    }
+
+    ///
+    /// Creates a new resource indicator from a parsed URL, returning an error if the URL contains
+    /// a fragment component.
+    ///
+    pub fn from_url(url: &Url) -> Result<Self, ResourceIndicatorError> {
+        if url.fragment().is_some() {
+            return Err(ResourceIndicatorError::Fragment(url.to_string()));
+        }
+        Ok(ResourceIndicator(url.to_string()))
+    }
+}
+impl Deref for ResourceIndicator {
+    type Target = String;
+    fn deref(&self) -> &String {
+        &self.0
+    }
+}
+impl From<ResourceIndicator> for String {
+    fn from(resource: ResourceIndicator) -> String {
+        resource.0
+    }
+}
+impl TryFrom<String> for ResourceIndicator {
+    type Error = ResourceIndicatorError;
+    fn try_from(uri: String) -> Result<Self, Self::Error> {
+        Self::new(uri)
+    }
+}
+
+///
+/// Error creating a [`ResourceIndicator`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResourceIndicatorError {
+    ///
+    /// The resource indicator is not an absolute URI.
+    ///
+    #[error("Failed to parse resource indicator")]
+    Parse(#[source] url::ParseError),
+    ///
+    /// The resource indicator contains a fragment component.
+    ///
+    #[error("Resource indicator must not contain a fragment: `{0}`")]
+    Fragment(String),
+}
+
+///
+/// Extension trait for adding [RFC 8707](https://tools.ietf.org/html/rfc8707) `resource`
+/// parameters to token requests.
+///
+/// Each call appends another `resource` parameter to the request body, so multiple resources may
+/// be requested by calling [`add_resource`](Self::add_resource) repeatedly.
+///
+pub trait ResourceIndicatorRequest: Sized {
+    ///
+    /// Requests an access token intended for use at the given protected resource.
+    ///
+    fn add_resource(self, resource: &ResourceIndicator) -> Self;
+
+    ///
+    /// Requests an access token intended for use at each of the given protected resources.
+    ///
+    fn add_resources<'r, I>(self, resources: I) -> Self
+    where
+        I: IntoIterator<Item = &'r ResourceIndicator>,
+    {
+        resources
+            .into_iter()
+            .fold(self, |request, resource| request.add_resource(resource))
+    }
+}
+
+macro_rules! impl_resource_indicator_request {
+    ($request:ident<$($lifetime:lifetime),*>) => {
+        impl<$($lifetime,)* TE, TR, TT> ResourceIndicatorRequest
+            for $request<$($lifetime,)* TE, TR, TT>
+        where
+            TE: ErrorResponse + 'static,
+            TR: TokenResponse<TT>,
+            TT: TokenType,
+        {
+            fn add_resource(self, resource: &ResourceIndicator) -> Self {
+                self.add_extra_param("resource", resource.to_string())
+            }
+        }
+    };
+}
+impl_resource_indicator_request![ClientCredentialsTokenRequest<'a>];
+impl_resource_indicator_request![CodeTokenRequest<'a>];
+impl_resource_indicator_request![PasswordTokenRequest<'a>];
+impl_resource_indicator_request![RefreshTokenRequest<'a>];
+
+#[cfg(test)]
+mod tests {
+    use oauth2::{AuthorizationCode, CsrfToken};
+    use url::form_urlencoded;
+
+    use crate::core::CoreResponseType;
+    use crate::tests::{mock_json_response, new_client};
+    use crate::{
+        AuthenticationFlow, Nonce, ResourceIndicator, ResourceIndicatorError,
+        ResourceIndicatorRequest,
+    };
+
+    fn resources() -> Vec<ResourceIndicator> {
+        vec![
+            ResourceIndicator::new("https://api.example.com/".to_string()).unwrap(),
+            ResourceIndicator::new("https://files.example.com/v1?tenant=1".to_string()).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_resource_indicator_validation() {
+        assert!(matches!(
+            ResourceIndicator::new("/relative".to_string()),
+            Err(ResourceIndicatorError::Parse(
+                url::ParseError::RelativeUrlWithoutBase
+            ))
+        ));
+        assert_eq!(
+            ResourceIndicator::new("https://api.example.com/#fragment".to_string()),
+            Err(ResourceIndicatorError::Fragment(
+                "https://api.example.com/#fragment".to_string()
+            ))
+        );
+        assert!(
+            serde_json::from_str::<ResourceIndicator>("\"https://api.example.com/#f\"").is_err()
+        );
+        assert_eq!(
+            serde_json::from_str::<ResourceIndicator>("\"urn:example:api\"").unwrap(),
+            ResourceIndicator::new("urn:example:api".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_resources() {
+        let (authorize_url, _, _) = new_client()
+            .authorize_url(
+                AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_resources(resources())
+            .url();
+
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&\
+             state=CSRF123&scope=openid&nonce=NONCE456&\
+             resource=https%3A%2F%2Fapi.example.com%2F&\
+             resource=https%3A%2F%2Ffiles.example.com%2Fv1%3Ftenant%3D1",
+            authorize_url.to_string()
+        );
+    }
+
+    #[test]
+    fn test_token_request_resources() {
+        let resources = resources();
+        new_client()
+            .exchange_code(AuthorizationCode::new("ccc".to_string()))
+            .add_resources(&resources)
+            .request(|request| {
+                let resource_params = form_urlencoded::parse(&request.body)
+                    .filter(|(name, _)| name == "resource")
+                    .map(|(_, value)| value.into_owned())
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    resource_params,
+                    vec![
+                        "https://api.example.com/".to_string(),
+                        "https://files.example.com/v1?tenant=1".to_string(),
+                    ]
+                );
+                mock_json_response(
+                    http::StatusCode::OK,
+                    "{\"access_token\": \"12/34\", \"token_type\": \"bearer\"}",
+                )
+            })
+            .unwrap();
+    }
+}
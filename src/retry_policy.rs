@@ -0,0 +1,357 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::method::Method;
+use rand::{thread_rng, Rng};
+
+use crate::{HttpRequest, HttpResponse};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(200);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(10);
+
+///
+/// Policy for retrying HTTP requests that fail due to transient errors.
+///
+/// A request is retried if the HTTP client returns an error (e.g., a connection failure) or the
+/// server responds with a `5xx` status code. The delay before each retry starts at the base
+/// delay and doubles after each attempt, up to the maximum delay. With jitter enabled (the
+/// default), each delay is randomized to between half and all of its nominal value.
+///
+/// Only `GET` requests (i.e., OpenID Connect Discovery, JSON Web Key Set, and WebFinger fetches)
+/// are retried by default, since retrying requests to the token, introspection, or other `POST`
+/// endpoints may not be idempotent. The policy is applied by wrapping an HTTP client using
+/// [`http_client`](Self::http_client) or [`async_http_client`](Self::async_http_client):
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "reqwest")]
+/// # fn err_wrapper() -> Result<(), anyhow::Error> {
+/// use std::time::Duration;
+///
+/// use openidconnect::core::CoreProviderMetadata;
+/// use openidconnect::reqwest::http_client;
+/// use openidconnect::{IssuerUrl, RetryPolicy};
+///
+/// let retry_policy = RetryPolicy::new()
+///     .set_max_attempts(5)
+///     .set_base_delay(Duration::from_millis(500));
+/// let provider_metadata = CoreProviderMetadata::discover(
+///     &IssuerUrl::new("https://accounts.example.com".to_string())?,
+///     retry_policy.http_client(http_client),
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    retry_non_idempotent: bool,
+    sleep_fn: Arc<dyn Fn(Duration) + Send + Sync>,
+}
+impl RetryPolicy {
+    ///
+    /// Creates a policy that makes up to 3 attempts, with a base delay of 200 milliseconds and a
+    /// maximum delay of 10 seconds.
+    ///
+    pub fn new() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            jitter: true,
+            retry_non_idempotent: false,
+            sleep_fn: Arc::new(std::thread::sleep),
+        }
+    }
+
+    ///
+    /// Sets the maximum number of attempts, including the initial request. A value of `1` (or
+    /// `0`) disables retries.
+    ///
+    pub fn set_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    ///
+    /// Sets the delay before the first retry, which doubles after each subsequent attempt.
+    ///
+    pub fn set_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    ///
+    /// Sets the maximum delay between attempts.
+    ///
+    pub fn set_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    ///
+    /// Enables or disables randomizing the delay between attempts. Jitter is enabled by default
+    /// to avoid many clients retrying in lockstep.
+    ///
+    pub fn set_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    ///
+    /// Enables retrying requests other than `GET` (e.g., token requests).
+    ///
+    /// This is disabled by default, since a request that fails after reaching the server may
+    /// have already taken effect (e.g., consuming a single-use authorization code).
+    ///
+    pub fn set_retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    ///
+    /// Specifies the function used by [`http_client`](Self::http_client) to wait between
+    /// attempts. Defaults to [`std::thread::sleep`].
+    ///
+    pub fn set_sleep_fn<S>(mut self, sleep_fn: S) -> Self
+    where
+        S: Fn(Duration) + Send + Sync + 'static,
+    {
+        self.sleep_fn = Arc::new(sleep_fn);
+        self
+    }
+
+    ///
+    /// Wraps a synchronous HTTP client so that requests are retried according to this policy.
+    ///
+    pub fn http_client<HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> impl Fn(HttpRequest) -> Result<HttpResponse, RE>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let policy = self.clone();
+        move |request| {
+            let max_attempts = policy.attempts_for(&request);
+            let mut attempt = 1;
+            loop {
+                let result = http_client(request.clone());
+                if attempt >= max_attempts || !is_transient(&result) {
+                    return result;
+                }
+                (*policy.sleep_fn)(policy.delay(attempt));
+                attempt += 1;
+            }
+        }
+    }
+
+    ///
+    /// Wraps an asynchronous HTTP client so that requests are retried according to this policy.
+    ///
+    /// Since this crate does not depend on any particular async runtime, the caller provides a
+    /// `sleep_fn` returning a future that completes after the given delay (e.g.,
+    /// `tokio::time::sleep`). The sleep function set via [`set_sleep_fn`](Self::set_sleep_fn) is
+    /// not used.
+    ///
+    pub fn async_http_client<'c, C, F, RE, S, SF>(
+        &'c self,
+        http_client: &'c C,
+        sleep_fn: &'c S,
+    ) -> impl Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<HttpResponse, RE>> + 'c>> + 'c
+    where
+        C: Fn(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>> + 'c,
+        RE: std::error::Error + 'static,
+        S: Fn(Duration) -> SF,
+        SF: Future<Output = ()> + 'c,
+    {
+        move |request| {
+            Box::pin(async move {
+                let max_attempts = self.attempts_for(&request);
+                let mut attempt = 1;
+                loop {
+                    let result = http_client(request.clone()).await;
+                    if attempt >= max_attempts || !is_transient(&result) {
+                        return result;
+                    }
+                    sleep_fn(self.delay(attempt)).await;
+                    attempt += 1;
+                }
+            })
+        }
+    }
+
+    fn attempts_for(&self, request: &HttpRequest) -> u32 {
+        if request.method == Method::GET || self.retry_non_idempotent {
+            self.max_attempts
+        } else {
+            1
+        }
+    }
+
+    // Returns the delay following the given (1-based) attempt.
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .checked_mul(1 << (attempt - 1).min(31))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        if self.jitter {
+            delay.mul_f64(thread_rng().gen_range(0.5..=1.0))
+        } else {
+            delay
+        }
+    }
+}
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("jitter", &self.jitter)
+            .field("retry_non_idempotent", &self.retry_non_idempotent)
+            .finish()
+    }
+}
+
+fn is_transient<RE>(result: &Result<HttpResponse, RE>) -> bool {
+    match result {
+        Ok(response) => response.status_code.is_server_error(),
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use oauth2::{AuthUrl, AuthorizationCode, ClientId, ClientSecret, TokenUrl};
+
+    use crate::core::{CoreClient, CoreJsonWebKeySet};
+    use crate::discovery::tests::block_on;
+    use crate::tests::mock_json_response;
+    use crate::{
+        HttpRequest, HttpResponse, IssuerUrl, JsonWebKeySet, JsonWebKeySetUrl, RequestTokenError,
+        RetryPolicy,
+    };
+
+    fn jwks_url() -> JsonWebKeySetUrl {
+        JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap()
+    }
+
+    // Fails with a transport error for the first `failures` requests, then succeeds.
+    fn flaky_http_client(
+        failures: u32,
+        attempts: &AtomicU32,
+    ) -> impl Fn(HttpRequest) -> Result<HttpResponse, std::io::Error> + '_ {
+        move |_| {
+            if attempts.fetch_add(1, Ordering::SeqCst) < failures {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "connection reset",
+                ));
+            }
+            mock_json_response(http::StatusCode::OK, "{\"keys\": []}")
+        }
+    }
+
+    fn recording_policy(delays: &Arc<Mutex<Vec<Duration>>>) -> RetryPolicy {
+        let delays = delays.clone();
+        RetryPolicy::new()
+            .set_base_delay(Duration::from_millis(100))
+            .set_jitter(false)
+            .set_sleep_fn(move |delay| delays.lock().unwrap().push(delay))
+    }
+
+    #[test]
+    fn test_retry_get() {
+        let delays = Arc::new(Mutex::new(Vec::new()));
+        let attempts = AtomicU32::new(0);
+
+        // Two failures followed by a success.
+        let jwks = CoreJsonWebKeySet::fetch(
+            &jwks_url(),
+            recording_policy(&delays).http_client(flaky_http_client(2, &attempts)),
+        )
+        .unwrap();
+        assert!(jwks.keys().is_empty());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            *delays.lock().unwrap(),
+            vec![Duration::from_millis(100), Duration::from_millis(200)]
+        );
+
+        // Giving up after the maximum number of attempts.
+        let attempts = AtomicU32::new(0);
+        assert!(CoreJsonWebKeySet::fetch(
+            &jwks_url(),
+            recording_policy(&delays)
+                .set_max_attempts(2)
+                .http_client(flaky_http_client(2, &attempts)),
+        )
+        .is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        // Asynchronous retries.
+        let attempts = AtomicU32::new(0);
+        let async_delays = Mutex::new(Vec::new());
+        let policy = recording_policy(&delays);
+        let http_client = flaky_http_client(2, &attempts);
+        let async_http_client = |request| std::future::ready(http_client(request));
+        let sleep_fn = |delay| {
+            async_delays.lock().unwrap().push(delay);
+            std::future::ready(())
+        };
+        block_on(CoreJsonWebKeySet::fetch_async(
+            &jwks_url(),
+            policy.async_http_client(&async_http_client, &sleep_fn),
+        ))
+        .unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            *async_delays.lock().unwrap(),
+            vec![Duration::from_millis(100), Duration::from_millis(200)]
+        );
+    }
+
+    #[test]
+    fn test_retry_post_not_retried() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            None,
+            JsonWebKeySet::default(),
+        );
+        let delays = Arc::new(Mutex::new(Vec::new()));
+        let attempts = AtomicU32::new(0);
+
+        match client
+            .exchange_code(AuthorizationCode::new("ccc".to_string()))
+            .request(recording_policy(&delays).http_client(flaky_http_client(1, &attempts)))
+        {
+            Err(RequestTokenError::Request(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert!(delays.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,358 @@
+use std::iter::FromIterator;
+
+use thiserror::Error;
+
+use crate::Scope;
+
+///
+/// Set of OAuth 2.0 scopes for an OpenID Connect request.
+///
+/// The `openid` scope is always included, and duplicate scopes are ignored while preserving the
+/// order in which scopes were added. Since [`AuthorizationRequest`](crate::AuthorizationRequest)
+/// and the other request builders accept any iterator of [`Scope`]s, a `Scopes` value may be
+/// passed directly to their `add_scopes` methods.
+///
+/// See [Section 5.4](https://openid.net/specs/openid-connect-core-1_0.html#ScopeClaims) of the
+/// OpenID Connect Core specification for the standard scopes.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scopes(Vec<Scope>);
+impl Scopes {
+    ///
+    /// Scope required by all OpenID Connect requests.
+    ///
+    pub const OPENID: &'static str = "openid";
+    ///
+    /// Scope requesting access to the End-User's default profile claims.
+    ///
+    pub const PROFILE: &'static str = "profile";
+    ///
+    /// Scope requesting access to the `email` and `email_verified` claims.
+    ///
+    pub const EMAIL: &'static str = "email";
+    ///
+    /// Scope requesting access to the `address` claim.
+    ///
+    pub const ADDRESS: &'static str = "address";
+    ///
+    /// Scope requesting access to the `phone_number` and `phone_number_verified` claims.
+    ///
+    pub const PHONE: &'static str = "phone";
+    ///
+    /// Scope requesting a refresh token for obtaining access tokens while the End-User is not
+    /// logged in.
+    ///
+    pub const OFFLINE_ACCESS: &'static str = "offline_access";
+
+    ///
+    /// Creates a new set containing only the `openid` scope.
+    ///
+    pub fn new() -> Self {
+        Scopes(vec![Scope::new(Self::OPENID.to_string())])
+    }
+
+    ///
+    /// Adds a scope to the set, unless it is already present.
+    ///
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        if !self.0.contains(&scope) {
+            self.0.push(scope);
+        }
+        self
+    }
+
+    ///
+    /// Adds the `profile` scope.
+    ///
+    pub fn add_profile(self) -> Self {
+        self.add_scope(Scope::new(Self::PROFILE.to_string()))
+    }
+
+    ///
+    /// Adds the `email` scope.
+    ///
+    pub fn add_email(self) -> Self {
+        self.add_scope(Scope::new(Self::EMAIL.to_string()))
+    }
+
+    ///
+    /// Adds the `address` scope.
+    ///
+    pub fn add_address(self) -> Self {
+        self.add_scope(Scope::new(Self::ADDRESS.to_string()))
+    }
+
+    ///
+    /// Adds the `phone` scope.
+    ///
+    pub fn add_phone(self) -> Self {
+        self.add_scope(Scope::new(Self::PHONE.to_string()))
+    }
+
+    ///
+    /// Adds the `offline_access` scope.
+    ///
+    pub fn add_offline_access(self) -> Self {
+        self.add_scope(Scope::new(Self::OFFLINE_ACCESS.to_string()))
+    }
+
+    ///
+    /// Returns whether the set contains the given scope.
+    ///
+    pub fn contains(&self, scope: &str) -> bool {
+        self.0.iter().any(|s| s.as_str() == scope)
+    }
+
+    ///
+    /// Returns an iterator over the scopes in the order they were added.
+    ///
+    pub fn iter(&self) -> std::slice::Iter<'_, Scope> {
+        self.0.iter()
+    }
+
+    ///
+    /// Returns the scopes not listed in the provider's `scopes_supported` metadata.
+    ///
+    /// If the provider did not publish `scopes_supported`, every scope is assumed to be
+    /// supported. This is useful for logging a warning about scopes that the provider may ignore.
+    ///
+    pub fn unsupported<'a>(&'a self, scopes_supported: Option<&Vec<Scope>>) -> Vec<&'a Scope> {
+        scopes_supported
+            .map(|supported| {
+                self.0
+                    .iter()
+                    .filter(|scope| !supported.contains(scope))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Returns an error if any scope is not listed in the provider's `scopes_supported`
+    /// metadata (see [`ProviderMetadata::scopes_supported`](crate::ProviderMetadata::scopes_supported)).
+    ///
+    /// If the provider did not publish `scopes_supported`, every scope is assumed to be
+    /// supported.
+    ///
+    pub fn validate(&self, scopes_supported: Option<&Vec<Scope>>) -> Result<(), ScopesError> {
+        let unsupported = self.unsupported(scopes_supported);
+        if unsupported.is_empty() {
+            Ok(())
+        } else {
+            Err(ScopesError::Unsupported(
+                unsupported.into_iter().cloned().collect(),
+            ))
+        }
+    }
+}
+impl Default for Scopes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Extend<Scope> for Scopes {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = Scope>,
+    {
+        for scope in iter {
+            if !self.0.contains(&scope) {
+                self.0.push(scope);
+            }
+        }
+    }
+}
+impl FromIterator<Scope> for Scopes {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Scope>,
+    {
+        let mut scopes = Scopes::new();
+        scopes.extend(iter);
+        scopes
+    }
+}
+impl IntoIterator for Scopes {
+    type Item = Scope;
+    type IntoIter = std::vec::IntoIter<Scope>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+impl From<Scopes> for Vec<Scope> {
+    fn from(scopes: Scopes) -> Self {
+        scopes.0
+    }
+}
+
+///
+/// Returns the minimal set of standard scopes needed to request the given claims.
+///
+/// Each standard claim is mapped to the scope that governs it, per
+/// [Section 5.4](https://openid.net/specs/openid-connect-core-1_0.html#ScopeClaims) of the
+/// OpenID Connect Core specification. Members of the `address` claim (e.g., `locality`) map to the
+/// `address` scope. Claims not governed by a standard scope (e.g., `sub` or provider-specific
+/// claims) are ignored; they may instead be requested individually using a
+/// [`ClaimsRequest`](crate::ClaimsRequest). The returned set always includes `openid`.
+///
+pub fn scopes_for_claims(claims: &[&str]) -> Scopes {
+    claims
+        .iter()
+        .filter_map(|claim| {
+            let scope = match *claim {
+                "name" | "family_name" | "given_name" | "middle_name" | "nickname"
+                | "preferred_username" | "profile" | "picture" | "website" | "gender"
+                | "birthdate" | "zoneinfo" | "locale" | "updated_at" => Scopes::PROFILE,
+                "email" | "email_verified" => Scopes::EMAIL,
+                "address" | "formatted" | "street_address" | "locality" | "region"
+                | "postal_code" | "country" => Scopes::ADDRESS,
+                "phone_number" | "phone_number_verified" => Scopes::PHONE,
+                _ => return None,
+            };
+            Some(Scope::new(scope.to_string()))
+        })
+        .collect()
+}
+
+///
+/// Error validating [`Scopes`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ScopesError {
+    ///
+    /// The provider does not support one or more of the requested scopes.
+    ///
+    #[error("Unsupported scopes: {}", .0.iter().map(|scope| scope.as_str()).collect::<Vec<_>>().join(" "))]
+    Unsupported(Vec<Scope>),
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::{AuthUrl, ClientId, CsrfToken, TokenUrl};
+
+    use crate::core::{CoreClient, CoreResponseType};
+    use crate::{
+        scopes_for_claims, AuthenticationFlow, IssuerUrl, JsonWebKeySet, Nonce, Scope, Scopes,
+        ScopesError,
+    };
+
+    #[test]
+    fn test_scopes_openid_and_dedup() {
+        let scopes = Scopes::new()
+            .add_email()
+            .add_scope(Scope::new("openid".to_string()))
+            .add_profile()
+            .add_scope(Scope::new("email".to_string()));
+        assert_eq!(
+            scopes
+                .iter()
+                .map(|scope| scope.as_str())
+                .collect::<Vec<_>>(),
+            vec!["openid", "email", "profile"]
+        );
+        assert!(scopes.contains(Scopes::PROFILE));
+        assert!(!scopes.contains(Scopes::OFFLINE_ACCESS));
+
+        let collected = vec![Scope::new("phone".to_string())]
+            .into_iter()
+            .collect::<Scopes>();
+        assert_eq!(
+            Vec::<Scope>::from(collected),
+            vec![
+                Scope::new("openid".to_string()),
+                Scope::new("phone".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scopes_authorize_url() {
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            None,
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            None,
+            JsonWebKeySet::default(),
+        );
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_scopes(Scopes::new().add_email().add_offline_access())
+            .url();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+             scope=openid+email+offline_access&nonce=NONCE456",
+            authorize_url.to_string()
+        );
+    }
+
+    #[test]
+    fn test_scopes_unsupported() {
+        let scopes = Scopes::new().add_email().add_address();
+        let supported = vec![
+            Scope::new("openid".to_string()),
+            Scope::new("email".to_string()),
+        ];
+
+        assert_eq!(scopes.validate(None), Ok(()));
+        assert_eq!(
+            scopes.unsupported(Some(&supported)),
+            vec![&Scope::new("address".to_string())]
+        );
+        let err = scopes.validate(Some(&supported)).unwrap_err();
+        assert_eq!(
+            err,
+            ScopesError::Unsupported(vec![Scope::new("address".to_string())])
+        );
+        assert_eq!(err.to_string(), "Unsupported scopes: address");
+        Scopes::new()
+            .add_email()
+            .validate(Some(&supported))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_scopes_for_claims() {
+        let scope_names = |scopes: Scopes| {
+            scopes
+                .into_iter()
+                .map(|scope| scope.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            scope_names(scopes_for_claims(&["email", "email_verified"])),
+            vec!["openid", "email"]
+        );
+        assert_eq!(
+            scope_names(scopes_for_claims(&["address", "locality", "postal_code"])),
+            vec!["openid", "address"]
+        );
+        assert_eq!(
+            scope_names(scopes_for_claims(&[
+                "name",
+                "picture",
+                "birthdate",
+                "updated_at"
+            ])),
+            vec!["openid", "profile"]
+        );
+        assert_eq!(
+            scope_names(scopes_for_claims(&[
+                "sub",
+                "phone_number",
+                "given_name",
+                "email",
+                "custom_claim"
+            ])),
+            vec!["openid", "phone", "profile", "email"]
+        );
+        assert_eq!(scope_names(scopes_for_claims(&[])), vec!["openid"]);
+    }
+}
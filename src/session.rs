@@ -0,0 +1,130 @@
+use std::fmt::Write;
+
+use oauth2::ClientId;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+///
+/// Computes the session state returned by the OpenID Connect Provider alongside an authorization
+/// response, as described in
+/// [Section 3 of OpenID Connect Session Management 1.0](
+/// https://openid.net/specs/openid-connect-session-1_0.html#CreatingUpdatingSessions).
+///
+/// The session state is the hex-encoded SHA-256 hash of the client ID, the `origin` of the Relying
+/// Party, the OpenID Connect Provider's browser state, and the `salt` (separated by spaces),
+/// followed by a `.` and the salt itself. This matches the session state computed by the example
+/// `check_session_iframe` implementation in the specification, which compares it to the session
+/// state supplied by the Relying Party's iframe in order to detect changes to the End-User's
+/// session. The salt should be a fresh random value each time the session state is computed.
+///
+pub fn compute_session_state(
+    client_id: &ClientId,
+    origin: &Url,
+    op_browser_state: &str,
+    salt: &str,
+) -> String {
+    let hash = Sha256::digest(
+        format!(
+            "{} {} {} {}",
+            client_id.as_str(),
+            origin.origin().ascii_serialization(),
+            op_browser_state,
+            salt
+        )
+        .as_bytes(),
+    );
+    let mut session_state = hash.iter().fold(String::new(), |mut acc, byte| {
+        let _ = write!(acc, "{:02x}", byte);
+        acc
+    });
+    session_state.push('.');
+    session_state.push_str(salt);
+    session_state
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::ClientId;
+    use url::Url;
+
+    use crate::core::CoreProviderMetadata;
+    use crate::{compute_session_state, CheckSessionIframeUrl};
+
+    #[test]
+    fn test_compute_session_state() {
+        let client_id = ClientId::new("my_client".to_string());
+        let session_state = compute_session_state(
+            &client_id,
+            &Url::parse("https://client.example.org").unwrap(),
+            "opbs_value",
+            "2a1c4f",
+        );
+        assert_eq!(
+            session_state,
+            "3d77fea8b184079f8366daa55bf498042ac48be4ef4c0b2dd4afb0979d489600.2a1c4f"
+        );
+
+        // Only the origin of the URL is used.
+        assert_eq!(
+            compute_session_state(
+                &client_id,
+                &Url::parse("https://client.example.org/callback?x=1").unwrap(),
+                "opbs_value",
+                "2a1c4f",
+            ),
+            session_state
+        );
+
+        // The session state changes along with the browser state or salt.
+        assert_ne!(
+            compute_session_state(
+                &client_id,
+                &Url::parse("https://client.example.org").unwrap(),
+                "other_opbs_value",
+                "2a1c4f",
+            ),
+            session_state
+        );
+        assert!(compute_session_state(
+            &client_id,
+            &Url::parse("https://client.example.org").unwrap(),
+            "opbs_value",
+            "other_salt",
+        )
+        .ends_with(".other_salt"));
+    }
+
+    #[test]
+    fn test_check_session_iframe_metadata() {
+        let provider_metadata: CoreProviderMetadata = serde_json::from_str(
+            "{
+                \"issuer\": \"https://server.example.com\",
+                \"authorization_endpoint\": \"https://server.example.com/authorize\",
+                \"jwks_uri\": \"https://server.example.com/jwks.json\",
+                \"response_types_supported\": [\"code\"],
+                \"subject_types_supported\": [\"public\"],
+                \"id_token_signing_alg_values_supported\": [\"RS256\"],
+                \"check_session_iframe\": \"https://server.example.com/check_session\",
+                \"op_policy_uri\": \"https://server.example.com/policy\"
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            provider_metadata.check_session_iframe(),
+            Some(
+                &CheckSessionIframeUrl::new("https://server.example.com/check_session".to_string())
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            provider_metadata.op_policy_uri().unwrap().as_str(),
+            "https://server.example.com/policy"
+        );
+
+        let serialized = serde_json::to_value(&provider_metadata).unwrap();
+        assert_eq!(
+            serialized["check_session_iframe"],
+            "https://server.example.com/check_session"
+        );
+    }
+}
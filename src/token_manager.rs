@@ -0,0 +1,534 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use oauth2::{
+    AccessToken, ErrorResponse, HttpRequest, HttpResponse, RefreshToken, RequestTokenError,
+    RevocableToken, TokenIntrospectionResponse, TokenType,
+};
+use thiserror::Error;
+
+use crate::{
+    AdditionalClaims, AuthDisplay, AuthPrompt, ClaimsVerificationError, Client, GenderClaim,
+    IdTokenVerifier, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
+    JwsSigningAlgorithm, Nonce, RefreshTokenUpdate, SubjectIdentifier, TokenResponse,
+};
+
+const DEFAULT_REFRESH_SKEW: Duration = Duration::from_secs(30);
+
+///
+/// Manages an access token obtained with a refresh token, refreshing it as needed.
+///
+/// This is useful for long-running background processes that request the `offline_access` scope
+/// (see [`Scopes::add_offline_access`](crate::Scopes::add_offline_access)) and subsequently
+/// operate on refresh tokens alone, without the End-User being present. Note that the OpenID
+/// Connect Core specification requires the `consent` prompt to be requested along with
+/// `offline_access`, unless the provider has other means of obtaining consent.
+///
+/// [`access_token`](Self::access_token) returns the current access token, first performing a
+/// refresh token grant if the access token expires within the refresh skew (30 seconds by
+/// default). Rotated refresh tokens replace the stored refresh token (see
+/// [`RefreshTokenUpdate`]), and any ID token returned by the refresh is verified using the
+/// client's [`IdTokenVerifier`], with its subject required to match the current End-User (see
+/// [Section 12.2](https://openid.net/specs/openid-connect-core-1_0.html#RefreshTokenResponse) of
+/// the OpenID Connect Core specification).
+///
+#[derive(Clone)]
+pub struct TokenManager {
+    access_token: AccessToken,
+    expires_at: Option<DateTime<Utc>>,
+    refresh_token: Option<RefreshToken>,
+    subject: Option<SubjectIdentifier>,
+    refresh_skew: Duration,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+}
+impl TokenManager {
+    ///
+    /// Initializes a manager for the given access token, which expires at `expires_at` (if
+    /// known), and the refresh token used to obtain new access tokens.
+    ///
+    pub fn new(
+        access_token: AccessToken,
+        expires_at: Option<DateTime<Utc>>,
+        refresh_token: Option<RefreshToken>,
+    ) -> Self {
+        Self {
+            access_token,
+            expires_at,
+            refresh_token,
+            subject: None,
+            refresh_skew: DEFAULT_REFRESH_SKEW,
+            time_fn: Arc::new(Utc::now),
+        }
+    }
+
+    ///
+    /// Initializes a manager from a token response (e.g., the response to the initial
+    /// authorization code exchange), computing the access token expiration time from the
+    /// response's `expires_in` field relative to the current system time.
+    ///
+    pub fn from_token_response<TR, TT>(token_response: &TR) -> Self
+    where
+        TR: oauth2::TokenResponse<TT>,
+        TT: TokenType,
+    {
+        Self::new(
+            token_response.access_token().clone(),
+            expiration(Utc::now(), token_response.expires_in()),
+            token_response.refresh_token().cloned(),
+        )
+    }
+
+    ///
+    /// Sets the subject identifier of the End-User, which is required to match the `sub` claim
+    /// of any ID token returned by a refresh. If not set, the subject of the first such ID token
+    /// is used for subsequent refreshes.
+    ///
+    pub fn set_subject(mut self, subject: SubjectIdentifier) -> Self {
+        self.subject = Some(subject);
+        self
+    }
+
+    ///
+    /// Sets how long before its expiration time an access token is refreshed. Defaults to 30
+    /// seconds.
+    ///
+    pub fn set_refresh_skew(mut self, refresh_skew: Duration) -> Self {
+        self.refresh_skew = refresh_skew;
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for determining whether the access token needs to be refreshed and
+    /// for verifying refreshed ID tokens. By default, the current system time is used (i.e.,
+    /// [`Utc::now`]).
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    ///
+    /// Returns the expiration time of the current access token, if known.
+    ///
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+
+    ///
+    /// Returns the refresh token to persist.
+    ///
+    pub fn refresh_token(&self) -> Option<&RefreshToken> {
+        self.refresh_token.as_ref()
+    }
+
+    ///
+    /// Returns whether the access token expires within the refresh skew. Access tokens with an
+    /// unknown expiration time are never refreshed.
+    ///
+    pub fn needs_refresh(&self) -> bool {
+        self.expires_at.map_or(false, |expires_at| {
+            chrono::Duration::from_std(self.refresh_skew)
+                .ok()
+                .and_then(|refresh_skew| expires_at.checked_sub_signed(refresh_skew))
+                .map_or(true, |refresh_at| (*self.time_fn)() >= refresh_at)
+        })
+    }
+
+    ///
+    /// Returns the current access token, first refreshing it using the specified synchronous
+    /// HTTP client if it expires within the refresh skew.
+    ///
+    /// If the refresh fails, the stored tokens are left unchanged and the error is returned.
+    ///
+    pub fn access_token<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE, HC, RE>(
+        &mut self,
+        client: &Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>,
+        http_client: HC,
+    ) -> Result<&AccessToken, TokenManagerError<RE, TE>>
+    where
+        AC: AdditionalClaims,
+        AD: AuthDisplay,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        P: AuthPrompt,
+        TE: ErrorResponse + 'static,
+        TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+        TT: TokenType + 'static,
+        TIR: TokenIntrospectionResponse<TT>,
+        RT: RevocableToken,
+        TRE: ErrorResponse + 'static,
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        if self.needs_refresh() {
+            let refresh_token = self
+                .refresh_token
+                .as_ref()
+                .ok_or(TokenManagerError::MissingRefreshToken)?;
+            let token_response = client
+                .exchange_refresh_token(refresh_token)
+                .request(http_client)
+                .map_err(TokenManagerError::Request)?;
+            self.update(client.id_token_verifier(), &token_response)?;
+        }
+        Ok(&self.access_token)
+    }
+
+    ///
+    /// Returns the current access token, first refreshing it using the specified asynchronous
+    /// HTTP client if it expires within the refresh skew.
+    ///
+    /// See [`access_token`](Self::access_token).
+    ///
+    pub async fn access_token_async<
+        AC,
+        AD,
+        GC,
+        JE,
+        JS,
+        JT,
+        JU,
+        K,
+        P,
+        TE,
+        TR,
+        TT,
+        TIR,
+        RT,
+        TRE,
+        C,
+        F,
+        RE,
+    >(
+        &mut self,
+        client: &Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>,
+        http_client: C,
+    ) -> Result<&AccessToken, TokenManagerError<RE, TE>>
+    where
+        AC: AdditionalClaims,
+        AD: AuthDisplay,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        P: AuthPrompt,
+        TE: ErrorResponse + 'static,
+        TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+        TT: TokenType + 'static,
+        TIR: TokenIntrospectionResponse<TT>,
+        RT: RevocableToken,
+        TRE: ErrorResponse + 'static,
+        C: FnOnce(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        if self.needs_refresh() {
+            let refresh_token = self
+                .refresh_token
+                .as_ref()
+                .ok_or(TokenManagerError::MissingRefreshToken)?;
+            let token_response = client
+                .exchange_refresh_token(refresh_token)
+                .request_async(http_client)
+                .await
+                .map_err(TokenManagerError::Request)?;
+            self.update(client.id_token_verifier(), &token_response)?;
+        }
+        Ok(&self.access_token)
+    }
+
+    fn update<AC, GC, JE, JS, JT, JU, K, TR, TT, RE, TE>(
+        &mut self,
+        verifier: IdTokenVerifier<JS, JT, JU, K>,
+        token_response: &TR,
+    ) -> Result<(), TokenManagerError<RE, TE>>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+        TT: TokenType,
+        RE: std::error::Error + 'static,
+        TE: ErrorResponse + 'static,
+    {
+        let now = (*self.time_fn)();
+        if let Some(id_token) = token_response.id_token() {
+            let time_fn = self.time_fn.clone();
+            let verifier = verifier.set_time_fn(move || time_fn());
+            // Refreshed ID tokens need not contain a nonce, since no authentication request was
+            // made.
+            let claims = id_token
+                .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+                .map_err(TokenManagerError::ClaimsVerification)?;
+            match self.subject {
+                Some(ref subject) if claims.subject() != subject => {
+                    return Err(TokenManagerError::ClaimsVerification(
+                        ClaimsVerificationError::InvalidSubject(format!(
+                            "expected `{}` (found `{}`)",
+                            subject.as_str(),
+                            claims.subject().as_str()
+                        )),
+                    ));
+                }
+                Some(_) => {}
+                None => self.subject = Some(claims.subject().clone()),
+            }
+        }
+
+        self.access_token = token_response.access_token().clone();
+        self.expires_at = expiration(now, token_response.expires_in());
+        if let Some(previous) = self.refresh_token.take() {
+            self.refresh_token =
+                Some(RefreshTokenUpdate::new(previous, token_response).into_refresh_token());
+        } else {
+            self.refresh_token = token_response.refresh_token().cloned();
+        }
+        Ok(())
+    }
+}
+impl std::fmt::Debug for TokenManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenManager")
+            .field("access_token", &self.access_token)
+            .field("expires_at", &self.expires_at)
+            .field("refresh_token", &self.refresh_token)
+            .field("subject", &self.subject)
+            .field("refresh_skew", &self.refresh_skew)
+            .finish()
+    }
+}
+
+fn expiration(now: DateTime<Utc>, expires_in: Option<Duration>) -> Option<DateTime<Utc>> {
+    expires_in
+        .and_then(|expires_in| chrono::Duration::from_std(expires_in).ok())
+        .and_then(|expires_in| now.checked_add_signed(expires_in))
+}
+
+///
+/// Error refreshing an access token using a [`TokenManager`].
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TokenManagerError<RE, TE>
+where
+    RE: std::error::Error + 'static,
+    TE: ErrorResponse + 'static,
+{
+    ///
+    /// The access token has expired, and no refresh token is available.
+    ///
+    #[error("Access token expired and no refresh token is available")]
+    MissingRefreshToken,
+    ///
+    /// The refresh token request failed.
+    ///
+    #[error("Failed to refresh access token")]
+    Request(#[source] RequestTokenError<RE, TE>),
+    ///
+    /// The ID token returned by the refresh failed verification.
+    ///
+    #[error("Failed to verify refreshed ID token")]
+    ClaimsVerification(#[source] ClaimsVerificationError),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    use chrono::{DateTime, TimeZone, Utc};
+    use oauth2::RefreshToken;
+    use serde_json::json;
+
+    use crate::claim_sources::tests::claim_source_jwt;
+    use crate::core::{CoreClient, CoreErrorResponseType, CoreJsonWebKey};
+    use crate::discovery::tests::block_on;
+    use crate::jwt::tests::TEST_RSA_PUB_KEY;
+    use crate::tests::mock_json_response;
+    use crate::{
+        AccessToken, ClaimsVerificationError, HttpRequest, HttpResponse, JsonWebKeySet,
+        RequestTokenError, SubjectIdentifier, TokenManager, TokenManagerError,
+    };
+
+    const NOW: i64 = 1700000000;
+
+    fn now() -> DateTime<Utc> {
+        Utc.timestamp_opt(NOW, 0).single().unwrap()
+    }
+
+    fn new_client() -> CoreClient {
+        let client = crate::tests::new_client();
+        client.replace_jwks(JsonWebKeySet::new(vec![serde_json::from_str::<
+            CoreJsonWebKey,
+        >(TEST_RSA_PUB_KEY)
+        .unwrap()]));
+        client
+    }
+
+    fn new_manager(expires_in: i64) -> TokenManager {
+        TokenManager::new(
+            AccessToken::new("access_1".to_string()),
+            Some(now() + chrono::Duration::seconds(expires_in)),
+            Some(RefreshToken::new("refresh_1".to_string())),
+        )
+        .set_time_fn(now)
+    }
+
+    fn id_token(subject: &str) -> String {
+        claim_source_jwt(
+            "https://example",
+            json!({
+                "aud": "aaa",
+                "sub": subject,
+                "iat": NOW,
+                "exp": NOW + 3600,
+            }),
+        )
+    }
+
+    fn token_response(body: serde_json::Value) -> Result<HttpResponse, std::io::Error> {
+        mock_json_response(http::StatusCode::OK, &body.to_string())
+    }
+
+    #[test]
+    fn test_token_manager_refresh_on_expiry() {
+        let client = new_client();
+        let mut manager = new_manager(-1);
+        let requests = Cell::new(0);
+
+        let http_client = |request: HttpRequest| {
+            requests.set(requests.get() + 1);
+            assert!(String::from_utf8(request.body)
+                .unwrap()
+                .contains("refresh_token=refresh_1"));
+            token_response(json!({
+                "access_token": "access_2",
+                "token_type": "bearer",
+                "expires_in": 3600,
+                "refresh_token": "refresh_2",
+                "id_token": id_token("subject"),
+            }))
+        };
+        assert!(manager.needs_refresh());
+        assert_eq!(
+            manager.access_token(&client, http_client).unwrap().secret(),
+            "access_2"
+        );
+        assert_eq!(requests.get(), 1);
+        assert_eq!(manager.refresh_token().unwrap().secret(), "refresh_2");
+        assert_eq!(
+            manager.expires_at(),
+            Some(now() + chrono::Duration::seconds(3600))
+        );
+
+        // The refreshed token is returned without another request.
+        assert!(!manager.needs_refresh());
+        assert_eq!(
+            manager
+                .access_token(&client, |_| -> Result<HttpResponse, std::io::Error> {
+                    panic!("no request should be sent")
+                })
+                .unwrap()
+                .secret(),
+            "access_2"
+        );
+
+        // A refreshed ID token must be issued to the same End-User.
+        let mut manager = new_manager(-1).set_subject(SubjectIdentifier::new("other".to_string()));
+        match manager.access_token(&client, |_| {
+            token_response(json!({
+                "access_token": "access_2",
+                "token_type": "bearer",
+                "id_token": id_token("subject"),
+            }))
+        }) {
+            Err(TokenManagerError::ClaimsVerification(
+                ClaimsVerificationError::InvalidSubject(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(manager.refresh_token().unwrap().secret(), "refresh_1");
+    }
+
+    #[test]
+    fn test_token_manager_refresh_skew() {
+        let client = new_client();
+
+        // Expires in 20 seconds, which is within the default 30 second skew.
+        let mut manager = new_manager(20);
+        assert!(manager.needs_refresh());
+        assert_eq!(
+            block_on(manager.access_token_async(&client, |_| async {
+                token_response(json!({"access_token": "access_2", "token_type": "bearer"}))
+            }))
+            .unwrap()
+            .secret(),
+            "access_2"
+        );
+        // The refresh token wasn't rotated, so the previous one is retained.
+        assert_eq!(manager.refresh_token().unwrap().secret(), "refresh_1");
+        assert_eq!(manager.expires_at(), None);
+        assert!(!manager.needs_refresh());
+
+        // With a smaller skew, the current access token is still used.
+        let mut manager = new_manager(20).set_refresh_skew(Duration::from_secs(10));
+        assert!(!manager.needs_refresh());
+        assert_eq!(
+            manager
+                .access_token(&client, |_| -> Result<HttpResponse, std::io::Error> {
+                    panic!("no request should be sent")
+                })
+                .unwrap()
+                .secret(),
+            "access_1"
+        );
+    }
+
+    #[test]
+    fn test_token_manager_refresh_failure() {
+        let client = new_client();
+
+        let mut manager = new_manager(-1);
+        match manager.access_token(&client, |_| {
+            mock_json_response(
+                http::StatusCode::BAD_REQUEST,
+                "{\"error\": \"invalid_grant\"}",
+            )
+        }) {
+            Err(TokenManagerError::Request(RequestTokenError::ServerResponse(err))) => {
+                assert_eq!(*err.error(), CoreErrorResponseType::InvalidGrant)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // The failed refresh leaves the stored tokens unchanged.
+        assert_eq!(manager.refresh_token().unwrap().secret(), "refresh_1");
+        assert!(manager.needs_refresh());
+
+        let mut manager =
+            TokenManager::new(AccessToken::new("access_1".to_string()), Some(now()), None)
+                .set_time_fn(now);
+        match manager.access_token(&client, |_| -> Result<HttpResponse, std::io::Error> {
+            panic!("no request should be sent")
+        }) {
+            Err(TokenManagerError::MissingRefreshToken) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
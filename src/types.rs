@@ -14,13 +14,16 @@ use rand::{thread_rng, Rng};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, VecSkipError};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use url::Url;
 
+use super::helpers::constant_time_eq;
 use super::http_utils::{check_content_type, MIME_TYPE_JSON, MIME_TYPE_JWKS};
+use super::webfinger::{ISSUER_REL, WEBFINGER_URL_PATH};
 use super::{
-    AccessToken, AuthorizationCode, DiscoveryError, HttpRequest, HttpResponse,
-    SignatureVerificationError,
+    AccessToken, AsyncHttpClient, AuthorizationCode, DiscoveryError, HttpRequest, HttpResponse,
+    RedirectUrl, SignatureVerificationError, SyncHttpClient, CONFIG_URL_SUFFIX,
 };
 
 ///
@@ -54,6 +57,10 @@ impl<T> LocalizedClaim<T> {
     ///
     /// Returns the entry for the specified locale or `None` if there is no such entry.
     ///
+    /// Passing `None` returns the default (untagged) entry. Locales are matched exactly: if the
+    /// claim only contains an `en` entry, looking up `en-US` returns `None` rather than falling
+    /// back to the less specific tag.
+    ///
     pub fn get(&self, locale: Option<&LanguageTag>) -> Option<&T> {
         if let Some(l) = locale {
             self.0.get(l)
@@ -62,6 +69,51 @@ impl<T> LocalizedClaim<T> {
         }
     }
 
+    ///
+    /// Returns the entry best matching the End-User's preferred locales.
+    ///
+    /// The `preferred` locales are considered in priority order, using the
+    /// [RFC 4647](https://tools.ietf.org/html/rfc4647#section-3.4) "lookup" scheme: if no entry
+    /// matches a locale exactly, its subtags are progressively removed from the end (e.g.,
+    /// `de-CH-1996`, then `de-CH`, then `de`) before moving on to the next preferred locale. If no
+    /// preferred locale matches, the default (untagged) entry is returned, followed by an
+    /// arbitrary (but deterministic) entry if the claim has no default entry.
+    ///
+    pub fn get_best(&self, preferred: &[LanguageTag]) -> Option<&T> {
+        preferred
+            .iter()
+            .find_map(|locale| {
+                let mut range = locale.as_str();
+                loop {
+                    if let Some(value) = self.0.get(&LanguageTag::new(range.to_string())) {
+                        return Some(value);
+                    }
+                    range = &range[..range.rfind('-')?];
+                    // Per RFC 4647, a single-character subtag (e.g., an extension singleton or the
+                    // private use `x`) is removed along with the subtag that follows it.
+                    if range.len() >= 2 && range.as_bytes()[range.len() - 2] == b'-' {
+                        range = &range[..range.len() - 2];
+                    }
+                }
+            })
+            .or(self.1.as_ref())
+            .or_else(|| {
+                self.0
+                    .iter()
+                    .min_by(|(a, _), (b, _)| a.cmp(b))
+                    .map(|(_, value)| value)
+            })
+    }
+
+    ///
+    /// Returns an iterator over the locales for which the claim contains an entry.
+    ///
+    /// The default (untagged) entry, if present, is returned first as `None`.
+    ///
+    pub fn languages(&self) -> impl Iterator<Item = Option<&LanguageTag>> {
+        self.iter().map(|(locale, _)| locale)
+    }
+
     ///
     /// Returns an iterator over the locales and claim value entries.
     ///
@@ -208,6 +260,26 @@ pub enum SigningError {
     Other(String),
 }
 
+///
+/// Error decrypting a message.
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecryptionError {
+    /// Failed to decrypt or authenticate the message using the given key and parameters.
+    #[error("Crypto error")]
+    CryptoError,
+    /// The supplied key cannot be used with the given algorithm.
+    #[error("Invalid cryptographic key: {0}")]
+    InvalidKey(String),
+    /// Unsupported key management or content encryption algorithm.
+    #[error("Unsupported encryption algorithm: {0}")]
+    UnsupportedAlg(String),
+    /// An unexpected error occurred.
+    #[error("Other error: {0}")]
+    Other(String),
+}
+
 ///
 /// JSON Web Key.
 ///
@@ -233,6 +305,17 @@ where
     ///
     fn key_use(&self) -> Option<&JU>;
 
+    ///
+    /// Returns whether the key's permitted operations (i.e., its `key_ops` parameter) include
+    /// verifying digital signatures.
+    ///
+    /// The default implementation permits all operations, which is appropriate for key types that
+    /// don't support restricting key operations.
+    ///
+    fn allows_signature_verification(&self) -> bool {
+        true
+    }
+
     ///
     /// Returns the algorithm (e.g. ES512) this key must be used with, or `Unspecified` if
     /// no algorithm constraint was given, or unsupported if the algorithm is not for signing.
@@ -242,6 +325,17 @@ where
     #[cfg(feature = "jwk-alg")]
     fn signing_alg(&self) -> JsonWebKeyAlgorithm<&JS>;
 
+    ///
+    /// Returns whether this key's type-specific parameters (e.g., the elliptic curve) are
+    /// compatible with the given signature algorithm.
+    ///
+    /// This is used during key selection to skip keys that share the algorithm's key type but
+    /// can't be used with it. The default implementation accepts every algorithm.
+    ///
+    fn supports_signing_alg(&self, _signature_alg: &JS) -> bool {
+        true
+    }
+
     ///
     /// Initializes a new symmetric key or shared signing secret from the specified raw bytes.
     ///
@@ -295,6 +389,44 @@ where
     fn as_verification_key(&self) -> K;
 }
 
+///
+/// Private or symmetric key for decrypting
+/// [JSON Web Encryption (JWE)](https://tools.ietf.org/html/rfc7516) payloads.
+///
+pub trait PrivateDecryptionKey<JE, JK, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Decrypts the JWE Encrypted Key using the given key management algorithm (`alg`) and returns
+    /// the Content Encryption Key (CEK) to be used with the given content encryption algorithm
+    /// (`enc`).
+    ///
+    fn decrypt_key(
+        &self,
+        alg: &JK,
+        enc: &JE,
+        encrypted_key: &[u8],
+    ) -> Result<Vec<u8>, DecryptionError>;
+
+    ///
+    /// Decrypts and authenticates the JWE `ciphertext` using the given content encryption
+    /// algorithm, Content Encryption Key (`cek`), initialization vector (`iv`), Additional
+    /// Authenticated Data (`aad`) and authentication tag (`tag`), and returns the plaintext.
+    ///
+    fn decrypt_content(
+        &self,
+        enc: &JE,
+        cek: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<Vec<u8>, DecryptionError>;
+}
+
 ///
 /// Key type (e.g., RSA).
 ///
@@ -306,6 +438,7 @@ pub trait JsonWebKeyType:
 ///
 /// Curve type (e.g., P256).
 ///
+#[allow(dead_code)]
 pub trait JsonCurveType:
     Clone + Debug + DeserializeOwned + PartialEq + Serialize + 'static
 {
@@ -423,12 +556,46 @@ impl AsRef<str> for AuthenticationContextClass {
 
 new_type![
     ///
-    /// Identifier for an authentication method (e.g., `password` or `totp`).
+    /// Identifier for an authentication method (e.g., `pwd` or `otp`).
     ///
     /// Defining specific AMR identifiers is beyond the scope of the OpenID Connect Core spec.
+    /// Constants are provided for common values registered by
+    /// [RFC 8176](https://www.rfc-editor.org/rfc/rfc8176.html#section-2), which may be compared
+    /// against the `amr` claim using
+    /// [`IdTokenClaims::amr_contains`](crate::IdTokenClaims::amr_contains).
     ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     AuthenticationMethodReference(String)
+    impl {
+        ///
+        /// Password-based authentication.
+        ///
+        pub const PWD: &'static str = "pwd";
+        ///
+        /// One-time password.
+        ///
+        pub const OTP: &'static str = "otp";
+        ///
+        /// Multiple-factor authentication.
+        ///
+        pub const MFA: &'static str = "mfa";
+        ///
+        /// Confirmation using SMS to a registered phone number.
+        ///
+        pub const SMS: &'static str = "sms";
+        ///
+        /// Proof-of-possession of a hardware-secured key.
+        ///
+        pub const HWK: &'static str = "hwk";
+        ///
+        /// Biometric authentication using facial recognition.
+        ///
+        pub const FACE: &'static str = "face";
+        ///
+        /// Biometric authentication using a fingerprint.
+        ///
+        pub const FPT: &'static str = "fpt";
+    }
 ];
 
 new_type![
@@ -449,13 +616,7 @@ new_type![
             JS: JwsSigningAlgorithm<JT>,
             JT: JsonWebKeyType,
         {
-            alg.hash_bytes(access_token.secret().as_bytes())
-                .map(|hash| {
-                    Self::new(
-                        base64::encode_config(&hash[0..hash.len() / 2], base64::URL_SAFE_NO_PAD)
-                    )
-                })
-                .map_err(SigningError::UnsupportedAlg)
+            compute_access_token_hash(alg, access_token).map(Self::new)
         }
     }
 ];
@@ -519,17 +680,59 @@ new_type![
             JS: JwsSigningAlgorithm<JT>,
             JT: JsonWebKeyType,
         {
-            alg.hash_bytes(code.secret().as_bytes())
-                .map(|hash| {
-                    Self::new(
-                        base64::encode_config(&hash[0..hash.len() / 2], base64::URL_SAFE_NO_PAD)
-                    )
-                })
-                .map_err(SigningError::UnsupportedAlg)
+            compute_code_hash(alg, code).map(Self::new)
         }
     }
 ];
 
+///
+/// Computes the access token hash (`at_hash`) of an [`AccessToken`] for an ID token signed with
+/// the given signature algorithm.
+///
+/// As specified in
+/// [Section 3.1.3.6](https://openid.net/specs/openid-connect-core-1_0.html#CodeIDToken) of the
+/// OpenID Connect Core spec, the hash is the base64url encoding of the left-most half of the hash
+/// of the access token, using the hash algorithm of the signature algorithm (e.g., SHA-256 for
+/// `RS256`). This is the same hash used by [`AccessTokenHash::from_token`] and by ID token
+/// verification.
+///
+pub fn compute_access_token_hash<JS, JT>(
+    alg: &JS,
+    access_token: &AccessToken,
+) -> Result<String, SigningError>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    compute_left_half_hash(alg, access_token.secret().as_bytes())
+}
+
+///
+/// Computes the authorization code hash (`c_hash`) of an [`AuthorizationCode`] for an ID token
+/// signed with the given signature algorithm.
+///
+/// The hash is computed in the same manner as [`compute_access_token_hash`] (see
+/// [Section 3.3.2.11](https://openid.net/specs/openid-connect-core-1_0.html#HybridIDToken) of the
+/// OpenID Connect Core spec).
+///
+pub fn compute_code_hash<JS, JT>(alg: &JS, code: &AuthorizationCode) -> Result<String, SigningError>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    compute_left_half_hash(alg, code.secret().as_bytes())
+}
+
+fn compute_left_half_hash<JS, JT>(alg: &JS, bytes: &[u8]) -> Result<String, SigningError>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    alg.hash_bytes(bytes)
+        .map(|hash| base64::encode_config(&hash[0..hash.len() / 2], base64::URL_SAFE_NO_PAD))
+        .map_err(SigningError::UnsupportedAlg)
+}
+
 new_type![
     #[derive(Deserialize, Eq, Hash, Serialize)]
     pub(crate) Base64UrlEncodedBytes(
@@ -538,6 +741,15 @@ new_type![
     )
 ];
 
+new_url_type![
+    ///
+    /// URL of a claims provider endpoint from which
+    /// [distributed claims](https://openid.net/specs/openid-connect-core-1_0.html#DistributedExample)
+    /// are retrieved.
+    ///
+    ClaimSourceEndpointUrl
+];
+
 new_type![
     ///
     /// OpenID Connect client name.
@@ -546,6 +758,15 @@ new_type![
     ClientName(String)
 ];
 
+new_url_type![
+    ///
+    /// URL of the OpenID Connect Provider's iframe for checking the End-User's session state, as
+    /// described in [OpenID Connect Session Management 1.0](
+    /// https://openid.net/specs/openid-connect-session-1_0.html).
+    ///
+    CheckSessionIframeUrl
+];
+
 new_url_type![
     ///
     /// Client configuration endpoint URL.
@@ -588,8 +809,82 @@ new_type![
     ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     EndUserBirthday(String)
+    impl {
+        ///
+        /// Initialize a new birthday, validating that it is a valid `YYYY-MM-DD` date, a
+        /// `0000-MM-DD` date with the year omitted, or a `YYYY` year.
+        ///
+        /// Use [`EndUserBirthday::new`] to accept any value.
+        ///
+        pub fn new_iso8601(s: String) -> Result<Self, BirthdayParseError> {
+            parse_birthday(&s)?;
+            Ok(Self::new(s))
+        }
+
+        ///
+        /// Parses the birthday into its year (`None` if omitted using the `0000` year), month,
+        /// and day.
+        ///
+        /// Returns [`BirthdayParseError::YearOnly`] if the birthday only specifies a year.
+        ///
+        pub fn parse(&self) -> Result<(Option<i32>, u32, u32), BirthdayParseError> {
+            match parse_birthday(self)? {
+                (year, Some((month, day))) => Ok((year, month, day)),
+                (_, None) => Err(BirthdayParseError::YearOnly),
+            }
+        }
+    }
 ];
 
+fn parse_birthday(s: &str) -> Result<(Option<i32>, Option<(u32, u32)>), BirthdayParseError> {
+    let invalid_format = || BirthdayParseError::InvalidFormat(s.to_string());
+    let parse_component = |component: &str, len: usize| {
+        if component.len() == len && component.bytes().all(|b| b.is_ascii_digit()) {
+            component.parse::<u32>().map_err(|_| invalid_format())
+        } else {
+            Err(invalid_format())
+        }
+    };
+
+    let mut components = s.split('-');
+    // str::split always returns at least one element.
+    let year = parse_component(components.next().unwrap(), 4)?;
+    let month_day = match (components.next(), components.next(), components.next()) {
+        (None, _, _) => None,
+        (Some(month), Some(day), None) => {
+            Some((parse_component(month, 2)?, parse_component(day, 2)?))
+        }
+        _ => return Err(invalid_format()),
+    };
+    if let Some((month, day)) = month_day {
+        // When the year is omitted, use a leap year so that February 29 is accepted.
+        if chrono::NaiveDate::from_ymd_opt(if year == 0 { 2000 } else { year as i32 }, month, day)
+            .is_none()
+        {
+            return Err(BirthdayParseError::InvalidDate(s.to_string()));
+        }
+    }
+
+    Ok((Some(year as i32).filter(|year| *year != 0), month_day))
+}
+
+///
+/// Error parsing an [`EndUserBirthday`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BirthdayParseError {
+    /// The birthday is not in `YYYY-MM-DD` or `YYYY` format.
+    #[error("Birthday `{0}` must be in `YYYY-MM-DD` or `YYYY` format")]
+    InvalidFormat(String),
+    /// The birthday's month or day is out of range.
+    #[error("Birthday `{0}` is not a valid date")]
+    InvalidDate(String),
+    /// The birthday only specifies a year.
+    #[error("Birthday only specifies a year")]
+    YearOnly,
+}
+
 new_type![
     ///
     /// End user's e-mail address.
@@ -644,8 +939,63 @@ new_type![
     ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     EndUserPhoneNumber(String)
+    impl {
+        ///
+        /// Initialize a new phone number, validating that it adheres to the
+        /// [E.164](https://www.itu.int/rec/T-REC-E.164) format recommended by
+        /// [OpenID Connect Core](https://openid.net/specs/openid-connect-core-1_0.html#StandardClaims).
+        ///
+        /// The value must consist of a leading `+` followed by up to 15 digits (e.g.,
+        /// `+14255551212`), optionally followed by an
+        /// [RFC 3966](https://tools.ietf.org/html/rfc3966) extension (e.g.,
+        /// `+16045551234;ext=5678`). Use [`EndUserPhoneNumber::new`] to accept any value.
+        ///
+        pub fn new_e164(s: String) -> Result<Self, PhoneNumberParseError> {
+            let (number, extension) = match s.split_once(";ext=") {
+                Some((number, extension)) => (number, Some(extension)),
+                None => (s.as_str(), None),
+            };
+
+            let digits = number
+                .strip_prefix('+')
+                .ok_or(PhoneNumberParseError::MissingPlus)?;
+            if let Some(c) = digits.chars().find(|c| !c.is_ascii_digit()) {
+                return Err(PhoneNumberParseError::InvalidCharacter(c));
+            }
+            if digits.is_empty() || digits.len() > 15 {
+                return Err(PhoneNumberParseError::InvalidLength(digits.len()));
+            }
+            if let Some(extension) = extension {
+                if extension.is_empty() || !extension.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(PhoneNumberParseError::InvalidExtension(extension.to_string()));
+                }
+            }
+
+            Ok(Self::new(s))
+        }
+    }
 ];
 
+///
+/// Error parsing an [`EndUserPhoneNumber`] in E.164 format.
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PhoneNumberParseError {
+    /// The phone number does not begin with `+`.
+    #[error("Phone number must begin with `+`")]
+    MissingPlus,
+    /// The phone number contains a character other than a digit.
+    #[error("Unexpected character `{0}` in phone number")]
+    InvalidCharacter(char),
+    /// The phone number contains too few or too many digits.
+    #[error("Phone number must contain between 1 and 15 digits (found {0})")]
+    InvalidLength(usize),
+    /// The phone number extension is empty or contains a character other than a digit.
+    #[error("Invalid phone number extension `{0}`")]
+    InvalidExtension(String),
+}
+
 new_type![
     ///
     /// URL of end user's profile picture.
@@ -669,8 +1019,47 @@ new_type![
     ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     EndUserTimezone(String)
+    impl {
+        ///
+        /// Initialize a new time zone, validating that it is a zone name (e.g.,
+        /// `Europe/Paris` or `Etc/GMT+1`) from the IANA time zone database.
+        ///
+        /// This requires the `chrono-tz` feature. Use [`EndUserTimezone::new`] to accept any
+        /// value.
+        ///
+        #[cfg(feature = "chrono-tz")]
+        pub fn new_iana(s: String) -> Result<Self, TimezoneParseError> {
+            let timezone = Self::new(s);
+            timezone.parse()?;
+            Ok(timezone)
+        }
+
+        ///
+        /// Parses the time zone using the IANA time zone database.
+        ///
+        /// This requires the `chrono-tz` feature.
+        ///
+        #[cfg(feature = "chrono-tz")]
+        pub fn parse(&self) -> Result<chrono_tz::Tz, TimezoneParseError> {
+            self.as_str()
+                .parse()
+                .map_err(|_| TimezoneParseError::UnknownTimezone(self.to_string()))
+        }
+    }
 ];
 
+///
+/// Error parsing an [`EndUserTimezone`].
+///
+#[cfg(feature = "chrono-tz")]
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TimezoneParseError {
+    /// The time zone is not in the IANA time zone database.
+    #[error("Unknown time zone `{0}`")]
+    UnknownTimezone(String),
+}
+
 new_type![
     ///
     /// URL of end user's website.
@@ -726,6 +1115,40 @@ new_url_type![
                 Url::parse(&(self.1.clone() + "/" + suffix))
             }
         }
+
+        ///
+        /// Returns the URL of the provider's OpenID Connect Discovery document.
+        ///
+        /// As described in [Section 4 of OpenID Connect Discovery 1.0](
+        /// https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderConfigurationRequest),
+        /// `/.well-known/openid-configuration` is appended to the issuer URL, including any path
+        /// component (e.g., `https://example.com/tenant` becomes
+        /// `https://example.com/tenant/.well-known/openid-configuration`).
+        ///
+        pub fn discovery_url(&self) -> Result<Url, url::ParseError> {
+            self.join(CONFIG_URL_SUFFIX)
+        }
+
+        ///
+        /// Returns the URL of a WebFinger request for discovering the issuer of the given
+        /// `resource` (e.g., `acct:joe@example.com`) on this issuer's host.
+        ///
+        /// Unlike the discovery document, the WebFinger endpoint is always located at
+        /// `/.well-known/webfinger` on the host, regardless of any path component of the issuer
+        /// URL (see [RFC 7033](https://tools.ietf.org/html/rfc7033#section-4)). The `resource`
+        /// is percent-encoded as a query parameter. To normalize user input before querying
+        /// WebFinger, use [`IssuerDiscovery`](crate::IssuerDiscovery) instead.
+        ///
+        pub fn webfinger_url(&self, resource: &str) -> Url {
+            let mut url = self.url().clone();
+            url.set_path(WEBFINGER_URL_PATH);
+            url.set_query(None);
+            url.set_fragment(None);
+            url.query_pairs_mut()
+                .append_pair("resource", resource)
+                .append_pair("rel", ISSUER_REL);
+            url
+        }
     }
 ];
 
@@ -740,24 +1163,58 @@ new_type![
 ///
 /// JSON Web Key Set.
 ///
+/// Keys are indexed by their key ID (`kid`) when the set is constructed, so that selecting the
+/// key(s) for a JWT that specifies a `kid` does not require scanning the entire set.
+///
 #[serde_as]
 #[derive(Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(
+    from = "JsonWebKeySetKeys<K>",
+    bound(deserialize = "K: JsonWebKey<JS, JT, JU>")
+)]
 pub struct JsonWebKeySet<JS, JT, JU, K>
 where
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
     JU: JsonWebKeyUse,
     K: JsonWebKey<JS, JT, JU>,
+{
+    #[serde(bound = "K: JsonWebKey<JS, JT, JU>")]
+    #[serde_as(as = "VecSkipError<_>")]
+    keys: Vec<K>,
+    // Indices into `keys` of the keys having each key ID, in the order they appear in the set.
+    #[serde(skip)]
+    keys_by_id: HashMap<JsonWebKeyId, Vec<usize>>,
+    #[serde(skip)]
+    _phantom: PhantomData<(JS, JT, JU)>,
+}
+
+// Deserialization helper for JsonWebKeySet, which builds the key ID index after the keys are
+// deserialized.
+#[serde_as]
+#[derive(Deserialize)]
+struct JsonWebKeySetKeys<K>
+where
+    K: DeserializeOwned,
 {
     // FIXME: write a test that ensures duplicate object member names cause an error
     // (see https://tools.ietf.org/html/rfc7517#section-5)
-    #[serde(bound = "K: JsonWebKey<JS, JT, JU>")]
+    #[serde(bound = "K: DeserializeOwned")]
     // Ignores invalid keys rather than failing. That way, clients can function using the keys that
     // they do understand, which is fine if they only ever get JWTs signed with those keys.
     #[serde_as(as = "VecSkipError<_>")]
     keys: Vec<K>,
-    #[serde(skip)]
-    _phantom: PhantomData<(JS, JT, JU)>,
+}
+impl<JS, JT, JU, K> From<JsonWebKeySetKeys<K>> for JsonWebKeySet<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn from(keys: JsonWebKeySetKeys<K>) -> Self {
+        Self::new(keys.keys)
+    }
 }
 
 ///
@@ -766,7 +1223,7 @@ where
 pub(crate) fn check_key_compatibility<JS, JT, JU, K>(
     key: &K,
     signing_algorithm: &JS,
-) -> Result<(), &'static str>
+) -> Result<(), SignatureVerificationError>
 where
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
@@ -776,22 +1233,37 @@ where
     // if this key isn't suitable for signing
     if let Some(use_) = key.key_use() {
         if !use_.allows_signature() {
-            return Err("key usage not permitted for digital signatures");
+            return Err(SignatureVerificationError::InvalidKey(
+                "key usage not permitted for digital signatures".to_string(),
+            ));
         }
     }
 
+    // if this key's operations don't include signature verification
+    if !key.allows_signature_verification() {
+        return Err(SignatureVerificationError::InvalidKey(
+            "key operations not permitted for signature verification".to_string(),
+        ));
+    }
+
     // if this key doesn't have the right key type
     if signing_algorithm.key_type().as_ref() != Some(key.key_type()) {
-        return Err("key type does not match signature algorithm");
+        return Err(SignatureVerificationError::KeyTypeMismatch(
+            "key type does not match signature algorithm".to_string(),
+        ));
     }
 
     #[cfg(feature = "jwk-alg")]
     match key.signing_alg() {
         // if no specific algorithm is mandated, any will do
         JsonWebKeyAlgorithm::Unspecified => Ok(()),
-        JsonWebKeyAlgorithm::Unsupported => Err("key algorithm is not a signing algorithm"),
+        JsonWebKeyAlgorithm::Unsupported => Err(SignatureVerificationError::InvalidKey(
+            "key algorithm is not a signing algorithm".to_string(),
+        )),
         JsonWebKeyAlgorithm::Algorithm(key_alg) if key_alg == signing_algorithm => Ok(()),
-        JsonWebKeyAlgorithm::Algorithm(_) => Err("incompatible key algorithm"),
+        JsonWebKeyAlgorithm::Algorithm(_) => Err(SignatureVerificationError::InvalidKey(
+            "incompatible key algorithm".to_string(),
+        )),
     }
 
     #[cfg(not(feature = "jwk-alg"))]
@@ -809,28 +1281,96 @@ where
     /// Create a new JSON Web Key Set.
     ///
     pub fn new(keys: Vec<K>) -> Self {
+        let mut keys_by_id: HashMap<JsonWebKeyId, Vec<usize>> = HashMap::new();
+        for (index, key) in keys.iter().enumerate() {
+            if let Some(key_id) = key.key_id() {
+                keys_by_id.entry(key_id.clone()).or_default().push(index);
+            }
+        }
         Self {
             keys,
+            keys_by_id,
             _phantom: PhantomData,
         }
     }
 
+    ///
+    /// Return the first key in this set with the specified key ID (`kid`), if any.
+    ///
+    /// Key IDs are not required to be unique within a JSON Web Key Set (e.g., a provider may
+    /// publish the same key for different algorithms under one `kid`), in which case the key
+    /// appearing first in the set is returned.
+    ///
+    pub fn key_by_id(&self, key_id: &JsonWebKeyId) -> Option<&K> {
+        self.keys_with_id(key_id).next()
+    }
+
+    // Returns the keys with the specified key ID, in the order they appear in the set.
+    fn keys_with_id<'a>(&'a self, key_id: &JsonWebKeyId) -> impl Iterator<Item = &'a K> + 'a {
+        self.keys_by_id
+            .get(key_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(move |index| &self.keys[*index])
+    }
+
     ///
     /// Return a list of suitable keys, given a key id an signature algorithm
     ///
     pub(crate) fn filter_keys(&self, key_id: &Option<JsonWebKeyId>, signature_alg: &JS) -> Vec<&K> {
-        self.keys()
-        .iter()
-        .filter(|key|
-            // Either the JWT doesn't include a 'kid' (in which case any 'kid'
-            // is acceptable), or the 'kid' matches the key's ID.
-            if key_id.is_some() && key_id.as_ref() != key.key_id() {
-                false
-            } else {
-                check_key_compatibility(*key, signature_alg).is_ok()
-            }
-        )
-        .collect()
+        let is_suitable = |key: &&K| {
+            check_key_compatibility(*key, signature_alg).is_ok()
+                && key.supports_signing_alg(signature_alg)
+        };
+        match key_id {
+            // If the JWT includes a 'kid', only keys with a matching ID are acceptable.
+            Some(key_id) => self.keys_with_id(key_id).filter(is_suitable).collect(),
+            // Otherwise, any key that is suitable for the algorithm is acceptable.
+            None => self.keys().iter().filter(is_suitable).collect(),
+        }
+    }
+
+    ///
+    /// Returns a [`SignatureVerificationError::KeyTypeMismatch`] if `key_id` identifies one or
+    /// more keys, none of which has the key type required by `signature_alg`.
+    ///
+    pub(crate) fn key_type_mismatch(
+        &self,
+        key_id: &Option<JsonWebKeyId>,
+        signature_alg: &JS,
+    ) -> Option<SignatureVerificationError> {
+        let key_id = key_id.as_ref()?;
+        let expected_key_type = signature_alg.key_type();
+        let identified_keys = self.keys_with_id(key_id).collect::<Vec<_>>();
+        if identified_keys.is_empty()
+            || identified_keys
+                .iter()
+                .any(|key| expected_key_type.as_ref() == Some(key.key_type()))
+        {
+            return None;
+        }
+
+        Some(SignatureVerificationError::KeyTypeMismatch(format!(
+            "algorithm `{}` cannot be used with key `{}` of type {}",
+            serde_plain::to_string(signature_alg).unwrap_or_else(|err| panic!(
+                "signature alg {:?} failed to serialize to a string: {}",
+                signature_alg, err,
+            )),
+            **key_id,
+            identified_keys
+                .iter()
+                .map(|key| format!(
+                    "`{}`",
+                    serde_plain::to_string(key.key_type()).unwrap_or_else(|err| panic!(
+                        "key type {:?} failed to serialize to a string: {}",
+                        key.key_type(),
+                        err,
+                    ))
+                ))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )))
     }
 
     ///
@@ -842,10 +1382,11 @@ where
         http_client: HC,
     ) -> Result<Self, DiscoveryError<RE>>
     where
-        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        HC: SyncHttpClient<Error = RE>,
         RE: std::error::Error + 'static,
     {
-        http_client(Self::fetch_request(url))
+        http_client
+            .call(Self::fetch_request(url))
             .map_err(DiscoveryError::Request)
             .and_then(Self::fetch_response)
     }
@@ -860,16 +1401,17 @@ where
     ) -> Result<Self, DiscoveryError<RE>>
     where
         F: Future<Output = Result<HttpResponse, RE>>,
-        HC: FnOnce(HttpRequest) -> F,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
         RE: std::error::Error + 'static,
     {
-        http_client(Self::fetch_request(url))
+        http_client
+            .call(Self::fetch_request(url))
             .await
             .map_err(DiscoveryError::Request)
             .and_then(Self::fetch_response)
     }
 
-    fn fetch_request(url: &JsonWebKeySetUrl) -> HttpRequest {
+    pub(crate) fn fetch_request(url: &JsonWebKeySetUrl) -> HttpRequest {
         HttpRequest {
             url: url.url().clone(),
             method: Method::GET,
@@ -880,7 +1422,9 @@ where
         }
     }
 
-    fn fetch_response<RE>(http_response: HttpResponse) -> Result<Self, DiscoveryError<RE>>
+    pub(crate) fn fetch_response<RE>(
+        http_response: HttpResponse,
+    ) -> Result<Self, DiscoveryError<RE>>
     where
         RE: std::error::Error + 'static,
     {
@@ -947,6 +1491,23 @@ new_url_type![
     JsonWebKeySetUrl
 ];
 
+new_type![
+    ///
+    /// Unique identifier for a JSON Web Token (`jti` claim).
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    JsonWebTokenId(String)
+    impl {
+        ///
+        /// Generate a new random, base64-encoded 128-bit JWT ID.
+        ///
+        pub fn new_random() -> Self {
+            let random_bytes: Vec<u8> = (0..16).map(|_| thread_rng().gen::<u8>()).collect();
+            JsonWebTokenId::new(base64::encode_config(random_bytes, base64::URL_SAFE_NO_PAD))
+        }
+    }
+];
+
 new_type![
     ///
     /// Language tag adhering to RFC 5646 (e.g., `fr` or `fr-CA`).
@@ -954,6 +1515,42 @@ new_type![
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     LanguageTag(String)
 ];
+impl LanguageTag {
+    ///
+    /// Returns the canonical casing of this language tag, as recommended by
+    /// [Section 2.1.1 of RFC 5646](https://tools.ietf.org/html/rfc5646#section-2.1.1).
+    ///
+    /// Language tags are case-insensitive. The canonical form uses lowercase for the language
+    /// subtag, titlecase for four-letter script subtags, and uppercase for two-letter region
+    /// subtags (e.g., `zh-hant-tw` becomes `zh-Hant-TW`). Extension and private use subtags (those
+    /// following a single-character subtag such as `x`) are lowercased.
+    ///
+    /// Unless the `preserve-language-tag-case` feature is enabled, the language tags of
+    /// [`LocalizedClaim`] entries are canonicalized during deserialization.
+    ///
+    pub fn canonicalize(&self) -> Self {
+        let mut after_singleton = false;
+        let subtags = self
+            .split('-')
+            .enumerate()
+            .map(|(i, subtag)| {
+                if subtag.len() == 1 {
+                    after_singleton = true;
+                }
+                if i == 0 || after_singleton {
+                    subtag.to_ascii_lowercase()
+                } else if subtag.len() == 2 {
+                    subtag.to_ascii_uppercase()
+                } else if subtag.len() == 4 {
+                    subtag[..1].to_ascii_uppercase() + &subtag[1..].to_ascii_lowercase()
+                } else {
+                    subtag.to_ascii_lowercase()
+                }
+            })
+            .collect::<Vec<_>>();
+        LanguageTag::new(subtags.join("-"))
+    }
+}
 impl AsRef<str> for LanguageTag {
     fn as_ref(&self) -> &str {
         self
@@ -970,6 +1567,17 @@ new_secret_type![
     LoginHint(String)
 ];
 
+new_secret_type![
+    ///
+    /// Token containing information identifying the end-user for whom Client-Initiated
+    /// Backchannel Authentication (CIBA) is being requested.
+    ///
+    /// The format of this token is specific to the OpenID Connect Provider.
+    ///
+    #[derive(Clone, Deserialize, Serialize)]
+    LoginHintToken(String)
+];
+
 new_secret_type![
     ///
     /// Hint about the logout identifier the End-User might use to log out.
@@ -1012,15 +1620,73 @@ new_secret_type![
             let random_bytes: Vec<u8> = (0..num_bytes).map(|_| thread_rng().gen::<u8>()).collect();
             Nonce::new(base64::encode_config(random_bytes, base64::URL_SAFE_NO_PAD))
         }
+        ///
+        /// Generate a new self-verifying nonce that expires after `ttl`.
+        ///
+        /// The nonce consists of 128 random bits and an expiration timestamp, protected by an
+        /// HMAC-SHA256 computed using `secret`. The nonce returned in the ID token can then be
+        /// validated using a [`SignedNonceVerifier`](crate::SignedNonceVerifier) configured with
+        /// the same secret, without storing the nonce server-side. Note that this scheme does not
+        /// prevent a nonce from being replayed until it expires, so `ttl` should be kept short.
+        ///
+        pub fn new_signed(secret: &[u8], ttl: std::time::Duration) -> Self {
+            // Very long TTLs are clamped to the latest representable time rather than overflowing.
+            let expiration = chrono::Utc::now()
+                .timestamp()
+                .saturating_add(i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX))
+                .min(chrono::DateTime::<chrono::Utc>::MAX_UTC.timestamp());
+            let random_bytes: Vec<u8> = (0..16).map(|_| thread_rng().gen::<u8>()).collect();
+            let payload = format!(
+                "{}.{}",
+                base64::encode_config(random_bytes, base64::URL_SAFE_NO_PAD),
+                expiration
+            );
+            let mac = hmac::Mac::finalize(signed_nonce_mac(secret, &payload)).into_bytes();
+            Nonce::new(format!(
+                "{}.{}",
+                payload,
+                base64::encode_config(mac, base64::URL_SAFE_NO_PAD)
+            ))
+        }
+        ///
+        /// Validates the HMAC of a nonce created by [`Nonce::new_signed`] and returns its
+        /// expiration time.
+        ///
+        pub(crate) fn signed_expiration(
+            &self,
+            secret: &[u8],
+        ) -> Result<chrono::DateTime<chrono::Utc>, String> {
+            use chrono::TimeZone;
+            use hmac::Mac;
+
+            let (payload, mac) = self
+                .secret()
+                .rsplit_once('.')
+                .ok_or_else(|| "malformed signed nonce".to_string())?;
+            let mac = base64::decode_config(mac, base64::URL_SAFE_NO_PAD)
+                .map_err(|_| "malformed signed nonce".to_string())?;
+            // Mac::verify_slice performs a constant time comparison.
+            signed_nonce_mac(secret, payload)
+                .verify_slice(&mac)
+                .map_err(|_| "invalid nonce signature".to_string())?;
+            payload
+                .rsplit_once('.')
+                .and_then(|(_, expiration)| expiration.parse::<i64>().ok())
+                .and_then(|expiration| chrono::Utc.timestamp_opt(expiration, 0).single())
+                .ok_or_else(|| "malformed signed nonce".to_string())
+        }
     }
 ];
+fn signed_nonce_mac(secret: &[u8], payload: &str) -> hmac::Hmac<Sha256> {
+    use hmac::Mac;
+    let mut mac =
+        hmac::Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
 impl PartialEq for Nonce {
     fn eq(&self, other: &Self) -> bool {
-        use subtle::ConstantTimeEq;
-        self.secret()
-            .as_bytes()
-            .ct_eq(other.secret().as_bytes())
-            .into()
+        constant_time_eq(self.secret(), other.secret())
     }
 }
 
@@ -1054,6 +1720,40 @@ new_url_type![
     PostLogoutRedirectUrl
 ];
 
+new_type![
+    ///
+    /// Reference to a pushed authorization request returned by the pushed authorization request
+    /// endpoint ([RFC 9126](https://tools.ietf.org/html/rfc9126)).
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    PushedAuthorizationRequestUri(String)
+];
+
+new_secret_type![
+    ///
+    /// Identifier of a Client-Initiated Backchannel Authentication (CIBA) request, returned by the
+    /// backchannel authentication endpoint and used to poll the token endpoint.
+    ///
+    #[derive(Clone, Deserialize, Serialize)]
+    AuthReqId(String)
+];
+
+new_url_type![
+    ///
+    /// URL of the Client-Initiated Backchannel Authentication (CIBA) backchannel authentication
+    /// endpoint.
+    ///
+    BackchannelAuthenticationUrl
+];
+
+new_url_type![
+    ///
+    /// URL of the pushed authorization request endpoint
+    /// ([RFC 9126](https://tools.ietf.org/html/rfc9126)).
+    ///
+    PushedAuthorizationRequestUrl
+];
+
 new_secret_type![
     ///
     /// Access token used by a client application to access the Client Registration endpoint.
@@ -1062,6 +1762,15 @@ new_secret_type![
     RegistrationAccessToken(String)
 ];
 
+new_type![
+    ///
+    /// Signed JWT asserting client metadata values about the client software, as described in
+    /// [Section 2.3 of RFC 7591](https://datatracker.ietf.org/doc/html/rfc7591#section-2.3).
+    ///
+    #[derive(Deserialize, Eq, Hash, Serialize)]
+    SoftwareStatement(String)
+];
+
 new_url_type![
     ///
     /// URL of the Client Registration endpoint.
@@ -1127,6 +1836,15 @@ impl Display for Timestamp {
         }
     }
 }
+impl Timestamp {
+    pub(crate) fn seconds(&self) -> Option<Seconds> {
+        match self {
+            Timestamp::Seconds(seconds) => Some(Seconds::new(seconds.clone())),
+            #[cfg(feature = "accept-rfc3339-timestamps")]
+            Timestamp::Rfc3339(_) => None,
+        }
+    }
+}
 
 ///
 /// Newtype around a bool, optionally supporting string values.
@@ -1147,6 +1865,15 @@ impl Display for Boolean {
     }
 }
 
+new_type![
+    ///
+    /// Timestamp expressed as (possibly fractional) seconds since the Unix epoch, exactly as
+    /// received from the OpenID Connect Provider.
+    ///
+    #[derive(Deserialize, Eq, Serialize)]
+    Seconds(serde_json::Number)
+];
+
 new_url_type![
     ///
     /// URL for retrieving redirect URIs that should receive identical pairwise subject identifiers.
@@ -1161,6 +1888,18 @@ new_url_type![
     ServiceDocUrl
 ];
 
+new_type![
+    ///
+    /// Identifier for a session between a User Agent and an OpenID Connect Provider (`sid`), as
+    /// described in [OpenID Connect Front-Channel Logout](
+    /// https://openid.net/specs/openid-connect-frontchannel-1_0.html#ClaimsContents) and
+    /// [OpenID Connect Back-Channel Logout](
+    /// https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken).
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    SessionIdentifier(String)
+];
+
 new_type![
     ///
     /// A user's street address.
@@ -1181,6 +1920,50 @@ new_type![
     ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     SubjectIdentifier(String)
+    impl {
+        ///
+        /// Computes a [pairwise subject identifier](
+        /// https://openid.net/specs/openid-connect-core-1_0.html#PairwiseAlg) for the given
+        /// sector identifier, local account ID, and provider-specific salt.
+        ///
+        /// The identifier is the base64url-encoded (without padding) SHA-256 hash of the
+        /// concatenation of the three values. The `sector_identifier` is typically obtained via
+        /// [`SubjectIdentifier::sector_identifier`].
+        ///
+        pub fn pairwise(sector_identifier: &str, local_account_id: &str, salt: &[u8]) -> Self {
+            let mut hasher = Sha256::new();
+            hasher.update(sector_identifier.as_bytes());
+            hasher.update(local_account_id.as_bytes());
+            hasher.update(salt);
+            Self::new(base64::encode_config(hasher.finalize(), base64::URL_SAFE_NO_PAD))
+        }
+
+        ///
+        /// Returns the sector identifier used for computing pairwise subject identifiers for a
+        /// client.
+        ///
+        /// This is the host component of the client's `sector_identifier_uri`, if registered.
+        /// Otherwise, it's the host component of the client's registered redirect URIs, which
+        /// must all share the same host. Returns `None` if no sector identifier can be
+        /// determined.
+        ///
+        pub fn sector_identifier(
+            sector_identifier_uri: Option<&SectorIdentifierUrl>,
+            redirect_uris: &[RedirectUrl],
+        ) -> Option<String> {
+            if let Some(sector_identifier_uri) = sector_identifier_uri {
+                return sector_identifier_uri.url().host_str().map(str::to_string);
+            }
+
+            let mut hosts = redirect_uris.iter().map(|redirect_uri| redirect_uri.url().host_str());
+            let host = hosts.next()??;
+            if hosts.all(|other_host| other_host == Some(host)) {
+                Some(host.to_string())
+            } else {
+                None
+            }
+        }
+    }
 ];
 
 new_url_type![
@@ -1216,6 +1999,31 @@ pub(crate) mod helpers {
         }
     }
 
+    ///
+    /// Wrapper for values that are expected to be JSON objects, but which some non-conformant
+    /// providers encode as strings containing the serialized JSON object.
+    ///
+    pub(crate) struct ObjectOrString<T>(pub T);
+    impl<'de, T> Deserialize<'de> for ObjectOrString<T>
+    where
+        T: DeserializeOwned,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            use serde::de::Error;
+
+            let value: Value = Deserialize::deserialize(deserializer)?;
+            match value {
+                Value::String(s) => serde_json::from_str(&s),
+                other => from_value(other),
+            }
+            .map(ObjectOrString)
+            .map_err(Error::custom)
+        }
+    }
+
     pub fn deserialize_string_or_vec_opt<'de, T, D>(
         deserializer: D,
     ) -> Result<Option<Vec<T>>, D::Error>
@@ -1284,6 +2092,11 @@ pub(crate) mod helpers {
             .filter(|language_tag| !language_tag.is_empty())
             .map(|language_tag| LanguageTag::new(language_tag.to_string()));
 
+        // Providers don't always use consistent casing, so canonicalize the tag to make lookups
+        // reliable.
+        #[cfg(not(feature = "preserve-language-tag-case"))]
+        let language_tag = language_tag.map(|language_tag| language_tag.canonicalize());
+
         (field_name, language_tag)
     }
 
@@ -1447,7 +2260,324 @@ mod serde_base64url_byte_array {
 
 #[cfg(test)]
 mod tests {
-    use super::IssuerUrl;
+    use super::{
+        compute_access_token_hash, compute_code_hash, AccessToken, AccessTokenHash,
+        AuthorizationCode, AuthorizationCodeHash, BirthdayParseError, EndUserBirthday,
+        EndUserPhoneNumber, IssuerUrl, LanguageTag, LocalizedClaim, PhoneNumberParseError,
+        RedirectUrl, SectorIdentifierUrl, SigningError, SubjectIdentifier,
+    };
+    use crate::core::CoreJwsSigningAlgorithm;
+
+    #[test]
+    fn test_compute_hashes() {
+        let access_token = AccessToken::new("jHkWEdUXMU1BwAsC4vtUsZwnNgMP1DhD".to_string());
+        let code = AuthorizationCode::new(
+            "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvk".to_string(),
+        );
+
+        // RS256 uses the left-most 128 bits of the SHA-256 hash.
+        let alg = CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256;
+        assert_eq!(
+            compute_access_token_hash(&alg, &access_token).unwrap(),
+            "EYWHg2WZeWd7orBjZSvyRw"
+        );
+        // Example from Appendix A of the OpenID Connect Core spec.
+        assert_eq!(
+            compute_code_hash(&alg, &code).unwrap(),
+            "LDktKdoQak3Pk0cnXxCltA"
+        );
+
+        // ES512 uses the left-most 256 bits of the SHA-512 hash.
+        let alg = CoreJwsSigningAlgorithm::EcdsaP521Sha512;
+        assert_eq!(
+            compute_access_token_hash(&alg, &access_token).unwrap(),
+            "23g3DuPHJJ6JsZt__80sYpZh0-13o6RU_bhHE5G1CPI"
+        );
+        assert_eq!(
+            compute_code_hash(&alg, &code).unwrap(),
+            "E9z1C-c0Az4eTEzE0Nm3OQ3BS2BhMgxuP7x5JAQj1_4"
+        );
+        assert_eq!(
+            AccessTokenHash::from_token(&access_token, &alg)
+                .unwrap()
+                .as_str(),
+            compute_access_token_hash(&alg, &access_token).unwrap()
+        );
+        assert_eq!(
+            AuthorizationCodeHash::from_code(&code, &alg)
+                .unwrap()
+                .as_str(),
+            compute_code_hash(&alg, &code).unwrap()
+        );
+
+        assert!(matches!(
+            compute_access_token_hash(&CoreJwsSigningAlgorithm::None, &access_token),
+            Err(SigningError::UnsupportedAlg(_))
+        ));
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    #[test]
+    fn test_timezone_iana() {
+        use super::{EndUserTimezone, TimezoneParseError};
+
+        let timezone = EndUserTimezone::new_iana("Europe/Paris".to_string()).unwrap();
+        assert_eq!(timezone.parse(), Ok(chrono_tz::Europe::Paris));
+
+        // POSIX-style offsets have inverted signs: `Etc/GMT+1` is one hour behind UTC.
+        let timezone = EndUserTimezone::new_iana("Etc/GMT+1".to_string()).unwrap();
+        assert_eq!(timezone.parse(), Ok(chrono_tz::Etc::GMTPlus1));
+
+        assert_eq!(
+            EndUserTimezone::new_iana("Europe/Atlantis".to_string()),
+            Err(TimezoneParseError::UnknownTimezone(
+                "Europe/Atlantis".to_string()
+            ))
+        );
+
+        // The lenient constructor accepts any value, which may then fail to parse.
+        assert_eq!(
+            EndUserTimezone::new("GMT+01:00".to_string()).parse(),
+            Err(TimezoneParseError::UnknownTimezone("GMT+01:00".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_birthday() {
+        let birthday = EndUserBirthday::new_iso8601("1990-02-28".to_string()).unwrap();
+        assert_eq!(birthday.parse(), Ok((Some(1990), 2, 28)));
+
+        // Year omitted.
+        let birthday = EndUserBirthday::new_iso8601("0000-02-29".to_string()).unwrap();
+        assert_eq!(birthday.parse(), Ok((None, 2, 29)));
+
+        // Year only.
+        let birthday = EndUserBirthday::new_iso8601("1990".to_string()).unwrap();
+        assert_eq!(birthday.parse(), Err(BirthdayParseError::YearOnly));
+
+        assert_eq!(
+            EndUserBirthday::new_iso8601("2020-13-40".to_string()),
+            Err(BirthdayParseError::InvalidDate("2020-13-40".to_string()))
+        );
+        assert_eq!(
+            EndUserBirthday::new_iso8601("2021-02-29".to_string()),
+            Err(BirthdayParseError::InvalidDate("2021-02-29".to_string()))
+        );
+        for invalid in [
+            "",
+            "90-01-01",
+            "1990-1-1",
+            "1990-01",
+            "1990/01/01",
+            "1990-01-01T00",
+        ] {
+            assert_eq!(
+                EndUserBirthday::new_iso8601(invalid.to_string()),
+                Err(BirthdayParseError::InvalidFormat(invalid.to_string()))
+            );
+        }
+
+        // The lenient constructor accepts any value, which may then fail to parse.
+        assert_eq!(
+            EndUserBirthday::new("01/02/1990".to_string()).parse(),
+            Err(BirthdayParseError::InvalidFormat("01/02/1990".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_phone_number_e164() {
+        for valid in [
+            "+14255551212",
+            "+1",
+            "+123456789012345",
+            "+16045551234;ext=5678",
+        ] {
+            assert_eq!(
+                EndUserPhoneNumber::new_e164(valid.to_string())
+                    .unwrap()
+                    .as_str(),
+                valid
+            );
+        }
+
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("14255551212".to_string()),
+            Err(PhoneNumberParseError::MissingPlus)
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+1425555CALL".to_string()),
+            Err(PhoneNumberParseError::InvalidCharacter('C'))
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+1 (425) 555-1212".to_string()),
+            Err(PhoneNumberParseError::InvalidCharacter(' '))
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+".to_string()),
+            Err(PhoneNumberParseError::InvalidLength(0))
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+1234567890123456".to_string()),
+            Err(PhoneNumberParseError::InvalidLength(16))
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+16045551234;ext=".to_string()),
+            Err(PhoneNumberParseError::InvalidExtension("".to_string()))
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+16045551234;ext=12a".to_string()),
+            Err(PhoneNumberParseError::InvalidExtension("12a".to_string()))
+        );
+
+        // The lenient constructor accepts any value.
+        assert_eq!(
+            EndUserPhoneNumber::new("+1 (425) 555-1212".to_string()).as_str(),
+            "+1 (425) 555-1212"
+        );
+    }
+
+    #[test]
+    fn test_pairwise_subject_identifier() {
+        // base64url(SHA-256("client.example.org" || "248289761001" || "provider-salt"))
+        assert_eq!(
+            SubjectIdentifier::pairwise("client.example.org", "248289761001", b"provider-salt")
+                .as_str(),
+            "UOSHYpf_ARShFNt3zZQ6W6sAQd9QplhenMAnNe1gTaM"
+        );
+        // Different sectors receive different subjects for the same account.
+        assert_ne!(
+            SubjectIdentifier::pairwise("other.example.org", "248289761001", b"provider-salt"),
+            SubjectIdentifier::pairwise("client.example.org", "248289761001", b"provider-salt"),
+        );
+
+        let redirect_uris = vec![
+            RedirectUrl::new("https://client.example.org/callback".to_string()).unwrap(),
+            RedirectUrl::new("https://client.example.org/callback2".to_string()).unwrap(),
+        ];
+        assert_eq!(
+            SubjectIdentifier::sector_identifier(None, &redirect_uris).as_deref(),
+            Some("client.example.org")
+        );
+
+        let redirect_uris = vec![
+            RedirectUrl::new("https://client.example.org/callback".to_string()).unwrap(),
+            RedirectUrl::new("https://other.example.net/callback".to_string()).unwrap(),
+        ];
+        assert_eq!(
+            SubjectIdentifier::sector_identifier(None, &redirect_uris),
+            None
+        );
+        // The sector identifier URI takes precedence over the redirect URIs.
+        assert_eq!(
+            SubjectIdentifier::sector_identifier(
+                Some(
+                    &SectorIdentifierUrl::new(
+                        "https://sector.example.org/file_of_redirect_uris.json".to_string()
+                    )
+                    .unwrap()
+                ),
+                &redirect_uris
+            )
+            .as_deref(),
+            Some("sector.example.org")
+        );
+        assert_eq!(SubjectIdentifier::sector_identifier(None, &[]), None);
+    }
+
+    #[test]
+    fn test_language_tag_canonicalize() {
+        let canonicalize = |tag: &str| LanguageTag::new(tag.to_string()).canonicalize().to_string();
+        assert_eq!(canonicalize("EN-us"), "en-US");
+        assert_eq!(canonicalize("en"), "en");
+        assert_eq!(canonicalize("ZH-hANT-tw"), "zh-Hant-TW");
+        assert_eq!(canonicalize("sr-latn-RS"), "sr-Latn-RS");
+        assert_eq!(canonicalize("es-419"), "es-419");
+        assert_eq!(canonicalize("DE-ch-1996"), "de-CH-1996");
+        assert_eq!(canonicalize("en-US-X-Priv-AB"), "en-US-x-priv-ab");
+        assert_eq!(canonicalize("X-Whatever"), "x-whatever");
+    }
+
+    #[test]
+    fn test_localized_claim_get() {
+        let claim: LocalizedClaim<String> = vec![
+            (None, "default".to_string()),
+            (
+                Some(LanguageTag::new("en".to_string())),
+                "english".to_string(),
+            ),
+            (
+                Some(LanguageTag::new("de-CH".to_string())),
+                "swiss".to_string(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(claim.get(None), Some(&"default".to_string()));
+        assert_eq!(
+            claim.get(Some(&LanguageTag::new("en".to_string()))),
+            Some(&"english".to_string())
+        );
+        // No widening from more specific to less specific tags.
+        assert_eq!(
+            claim.get(Some(&LanguageTag::new("en-US".to_string()))),
+            None
+        );
+        assert_eq!(claim.get(Some(&LanguageTag::new("de".to_string()))), None);
+
+        let mut languages = claim
+            .languages()
+            .map(|locale| locale.map(|l| l.as_str().to_string()))
+            .collect::<Vec<_>>();
+        languages.sort();
+        assert_eq!(
+            languages,
+            vec![None, Some("de-CH".to_string()), Some("en".to_string())]
+        );
+        assert_eq!(LocalizedClaim::<String>::new().languages().count(), 0);
+    }
+
+    #[test]
+    fn test_localized_claim_get_best() {
+        fn tags(tags: &[&str]) -> Vec<LanguageTag> {
+            tags.iter()
+                .map(|tag| LanguageTag::new(tag.to_string()))
+                .collect()
+        }
+
+        let claim: LocalizedClaim<&str> = vec![
+            (None, "default"),
+            (Some(LanguageTag::new("de".to_string())), "german"),
+            (Some(LanguageTag::new("de-CH".to_string())), "swiss"),
+            (Some(LanguageTag::new("zh-Hant".to_string())), "traditional"),
+        ]
+        .into_iter()
+        .collect();
+
+        // Region fallback.
+        assert_eq!(claim.get_best(&tags(&["de-CH-1996"])), Some(&"swiss"));
+        assert_eq!(claim.get_best(&tags(&["de-AT"])), Some(&"german"));
+        // Script subtags.
+        assert_eq!(claim.get_best(&tags(&["zh-Hant-TW"])), Some(&"traditional"));
+        assert_eq!(claim.get_best(&tags(&["zh-Hans-CN"])), Some(&"default"));
+        // Extension singletons are removed along with the following subtag.
+        assert_eq!(claim.get_best(&tags(&["de-CH-x-phonebk"])), Some(&"swiss"));
+        // Earlier preferences take priority over later ones, even if less specific.
+        assert_eq!(
+            claim.get_best(&tags(&["fr", "de-DE", "de-CH"])),
+            Some(&"german")
+        );
+        // Empty preference list returns the default.
+        assert_eq!(claim.get_best(&[]), Some(&"default"));
+
+        // Without a default, an arbitrary entry is returned.
+        let claim: LocalizedClaim<&str> =
+            vec![(Some(LanguageTag::new("fr".to_string())), "french")]
+                .into_iter()
+                .collect();
+        assert_eq!(claim.get_best(&tags(&["en"])), Some(&"french"));
+        assert_eq!(LocalizedClaim::<&str>::new().get_best(&tags(&["en"])), None);
+    }
 
     #[test]
     fn test_issuer_url_append() {
@@ -1485,6 +2615,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_issuer_well_known_urls() {
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        assert_eq!(
+            issuer.discovery_url().unwrap().as_str(),
+            "https://example.com/.well-known/openid-configuration"
+        );
+        assert_eq!(
+            issuer.webfinger_url("acct:joe@example.com").as_str(),
+            "https://example.com/.well-known/webfinger?resource=acct%3Ajoe%40example.com\
+             &rel=http%3A%2F%2Fopenid.net%2Fspecs%2Fconnect%2F1.0%2Fissuer"
+        );
+
+        // The discovery document is relative to the issuer's path, but WebFinger isn't.
+        let issuer = IssuerUrl::new("https://example.com:8443/tenants/one/".to_string()).unwrap();
+        assert_eq!(
+            issuer.discovery_url().unwrap().as_str(),
+            "https://example.com:8443/tenants/one/.well-known/openid-configuration"
+        );
+        assert_eq!(
+            issuer
+                .webfinger_url("https://example.com/joe?x=1&y=2#z")
+                .as_str(),
+            "https://example.com:8443/.well-known/webfinger\
+             ?resource=https%3A%2F%2Fexample.com%2Fjoe%3Fx%3D1%26y%3D2%23z\
+             &rel=http%3A%2F%2Fopenid.net%2Fspecs%2Fconnect%2F1.0%2Fissuer"
+        );
+        let url = issuer.webfinger_url("acct:jöe+tag@example.com");
+        assert_eq!(
+            url.query_pairs()
+                .find(|(name, _)| name == "resource")
+                .unwrap()
+                .1,
+            "acct:jöe+tag@example.com"
+        );
+    }
+
     #[test]
     fn test_url_serialize() {
         let issuer_url =
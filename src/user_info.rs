@@ -1,3 +1,6 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::future::Future;
 use std::ops::Deref;
 use std::str;
@@ -10,20 +13,25 @@ use oauth2::AccessToken;
 use thiserror::Error;
 use url::Url;
 
+use crate::claim_sources;
 use crate::helpers::FilteredFlatten;
-use crate::http_utils::{auth_bearer, content_type_has_essence, MIME_TYPE_JSON, MIME_TYPE_JWT};
-use crate::jwt::{JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
+use crate::http_utils::{
+    auth_bearer, auth_dpop, content_type_has_essence, DPOP, MIME_TYPE_JSON, MIME_TYPE_JWT,
+};
+use crate::jwt::{is_compact_jwe, JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
 use crate::types::helpers::deserialize_string_or_vec_opt;
 use crate::types::LocalizedClaim;
 use crate::verification::UserInfoVerifier;
 use crate::{
-    AdditionalClaims, AddressClaim, Audience, AudiencesClaim, ClaimsVerificationError,
-    EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName,
-    EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl,
-    EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, GenderClaim, HttpRequest, HttpResponse,
-    IssuerClaim, IssuerUrl, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken,
-    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, LanguageTag, PrivateSigningKey,
-    StandardClaims, SubjectIdentifier,
+    AdditionalClaims, AddressClaim, AsyncHttpClient, Audience, AudiencesClaim, ClaimSource,
+    ClaimSourceError, ClaimSourceVerifier, ClaimsVerificationError, DPoPKey, EndUserBirthday,
+    EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName,
+    EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone,
+    EndUserUsername, EndUserWebsiteUrl, GenderClaim, HttpRequest, HttpResponse, IssuerClaim,
+    IssuerUrl, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken,
+    JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag,
+    PrivateDecryptionKey, PrivateSigningKey, Seconds, StandardClaims, SubjectIdentifier,
+    SyncHttpClient,
 };
 
 ///
@@ -37,10 +45,11 @@ where
     JU: JsonWebKeyUse,
     K: JsonWebKey<JS, JT, JU>,
 {
-    pub(super) url: &'a UserInfoUrl,
+    pub(super) url: Cow<'a, UserInfoUrl>,
     pub(super) access_token: AccessToken,
     pub(super) require_signed_response: bool,
     pub(super) signed_response_verifier: UserInfoVerifier<'static, JE, JS, JT, JU, K>,
+    pub(super) dpop_key: Option<DPoPKey>,
 }
 impl<'a, JE, JS, JT, JU, K> UserInfoRequest<'a, JE, JS, JT, JU, K>
 where
@@ -61,40 +70,62 @@ where
     where
         AC: AdditionalClaims,
         GC: GenderClaim,
-        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        HC: SyncHttpClient<Error = RE>,
         RE: std::error::Error + 'static,
     {
-        http_client(self.prepare_request())
-            .map_err(UserInfoError::Request)
-            .and_then(|http_response| self.user_info_response(http_response))
+        let mut http_response = http_client
+            .call(self.prepare_request()?)
+            .map_err(UserInfoError::Request)?;
+        if self.update_dpop_nonce(&http_response) {
+            http_response = http_client
+                .call(self.prepare_request()?)
+                .map_err(UserInfoError::Request)?;
+            self.update_dpop_nonce(&http_response);
+        }
+
+        self.user_info_response(http_response)
     }
 
     ///
     /// Submits this request to the associated user info endpoint using the specified asynchronous
     /// HTTP client.
     ///
-    pub async fn request_async<AC, C, F, GC, RE>(
+    pub async fn request_async<AC, GC, C, F, RE>(
         self,
         http_client: C,
     ) -> Result<UserInfoClaims<AC, GC>, UserInfoError<RE>>
     where
         AC: AdditionalClaims,
-        C: FnOnce(HttpRequest) -> F,
-        F: Future<Output = Result<HttpResponse, RE>>,
         GC: GenderClaim,
+        C: AsyncHttpClient<Error = RE, Future = F>,
+        F: Future<Output = Result<HttpResponse, RE>>,
         RE: std::error::Error + 'static,
     {
-        let http_request = self.prepare_request();
-        let http_response = http_client(http_request)
+        let mut http_response = http_client
+            .call(self.prepare_request()?)
             .await
             .map_err(UserInfoError::Request)?;
+        if self.update_dpop_nonce(&http_response) {
+            http_response = http_client
+                .call(self.prepare_request()?)
+                .await
+                .map_err(UserInfoError::Request)?;
+            self.update_dpop_nonce(&http_response);
+        }
 
         self.user_info_response(http_response)
     }
 
-    fn prepare_request(&self) -> HttpRequest {
-        let (auth_header, auth_value) = auth_bearer(&self.access_token);
-        HttpRequest {
+    fn prepare_request<RE>(&self) -> Result<HttpRequest, UserInfoError<RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        let (auth_header, auth_value) = if self.dpop_key.is_some() {
+            auth_dpop(&self.access_token)
+        } else {
+            auth_bearer(&self.access_token)
+        };
+        let http_request = HttpRequest {
             url: self.url.url().clone(),
             method: Method::GET,
             headers: vec![
@@ -104,9 +135,25 @@ where
             .into_iter()
             .collect(),
             body: Vec::new(),
+        };
+        if let Some(ref dpop_key) = self.dpop_key {
+            dpop_key
+                .add_proof(http_request)
+                .map_err(|err| UserInfoError::Other(format!("Failed to sign DPoP proof: {}", err)))
+        } else {
+            Ok(http_request)
         }
     }
 
+    // Remembers any DPoP nonce provided by the server for subsequent requests, and returns
+    // whether the request should be retried because the server rejected the proof with a
+    // `use_dpop_nonce` error.
+    fn update_dpop_nonce(&self, http_response: &HttpResponse) -> bool {
+        self.dpop_key.as_ref().map_or(false, |dpop_key| {
+            dpop_key.update_nonce(self.url.url(), http_response)
+        })
+    }
+
     fn user_info_response<AC, GC, RE>(
         self,
         http_response: HttpResponse,
@@ -142,9 +189,16 @@ where
                 )
             }
             ref content_type if content_type_has_essence(content_type, MIME_TYPE_JWT) => {
-                let jwt_str = String::from_utf8(http_response.body).map_err(|_| {
+                let mut jwt_str = String::from_utf8(http_response.body).map_err(|_| {
                     UserInfoError::Other("response body has invalid UTF-8 encoding".to_string())
                 })?;
+                // Encrypted responses contain a nested signed JWT.
+                if is_compact_jwe(&jwt_str) {
+                    jwt_str = self
+                        .signed_response_verifier
+                        .decrypt(&jwt_str)
+                        .map_err(UserInfoError::ClaimsVerification)?;
+                }
                 serde_path_to_error::deserialize::<_, UserInfoJsonWebToken<AC, GC, JE, JS, JT>>(
                     serde_json::Value::String(jwt_str),
                 )
@@ -160,6 +214,43 @@ where
         }
     }
 
+    ///
+    /// Overrides the user info endpoint URL (e.g., to reach the endpoint through an internal
+    /// gateway rather than at the URL advertised in the provider metadata).
+    ///
+    /// The response is verified exactly as it would be for the original endpoint: the expected
+    /// issuer, audience, signature, and subject checks are unaffected.
+    ///
+    pub fn set_user_info_url(mut self, url: Cow<'a, UserInfoUrl>) -> Self {
+        self.url = url;
+        self
+    }
+
+    ///
+    /// Presents the access token using the `DPoP` authorization scheme
+    /// ([RFC 9449](https://tools.ietf.org/html/rfc9449)) if `token_type` (i.e., the `token_type`
+    /// of the token response that issued the access token) is `DPoP`.
+    ///
+    /// DPoP-bound access tokens are sent in an `Authorization: DPoP <access token>` header along
+    /// with a `DPoP` header containing a proof generated by `dpop_key`, which must be the key used
+    /// when requesting the token. Otherwise, the access token is presented as a bearer token. If
+    /// the server responds with a `DPoP-Nonce` header, the nonce is included in subsequent proofs
+    /// generated by `dpop_key`. If the server rejects the proof with a `use_dpop_nonce` error
+    /// (see [Section 9 of RFC 9449](https://tools.ietf.org/html/rfc9449#section-9)), the request
+    /// is retried once using the new nonce, as with [`DPoPKey::http_client`].
+    ///
+    pub fn set_dpop_key<TT>(mut self, token_type: &TT, dpop_key: &DPoPKey) -> Self
+    where
+        TT: AsRef<str>,
+    {
+        self.dpop_key = if token_type.as_ref().eq_ignore_ascii_case(DPOP) {
+            Some(dpop_key.clone())
+        } else {
+            None
+        };
+        self
+    }
+
     ///
     /// Specifies whether to require the user info response to be a signed JSON Web Token (JWT).
     ///
@@ -168,6 +259,24 @@ where
         self
     }
 
+    ///
+    /// Specifies the key used to decrypt user info responses encrypted using JSON Web Encryption
+    /// (JWE).
+    ///
+    /// Without a decryption key, encrypted responses are rejected with
+    /// [`ClaimsVerificationError::Unsupported`].
+    ///
+    pub fn set_decryption_key<JK, D>(mut self, decryption_key: D) -> Self
+    where
+        JK: JweKeyManagementAlgorithm,
+        D: PrivateDecryptionKey<JE, JK, JT> + 'static + Send + Sync,
+    {
+        self.signed_response_verifier = self
+            .signed_response_verifier
+            .set_decryption_key(decryption_key);
+        self
+    }
+
     ///
     /// Specifies whether to require the issuer of the signed JWT response to match the expected
     /// issuer URL for this provider.
@@ -212,6 +321,8 @@ where
         Self(UserInfoClaimsImpl {
             issuer: None,
             audiences: None,
+            claim_names: None,
+            claim_sources: None,
             standard_claims,
             additional_claims: additional_claims.into(),
         })
@@ -258,9 +369,103 @@ where
         pub self [self.0] ["claim"] {
             set_issuer -> issuer[Option<IssuerUrl>],
             set_audiences -> audiences[Option<Vec<Audience>>] ["aud"],
+            set_claim_names -> claim_names[Option<HashMap<String, String>>] ["_claim_names"],
+            set_claim_sources -> claim_sources[Option<HashMap<String, ClaimSource>>] ["_claim_sources"],
         }
     ];
 
+    ///
+    /// Verifies the [aggregated claims](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#AggregatedExample) embedded in these
+    /// claims and returns a copy of these claims with the aggregated claims merged in.
+    ///
+    /// Each aggregated claim source's JWT must be signed by one of the claims providers trusted by
+    /// `verifier`, which may differ from the issuer of these claims. Only the claims that
+    /// `_claim_names` attributes to each source are merged, and the resolved entries are removed
+    /// from `_claim_names` and `_claim_sources`. Distributed claim sources are left untouched (see
+    /// [`resolve_distributed_claims`](Self::resolve_distributed_claims)).
+    ///
+    pub fn resolve_aggregated_claims<JE, JS, JT, JU, K>(
+        &self,
+        verifier: &ClaimSourceVerifier<JE, JS, JT, JU, K>,
+    ) -> Result<Self, ClaimSourceError<Infallible>>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        claim_sources::resolve_aggregated_claims(&self.0, self.0.claim_sources.as_ref(), verifier)
+            .map(Self)
+    }
+
+    ///
+    /// Fetches the [distributed claims](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#DistributedExample) referenced by
+    /// these claims and returns a copy of these claims with the distributed claims merged in.
+    ///
+    /// Each distributed claim source's endpoint is requested (using the synchronous
+    /// `http_client`) with the source's access token, if any, and the returned JWT is verified by
+    /// `verifier`. Only the claims that `_claim_names` attributes to each source are merged, and
+    /// the resolved entries are removed from `_claim_names` and `_claim_sources`. Aggregated claim
+    /// sources are left untouched (see
+    /// [`resolve_aggregated_claims`](Self::resolve_aggregated_claims)).
+    ///
+    pub fn resolve_distributed_claims<JE, JS, JT, JU, K, HC, RE>(
+        &self,
+        verifier: &ClaimSourceVerifier<JE, JS, JT, JU, K>,
+        http_client: HC,
+    ) -> Result<Self, ClaimSourceError<RE>>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        claim_sources::resolve_distributed_claims(
+            &self.0,
+            self.0.claim_sources.as_ref(),
+            verifier,
+            http_client,
+        )
+        .map(Self)
+    }
+
+    ///
+    /// Asynchronously fetches the distributed claims referenced by these claims and returns a copy
+    /// of these claims with the distributed claims merged in.
+    ///
+    /// See [`resolve_distributed_claims`](Self::resolve_distributed_claims) for details.
+    ///
+    pub async fn resolve_distributed_claims_async<JE, JS, JT, JU, K, F, HC, RE>(
+        &self,
+        verifier: &ClaimSourceVerifier<'_, JE, JS, JT, JU, K>,
+        http_client: HC,
+    ) -> Result<Self, ClaimSourceError<RE>>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
+        RE: std::error::Error + 'static,
+    {
+        claim_sources::resolve_distributed_claims_async(
+            &self.0,
+            self.0.claim_sources.as_ref(),
+            verifier,
+            http_client,
+        )
+        .await
+        .map(Self)
+    }
+
     ///
     /// Returns the `sub` claim.
     ///
@@ -297,10 +502,34 @@ where
             set_phone_number -> phone_number[Option<EndUserPhoneNumber>],
             set_phone_number_verified -> phone_number_verified[Option<bool>],
             set_address -> address[Option<AddressClaim>],
-            set_updated_at -> updated_at[Option<DateTime<Utc>>],
         }
     ];
 
+    ///
+    /// Returns the `updated_at` claim.
+    ///
+    pub fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.0.standard_claims.updated_at()
+    }
+
+    ///
+    /// Returns the `updated_at` claim as the number of seconds since the Unix epoch, exactly as
+    /// received from the OpenID Connect Provider.
+    ///
+    /// See [`StandardClaims::updated_at_seconds`] for details.
+    ///
+    pub fn updated_at_seconds(&self) -> Option<Seconds> {
+        self.0.standard_claims.updated_at_seconds()
+    }
+
+    ///
+    /// Sets the `updated_at` claim.
+    ///
+    pub fn set_updated_at(mut self, updated_at: Option<DateTime<Utc>>) -> Self {
+        self.0.standard_claims = self.0.standard_claims.set_updated_at(updated_at);
+        self
+    }
+
     ///
     /// Returns the standard claims as a `StandardClaims` object.
     ///
@@ -338,6 +567,18 @@ where
         skip_serializing_if = "Option::is_none"
     )]
     pub audiences: Option<Vec<Audience>>,
+    #[serde(
+        default,
+        rename = "_claim_names",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub claim_names: Option<HashMap<String, String>>,
+    #[serde(
+        default,
+        rename = "_claim_sources",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub claim_sources: Option<HashMap<String, ClaimSource>>,
 
     #[serde(bound = "GC: GenderClaim", flatten)]
     pub standard_claims: StandardClaims<GC>,
@@ -354,7 +595,7 @@ where
         self.audiences.as_ref()
     }
 }
-impl<'a, AC, GC> AudiencesClaim for &'a UserInfoClaimsImpl<AC, GC>
+impl<AC, GC> AudiencesClaim for &UserInfoClaimsImpl<AC, GC>
 where
     AC: AdditionalClaims,
     GC: GenderClaim,
@@ -373,7 +614,7 @@ where
         self.issuer.as_ref()
     }
 }
-impl<'a, AC, GC> IssuerClaim for &'a UserInfoClaimsImpl<AC, GC>
+impl<AC, GC> IssuerClaim for &UserInfoClaimsImpl<AC, GC>
 where
     AC: AdditionalClaims,
     GC: GenderClaim,
@@ -483,11 +724,320 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::core::CoreGenderClaim;
-    use crate::{AdditionalClaims, UserInfoClaims};
+    use crate::core::{
+        CoreGenderClaim, CoreJsonWebKey, CoreJsonWebKeySet, CoreJwsSigningAlgorithm,
+        CoreRsaPrivateDecryptionKey, CoreRsaPrivateSigningKey, CoreUserInfoClaims,
+        CoreUserInfoJsonWebToken, CoreUserInfoVerifier,
+    };
+    use crate::discovery::tests::block_on;
+    use crate::dpop::tests::{decode_proof, new_dpop_key};
+    use crate::jwt::tests::{encrypt_compact_jwe, TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
+    use crate::{
+        AdditionalClaims, Audience, ClaimsVerificationError, HttpRequest, HttpResponse, IssuerUrl,
+        StandardClaims, SubjectIdentifier, UserInfoClaims, UserInfoError, UserInfoRequest,
+        UserInfoUrl,
+    };
+
+    use http::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+    use http::status::StatusCode;
+    use oauth2::basic::BasicTokenType;
+    use oauth2::{AccessToken, ClientId};
+    use sha2::{Digest, Sha256};
 
+    use std::borrow::Cow;
+    use std::cell::RefCell;
     use std::collections::HashMap;
 
+    type TestUserInfoRequest<'a> = UserInfoRequest<
+        'a,
+        crate::core::CoreJweContentEncryptionAlgorithm,
+        CoreJwsSigningAlgorithm,
+        crate::core::CoreJsonWebKeyType,
+        crate::core::CoreJsonWebKeyUse,
+        CoreJsonWebKey,
+    >;
+
+    fn new_user_info_request(
+        url: &UserInfoUrl,
+        expected_subject: Option<SubjectIdentifier>,
+    ) -> TestUserInfoRequest<'_> {
+        UserInfoRequest {
+            url: Cow::Borrowed(url),
+            access_token: AccessToken::new("access_token".to_string()),
+            require_signed_response: false,
+            signed_response_verifier: CoreUserInfoVerifier::new(
+                ClientId::new("my_client".to_string()),
+                IssuerUrl::new("https://example.com".to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![serde_json::from_str(TEST_RSA_PUB_KEY).unwrap()]),
+                expected_subject,
+            ),
+            dpop_key: None,
+        }
+    }
+
+    fn signed_user_info(subject: &str) -> String {
+        let claims = CoreUserInfoClaims::new(
+            StandardClaims::new(SubjectIdentifier::new(subject.to_string())),
+            Default::default(),
+        )
+        .set_issuer(Some(
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+        ))
+        .set_audiences(Some(vec![Audience::new("my_client".to_string())]));
+        let jwt = CoreUserInfoJsonWebToken::new(
+            claims,
+            &CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap(),
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+        serde_json::to_value(jwt)
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn mock_response(
+        content_type: &'static str,
+        body: String,
+    ) -> impl Fn(HttpRequest) -> Result<HttpResponse, crate::reqwest::AsyncHttpClientError> {
+        move |_| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+            Ok(HttpResponse {
+                status_code: StatusCode::OK,
+                headers,
+                body: body.clone().into_bytes(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_user_info_response_json() {
+        let url = UserInfoUrl::new("https://example.com/userinfo".to_string()).unwrap();
+        let claims: CoreUserInfoClaims =
+            new_user_info_request(&url, Some(SubjectIdentifier::new("subject".to_string())))
+                .request(mock_response(
+                    "application/json",
+                    "{\"sub\": \"subject\"}".to_string(),
+                ))
+                .unwrap();
+        assert_eq!(claims.subject().as_str(), "subject");
+
+        // Unsigned responses are rejected when a signed response is required.
+        match new_user_info_request(&url, None)
+            .require_signed_response(true)
+            .request::<crate::EmptyAdditionalClaims, CoreGenderClaim, _, _>(mock_response(
+                "application/json",
+                "{\"sub\": \"subject\"}".to_string(),
+            )) {
+            Err(UserInfoError::ClaimsVerification(ClaimsVerificationError::NoSignature)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_user_info_url_override() {
+        let url = UserInfoUrl::new("https://example.com/userinfo".to_string()).unwrap();
+        let internal_url =
+            UserInfoUrl::new("http://userinfo.internal:8080/userinfo".to_string()).unwrap();
+        let request = |subject: &str| {
+            let subject = subject.to_string();
+            new_user_info_request(&url, Some(SubjectIdentifier::new("subject".to_string())))
+                .set_user_info_url(Cow::Borrowed(&internal_url))
+                .require_signed_response(true)
+                .request::<crate::EmptyAdditionalClaims, CoreGenderClaim, _, _>(
+                    |http_request: HttpRequest| {
+                        assert_eq!(http_request.url, *internal_url.url());
+                        mock_response("application/jwt", signed_user_info(&subject))(http_request)
+                    },
+                )
+        };
+
+        // The signature, issuer, and audience are still verified.
+        let claims = request("subject").unwrap();
+        assert_eq!(claims.subject().as_str(), "subject");
+        assert_eq!(claims.issuer().unwrap().as_str(), "https://example.com");
+
+        match request("other_subject") {
+            Err(UserInfoError::ClaimsVerification(ClaimsVerificationError::InvalidSubject(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_user_info_dpop() {
+        let url =
+            UserInfoUrl::new("https://example.com/userinfo?schema=openid".to_string()).unwrap();
+        let dpop_key = new_dpop_key();
+        let request_with_headers =
+            |request: TestUserInfoRequest<'_>| -> (HeaderMap, CoreUserInfoClaims) {
+                let headers = RefCell::new(None);
+                let claims = request
+                    .request(|http_request: HttpRequest| {
+                        *headers.borrow_mut() = Some(http_request.headers.clone());
+                        mock_response("application/json", "{\"sub\": \"subject\"}".to_string())(
+                            http_request,
+                        )
+                    })
+                    .unwrap();
+                (headers.into_inner().unwrap(), claims)
+            };
+
+        // Bearer tokens are sent without a DPoP proof, even if a DPoP key is provided.
+        let (headers, claims) = request_with_headers(
+            new_user_info_request(&url, None).set_dpop_key(&BasicTokenType::Bearer, &dpop_key),
+        );
+        assert_eq!(claims.subject().as_str(), "subject");
+        assert_eq!(headers[AUTHORIZATION], "Bearer access_token");
+        assert!(!headers.contains_key("dpop"));
+
+        let (headers, claims) = request_with_headers(
+            new_user_info_request(&url, None)
+                .set_dpop_key(&BasicTokenType::Extension("DPoP".to_string()), &dpop_key),
+        );
+        assert_eq!(claims.subject().as_str(), "subject");
+        assert_eq!(headers[AUTHORIZATION], "DPoP access_token");
+        let (header, claims) = decode_proof(headers["dpop"].to_str().unwrap());
+        assert_eq!(header["typ"], "dpop+jwt");
+        assert_eq!(claims["htm"], "GET");
+        assert_eq!(claims["htu"], "https://example.com/userinfo");
+        assert_eq!(
+            claims["ath"],
+            base64::encode_config(Sha256::digest(b"access_token"), base64::URL_SAFE_NO_PAD)
+        );
+        assert!(claims.get("nonce").is_none());
+    }
+
+    #[test]
+    fn test_user_info_dpop_nonce_retry() {
+        let url = UserInfoUrl::new("https://example.com/userinfo".to_string()).unwrap();
+        let dpop_key = new_dpop_key();
+        let use_dpop_nonce = |nonce: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                WWW_AUTHENTICATE,
+                HeaderValue::from_static("DPoP error=\"use_dpop_nonce\""),
+            );
+            headers.insert("DPoP-Nonce", HeaderValue::from_static(nonce));
+            HttpResponse {
+                status_code: StatusCode::UNAUTHORIZED,
+                headers,
+                body: Vec::new(),
+            }
+        };
+        let new_request = || {
+            new_user_info_request(&url, None)
+                .set_dpop_key(&BasicTokenType::Extension("DPoP".to_string()), &dpop_key)
+        };
+
+        let nonces = RefCell::new(Vec::new());
+        let claims: CoreUserInfoClaims = new_request()
+            .request(|http_request: HttpRequest| {
+                let (_, claims) = decode_proof(http_request.headers["dpop"].to_str().unwrap());
+                nonces.borrow_mut().push(claims.get("nonce").cloned());
+                if nonces.borrow().len() == 1 {
+                    Ok(use_dpop_nonce("nonce-1"))
+                } else {
+                    mock_response("application/json", "{\"sub\": \"subject\"}".to_string())(
+                        http_request,
+                    )
+                }
+            })
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "subject");
+        assert_eq!(
+            nonces.into_inner(),
+            vec![None, Some(serde_json::Value::String("nonce-1".to_string()))]
+        );
+
+        // The request is retried only once, even if the server keeps demanding new nonces.
+        let attempts = RefCell::new(0);
+        let nonces = ["nonce-2", "nonce-3"];
+        match block_on(
+            new_request().request_async::<crate::EmptyAdditionalClaims, CoreGenderClaim, _, _, _>(
+                |_: HttpRequest| {
+                    let nonce = nonces[*attempts.borrow()];
+                    *attempts.borrow_mut() += 1;
+                    std::future::ready(Ok::<_, std::io::Error>(use_dpop_nonce(nonce)))
+                },
+            ),
+        ) {
+            Err(UserInfoError::Response(status_code, _, _)) => {
+                assert_eq!(status_code, StatusCode::UNAUTHORIZED)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(attempts.into_inner(), 2);
+    }
+
+    #[test]
+    fn test_user_info_response_signed() {
+        let url = UserInfoUrl::new("https://example.com/userinfo".to_string()).unwrap();
+        let claims: CoreUserInfoClaims =
+            new_user_info_request(&url, Some(SubjectIdentifier::new("subject".to_string())))
+                .require_signed_response(true)
+                .request(mock_response(
+                    "application/jwt; charset=UTF-8",
+                    signed_user_info("subject"),
+                ))
+                .unwrap();
+        assert_eq!(claims.subject().as_str(), "subject");
+        assert_eq!(claims.issuer().unwrap().as_str(), "https://example.com");
+
+        // The subject must match the ID token's subject.
+        match new_user_info_request(&url, Some(SubjectIdentifier::new("subject".to_string())))
+            .request::<crate::EmptyAdditionalClaims, CoreGenderClaim, _, _>(mock_response(
+            "application/jwt",
+            signed_user_info("other_subject"),
+        )) {
+            Err(UserInfoError::ClaimsVerification(ClaimsVerificationError::InvalidSubject(
+                msg,
+            ))) => assert_eq!(msg, "expected `subject` (found `other_subject`)"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Tampered signatures are rejected.
+        let mut tampered = signed_user_info("subject");
+        tampered.push('A');
+        match new_user_info_request(&url, None)
+            .request::<crate::EmptyAdditionalClaims, CoreGenderClaim, _, _>(mock_response(
+                "application/jwt",
+                tampered,
+            )) {
+            Err(UserInfoError::ClaimsVerification(
+                ClaimsVerificationError::SignatureVerification(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_user_info_response_encrypted() {
+        let url = UserInfoUrl::new("https://example.com/userinfo".to_string()).unwrap();
+        let jwe = encrypt_compact_jwe(&signed_user_info("subject"), "RSA-OAEP-256", "A256GCM");
+
+        // Without a decryption key, encrypted responses are rejected.
+        match new_user_info_request(&url, None)
+            .request::<crate::EmptyAdditionalClaims, CoreGenderClaim, _, _>(mock_response(
+                "application/jwt",
+                jwe.clone(),
+            )) {
+            Err(UserInfoError::ClaimsVerification(ClaimsVerificationError::Unsupported(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let claims: CoreUserInfoClaims =
+            new_user_info_request(&url, Some(SubjectIdentifier::new("subject".to_string())))
+                .set_decryption_key(
+                    CoreRsaPrivateDecryptionKey::from_pem(TEST_RSA_PRIV_KEY).unwrap(),
+                )
+                .require_signed_response(true)
+                .request(mock_response("application/jwt", jwe))
+                .unwrap();
+        assert_eq!(claims.subject().as_str(), "subject");
+    }
+
     #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
     struct TestClaims {
         pub tfa_method: String,
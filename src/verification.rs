@@ -1,24 +1,36 @@
-use std::collections::HashSet;
+use std::any::{type_name, Any};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use oauth2::{ClientId, ClientSecret};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::{Map, Value};
 use thiserror::Error;
 
-use crate::jwt::{JsonWebToken, JsonWebTokenJsonPayloadSerde};
+use crate::authorization_response::JarmClaims;
+use crate::claim_sources::ClaimSourceJwtClaims;
+use crate::jwks_cache::{JwksCache, JwksSource};
+use crate::jwt::{decrypt_compact_jwe, JsonWebToken, JsonWebTokenJsonPayloadSerde};
+use crate::request_object::{RequestObjectClaims, REQUEST_OBJECT_JWT_TYPE};
 use crate::user_info::UserInfoClaimsImpl;
 use crate::{
-    AdditionalClaims, Audience, AuthenticationContextClass, GenderClaim, IdTokenClaims, IssuerUrl,
-    JsonWebKey, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse, JsonWebTokenAccess,
-    JsonWebTokenAlgorithm, JsonWebTokenHeader, JweContentEncryptionAlgorithm, JwsSigningAlgorithm,
-    Nonce, SubjectIdentifier,
+    AdditionalClaims, Audience, AuthenticationContextClass, AuthenticationMethodReference,
+    DecryptionError, GenderClaim, IdToken, IdTokenClaims, IssuerUrl, JsonWebKey, JsonWebKeySet,
+    JsonWebKeyType, JsonWebKeyUse, JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenHeader,
+    JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm,
+    LogoutTokenClaims, Nonce, PrivateDecryptionKey, SubjectIdentifier,
 };
 
+// Clock skew tolerated by default when verifying token times, to accommodate small differences
+// between the client's and the provider's clocks.
+const DEFAULT_TIME_LEEWAY: Duration = Duration::from_secs(60);
+
 pub(crate) trait AudiencesClaim {
     fn audiences(&self) -> Option<&Vec<Audience>>;
 }
@@ -36,15 +48,31 @@ pub enum ClaimsVerificationError {
     /// Claims have expired.
     #[error("Expired: {0}")]
     Expired(String),
+    /// Access token hash (`at_hash`) claim does not match the access token.
+    #[error("Invalid access token hash: {0}")]
+    InvalidAccessTokenHash(String),
     /// Audience claim is invalid.
     #[error("Invalid audiences: {0}")]
     InvalidAudience(String),
+    /// Authorization code hash (`c_hash`) claim does not match the authorization code, or no
+    /// authorization code was supplied.
+    #[error("Invalid authorization code hash: {0}")]
+    InvalidCodeHash(String),
     /// Authorization context class reference (`acr`) claim is invalid.
     #[error("Invalid authorization context class reference: {0}")]
     InvalidAuthContext(String),
+    /// Authentication method references (`amr`) claim is invalid.
+    #[error("Invalid authentication method references: {0}")]
+    InvalidAuthMethods(String),
     /// User authenticated too long ago.
     #[error("Invalid authentication time: {0}")]
     InvalidAuthTime(String),
+    /// Custom claims validation (see [`IdTokenVerifier::set_claims_validator`]) failed.
+    #[error("Invalid claims: {0}")]
+    InvalidClaims(String),
+    /// Events claim (`events`) of a logout token is missing or invalid.
+    #[error("Invalid events: {0}")]
+    InvalidEvents(String),
     /// Issuer claim is invalid.
     #[error("Invalid issuer: {0}")]
     InvalidIssuer(String),
@@ -60,6 +88,9 @@ pub enum ClaimsVerificationError {
     /// An unexpected error occurred.
     #[error("{0}")]
     Other(String),
+    /// Failed to decrypt the JWE-encrypted claims.
+    #[error("Decryption failed")]
+    Decryption(#[source] DecryptionError),
     /// Failed to verify the claims signature.
     #[error("Signature verification failed")]
     SignatureVerification(#[source] SignatureVerificationError),
@@ -88,6 +119,12 @@ pub enum SignatureVerificationError {
     /// disallows signatures.
     #[error("Invalid cryptographic key: {0}")]
     InvalidKey(String),
+    /// The type of the selected key does not match the signature algorithm specified in the JOSE
+    /// header (e.g., an `HS256` header paired with an RSA key). Keys are never used with an
+    /// algorithm of a different type, which prevents algorithm confusion attacks such as using
+    /// an RSA public key as an HMAC secret.
+    #[error("Key type does not match signature algorithm: {0}")]
+    KeyTypeMismatch(String),
     /// The signing key needed for verifying the
     /// [JSON Web Token](https://tools.ietf.org/html/rfc7519)'s signature/MAC could not be found.
     /// This error can occur if the key ID (`kid`) specified in the JWT's
@@ -100,11 +137,17 @@ pub enum SignatureVerificationError {
     /// should consider refreshing the JWKS document (via
     /// [`JsonWebKeySet::fetch`][crate::JsonWebKeySet::fetch]).
     ///
-    /// This error can also occur if the identified
-    /// [JSON Web Key](https://tools.ietf.org/html/rfc7517) is of the wrong type (e.g., an RSA key
-    /// when the JOSE header specifies an ECDSA algorithm) or does not support signing.
+    /// This error can also occur if the JOSE header doesn't specify a key ID and no
+    /// [JSON Web Key](https://tools.ietf.org/html/rfc7517) is of the right type (e.g., only RSA
+    /// keys when the JOSE header specifies an ECDSA algorithm), or if the identified key does not
+    /// support signing. A key ID identifying only keys of the wrong type results in a
+    /// [`SignatureVerificationError::KeyTypeMismatch`] error instead.
     #[error("No matching key found")]
     NoMatchingKey,
+    /// The JOSE header specifies the `none` algorithm (i.e., the token is unsigned). Unsigned
+    /// tokens are always rejected, regardless of the verifier's configuration.
+    #[error("Unsigned tokens (`alg` of `none`) are not accepted")]
+    NoneAlgorithmRejected,
     /// Unsupported signature algorithm.
     #[error("Unsupported signature algorithm: {0}")]
     UnsupportedAlg(String),
@@ -113,6 +156,50 @@ pub enum SignatureVerificationError {
     Other(String),
 }
 
+///
+/// Mode for comparing the issuer (`iss`) claim to the expected issuer URL.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IssuerMatch {
+    ///
+    /// The issuer claim must exactly match the expected issuer URL, as required by
+    /// [Section 3.1.3.7 of OpenID Connect Core 1.0](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation).
+    ///
+    #[default]
+    Exact,
+    ///
+    /// The issuer claim must match the expected issuer URL after removing any trailing slashes
+    /// from both (e.g., `https://issuer.example/` matches `https://issuer.example`).
+    ///
+    IgnoreTrailingSlash,
+}
+impl IssuerMatch {
+    fn matches(self, expected: &IssuerUrl, actual: &IssuerUrl) -> bool {
+        match self {
+            IssuerMatch::Exact => expected == actual,
+            IssuerMatch::IgnoreTrailingSlash => {
+                expected.as_str().trim_end_matches('/') == actual.as_str().trim_end_matches('/')
+            }
+        }
+    }
+}
+
+// Keys used for verifying signatures. This enum is intentionally private.
+#[derive(Clone)]
+enum SignatureKeys<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    // Shared so that cloning a verifier doesn't copy the key set.
+    Static(Arc<JsonWebKeySet<JS, JT, JU, K>>),
+    Cached(Arc<dyn JwksSource<JS, JT, JU, K>>),
+}
+
 // This struct is intentionally private.
 #[derive(Clone)]
 struct JwtClaimsVerifier<'a, JS, JT, JU, K>
@@ -123,14 +210,20 @@ where
     K: JsonWebKey<JS, JT, JU>,
 {
     allowed_algs: Option<HashSet<JS>>,
+    allowed_audiences: Vec<Audience>,
     aud_match_required: bool,
     client_id: ClientId,
     client_secret: Option<ClientSecret>,
+    #[allow(clippy::type_complexity)]
+    decryption_fn: Option<Arc<dyn Fn(&str) -> Result<Vec<u8>, DecryptionError> + 'a + Send + Sync>>,
     iss_required: bool,
     issuer: IssuerUrl,
+    issuer_match: IssuerMatch,
     is_signature_check_enabled: bool,
+    jwt_types: &'static [&'static str],
     other_aud_verifier_fn: Arc<dyn Fn(&Audience) -> bool + 'a + Send + Sync>,
-    signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    signature_keys: SignatureKeys<JS, JT, JU, K>,
+    time_leeway: Duration,
 }
 impl<'a, JS, JT, JU, K> JwtClaimsVerifier<'a, JS, JT, JU, K>
 where
@@ -146,20 +239,35 @@ where
     ) -> Self {
         JwtClaimsVerifier {
             allowed_algs: Some([JS::rsa_sha_256()].iter().cloned().collect()),
+            allowed_audiences: Vec::new(),
             aud_match_required: true,
             client_id,
             client_secret: None,
+            decryption_fn: None,
             iss_required: true,
             issuer,
+            issuer_match: IssuerMatch::Exact,
             is_signature_check_enabled: true,
+            jwt_types: &["JWT"],
             // Secure default: reject all other audiences as untrusted, since any other audience
             // can potentially impersonate the user when by sending its copy of these claims
             // to this relying party.
             other_aud_verifier_fn: Arc::new(|_| false),
-            signature_keys,
+            signature_keys: SignatureKeys::Static(Arc::new(signature_keys)),
+            time_leeway: DEFAULT_TIME_LEEWAY,
         }
     }
 
+    pub fn set_jwks_source(mut self, jwks_source: Arc<dyn JwksSource<JS, JT, JU, K>>) -> Self {
+        self.signature_keys = SignatureKeys::Cached(jwks_source);
+        self
+    }
+
+    pub fn set_signature_keys(mut self, signature_keys: Arc<JsonWebKeySet<JS, JT, JU, K>>) -> Self {
+        self.signature_keys = SignatureKeys::Static(signature_keys);
+        self
+    }
+
     pub fn require_audience_match(mut self, aud_required: bool) -> Self {
         self.aud_match_required = aud_required;
         self
@@ -170,6 +278,11 @@ where
         self
     }
 
+    pub fn set_issuer_match(mut self, issuer_match: IssuerMatch) -> Self {
+        self.issuer_match = issuer_match;
+        self
+    }
+
     pub fn require_signature_check(mut self, sig_required: bool) -> Self {
         self.is_signature_check_enabled = sig_required;
         self
@@ -182,6 +295,14 @@ where
         self.allowed_algs = Some(algs.into_iter().collect());
         self
     }
+
+    // Sets the accepted values of the 'typ' JOSE header field (compared case-insensitively and
+    // ignoring any `application/` prefix). The field may always be omitted.
+    pub fn set_jwt_types(mut self, jwt_types: &'static [&'static str]) -> Self {
+        self.jwt_types = jwt_types;
+        self
+    }
+
     pub fn allow_any_alg(mut self) -> Self {
         self.allowed_algs = None;
         self
@@ -200,15 +321,121 @@ where
         self
     }
 
+    pub fn set_time_leeway(mut self, leeway: Duration) -> Self {
+        self.time_leeway = leeway;
+        self
+    }
+
+    // Returns the given time plus the clock skew leeway, or `None` if the result overflows (in
+    // which case the time should be treated as unbounded).
+    pub fn add_time_leeway(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        chrono::Duration::from_std(self.time_leeway)
+            .ok()
+            .and_then(|leeway| time.checked_add_signed(leeway))
+    }
+
+    // Verifies that a token (described by `token_type` in error messages) has not expired and was
+    // not issued in the future, tolerating the clock skew leeway in both directions.
+    pub fn verify_time_claims(
+        &self,
+        token_type: &str,
+        cur_time: DateTime<Utc>,
+        expiration: Option<DateTime<Utc>>,
+        issue_time: Option<DateTime<Utc>>,
+    ) -> Result<(), ClaimsVerificationError> {
+        if let Some(expiration) = expiration {
+            if self
+                .add_time_leeway(expiration)
+                .map(|expiration| cur_time >= expiration)
+                .unwrap_or(false)
+            {
+                return Err(ClaimsVerificationError::Expired(format!(
+                    "{} expired at {} (current time is {})",
+                    token_type, expiration, cur_time
+                )));
+            }
+        }
+        if let Some(issue_time) = issue_time {
+            if self
+                .add_time_leeway(cur_time)
+                .map(|cur_time| issue_time > cur_time)
+                .unwrap_or(false)
+            {
+                return Err(ClaimsVerificationError::Expired(format!(
+                    "{} issued in the future at {} (current time is {})",
+                    token_type, issue_time, cur_time
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Sets additional audiences that identify this client (alongside its client ID).
+    pub fn set_allowed_audiences(mut self, allowed_audiences: Vec<Audience>) -> Self {
+        self.allowed_audiences = allowed_audiences;
+        self
+    }
+
+    // Returns whether the given audience identifies this client.
+    pub fn is_own_audience(&self, aud: &str) -> bool {
+        aud == self.client_id.as_str()
+            || self
+                .allowed_audiences
+                .iter()
+                .any(|allowed_aud| allowed_aud.as_str() == aud)
+    }
+
+    fn own_audiences_description(&self) -> String {
+        std::iter::once(self.client_id.as_str())
+            .chain(self.allowed_audiences.iter().map(|aud| aud.as_str()))
+            .map(|aud| format!("`{}`", aud))
+            .collect::<Vec<_>>()
+            .join(" or ")
+    }
+
+    pub fn set_decryption_key<JE, JK, D>(mut self, decryption_key: D) -> Self
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JK: JweKeyManagementAlgorithm,
+        D: PrivateDecryptionKey<JE, JK, JT> + 'a + Send + Sync,
+    {
+        self.decryption_fn = Some(Arc::new(move |jwe| {
+            decrypt_compact_jwe::<JE, JK, JT, D>(jwe, &decryption_key)
+        }));
+        self
+    }
+
+    // Decrypts a JWE compact serialization and returns the nested JWT it contains.
+    pub fn decrypt(&self, jwe: &str) -> Result<String, ClaimsVerificationError> {
+        let decryption_fn = self.decryption_fn.as_ref().ok_or_else(|| {
+            ClaimsVerificationError::Unsupported(
+                "JWE-encrypted token received, but no decryption key is configured".to_string(),
+            )
+        })?;
+        let plaintext = decryption_fn(jwe).map_err(ClaimsVerificationError::Decryption)?;
+        String::from_utf8(plaintext).map_err(|_| {
+            ClaimsVerificationError::Other("decrypted JWE payload is not valid UTF-8".to_string())
+        })
+    }
+
     fn validate_jose_header<JE>(
         jose_header: &JsonWebTokenHeader<JE, JS, JT>,
+        jwt_types: &[&str],
     ) -> Result<(), ClaimsVerificationError>
     where
         JE: JweContentEncryptionAlgorithm<JT>,
     {
-        // The 'typ' header field must either be omitted or have the canonicalized value JWT.
+        // The 'typ' header field must either be omitted or have one of the expected values (JWT,
+        // unless otherwise specified). Per RFC 7515, the `application/` prefix may be omitted.
         if let Some(ref jwt_type) = jose_header.typ {
-            if jwt_type.to_uppercase() != "JWT" {
+            let jwt_type_lower = jwt_type.to_lowercase();
+            let media_subtype = jwt_type_lower
+                .strip_prefix("application/")
+                .unwrap_or(&jwt_type_lower);
+            if !jwt_types
+                .iter()
+                .any(|expected| expected.eq_ignore_ascii_case(media_subtype))
+            {
                 return Err(ClaimsVerificationError::Unsupported(format!(
                     "unexpected or unsupported JWT type `{}`",
                     **jwt_type
@@ -252,7 +479,7 @@ where
     {
         {
             let jose_header = jwt.unverified_header();
-            Self::validate_jose_header(jose_header)?;
+            Self::validate_jose_header(jose_header, self.jwt_types)?;
 
             // The code below roughly follows the validation steps described in
             // https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation
@@ -262,9 +489,11 @@ where
             //    encryption was negotiated with the OP at Registration time and the ID Token is not
             //    encrypted, the RP SHOULD reject it.
 
+            // Encrypted tokens (which use the five-part JWE compact serialization) are decrypted
+            // before reaching this point, so a JWS with an encryption algorithm is malformed.
             if let JsonWebTokenAlgorithm::Encryption(ref encryption_alg) = jose_header.alg {
                 return Err(ClaimsVerificationError::Unsupported(format!(
-                    "JWE encryption is not currently supported (found algorithm `{}`)",
+                    "JWE encryption algorithm used in JWS header (found algorithm `{}`)",
                     serde_plain::to_string(encryption_alg).unwrap_or_else(|err| panic!(
                         "encryption alg {:?} failed to serialize to a string: {}",
                         encryption_alg, err
@@ -273,14 +502,13 @@ where
             }
         }
 
-        // TODO: Add encryption (JWE) support
         {
             // 2. The Issuer Identifier for the OpenID Provider (which is typically obtained during
             //    Discovery) MUST exactly match the value of the iss (issuer) Claim.
             let unverified_claims = jwt.unverified_payload_ref();
             if self.iss_required {
                 if let Some(issuer) = unverified_claims.issuer() {
-                    if *issuer != self.issuer {
+                    if !self.issuer_match.matches(&self.issuer, issuer) {
                         return Err(ClaimsVerificationError::InvalidIssuer(format!(
                             "expected `{}` (found `{}`)",
                             *self.issuer, **issuer
@@ -300,13 +528,10 @@ where
             //    contains additional audiences not trusted by the Client.
             if self.aud_match_required {
                 if let Some(audiences) = unverified_claims.audiences() {
-                    if !audiences
-                        .iter()
-                        .any(|aud| (**aud).deref() == self.client_id.deref())
-                    {
+                    if !audiences.iter().any(|aud| self.is_own_audience(aud)) {
                         return Err(ClaimsVerificationError::InvalidAudience(format!(
-                            "must contain `{}` (found audiences: {})",
-                            *self.client_id,
+                            "must contain {} (found audiences: {})",
+                            self.own_audiences_description(),
                             audiences
                                 .iter()
                                 .map(|aud| format!("`{}`", Deref::deref(aud)))
@@ -316,7 +541,7 @@ where
                     } else if audiences.len() > 1 {
                         audiences
                             .iter()
-                            .filter(|aud| (**aud).deref() != self.client_id.deref())
+                            .filter(|aud| !self.is_own_audience(aud))
                             .find(|aud| !(self.other_aud_verifier_fn)(aud))
                             .map(|aud| {
                                 Err(ClaimsVerificationError::InvalidAudience(format!(
@@ -340,6 +565,22 @@ where
         //    the issuer in place of checking the token signature. The Client MUST validate the
         //    signature of all other ID Tokens according to JWS [JWS] using the algorithm specified
         //    in the JWT alg Header Parameter. The Client MUST use the keys provided by the Issuer.
+        //
+        // Section 2 of OpenID Connect Core 1.0 specifies that "ID Tokens MUST NOT use none as
+        // the alg value unless the Response Type used returns no ID Token from the
+        // Authorization Endpoint (such as when using the Authorization Code Flow) and the
+        // Client explicitly requested the use of none at Registration time."
+        //
+        // While there's technically a use case where this is ok, we choose not to support it to
+        // protect against accidental misuse. Unsigned tokens are rejected before consulting any
+        // other configuration (including disabled signature checks), since accepting them is a
+        // classic JWT vulnerability.
+        if matches!(jwt.unverified_header().alg, JsonWebTokenAlgorithm::None) {
+            return Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoneAlgorithmRejected,
+            ));
+        }
+
         if !self.is_signature_check_enabled {
             return Ok(jwt.unverified_payload());
         }
@@ -350,15 +591,8 @@ where
             // Encryption is handled above.
             JsonWebTokenAlgorithm::Encryption(_) => unreachable!(),
             JsonWebTokenAlgorithm::Signature(ref signature_alg, _) => signature_alg,
-            // Section 2 of OpenID Connect Core 1.0 specifies that "ID Tokens MUST NOT use
-            // none as the alg value unless the Response Type used returns no ID Token from
-            // the Authorization Endpoint (such as when using the Authorization Code Flow)
-            // and the Client explicitly requested the use of none at Registration time."
-            //
-            // While there's technically a use case where this is ok, we choose not to
-            // support it for now to protect against accidental misuse. If demand arises,
-            // we can figure out a API that mitigates the risk.
-            JsonWebTokenAlgorithm::None => return Err(ClaimsVerificationError::NoSignature),
+            // Handled above.
+            JsonWebTokenAlgorithm::None => unreachable!(),
         }
         .clone();
 
@@ -399,6 +633,9 @@ where
             //    as the key to validate the signature. For MAC based algorithms, the behavior
             //    is unspecified if the aud is multi-valued or if an azp value is present that
             //    is different than the aud value.
+            //
+            // The JWK set is never consulted for MAC based algorithms, so a key published by the
+            // provider (e.g., an RSA public key) can't be used as the HMAC secret.
             if let Some(ref client_secret) = self.client_secret {
                 let key = K::new_symmetric(client_secret.secret().clone().into_bytes());
                 return jwt
@@ -419,13 +656,44 @@ where
         // if the JWK set contains more than one public key.
 
         // See if any key has a matching key ID (if supplied) and compatible type.
-        let public_keys = {
-            let key_id = &jwt.unverified_header().kid;
-            self.signature_keys.filter_keys(key_id, &signature_alg)
+        let key_id = jwt.unverified_header().kid.clone();
+        let cached_keys;
+        let refreshed_keys;
+        let (signature_keys, public_keys) = match self.signature_keys {
+            SignatureKeys::Static(ref signature_keys) => (
+                &**signature_keys,
+                signature_keys.filter_keys(&key_id, &signature_alg),
+            ),
+            SignatureKeys::Cached(ref jwks_source) => {
+                cached_keys = jwks_source.current_keys().map_err(jwks_source_error)?;
+                let public_keys = cached_keys.filter_keys(&key_id, &signature_alg);
+                // The signing key may have been rotated since the cached keys were fetched.
+                if public_keys.is_empty() {
+                    match jwks_source
+                        .refresh_stale_keys(&cached_keys)
+                        .map_err(jwks_source_error)?
+                    {
+                        Some(keys) => {
+                            refreshed_keys = keys;
+                            (
+                                &*refreshed_keys,
+                                refreshed_keys.filter_keys(&key_id, &signature_alg),
+                            )
+                        }
+                        None => (&*cached_keys, public_keys),
+                    }
+                } else {
+                    (&*cached_keys, public_keys)
+                }
+            }
         };
         if public_keys.is_empty() {
+            // Report a key ID that identifies only keys of the wrong type (e.g., a forged `HS256`
+            // token referencing the provider's RSA key) distinctly from a missing key.
             return Err(ClaimsVerificationError::SignatureVerification(
-                SignatureVerificationError::NoMatchingKey,
+                signature_keys
+                    .key_type_mismatch(&key_id, &signature_alg)
+                    .unwrap_or(SignatureVerificationError::NoMatchingKey),
             ));
         } else if public_keys.len() != 1 {
             return Err(ClaimsVerificationError::SignatureVerification(
@@ -462,6 +730,10 @@ where
     }
 }
 
+fn jwks_source_error(message: String) -> ClaimsVerificationError {
+    ClaimsVerificationError::SignatureVerification(SignatureVerificationError::Other(message))
+}
+
 ///
 /// Trait for verifying ID token nonces.
 ///
@@ -488,6 +760,95 @@ impl NonceVerifier for &Nonce {
     }
 }
 
+///
+/// Explicit nonce verification policy.
+///
+/// Passing a `&Nonce` as the [`NonceVerifier`] is equivalent to [`NonceCheck::Required`]. Flows
+/// that don't send a nonce in the authentication request (e.g., the Authorization Code flow
+/// protected by a PKCE code verifier) may instead use [`NonceCheck::Absent`] or
+/// [`NonceCheck::IfPresent`].
+///
+/// **Security note:** a nonce should always be used with the Implicit and Hybrid flows, in which
+/// the ID token is returned from the authorization endpoint. For those flows, only
+/// [`NonceCheck::Required`] provides protection against ID token replay.
+///
+#[derive(Clone, Copy, Debug)]
+pub enum NonceCheck<'a> {
+    ///
+    /// Require a `nonce` claim equal to the given nonce.
+    ///
+    Required(&'a Nonce),
+    ///
+    /// Require the `nonce` claim to be absent, as expected when no nonce was sent in the
+    /// authentication request.
+    ///
+    Absent,
+    ///
+    /// Accept a token without a `nonce` claim, but require any `nonce` claim that is present to
+    /// equal the given nonce.
+    ///
+    IfPresent(&'a Nonce),
+}
+
+impl NonceVerifier for NonceCheck<'_> {
+    fn verify(self, nonce: Option<&Nonce>) -> Result<(), String> {
+        match (self, nonce) {
+            (NonceCheck::Required(expected), _) => expected.verify(nonce),
+            (NonceCheck::Absent, None) | (NonceCheck::IfPresent(_), None) => Ok(()),
+            (NonceCheck::Absent, Some(_)) => Err("unexpected nonce claim".to_string()),
+            (NonceCheck::IfPresent(expected), Some(_)) => expected.verify(nonce),
+        }
+    }
+}
+
+///
+/// Stateless verifier for nonces created by [`Nonce::new_signed`].
+///
+/// Rather than comparing the `nonce` claim against a stored value, this verifier re-derives the
+/// nonce's HMAC using the shared secret and checks that the nonce has not expired.
+///
+#[derive(Clone)]
+pub struct SignedNonceVerifier<'a> {
+    secret: &'a [u8],
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'a + Send + Sync>,
+}
+impl<'a> SignedNonceVerifier<'a> {
+    ///
+    /// Initializes a new verifier using the secret that was passed to [`Nonce::new_signed`].
+    ///
+    pub fn new(secret: &'a [u8]) -> Self {
+        SignedNonceVerifier {
+            secret,
+            time_fn: Arc::new(Utc::now),
+        }
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for verifying the nonce expiration time. By default, the current
+    /// system time is used (i.e., [`Utc::now`]).
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + 'a + Send + Sync,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+}
+impl NonceVerifier for &SignedNonceVerifier<'_> {
+    fn verify(self, nonce: Option<&Nonce>) -> Result<(), String> {
+        let expiration = nonce
+            .ok_or_else(|| "missing nonce claim".to_string())?
+            .signed_expiration(self.secret)?;
+        if (*self.time_fn)() >= expiration {
+            return Err(format!("nonce expired at {}", expiration));
+        }
+        Ok(())
+    }
+}
+
 impl<F> NonceVerifier for F
 where
     F: FnOnce(Option<&Nonce>) -> Result<(), String>,
@@ -500,6 +861,13 @@ where
 ///
 /// ID token verifier.
 ///
+/// Verification methods such as [`IdToken::claims`](crate::IdToken::claims) borrow the verifier,
+/// so a single configured verifier may be used to verify any number of ID tokens. The verifier is
+/// `Send + Sync` (for `Send + Sync` signing algorithm and key types, including the
+/// [`core`](crate::core) types), so it may be shared across threads (e.g., using an [`Arc`]).
+/// Cloning a verifier is also cheap, since the configured key set and callbacks are shared
+/// between clones rather than copied.
+///
 #[derive(Clone)]
 pub struct IdTokenVerifier<'a, JS, JT, JU, K>
 where
@@ -510,9 +878,23 @@ where
 {
     acr_verifier_fn:
         Arc<dyn Fn(Option<&AuthenticationContextClass>) -> Result<(), String> + 'a + Send + Sync>,
+    allowed_acrs: Option<Vec<AuthenticationContextClass>>,
+    #[allow(clippy::type_complexity)]
+    amr_verifier_fn: Arc<
+        dyn Fn(Option<&Vec<AuthenticationMethodReference>>) -> Result<(), String>
+            + 'a
+            + Send
+            + Sync,
+    >,
     #[allow(clippy::type_complexity)]
     auth_time_verifier_fn:
         Arc<dyn Fn(Option<DateTime<Utc>>) -> Result<(), String> + 'a + Send + Sync>,
+    auth_time_max_age: Option<Duration>,
+    auth_time_required: bool,
+    azp_match_required: bool,
+    #[allow(clippy::type_complexity)]
+    claims_validator_fn: Option<Arc<dyn Fn(&dyn Any) -> Result<(), String> + 'a + Send + Sync>>,
+    code_hash_requires_code: bool,
     iat_verifier_fn: Arc<dyn Fn(DateTime<Utc>) -> Result<(), String> + 'a + Send + Sync>,
     jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
     time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'a + Send + Sync>,
@@ -528,7 +910,20 @@ where
         IdTokenVerifier {
             // By default, accept authorization context reference (acr claim).
             acr_verifier_fn: Arc::new(|_| Ok(())),
+            allowed_acrs: None,
+            // By default, accept any authentication method references (amr claim).
+            amr_verifier_fn: Arc::new(|_| Ok(())),
             auth_time_verifier_fn: Arc::new(|_| Ok(())),
+            // By default, accept any authentication time (auth_time claim), including none.
+            auth_time_max_age: None,
+            auth_time_required: false,
+            // By default, don't verify the authorized party (azp claim).
+            azp_match_required: false,
+            // By default, don't run any custom claims validation.
+            claims_validator_fn: None,
+            // By default, reject ID tokens with a c_hash claim if no authorization code is
+            // supplied.
+            code_hash_requires_code: true,
             // By default, accept any issued time (iat claim).
             iat_verifier_fn: Arc::new(|_| Ok(())),
             jwt_verifier,
@@ -548,6 +943,17 @@ where
         Self::new(JwtClaimsVerifier::new(client_id, issuer, signature_keys))
     }
 
+    ///
+    /// Initializes a new verifier for a public client that verifies signatures using a single
+    /// key, such as one distributed by the provider outside of a JSON Web Key Set endpoint.
+    ///
+    /// All claims are verified as with [`IdTokenVerifier::new_public_client`]. If the ID token
+    /// specifies a key ID (`kid`), it must match the key's ID.
+    ///
+    pub fn new_with_key(client_id: ClientId, issuer: IssuerUrl, key: K) -> Self {
+        Self::new_public_client(client_id, issuer, JsonWebKeySet::new(vec![key]))
+    }
+
     ///
     /// Initializes a no-op verifier that performs no signature, audience, or issuer verification.
     /// The token's expiration time is still checked, and the token is otherwise required to conform to the expected format.
@@ -584,9 +990,48 @@ where
         )
     }
 
+    ///
+    /// Verifies signatures using the keys in the given [`JwksCache`] instead of the signature
+    /// keys this verifier was initialized with.
+    ///
+    /// Keys are refetched as needed to support key rotation, as described in the [`JwksCache`]
+    /// documentation.
+    ///
+    pub fn set_jwks_cache<RE>(self, jwks_cache: JwksCache<JS, JT, JU, K, RE>) -> Self
+    where
+        RE: std::error::Error + 'static,
+        JsonWebKeySet<JS, JT, JU, K>: Send + Sync,
+    {
+        self.set_jwks_source(Arc::new(jwks_cache))
+    }
+
+    pub(crate) fn set_jwks_source(
+        mut self,
+        jwks_source: Arc<dyn JwksSource<JS, JT, JU, K>>,
+    ) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_jwks_source(jwks_source);
+        self
+    }
+
+    pub(crate) fn set_signature_keys(
+        mut self,
+        signature_keys: Arc<JsonWebKeySet<JS, JT, JU, K>>,
+    ) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_signature_keys(signature_keys);
+        self
+    }
+
     ///
     /// Specifies which JSON Web Signature algorithms are supported.
     ///
+    /// ID tokens whose JOSE header `alg` is not in this allowlist are rejected with
+    /// [`SignatureVerificationError::DisallowedAlg`], even if a matching key is available. Pinning
+    /// the accepted algorithms protects against algorithm downgrade and confusion attacks, in
+    /// which an attacker switches the `alg` to a weaker scheme (e.g., `HS256` using the client
+    /// secret). By default, only `RS256` is accepted, while verifiers returned by
+    /// [`Client::id_token_verifier`](crate::Client::id_token_verifier) accept the algorithms
+    /// advertised in the provider's `id_token_signing_alg_values_supported` metadata.
+    ///
     pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
     where
         I: IntoIterator<Item = JS>,
@@ -603,6 +1048,21 @@ where
         self
     }
 
+    ///
+    /// Specifies the client secret used for verifying ID tokens signed using a shared secret
+    /// algorithm (`HS256`, `HS384`, or `HS512`).
+    ///
+    /// The octets of the UTF-8 representation of the client secret are the HMAC key, as described
+    /// in [Section 10.1](https://openid.net/specs/openid-connect-core-1_0.html#Signing) of the
+    /// OpenID Connect Core specification. Since the default allowlist only accepts `RS256`, the
+    /// desired algorithms must also be allowed via [`IdTokenVerifier::set_allowed_algs`]. Without
+    /// a client secret, tokens signed using a shared secret algorithm are always rejected.
+    ///
+    pub fn set_client_secret(mut self, client_secret: ClientSecret) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_client_secret(client_secret);
+        self
+    }
+
     ///
     /// Specifies a function for verifying the `acr` claim.
     ///
@@ -617,6 +1077,62 @@ where
         self
     }
 
+    ///
+    /// Requires the `acr` claim to be present and equal to one of the `allowed` authentication
+    /// context classes (e.g., when requesting step-up authentication via
+    /// [`AuthorizationRequest::add_auth_context_value`](crate::AuthorizationRequest::add_auth_context_value)).
+    ///
+    /// This check is applied in addition to any function passed to
+    /// [`IdTokenVerifier::set_auth_context_verifier_fn`].
+    ///
+    pub fn require_acr(mut self, allowed: Vec<AuthenticationContextClass>) -> Self {
+        self.allowed_acrs = Some(allowed);
+        self
+    }
+
+    ///
+    /// Specifies a function for verifying the `amr` claim (e.g., to require that the list of
+    /// authentication methods includes `mfa`).
+    ///
+    /// The function should return `Ok(())` if the claim is valid, or a string describing the error
+    /// otherwise.
+    ///
+    pub fn set_auth_methods_verifier_fn<T>(mut self, amr_verifier_fn: T) -> Self
+    where
+        T: Fn(Option<&Vec<AuthenticationMethodReference>>) -> Result<(), String> + 'a + Send + Sync,
+    {
+        self.amr_verifier_fn = Arc::new(amr_verifier_fn);
+        self
+    }
+
+    ///
+    /// Specifies a function for performing custom validation of the ID token claims (e.g., to
+    /// require a specific value of an additional claim).
+    ///
+    /// The function is called after the signature and all other claims have been verified. It
+    /// should return `Ok(())` if the claims are valid, or a string describing the error
+    /// otherwise, in which case verification fails with
+    /// [`ClaimsVerificationError::InvalidClaims`]. Verification also fails if this verifier is
+    /// used with ID tokens whose claims are of a type other than `IdTokenClaims<AC, GC>`.
+    ///
+    pub fn set_claims_validator<AC, GC, T>(mut self, claims_validator_fn: T) -> Self
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        T: Fn(&IdTokenClaims<AC, GC>) -> Result<(), String> + 'a + Send + Sync,
+    {
+        self.claims_validator_fn = Some(Arc::new(move |claims: &dyn Any| {
+            match claims.downcast_ref::<IdTokenClaims<AC, GC>>() {
+                Some(claims) => claims_validator_fn(claims),
+                None => Err(format!(
+                    "claims validator expects claims of type `{}`",
+                    type_name::<IdTokenClaims<AC, GC>>()
+                )),
+            }
+        }));
+        self
+    }
+
     ///
     /// Specifies a function for verifying the `auth_time` claim.
     ///
@@ -631,6 +1147,38 @@ where
         self
     }
 
+    ///
+    /// Requires the `auth_time` claim to be present and no more than `max_age` before the current
+    /// time (see [`IdTokenVerifier::set_time_fn`]), plus the configured
+    /// [time leeway](IdTokenVerifier::set_time_leeway).
+    ///
+    /// This should be used whenever the `max_age` parameter is included in the authorization
+    /// request, since the provider must then return the `auth_time` claim, as described in
+    /// [Section 3.1.2.1 of OpenID Connect Core 1.0](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#AuthRequest). This check is applied
+    /// in addition to any function passed to [`IdTokenVerifier::set_auth_time_verifier_fn`].
+    ///
+    pub fn require_auth_time(mut self, max_age: Duration) -> Self {
+        self.auth_time_max_age = Some(max_age);
+        self
+    }
+
+    ///
+    /// Requires the `auth_time` claim to be present, regardless of its value.
+    ///
+    /// This should be used when the `auth_time` claim is requested as an Essential Claim (e.g.,
+    /// using [`ClaimsRequest`](crate::ClaimsRequest)), in which case the provider must return it,
+    /// as described in
+    /// [Section 5.5.1 of OpenID Connect Core 1.0](
+    /// https://openid.net/specs/openid-connect-core-1_0.html#IndividualClaimsRequests). Unlike
+    /// [`IdTokenVerifier::require_auth_time`], the authentication time isn't compared against a
+    /// maximum age.
+    ///
+    pub fn require_auth_time_present(mut self) -> Self {
+        self.auth_time_required = true;
+        self
+    }
+
     ///
     /// Enables signature verification.
     ///
@@ -664,6 +1212,16 @@ where
         self
     }
 
+    ///
+    /// Specifies how the issuer claim is compared to the expected issuer URL for the provider.
+    ///
+    /// By default, the issuer claim must match exactly ([`IssuerMatch::Exact`]).
+    ///
+    pub fn set_issuer_match(mut self, issuer_match: IssuerMatch) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_issuer_match(issuer_match);
+        self
+    }
+
     ///
     /// Specifies whether the audience claim must match this client's client ID.
     ///
@@ -672,10 +1230,28 @@ where
         self
     }
 
+    ///
+    /// Specifies whether [`IdToken::claims_with_code`](crate::IdToken::claims_with_code) rejects
+    /// ID tokens containing an authorization code hash (`c_hash`) claim when no authorization
+    /// code is supplied.
+    ///
+    /// Defaults to `true`. If `false`, the `c_hash` claim is ignored in this case.
+    ///
+    pub fn require_code_for_code_hash(mut self, code_required: bool) -> Self {
+        self.code_hash_requires_code = code_required;
+        self
+    }
+
+    pub(crate) fn code_hash_requires_code(&self) -> bool {
+        self.code_hash_requires_code
+    }
+
     ///
     /// Specifies a function for returning the current time.
     ///
-    /// This function is used for verifying the ID token expiration time.
+    /// This function is used for verifying the ID token expiration time. By default, the current
+    /// system time is used (i.e., [`Utc::now`]). Overriding it is mainly useful for deterministic
+    /// tests and for replaying previously issued ID tokens.
     ///
     pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
     where
@@ -685,6 +1261,29 @@ where
         self
     }
 
+    ///
+    /// Specifies the amount of clock skew to tolerate when verifying the ID token expiration and
+    /// issue times.
+    ///
+    /// An ID token is considered expired once the current time (as returned by the function
+    /// passed to [`IdTokenVerifier::set_time_fn`]) reaches its expiration time plus this leeway,
+    /// and is rejected if its issue time is later than the current time plus this leeway. The
+    /// leeway also extends the `max_age` window (see [`IdTokenVerifier::require_auth_time`]). By
+    /// default, 60 seconds of clock skew are tolerated; strict deployments with synchronized
+    /// clocks may reduce this to zero.
+    ///
+    /// # Security Warning
+    ///
+    /// Expired ID tokens are accepted for the duration of the leeway, extending the window during
+    /// which a stolen or leaked ID token can be replayed. The leeway should therefore be no larger
+    /// than the expected clock skew between the client and the OpenID Connect Provider (typically
+    /// no more than a few minutes).
+    ///
+    pub fn set_time_leeway(mut self, leeway: Duration) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_time_leeway(leeway);
+        self
+    }
+
     ///
     /// Specifies a function for verifying the ID token issue time.
     ///
@@ -699,6 +1298,32 @@ where
         self
     }
 
+    ///
+    /// Specifies additional audiences that identify this client, such as the client IDs it's
+    /// registered under in other environments.
+    ///
+    /// The `aud` claim must contain either this client's client ID or one of these audiences.
+    /// Consider also enabling [`require_authorized_party_match`](Self::require_authorized_party_match)
+    /// to verify the `azp` claim of ID tokens with multiple audiences.
+    ///
+    pub fn set_allowed_audiences(mut self, allowed_audiences: Vec<Audience>) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_audiences(allowed_audiences);
+        self
+    }
+
+    ///
+    /// Specifies whether to verify the authorized party (`azp`) claim.
+    ///
+    /// If enabled, ID tokens with multiple audiences must contain an `azp` claim, and the `azp`
+    /// claim (if present) must be this client's client ID or one of the audiences specified via
+    /// [`set_allowed_audiences`](Self::set_allowed_audiences). By default, the `azp` claim is not
+    /// verified.
+    ///
+    pub fn require_authorized_party_match(mut self, azp_required: bool) -> Self {
+        self.azp_match_required = azp_required;
+        self
+    }
+
     ///
     /// Specifies a function for verifying audiences included in the `aud` claim that differ from
     /// this client's client ID.
@@ -709,6 +1334,18 @@ where
     /// states that *"The ID Token MUST be rejected if the ID Token does not list the Client as a
     /// valid audience, or if it contains additional audiences not trusted by the Client."*
     ///
+    /// This function is only consulted for audiences other than this client's client ID (and any
+    /// audiences specified via [`set_allowed_audiences`](Self::set_allowed_audiences)). The `aud`
+    /// claim must still contain this client's client ID unless audience verification is disabled
+    /// via [`require_audience_match`](Self::require_audience_match). By default, all other
+    /// audiences are rejected.
+    ///
+    /// # Security Warning
+    ///
+    /// Any audience accepted by this function receives a copy of the same ID token, and a
+    /// malicious audience can replay it to this client to impersonate the user. Accepting
+    /// audiences broadly (e.g., always returning `true`) defeats this protection.
+    ///
     pub fn set_other_audience_verifier_fn<T>(mut self, other_aud_verifier_fn: T) -> Self
     where
         T: Fn(&Audience) -> bool + 'a + Send + Sync,
@@ -719,11 +1356,37 @@ where
         self
     }
 
-    pub(super) fn verified_claims<'b, AC, GC, JE, N>(
-        &self,
-        jwt: &'b JsonWebToken<JE, JS, JT, IdTokenClaims<AC, GC>, JsonWebTokenJsonPayloadSerde>,
-        nonce_verifier: N,
-    ) -> Result<&'b IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    ///
+    /// Specifies the key used to decrypt ID tokens encrypted using
+    /// [JSON Web Encryption (JWE)](https://tools.ietf.org/html/rfc7516).
+    ///
+    /// [Section 3.1.3.7](https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation)
+    /// states that encrypted ID tokens are nested JWTs: once decrypted, the inner JWT's signature
+    /// and claims are verified as usual. Unencrypted ID tokens are still accepted when a decryption
+    /// key is configured.
+    ///
+    /// Encrypted ID tokens must be verified using [`IdToken::into_claims`][crate::IdToken::into_claims]
+    /// or first decrypted using [`IdToken::decrypt`][crate::IdToken::decrypt].
+    ///
+    pub fn set_decryption_key<JE, JK, D>(mut self, decryption_key: D) -> Self
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JK: JweKeyManagementAlgorithm,
+        D: PrivateDecryptionKey<JE, JK, JT> + 'a + Send + Sync,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_decryption_key(decryption_key);
+        self
+    }
+
+    pub(super) fn decrypt(&self, jwe: &str) -> Result<String, ClaimsVerificationError> {
+        self.jwt_verifier.decrypt(jwe)
+    }
+
+    pub(super) fn verified_claims<'b, AC, GC, JE, N>(
+        &self,
+        jwt: &'b JsonWebToken<JE, JS, JT, IdTokenClaims<AC, GC>, JsonWebTokenJsonPayloadSerde>,
+        nonce_verifier: N,
+    ) -> Result<&'b IdTokenClaims<AC, GC>, ClaimsVerificationError>
     where
         AC: AdditionalClaims,
         GC: GenderClaim,
@@ -776,46 +1439,44 @@ where
 
         // There is significant confusion and contradiction in the OpenID Connect Core spec around
         // the azp claim. See https://bitbucket.org/openid/connect/issues/973/ for a detailed
-        // discussion. Given the lack of clarity around how this claim should be used, we defer
-        // any verification of it here until a use case becomes apparent. If such a use case does
-        // arise, we most likely want to allow clients to pass in a function for validating the
-        // azp claim rather than introducing logic that affects all clients of this library.
-
-        // This naive implementation of the spec would almost certainly not be useful in practice:
-        /*
-        let azp_required = partially_verified_claims.audiences().len() > 1;
-
-        // 5. If an azp (authorized party) Claim is present, the Client SHOULD verify that its
-        //    client_id is the Claim Value.
-        if let Some(authorized_party) = partially_verified_claims.authorized_party() {
-            if *authorized_party != self.client_id {
-                return Err(ClaimsVerificationError::InvalidAudience(format!(
-                    "authorized party must match client ID `{}` (found `{}`",
-                    *self.client_id, **authorized_party
-                )));
+        // discussion. Given the lack of clarity around how this claim should be used, it's only
+        // verified if the client opts in.
+        if self.azp_match_required {
+            let azp_required = partially_verified_claims.audiences().len() > 1;
+
+            // 5. If an azp (authorized party) Claim is present, the Client SHOULD verify that its
+            //    client_id is the Claim Value.
+            if let Some(authorized_party) = partially_verified_claims.authorized_party() {
+                if !self.jwt_verifier.is_own_audience(authorized_party) {
+                    return Err(ClaimsVerificationError::InvalidAudience(format!(
+                        "authorized party must be {} (found `{}`)",
+                        self.jwt_verifier.own_audiences_description(),
+                        **authorized_party
+                    )));
+                }
+            } else if azp_required {
+                return Err(ClaimsVerificationError::InvalidAudience(
+                    "missing authorized party claim but multiple audiences found".to_string(),
+                ));
             }
-        } else if azp_required {
-            return Err(ClaimsVerificationError::InvalidAudience(format!(
-                "missing authorized party claim but multiple audiences found"
-            )));
         }
-        */
 
         // Steps 6--8 are handled by the generic JwtClaimsVerifier.
 
-        // 9. The current time MUST be before the time represented by the exp Claim.
-        let cur_time = (*self.time_fn)();
-        if cur_time >= partially_verified_claims.expiration() {
-            return Err(ClaimsVerificationError::Expired(format!(
-                "ID token expired at {} (current time is {})",
-                partially_verified_claims.expiration(),
-                cur_time
-            )));
-        }
-
+        // 9. The current time MUST be before the time represented by the exp Claim. We tolerate
+        //    the configured amount of clock skew between the client and the provider.
+        //
         // 10. The iat Claim can be used to reject tokens that were issued too far away from the
         //     current time, limiting the amount of time that nonces need to be stored to prevent
-        //     attacks. The acceptable range is Client specific.
+        //     attacks. The acceptable range is Client specific. ID tokens issued in the future
+        //     (beyond the clock skew leeway) are always rejected.
+        let cur_time = (*self.time_fn)();
+        self.jwt_verifier.verify_time_claims(
+            "ID token",
+            cur_time,
+            Some(partially_verified_claims.expiration()),
+            Some(partially_verified_claims.issue_time()),
+        )?;
         (*self.iat_verifier_fn)(partially_verified_claims.issue_time())
             .map_err(ClaimsVerificationError::Expired)?;
 
@@ -832,6 +1493,29 @@ where
         //     this specification.
         (*self.acr_verifier_fn)(partially_verified_claims.auth_context_ref())
             .map_err(ClaimsVerificationError::InvalidAuthContext)?;
+        if let Some(ref allowed_acrs) = self.allowed_acrs {
+            match partially_verified_claims.auth_context_ref() {
+                Some(acr) if allowed_acrs.contains(acr) => {}
+                Some(acr) => {
+                    return Err(ClaimsVerificationError::InvalidAuthContext(format!(
+                        "authentication context class `{}` is not allowed (allowed: {})",
+                        **acr,
+                        allowed_acrs
+                            .iter()
+                            .map(|allowed| format!("`{}`", **allowed))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )))
+                }
+                None => {
+                    return Err(ClaimsVerificationError::InvalidAuthContext(
+                        "missing acr claim".to_string(),
+                    ))
+                }
+            }
+        }
+        (*self.amr_verifier_fn)(partially_verified_claims.auth_method_refs())
+            .map_err(ClaimsVerificationError::InvalidAuthMethods)?;
 
         // 13. If the auth_time Claim was requested, either through a specific request for this
         //     Claim or by using the max_age parameter, the Client SHOULD check the auth_time Claim
@@ -839,53 +1523,157 @@ where
         //     the last End-User authentication.
         (*self.auth_time_verifier_fn)(partially_verified_claims.auth_time())
             .map_err(ClaimsVerificationError::InvalidAuthTime)?;
+        if self.auth_time_required && partially_verified_claims.auth_time().is_none() {
+            return Err(ClaimsVerificationError::InvalidAuthTime(
+                "missing required auth_time claim".to_string(),
+            ));
+        }
+        if let Some(max_age) = self.auth_time_max_age {
+            let auth_time = partially_verified_claims.auth_time().ok_or_else(|| {
+                ClaimsVerificationError::InvalidAuthTime(
+                    "missing auth_time claim, which is required when max_age is requested"
+                        .to_string(),
+                )
+            })?;
+            let max_auth_time_with_leeway = chrono::Duration::from_std(max_age)
+                .ok()
+                .and_then(|max_age| auth_time.checked_add_signed(max_age))
+                .and_then(|max_auth_time| self.jwt_verifier.add_time_leeway(max_auth_time));
+            if max_auth_time_with_leeway
+                .map(|max_auth_time| cur_time > max_auth_time)
+                .unwrap_or(false)
+            {
+                return Err(ClaimsVerificationError::InvalidAuthTime(format!(
+                    "authentication at {} is older than max_age of {} seconds (current time is \
+                     {})",
+                    auth_time,
+                    max_age.as_secs(),
+                    cur_time
+                )));
+            }
+        }
+
+        if let Some(ref claims_validator_fn) = self.claims_validator_fn {
+            claims_validator_fn(partially_verified_claims)
+                .map_err(ClaimsVerificationError::InvalidClaims)?;
+        }
 
         Ok(())
     }
 }
 
 ///
-/// User info verifier.
+/// Logout token verifier for [OpenID Connect Back-Channel Logout 1.0](
+/// https://openid.net/specs/openid-connect-backchannel-1_0.html).
 ///
 #[derive(Clone)]
-pub struct UserInfoVerifier<'a, JE, JS, JT, JU, K>
+pub struct LogoutTokenVerifier<'a, JS, JT, JU, K>
 where
-    JE: JweContentEncryptionAlgorithm<JT>,
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
     JU: JsonWebKeyUse,
     K: JsonWebKey<JS, JT, JU>,
 {
+    iat_verifier_fn: Arc<dyn Fn(DateTime<Utc>) -> Result<(), String> + 'a + Send + Sync>,
     jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
-    expected_subject: Option<SubjectIdentifier>,
-    _phantom: PhantomData<JE>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'a + Send + Sync>,
 }
-impl<'a, JE, JS, JT, JU, K> UserInfoVerifier<'a, JE, JS, JT, JU, K>
+impl<'a, JS, JT, JU, K> LogoutTokenVerifier<'a, JS, JT, JU, K>
 where
-    JE: JweContentEncryptionAlgorithm<JT>,
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
     JU: JsonWebKeyUse,
     K: JsonWebKey<JS, JT, JU>,
 {
+    fn new(jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>) -> Self {
+        LogoutTokenVerifier {
+            // By default, accept any issued time (iat claim).
+            iat_verifier_fn: Arc::new(|_| Ok(())),
+            // Section 2.4 recommends explicitly typing logout tokens as `logout+jwt`.
+            jwt_verifier: jwt_verifier.set_jwt_types(&["JWT", "logout+jwt"]),
+            // By default, use the current system time.
+            time_fn: Arc::new(Utc::now),
+        }
+    }
+
     ///
-    /// Instantiates a user info verifier.
+    /// Initializes a new verifier for a public client (i.e., one without a client secret).
     ///
-    pub fn new(
+    pub fn new_public_client(
         client_id: ClientId,
         issuer: IssuerUrl,
         signature_keys: JsonWebKeySet<JS, JT, JU, K>,
-        expected_subject: Option<SubjectIdentifier>,
     ) -> Self {
-        UserInfoVerifier {
-            jwt_verifier: JwtClaimsVerifier::new(client_id, issuer, signature_keys),
-            expected_subject,
-            _phantom: PhantomData,
-        }
+        Self::new(JwtClaimsVerifier::new(client_id, issuer, signature_keys))
     }
 
-    pub(crate) fn expected_subject(&self) -> Option<&SubjectIdentifier> {
-        self.expected_subject.as_ref()
+    ///
+    /// Initializes a new verifier for a confidential client (i.e., one with a client secret).
+    ///
+    /// A confidential client verifier is required in order to verify logout tokens signed using a
+    /// shared secret algorithm such as `HS256`, `HS384`, or `HS512`. For these algorithms, the
+    /// client secret is the shared secret.
+    ///
+    pub fn new_confidential_client(
+        client_id: ClientId,
+        client_secret: ClientSecret,
+        issuer: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Self {
+        Self::new(
+            JwtClaimsVerifier::new(client_id, issuer, signature_keys)
+                .set_client_secret(client_secret),
+        )
+    }
+
+    ///
+    /// Verifies signatures using the keys in the given [`JwksCache`] instead of the signature
+    /// keys this verifier was initialized with.
+    ///
+    /// Keys are refetched as needed to support key rotation, as described in the [`JwksCache`]
+    /// documentation.
+    ///
+    pub fn set_jwks_cache<RE>(self, jwks_cache: JwksCache<JS, JT, JU, K, RE>) -> Self
+    where
+        RE: std::error::Error + 'static,
+        JsonWebKeySet<JS, JT, JU, K>: Send + Sync,
+    {
+        self.set_jwks_source(Arc::new(jwks_cache))
+    }
+
+    pub(crate) fn set_jwks_source(
+        mut self,
+        jwks_source: Arc<dyn JwksSource<JS, JT, JU, K>>,
+    ) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_jwks_source(jwks_source);
+        self
+    }
+
+    pub(crate) fn set_signature_keys(
+        mut self,
+        signature_keys: Arc<JsonWebKeySet<JS, JT, JU, K>>,
+    ) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_signature_keys(signature_keys);
+        self
+    }
+
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
     }
 
     ///
@@ -896,6 +1684,16 @@ where
         self
     }
 
+    ///
+    /// Specifies how the issuer claim is compared to the expected issuer URL for the provider.
+    ///
+    /// By default, the issuer claim must match exactly ([`IssuerMatch::Exact`]).
+    ///
+    pub fn set_issuer_match(mut self, issuer_match: IssuerMatch) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_issuer_match(issuer_match);
+        self
+    }
+
     ///
     /// Specifies whether the audience claim must match this client's client ID.
     ///
@@ -904,1018 +1702,3212 @@ where
         self
     }
 
-    pub(crate) fn verified_claims<AC, GC>(
-        &self,
-        user_info_jwt: JsonWebToken<
-            JE,
-            JS,
-            JT,
-            UserInfoClaimsImpl<AC, GC>,
-            JsonWebTokenJsonPayloadSerde,
-        >,
-    ) -> Result<UserInfoClaimsImpl<AC, GC>, ClaimsVerificationError>
+    ///
+    /// Specifies a function for verifying audiences included in the `aud` claim that differ from
+    /// this client's client ID.
+    ///
+    /// The function should return `true` if the audience is trusted, or `false` otherwise.
+    ///
+    pub fn set_other_audience_verifier_fn<T>(mut self, other_aud_verifier_fn: T) -> Self
     where
-        AC: AdditionalClaims,
-        GC: GenderClaim,
+        T: Fn(&Audience) -> bool + 'a + Send + Sync,
     {
-        let user_info = self.jwt_verifier.verified_claims(user_info_jwt)?;
-        if self
-            .expected_subject
-            .iter()
-            .all(|expected_subject| user_info.standard_claims.sub == *expected_subject)
-        {
-            Ok(user_info)
-        } else {
-            Err(ClaimsVerificationError::InvalidSubject(format!(
-                "expected `{}` (found `{}`)",
-                // This can only happen when self.expected_subject is not None.
-                self.expected_subject.as_ref().unwrap().as_str(),
-                user_info.standard_claims.sub.as_str()
-            )))
-        }
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_other_audience_verifier_fn(other_aud_verifier_fn);
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for verifying the logout token expiration time, if present. By
+    /// default, the current system time is used (i.e., [`Utc::now`]).
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + 'a + Send + Sync,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
 
-    use chrono::{TimeZone, Utc};
-    use oauth2::{ClientId, ClientSecret};
+    ///
+    /// Specifies the amount of clock skew to tolerate when verifying the logout token expiration
+    /// and issue times. By default, 60 seconds of clock skew are tolerated.
+    ///
+    /// See [`IdTokenVerifier::set_time_leeway`] for the security implications of this setting.
+    ///
+    pub fn set_time_leeway(mut self, leeway: Duration) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_time_leeway(leeway);
+        self
+    }
 
-    use super::{
-        AudiencesClaim, ClaimsVerificationError, IssuerClaim, JsonWebTokenHeader,
-        JwtClaimsVerifier, SignatureVerificationError, SubjectIdentifier,
-    };
-    use crate::core::{
-        CoreIdToken, CoreIdTokenClaims, CoreIdTokenVerifier, CoreJsonWebKey, CoreJsonWebKeySet,
-        CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreJweContentEncryptionAlgorithm,
-        CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey, CoreUserInfoClaims,
-        CoreUserInfoJsonWebToken, CoreUserInfoVerifier,
-    };
-    use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
-    use crate::jwt::{JsonWebToken, JsonWebTokenJsonPayloadSerde};
-    use crate::types::helpers::timestamp_to_utc;
-    use crate::types::Base64UrlEncodedBytes;
-    use crate::types::Timestamp;
-    use crate::{
-        AccessToken, Audience, AuthenticationContextClass, AuthorizationCode, EndUserName,
-        IssuerUrl, JsonWebKeyId, Nonce, StandardClaims, UserInfoError,
-    };
+    ///
+    /// Specifies a function for verifying the logout token issue time.
+    ///
+    /// The function should return `Ok(())` if the claim is valid, or a string describing the error
+    /// otherwise. Since logout tokens are often valid for only a short time, this may be used to
+    /// reject tokens issued too long ago.
+    ///
+    pub fn set_issue_time_verifier_fn<T>(mut self, iat_verifier_fn: T) -> Self
+    where
+        T: Fn(DateTime<Utc>) -> Result<(), String> + 'a + Send + Sync,
+    {
+        self.iat_verifier_fn = Arc::new(iat_verifier_fn);
+        self
+    }
 
-    type CoreJsonWebTokenHeader = JsonWebTokenHeader<
-        CoreJweContentEncryptionAlgorithm,
-        CoreJwsSigningAlgorithm,
-        CoreJsonWebKeyType,
-    >;
+    pub(crate) fn verified_claims<'b, JE>(
+        &self,
+        jwt: &'b JsonWebToken<JE, JS, JT, LogoutTokenClaims, JsonWebTokenJsonPayloadSerde>,
+    ) -> Result<&'b LogoutTokenClaims, ClaimsVerificationError>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+    {
+        // The code below roughly follows the validation steps described in
+        // https://openid.net/specs/openid-connect-backchannel-1_0.html#Validation
 
-    type CoreJwtClaimsVerifier<'a> = JwtClaimsVerifier<
-        'a,
-        CoreJwsSigningAlgorithm,
-        CoreJsonWebKeyType,
-        CoreJsonWebKeyUse,
-        CoreJsonWebKey,
-    >;
+        // 2. Validate the Logout Token signature in the same way that an ID Token signature is
+        //    validated. 3. Validate the iss, aud, iat, and exp Claims in the same way they are
+        //    validated in ID Tokens.
+        let partially_verified_claims = self.jwt_verifier.verified_claims(jwt)?;
 
-    fn assert_unsupported<T>(result: Result<T, ClaimsVerificationError>, expected_substr: &str) {
-        match result {
-            Err(ClaimsVerificationError::Unsupported(msg)) => {
-                assert!(msg.contains(expected_substr))
+        self.jwt_verifier.verify_time_claims(
+            "logout token",
+            (*self.time_fn)(),
+            partially_verified_claims.expiration(),
+            Some(partially_verified_claims.issue_time()),
+        )?;
+        (*self.iat_verifier_fn)(partially_verified_claims.issue_time())
+            .map_err(ClaimsVerificationError::Expired)?;
+
+        // 4. Verify that the Logout Token contains a sub Claim, a sid Claim, or both.
+        if partially_verified_claims.subject().is_none()
+            && partially_verified_claims.session_id().is_none()
+        {
+            return Err(ClaimsVerificationError::InvalidSubject(
+                "logout token must contain a `sub` claim, a `sid` claim, or both".to_string(),
+            ));
+        }
+
+        // 5. Verify that the Logout Token contains an events Claim whose value is JSON object
+        //    containing the member name http://schemas.openid.net/event/backchannel-logout.
+        match partially_verified_claims
+            .events()
+            .get(LogoutTokenClaims::BACKCHANNEL_LOGOUT_EVENT)
+        {
+            Some(serde_json::Value::Object(_)) => {}
+            Some(_) => {
+                return Err(ClaimsVerificationError::InvalidEvents(format!(
+                    "`{}` event must be a JSON object",
+                    LogoutTokenClaims::BACKCHANNEL_LOGOUT_EVENT
+                )))
+            }
+            None => {
+                return Err(ClaimsVerificationError::InvalidEvents(format!(
+                    "missing `{}` event",
+                    LogoutTokenClaims::BACKCHANNEL_LOGOUT_EVENT
+                )))
             }
-            Err(err) => panic!("unexpected error: {:?}", err),
-            Ok(_) => panic!("validation should fail"),
         }
-    }
 
-    #[test]
-    fn test_jose_header() {
-        // Unexpected JWT type.
-        assert_unsupported(
-            CoreJwtClaimsVerifier::validate_jose_header(
-                &serde_json::from_str::<CoreJsonWebTokenHeader>(
-                    "{\"alg\":\"RS256\",\"typ\":\"NOT_A_JWT\"}",
-                )
-                .expect("failed to deserialize"),
-            ),
-            "unsupported JWT type",
-        );
+        // 6. Verify that the Logout Token does not contain a nonce Claim.
+        if partially_verified_claims.has_nonce() {
+            return Err(ClaimsVerificationError::InvalidNonce(
+                "logout token must not contain a nonce".to_string(),
+            ));
+        }
 
-        // Nested JWTs.
-        assert_unsupported(
-            CoreJwtClaimsVerifier::validate_jose_header(
-                &serde_json::from_str::<CoreJsonWebTokenHeader>(
-                    "{\"alg\":\"RS256\",\"cty\":\"JWT\"}",
-                )
-                .expect("failed to deserialize"),
-            ),
-            "nested JWT",
-        );
-        assert_unsupported(
-            CoreJwtClaimsVerifier::validate_jose_header(
-                &serde_json::from_str::<CoreJsonWebTokenHeader>(
-                    "{\"alg\":\"RS256\",\"cty\":\"NOT_A_JWT\"}",
-                )
-                .expect("failed to deserialize"),
-            ),
-            "unsupported JWT content type",
-        );
+        // Steps 7--8 (jti replay detection and sid/sub matching) are application-specific.
 
-        // Critical fields. Adapted from https://tools.ietf.org/html/rfc7515#appendix-E
-        assert_unsupported(
-            CoreJwtClaimsVerifier::validate_jose_header(
-                &serde_json::from_str::<CoreJsonWebTokenHeader>(
-                    "{\
-                     \"alg\":\"RS256\",\
-                     \"crit\":[\"http://example.invalid/UNDEFINED\"],\
-                     \"http://example.invalid/UNDEFINED\":true\
-                     }",
-                )
-                .expect("failed to deserialize"),
-            ),
-            "critical JWT header fields are unsupported",
-        );
+        Ok(partially_verified_claims)
     }
+}
 
-    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
-    struct TestClaims {
-        aud: Option<Vec<Audience>>,
-        iss: Option<IssuerUrl>,
-        payload: String,
-    }
-    impl AudiencesClaim for TestClaims {
-        fn audiences(&self) -> Option<&Vec<Audience>> {
-            self.aud.as_ref()
+///
+/// JWT Secured Authorization Response Mode (JARM) response verifier.
+///
+/// Verifies the `response` JWT returned when requesting one of the JARM response modes (e.g.,
+/// [`core::CoreResponseMode::Jwt`](crate::core::CoreResponseMode::Jwt)), as described in
+/// [Section 2.4 of JARM](https://openid.net/specs/oauth-v2-jarm.html#name-processing-rules). The
+/// JWT must be signed by the provider, issued to this client, and unexpired.
+///
+/// See [`AuthorizationResponse::from_jarm`](crate::AuthorizationResponse::from_jarm).
+///
+#[derive(Clone)]
+pub struct JarmVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'a + Send + Sync>,
+    _phantom: PhantomData<JE>,
+}
+impl<'a, JE, JS, JT, JU, K> JarmVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn new(jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>) -> Self {
+        JarmVerifier {
+            jwt_verifier,
+            // By default, use the current system time.
+            time_fn: Arc::new(Utc::now),
+            _phantom: PhantomData,
         }
     }
-    impl IssuerClaim for TestClaims {
-        fn issuer(&self) -> Option<&IssuerUrl> {
-            self.iss.as_ref()
-        }
+
+    ///
+    /// Initializes a new verifier for a public client (i.e., one without a client secret).
+    ///
+    pub fn new_public_client(
+        client_id: ClientId,
+        issuer: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Self {
+        Self::new(JwtClaimsVerifier::new(client_id, issuer, signature_keys))
     }
-    type TestClaimsJsonWebToken = JsonWebToken<
-        CoreJweContentEncryptionAlgorithm,
-        CoreJwsSigningAlgorithm,
-        CoreJsonWebKeyType,
-        TestClaims,
-        JsonWebTokenJsonPayloadSerde,
-    >;
 
-    #[test]
-    fn test_jwt_verified_claims() {
-        let rsa_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
-            .expect("deserialization failed");
+    ///
+    /// Initializes a new verifier for a confidential client (i.e., one with a client secret).
+    ///
+    /// A confidential client verifier is required in order to verify responses signed using a
+    /// shared secret algorithm such as `HS256`, `HS384`, or `HS512`. For these algorithms, the
+    /// client secret is the shared secret.
+    ///
+    pub fn new_confidential_client(
+        client_id: ClientId,
+        client_secret: ClientSecret,
+        issuer: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Self {
+        Self::new(
+            JwtClaimsVerifier::new(client_id, issuer, signature_keys)
+                .set_client_secret(client_secret),
+        )
+    }
 
-        let client_id = ClientId::new("my_client".to_string());
-        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
-        let verifier = CoreJwtClaimsVerifier::new(
-            client_id.clone(),
-            issuer.clone(),
-            CoreJsonWebKeySet::new(vec![rsa_key.clone()]),
-        );
+    ///
+    /// Verifies signatures using the keys in the given [`JwksCache`] instead of the signature
+    /// keys this verifier was initialized with.
+    ///
+    pub fn set_jwks_cache<RE>(self, jwks_cache: JwksCache<JS, JT, JU, K, RE>) -> Self
+    where
+        RE: std::error::Error + 'static,
+        JsonWebKeySet<JS, JT, JU, K>: Send + Sync,
+    {
+        self.set_jwks_source(Arc::new(jwks_cache))
+    }
 
-        // Invalid JOSE header.
-        assert_unsupported(
-            verifier.verified_claims(
-                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                    "eyJhbGciOiJBMjU2R0NNIiwiY3R5IjoiSldUIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Im\
-                     h0dHBzOi8vZXhhbXBsZS5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
-                        .to_string(),
-                )).expect("failed to deserialize"),
-            ),
-            "nested JWT",
-        );
+    pub(crate) fn set_jwks_source(
+        mut self,
+        jwks_source: Arc<dyn JwksSource<JS, JT, JU, K>>,
+    ) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_jwks_source(jwks_source);
+        self
+    }
 
-        // JWE-encrypted JWT.
-        assert_unsupported(
-            verifier.verified_claims(
-                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                    "eyJhbGciOiJBMjU2R0NNIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbX\
-                     BsZS5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
-                        .to_string(),
-                )).expect("failed to deserialize"),
-            ),
-            "JWE encryption",
-        );
+    pub(crate) fn set_signature_keys(
+        mut self,
+        signature_keys: Arc<JsonWebKeySet<JS, JT, JU, K>>,
+    ) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_signature_keys(signature_keys);
+        self
+    }
 
-        // Wrong issuer.
-        match verifier.verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vYXR0YWNrZXIuY\
-                 29tIiwicGF5bG9hZCI6ImhlbGxvIHdvcmxkIn0.YmFkX2hhc2g"
-                    .to_string(),
-            )).expect("failed to deserialize"),
-        ) {
-            Err(ClaimsVerificationError::InvalidIssuer(_)) => {},
-            other => panic!("unexpected result: {:?}", other),
-        }
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
 
-        // Missing issuer.
-        match verifier.verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.\
-                 YmFkX2hhc2g"
-                    .to_string(),
-            )).expect("failed to deserialize"),
-        ) {
-            Err(ClaimsVerificationError::InvalidIssuer(_)) => {},
-            other => panic!("unexpected result: {:?}", other),
-        }
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
+    }
 
-        // Ignore missing issuer.
-        verifier
-            .clone()
-            .require_issuer_match(false)
-            .verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.\
-                 nv09al63NNDfb8cF3IozegXKbPaUC08zknRPKmQ5qKgXv80hjVxknkpRz7BxocB3JYTBjhYd0gyN9wAuJj\
-                 byZ1QaUC14HOB83awAGbehy5yFLkLadTfPT7-siBCvE2V7AF73a_21YvwdkKmJ-RaKWHzFnG8CDmioma3X\
-                 cWyrsdRLgvUkrWllajLRo8DCIXQ8OuZo1_o4n17PSlPxSkhKIrgaWCvG6tan40Y_1DZOFv47bx4hQUGd-J\
-                 h2aEjiwn65WV3M_Xb2vQMP7VgYNVaNlfxzpL4yDASItbPMWaXBt3ZUa_IOGoSx2GMnPkrQ4xp56qUth6U7\
-                 esWPqRSqqolnHg"
-                    .to_string(),
-            )).expect("failed to deserialize"),
-        ).expect("verification should succeed");
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for verifying the response expiration time. By default, the current
+    /// system time is used (i.e., [`Utc::now`]).
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + 'a + Send + Sync,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
 
-        // Wrong audience.
-        match verifier.verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsib3RoZXJfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
-                    .to_string(),
-            )).expect("failed to deserialize"),
-        ) {
-            Err(ClaimsVerificationError::InvalidAudience(_)) => {},
-            other => panic!("unexpected result: {:?}", other),
-        }
+    ///
+    /// Specifies the amount of clock skew to tolerate when verifying the response expiration
+    /// time. By default, 60 seconds of clock skew are tolerated.
+    ///
+    /// See [`IdTokenVerifier::set_time_leeway`] for the security implications of this setting.
+    ///
+    pub fn set_time_leeway(mut self, leeway: Duration) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_time_leeway(leeway);
+        self
+    }
 
-        // Missing audience.
-        match verifier.verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiJ9.eyJpc3MiOiJodHRwczovL2V4YW1wbGUuY29tIiwicGF5bG9hZCI6ImhlbGxvI\
-                 HdvcmxkIn0.YmFkX2hhc2g"
-                    .to_string(),
-            )).expect("failed to deserialize"),
-        ) {
-            Err(ClaimsVerificationError::InvalidAudience(_)) => {},
-            other => panic!("unexpected result: {:?}", other),
-        }
+    // Verifies the given JARM response JWT and returns the authorization response parameters it
+    // contains (excluding registered JWT claims such as `iss`, `aud`, and `exp`).
+    pub(crate) fn verified_params(
+        &self,
+        jwt: &str,
+    ) -> Result<Map<String, Value>, ClaimsVerificationError> {
+        let jwt: JsonWebToken<JE, JS, JT, JarmClaims, JsonWebTokenJsonPayloadSerde> =
+            serde_json::from_value(Value::String(jwt.to_string())).map_err(|err| {
+                ClaimsVerificationError::Other(format!("failed to parse JARM response: {}", err))
+            })?;
+
+        // Section 2.4: the client MUST validate the iss, aud, and exp claims, along with the
+        // signature.
+        let claims = self.jwt_verifier.verified_claims(jwt)?;
+
+        let expiration = claims.expiration.ok_or_else(|| {
+            ClaimsVerificationError::Expired("missing expiration claim".to_string())
+        })?;
+        self.jwt_verifier.verify_time_claims(
+            "JARM response",
+            (*self.time_fn)(),
+            Some(expiration),
+            None,
+        )?;
+
+        Ok(claims.params)
+    }
+}
 
-        // Ignore missing audience.
-        verifier
-            .clone()
-            .require_audience_match(false)
-            .verified_claims(
-                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                    "eyJhbGciOiJSUzI1NiJ9.eyJpc3MiOiJodHRwczovL2V4YW1wbGUuY29tIiwicGF5bG9hZCI6Imhlb\
-                     GxvIHdvcmxkIn0.lP-Z_zGPNoKIbLQsnrZc2LAc5qJrKyb7t07ZtJUKVhcwHiCUou4bBhq5RHlElCh\
-                     0ElRRP6I25lp6UszkRvIC46UV3GVze0x73kVkHSvCVI7MO75LbL9BRqrm5b4CN2zCiFBY8-EwTXnJd\
-                     Ri0d_U8K29TV24L2I-Z5ZILebwUue1N59AGDjx2yYLFx5NOw3TUsPyscG62aZAT321pL_jcYwTWTWw\
-                     2FYm07zguwx-PUTZwGXlJiOgXQqRIbY_1bS3I_D8UWsmEB3DmV0f9z-iklgIPFawa4wHaE-hpzBAEx\
-                     pSieyOavA5pl0Se3XRYA-CkdDVgzG0Pt4IdnxFanfUXTw"
-                        .to_string(),
-                )).expect("failed to deserialize"),
-            ).expect("verification should succeed");
+///
+/// Verifier for request objects received by a party acting as an authorization server (e.g., a
+/// relying party proxying authorization requests), as described in
+/// [Section 6.3 of OpenID Connect Core 1.0](
+/// https://openid.net/specs/openid-connect-core-1_0.html#RequestObjectValidation).
+///
+#[derive(Clone)]
+pub struct RequestObjectVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    client_id: ClientId,
+    jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'a + Send + Sync>,
+}
+impl<'a, JS, JT, JU, K> RequestObjectVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Initializes a new verifier for request objects sent by the client with the given ID.
+    ///
+    /// The `audience` is the issuer identifier of the party receiving the request object, and
+    /// `signature_keys` are the requesting client's public keys (e.g., from its registered
+    /// `jwks` or `jwks_uri`).
+    ///
+    pub fn new(
+        client_id: ClientId,
+        audience: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Self {
+        RequestObjectVerifier {
+            client_id,
+            // The audience of a request object is the receiving party's issuer identifier rather
+            // than a client ID, and its issuer is a client ID, which is verified separately.
+            jwt_verifier: JwtClaimsVerifier::new(
+                ClientId::new(audience.to_string()),
+                audience,
+                signature_keys,
+            )
+            .require_issuer_match(false)
+            .set_jwt_types(&["JWT", REQUEST_OBJECT_JWT_TYPE]),
+            // By default, use the current system time.
+            time_fn: Arc::new(Utc::now),
+        }
+    }
 
-        // Multiple audiences, where one is a match (default = reject)
-        match verifier.verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsiYXVkMSIsIm15X2NsaWVudCIsImF1ZDIiXSwiaXNzIjoia\
-                 HR0cHM6Ly9leGFtcGxlLmNvbSIsInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.N9ibisEe0kKLe1GDWM\
-                 ON3PmYqbL73dag-loM8pjKJNinF9SB7n4JuSu4FrNkeW4F1Cz8MIbLuWfKvDa_4v_3FstMA3GODZWH\
-                 BVIiuNFay2ovCfGFyykwe47dF_47g_OM5AkJc_teE5MN8lPh9V5zYCy3ON3zZ3acFPJMOPTdbU56xD\
-                 eFe7lil6DmV4JU9A52t5ZkJILFaIuxxXJUIDmqpPTvHkggh_QOj9C2US9bgg5b543JwT4j-HbDp51L\
-                 dDB4k3azOssT1ddtoAuuDOctnraMKUtqffJXexxfwA1uM6EIofSrK5v11xwgTciL9xDXAvav_G2buP\
-                 ol1bjGLa2t0Q"
-                    .to_string(),
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
+    }
+
+    ///
+    /// Specifies whether the audience claim must contain the receiving party's issuer identifier.
+    ///
+    pub fn require_audience_match(mut self, aud_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_audience_match(aud_required);
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for verifying the request object expiration time, if present. By
+    /// default, the current system time is used (i.e., [`Utc::now`]).
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + 'a + Send + Sync,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    ///
+    /// Specifies the amount of clock skew to tolerate when verifying the request object
+    /// expiration time. By default, 60 seconds of clock skew are tolerated.
+    ///
+    /// See [`IdTokenVerifier::set_time_leeway`] for the security implications of this setting.
+    ///
+    pub fn set_time_leeway(mut self, leeway: Duration) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_time_leeway(leeway);
+        self
+    }
+
+    pub(crate) fn verified_claims<'b, JE>(
+        &self,
+        jwt: &'b JsonWebToken<JE, JS, JT, RequestObjectClaims, JsonWebTokenJsonPayloadSerde>,
+    ) -> Result<&'b RequestObjectClaims, ClaimsVerificationError>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+    {
+        let partially_verified_claims = self.jwt_verifier.verified_claims(jwt)?;
+
+        for (claim_name, claim) in [
+            ("iss", partially_verified_claims.issuer()),
+            ("client_id", partially_verified_claims.client_id()),
+        ] {
+            if let Some(client_id) = claim {
+                if *client_id != self.client_id {
+                    return Err(ClaimsVerificationError::InvalidIssuer(format!(
+                        "expected `{}` claim to be `{}` (found `{}`)",
+                        claim_name, *self.client_id, **client_id
+                    )));
+                }
+            }
+        }
+
+        self.jwt_verifier.verify_time_claims(
+            "request object",
+            (*self.time_fn)(),
+            partially_verified_claims.expiration(),
+            None,
+        )?;
+
+        Ok(partially_verified_claims)
+    }
+}
+
+///
+/// ID token verifier that dispatches to one of several per-issuer verifiers based on the ID
+/// token's issuer (`iss`) claim.
+///
+/// This is useful for services that accept ID tokens from multiple OpenID Connect Providers
+/// (e.g., a multi-tenant gateway), each with its own client ID and signing keys. The unverified
+/// `iss` claim is only used to select a verifier: ID tokens from unknown issuers are rejected
+/// before any signature verification, and each ID token must then pass full verification by the
+/// selected verifier.
+///
+#[derive(Clone)]
+pub struct MultiIssuerVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    verifiers: HashMap<IssuerUrl, IdTokenVerifier<'a, JS, JT, JU, K>>,
+}
+impl<'a, JS, JT, JU, K> MultiIssuerVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Initializes a new verifier that doesn't trust any issuers.
+    ///
+    pub fn new() -> Self {
+        MultiIssuerVerifier {
+            verifiers: HashMap::new(),
+        }
+    }
+
+    ///
+    /// Verifies ID tokens whose issuer claim exactly matches `issuer` using the given `verifier`.
+    ///
+    /// The `verifier` should be configured with the same expected issuer (e.g., using
+    /// [`IdTokenVerifier::new_confidential_client`] or [`IdTokenVerifier::new_public_client`]),
+    /// along with the client ID and signing keys registered with that issuer. Any verifier
+    /// previously added for the same issuer is replaced.
+    ///
+    pub fn add_issuer(
+        mut self,
+        issuer: IssuerUrl,
+        verifier: IdTokenVerifier<'a, JS, JT, JU, K>,
+    ) -> Self {
+        self.verifiers.insert(issuer, verifier);
+        self
+    }
+
+    ///
+    /// Returns the verifier for the given issuer, if any.
+    ///
+    pub fn verifier(&self, issuer: &IssuerUrl) -> Option<&IdTokenVerifier<'a, JS, JT, JU, K>> {
+        self.verifiers.get(issuer)
+    }
+
+    ///
+    /// Returns the verifier for the given ID token's (unverified) issuer claim.
+    ///
+    /// Returns [`ClaimsVerificationError::InvalidIssuer`] if no verifier is configured for the
+    /// issuer. Encrypted ID tokens are unsupported, since their issuer claim can't be read until
+    /// they're decrypted.
+    ///
+    pub fn select_verifier<AC, GC, JE>(
+        &self,
+        id_token: &IdToken<AC, GC, JE, JS, JT>,
+    ) -> Result<&IdTokenVerifier<'a, JS, JT, JU, K>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+    {
+        let issuer = id_token.unverified_claims()?.issuer();
+        self.verifier(issuer).ok_or_else(|| {
+            ClaimsVerificationError::InvalidIssuer(format!(
+                "`{}` is not a trusted issuer",
+                **issuer
             ))
-            .expect("failed to deserialize"),
-        ) {
-            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
-            other => panic!("unexpected result: {:?}", other),
+        })
+    }
+
+    ///
+    /// Verifies and returns a reference to the ID token claims using the verifier for the ID
+    /// token's issuer.
+    ///
+    /// See [`select_verifier`](Self::select_verifier) and [`IdToken::claims`].
+    ///
+    pub fn verified_claims<'b, AC, GC, JE, N>(
+        &self,
+        id_token: &'b IdToken<AC, GC, JE, JS, JT>,
+        nonce_verifier: N,
+    ) -> Result<&'b IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        N: NonceVerifier,
+    {
+        id_token.claims(self.select_verifier(id_token)?, nonce_verifier)
+    }
+}
+impl<'a, JS, JT, JU, K> Default for MultiIssuerVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///
+/// Verifier for JWTs returned by the claims providers of
+/// [aggregated and distributed claims](
+/// https://openid.net/specs/openid-connect-core-1_0.html#AggregatedDistributedClaims).
+///
+/// Claims providers are typically distinct from the OpenID Connect provider, so each trusted
+/// claims provider must be registered along with its signing keys via
+/// [`add_trusted_issuer`](ClaimSourceVerifier::add_trusted_issuer). Claims signed by any other
+/// issuer are rejected.
+///
+#[derive(Clone)]
+pub struct ClaimSourceVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    allowed_algs: Option<HashSet<JS>>,
+    trusted_issuers: Vec<JwtClaimsVerifier<'a, JS, JT, JU, K>>,
+    _phantom: PhantomData<JE>,
+}
+impl<'a, JE, JS, JT, JU, K> ClaimSourceVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Initializes a new verifier that doesn't trust any claims providers.
+    ///
+    pub fn new() -> Self {
+        ClaimSourceVerifier {
+            allowed_algs: Some([JS::rsa_sha_256()].iter().cloned().collect()),
+            trusted_issuers: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Trusts claims signed by the claims provider with the given issuer identifier using any of
+    /// the given `signature_keys`.
+    ///
+    pub fn add_trusted_issuer(
+        mut self,
+        issuer: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Self {
+        // Claim source JWTs aren't necessarily issued for any particular audience.
+        let jwt_verifier =
+            JwtClaimsVerifier::new(ClientId::new(String::new()), issuer, signature_keys)
+                .require_audience_match(false);
+        self.trusted_issuers
+            .push(Self::apply_allowed_algs(jwt_verifier, &self.allowed_algs));
+        self
+    }
+
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.allowed_algs = Some(algs.into_iter().collect());
+        self.reapply_allowed_algs()
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.allowed_algs = None;
+        self.reapply_allowed_algs()
+    }
+
+    fn apply_allowed_algs(
+        jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
+        allowed_algs: &Option<HashSet<JS>>,
+    ) -> JwtClaimsVerifier<'a, JS, JT, JU, K> {
+        match allowed_algs {
+            Some(algs) => jwt_verifier.set_allowed_algs(algs.iter().cloned()),
+            None => jwt_verifier.allow_any_alg(),
         }
+    }
+
+    fn reapply_allowed_algs(mut self) -> Self {
+        let allowed_algs = &self.allowed_algs;
+        self.trusted_issuers = self
+            .trusted_issuers
+            .into_iter()
+            .map(|jwt_verifier| Self::apply_allowed_algs(jwt_verifier, allowed_algs))
+            .collect();
+        self
+    }
+
+    // Verifies the given claim source JWT and returns the claims it contains (excluding `iss` and
+    // `aud`).
+    pub(crate) fn verified_claims(
+        &self,
+        jwt: &str,
+    ) -> Result<Map<String, Value>, ClaimsVerificationError> {
+        let jwt: JsonWebToken<JE, JS, JT, ClaimSourceJwtClaims, JsonWebTokenJsonPayloadSerde> =
+            serde_json::from_value(Value::String(jwt.to_string())).map_err(|err| {
+                ClaimsVerificationError::Other(format!("failed to parse claim source JWT: {}", err))
+            })?;
+
+        let issuer = jwt.unverified_payload_ref().issuer().ok_or_else(|| {
+            ClaimsVerificationError::InvalidIssuer("missing issuer claim".to_string())
+        })?;
+        let jwt_verifier = self
+            .trusted_issuers
+            .iter()
+            .find(|jwt_verifier| jwt_verifier.issuer == *issuer)
+            .ok_or_else(|| {
+                ClaimsVerificationError::InvalidIssuer(format!(
+                    "`{}` is not a trusted claims provider",
+                    **issuer
+                ))
+            })?;
+
+        Ok(jwt_verifier.verified_claims(jwt)?.claims)
+    }
+}
+impl<'a, JE, JS, JT, JU, K> Default for ClaimSourceVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///
+/// Signed provider metadata verifier.
+///
+/// Verifies the JWT form of provider metadata (i.e., the `signed_metadata` value described in
+/// [Section 2.1 of RFC 8414](https://datatracker.ietf.org/doc/html/rfc8414#section-2.1), or a
+/// discovery document served as `application/jwt`). The JWT must be signed by the configured trust
+/// anchor (e.g., a federation operator), which is identified by the JWT's `iss` claim and need not
+/// be the OpenID Connect provider itself.
+///
+#[derive(Clone)]
+pub struct SignedMetadataVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
+    _phantom: PhantomData<JE>,
+}
+impl<'a, JE, JS, JT, JU, K> SignedMetadataVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Initializes a new verifier that trusts provider metadata signed by the trust anchor with
+    /// the given issuer identifier using any of the given `signature_keys`.
+    ///
+    pub fn new(trust_anchor: IssuerUrl, signature_keys: JsonWebKeySet<JS, JT, JU, K>) -> Self {
+        SignedMetadataVerifier {
+            // Signed metadata isn't issued for any particular audience.
+            jwt_verifier: JwtClaimsVerifier::new(
+                ClientId::new(String::new()),
+                trust_anchor,
+                signature_keys,
+            )
+            .require_audience_match(false),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
+    }
+
+    // Verifies the given signed metadata JWT and returns the metadata values it contains
+    // (excluding registered JWT claims such as `iss`).
+    pub(crate) fn verified_metadata(
+        &self,
+        jwt: &str,
+    ) -> Result<Map<String, Value>, ClaimsVerificationError> {
+        let jwt: JsonWebToken<JE, JS, JT, ClaimSourceJwtClaims, JsonWebTokenJsonPayloadSerde> =
+            serde_json::from_value(Value::String(jwt.to_string())).map_err(|err| {
+                ClaimsVerificationError::Other(format!("failed to parse signed metadata: {}", err))
+            })?;
+
+        let mut metadata = self.jwt_verifier.verified_claims(jwt)?.claims;
+        for claim in ["exp", "iat", "jti", "nbf", "sub"] {
+            metadata.remove(claim);
+        }
+        Ok(metadata)
+    }
+}
+
+///
+/// User info verifier.
+///
+#[derive(Clone)]
+pub struct UserInfoVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
+    expected_subject: Option<SubjectIdentifier>,
+    _phantom: PhantomData<JE>,
+}
+impl<'a, JE, JS, JT, JU, K> UserInfoVerifier<'a, JE, JS, JT, JU, K>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Instantiates a user info verifier.
+    ///
+    pub fn new(
+        client_id: ClientId,
+        issuer: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+        expected_subject: Option<SubjectIdentifier>,
+    ) -> Self {
+        UserInfoVerifier {
+            jwt_verifier: JwtClaimsVerifier::new(client_id, issuer, signature_keys),
+            expected_subject,
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Verifies signatures using the keys in the given [`JwksCache`] instead of the signature
+    /// keys this verifier was initialized with.
+    ///
+    /// Keys are refetched as needed to support key rotation, as described in the [`JwksCache`]
+    /// documentation.
+    ///
+    pub fn set_jwks_cache<RE>(self, jwks_cache: JwksCache<JS, JT, JU, K, RE>) -> Self
+    where
+        RE: std::error::Error + 'static,
+        JsonWebKeySet<JS, JT, JU, K>: Send + Sync,
+    {
+        self.set_jwks_source(Arc::new(jwks_cache))
+    }
+
+    pub(crate) fn set_jwks_source(
+        mut self,
+        jwks_source: Arc<dyn JwksSource<JS, JT, JU, K>>,
+    ) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_jwks_source(jwks_source);
+        self
+    }
+
+    pub(crate) fn set_signature_keys(
+        mut self,
+        signature_keys: Arc<JsonWebKeySet<JS, JT, JU, K>>,
+    ) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_signature_keys(signature_keys);
+        self
+    }
+
+    pub(crate) fn expected_subject(&self) -> Option<&SubjectIdentifier> {
+        self.expected_subject.as_ref()
+    }
+
+    ///
+    /// Specifies whether the issuer claim must match the expected issuer URL for the provider.
+    ///
+    pub fn require_issuer_match(mut self, iss_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_issuer_match(iss_required);
+        self
+    }
+
+    ///
+    /// Specifies how the issuer claim is compared to the expected issuer URL for the provider.
+    ///
+    /// By default, the issuer claim must match exactly ([`IssuerMatch::Exact`]).
+    ///
+    pub fn set_issuer_match(mut self, issuer_match: IssuerMatch) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_issuer_match(issuer_match);
+        self
+    }
+
+    ///
+    /// Specifies whether the audience claim must match this client's client ID.
+    ///
+    pub fn require_audience_match(mut self, aud_required: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.require_audience_match(aud_required);
+        self
+    }
+
+    ///
+    /// Specifies the key used to decrypt user info responses encrypted using
+    /// [JSON Web Encryption (JWE)](https://tools.ietf.org/html/rfc7516).
+    ///
+    /// Encrypted user info responses contain a nested signed JWT, which is verified as usual once
+    /// decrypted.
+    ///
+    pub fn set_decryption_key<JK, D>(mut self, decryption_key: D) -> Self
+    where
+        JK: JweKeyManagementAlgorithm,
+        D: PrivateDecryptionKey<JE, JK, JT> + 'a + Send + Sync,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_decryption_key(decryption_key);
+        self
+    }
+
+    pub(crate) fn decrypt(&self, jwe: &str) -> Result<String, ClaimsVerificationError> {
+        self.jwt_verifier.decrypt(jwe)
+    }
+
+    pub(crate) fn verified_claims<AC, GC>(
+        &self,
+        user_info_jwt: JsonWebToken<
+            JE,
+            JS,
+            JT,
+            UserInfoClaimsImpl<AC, GC>,
+            JsonWebTokenJsonPayloadSerde,
+        >,
+    ) -> Result<UserInfoClaimsImpl<AC, GC>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+    {
+        let user_info = self.jwt_verifier.verified_claims(user_info_jwt)?;
+        if self
+            .expected_subject
+            .iter()
+            .all(|expected_subject| user_info.standard_claims.sub == *expected_subject)
+        {
+            Ok(user_info)
+        } else {
+            Err(ClaimsVerificationError::InvalidSubject(format!(
+                "expected `{}` (found `{}`)",
+                // This can only happen when self.expected_subject is not None.
+                self.expected_subject.as_ref().unwrap().as_str(),
+                user_info.standard_claims.sub.as_str()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use oauth2::{ClientId, ClientSecret};
+    use sha2::{Digest, Sha256};
+
+    use super::{
+        AudiencesClaim, ClaimsVerificationError, IssuerClaim, IssuerMatch, JsonWebTokenHeader,
+        JwtClaimsVerifier, NonceCheck, NonceVerifier, SignatureVerificationError,
+        SignedNonceVerifier, SubjectIdentifier,
+    };
+    use crate::core::{
+        CoreGenderClaim, CoreHmacKey, CoreIdToken, CoreIdTokenClaims, CoreIdTokenVerifier,
+        CoreJsonWebKey, CoreJsonWebKeySet, CoreJsonWebKeyType, CoreJsonWebKeyUse,
+        CoreJweContentEncryptionAlgorithm, CoreJwsSigningAlgorithm, CoreMultiIssuerVerifier,
+        CoreRsaPrivateDecryptionKey, CoreRsaPrivateSigningKey, CoreSymmetricDecryptionKey,
+        CoreUserInfoClaims, CoreUserInfoJsonWebToken, CoreUserInfoVerifier,
+    };
+    use crate::jwt::tests::{
+        encrypt_compact_jwe, encrypt_compact_jwe_symmetric, TEST_EC_PUB_KEY_P256,
+        TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY,
+    };
+    use crate::jwt::{JsonWebToken, JsonWebTokenJsonPayloadSerde};
+    use crate::types::helpers::timestamp_to_utc;
+    use crate::types::Base64UrlEncodedBytes;
+    use crate::types::Timestamp;
+    use crate::{
+        AccessToken, AdditionalClaims, Audience, AuthenticationContextClass,
+        AuthenticationMethodReference, AuthorizationCode, DecryptionError, EndUserName, IdToken,
+        IdTokenClaims, IssuerUrl, JsonWebKey, JsonWebKeyId, Nonce, Seconds, StandardClaims,
+        UserInfoError,
+    };
+
+    type CoreJsonWebTokenHeader = JsonWebTokenHeader<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+    >;
+
+    type CoreJwtClaimsVerifier<'a> = JwtClaimsVerifier<
+        'a,
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+        CoreJsonWebKeyUse,
+        CoreJsonWebKey,
+    >;
+
+    fn assert_unsupported<T>(result: Result<T, ClaimsVerificationError>, expected_substr: &str) {
+        match result {
+            Err(ClaimsVerificationError::Unsupported(msg)) => {
+                assert!(msg.contains(expected_substr))
+            }
+            Err(err) => panic!("unexpected error: {:?}", err),
+            Ok(_) => panic!("validation should fail"),
+        }
+    }
+
+    // Returns ID token claims for subject `subject`, issued at 1544928549 and expiring an hour
+    // later.
+    fn new_id_token_claims<AC>(
+        issuer: &str,
+        audiences: &[&str],
+        additional_claims: AC,
+    ) -> IdTokenClaims<AC, CoreGenderClaim>
+    where
+        AC: AdditionalClaims,
+    {
+        IdTokenClaims::new(
+            IssuerUrl::new(issuer.to_string()).unwrap(),
+            audiences
+                .iter()
+                .map(|aud| Audience::new(aud.to_string()))
+                .collect(),
+            Utc.timestamp_opt(1544932149, 0).single().unwrap(),
+            Utc.timestamp_opt(1544928549, 0).single().unwrap(),
+            StandardClaims::new(SubjectIdentifier::new("subject".to_string())),
+            additional_claims,
+        )
+    }
+
+    // Signs the given claims with `TEST_RSA_PRIV_KEY` using RS256.
+    fn sign_id_token<AC>(
+        claims: IdTokenClaims<AC, CoreGenderClaim>,
+    ) -> IdToken<
+        AC,
+        CoreGenderClaim,
+        CoreJweContentEncryptionAlgorithm,
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+    >
+    where
+        AC: AdditionalClaims,
+    {
+        IdToken::new(
+            claims,
+            &CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap(),
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_jose_header() {
+        // Unexpected JWT type.
+        assert_unsupported(
+            CoreJwtClaimsVerifier::validate_jose_header(
+                &serde_json::from_str::<CoreJsonWebTokenHeader>(
+                    "{\"alg\":\"RS256\",\"typ\":\"NOT_A_JWT\"}",
+                )
+                .expect("failed to deserialize"),
+                &["JWT"],
+            ),
+            "unsupported JWT type",
+        );
+
+        // Nested JWTs.
+        assert_unsupported(
+            CoreJwtClaimsVerifier::validate_jose_header(
+                &serde_json::from_str::<CoreJsonWebTokenHeader>(
+                    "{\"alg\":\"RS256\",\"cty\":\"JWT\"}",
+                )
+                .expect("failed to deserialize"),
+                &["JWT"],
+            ),
+            "nested JWT",
+        );
+        assert_unsupported(
+            CoreJwtClaimsVerifier::validate_jose_header(
+                &serde_json::from_str::<CoreJsonWebTokenHeader>(
+                    "{\"alg\":\"RS256\",\"cty\":\"NOT_A_JWT\"}",
+                )
+                .expect("failed to deserialize"),
+                &["JWT"],
+            ),
+            "unsupported JWT content type",
+        );
+
+        // Critical fields. Adapted from https://tools.ietf.org/html/rfc7515#appendix-E
+        assert_unsupported(
+            CoreJwtClaimsVerifier::validate_jose_header(
+                &serde_json::from_str::<CoreJsonWebTokenHeader>(
+                    "{\
+                     \"alg\":\"RS256\",\
+                     \"crit\":[\"http://example.invalid/UNDEFINED\"],\
+                     \"http://example.invalid/UNDEFINED\":true\
+                     }",
+                )
+                .expect("failed to deserialize"),
+                &["JWT"],
+            ),
+            "critical JWT header fields are unsupported",
+        );
+
+        // Explicitly typed JWTs (e.g., logout tokens).
+        let logout_header = serde_json::from_str::<CoreJsonWebTokenHeader>(
+            "{\"alg\":\"RS256\",\"typ\":\"logout+jwt\"}",
+        )
+        .expect("failed to deserialize");
+        assert_unsupported(
+            CoreJwtClaimsVerifier::validate_jose_header(&logout_header, &["JWT"]),
+            "unsupported JWT type",
+        );
+        CoreJwtClaimsVerifier::validate_jose_header(&logout_header, &["JWT", "logout+jwt"])
+            .expect("logout+jwt type should be accepted");
+        CoreJwtClaimsVerifier::validate_jose_header(
+            &serde_json::from_str::<CoreJsonWebTokenHeader>(
+                "{\"alg\":\"RS256\",\"typ\":\"application/logout+jwt\"}",
+            )
+            .expect("failed to deserialize"),
+            &["JWT", "logout+jwt"],
+        )
+        .expect("application/logout+jwt type should be accepted");
+    }
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    struct TestClaims {
+        aud: Option<Vec<Audience>>,
+        iss: Option<IssuerUrl>,
+        payload: String,
+    }
+    impl AudiencesClaim for TestClaims {
+        fn audiences(&self) -> Option<&Vec<Audience>> {
+            self.aud.as_ref()
+        }
+    }
+    impl IssuerClaim for TestClaims {
+        fn issuer(&self) -> Option<&IssuerUrl> {
+            self.iss.as_ref()
+        }
+    }
+    type TestClaimsJsonWebToken = JsonWebToken<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+        TestClaims,
+        JsonWebTokenJsonPayloadSerde,
+    >;
+
+    #[test]
+    fn test_jwt_verified_claims() {
+        let rsa_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .expect("deserialization failed");
+
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let verifier = CoreJwtClaimsVerifier::new(
+            client_id.clone(),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![rsa_key.clone()]),
+        );
+
+        // Invalid JOSE header.
+        assert_unsupported(
+            verifier.verified_claims(
+                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                    "eyJhbGciOiJBMjU2R0NNIiwiY3R5IjoiSldUIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Im\
+                     h0dHBzOi8vZXhhbXBsZS5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
+                        .to_string(),
+                )).expect("failed to deserialize"),
+            ),
+            "nested JWT",
+        );
+
+        // JWE-encrypted JWT.
+        assert_unsupported(
+            verifier.verified_claims(
+                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                    "eyJhbGciOiJBMjU2R0NNIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbX\
+                     BsZS5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
+                        .to_string(),
+                )).expect("failed to deserialize"),
+            ),
+            "JWE encryption",
+        );
+
+        // Wrong issuer.
+        match verifier.verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vYXR0YWNrZXIuY\
+                 29tIiwicGF5bG9hZCI6ImhlbGxvIHdvcmxkIn0.YmFkX2hhc2g"
+                    .to_string(),
+            )).expect("failed to deserialize"),
+        ) {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {},
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Missing issuer.
+        match verifier.verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.\
+                 YmFkX2hhc2g"
+                    .to_string(),
+            )).expect("failed to deserialize"),
+        ) {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {},
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Ignore missing issuer.
+        verifier
+            .clone()
+            .require_issuer_match(false)
+            .verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.\
+                 nv09al63NNDfb8cF3IozegXKbPaUC08zknRPKmQ5qKgXv80hjVxknkpRz7BxocB3JYTBjhYd0gyN9wAuJj\
+                 byZ1QaUC14HOB83awAGbehy5yFLkLadTfPT7-siBCvE2V7AF73a_21YvwdkKmJ-RaKWHzFnG8CDmioma3X\
+                 cWyrsdRLgvUkrWllajLRo8DCIXQ8OuZo1_o4n17PSlPxSkhKIrgaWCvG6tan40Y_1DZOFv47bx4hQUGd-J\
+                 h2aEjiwn65WV3M_Xb2vQMP7VgYNVaNlfxzpL4yDASItbPMWaXBt3ZUa_IOGoSx2GMnPkrQ4xp56qUth6U7\
+                 esWPqRSqqolnHg"
+                    .to_string(),
+            )).expect("failed to deserialize"),
+        ).expect("verification should succeed");
+
+        // Wrong audience.
+        match verifier.verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsib3RoZXJfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
+                    .to_string(),
+            )).expect("failed to deserialize"),
+        ) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {},
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Missing audience.
+        match verifier.verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJpc3MiOiJodHRwczovL2V4YW1wbGUuY29tIiwicGF5bG9hZCI6ImhlbGxvI\
+                 HdvcmxkIn0.YmFkX2hhc2g"
+                    .to_string(),
+            )).expect("failed to deserialize"),
+        ) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {},
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Ignore missing audience.
+        verifier
+            .clone()
+            .require_audience_match(false)
+            .verified_claims(
+                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                    "eyJhbGciOiJSUzI1NiJ9.eyJpc3MiOiJodHRwczovL2V4YW1wbGUuY29tIiwicGF5bG9hZCI6Imhlb\
+                     GxvIHdvcmxkIn0.lP-Z_zGPNoKIbLQsnrZc2LAc5qJrKyb7t07ZtJUKVhcwHiCUou4bBhq5RHlElCh\
+                     0ElRRP6I25lp6UszkRvIC46UV3GVze0x73kVkHSvCVI7MO75LbL9BRqrm5b4CN2zCiFBY8-EwTXnJd\
+                     Ri0d_U8K29TV24L2I-Z5ZILebwUue1N59AGDjx2yYLFx5NOw3TUsPyscG62aZAT321pL_jcYwTWTWw\
+                     2FYm07zguwx-PUTZwGXlJiOgXQqRIbY_1bS3I_D8UWsmEB3DmV0f9z-iklgIPFawa4wHaE-hpzBAEx\
+                     pSieyOavA5pl0Se3XRYA-CkdDVgzG0Pt4IdnxFanfUXTw"
+                        .to_string(),
+                )).expect("failed to deserialize"),
+            ).expect("verification should succeed");
+
+        // Multiple audiences, where one is a match (default = reject)
+        match verifier.verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsiYXVkMSIsIm15X2NsaWVudCIsImF1ZDIiXSwiaXNzIjoia\
+                 HR0cHM6Ly9leGFtcGxlLmNvbSIsInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.N9ibisEe0kKLe1GDWM\
+                 ON3PmYqbL73dag-loM8pjKJNinF9SB7n4JuSu4FrNkeW4F1Cz8MIbLuWfKvDa_4v_3FstMA3GODZWH\
+                 BVIiuNFay2ovCfGFyykwe47dF_47g_OM5AkJc_teE5MN8lPh9V5zYCy3ON3zZ3acFPJMOPTdbU56xD\
+                 eFe7lil6DmV4JU9A52t5ZkJILFaIuxxXJUIDmqpPTvHkggh_QOj9C2US9bgg5b543JwT4j-HbDp51L\
+                 dDB4k3azOssT1ddtoAuuDOctnraMKUtqffJXexxfwA1uM6EIofSrK5v11xwgTciL9xDXAvav_G2buP\
+                 ol1bjGLa2t0Q"
+                    .to_string(),
+            ))
+            .expect("failed to deserialize"),
+        ) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Multiple audiences, where one is a match (allowed)
+        verifier
+            .clone()
+            .set_other_audience_verifier_fn(|aud| **aud == "aud1" || **aud == "aud2")
+            .verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsiYXVkMSIsIm15X2NsaWVudCIsImF1ZDIiXSwiaXNzIjoia\
+                 HR0cHM6Ly9leGFtcGxlLmNvbSIsInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.N9ibisEe0kKLe1GDWM\
+                 ON3PmYqbL73dag-loM8pjKJNinF9SB7n4JuSu4FrNkeW4F1Cz8MIbLuWfKvDa_4v_3FstMA3GODZWH\
+                 BVIiuNFay2ovCfGFyykwe47dF_47g_OM5AkJc_teE5MN8lPh9V5zYCy3ON3zZ3acFPJMOPTdbU56xD\
+                 eFe7lil6DmV4JU9A52t5ZkJILFaIuxxXJUIDmqpPTvHkggh_QOj9C2US9bgg5b543JwT4j-HbDp51L\
+                 dDB4k3azOssT1ddtoAuuDOctnraMKUtqffJXexxfwA1uM6EIofSrK5v11xwgTciL9xDXAvav_G2buP\
+                 ol1bjGLa2t0Q"
+                    .to_string(),
+            )).expect("failed to deserialize"),
+        ).expect("verification should succeed");
+
+        // Multiple audiences, where none is a match
+        match verifier.verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsiYXVkMSIsImF1ZDIiXSwiaXNzIjoiaHR0cHM6Ly9leGFtcGxlL\
+                 mNvbSIsInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.YmFkX2hhc2g"
+                    .to_string(),
+            )).expect("failed to deserialize"),
+        ) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {},
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Disable signature check.
+        verifier
+            .clone()
+            .require_signature_check(false)
+            .verified_claims(
+                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                     S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
+                        .to_string(),
+                )).expect("failed to deserialize"),
+            ).expect("verification should succeed");
+
+        // "none" algorithm (unsigned JWT), which is rejected regardless of the verifier's
+        // configuration.
+        let unsigned_jwt =
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJub25lIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ."
+                    .to_string(),
+            ))
+            .expect("failed to deserialize");
+        for none_verifier in [
+            verifier.clone(),
+            verifier.clone().allow_any_alg(),
+            verifier.clone().require_signature_check(false),
+        ] {
+            match none_verifier.verified_claims(unsigned_jwt.clone()) {
+                Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::NoneAlgorithmRejected,
+                )) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        let valid_rs256_jwt =
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.UZ7vmAsDmOBzeB6e2_0POUfyhMRZKM6WSKz3\
+                 jB2QdmO-eZ9605EzhkJufJQ8515ryWnHv-gUHtZHQi3zilrzhBwvE2cVP83Gv2XIL1EKaMMmfISeEB\
+                 ShWez_FvqxN_bamh5yTROhWmoZTmof-MweBCHgINcsEd7K4e_BHHgq3aaRBpvSFlL_z4l_1NwNcTBo\
+                 kqjNScKZITk42AbsSuGR39L94BWLhz6WXQZ_Sn6R1Ro6roOm1b7E82jJiQEtlseQiCCvPR2JJ6LgW6\
+                 XTMzQ0vCqSh1A7U_IBDsjY_yag8_X3xxFh2URCtHJ47ZSjqfv6hq7OAq8tmVecOVgfIvABOg"
+                    .to_string(),
+            ))
+            .expect("failed to deserialize");
+        // Default algs + RS256 -> allowed
+        verifier
+            .verified_claims(valid_rs256_jwt.clone())
+            .expect("verification should succeed");
+
+        let verifier_with_client_secret = CoreJwtClaimsVerifier::new(
+            client_id.clone(),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .set_client_secret(ClientSecret::new("my_secret".to_string()));
+        let valid_hs256_jwt =
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJIUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.dTXvSWen74_rC4oiWw0ziLZNe4KZk8Jw2VZe\
+                 N6vLCDo"
+                    .to_string(),
+            ))
+            .expect("failed to deserialize");
+
+        // Default algs + HS256 -> disallowed
+        match verifier_with_client_secret.verified_claims(valid_hs256_jwt.clone()) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::DisallowedAlg(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // none algs + RS256 -> allowed
+        verifier
+            .clone()
+            .allow_any_alg()
+            .verified_claims(valid_rs256_jwt.clone())
+            .expect("verification should succeed");
+
+        // none algs + HS256 -> allowed
+        verifier_with_client_secret
+            .clone()
+            .allow_any_alg()
+            .verified_claims(valid_hs256_jwt.clone())
+            .expect("verification should succeed");
+
+        // none algs + none -> disallowed
+        match verifier.clone().allow_any_alg().verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJub25lIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ."
+                    .to_string(),
+            ))
+            .expect("failed to deserialize"),
+        ) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoneAlgorithmRejected,
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // HS256 + no client secret -> disallowed
+        match verifier
+            .clone()
+            .allow_any_alg()
+            .verified_claims(valid_hs256_jwt.clone())
+        {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::DisallowedAlg(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // HS256 + valid signature
+        verifier_with_client_secret
+            .clone()
+            .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+            .verified_claims(valid_hs256_jwt)
+            .expect("verification should succeed");
+
+        // HS256 + invalid signature
+        match verifier_with_client_secret
+            .clone()
+            .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+            .verified_claims(
+                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                    "eyJhbGciOiJIUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                     S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.dTXvSWen74_rC4oiWw0ziLZNe4KZk8Jw2VZe\
+                     N6vLCEo"
+                        .to_string(),
+                )).expect("failed to deserialize")
+            )
+        {
+            Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::CryptoError(_),
+                )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // No public keys
+        match CoreJwtClaimsVerifier::new(
+            client_id.clone(),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .verified_claims(valid_rs256_jwt.clone())
+        {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey,
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let kid = JsonWebKeyId::new("bilbo.baggins@hobbiton.example".to_string());
+        let n = Base64UrlEncodedBytes::new(vec![
+            159, 129, 15, 180, 3, 130, 115, 208, 37, 145, 228, 7, 63, 49, 210, 182, 0, 27, 130,
+            206, 219, 77, 146, 240, 80, 22, 93, 71, 207, 202, 184, 163, 196, 28, 183, 120, 172,
+            117, 83, 121, 63, 142, 249, 117, 118, 141, 26, 35, 116, 216, 113, 37, 100, 195, 188,
+            215, 123, 158, 164, 52, 84, 72, 153, 64, 124, 255, 0, 153, 146, 10, 147, 26, 36, 196,
+            65, 72, 82, 171, 41, 189, 176, 169, 92, 6, 83, 243, 108, 96, 230, 11, 249, 11, 98, 88,
+            221, 165, 111, 55, 4, 123, 165, 194, 209, 208, 41, 175, 156, 157, 64, 186, 199, 170,
+            65, 199, 138, 13, 209, 6, 138, 221, 105, 158, 128, 143, 234, 1, 30, 161, 68, 29, 138,
+            79, 123, 180, 233, 123, 227, 159, 85, 241, 221, 212, 78, 156, 75, 163, 53, 21, 151, 3,
+            212, 211, 75, 96, 62, 101, 20, 122, 79, 35, 214, 211, 192, 153, 108, 117, 237, 238,
+            132, 106, 130, 209, 144, 174, 16, 120, 60, 150, 28, 240, 56, 122, 237, 33, 6, 210, 208,
+            85, 91, 111, 217, 55, 250, 213, 83, 83, 135, 224, 255, 114, 255, 190, 120, 148, 20, 2,
+            176, 184, 34, 234, 42, 116, 182, 5, 140, 29, 171, 249, 179, 74, 118, 203, 99, 184, 127,
+            170, 44, 104, 71, 184, 226, 131, 127, 255, 145, 24, 110, 107, 28, 20, 145, 28, 249,
+            137, 168, 144, 146, 168, 28, 230, 1, 221, 172, 211, 249, 207,
+        ]);
+        let e = Base64UrlEncodedBytes::new(vec![1, 0, 1]);
+
+        // Wrong key type (symmetric key)
+        match CoreJwtClaimsVerifier::new(
+            client_id.clone(),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![CoreJsonWebKey {
+                kty: CoreJsonWebKeyType::Symmetric,
+                use_: Some(CoreJsonWebKeyUse::Signature),
+                kid: Some(kid.clone()),
+                n: None,
+                e: None,
+                k: Some(Base64UrlEncodedBytes::new(vec![1, 2, 3, 4])),
+                crv: None,
+                x: None,
+                y: None,
+                d: None,
+                key_ops: None,
+                #[cfg(feature = "jwk-alg")]
+                alg: None,
+            }]),
+        )
+        .verified_claims(valid_rs256_jwt.clone())
+        {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey,
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Correct public key, but with signing disallowed
+        match CoreJwtClaimsVerifier::new(
+            client_id.clone(),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![CoreJsonWebKey {
+                kty: CoreJsonWebKeyType::RSA,
+                use_: Some(CoreJsonWebKeyUse::Encryption),
+                kid: Some(kid.clone()),
+                n: Some(n.clone()),
+                e: Some(e.clone()),
+                k: None,
+                crv: None,
+                x: None,
+                y: None,
+                d: None,
+                key_ops: None,
+                #[cfg(feature = "jwk-alg")]
+                alg: None,
+            }]),
+        )
+        .verified_claims(valid_rs256_jwt.clone())
+        {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey,
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let sig_key = CoreJsonWebKey {
+            kty: CoreJsonWebKeyType::RSA,
+            use_: None,
+            kid: Some(kid),
+            n: Some(n),
+            e: Some(e),
+            k: None,
+            crv: None,
+            x: None,
+            y: None,
+            d: None,
+            key_ops: Some(vec!["verify".to_string()]),
+            #[cfg(feature = "jwk-alg")]
+            alg: None,
+        };
+        let enc_key = CoreJsonWebKey {
+            key_ops: Some(vec!["encrypt".to_string(), "wrapKey".to_string()]),
+            ..sig_key.clone()
+        };
+
+        // Correct public key, but with signature verification excluded from its key operations
+        match CoreJwtClaimsVerifier::new(
+            client_id.clone(),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![enc_key.clone()]),
+        )
+        .verified_claims(valid_rs256_jwt.clone())
+        {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey,
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Mixed signing and encryption keys sharing a key ID: only the signing key is considered
+        CoreJwtClaimsVerifier::new(
+            client_id.clone(),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![
+                CoreJsonWebKey {
+                    use_: Some(CoreJsonWebKeyUse::Encryption),
+                    key_ops: None,
+                    ..sig_key.clone()
+                },
+                enc_key,
+                sig_key,
+            ]),
+        )
+        .verified_claims(valid_rs256_jwt.clone())
+        .expect("verification should succeed");
+
+        // Wrong key ID
+        match verifier.verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiIsImtpZCI6Indyb25nX2tleSJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6I\
+                 mh0dHBzOi8vZXhhbXBsZS5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.lVLomyIyO8WmyS1VZWPu\
+                 cGhRTUyK9RCw90fJC5CfDWUCgt1CBn-aP_ieWWBGfjb4ccR4dl57OYxdLl0Day8QN5pTCBud9QKpQ0rKQX\
+                 K8eBlOW8uSosx8q5pwU_bRyy-XuKJiPlDCOwTEHOp_hOgZFGjoN27MH3Xm8kc0iT3PgyqQ46-wsqHY9S02\
+                 hdJORX7vqYwQLZF8_k_L8K0IG_dC-1Co0g5oAf37oVSdl8hE-ScQ9K-AiSpS-cGYyldbMhyKNDL3ry2cuI\
+                 EUgYSIznkVFuM7RrEdNK222z5PF11ijYx-TM7BIDggbcIyJm-UqpmvVaJImmj5FNkMzuHYznLtdg"
+                    .to_string(),
+            )).expect("failed to deserialize")
+        ) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::NoMatchingKey,
+                )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Client secret + public key
+        verifier
+            .clone()
+            .set_client_secret(ClientSecret::new("my_secret".to_string()))
+            .verified_claims(valid_rs256_jwt.clone())
+            .expect("verification should succeed");
+
+        // Multiple matching public keys: no KID specified
+        match CoreJwtClaimsVerifier::new(
+            client_id.clone(),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![rsa_key.clone(), rsa_key.clone()]),
+        )
+        .verified_claims(valid_rs256_jwt.clone())
+        {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::AmbiguousKeyId(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Multiple matching public keys: KID specified
+        match CoreJwtClaimsVerifier::new(
+            client_id,
+            issuer,
+            CoreJsonWebKeySet::new(vec![rsa_key.clone(), rsa_key]),
+        ).verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiIsImtpZCI6ImJpbGJvLmJhZ2dpbnNAaG9iYml0b24uZXhhbXBsZSJ9.eyJhdWQiO\
+                 lsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZS5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29\
+                 ybGQifQ.jH0v2fQGvH2MD0jn5pQP6W6AF5rJlizyofdyRUIt7E3GraGA1LYDiLAVIfhST3uwJopP-TgtBk\
+                 zc-zyJSvgTR63S8iI1YlHypItpx7r4I9ydzo8GSN5RrZudcU2esY4uEnLbVl17ZVNu4IyTExeKJ0sPM0Hj\
+                 qkOA4XaP2cJwsK-bookNHSA8NRE6adRMrHAKJbor5jrGjpkZAKHbnQFK-wu-nEV_OjS9jpN_FboRZVcDTZ\
+                 GFzeFbqFqHdRn6UWPFnVpVnUhih16UjNH1om6gwc0uFoPWTDxJlXQCFbHMhZtgCbUkXQBH7twPMc4YUziw\
+                 S8GIRKCcXjdrP5oyxmcitQ"
+                    .to_string(),
+            )).expect("failed to deserialize")
+        ) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::AmbiguousKeyId(_),
+                )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // RS256 + valid signature
+        verifier
+            .verified_claims(valid_rs256_jwt)
+            .expect("verification should succeed");
+
+        // RS256 + invalid signature
+        match verifier.verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZS5jb\
+                 20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
+                    .to_string(),
+            )).expect("failed to deserialize"),
+        ) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::CryptoError(_),
+                )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_alg_key_type_confusion() {
+        use hmac::Mac;
+
+        let rsa_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .expect("deserialization failed");
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let verifier = CoreJwtClaimsVerifier::new(
+            client_id,
+            issuer,
+            CoreJsonWebKeySet::new(vec![rsa_key.clone()]),
+        )
+        .allow_any_alg();
+
+        // An attacker forges an HS256 token referencing the provider's RSA key, using the
+        // (public) RSA key as the HMAC secret.
+        let forge = |hmac_secret: &[u8]| {
+            let signing_input = format!(
+                "{}.{}",
+                base64::encode_config(
+                    "{\"alg\":\"HS256\",\"kid\":\"bilbo.baggins@hobbiton.example\"}",
+                    base64::URL_SAFE_NO_PAD
+                ),
+                base64::encode_config(
+                    "{\"aud\":[\"my_client\"],\"iss\":\"https://example.com\",\
+                     \"payload\":\"hello world\"}",
+                    base64::URL_SAFE_NO_PAD
+                ),
+            );
+            let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(hmac_secret).unwrap();
+            mac.update(signing_input.as_bytes());
+            let signature = mac.finalize().into_bytes().to_vec();
+            let jwt = serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                format!(
+                    "{}.{}",
+                    signing_input,
+                    base64::encode_config(&signature, base64::URL_SAFE_NO_PAD)
+                ),
+            ))
+            .expect("failed to deserialize");
+            (jwt, signing_input, signature)
+        };
+        let rsa_modulus = rsa_key.n.as_ref().unwrap().to_vec();
+        for (forged_jwt, signing_input, signature) in
+            [forge(TEST_RSA_PUB_KEY.as_bytes()), forge(&rsa_modulus)]
+        {
+            // The JWK set is never consulted for HMAC algorithms, so public clients reject the
+            // token outright...
+            match verifier.verified_claims(forged_jwt.clone()) {
+                Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::DisallowedAlg(_),
+                )) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+            // ...and confidential clients only accept MACs computed with the client secret.
+            match verifier
+                .clone()
+                .set_client_secret(ClientSecret::new("my_secret".to_string()))
+                .verified_claims(forged_jwt.clone())
+            {
+                Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::CryptoError(_),
+                )) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            // The RSA key itself refuses to verify an HMAC.
+            match rsa_key.verify_signature(
+                &CoreJwsSigningAlgorithm::HmacSha256,
+                signing_input.as_bytes(),
+                &signature,
+            ) {
+                Err(SignatureVerificationError::KeyTypeMismatch(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        // Conversely, an RS256 token whose key ID identifies a symmetric key is rejected with a
+        // distinct error rather than treated as a missing key.
+        let symmetric_key = CoreJsonWebKey::new_symmetric(b"my_secret".to_vec());
+        let symmetric_key = CoreJsonWebKey {
+            kid: rsa_key.kid.clone(),
+            use_: Some(CoreJsonWebKeyUse::Signature),
+            ..symmetric_key
+        };
+        let rs256_jwt = serde_json::from_value::<TestClaimsJsonWebToken>(
+            serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiIsImtpZCI6ImJpbGJvLmJhZ2dpbnNAaG9iYml0b24uZXhhbXBsZSJ9.eyJhdWQiO\
+                 lsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZS5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29\
+                 ybGQifQ.jH0v2fQGvH2MD0jn5pQP6W6AF5rJlizyofdyRUIt7E3GraGA1LYDiLAVIfhST3uwJopP-TgtBk\
+                 zc-zyJSvgTR63S8iI1YlHypItpx7r4I9ydzo8GSN5RrZudcU2esY4uEnLbVl17ZVNu4IyTExeKJ0sPM0Hj\
+                 qkOA4XaP2cJwsK-bookNHSA8NRE6adRMrHAKJbor5jrGjpkZAKHbnQFK-wu-nEV_OjS9jpN_FboRZVcDTZ\
+                 GFzeFbqFqHdRn6UWPFnVpVnUhih16UjNH1om6gwc0uFoPWTDxJlXQCFbHMhZtgCbUkXQBH7twPMc4YUziw\
+                 S8GIRKCcXjdrP5oyxmcitQ"
+                    .to_string(),
+            ),
+        )
+        .expect("failed to deserialize");
+        match CoreJwtClaimsVerifier::new(
+            ClientId::new("my_client".to_string()),
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![symmetric_key]),
+        )
+        .verified_claims(rs256_jwt.clone())
+        {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::KeyTypeMismatch(msg),
+            )) => assert_eq!(
+                msg,
+                "algorithm `RS256` cannot be used with key `bilbo.baggins@hobbiton.example` of \
+                 type `oct`"
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        CoreJwtClaimsVerifier::new(
+            ClientId::new("my_client".to_string()),
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![rsa_key]),
+        )
+        .verified_claims(rs256_jwt)
+        .expect("verification should succeed");
+    }
+
+    type CoreIdTokenJwt = JsonWebToken<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+        CoreIdTokenClaims,
+        JsonWebTokenJsonPayloadSerde,
+    >;
+
+    #[test]
+    fn test_id_token_verified_claims() {
+        let rsa_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .expect("deserialization failed");
+
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let mock_current_time = AtomicUsize::new(1544932149);
+        let mock_is_valid_issue_time = AtomicBool::new(true);
+        // Extra scope needed to ensure closures are destroyed before the values they borrow.
+        {
+            let public_client_verifier = CoreIdTokenVerifier::new_public_client(
+                client_id.clone(),
+                issuer.clone(),
+                CoreJsonWebKeySet::new(vec![rsa_key.clone()]),
+            )
+            .set_time_fn(|| {
+                timestamp_to_utc(&Timestamp::Seconds(
+                    mock_current_time.load(Ordering::Relaxed).into(),
+                ))
+                .unwrap()
+            })
+            .set_issue_time_verifier_fn(|_| {
+                if mock_is_valid_issue_time.load(Ordering::Relaxed) {
+                    Ok(())
+                } else {
+                    Err("Invalid iat claim".to_string())
+                }
+            });
+
+            let insecure_verifier = CoreIdTokenVerifier::new_insecure_without_verification()
+                .set_time_fn(|| {
+                    timestamp_to_utc(&Timestamp::Seconds(
+                        mock_current_time.load(Ordering::Relaxed).into(),
+                    ))
+                    .unwrap()
+                });
+
+            // This JWTs below have an issue time of 1544928549 and an expiration time of 1544932149.
+
+            let test_jwt_without_nonce =
+                serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
+                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                     S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDl9.nN\
+                     aTxNwclnTHd1Q9POkddm5wB1w3wJ-gwQWHomhimttk3SWQTLhxI0SSjWrHahGxlfkjufJlSyt-t_VO\
+                     SdcROvIYZTDznDfFZz3oSOev-p9XiZ-EZTS-U6N11Y923sDQjbTMeukz1F3ZFEfn5Mv2xjdEoJccCe\
+                     7SaGuDmVqMqTLXMtsw9NCE_KDd0oKSwDzbJIBBPEfG3JjbKg0Dln7ENHg9wzoNFQzPXrkKzjneBgD3\
+                     vuwFCV5y-e8xUBdLaLZF1kdkDZJIA48uRROLlWjsM8pEptosA5QK07luQCZNqcaZWEczoGXeQs8PyA\
+                     zkNV7JEmti3bJnWSN-ud4cFU0LiQ"
+                            .to_string(),
+                ))
+                .expect("failed to deserialize");
+
+            // Invalid JWT claims
+            match public_client_verifier.verified_claims(
+                &serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
+                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vYXR0YWNrZ\
+                     XIuY29tIiwic3ViIjoic3ViamVjdCIsImV4cCI6MTU0NDkzMjE0OSwiaWF0IjoxNTQ0OTI4NTQ5LCJ\
+                     ub25jZSI6InRoZV9ub25jZSIsImFjciI6InRoZV9hY3IifQ.Pkicxk0dTU5BkSxgqTON6lE7A7ir3l\
+                     aADRyoeRoCNDX3AOx7BXCbfzbda6HJiPskN2nu56w0q-0OdkDSIHls-2xTUlLEJv2Bv0BLYwV5ZVJ8\
+                     hoc-rTd0_oLUb5NzyD80RyVByjVMK8bh6cwysTnr8QDxsEiFZbFo3mVJob2yjPZnNOdcNJWPcVVueP\
+                     8vqMJnx5kHih1gKZpWj_dMN9b2AW6zVLOInW3Ox__gx6fsFFz7rjxItG-PTY_OQMzthqeHUyq4o9y7\
+                     Jv8mB_jFkTZGVKHTPpObHV-qptJ_rnlwvF_mP5GARBLng-4Yd7nmSr31onYL48QDjGOrwPqQ-IyaCQ"
+                        .to_string(),
+                ))
+                    .expect("failed to deserialize"), |_: Option<&Nonce>| Ok(())) {
+                Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            // TODO: disallowed algs
+
+            // Expired token (beyond the default clock skew leeway)
+            mock_current_time.store(1544928549 + 3600 + 60, Ordering::Relaxed);
+            match public_client_verifier
+                .verified_claims(&test_jwt_without_nonce, |_: Option<&Nonce>| Ok(()))
+            {
+                Err(ClaimsVerificationError::Expired(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+            mock_current_time.store(1544928549 + 1, Ordering::Relaxed);
+
+            // Invalid issue time
+            mock_is_valid_issue_time.store(false, Ordering::Relaxed);
+            match public_client_verifier
+                .verified_claims(&test_jwt_without_nonce, |_: Option<&Nonce>| Ok(()))
+            {
+                Err(ClaimsVerificationError::Expired(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+            mock_is_valid_issue_time.store(true, Ordering::Relaxed);
+
+            let valid_nonce = Nonce::new("the_nonce".to_string());
+
+            // Successful verification w/o checking nonce
+            public_client_verifier
+                .verified_claims(&test_jwt_without_nonce, |_: Option<&Nonce>| Ok(()))
+                .expect("verification should succeed");
+
+            // Missing nonce
+            match public_client_verifier.verified_claims(&test_jwt_without_nonce, &valid_nonce) {
+                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            // Missing nonce w/ closure
+            match public_client_verifier.verified_claims(
+                &test_jwt_without_nonce,
+                |nonce: Option<&Nonce>| {
+                    if nonce.iter().any(|n| n.secret() == valid_nonce.secret()) {
+                        Ok(())
+                    } else {
+                        Err("invalid nonce".to_string())
+                    }
+                },
+            ) {
+                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            let test_jwt_with_nonce =
+                serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
+                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                     S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDksIm5\
+                     vbmNlIjoidGhlX25vbmNlIiwiYWNyIjoidGhlX2FjciIsImF1dGhfdGltZSI6MTU0NDkyODU0OH0.W\
+                     XA7SS9aMh_6rvBEgQce5D2J84OqphmmnCLGgEKRTN5G-UuQTNOBp8VS5_4f3xgzMEEMvGJJauJoALk\
+                     muUeHB-N_ESrkmB3tgDzBSYBa7kuYPHUPYpdjZM2UVolqI9RYyHaWwKjL_Io5YyAazB5lH5ibPaiBl\
+                     UNKGs3cmVsEB22UGMFKM6cek7GinrHQe_aJQsMU839-c2zzlEyFSeI8QBphQtG6AN82IPkNRv8QWmw\
+                     ZjUiB5a-W73Z3gURYMNs7f32BjAUNoJzW0Qj34vzD2djoSHhltE0wHKBzPqGhUM1Y3A-a3q-LS2g1h\
+                     6qgXb_KQ_Mmok8v8ld0cW_aYRLfNg"
+                        .to_string(),
+                ))
+                .expect("failed to deserialize");
+
+            // Invalid nonce
+            match public_client_verifier.verified_claims(
+                &test_jwt_with_nonce,
+                &Nonce::new("different_nonce".to_string()),
+            ) {
+                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            // Explicit nonce policies
+            let different_nonce = Nonce::new("different_nonce".to_string());
+            public_client_verifier
+                .verified_claims(&test_jwt_with_nonce, NonceCheck::Required(&valid_nonce))
+                .expect("verification should succeed");
+            match public_client_verifier
+                .verified_claims(&test_jwt_without_nonce, NonceCheck::Required(&valid_nonce))
+            {
+                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+            public_client_verifier
+                .verified_claims(&test_jwt_without_nonce, NonceCheck::Absent)
+                .expect("verification should succeed");
+            match public_client_verifier.verified_claims(&test_jwt_with_nonce, NonceCheck::Absent) {
+                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+            public_client_verifier
+                .verified_claims(&test_jwt_without_nonce, NonceCheck::IfPresent(&valid_nonce))
+                .expect("verification should succeed");
+            public_client_verifier
+                .verified_claims(&test_jwt_with_nonce, NonceCheck::IfPresent(&valid_nonce))
+                .expect("verification should succeed");
+            match public_client_verifier.verified_claims(
+                &test_jwt_with_nonce,
+                NonceCheck::IfPresent(&different_nonce),
+            ) {
+                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            // Invalid AuthenticationContextClass reference
+            match public_client_verifier
+                .clone()
+                .set_auth_context_verifier_fn(|acr| {
+                    assert_eq!(**acr.unwrap(), "the_acr");
+                    Err("Invalid acr claim".to_string())
+                })
+                .verified_claims(&test_jwt_with_nonce, &valid_nonce)
+            {
+                Err(ClaimsVerificationError::InvalidAuthContext(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            let test_jwt_without_auth_time =
+                serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
+                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                     S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDksIm5\
+                     vbmNlIjoidGhlX25vbmNlIiwiYWNyIjoidGhlX2FjciJ9.c_lU1VRasTg0mB4lwdOzbzvFS_XShMLN\
+                     lAPUpHBaMtCSPtI71L2x3hIByfkqIrAED-Qc_am2gNJ20bifidlkTOO6nyaBrJuaSjwT8aqajEbXon\
+                     5JFswwPvqCIWjd0eV5dXC1MZunpd7ANXSC7Qw16v3m_crc9wcI_fLFCzuAKrWYokGvNy0gr1CxcgVg\
+                     aE9qR0eqaatetzCuaOJhYOq4njrRlGZWtbj5Q56q3zhxJ_yS8K8gv1QcB4sHjUyXIj21jzjUD87zVG\
+                     dJsn8E-nFJSltBdQhEaLksTBH6ZZhkeGicQ8cEPnNeS4L1vfVyAd_cjl64JHLmzw8RUp8XuoF9nA"
+                        .to_string(),
+                ))
+                .expect("failed to deserialize");
+
+            // Missing auth_time (ok)
+            public_client_verifier
+                .verified_claims(&test_jwt_without_auth_time, |_: Option<&Nonce>| Ok(()))
+                .expect("verification should succeed");
+
+            // Missing auth_time (error)
+            match public_client_verifier
+                .clone()
+                .set_auth_time_verifier_fn(|auth_time| {
+                    assert!(auth_time.is_none());
+                    Err("Invalid auth_time claim".to_string())
+                })
+                .verified_claims(&test_jwt_without_auth_time, |_: Option<&Nonce>| Ok(()))
+            {
+                Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            // Invalid auth_time
+            match public_client_verifier
+                .clone()
+                .set_auth_time_verifier_fn(|auth_time| {
+                    assert_eq!(
+                        auth_time.unwrap(),
+                        timestamp_to_utc(&Timestamp::Seconds(1544928548.into())).unwrap(),
+                    );
+                    Err("Invalid auth_time claim".to_string())
+                })
+                .verified_claims(&test_jwt_with_nonce, &valid_nonce)
+            {
+                Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            // Successful verification with nonce, acr, and auth_time specified (no expected Nonce)
+            public_client_verifier
+                .verified_claims(&test_jwt_with_nonce, |_: Option<&Nonce>| Ok(()))
+                .expect("verification should succeed");
+            insecure_verifier
+                .verified_claims(&test_jwt_with_nonce, |_: Option<&Nonce>| Ok(()))
+                .expect("verification should succeed");
+
+            // Successful verification with nonce, acr, and auth_time specified (w/ expected Nonce)
+            public_client_verifier
+                .verified_claims(&test_jwt_with_nonce, &valid_nonce)
+                .expect("verification should succeed");
+            insecure_verifier
+                .verified_claims(&test_jwt_with_nonce, &valid_nonce)
+                .expect("verification should succeed");
+
+            // Successful verification with nonce, acr, and auth_time specified (w/ closure)
+            public_client_verifier
+                .verified_claims(&test_jwt_with_nonce, |nonce: Option<&Nonce>| {
+                    if nonce.iter().any(|n| n.secret() == valid_nonce.secret()) {
+                        Ok(())
+                    } else {
+                        Err("invalid nonce".to_string())
+                    }
+                })
+                .expect("verification should succeed");
+            insecure_verifier
+                .verified_claims(&test_jwt_with_nonce, |nonce: Option<&Nonce>| {
+                    if nonce.iter().any(|n| n.secret() == valid_nonce.secret()) {
+                        Ok(())
+                    } else {
+                        Err("invalid nonce".to_string())
+                    }
+                })
+                .expect("verification should succeed");
+
+            // HS256 w/ default algs
+            let test_jwt_hs256 =
+                serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
+                    "eyJhbGciOiJIUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                     S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDksIm5\
+                     vbmNlIjoidGhlX25vbmNlIn0.xUnSwSbcHsHWyJxwKGg69BIo_CktcyN5BVulGDb_QzE"
+                        .to_string(),
+                ))
+                .expect("failed to deserialize");
+            let private_client_verifier = CoreIdTokenVerifier::new_confidential_client(
+                client_id.clone(),
+                ClientSecret::new("my_secret".to_string()),
+                issuer.clone(),
+                CoreJsonWebKeySet::new(vec![rsa_key.clone()]),
+            )
+            .set_time_fn(|| {
+                timestamp_to_utc(&Timestamp::Seconds(
+                    mock_current_time.load(Ordering::Relaxed).into(),
+                ))
+                .unwrap()
+            });
+            match private_client_verifier.verified_claims(&test_jwt_hs256, &valid_nonce) {
+                Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::DisallowedAlg(_),
+                )) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
 
-        // Multiple audiences, where one is a match (allowed)
-        verifier
-            .clone()
-            .set_other_audience_verifier_fn(|aud| **aud == "aud1" || **aud == "aud2")
-            .verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsiYXVkMSIsIm15X2NsaWVudCIsImF1ZDIiXSwiaXNzIjoia\
-                 HR0cHM6Ly9leGFtcGxlLmNvbSIsInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.N9ibisEe0kKLe1GDWM\
-                 ON3PmYqbL73dag-loM8pjKJNinF9SB7n4JuSu4FrNkeW4F1Cz8MIbLuWfKvDa_4v_3FstMA3GODZWH\
-                 BVIiuNFay2ovCfGFyykwe47dF_47g_OM5AkJc_teE5MN8lPh9V5zYCy3ON3zZ3acFPJMOPTdbU56xD\
-                 eFe7lil6DmV4JU9A52t5ZkJILFaIuxxXJUIDmqpPTvHkggh_QOj9C2US9bgg5b543JwT4j-HbDp51L\
-                 dDB4k3azOssT1ddtoAuuDOctnraMKUtqffJXexxfwA1uM6EIofSrK5v11xwgTciL9xDXAvav_G2buP\
-                 ol1bjGLa2t0Q"
-                    .to_string(),
-            )).expect("failed to deserialize"),
-        ).expect("verification should succeed");
+            // An explicit allowlist rejects algorithms outside of it, even when a matching key
+            // (here, the client secret) is available.
+            let pinned_verifier = private_client_verifier.clone().set_allowed_algs(vec![
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                CoreJwsSigningAlgorithm::EcdsaP256Sha256,
+            ]);
+            pinned_verifier
+                .verified_claims(&test_jwt_with_nonce, &valid_nonce)
+                .expect("verification should succeed");
+            match pinned_verifier.verified_claims(&test_jwt_hs256, &valid_nonce) {
+                Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::DisallowedAlg(msg),
+                )) => assert!(
+                    msg.starts_with("algorithm `HS256` is not one of: "),
+                    "{}",
+                    msg
+                ),
+                other => panic!("unexpected result: {:?}", other),
+            }
+            insecure_verifier
+                .clone()
+                .verified_claims(&test_jwt_hs256, &valid_nonce)
+                .expect("verification should succeed");
 
-        // Multiple audiences, where none is a match
-        match verifier.verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsiYXVkMSIsImF1ZDIiXSwiaXNzIjoiaHR0cHM6Ly9leGFtcGxlL\
-                 mNvbSIsInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.YmFkX2hhc2g"
-                    .to_string(),
-            )).expect("failed to deserialize"),
-        ) {
-            Err(ClaimsVerificationError::InvalidAudience(_)) => {},
-            other => panic!("unexpected result: {:?}", other),
-        }
+            // HS256 w/ set_allowed_algs
+            private_client_verifier
+                .clone()
+                .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+                .verified_claims(&test_jwt_hs256, &valid_nonce)
+                .expect("verification should succeed");
 
-        // Disable signature check.
-        verifier
-            .clone()
-            .require_signature_check(false)
-            .verified_claims(
-                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                     S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
-                        .to_string(),
-                )).expect("failed to deserialize"),
-            ).expect("verification should succeed");
+            // HS256 w/ allow_any_alg
+            private_client_verifier
+                .clone()
+                .allow_any_alg()
+                .verified_claims(&test_jwt_hs256, &valid_nonce)
+                .expect("verification should succeed");
 
-        // "none" algorithm (unsigned JWT).
-        match verifier.verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJub25lIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ."
-                    .to_string(),
-            ))
-            .expect("failed to deserialize"),
-        ) {
-            Err(ClaimsVerificationError::NoSignature) => {}
-            other => panic!("unexpected result: {:?}", other),
-        }
+            // Invalid signature
+            let private_client_verifier_with_other_secret =
+                CoreIdTokenVerifier::new_confidential_client(
+                    client_id,
+                    ClientSecret::new("other_secret".to_string()),
+                    issuer,
+                    CoreJsonWebKeySet::new(vec![rsa_key]),
+                )
+                .allow_any_alg()
+                .set_time_fn(|| {
+                    timestamp_to_utc(&Timestamp::Seconds(
+                        mock_current_time.load(Ordering::Relaxed).into(),
+                    ))
+                    .unwrap()
+                });
+            match private_client_verifier_with_other_secret
+                .verified_claims(&test_jwt_hs256, &valid_nonce)
+            {
+                Err(ClaimsVerificationError::SignatureVerification(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
 
-        let valid_rs256_jwt =
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.UZ7vmAsDmOBzeB6e2_0POUfyhMRZKM6WSKz3\
-                 jB2QdmO-eZ9605EzhkJufJQ8515ryWnHv-gUHtZHQi3zilrzhBwvE2cVP83Gv2XIL1EKaMMmfISeEB\
-                 ShWez_FvqxN_bamh5yTROhWmoZTmof-MweBCHgINcsEd7K4e_BHHgq3aaRBpvSFlL_z4l_1NwNcTBo\
-                 kqjNScKZITk42AbsSuGR39L94BWLhz6WXQZ_Sn6R1Ro6roOm1b7E82jJiQEtlseQiCCvPR2JJ6LgW6\
-                 XTMzQ0vCqSh1A7U_IBDsjY_yag8_X3xxFh2URCtHJ47ZSjqfv6hq7OAq8tmVecOVgfIvABOg"
-                    .to_string(),
-            ))
-            .expect("failed to deserialize");
-        // Default algs + RS256 -> allowed
-        verifier
-            .verified_claims(valid_rs256_jwt.clone())
-            .expect("verification should succeed");
+            // Invalid signature w/ signature check disabled
+            private_client_verifier_with_other_secret
+                .clone()
+                .insecure_disable_signature_check()
+                .verified_claims(&test_jwt_hs256, &valid_nonce)
+                .expect("verification should succeed");
+        };
+    }
 
-        let verifier_with_client_secret = CoreJwtClaimsVerifier::new(
+    #[test]
+    fn test_hmac_id_token() {
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let nonce = Nonce::new("the_nonce".to_string());
+        let new_hs384_id_token = |secret: &str| {
+            CoreIdToken::new(
+                new_id_token_claims(issuer.as_str(), &[client_id.as_str()], Default::default())
+                    .set_nonce(Some(nonce.clone())),
+                &CoreHmacKey::new(secret.as_bytes()),
+                CoreJwsSigningAlgorithm::HmacSha384,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+        let verifier = CoreIdTokenVerifier::new_public_client(
             client_id.clone(),
             issuer.clone(),
             CoreJsonWebKeySet::new(vec![]),
         )
-        .set_client_secret(ClientSecret::new("my_secret".to_string()));
-        let valid_hs256_jwt =
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJIUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.dTXvSWen74_rC4oiWw0ziLZNe4KZk8Jw2VZe\
-                 N6vLCDo"
-                    .to_string(),
-            ))
-            .expect("failed to deserialize");
+        .set_time_fn(|| {
+            Utc.timestamp_opt(1544928549, 0)
+                .single()
+                .expect("valid timestamp")
+        });
+        let hmac_verifier = verifier
+            .clone()
+            .set_client_secret(ClientSecret::new("my_secret".to_string()))
+            .set_allowed_algs(vec![
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                CoreJwsSigningAlgorithm::HmacSha384,
+            ]);
+
+        let id_token = new_hs384_id_token("my_secret");
+        let claims = id_token
+            .claims(&hmac_verifier, &nonce)
+            .expect("verification should succeed");
+        assert_eq!(claims.subject().as_str(), "subject");
 
-        // Default algs + HS256 -> disallowed
-        match verifier_with_client_secret.verified_claims(valid_hs256_jwt.clone()) {
+        // Signed with the wrong secret.
+        match new_hs384_id_token("other_secret").claims(&hmac_verifier, &nonce) {
             Err(ClaimsVerificationError::SignatureVerification(
-                SignatureVerificationError::DisallowedAlg(_),
+                SignatureVerificationError::CryptoError(_),
             )) => {}
             other => panic!("unexpected result: {:?}", other),
         }
 
-        // none algs + RS256 -> allowed
-        verifier
-            .clone()
-            .allow_any_alg()
-            .verified_claims(valid_rs256_jwt.clone())
-            .expect("verification should succeed");
-
-        // none algs + HS256 -> allowed
-        verifier_with_client_secret
-            .clone()
-            .allow_any_alg()
-            .verified_claims(valid_hs256_jwt.clone())
-            .expect("verification should succeed");
-
-        // none algs + none -> disallowed
-        match verifier.clone().allow_any_alg().verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJub25lIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ."
-                    .to_string(),
-            ))
-            .expect("failed to deserialize"),
+        // The client secret doesn't bypass the (default) algorithm allowlist.
+        match id_token.claims(
+            &verifier
+                .clone()
+                .set_client_secret(ClientSecret::new("my_secret".to_string())),
+            &nonce,
         ) {
-            Err(ClaimsVerificationError::NoSignature) => {}
-            other => panic!("unexpected result: {:?}", other),
-        }
-
-        // HS256 + no client secret -> disallowed
-        match verifier
-            .clone()
-            .allow_any_alg()
-            .verified_claims(valid_hs256_jwt.clone())
-        {
             Err(ClaimsVerificationError::SignatureVerification(
                 SignatureVerificationError::DisallowedAlg(_),
             )) => {}
             other => panic!("unexpected result: {:?}", other),
         }
 
-        // HS256 + valid signature
-        verifier_with_client_secret
-            .clone()
-            .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
-            .verified_claims(valid_hs256_jwt)
-            .expect("verification should succeed");
-
-        // HS256 + invalid signature
-        match verifier_with_client_secret
-            .clone()
-            .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
-            .verified_claims(
-                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                    "eyJhbGciOiJIUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                     S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.dTXvSWen74_rC4oiWw0ziLZNe4KZk8Jw2VZe\
-                     N6vLCEo"
-                        .to_string(),
-                )).expect("failed to deserialize")
-            )
-        {
-            Err(ClaimsVerificationError::SignatureVerification(
-                    SignatureVerificationError::CryptoError(_),
-                )) => {}
-            other => panic!("unexpected result: {:?}", other),
-        }
-
-        // No public keys
-        match CoreJwtClaimsVerifier::new(
-            client_id.clone(),
-            issuer.clone(),
-            CoreJsonWebKeySet::new(vec![]),
-        )
-        .verified_claims(valid_rs256_jwt.clone())
-        {
+        // HMAC algorithms are rejected unless a client secret is explicitly configured.
+        match id_token.claims(&verifier.allow_any_alg(), &nonce) {
             Err(ClaimsVerificationError::SignatureVerification(
-                SignatureVerificationError::NoMatchingKey,
+                SignatureVerificationError::DisallowedAlg(_),
             )) => {}
             other => panic!("unexpected result: {:?}", other),
         }
+    }
 
-        let kid = JsonWebKeyId::new("bilbo.baggins@hobbiton.example".to_string());
-        let n = Base64UrlEncodedBytes::new(vec![
-            159, 129, 15, 180, 3, 130, 115, 208, 37, 145, 228, 7, 63, 49, 210, 182, 0, 27, 130,
-            206, 219, 77, 146, 240, 80, 22, 93, 71, 207, 202, 184, 163, 196, 28, 183, 120, 172,
-            117, 83, 121, 63, 142, 249, 117, 118, 141, 26, 35, 116, 216, 113, 37, 100, 195, 188,
-            215, 123, 158, 164, 52, 84, 72, 153, 64, 124, 255, 0, 153, 146, 10, 147, 26, 36, 196,
-            65, 72, 82, 171, 41, 189, 176, 169, 92, 6, 83, 243, 108, 96, 230, 11, 249, 11, 98, 88,
-            221, 165, 111, 55, 4, 123, 165, 194, 209, 208, 41, 175, 156, 157, 64, 186, 199, 170,
-            65, 199, 138, 13, 209, 6, 138, 221, 105, 158, 128, 143, 234, 1, 30, 161, 68, 29, 138,
-            79, 123, 180, 233, 123, 227, 159, 85, 241, 221, 212, 78, 156, 75, 163, 53, 21, 151, 3,
-            212, 211, 75, 96, 62, 101, 20, 122, 79, 35, 214, 211, 192, 153, 108, 117, 237, 238,
-            132, 106, 130, 209, 144, 174, 16, 120, 60, 150, 28, 240, 56, 122, 237, 33, 6, 210, 208,
-            85, 91, 111, 217, 55, 250, 213, 83, 83, 135, 224, 255, 114, 255, 190, 120, 148, 20, 2,
-            176, 184, 34, 234, 42, 116, 182, 5, 140, 29, 171, 249, 179, 74, 118, 203, 99, 184, 127,
-            170, 44, 104, 71, 184, 226, 131, 127, 255, 145, 24, 110, 107, 28, 20, 145, 28, 249,
-            137, 168, 144, 146, 168, 28, 230, 1, 221, 172, 211, 249, 207,
-        ]);
-        let e = Base64UrlEncodedBytes::new(vec![1, 0, 1]);
+    #[test]
+    fn test_id_token_verifier_with_key() {
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let nonce = Nonce::new("the_nonce".to_string());
+        let new_id_token = |issuer: &IssuerUrl, kid: Option<&str>| {
+            CoreIdToken::new(
+                new_id_token_claims(issuer.as_str(), &[client_id.as_str()], Default::default())
+                    .set_nonce(Some(nonce.clone())),
+                &CoreRsaPrivateSigningKey::from_pem(
+                    TEST_RSA_PRIV_KEY,
+                    kid.map(|kid| JsonWebKeyId::new(kid.to_string())),
+                )
+                .unwrap(),
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+        let new_verifier = |key: CoreJsonWebKey| {
+            CoreIdTokenVerifier::new_with_key(client_id.clone(), issuer.clone(), key).set_time_fn(
+                || {
+                    Utc.timestamp_opt(1544928549, 0)
+                        .single()
+                        .expect("valid timestamp")
+                },
+            )
+        };
+        let rsa_key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PUB_KEY).expect("deserialization failed");
+        let verifier = new_verifier(rsa_key);
 
-        // Wrong key type (symmetric key)
-        match CoreJwtClaimsVerifier::new(
-            client_id.clone(),
-            issuer.clone(),
-            CoreJsonWebKeySet::new(vec![CoreJsonWebKey {
-                kty: CoreJsonWebKeyType::Symmetric,
-                use_: Some(CoreJsonWebKeyUse::Signature),
-                kid: Some(kid.clone()),
-                n: None,
-                e: None,
-                k: Some(Base64UrlEncodedBytes::new(vec![1, 2, 3, 4])),
-                crv: None,
-                x: None,
-                y: None,
-                d: None,
-                #[cfg(feature = "jwk-alg")]
-                alg: None,
-            }]),
-        )
-        .verified_claims(valid_rs256_jwt.clone())
-        {
+        let id_token = new_id_token(&issuer, Some("bilbo.baggins@hobbiton.example"));
+        let claims = id_token
+            .claims(&verifier, &nonce)
+            .expect("verification should succeed");
+        assert_eq!(claims.subject().as_str(), "subject");
+
+        // An ID token without a key ID may be verified by the key regardless of its key ID, but
+        // an ID token with a different key ID may not.
+        new_id_token(&issuer, None)
+            .claims(&verifier, &nonce)
+            .expect("verification should succeed");
+        match new_id_token(&issuer, Some("other_key")).claims(&verifier, &nonce) {
             Err(ClaimsVerificationError::SignatureVerification(
                 SignatureVerificationError::NoMatchingKey,
             )) => {}
             other => panic!("unexpected result: {:?}", other),
         }
 
-        // Correct public key, but with signing disallowed
-        match CoreJwtClaimsVerifier::new(
-            client_id.clone(),
-            issuer.clone(),
-            CoreJsonWebKeySet::new(vec![CoreJsonWebKey {
-                kty: CoreJsonWebKeyType::RSA,
-                use_: Some(CoreJsonWebKeyUse::Encryption),
-                kid: Some(kid),
-                n: Some(n),
-                e: Some(e),
-                k: None,
-                crv: None,
-                x: None,
-                y: None,
-                d: None,
-                #[cfg(feature = "jwk-alg")]
-                alg: None,
-            }]),
+        // Claims are still verified.
+        match new_id_token(
+            &IssuerUrl::new("https://attacker.com".to_string()).unwrap(),
+            Some("bilbo.baggins@hobbiton.example"),
         )
-        .verified_claims(valid_rs256_jwt.clone())
+        .claims(&verifier, &nonce)
         {
-            Err(ClaimsVerificationError::SignatureVerification(
-                SignatureVerificationError::NoMatchingKey,
-            )) => {}
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
             other => panic!("unexpected result: {:?}", other),
         }
 
-        // Wrong key ID
-        match verifier.verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiIsImtpZCI6Indyb25nX2tleSJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6I\
-                 mh0dHBzOi8vZXhhbXBsZS5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.lVLomyIyO8WmyS1VZWPu\
-                 cGhRTUyK9RCw90fJC5CfDWUCgt1CBn-aP_ieWWBGfjb4ccR4dl57OYxdLl0Day8QN5pTCBud9QKpQ0rKQX\
-                 K8eBlOW8uSosx8q5pwU_bRyy-XuKJiPlDCOwTEHOp_hOgZFGjoN27MH3Xm8kc0iT3PgyqQ46-wsqHY9S02\
-                 hdJORX7vqYwQLZF8_k_L8K0IG_dC-1Co0g5oAf37oVSdl8hE-ScQ9K-AiSpS-cGYyldbMhyKNDL3ry2cuI\
-                 EUgYSIznkVFuM7RrEdNK222z5PF11ijYx-TM7BIDggbcIyJm-UqpmvVaJImmj5FNkMzuHYznLtdg"
-                    .to_string(),
-            )).expect("failed to deserialize")
+        // Signatures by other keys are rejected.
+        match id_token.claims(
+            &new_verifier(serde_json::from_str(TEST_EC_PUB_KEY_P256).unwrap()),
+            &nonce,
         ) {
             Err(ClaimsVerificationError::SignatureVerification(
-                    SignatureVerificationError::NoMatchingKey,
-                )) => {}
+                SignatureVerificationError::KeyTypeMismatch(_),
+            )) => {}
             other => panic!("unexpected result: {:?}", other),
         }
+    }
 
-        // Client secret + public key
-        verifier
-            .clone()
-            .set_client_secret(ClientSecret::new("my_secret".to_string()))
-            .verified_claims(valid_rs256_jwt.clone())
-            .expect("verification should succeed");
+    #[test]
+    fn test_id_token_verifier_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
 
-        // Multiple matching public keys: no KID specified
-        match CoreJwtClaimsVerifier::new(
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let nonce = Nonce::new("the_nonce".to_string());
+        let new_id_token = |subject: &str| -> CoreIdToken {
+            sign_id_token(
+                new_id_token_claims(issuer.as_str(), &[client_id.as_str()], Default::default())
+                    .set_subject(SubjectIdentifier::new(subject.to_string()))
+                    .set_nonce(Some(nonce.clone())),
+            )
+        };
+        let id_tokens = [new_id_token("alice"), new_id_token("bob")];
+
+        let verifier = CoreIdTokenVerifier::new_public_client(
             client_id.clone(),
             issuer.clone(),
-            CoreJsonWebKeySet::new(vec![rsa_key.clone(), rsa_key.clone()]),
+            CoreJsonWebKeySet::new(vec![serde_json::from_str(TEST_RSA_PUB_KEY).unwrap()]),
         )
-        .verified_claims(valid_rs256_jwt.clone())
-        {
-            Err(ClaimsVerificationError::SignatureVerification(
-                SignatureVerificationError::AmbiguousKeyId(_),
-            )) => {}
+        .set_time_fn(|| {
+            Utc.timestamp_opt(1544928549, 0)
+                .single()
+                .expect("valid timestamp")
+        });
+        assert_send_sync(&verifier);
+
+        let verifier = &verifier;
+        let nonce = &nonce;
+        let subjects = std::thread::scope(|scope| {
+            id_tokens
+                .iter()
+                .map(|id_token| {
+                    scope.spawn(move || {
+                        id_token
+                            .claims(verifier, nonce)
+                            .expect("verification should succeed")
+                            .subject()
+                            .to_string()
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(subjects, vec!["alice", "bob"]);
+
+        // The verifier remains usable after verifying tokens on other threads.
+        id_tokens[0]
+            .claims(verifier, nonce)
+            .expect("verification should succeed");
+    }
+
+    #[test]
+    fn test_new_id_token() {
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let nonce = Nonce::new("the_nonce".to_string());
+        let rsa_priv_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+
+        let id_token = CoreIdToken::new(
+            CoreIdTokenClaims::new(
+                issuer.clone(),
+                vec![Audience::new((*client_id).clone())],
+                Utc.timestamp_opt(1544932149, 0)
+                    .single()
+                    .expect("valid timestamp"),
+                Utc.timestamp_opt(1544928549, 0)
+                    .single()
+                    .expect("valid timestamp"),
+                StandardClaims::new(SubjectIdentifier::new("subject".to_string())),
+                Default::default(),
+            )
+            .set_nonce(Some(nonce.clone()))
+            .set_auth_context_ref(Some(AuthenticationContextClass::new("the_acr".to_string())))
+            .set_auth_time(Some(
+                Utc.timestamp_opt(1544928548, 0)
+                    .single()
+                    .expect("valid timestamp"),
+            )),
+            &rsa_priv_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            Some(&AccessToken::new("the_access_token".to_string())),
+            Some(&AuthorizationCode::new(
+                "the_authorization_code".to_string(),
+            )),
+        )
+        .unwrap();
+
+        let serialized_jwt: serde_json::Value = serde_json::to_value(&id_token).unwrap();
+        let expected_serialized_jwt =
+            "eyJhbGciOiJSUzI1NiJ9.eyJpc3MiOiJodHRwczovL2V4YW1wbGUuY29tIiwiYXVkIjpbIm15X2NsaWVudCJdL\
+             CJleHAiOjE1NDQ5MzIxNDksImlhdCI6MTU0NDkyODU0OSwiYXV0aF90aW1lIjoxNTQ0OTI4NTQ4LCJub25jZSI\
+             6InRoZV9ub25jZSIsImFjciI6InRoZV9hY3IiLCJhdF9oYXNoIjoiWjNJQUNVR00tbXhIV3lZUXZpSzhFUSIsI\
+             mNfaGFzaCI6Imo2OW1CZmFIbmRMM1Y1RmNoak9LVXciLCJzdWIiOiJzdWJqZWN0In0.CHCWFcIqbCZhZwZH4oY\
+             _mlcRy5aUQQtlNI0VHNYxiILn9ppRHLL4Bn_LMn9VP8tGXkfZWxCgP25ZTyBXXKfk0fQvnukVdyM0bCOpQbiBg\
+             5gB9c46l_f-ZznDoHWonpnKky2Gmzk3ocb3TCUQ9GSeRXAzRdRNWTT0ElWNBsLWU4j2IIdnghM78gkXwOC76Rk\
+             pshgB73ubtuHGdIf5L9Ec3hifHlVjzKuvedAM4SIOjdBOelgtBlF3463ufX_Ut91CjP5TzLMsuK3Lh_vyo8ttn\
+             S41rBDuetR2ENvR0yj5RjkX_SPY3V0yCW8_NPPu1CHu_1oL0Nma0ohCbF3vnUJcwg";
+        assert_eq!(expected_serialized_jwt, serialized_jwt.as_str().unwrap());
+
+        let rsa_pub_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .expect("deserialization failed");
+
+        let mock_current_time = AtomicUsize::new(1544932148);
+        let time_fn = || {
+            timestamp_to_utc(&Timestamp::Seconds(
+                mock_current_time.load(Ordering::Relaxed).into(),
+            ))
+            .unwrap()
+        };
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            client_id,
+            issuer,
+            CoreJsonWebKeySet::new(vec![rsa_pub_key]),
+        )
+        .set_time_fn(time_fn);
+        let claims = id_token.claims(&verifier, &nonce).unwrap();
+        let unverified = id_token
+            .claims(
+                &CoreIdTokenVerifier::new_insecure_without_verification().set_time_fn(time_fn),
+                &nonce,
+            )
+            .unwrap();
+        assert_eq!(claims, unverified);
+    }
+
+    #[test]
+    fn test_encrypted_id_token() {
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let nonce = Nonce::new("the_nonce".to_string());
+
+        let signed_id_token = sign_id_token(
+            new_id_token_claims(issuer.as_str(), &[client_id.as_str()], Default::default())
+                .set_nonce(Some(nonce.clone())),
+        );
+
+        let rsa_pub_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .expect("deserialization failed");
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            client_id,
+            issuer,
+            CoreJsonWebKeySet::new(vec![rsa_pub_key]),
+        )
+        .set_time_fn(|| Utc.timestamp_opt(1544932148, 0).unwrap());
+        let decrypting_verifier = verifier
+            .clone()
+            .set_decryption_key(CoreRsaPrivateDecryptionKey::from_pem(TEST_RSA_PRIV_KEY).unwrap());
+        let expected_claims = signed_id_token.claims(&verifier, &nonce).unwrap().clone();
+
+        for (alg, enc) in [
+            ("RSA-OAEP", "A128GCM"),
+            ("RSA-OAEP", "A256GCM"),
+            ("RSA-OAEP-256", "A128GCM"),
+            ("RSA-OAEP-256", "A256GCM"),
+        ] {
+            let jwe = encrypt_compact_jwe(&signed_id_token.to_string(), alg, enc);
+            let id_token: CoreIdToken = jwe.parse().expect("failed to parse JWE");
+            assert!(id_token.is_encrypted());
+            assert_eq!(id_token.to_string(), jwe);
+            assert!(id_token.signing_alg().is_err());
+
+            // Encrypted ID tokens can't be verified by reference.
+            match id_token.claims(&decrypting_verifier, &nonce) {
+                Err(ClaimsVerificationError::Unsupported(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            // Without a decryption key, the ID token can't be decrypted.
+            match id_token.clone().into_claims(&verifier, &nonce) {
+                Err(ClaimsVerificationError::Unsupported(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            let decrypted = id_token.clone().decrypt(&decrypting_verifier).unwrap();
+            assert!(!decrypted.is_encrypted());
+            assert_eq!(decrypted, signed_id_token);
+            assert_eq!(
+                decrypted.claims(&decrypting_verifier, &nonce).unwrap(),
+                &expected_claims
+            );
+
+            assert_eq!(
+                id_token.into_claims(&decrypting_verifier, &nonce).unwrap(),
+                expected_claims
+            );
+        }
+
+        // Tampering with the ciphertext must be detected.
+        let jwe = encrypt_compact_jwe(&signed_id_token.to_string(), "RSA-OAEP-256", "A256GCM");
+        let mut parts = jwe.split('.').map(str::to_string).collect::<Vec<_>>();
+        let mut ciphertext =
+            base64::decode_config(&parts[3], crate::core::base64_url_safe_no_pad()).unwrap();
+        ciphertext[0] ^= 1;
+        parts[3] = base64::encode_config(ciphertext, base64::URL_SAFE_NO_PAD);
+        let id_token: CoreIdToken = parts.join(".").parse().unwrap();
+        match id_token.into_claims(&decrypting_verifier, &nonce) {
+            Err(ClaimsVerificationError::Decryption(DecryptionError::CryptoError)) => {}
             other => panic!("unexpected result: {:?}", other),
         }
 
-        // Multiple matching public keys: KID specified
-        match CoreJwtClaimsVerifier::new(
+        // Unsupported key management algorithms are rejected.
+        let jwe = encrypt_compact_jwe(&signed_id_token.to_string(), "RSA-OAEP-256", "A256GCM");
+        let mut parts = jwe.split('.').map(str::to_string).collect::<Vec<_>>();
+        parts[0] = base64::encode_config(
+            "{\"alg\":\"RSA1_5\",\"enc\":\"A256GCM\"}",
+            base64::URL_SAFE_NO_PAD,
+        );
+        let id_token: CoreIdToken = parts.join(".").parse().unwrap();
+        match id_token.into_claims(&decrypting_verifier, &nonce) {
+            Err(ClaimsVerificationError::Decryption(DecryptionError::UnsupportedAlg(alg))) => {
+                assert_eq!(alg, "RSA1_5")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_symmetric_encrypted_id_token() {
+        let client_id = ClientId::new("my_client".to_string());
+        let client_secret = ClientSecret::new("my_secret".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let nonce = Nonce::new("the_nonce".to_string());
+
+        let signed_id_token = sign_id_token(
+            new_id_token_claims(issuer.as_str(), &[client_id.as_str()], Default::default())
+                .set_nonce(Some(nonce.clone())),
+        );
+        let verifier = CoreIdTokenVerifier::new_public_client(
             client_id,
             issuer,
-            CoreJsonWebKeySet::new(vec![rsa_key.clone(), rsa_key]),
-        ).verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiIsImtpZCI6ImJpbGJvLmJhZ2dpbnNAaG9iYml0b24uZXhhbXBsZSJ9.eyJhdWQiO\
-                 lsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZS5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29\
-                 ybGQifQ.jH0v2fQGvH2MD0jn5pQP6W6AF5rJlizyofdyRUIt7E3GraGA1LYDiLAVIfhST3uwJopP-TgtBk\
-                 zc-zyJSvgTR63S8iI1YlHypItpx7r4I9ydzo8GSN5RrZudcU2esY4uEnLbVl17ZVNu4IyTExeKJ0sPM0Hj\
-                 qkOA4XaP2cJwsK-bookNHSA8NRE6adRMrHAKJbor5jrGjpkZAKHbnQFK-wu-nEV_OjS9jpN_FboRZVcDTZ\
-                 GFzeFbqFqHdRn6UWPFnVpVnUhih16UjNH1om6gwc0uFoPWTDxJlXQCFbHMhZtgCbUkXQBH7twPMc4YUziw\
-                 S8GIRKCcXjdrP5oyxmcitQ"
-                    .to_string(),
-            )).expect("failed to deserialize")
+            CoreJsonWebKeySet::new(vec![serde_json::from_str(TEST_RSA_PUB_KEY).unwrap()]),
+        )
+        .set_time_fn(|| Utc.timestamp_opt(1544932148, 0).unwrap());
+        let expected_claims = signed_id_token.claims(&verifier, &nonce).unwrap().clone();
+        let decrypt = |jwe: String, key: CoreSymmetricDecryptionKey| {
+            jwe.parse::<CoreIdToken>()
+                .expect("failed to parse JWE")
+                .into_claims(&verifier.clone().set_decryption_key(key), &nonce)
+        };
+
+        // Direct encryption using a raw key.
+        let key = [7; 32];
+        let jwe =
+            encrypt_compact_jwe_symmetric(&signed_id_token.to_string(), "dir", "A256GCM", &key);
+        assert_eq!(
+            decrypt(jwe.clone(), CoreSymmetricDecryptionKey::new(&key)).unwrap(),
+            expected_claims
+        );
+        match decrypt(jwe, CoreSymmetricDecryptionKey::new(&key[..16])) {
+            Err(ClaimsVerificationError::Decryption(DecryptionError::InvalidKey(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // AES Key Wrap using a key derived from the client secret.
+        let derived_key = Sha256::digest(client_secret.secret().as_bytes());
+        let jwe = encrypt_compact_jwe_symmetric(
+            &signed_id_token.to_string(),
+            "A256KW",
+            "A128GCM",
+            &derived_key,
+        );
+        assert_eq!(
+            decrypt(
+                jwe.clone(),
+                CoreSymmetricDecryptionKey::from_client_secret(&client_secret)
+            )
+            .unwrap(),
+            expected_claims
+        );
+        match decrypt(
+            jwe,
+            CoreSymmetricDecryptionKey::from_client_secret(&ClientSecret::new(
+                "other_secret".to_string(),
+            )),
         ) {
-            Err(ClaimsVerificationError::SignatureVerification(
-                    SignatureVerificationError::AmbiguousKeyId(_),
-                )) => {}
+            Err(ClaimsVerificationError::Decryption(DecryptionError::CryptoError)) => {}
             other => panic!("unexpected result: {:?}", other),
         }
 
-        // RS256 + valid signature
-        verifier
-            .verified_claims(valid_rs256_jwt)
-            .expect("verification should succeed");
+        let jwe = encrypt_compact_jwe_symmetric(
+            &signed_id_token.to_string(),
+            "A128KW",
+            "A256GCM",
+            &derived_key[..16],
+        );
+        assert_eq!(
+            decrypt(
+                jwe.clone(),
+                CoreSymmetricDecryptionKey::from_client_secret(&client_secret)
+            )
+            .unwrap(),
+            expected_claims
+        );
 
-        // RS256 + invalid signature
-        match verifier.verified_claims(
-            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZS5jb\
-                 20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
-                    .to_string(),
-            )).expect("failed to deserialize"),
+        // Direct encryption requires an empty encrypted key.
+        let mut parts = jwe.split('.').map(str::to_string).collect::<Vec<_>>();
+        parts[0] = base64::encode_config(
+            "{\"alg\":\"dir\",\"enc\":\"A256GCM\"}",
+            base64::URL_SAFE_NO_PAD,
+        );
+        match decrypt(
+            parts.join("."),
+            CoreSymmetricDecryptionKey::from_client_secret(&client_secret),
         ) {
-            Err(ClaimsVerificationError::SignatureVerification(
-                    SignatureVerificationError::CryptoError(_),
-                )) => {}
+            Err(ClaimsVerificationError::Decryption(DecryptionError::Other(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Asymmetric key management algorithms are rejected.
+        let jwe = encrypt_compact_jwe(&signed_id_token.to_string(), "RSA-OAEP-256", "A256GCM");
+        match decrypt(jwe, CoreSymmetricDecryptionKey::new(&key)) {
+            Err(ClaimsVerificationError::Decryption(DecryptionError::UnsupportedAlg(alg))) => {
+                assert_eq!(alg, "RSA-OAEP-256")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_id_token_time_fn() {
+        // This JWT has an issue time of 1544928549 and an expiration time of 1544932149, so it's
+        // long expired according to the system clock.
+        let test_jwt = serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
+            "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+             S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDl9.nN\
+             aTxNwclnTHd1Q9POkddm5wB1w3wJ-gwQWHomhimttk3SWQTLhxI0SSjWrHahGxlfkjufJlSyt-t_VO\
+             SdcROvIYZTDznDfFZz3oSOev-p9XiZ-EZTS-U6N11Y923sDQjbTMeukz1F3ZFEfn5Mv2xjdEoJccCe\
+             7SaGuDmVqMqTLXMtsw9NCE_KDd0oKSwDzbJIBBPEfG3JjbKg0Dln7ENHg9wzoNFQzPXrkKzjneBgD3\
+             vuwFCV5y-e8xUBdLaLZF1kdkDZJIA48uRROLlWjsM8pEptosA5QK07luQCZNqcaZWEczoGXeQs8PyA\
+             zkNV7JEmti3bJnWSN-ud4cFU0LiQ"
+                .to_string(),
+        ))
+        .expect("failed to deserialize");
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            ClientId::new("my_client".to_string()),
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .expect("deserialization failed")]),
+        );
+
+        match verifier.verified_claims(&test_jwt, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
             other => panic!("unexpected result: {:?}", other),
         }
+
+        let claims = verifier
+            .set_time_fn(|| Utc.timestamp_opt(1544928549, 0).single().unwrap())
+            .verified_claims(&test_jwt, |_: Option<&Nonce>| Ok(()))
+            .expect("verification should succeed at the token's issue time");
+        assert_eq!(claims.issue_time().timestamp(), 1544928549);
     }
 
-    type CoreIdTokenJwt = JsonWebToken<
-        CoreJweContentEncryptionAlgorithm,
-        CoreJwsSigningAlgorithm,
-        CoreJsonWebKeyType,
-        CoreIdTokenClaims,
-        JsonWebTokenJsonPayloadSerde,
-    >;
+    fn new_id_token_with_auth_time(auth_time: Option<i64>) -> CoreIdToken {
+        sign_id_token(
+            new_id_token_claims("https://example.com", &["my_client"], Default::default())
+                .set_auth_time(auth_time.map(|auth_time| Utc.timestamp_opt(auth_time, 0).unwrap())),
+        )
+    }
 
     #[test]
-    fn test_id_token_verified_claims() {
-        let rsa_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
-            .expect("deserialization failed");
-
-        let client_id = ClientId::new("my_client".to_string());
-        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
-        let mock_current_time = AtomicUsize::new(1544932149);
-        let mock_is_valid_issue_time = AtomicBool::new(true);
-        // Extra scope needed to ensure closures are destroyed before the values they borrow.
+    fn test_id_token_auth_time_max_age() {
+        let current_time = 1544928549 + 600;
+        let verifier = CoreIdTokenVerifier::new_insecure_without_verification()
+            .set_time_fn(|| Utc.timestamp_opt(current_time, 0).single().unwrap())
+            .set_time_leeway(Duration::from_secs(0))
+            .require_auth_time(Duration::from_secs(300));
+
+        // Authenticated 300 seconds ago.
+        new_id_token_with_auth_time(Some(current_time - 300))
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("authentication within max_age should be accepted");
+
+        // Authenticated 301 seconds ago.
+        match new_id_token_with_auth_time(Some(current_time - 301))
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
         {
-            let public_client_verifier = CoreIdTokenVerifier::new_public_client(
-                client_id.clone(),
-                issuer.clone(),
-                CoreJsonWebKeySet::new(vec![rsa_key.clone()]),
+            Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        new_id_token_with_auth_time(Some(current_time - 301))
+            .claims(
+                &verifier.clone().set_time_leeway(Duration::from_secs(1)),
+                |_: Option<&Nonce>| Ok(()),
             )
-            .set_time_fn(|| {
-                timestamp_to_utc(&Timestamp::Seconds(
-                    mock_current_time.load(Ordering::Relaxed).into(),
-                ))
-                .unwrap()
-            })
-            .set_issue_time_verifier_fn(|_| {
-                if mock_is_valid_issue_time.load(Ordering::Relaxed) {
-                    Ok(())
-                } else {
-                    Err("Invalid iat claim".to_string())
-                }
-            });
-
-            let insecure_verifier = CoreIdTokenVerifier::new_insecure_without_verification()
-                .set_time_fn(|| {
-                    timestamp_to_utc(&Timestamp::Seconds(
-                        mock_current_time.load(Ordering::Relaxed).into(),
-                    ))
-                    .unwrap()
-                });
+            .expect("authentication within max_age plus the leeway should be accepted");
 
-            // This JWTs below have an issue time of 1544928549 and an expiration time of 1544932149.
+        // The auth_time claim is required when max_age is requested.
+        match new_id_token_with_auth_time(None).claims(&verifier, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        new_id_token_with_auth_time(None)
+            .claims(
+                &CoreIdTokenVerifier::new_insecure_without_verification()
+                    .set_time_fn(|| Utc.timestamp_opt(current_time, 0).single().unwrap()),
+                |_: Option<&Nonce>| Ok(()),
+            )
+            .expect("auth_time should be optional by default");
+    }
 
-            let test_jwt_without_nonce =
-                serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
-                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                     S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDl9.nN\
-                     aTxNwclnTHd1Q9POkddm5wB1w3wJ-gwQWHomhimttk3SWQTLhxI0SSjWrHahGxlfkjufJlSyt-t_VO\
-                     SdcROvIYZTDznDfFZz3oSOev-p9XiZ-EZTS-U6N11Y923sDQjbTMeukz1F3ZFEfn5Mv2xjdEoJccCe\
-                     7SaGuDmVqMqTLXMtsw9NCE_KDd0oKSwDzbJIBBPEfG3JjbKg0Dln7ENHg9wzoNFQzPXrkKzjneBgD3\
-                     vuwFCV5y-e8xUBdLaLZF1kdkDZJIA48uRROLlWjsM8pEptosA5QK07luQCZNqcaZWEczoGXeQs8PyA\
-                     zkNV7JEmti3bJnWSN-ud4cFU0LiQ"
-                            .to_string(),
-                ))
-                .expect("failed to deserialize");
+    #[test]
+    fn test_id_token_auth_time_present() {
+        let current_time = 1544928549 + 600;
+        let verifier = CoreIdTokenVerifier::new_insecure_without_verification()
+            .set_time_fn(move || Utc.timestamp_opt(current_time, 0).single().unwrap())
+            .require_auth_time_present();
+
+        // Any authentication time is accepted, since no max_age is configured.
+        for auth_time in [current_time - 300, current_time - 86400 * 365] {
+            let id_token = new_id_token_with_auth_time(Some(auth_time));
+            let claims = id_token
+                .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+                .expect("auth_time should be accepted");
+            assert_eq!(claims.auth_time().unwrap().timestamp(), auth_time);
+        }
 
-            // Invalid JWT claims
-            match public_client_verifier.verified_claims(
-                &serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
-                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vYXR0YWNrZ\
-                     XIuY29tIiwic3ViIjoic3ViamVjdCIsImV4cCI6MTU0NDkzMjE0OSwiaWF0IjoxNTQ0OTI4NTQ5LCJ\
-                     ub25jZSI6InRoZV9ub25jZSIsImFjciI6InRoZV9hY3IifQ.Pkicxk0dTU5BkSxgqTON6lE7A7ir3l\
-                     aADRyoeRoCNDX3AOx7BXCbfzbda6HJiPskN2nu56w0q-0OdkDSIHls-2xTUlLEJv2Bv0BLYwV5ZVJ8\
-                     hoc-rTd0_oLUb5NzyD80RyVByjVMK8bh6cwysTnr8QDxsEiFZbFo3mVJob2yjPZnNOdcNJWPcVVueP\
-                     8vqMJnx5kHih1gKZpWj_dMN9b2AW6zVLOInW3Ox__gx6fsFFz7rjxItG-PTY_OQMzthqeHUyq4o9y7\
-                     Jv8mB_jFkTZGVKHTPpObHV-qptJ_rnlwvF_mP5GARBLng-4Yd7nmSr31onYL48QDjGOrwPqQ-IyaCQ"
-                        .to_string(),
-                ))
-                    .expect("failed to deserialize"), |_: Option<&Nonce>| Ok(())) {
-                Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
-                other => panic!("unexpected result: {:?}", other),
+        match new_id_token_with_auth_time(None).claims(&verifier, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::InvalidAuthTime(message)) => {
+                assert_eq!(message, "missing required auth_time claim")
             }
+            other => panic!("unexpected result: {:?}", other),
+        }
 
-            // TODO: disallowed algs
-
-            // Expired token
-            mock_current_time.store(1544928549 + 3600, Ordering::Relaxed);
-            match public_client_verifier
-                .verified_claims(&test_jwt_without_nonce, |_: Option<&Nonce>| Ok(()))
-            {
-                Err(ClaimsVerificationError::Expired(_)) => {}
-                other => panic!("unexpected result: {:?}", other),
-            }
-            mock_current_time.store(1544928549 + 1, Ordering::Relaxed);
+        // The max_age check is applied independently (and extended by the default leeway).
+        match new_id_token_with_auth_time(Some(current_time - 361)).claims(
+            &verifier.require_auth_time(Duration::from_secs(300)),
+            |_: Option<&Nonce>| Ok(()),
+        ) {
+            Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 
-            // Invalid issue time
-            mock_is_valid_issue_time.store(false, Ordering::Relaxed);
-            match public_client_verifier
-                .verified_claims(&test_jwt_without_nonce, |_: Option<&Nonce>| Ok(()))
+    #[test]
+    fn test_id_token_claims_validator() {
+        #[derive(Clone, Debug, Deserialize, Serialize)]
+        struct TenantClaims {
+            tenant: Option<String>,
+        }
+        impl AdditionalClaims for TenantClaims {}
+        type TenantIdToken = IdToken<
+            TenantClaims,
+            CoreGenderClaim,
+            CoreJweContentEncryptionAlgorithm,
+            CoreJwsSigningAlgorithm,
+            CoreJsonWebKeyType,
+        >;
+
+        let new_id_token = |tenant: Option<&str>| -> TenantIdToken {
+            sign_id_token(new_id_token_claims(
+                "https://example.com",
+                &["my_client"],
+                TenantClaims {
+                    tenant: tenant.map(str::to_string),
+                },
+            ))
+        };
+        let verifier = new_audience_test_verifier("my_client").set_claims_validator(
+            |claims: &IdTokenClaims<TenantClaims, CoreGenderClaim>| match claims
+                .additional_claims()
+                .tenant
+                .as_deref()
             {
-                Err(ClaimsVerificationError::Expired(_)) => {}
-                other => panic!("unexpected result: {:?}", other),
-            }
-            mock_is_valid_issue_time.store(true, Ordering::Relaxed);
-
-            let valid_nonce = Nonce::new("the_nonce".to_string());
+                Some("tenant-a") => Ok(()),
+                Some(tenant) => Err(format!("unexpected tenant `{}`", tenant)),
+                None => Err("missing tenant claim".to_string()),
+            },
+        );
 
-            // Successful verification w/o checking nonce
-            public_client_verifier
-                .verified_claims(&test_jwt_without_nonce, |_: Option<&Nonce>| Ok(()))
-                .expect("verification should succeed");
+        let id_token = new_id_token(Some("tenant-a"));
+        let claims = id_token
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("tenant-a should be accepted");
+        assert_eq!(
+            claims.additional_claims().tenant.as_deref(),
+            Some("tenant-a")
+        );
+        id_token
+            .clone()
+            .into_claims(&verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("tenant-a should be accepted");
 
-            // Missing nonce
-            match public_client_verifier.verified_claims(&test_jwt_without_nonce, &valid_nonce) {
-                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
-                other => panic!("unexpected result: {:?}", other),
+        match new_id_token(Some("tenant-b")).claims(&verifier, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::InvalidClaims(message)) => {
+                assert_eq!(message, "unexpected tenant `tenant-b`")
             }
-
-            // Missing nonce w/ closure
-            match public_client_verifier.verified_claims(
-                &test_jwt_without_nonce,
-                |nonce: Option<&Nonce>| {
-                    if nonce.iter().any(|n| n.secret() == valid_nonce.secret()) {
-                        Ok(())
-                    } else {
-                        Err("invalid nonce".to_string())
-                    }
-                },
-            ) {
-                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
-                other => panic!("unexpected result: {:?}", other),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match new_id_token(None).claims(&verifier, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::InvalidClaims(message)) => {
+                assert_eq!(message, "missing tenant claim")
             }
+            other => panic!("unexpected result: {:?}", other),
+        }
 
-            let test_jwt_with_nonce =
-                serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
-                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                     S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDksIm5\
-                     vbmNlIjoidGhlX25vbmNlIiwiYWNyIjoidGhlX2FjciIsImF1dGhfdGltZSI6MTU0NDkyODU0OH0.W\
-                     XA7SS9aMh_6rvBEgQce5D2J84OqphmmnCLGgEKRTN5G-UuQTNOBp8VS5_4f3xgzMEEMvGJJauJoALk\
-                     muUeHB-N_ESrkmB3tgDzBSYBa7kuYPHUPYpdjZM2UVolqI9RYyHaWwKjL_Io5YyAazB5lH5ibPaiBl\
-                     UNKGs3cmVsEB22UGMFKM6cek7GinrHQe_aJQsMU839-c2zzlEyFSeI8QBphQtG6AN82IPkNRv8QWmw\
-                     ZjUiB5a-W73Z3gURYMNs7f32BjAUNoJzW0Qj34vzD2djoSHhltE0wHKBzPqGhUM1Y3A-a3q-LS2g1h\
-                     6qgXb_KQ_Mmok8v8ld0cW_aYRLfNg"
-                        .to_string(),
-                ))
-                .expect("failed to deserialize");
+        // The validator runs only after the standard checks have passed.
+        match id_token.claims(
+            &verifier
+                .clone()
+                .set_time_fn(|| Utc.timestamp_opt(1544932149 + 60, 0).single().unwrap()),
+            |_: Option<&Nonce>| Ok(()),
+        ) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
 
-            // Invalid nonce
-            match public_client_verifier.verified_claims(
-                &test_jwt_with_nonce,
-                &Nonce::new("different_nonce".to_string()),
-            ) {
-                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
-                other => panic!("unexpected result: {:?}", other),
+        // Claims of a different type are rejected rather than silently skipping validation.
+        match new_id_token_with_audiences(vec!["my_client"], None)
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+        {
+            Err(ClaimsVerificationError::InvalidClaims(message)) => {
+                assert!(message.starts_with("claims validator expects claims of type"))
             }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 
-            // Invalid AuthenticationContextClass reference
-            match public_client_verifier
-                .clone()
-                .set_auth_context_verifier_fn(|acr| {
-                    assert_eq!(**acr.unwrap(), "the_acr");
-                    Err("Invalid acr claim".to_string())
-                })
-                .verified_claims(&test_jwt_with_nonce, &valid_nonce)
-            {
-                Err(ClaimsVerificationError::InvalidAuthContext(_)) => {}
-                other => panic!("unexpected result: {:?}", other),
-            }
+    fn new_id_token_with_audiences(audiences: Vec<&str>, azp: Option<&str>) -> CoreIdToken {
+        sign_id_token(
+            new_id_token_claims("https://example.com", &audiences, Default::default())
+                .set_authorized_party(azp.map(|azp| ClientId::new(azp.to_string()))),
+        )
+    }
 
-            let test_jwt_without_auth_time =
-                serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
-                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                     S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDksIm5\
-                     vbmNlIjoidGhlX25vbmNlIiwiYWNyIjoidGhlX2FjciJ9.c_lU1VRasTg0mB4lwdOzbzvFS_XShMLN\
-                     lAPUpHBaMtCSPtI71L2x3hIByfkqIrAED-Qc_am2gNJ20bifidlkTOO6nyaBrJuaSjwT8aqajEbXon\
-                     5JFswwPvqCIWjd0eV5dXC1MZunpd7ANXSC7Qw16v3m_crc9wcI_fLFCzuAKrWYokGvNy0gr1CxcgVg\
-                     aE9qR0eqaatetzCuaOJhYOq4njrRlGZWtbj5Q56q3zhxJ_yS8K8gv1QcB4sHjUyXIj21jzjUD87zVG\
-                     dJsn8E-nFJSltBdQhEaLksTBH6ZZhkeGicQ8cEPnNeS4L1vfVyAd_cjl64JHLmzw8RUp8XuoF9nA"
-                        .to_string(),
-                ))
-                .expect("failed to deserialize");
+    fn new_audience_test_verifier(client_id: &str) -> CoreIdTokenVerifier<'static> {
+        CoreIdTokenVerifier::new_public_client(
+            ClientId::new(client_id.to_string()),
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        )
+        .set_time_fn(|| Utc.timestamp_opt(1544928549, 0).single().unwrap())
+    }
 
-            // Missing auth_time (ok)
-            public_client_verifier
-                .verified_claims(&test_jwt_without_auth_time, |_: Option<&Nonce>| Ok(()))
-                .expect("verification should succeed");
+    #[test]
+    fn test_id_token_allowed_audiences() {
+        let verifier = new_audience_test_verifier("dev_client")
+            .set_other_audience_verifier_fn(|aud| aud.as_str() == "resource_server")
+            .set_allowed_audiences(vec![
+                Audience::new("staging_client".to_string()),
+                Audience::new("prod_client".to_string()),
+            ])
+            .require_authorized_party_match(true);
+
+        // A single audience matching any of the client's identifiers is accepted.
+        for aud in ["dev_client", "prod_client"] {
+            new_id_token_with_audiences(vec![aud], None)
+                .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+                .unwrap_or_else(|err| panic!("audience `{}` should be accepted: {:?}", aud, err));
+        }
+        match new_id_token_with_audiences(vec!["other_client"], None)
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+        {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
 
-            // Missing auth_time (error)
-            match public_client_verifier
-                .clone()
-                .set_auth_time_verifier_fn(|auth_time| {
-                    assert!(auth_time.is_none());
-                    Err("Invalid auth_time claim".to_string())
-                })
-                .verified_claims(&test_jwt_without_auth_time, |_: Option<&Nonce>| Ok(()))
-            {
-                Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
-                other => panic!("unexpected result: {:?}", other),
+        // Multiple audiences with a matching azp claim.
+        new_id_token_with_audiences(vec!["prod_client", "resource_server"], Some("prod_client"))
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("multiple audiences with matching azp should be accepted");
+
+        // Multiple audiences require an azp claim.
+        match new_id_token_with_audiences(vec!["prod_client", "resource_server"], None)
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+        {
+            Err(ClaimsVerificationError::InvalidAudience(msg)) => {
+                assert!(msg.contains("missing authorized party"), "{}", msg)
             }
+            other => panic!("unexpected result: {:?}", other),
+        }
 
-            // Invalid auth_time
-            match public_client_verifier
-                .clone()
-                .set_auth_time_verifier_fn(|auth_time| {
-                    assert_eq!(
-                        auth_time.unwrap(),
-                        timestamp_to_utc(&Timestamp::Seconds(1544928548.into())).unwrap(),
-                    );
-                    Err("Invalid auth_time claim".to_string())
-                })
-                .verified_claims(&test_jwt_with_nonce, &valid_nonce)
-            {
-                Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
-                other => panic!("unexpected result: {:?}", other),
+        // The azp claim must identify this client.
+        match new_id_token_with_audiences(
+            vec!["prod_client", "resource_server"],
+            Some("resource_server"),
+        )
+        .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+        {
+            Err(ClaimsVerificationError::InvalidAudience(msg)) => {
+                assert!(msg.contains("authorized party must be"), "{}", msg)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The azp claim isn't verified by default.
+        new_id_token_with_audiences(vec!["prod_client", "resource_server"], None)
+            .claims(
+                &verifier.require_authorized_party_match(false),
+                |_: Option<&Nonce>| Ok(()),
+            )
+            .expect("azp should not be required by default");
+    }
+
+    #[test]
+    fn test_id_token_other_audience_verifier_fn() {
+        let verifier = new_audience_test_verifier("my_client")
+            .set_other_audience_verifier_fn(|aud| aud.ends_with(".example.com"));
+
+        // The closure accepts the secondary audience.
+        new_id_token_with_audiences(vec!["my_client", "api.example.com"], None)
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("trusted secondary audience should be accepted");
+
+        // The closure rejects the secondary audience.
+        match new_id_token_with_audiences(vec!["my_client", "api.example.net"], None)
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+        {
+            Err(ClaimsVerificationError::InvalidAudience(msg)) => {
+                assert!(msg.contains("api.example.net"), "{}", msg)
             }
+            other => panic!("unexpected result: {:?}", other),
+        }
 
-            // Successful verification with nonce, acr, and auth_time specified (no expected Nonce)
-            public_client_verifier
-                .verified_claims(&test_jwt_with_nonce, |_: Option<&Nonce>| Ok(()))
-                .expect("verification should succeed");
-            insecure_verifier
-                .verified_claims(&test_jwt_with_nonce, |_: Option<&Nonce>| Ok(()))
-                .expect("verification should succeed");
+        // The client ID must still be an audience, even if the closure accepts all others.
+        match new_id_token_with_audiences(vec!["api.example.com"], None)
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+        {
+            Err(ClaimsVerificationError::InvalidAudience(msg)) => {
+                assert!(msg.contains("my_client"), "{}", msg)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        new_id_token_with_audiences(vec!["api.example.com"], None)
+            .claims(
+                &verifier.require_audience_match(false),
+                |_: Option<&Nonce>| Ok(()),
+            )
+            .expect("audience check should be relaxed");
+    }
 
-            // Successful verification with nonce, acr, and auth_time specified (w/ expected Nonce)
-            public_client_verifier
-                .verified_claims(&test_jwt_with_nonce, &valid_nonce)
-                .expect("verification should succeed");
-            insecure_verifier
-                .verified_claims(&test_jwt_with_nonce, &valid_nonce)
-                .expect("verification should succeed");
+    #[test]
+    fn test_id_token_issuer_match() {
+        let new_id_token = |issuer: &str| -> CoreIdToken {
+            sign_id_token(new_id_token_claims(
+                issuer,
+                &["my_client"],
+                Default::default(),
+            ))
+        };
+        let new_verifier = |issuer: &str| {
+            CoreIdTokenVerifier::new_public_client(
+                ClientId::new("my_client".to_string()),
+                IssuerUrl::new(issuer.to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                    TEST_RSA_PUB_KEY,
+                )
+                .unwrap()]),
+            )
+            .set_time_fn(|| Utc.timestamp_opt(1544928549, 0).single().unwrap())
+        };
 
-            // Successful verification with nonce, acr, and auth_time specified (w/ closure)
-            public_client_verifier
-                .verified_claims(&test_jwt_with_nonce, |nonce: Option<&Nonce>| {
-                    if nonce.iter().any(|n| n.secret() == valid_nonce.secret()) {
-                        Ok(())
-                    } else {
-                        Err("invalid nonce".to_string())
-                    }
-                })
-                .expect("verification should succeed");
-            insecure_verifier
-                .verified_claims(&test_jwt_with_nonce, |nonce: Option<&Nonce>| {
-                    if nonce.iter().any(|n| n.secret() == valid_nonce.secret()) {
-                        Ok(())
-                    } else {
-                        Err("invalid nonce".to_string())
-                    }
-                })
-                .expect("verification should succeed");
+        for (expected_issuer, token_issuer) in [
+            ("https://issuer.example", "https://issuer.example/"),
+            ("https://issuer.example/", "https://issuer.example"),
+        ] {
+            let id_token = new_id_token(token_issuer);
 
-            // HS256 w/ default algs
-            let test_jwt_hs256 =
-                serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
-                    "eyJhbGciOiJIUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                     S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDksIm5\
-                     vbmNlIjoidGhlX25vbmNlIn0.xUnSwSbcHsHWyJxwKGg69BIo_CktcyN5BVulGDb_QzE"
-                        .to_string(),
-                ))
-                .expect("failed to deserialize");
-            let private_client_verifier = CoreIdTokenVerifier::new_confidential_client(
-                client_id.clone(),
-                ClientSecret::new("my_secret".to_string()),
-                issuer.clone(),
-                CoreJsonWebKeySet::new(vec![rsa_key.clone()]),
-            )
-            .set_time_fn(|| {
-                timestamp_to_utc(&Timestamp::Seconds(
-                    mock_current_time.load(Ordering::Relaxed).into(),
-                ))
-                .unwrap()
-            });
-            match private_client_verifier.verified_claims(&test_jwt_hs256, &valid_nonce) {
-                Err(ClaimsVerificationError::SignatureVerification(_)) => {}
+            // Strict matching is the default.
+            match id_token.claims(&new_verifier(expected_issuer), |_: Option<&Nonce>| Ok(())) {
+                Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
                 other => panic!("unexpected result: {:?}", other),
             }
-            insecure_verifier
-                .clone()
-                .verified_claims(&test_jwt_hs256, &valid_nonce)
-                .expect("verification should succeed");
 
-            // HS256 w/ set_allowed_algs
-            private_client_verifier
-                .clone()
-                .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
-                .verified_claims(&test_jwt_hs256, &valid_nonce)
-                .expect("verification should succeed");
+            id_token
+                .claims(
+                    &new_verifier(expected_issuer)
+                        .set_issuer_match(IssuerMatch::IgnoreTrailingSlash),
+                    |_: Option<&Nonce>| Ok(()),
+                )
+                .expect("trailing slash difference should be tolerated");
+        }
 
-            // HS256 w/ allow_any_alg
-            private_client_verifier
-                .clone()
-                .allow_any_alg()
-                .verified_claims(&test_jwt_hs256, &valid_nonce)
-                .expect("verification should succeed");
+        // Other differences are still rejected.
+        match new_id_token("https://issuer.example/tenant").claims(
+            &new_verifier("https://issuer.example")
+                .set_issuer_match(IssuerMatch::IgnoreTrailingSlash),
+            |_: Option<&Nonce>| Ok(()),
+        ) {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 
-            // Invalid signature
-            let private_client_verifier_with_other_secret =
-                CoreIdTokenVerifier::new_confidential_client(
-                    client_id,
-                    ClientSecret::new("other_secret".to_string()),
-                    issuer,
-                    CoreJsonWebKeySet::new(vec![rsa_key]),
-                )
-                .allow_any_alg()
-                .set_time_fn(|| {
-                    timestamp_to_utc(&Timestamp::Seconds(
-                        mock_current_time.load(Ordering::Relaxed).into(),
-                    ))
-                    .unwrap()
-                });
-            match private_client_verifier_with_other_secret
-                .verified_claims(&test_jwt_hs256, &valid_nonce)
-            {
-                Err(ClaimsVerificationError::SignatureVerification(_)) => {}
-                other => panic!("unexpected result: {:?}", other),
+    #[test]
+    fn test_multi_issuer_verifier() {
+        let new_id_token = |issuer: &str, client_id: &str| -> CoreIdToken {
+            sign_id_token(new_id_token_claims(
+                issuer,
+                &[client_id],
+                Default::default(),
+            ))
+        };
+        let new_verifier = |issuer: &str, client_id: &str, key: &str| {
+            CoreIdTokenVerifier::new_public_client(
+                ClientId::new(client_id.to_string()),
+                IssuerUrl::new(issuer.to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(key).unwrap()]),
+            )
+            .set_time_fn(|| Utc.timestamp_opt(1544928549, 0).single().unwrap())
+        };
+        let tenant_a = "https://tenant-a.example.com";
+        let tenant_b = "https://tenant-b.example.com";
+        let verifier = CoreMultiIssuerVerifier::new()
+            .add_issuer(
+                IssuerUrl::new(tenant_a.to_string()).unwrap(),
+                new_verifier(tenant_a, "client_a", TEST_RSA_PUB_KEY),
+            )
+            // Tenant B signs its ID tokens with a different key.
+            .add_issuer(
+                IssuerUrl::new(tenant_b.to_string()).unwrap(),
+                new_verifier(tenant_b, "client_b", TEST_EC_PUB_KEY_P256)
+                    .set_allowed_algs(vec![CoreJwsSigningAlgorithm::EcdsaP256Sha256]),
+            );
+
+        // Routed to tenant A's verifier, which checks tenant A's client ID.
+        let id_token = new_id_token(tenant_a, "client_a");
+        let claims = verifier
+            .verified_claims(&id_token, |_: Option<&Nonce>| Ok(()))
+            .expect("tenant A's ID token should be accepted");
+        assert_eq!(claims.issuer().as_str(), tenant_a);
+        match verifier.verified_claims(&new_id_token(tenant_a, "client_b"), |_: Option<&Nonce>| {
+            Ok(())
+        }) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Unknown issuers are rejected.
+        match verifier.verified_claims(
+            &new_id_token("https://tenant-c.example.com", "client_a"),
+            |_: Option<&Nonce>| Ok(()),
+        ) {
+            Err(ClaimsVerificationError::InvalidIssuer(msg)) => {
+                assert!(msg.contains("tenant-c"), "{}", msg)
             }
+            other => panic!("unexpected result: {:?}", other),
+        }
 
-            // Invalid signature w/ signature check disabled
-            private_client_verifier_with_other_secret
-                .clone()
-                .insecure_disable_signature_check()
-                .verified_claims(&test_jwt_hs256, &valid_nonce)
-                .expect("verification should succeed");
-        };
+        // Tenant A's keys can't be used to sign ID tokens claiming to be from tenant B.
+        match verifier.verified_claims(&new_id_token(tenant_b, "client_b"), |_: Option<&Nonce>| {
+            Ok(())
+        }) {
+            Err(ClaimsVerificationError::SignatureVerification(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
     }
 
     #[test]
-    fn test_new_id_token() {
-        let client_id = ClientId::new("my_client".to_string());
-        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
-        let nonce = Nonce::new("the_nonce".to_string());
-        let rsa_priv_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
-
-        let id_token = CoreIdToken::new(
-            CoreIdTokenClaims::new(
-                issuer.clone(),
-                vec![Audience::new((*client_id).clone())],
-                Utc.timestamp_opt(1544932149, 0)
-                    .single()
-                    .expect("valid timestamp"),
-                Utc.timestamp_opt(1544928549, 0)
-                    .single()
-                    .expect("valid timestamp"),
-                StandardClaims::new(SubjectIdentifier::new("subject".to_string())),
-                Default::default(),
+    fn test_id_token_acr_amr_policy() {
+        let new_id_token = |acr: Option<&str>, amr: Option<Vec<&str>>| -> CoreIdToken {
+            sign_id_token(
+                new_id_token_claims("https://example.com", &["my_client"], Default::default())
+                    .set_auth_context_ref(
+                        acr.map(|acr| AuthenticationContextClass::new(acr.to_string())),
+                    )
+                    .set_auth_method_refs(amr.map(|amr| {
+                        amr.into_iter()
+                            .map(|method| AuthenticationMethodReference::new(method.to_string()))
+                            .collect()
+                    })),
             )
-            .set_nonce(Some(nonce.clone()))
-            .set_auth_context_ref(Some(AuthenticationContextClass::new("the_acr".to_string())))
-            .set_auth_time(Some(
-                Utc.timestamp_opt(1544928548, 0)
-                    .single()
-                    .expect("valid timestamp"),
-            )),
-            &rsa_priv_key,
-            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
-            Some(&AccessToken::new("the_access_token".to_string())),
-            Some(&AuthorizationCode::new(
-                "the_authorization_code".to_string(),
-            )),
-        )
-        .unwrap();
+        };
+        let verifier = CoreIdTokenVerifier::new_insecure_without_verification()
+            .set_time_fn(|| Utc.timestamp_opt(1544928549, 0).single().unwrap());
 
-        let serialized_jwt: serde_json::Value = serde_json::to_value(&id_token).unwrap();
-        let expected_serialized_jwt =
-            "eyJhbGciOiJSUzI1NiJ9.eyJpc3MiOiJodHRwczovL2V4YW1wbGUuY29tIiwiYXVkIjpbIm15X2NsaWVudCJdL\
-             CJleHAiOjE1NDQ5MzIxNDksImlhdCI6MTU0NDkyODU0OSwiYXV0aF90aW1lIjoxNTQ0OTI4NTQ4LCJub25jZSI\
-             6InRoZV9ub25jZSIsImFjciI6InRoZV9hY3IiLCJhdF9oYXNoIjoiWjNJQUNVR00tbXhIV3lZUXZpSzhFUSIsI\
-             mNfaGFzaCI6Imo2OW1CZmFIbmRMM1Y1RmNoak9LVXciLCJzdWIiOiJzdWJqZWN0In0.CHCWFcIqbCZhZwZH4oY\
-             _mlcRy5aUQQtlNI0VHNYxiILn9ppRHLL4Bn_LMn9VP8tGXkfZWxCgP25ZTyBXXKfk0fQvnukVdyM0bCOpQbiBg\
-             5gB9c46l_f-ZznDoHWonpnKky2Gmzk3ocb3TCUQ9GSeRXAzRdRNWTT0ElWNBsLWU4j2IIdnghM78gkXwOC76Rk\
-             pshgB73ubtuHGdIf5L9Ec3hifHlVjzKuvedAM4SIOjdBOelgtBlF3463ufX_Ut91CjP5TzLMsuK3Lh_vyo8ttn\
-             S41rBDuetR2ENvR0yj5RjkX_SPY3V0yCW8_NPPu1CHu_1oL0Nma0ohCbF3vnUJcwg";
-        assert_eq!(expected_serialized_jwt, serialized_jwt.as_str().unwrap());
+        let acr_verifier = verifier.clone().require_acr(vec![
+            AuthenticationContextClass::new("urn:mace:incommon:iap:silver".to_string()),
+            AuthenticationContextClass::new("urn:mace:incommon:iap:gold".to_string()),
+        ]);
+        let id_token = new_id_token(Some("urn:mace:incommon:iap:gold"), None);
+        let claims = id_token
+            .claims(&acr_verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("allowed acr should be accepted");
+        assert_eq!(
+            claims.acr(),
+            Some(&AuthenticationContextClass::new(
+                "urn:mace:incommon:iap:gold".to_string()
+            ))
+        );
 
-        let rsa_pub_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
-            .expect("deserialization failed");
+        for id_token in [
+            new_id_token(Some("urn:mace:incommon:iap:bronze"), None),
+            new_id_token(None, None),
+        ] {
+            match id_token.claims(&acr_verifier, |_: Option<&Nonce>| Ok(())) {
+                Err(ClaimsVerificationError::InvalidAuthContext(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
 
-        let mock_current_time = AtomicUsize::new(1544932148);
+        let mfa = AuthenticationMethodReference::new("mfa".to_string());
+        let amr_verifier = verifier.set_auth_methods_verifier_fn(|amr| {
+            if amr.map(|amr| amr.contains(&mfa)).unwrap_or(false) {
+                Ok(())
+            } else {
+                Err("mfa is required".to_string())
+            }
+        });
+        let id_token = new_id_token(None, Some(vec!["pwd", "mfa", "otp"]));
+        let claims = id_token
+            .claims(&amr_verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("amr containing mfa should be accepted");
+        assert_eq!(claims.amr().map(<[_]>::len), Some(3));
+        for id_token in [
+            new_id_token(None, Some(vec!["pwd"])),
+            new_id_token(None, None),
+        ] {
+            match id_token.claims(&amr_verifier, |_: Option<&Nonce>| Ok(())) {
+                Err(ClaimsVerificationError::InvalidAuthMethods(msg)) => {
+                    assert_eq!(msg, "mfa is required")
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_id_token_time_leeway() {
+        let mock_current_time = AtomicUsize::new(1544932149 + 30);
         let time_fn = || {
             timestamp_to_utc(&Timestamp::Seconds(
                 mock_current_time.load(Ordering::Relaxed).into(),
             ))
             .unwrap()
         };
-        let verifier = CoreIdTokenVerifier::new_public_client(
-            client_id,
-            issuer,
-            CoreJsonWebKeySet::new(vec![rsa_pub_key]),
-        )
-        .set_time_fn(time_fn);
-        let claims = id_token.claims(&verifier, &nonce).unwrap();
-        let unverified = id_token
-            .claims(
-                &CoreIdTokenVerifier::new_insecure_without_verification().set_time_fn(time_fn),
-                &nonce,
-            )
-            .unwrap();
-        assert_eq!(claims, unverified);
+
+        // This JWT has an expiration time of 1544932149.
+        let test_jwt = serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
+            "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+             S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDl9.nN\
+             aTxNwclnTHd1Q9POkddm5wB1w3wJ-gwQWHomhimttk3SWQTLhxI0SSjWrHahGxlfkjufJlSyt-t_VO\
+             SdcROvIYZTDznDfFZz3oSOev-p9XiZ-EZTS-U6N11Y923sDQjbTMeukz1F3ZFEfn5Mv2xjdEoJccCe\
+             7SaGuDmVqMqTLXMtsw9NCE_KDd0oKSwDzbJIBBPEfG3JjbKg0Dln7ENHg9wzoNFQzPXrkKzjneBgD3\
+             vuwFCV5y-e8xUBdLaLZF1kdkDZJIA48uRROLlWjsM8pEptosA5QK07luQCZNqcaZWEczoGXeQs8PyA\
+             zkNV7JEmti3bJnWSN-ud4cFU0LiQ"
+                .to_string(),
+        ))
+        .expect("failed to deserialize");
+
+        // Expired by 30 seconds, which is within the default leeway.
+        let default_verifier =
+            CoreIdTokenVerifier::new_insecure_without_verification().set_time_fn(time_fn);
+        default_verifier
+            .verified_claims(&test_jwt, |_: Option<&Nonce>| Ok(()))
+            .expect("verification should succeed within the default leeway");
+
+        let strict_verifier = default_verifier
+            .clone()
+            .set_time_leeway(Duration::from_secs(0));
+        match strict_verifier.verified_claims(&test_jwt, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let lenient_verifier = strict_verifier.set_time_leeway(Duration::from_secs(60));
+        lenient_verifier
+            .verified_claims(&test_jwt, |_: Option<&Nonce>| Ok(()))
+            .expect("verification should succeed within the leeway");
+
+        // Expired by more than the leeway.
+        mock_current_time.store(1544932149 + 60, Ordering::Relaxed);
+        match lenient_verifier.verified_claims(&test_jwt, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Issued 30 seconds in the future (iat is 1544928549), which is within the leeway.
+        mock_current_time.store(1544928549 - 30, Ordering::Relaxed);
+        default_verifier
+            .verified_claims(&test_jwt, |_: Option<&Nonce>| Ok(()))
+            .expect("verification should succeed within the default leeway");
+        match default_verifier
+            .clone()
+            .set_time_leeway(Duration::from_secs(0))
+            .verified_claims(&test_jwt, |_: Option<&Nonce>| Ok(()))
+        {
+            Err(ClaimsVerificationError::Expired(message)) => assert_eq!(
+                message,
+                "ID token issued in the future at 2018-12-16 02:49:09 UTC (current time is \
+                 2018-12-16 02:48:39 UTC)"
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Issued further in the future than the leeway.
+        mock_current_time.store(1544928549 - 61, Ordering::Relaxed);
+        match default_verifier.verified_claims(&test_jwt, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // Excessively large leeways don't overflow.
+        mock_current_time.store(1544932149 + 30, Ordering::Relaxed);
+        CoreIdTokenVerifier::new_insecure_without_verification()
+            .set_time_fn(time_fn)
+            .set_time_leeway(Duration::from_secs(u64::MAX))
+            .verified_claims(&test_jwt, |_: Option<&Nonce>| Ok(()))
+            .expect("verification should succeed within the leeway");
     }
 
     #[test]
@@ -2080,6 +5072,7 @@ mod tests {
                         .single()
                         .expect("valid timestamp"),
                 ),
+                updated_at_seconds: Some(Seconds::new(1544928548.into())),
             },
             Default::default(),
         );
@@ -2106,4 +5099,53 @@ mod tests {
             serde_json::to_value(claims_jwt).unwrap().as_str().unwrap()
         );
     }
+
+    #[test]
+    fn test_signed_nonce() {
+        let secret = b"nonce signing secret";
+        let nonce = Nonce::new_signed(secret, Duration::from_secs(300));
+        let verifier = SignedNonceVerifier::new(secret);
+
+        // Valid self-verifying nonce
+        (&verifier).verify(Some(&nonce)).unwrap();
+        assert_eq!(
+            (&verifier).verify(None),
+            Err("missing nonce claim".to_string())
+        );
+
+        // Expired nonce
+        let expired_verifier = SignedNonceVerifier::new(secret)
+            .set_time_fn(|| Utc::now() + chrono::Duration::seconds(301));
+        assert!((&expired_verifier)
+            .verify(Some(&nonce))
+            .unwrap_err()
+            .starts_with("nonce expired at "));
+
+        // Very long TTLs saturate rather than overflowing.
+        for ttl in [
+            Duration::from_secs(i64::MAX as u64),
+            Duration::from_secs(u64::MAX),
+        ] {
+            (&verifier)
+                .verify(Some(&Nonce::new_signed(secret, ttl)))
+                .unwrap();
+        }
+
+        // Tampered nonce
+        let (random, rest) = nonce.secret().split_once('.').unwrap();
+        let (_, mac) = rest.split_once('.').unwrap();
+        let extended = Nonce::new(format!("{}.{}.{}", random, i64::MAX, mac));
+        assert_eq!(
+            (&verifier).verify(Some(&extended)),
+            Err("invalid nonce signature".to_string())
+        );
+        assert_eq!(
+            SignedNonceVerifier::new(b"other secret").verify(Some(&nonce)),
+            Err("invalid nonce signature".to_string())
+        );
+        assert_eq!(
+            (&verifier).verify(Some(&Nonce::new("plain_nonce".to_string()))),
+            Err("malformed signed nonce".to_string())
+        );
+    }
 }
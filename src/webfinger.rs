@@ -0,0 +1,379 @@
+use std::future::Future;
+
+use http::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
+use http::method::Method;
+use http::status::StatusCode;
+use serde::Deserialize;
+use url::Url;
+
+use crate::http_utils::{content_type_has_essence, MIME_TYPE_JSON};
+use crate::{
+    AsyncHttpClient, DiscoveryError, HttpRequest, HttpResponse, IssuerUrl, SyncHttpClient,
+};
+
+const MIME_TYPE_JRD: &str = "application/jrd+json";
+pub(crate) const WEBFINGER_URL_PATH: &str = "/.well-known/webfinger";
+pub(crate) const ISSUER_REL: &str = "http://openid.net/specs/connect/1.0/issuer";
+
+#[derive(Deserialize)]
+struct JsonResourceDescriptor {
+    #[serde(default)]
+    links: Vec<JsonResourceDescriptorLink>,
+}
+
+#[derive(Deserialize)]
+struct JsonResourceDescriptorLink {
+    rel: String,
+    href: Option<String>,
+}
+
+///
+/// Issuer discovery using [WebFinger](https://tools.ietf.org/html/rfc7033), as described in
+/// [Section 2 of OpenID Connect Discovery 1.0](
+/// https://openid.net/specs/openid-connect-discovery-1_0.html#IssuerDiscovery).
+///
+/// This determines the issuer URL of the OpenID Connect Provider for a user-supplied identifier
+/// such as an email address (e.g., `joe@example.com`) or URL (e.g., `https://example.com/joe`).
+/// The resulting issuer URL may then be passed to [`ProviderMetadata::discover`](
+/// crate::ProviderMetadata::discover).
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IssuerDiscovery {
+    resource: String,
+    host: String,
+}
+impl IssuerDiscovery {
+    ///
+    /// Link relation type identifying the issuer in a WebFinger response.
+    ///
+    pub const ISSUER_REL: &'static str = ISSUER_REL;
+
+    ///
+    /// Initializes issuer discovery for the given user input identifier, which is normalized
+    /// according to [Section 2.1 of OpenID Connect Discovery 1.0](
+    /// https://openid.net/specs/openid-connect-discovery-1_0.html#NormalizationSteps).
+    ///
+    /// Identifiers with an `acct:` scheme, or that consist only of a user and host (e.g.,
+    /// `joe@example.com`), are treated as `acct:` URIs. All other identifiers are treated as
+    /// URLs, with the `https` scheme assumed if none is specified and any fragment removed.
+    ///
+    pub fn new(identifier: &str) -> Result<Self, url::ParseError> {
+        let identifier = identifier.trim();
+
+        let acct = identifier.strip_prefix("acct:").or_else(|| {
+            // Without a scheme, an identifier containing only a user and a host (i.e., no path,
+            // query, port, or fragment) is assumed to use the acct scheme.
+            identifier
+                .rsplit_once('@')
+                .filter(|(_, host)| {
+                    !identifier.contains("://") && !host.contains([':', '/', '?', '#'])
+                })
+                .map(|_| identifier)
+        });
+        if let Some(acct) = acct {
+            let host = acct
+                .rsplit_once('@')
+                .map(|(_, host)| host)
+                .filter(|host| !host.is_empty())
+                .ok_or(url::ParseError::EmptyHost)?;
+            return Ok(Self {
+                resource: format!("acct:{}", acct),
+                host: host.to_string(),
+            });
+        }
+
+        let mut url = if identifier.contains("://") {
+            Url::parse(identifier)?
+        } else {
+            Url::parse(&format!("https://{}", identifier))?
+        };
+        url.set_fragment(None);
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(url::ParseError::EmptyHost),
+        };
+        Ok(Self {
+            resource: url.to_string(),
+            host,
+        })
+    }
+
+    ///
+    /// Returns the normalized resource identifier sent to the WebFinger endpoint.
+    ///
+    pub fn resource(&self) -> &str {
+        &self.resource
+    }
+
+    ///
+    /// Returns the host (and port, if any) whose WebFinger endpoint is queried.
+    ///
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    ///
+    /// Returns the URL of the WebFinger request used for discovering the issuer.
+    ///
+    pub fn webfinger_url(&self) -> Result<Url, url::ParseError> {
+        let mut url = Url::parse(&format!("https://{}{}", self.host, WEBFINGER_URL_PATH))?;
+        url.query_pairs_mut()
+            .append_pair("resource", &self.resource)
+            .append_pair("rel", Self::ISSUER_REL);
+        Ok(url)
+    }
+
+    ///
+    /// Queries the WebFinger endpoint and returns the discovered issuer URL.
+    ///
+    pub fn discover<HC, RE>(&self, http_client: HC) -> Result<IssuerUrl, DiscoveryError<RE>>
+    where
+        HC: SyncHttpClient<Error = RE>,
+        RE: std::error::Error + 'static,
+    {
+        let webfinger_url = self.webfinger_url().map_err(DiscoveryError::UrlParse)?;
+        http_client
+            .call(Self::webfinger_request(webfinger_url.clone()))
+            .map_err(DiscoveryError::Request)
+            .and_then(|http_response| self.webfinger_response(&webfinger_url, http_response))
+    }
+
+    ///
+    /// Asynchronously queries the WebFinger endpoint and returns the discovered issuer URL.
+    ///
+    pub async fn discover_async<F, HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> Result<IssuerUrl, DiscoveryError<RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: AsyncHttpClient<Error = RE, Future = F>,
+        RE: std::error::Error + 'static,
+    {
+        let webfinger_url = self.webfinger_url().map_err(DiscoveryError::UrlParse)?;
+        http_client
+            .call(Self::webfinger_request(webfinger_url.clone()))
+            .await
+            .map_err(DiscoveryError::Request)
+            .and_then(|http_response| self.webfinger_response(&webfinger_url, http_response))
+    }
+
+    fn webfinger_request(webfinger_url: Url) -> HttpRequest {
+        HttpRequest {
+            url: webfinger_url,
+            method: Method::GET,
+            headers: vec![(ACCEPT, HeaderValue::from_static(MIME_TYPE_JRD))]
+                .into_iter()
+                .collect(),
+            body: Vec::new(),
+        }
+    }
+
+    fn webfinger_response<RE>(
+        &self,
+        webfinger_url: &Url,
+        http_response: HttpResponse,
+    ) -> Result<IssuerUrl, DiscoveryError<RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        if http_response.status_code != StatusCode::OK {
+            return Err(DiscoveryError::Response(
+                http_response.status_code,
+                http_response.body,
+                format!(
+                    "HTTP status code {} at {}",
+                    http_response.status_code, webfinger_url
+                ),
+            ));
+        }
+
+        // Section 10.2 of RFC 7033 registers application/jrd+json, but some servers respond with
+        // application/json instead.
+        if let Some(content_type) = http_response.headers.get(CONTENT_TYPE) {
+            if !content_type_has_essence(content_type, MIME_TYPE_JRD)
+                && !content_type_has_essence(content_type, MIME_TYPE_JSON)
+            {
+                return Err(DiscoveryError::Response(
+                    http_response.status_code,
+                    http_response.body.clone(),
+                    format!(
+                        "Unexpected response Content-Type: {:?}, should be `{}`",
+                        content_type, MIME_TYPE_JRD
+                    ),
+                ));
+            }
+        }
+
+        let jrd = serde_path_to_error::deserialize::<_, JsonResourceDescriptor>(
+            &mut serde_json::Deserializer::from_slice(&http_response.body),
+        )
+        .map_err(DiscoveryError::Parse)?;
+
+        jrd.links
+            .into_iter()
+            .find(|link| link.rel == Self::ISSUER_REL && link.href.is_some())
+            .and_then(|link| link.href)
+            .ok_or_else(|| DiscoveryError::IssuerNotFound(self.resource.clone()))
+            .and_then(|href| IssuerUrl::new(href).map_err(DiscoveryError::UrlParse))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+    use http::method::Method;
+    use http::status::StatusCode;
+
+    use super::IssuerDiscovery;
+    use crate::{DiscoveryError, HttpRequest, HttpResponse};
+
+    fn jrd_response(body: &str) -> Result<HttpResponse, std::io::Error> {
+        Ok(HttpResponse {
+            status_code: StatusCode::OK,
+            headers: vec![(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/jrd+json"),
+            )]
+            .into_iter()
+            .collect(),
+            body: body.as_bytes().to_vec(),
+        })
+    }
+
+    #[test]
+    fn test_normalization() {
+        // Examples from Section 2.1 and Appendix A of OpenID Connect Discovery 1.0.
+        for (identifier, expected_resource, expected_host) in [
+            ("joe@example.com", "acct:joe@example.com", "example.com"),
+            ("  joe@example.com ", "acct:joe@example.com", "example.com"),
+            (
+                "acct:joe@example.com",
+                "acct:joe@example.com",
+                "example.com",
+            ),
+            (
+                "acct:juliet%40capulet.example@shopping.example.com",
+                "acct:juliet%40capulet.example@shopping.example.com",
+                "shopping.example.com",
+            ),
+            (
+                "https://example.com/joe",
+                "https://example.com/joe",
+                "example.com",
+            ),
+            ("example.com/joe", "https://example.com/joe", "example.com"),
+            ("example.com", "https://example.com/", "example.com"),
+            (
+                "example.com:8080",
+                "https://example.com:8080/",
+                "example.com:8080",
+            ),
+            (
+                "joe@example.com:8080",
+                "https://joe@example.com:8080/",
+                "example.com:8080",
+            ),
+            (
+                "https://example.com/joe#fragment",
+                "https://example.com/joe",
+                "example.com",
+            ),
+            (
+                "https://example.com/joe?query=1",
+                "https://example.com/joe?query=1",
+                "example.com",
+            ),
+        ] {
+            let discovery = IssuerDiscovery::new(identifier).unwrap();
+            assert_eq!(discovery.resource(), expected_resource, "{}", identifier);
+            assert_eq!(discovery.host(), expected_host, "{}", identifier);
+        }
+
+        assert_eq!(
+            IssuerDiscovery::new("acct:joe@"),
+            Err(url::ParseError::EmptyHost)
+        );
+        assert_eq!(
+            IssuerDiscovery::new("acct:joe"),
+            Err(url::ParseError::EmptyHost)
+        );
+    }
+
+    #[test]
+    fn test_discover() {
+        let discovery = IssuerDiscovery::new("joe@example.com").unwrap();
+        let issuer = discovery
+            .discover(|request: HttpRequest| {
+                assert_eq!(request.method, Method::GET);
+                assert_eq!(
+                    request.url.as_str(),
+                    "https://example.com/.well-known/webfinger?resource=acct%3Ajoe%40example.com\
+                     &rel=http%3A%2F%2Fopenid.net%2Fspecs%2Fconnect%2F1.0%2Fissuer"
+                );
+                jrd_response(
+                    "{
+                        \"subject\": \"acct:joe@example.com\",
+                        \"links\": [
+                            {
+                                \"rel\": \"http://webfinger.net/rel/avatar\",
+                                \"href\": \"https://example.com/joe.png\"
+                            },
+                            {
+                                \"rel\": \"http://openid.net/specs/connect/1.0/issuer\",
+                                \"href\": \"https://server.example.com\"
+                            }
+                        ]
+                    }",
+                )
+            })
+            .unwrap();
+        assert_eq!(issuer.as_str(), "https://server.example.com");
+    }
+
+    #[test]
+    fn test_discover_errors() {
+        let discovery = IssuerDiscovery::new("https://example.com/joe").unwrap();
+
+        match discovery.discover(|_| {
+            jrd_response(
+                "{
+                    \"subject\": \"https://example.com/joe\",
+                    \"links\": [
+                        {
+                            \"rel\": \"http://webfinger.net/rel/avatar\",
+                            \"href\": \"https://example.com/joe.png\"
+                        }
+                    ]
+                }",
+            )
+        }) {
+            Err(DiscoveryError::IssuerNotFound(resource)) => {
+                assert_eq!(resource, "https://example.com/joe")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(matches!(
+            discovery.discover(|_| jrd_response("{\"subject\": \"https://example.com/joe\"}")),
+            Err(DiscoveryError::IssuerNotFound(_))
+        ));
+
+        assert!(matches!(
+            discovery.discover(|_| jrd_response("{\"links\": {}}")),
+            Err(DiscoveryError::Parse(_))
+        ));
+
+        assert!(matches!(
+            discovery.discover(|_| {
+                Ok::<_, std::io::Error>(HttpResponse {
+                    status_code: StatusCode::NOT_FOUND,
+                    headers: HeaderMap::new(),
+                    body: vec![],
+                })
+            }),
+            Err(DiscoveryError::Response(StatusCode::NOT_FOUND, _, _))
+        ));
+    }
+}
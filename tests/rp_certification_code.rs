@@ -182,7 +182,7 @@ impl TestState {
         self.id_token.as_ref().expect("no id_token")
     }
 
-    pub fn id_token_verifier(&self, jwks: CoreJsonWebKeySet) -> CoreIdTokenVerifier {
+    pub fn id_token_verifier(&self, jwks: CoreJsonWebKeySet) -> CoreIdTokenVerifier<'_> {
         CoreIdTokenVerifier::new_confidential_client(
             self.registration_response.client_id().clone(),
             self.registration_response
@@ -266,7 +266,7 @@ fn rp_response_type_code() {
 #[test]
 #[ignore]
 fn rp_scope_userinfo_claims() {
-    let user_info_scopes = vec!["profile", "email", "address", "phone"]
+    let user_info_scopes = ["profile", "email", "address", "phone"]
         .iter()
         .map(|scope| Scope::new((*scope).to_string()))
         .collect::<Vec<_>>();